@@ -0,0 +1,69 @@
+use std::collections::BTreeMap;
+
+/// Expand a REPL alias from the config's `[aliases]` table into the message it stands for.
+///
+/// Templates can use `{input}` for the text typed after the alias and `{topic}` for the
+/// session's topic. Text typed after an alias whose template has no `{input}` is added on a
+/// new line. Returns None when `input` isn't a configured alias.
+pub fn expand(aliases: &BTreeMap<String, String>, input: &str, topic: &str) -> Option<String> {
+    let command = input.strip_prefix('/')?;
+    let (name, rest) = command.split_once(char::is_whitespace).unwrap_or((command, ""));
+    let rest = rest.trim();
+
+    let template = aliases
+        .iter()
+        .find(|(alias, _)| alias.trim_start_matches('/').eq_ignore_ascii_case(name))
+        .map(|(_, template)| template)?;
+
+    let mut message = template.replace("{topic}", topic);
+    if message.contains("{input}") {
+        message = message.replace("{input}", rest);
+    } else if !rest.is_empty() {
+        message = format!("{}\n\n{}", message, rest);
+    }
+    Some(message.trim().to_string())
+}
+
+/// Configured alias names as typed in the REPL, e.g. "/why"
+pub fn names(aliases: &BTreeMap<String, String>) -> Vec<String> {
+    aliases
+        .keys()
+        .map(|alias| format!("/{}", alias.trim_start_matches('/')))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn aliases() -> BTreeMap<String, String> {
+        BTreeMap::from([
+            ("why".to_string(), "Explain why this design decision was made: {input}".to_string()),
+            ("/tldr".to_string(), "Summarize your last answer about {topic} in three bullets.".to_string()),
+        ])
+    }
+
+    #[test]
+    fn test_expand() {
+        let aliases = aliases();
+        assert_eq!(
+            expand(&aliases, "/why  tokio uses work stealing", "async"),
+            Some("Explain why this design decision was made: tokio uses work stealing".to_string())
+        );
+        assert_eq!(
+            expand(&aliases, "/TLDR", "Rust lifetimes"),
+            Some("Summarize your last answer about Rust lifetimes in three bullets.".to_string())
+        );
+        assert_eq!(
+            expand(&aliases, "/tldr skip the examples", "x"),
+            Some("Summarize your last answer about x in three bullets.\n\nskip the examples".to_string())
+        );
+        assert_eq!(expand(&aliases, "/whynot", "x"), None);
+        assert_eq!(expand(&aliases, "why", "x"), None);
+    }
+
+    #[test]
+    fn test_names() {
+        assert_eq!(names(&aliases()), vec!["/tldr", "/why"]);
+    }
+}
@@ -0,0 +1,448 @@
+use crate::config::Config;
+use crate::{crypto, markdown, notes, til};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local, NaiveDate};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// What kind of saved artifact an entry is
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EntryKind {
+    Til,
+    Note,
+}
+
+impl std::fmt::Display for EntryKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EntryKind::Til => write!(f, "TIL"),
+            EntryKind::Note => write!(f, "note"),
+        }
+    }
+}
+
+/// A saved TIL or note on disk
+#[derive(Debug, Clone)]
+pub struct Entry {
+    pub kind: EntryKind,
+    pub title: String,
+    pub path: PathBuf,
+    pub category: Option<String>,
+    pub reading_minutes: u32,
+    pub level: Option<String>,
+}
+
+/// Collect all TILs in the archive and all notes in the notes directory
+pub fn all_entries(config: &Config) -> Result<Vec<Entry>> {
+    let mut entries = til_entries(&config.archive_path())?;
+    if let Some(ref notes_path) = config.notes_path {
+        entries.extend(note_entries(notes_path)?);
+    }
+    Ok(entries)
+}
+
+/// Collect all TIL entries below the archive directory
+pub fn til_entries(archive_path: &Path) -> Result<Vec<Entry>> {
+    let mut entries = Vec::new();
+
+    for path in markdown_files(archive_path)? {
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read TIL: {:?}", path))?;
+        let category = entry_category(archive_path, &path);
+
+        entries.push(Entry {
+            kind: EntryKind::Til,
+            title: til::writer::extract_title(&content).unwrap_or_else(|| file_stem(&path)),
+            path,
+            category,
+            reading_minutes: markdown::reading_minutes(&content),
+            level: notes::writer::difficulty(&content),
+        });
+    }
+
+    Ok(entries)
+}
+
+/// Collect all notes (markdown or org) below the notes directory
+pub fn note_entries(notes_path: &Path) -> Result<Vec<Entry>> {
+    let mut entries = Vec::new();
+
+    let note_files = files_where(notes_path, &|path| path.extension().is_some_and(|ext| ext == "md" || ext == "org"))?;
+    for path in note_files {
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read note: {:?}", path))?;
+        let (title, level) = if path.extension().is_some_and(|ext| ext == "org") {
+            (notes::org::keyword(&content, "title"), notes::org::keyword(&content, "level"))
+        } else {
+            (notes::writer::extract_title(&content), notes::writer::difficulty(&content))
+        };
+
+        entries.push(Entry {
+            kind: EntryKind::Note,
+            title: title.unwrap_or_else(|| file_stem(&path)),
+            path,
+            category: None,
+            reading_minutes: markdown::reading_minutes(&content),
+            level,
+        });
+    }
+
+    Ok(entries)
+}
+
+/// Counts for the welcome banner and `/status`, cheap enough to gather on every start
+#[derive(Debug, Default)]
+pub struct Summary {
+    pub total: usize,
+    /// Title and modification date of the most recently saved TIL
+    pub latest: Option<(String, NaiveDate)>,
+}
+
+/// Count the TILs in the archive, reading only the newest one
+pub fn summary(archive_path: &Path) -> Result<Summary> {
+    let files = markdown_files(archive_path)?;
+    let newest = files
+        .iter()
+        .filter_map(|path| Some((fs::metadata(path).ok()?.modified().ok()?, path)))
+        .max_by_key(|&(modified, _)| modified);
+
+    let latest = match newest {
+        Some((modified, path)) => {
+            let content = fs::read_to_string(path).with_context(|| format!("Failed to read TIL: {:?}", path))?;
+            let title = til::writer::extract_title(&content).unwrap_or_else(|| file_stem(path));
+            Some((title, DateTime::<Local>::from(modified).date_naive()))
+        }
+        None => None,
+    };
+
+    Ok(Summary {
+        total: files.len(),
+        latest,
+    })
+}
+
+/// Recursively list markdown files, skipping hidden directories
+pub fn markdown_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    files_where(dir, &|path| path.extension().is_some_and(|ext| ext == "md"))
+}
+
+/// Recursively list encrypted TILs and notes (`.md.age` / `.md.gpg`), skipping hidden directories
+pub fn encrypted_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    files_where(dir, &crypto::is_encrypted)
+}
+
+fn files_where(dir: &Path, keep: &dyn Fn(&Path) -> bool) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    if !dir.is_dir() {
+        return Ok(files);
+    }
+
+    let mut read_dir: Vec<_> = fs::read_dir(dir)
+        .with_context(|| format!("Failed to read directory: {:?}", dir))?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .collect();
+    read_dir.sort();
+
+    for path in read_dir {
+        let hidden = path
+            .file_name()
+            .is_some_and(|name| name.to_string_lossy().starts_with('.'));
+        if hidden {
+            continue;
+        }
+
+        if path.is_dir() {
+            files.extend(files_where(&path, keep)?);
+        } else if keep(&path) {
+            files.push(path);
+        }
+    }
+
+    Ok(files)
+}
+
+/// Rank entries against a query, best match first. Non-matching entries are dropped.
+pub fn fuzzy_find<'a>(entries: &'a [Entry], query: &str) -> Vec<&'a Entry> {
+    let mut scored: Vec<(i64, &Entry)> = entries
+        .iter()
+        .filter_map(|entry| fuzzy_score(query, &entry.title).map(|score| (score, entry)))
+        .collect();
+
+    scored.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.title.cmp(&b.1.title)));
+    scored.into_iter().map(|(_, entry)| entry).collect()
+}
+
+/// Score how well `query` matches `text` as a case-insensitive subsequence.
+/// Substring matches, consecutive characters, and word starts score higher.
+pub fn fuzzy_score(query: &str, text: &str) -> Option<i64> {
+    let query: Vec<char> = query.to_lowercase().chars().filter(|c| !c.is_whitespace()).collect();
+    let text_lower = text.to_lowercase();
+    let chars: Vec<char> = text_lower.chars().collect();
+
+    if query.is_empty() {
+        return Some(0);
+    }
+
+    let mut score = 0;
+    let mut qi = 0;
+    let mut last_match: Option<usize> = None;
+
+    for (i, c) in chars.iter().enumerate() {
+        if qi < query.len() && *c == query[qi] {
+            score += 1;
+            if last_match.is_some_and(|last| last + 1 == i) {
+                score += 5;
+            }
+            if i == 0 || !chars[i - 1].is_alphanumeric() {
+                score += 3;
+            }
+            last_match = Some(i);
+            qi += 1;
+        }
+    }
+
+    if qi < query.len() {
+        return None;
+    }
+
+    let query_str: String = query.iter().collect();
+    if text_lower.replace(' ', "").contains(&query_str) {
+        score += 20;
+    }
+
+    // Prefer shorter titles when the match quality is otherwise equal
+    Some(score * 100 - chars.len() as i64)
+}
+
+/// The category of an archive file, from its directory inside the archive
+pub fn entry_category(archive_path: &Path, path: &Path) -> Option<String> {
+    let dir = path.parent()?.strip_prefix(archive_path).ok()?;
+    category_of(dir)
+}
+
+/// The category part of a directory inside the archive: its non-numeric components, so `rust`,
+/// `rust/2024` and `2024/06/rust` are all `rust`, and `2024/06/rust/async` is `rust/async`
+pub fn category_of(dir: &Path) -> Option<String> {
+    let parts: Vec<String> = dir
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy().to_string())
+        .filter(|name| !name.chars().all(|c| c.is_ascii_digit()))
+        .collect();
+    (!parts.is_empty()).then(|| parts.join("/"))
+}
+
+/// The date an entry was added: its first git commit, falling back to the file's mtime
+pub fn entry_date(path: &Path) -> Option<NaiveDate> {
+    git_added_date(path).or_else(|| {
+        let modified = fs::metadata(path).ok()?.modified().ok()?;
+        Some(DateTime::<Local>::from(modified).date_naive())
+    })
+}
+
+fn git_added_date(path: &Path) -> Option<NaiveDate> {
+    let dir = path.parent()?;
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(["log", "--follow", "--diff-filter=A", "--format=%cs", "--"])
+        .arg(path)
+        .output()
+        .ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let first_added = stdout.lines().last()?;
+    NaiveDate::parse_from_str(first_added.trim(), "%Y-%m-%d").ok()
+}
+
+/// When each file below `dir` was first committed, from a single `git log` rather than one per
+/// file. Files git doesn't know about, or only knows under an earlier name, are left out.
+pub fn git_added_dates(dir: &Path) -> HashMap<PathBuf, NaiveDate> {
+    let mut dates = HashMap::new();
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(["-c", "core.quotePath=false", "log", "--diff-filter=A", "--relative", "--name-only"])
+        .args(["--format=%x00%cs", "--", "."])
+        .output();
+    let Ok(output) = output else {
+        return dates;
+    };
+    if !output.status.success() {
+        return dates;
+    }
+
+    // Newest commits come first, so the last date seen for a file is when it was added
+    let mut date = None;
+    for line in String::from_utf8_lossy(&output.stdout).lines() {
+        if let Some(commit_date) = line.strip_prefix('\0') {
+            date = NaiveDate::parse_from_str(commit_date.trim(), "%Y-%m-%d").ok();
+        } else if let (Some(date), false) = (date, line.trim().is_empty()) {
+            dates.insert(dir.join(line), date);
+        }
+    }
+    dates
+}
+
+fn file_stem(path: &Path) -> String {
+    path.file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn entry(title: &str) -> Entry {
+        Entry {
+            kind: EntryKind::Til,
+            title: title.to_string(),
+            path: PathBuf::from(format!("{}.md", title)),
+            category: None,
+            reading_minutes: 1,
+            level: None,
+        }
+    }
+
+    #[test]
+    fn test_fuzzy_score_subsequence() {
+        assert!(fuzzy_score("rbs", "Rebase Basics").is_some());
+        assert!(fuzzy_score("xyz", "Rebase Basics").is_none());
+    }
+
+    #[test]
+    fn test_fuzzy_score_prefers_substring() {
+        let substring = fuzzy_score("rebase", "Interactive Rebase");
+        let scattered = fuzzy_score("rebase", "Reset Before A Squash Edit");
+
+        assert!(substring > scattered);
+    }
+
+    #[test]
+    fn test_fuzzy_find_orders_by_score() {
+        let entries = vec![
+            entry("Create Table In Postgres"),
+            entry("Update A Forked Repo"),
+            entry("Git Rebase Onto"),
+        ];
+
+        let matches = fuzzy_find(&entries, "rebase");
+
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].title, "Git Rebase Onto");
+    }
+
+    #[test]
+    fn test_til_entries_reads_titles_and_categories() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let archive = temp_dir.path().join("archive");
+        fs::create_dir_all(archive.join("git"))?;
+        fs::create_dir_all(archive.join(".hidden"))?;
+        fs::write(archive.join("git/rebase.md"), "# Rebase Onto\n\nBody")?;
+        fs::write(archive.join("git/untitled.md"), "No heading")?;
+        fs::write(archive.join(".hidden/skip.md"), "# Skip")?;
+
+        let entries = til_entries(&archive)?;
+
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].title, "Rebase Onto");
+        assert_eq!(entries[0].category, Some("git".to_string()));
+        assert_eq!(entries[1].title, "untitled");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_til_entries_dated_layouts() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let archive = temp_dir.path().join("archive");
+        fs::create_dir_all(archive.join("rust/2024"))?;
+        fs::create_dir_all(archive.join("2024/06/git"))?;
+        fs::write(archive.join("rust/2024/borrow.md"), "# Borrowing
+")?;
+        fs::write(archive.join("2024/06/git/rebase.md"), "# Rebase
+")?;
+
+        let categories: Vec<Option<String>> = til_entries(&archive)?.into_iter().map(|e| e.category).collect();
+
+        assert_eq!(categories, vec![Some("git".to_string()), Some("rust".to_string())]);
+        assert_eq!(category_of(Path::new("2024/06")), None);
+        assert_eq!(category_of(Path::new("2024/06/rust/async")), Some("rust/async".to_string()));
+        assert_eq!(category_of(Path::new("rust/async/2024")), Some("rust/async".to_string()));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_note_entries_reads_metadata() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        fs::write(
+            temp_dir.path().join("note.md"),
+            "---\ntitle: Ownership\nlevel: intermediate\n---\n\n# Ownership\n",
+        )?;
+        fs::write(
+            temp_dir.path().join("roam.org"),
+            ":PROPERTIES:\n:ID: abc\n:END:\n#+TITLE: Borrowing\n#+LEVEL: beginner\n\nBody\n",
+        )?;
+
+        let entries = note_entries(temp_dir.path())?;
+
+        assert_eq!(entries[0].title, "Ownership");
+        assert_eq!(entries[0].level, Some("intermediate".to_string()));
+        assert_eq!(entries[0].reading_minutes, 1);
+        assert_eq!(entries[1].title, "Borrowing");
+        assert_eq!(entries[1].level, Some("beginner".to_string()));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_entry_date_falls_back_to_mtime() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let path = temp_dir.path().join("entry.md");
+        fs::write(&path, "# Entry")?;
+
+        assert_eq!(entry_date(&path), Some(Local::now().date_naive()));
+        assert_eq!(entry_date(&temp_dir.path().join("missing.md")), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_note_entries_missing_dir() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let entries = note_entries(&temp_dir.path().join("missing"))?;
+        assert!(entries.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_summary_finds_newest_til() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let archive = temp_dir.path();
+        fs::create_dir_all(archive.join("rust"))?;
+        fs::create_dir_all(archive.join("git"))?;
+        fs::write(archive.join("rust/ownership.md"), "# Ownership\n")?;
+        fs::write(archive.join("git/rebase.md"), "# Rebase Basics\n")?;
+        let last_week = std::time::SystemTime::now() - std::time::Duration::from_secs(7 * 24 * 60 * 60);
+        fs::File::options()
+            .write(true)
+            .open(archive.join("rust/ownership.md"))?
+            .set_modified(last_week)?;
+
+        let found = summary(archive)?;
+        assert_eq!(found.total, 2);
+        assert_eq!(found.latest, Some(("Rebase Basics".to_string(), Local::now().date_naive())));
+        assert_eq!(summary(&archive.join("missing"))?.total, 0);
+
+        Ok(())
+    }
+}
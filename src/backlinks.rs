@@ -0,0 +1,115 @@
+use crate::{crypto, files};
+use crate::markdown::set_frontmatter_field;
+use crate::notes::org;
+use crate::til::linker::relative_path;
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::Path;
+
+/// Frontmatter field on a note that points at its TIL
+const QUICK_REFERENCE_FIELD: &str = "quick_reference";
+
+/// Append a "Detailed note" wikilink to a TIL, unless it already has one
+pub fn link_til_to_note(til_content: &str, note_path: &Path) -> String {
+    let line = format!("Detailed note: [[{}]]", note_name(note_path));
+    if til_content.contains(&line) {
+        return til_content.to_string();
+    }
+    format!("{}\n\n{}\n", til_content.trim_end(), line)
+}
+
+/// Point a note's `quick_reference` frontmatter (a `#+QUICK_REFERENCE:` keyword in org notes) at its TIL, relative to the note
+pub fn link_note_to_til(note_content: &str, note_path: &Path, til_path: &Path) -> String {
+    let from_dir = note_path.parent().unwrap_or(Path::new(""));
+    let href = relative_path(from_dir, til_path).to_string_lossy().replace('\\', "/");
+    if note_path.extension().is_some_and(|ext| ext == "org") {
+        return org::set_keyword(note_content, QUICK_REFERENCE_FIELD, &href);
+    }
+    set_frontmatter_field(note_content, QUICK_REFERENCE_FIELD, &href)
+}
+
+/// Add the note link to a TIL already on disk. Encrypted TILs are left alone.
+pub fn update_saved_til(til_path: &Path, note_path: &Path) -> Result<bool> {
+    update_file(til_path, |content| link_til_to_note(content, note_path))
+}
+
+/// Add the TIL reference to a note already on disk. Encrypted notes are left alone.
+pub fn update_saved_note(note_path: &Path, til_path: &Path) -> Result<bool> {
+    update_file(note_path, |content| link_note_to_til(content, note_path, til_path))
+}
+
+fn update_file<F>(path: &Path, link: F) -> Result<bool>
+where
+    F: FnOnce(&str) -> String,
+{
+    if crypto::is_encrypted(path) {
+        return Ok(false);
+    }
+
+    let content = fs::read_to_string(path).with_context(|| format!("Failed to read {:?}", path))?;
+    let linked = link(&content);
+    if linked == content {
+        return Ok(false);
+    }
+    files::write_atomic(path, linked).with_context(|| format!("Failed to write {:?}", path))?;
+    Ok(true)
+}
+
+/// The name Obsidian knows a note by: its filename without `.md` (or `.md.age`)
+fn note_name(note_path: &Path) -> String {
+    let filename = note_path.file_name().unwrap_or_default().to_string_lossy();
+    filename.split(".md").next().unwrap_or_default().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_link_til_to_note_once() {
+        let linked = link_til_to_note("# Rebase\n\nBody\n", Path::new("/vault/git_rebase.md"));
+        assert_eq!(linked, "# Rebase\n\nBody\n\nDetailed note: [[git_rebase]]\n");
+        assert_eq!(link_til_to_note(&linked, Path::new("/vault/git_rebase.md")), linked);
+    }
+
+    #[test]
+    fn test_link_note_to_til() {
+        let linked = link_note_to_til(
+            "---\ntitle: Rebase\n---\n\n# Rebase\n",
+            Path::new("/home/me/vault/git_rebase.md"),
+            Path::new("/home/me/til/archive/git/rebase.md"),
+        );
+        assert_eq!(
+            linked,
+            "---\ntitle: Rebase\nquick_reference: ../til/archive/git/rebase.md\n---\n\n# Rebase\n"
+        );
+
+        let linked = link_note_to_til(
+            ":PROPERTIES:\n:ID: abc\n:END:\n#+TITLE: Rebase\n\nBody\n",
+            Path::new("/home/me/roam/git_rebase.org"),
+            Path::new("/home/me/til/archive/git/rebase.md"),
+        );
+        assert!(linked.contains("#+TITLE: Rebase\n#+QUICK_REFERENCE: ../til/archive/git/rebase.md\n\nBody"));
+    }
+
+    #[test]
+    fn test_update_saved_files() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let til = temp_dir.path().join("til/git/rebase.md");
+        let note = temp_dir.path().join("vault/rebase.md");
+        fs::create_dir_all(til.parent().unwrap_or(temp_dir.path()))?;
+        fs::create_dir_all(note.parent().unwrap_or(temp_dir.path()))?;
+        fs::write(&til, "# Rebase\n")?;
+        fs::write(&note, "# Rebase in depth\n")?;
+
+        assert!(update_saved_til(&til, &note)?);
+        assert!(!update_saved_til(&til, &note)?);
+        assert!(update_saved_note(&note, &til)?);
+        assert!(!update_saved_til(&temp_dir.path().join("secret.md.age"), &note)?);
+
+        assert!(fs::read_to_string(&til)?.ends_with("Detailed note: [[rebase]]\n"));
+        assert!(fs::read_to_string(&note)?.contains("quick_reference: ../til/git/rebase.md"));
+        Ok(())
+    }
+}
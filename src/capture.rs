@@ -0,0 +1,251 @@
+use crate::queue::QueueItem;
+use anyhow::{anyhow, Context, Result};
+use serde_json::{json, Value};
+use std::fs::File;
+use std::io::{BufRead, Read, Write};
+
+/// Largest request body accepted; a link or a snippet never comes close
+const MAX_BODY_BYTES: usize = 64 * 1024;
+
+/// Most headers accepted, and their most bytes together with the request line
+const MAX_HEADERS: usize = 64;
+const MAX_HEADER_BYTES: usize = 16 * 1024;
+
+/// A parsed HTTP request, as much of one as the capture endpoint needs
+#[derive(Debug, Default)]
+pub struct Request {
+    pub method: String,
+    pub path: String,
+    /// Header names lowercased
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl Request {
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(header, _)| header == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// A JSON response
+#[derive(Debug)]
+pub struct Response {
+    pub status: u16,
+    pub body: Value,
+}
+
+impl Response {
+    pub fn new(status: u16, body: Value) -> Self {
+        Self { status, body }
+    }
+
+    pub fn error(status: u16, message: &str) -> Self {
+        Self::new(status, json!({ "error": message }))
+    }
+
+    pub fn write_to(&self, writer: &mut impl Write) -> std::io::Result<()> {
+        let body = self.body.to_string();
+        let reason = match self.status {
+            200 => "OK",
+            201 => "Created",
+            400 => "Bad Request",
+            401 => "Unauthorized",
+            404 => "Not Found",
+            405 => "Method Not Allowed",
+            _ => "Internal Server Error",
+        };
+        write!(
+            writer,
+            "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            self.status,
+            reason,
+            body.len(),
+            body
+        )?;
+        writer.flush()
+    }
+}
+
+/// Read one request: the request line, headers, and a body of `Content-Length` bytes
+pub fn read_request(reader: &mut impl BufRead) -> Result<Request> {
+    let mut budget = MAX_HEADER_BYTES;
+    let mut line = String::new();
+    read_head_line(reader, &mut line, &mut budget).context("Failed to read request")?;
+    let mut parts = line.split_whitespace();
+    let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+        return Err(anyhow!("Malformed request line"));
+    };
+    let mut request = Request {
+        method: method.to_string(),
+        path: target.split('?').next().unwrap_or_default().to_string(),
+        ..Request::default()
+    };
+
+    loop {
+        line.clear();
+        if read_head_line(reader, &mut line, &mut budget)? == 0 || line.trim().is_empty() {
+            break;
+        }
+        if request.headers.len() == MAX_HEADERS {
+            return Err(anyhow!("More than {} headers", MAX_HEADERS));
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            request.headers.push((name.trim().to_lowercase(), value.trim().to_string()));
+        }
+    }
+
+    let length: usize = request.header("content-length").and_then(|n| n.parse().ok()).unwrap_or(0);
+    if length > MAX_BODY_BYTES {
+        return Err(anyhow!("Body of {} bytes is over the {} byte limit", length, MAX_BODY_BYTES));
+    }
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body).context("Failed to read request body")?;
+    request.body = String::from_utf8_lossy(&body).to_string();
+    Ok(request)
+}
+
+/// Read a line of the request head, failing once the head is over `MAX_HEADER_BYTES`
+fn read_head_line(reader: &mut impl BufRead, line: &mut String, budget: &mut usize) -> Result<usize> {
+    let read = reader.take(*budget as u64 + 1).read_line(line)?;
+    if read > *budget {
+        return Err(anyhow!("Headers are over the {} byte limit", MAX_HEADER_BYTES));
+    }
+    *budget -= read;
+    Ok(read)
+}
+
+/// Check that a request is an authorized `POST /queue` and turn its body into a queue item. The
+/// body is JSON (`{"url": ..., "title": ...}` or `{"text": ...}`) or plain text; text that is
+/// just a link is queued as the link.
+pub fn capture(request: &Request, token: &str) -> Result<QueueItem, Response> {
+    if request.path != "/queue" {
+        return Err(Response::error(404, "Not found. Captures go to POST /queue"));
+    }
+    if request.method != "POST" {
+        return Err(Response::error(405, "Use POST"));
+    }
+    let sent = request.header("authorization").and_then(|auth| auth.strip_prefix("Bearer "));
+    if !sent.is_some_and(|sent| same_token(sent.trim(), token)) {
+        return Err(Response::error(401, "Missing or wrong token"));
+    }
+
+    let is_json = request.header("content-type").is_some_and(|kind| kind.contains("json"));
+    let (url, text, title) = if is_json {
+        let body: Value = serde_json::from_str(&request.body).map_err(|_| Response::error(400, "Invalid JSON"))?;
+        let field = |name: &str| body[name].as_str().map(str::trim).filter(|v| !v.is_empty()).map(str::to_string);
+        (field("url"), field("text"), field("title"))
+    } else {
+        (None, Some(request.body.trim().to_string()).filter(|t| !t.is_empty()), None)
+    };
+
+    let (url, text) = match (url, text) {
+        (Some(url), _) => (url, None),
+        (None, Some(text)) if is_link(&text) => (text, None),
+        (None, Some(text)) => (String::new(), Some(text)),
+        (None, None) => return Err(Response::error(400, "Send a url or some text")),
+    };
+    Ok(QueueItem {
+        url,
+        title,
+        source: "capture".to_string(),
+        added: chrono::Local::now(),
+        text,
+    })
+}
+
+/// Where random bytes for tokens come from: the OS's cryptographic RNG
+const RANDOM_SOURCE: &str = "/dev/urandom";
+
+/// A fresh random token for the endpoint: 16 bytes from the OS's RNG, as 32 hex characters
+pub fn new_token() -> Result<String> {
+    let mut bytes = [0u8; 16];
+    File::open(RANDOM_SOURCE)
+        .and_then(|mut random| random.read_exact(&mut bytes))
+        .with_context(|| format!("Failed to read random bytes from {}", RANDOM_SOURCE))?;
+    Ok(bytes.iter().map(|byte| format!("{:02x}", byte)).collect())
+}
+
+fn is_link(text: &str) -> bool {
+    (text.starts_with("https://") || text.starts_with("http://")) && !text.contains(char::is_whitespace)
+}
+
+/// Compare tokens without stopping at the first difference, so timing doesn't give them away
+fn same_token(sent: &str, token: &str) -> bool {
+    sent.len() == token.len() && sent.bytes().zip(token.bytes()).fold(0, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(raw: &str) -> Result<Request> {
+        read_request(&mut raw.as_bytes())
+    }
+
+    #[test]
+    fn test_read_request() -> Result<()> {
+        let parsed = request("POST /queue?x=1 HTTP/1.1\r\nHost: phone\r\nContent-Length: 5\r\n\r\nhello, and more")?;
+        assert_eq!(parsed.method, "POST");
+        assert_eq!(parsed.path, "/queue");
+        assert_eq!(parsed.header("host"), Some("phone"));
+        assert_eq!(parsed.body, "hello");
+
+        assert!(request("POST /queue HTTP/1.1\r\nContent-Length: 999999\r\n\r\n").is_err());
+        assert!(request("\r\n").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_request_limits_headers() -> Result<()> {
+        let many = "X-A: 1\r\n".repeat(MAX_HEADERS + 1);
+        assert!(request(&format!("POST /queue HTTP/1.1\r\n{}\r\n", many)).is_err());
+        assert!(request(&format!("POST /queue HTTP/1.1\r\n{}\r\n", "X-A: 1\r\n".repeat(MAX_HEADERS))).is_ok());
+
+        let long = format!("X-Long: {}\r\n", "a".repeat(MAX_HEADER_BYTES));
+        assert!(request(&format!("POST /queue HTTP/1.1\r\n{}\r\n", long)).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_capture_links_and_snippets() -> Result<()> {
+        let send = |content_type: &str, body: &str| -> Result<Result<QueueItem, Response>> {
+            let raw = format!(
+                "POST /queue HTTP/1.1\r\nAuthorization: Bearer secret\r\nContent-Type: {}\r\nContent-Length: {}\r\n\r\n{}",
+                content_type,
+                body.len(),
+                body
+            );
+            Ok(capture(&request(&raw)?, "secret"))
+        };
+
+        let item = send("application/json", r#"{"url": "https://a.com/post", "title": "Post"}"#)?;
+        assert!(item.is_ok_and(|item| item.url == "https://a.com/post" && item.title.as_deref() == Some("Post")));
+
+        let item = send("text/plain", " https://a.com/post\n")?;
+        assert!(item.is_ok_and(|item| item.url == "https://a.com/post" && item.text.is_none()));
+
+        let item = send("application/json", r#"{"text": "Why does VACUUM exist?"}"#)?;
+        assert!(item.is_ok_and(|item| item.url.is_empty() && item.text.as_deref() == Some("Why does VACUUM exist?")));
+
+        assert!(send("application/json", "{}")?.is_err_and(|response| response.status == 400));
+        assert!(send("application/json", "not json")?.is_err_and(|response| response.status == 400));
+        Ok(())
+    }
+
+    #[test]
+    fn test_capture_rejects() -> Result<()> {
+        let status = |raw: &str| -> Result<u16> {
+            Ok(capture(&request(raw)?, "secret").err().map_or(0, |response| response.status))
+        };
+        assert_eq!(status("POST /queue HTTP/1.1\r\nContent-Length: 1\r\n\r\nx")?, 401);
+        assert_eq!(status("POST /queue HTTP/1.1\r\nAuthorization: Bearer secreT\r\nContent-Length: 1\r\n\r\nx")?, 401);
+        assert_eq!(status("GET /queue HTTP/1.1\r\nAuthorization: Bearer secret\r\n\r\n")?, 405);
+        assert_eq!(status("POST / HTTP/1.1\r\nAuthorization: Bearer secret\r\n\r\n")?, 404);
+        assert_ne!(new_token()?, new_token()?);
+        assert_eq!(new_token()?.len(), 32);
+        Ok(())
+    }
+}
@@ -0,0 +1,71 @@
+use anyhow::{anyhow, Context, Result};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+
+/// Point this at a fixtures directory to replay canned responses instead of running `claude`
+pub const MOCK_BACKEND_ENV: &str = "HOLOCRON_MOCK_BACKEND";
+
+/// Each request's arguments, one JSON array per line, kept next to the fixtures
+pub const REQUEST_LOG: &str = "requests.jsonl";
+
+/// A stand-in for the `claude` CLI, for testing sessions end to end.
+/// Request N is answered with `<dir>/N.jsonl`, a recorded stream-json response.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MockBackend {
+    dir: PathBuf,
+}
+
+impl MockBackend {
+    pub fn from_env() -> Option<Self> {
+        std::env::var_os(MOCK_BACKEND_ENV).map(|dir| Self { dir: PathBuf::from(dir) })
+    }
+
+    /// Log a request and return the fixture that answers it. The count lives in the log,
+    /// so it carries across holocron invocations sharing the directory.
+    pub fn next_response(&self, args: &[&str]) -> Result<PathBuf> {
+        let log_path = self.dir.join(REQUEST_LOG);
+        let answered = fs::read_to_string(&log_path)
+            .map(|log| log.lines().count())
+            .unwrap_or(0);
+
+        let mut log = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&log_path)
+            .with_context(|| format!("Failed to open {:?}", log_path))?;
+        writeln!(log, "{}", serde_json::to_string(args)?)?;
+
+        let fixture = self.dir.join(format!("{}.jsonl", answered + 1));
+        if !fixture.is_file() {
+            return Err(anyhow!(
+                "Mock backend has no response for request {} (expected {:?})",
+                answered + 1,
+                fixture
+            ));
+        }
+        Ok(fixture)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_next_response_replays_in_order() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        fs::write(temp_dir.path().join("1.jsonl"), "")?;
+        let mock = MockBackend {
+            dir: temp_dir.path().to_path_buf(),
+        };
+
+        assert_eq!(mock.next_response(&["--print", "hi"])?, temp_dir.path().join("1.jsonl"));
+        assert!(mock.next_response(&["--resume", "s1", "again"]).is_err());
+
+        let log = fs::read_to_string(temp_dir.path().join(REQUEST_LOG))?;
+        assert_eq!(log, "[\"--print\",\"hi\"]\n[\"--resume\",\"s1\",\"again\"]\n");
+        Ok(())
+    }
+}
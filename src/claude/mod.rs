@@ -1,3 +1,10 @@
+mod mock;
 mod process;
+pub mod transcripts;
 
-pub use process::{continue_conversation, run_claude_command};
+pub use process::{
+    handle_interrupts, image_input_settings, is_cancelled, is_installed, is_stale_session, no_tools_settings,
+    partial_response, run_claude_command, send, send_events, tools_only_settings, webfetch_only_settings,
+    ClaudeResponse, Resume, StreamEvent, Usage,
+};
+pub use tokio_util::sync::CancellationToken;
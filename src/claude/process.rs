@@ -1,7 +1,27 @@
+use super::mock::MockBackend;
+use crate::config::Config;
+use crate::{files, markdown, verbosity};
 use anyhow::{anyhow, Result};
-use serde::Deserialize;
-use std::io::{BufRead, BufReader};
-use std::process::{Command, Stdio};
+use serde::{Deserialize, Serialize};
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::Mutex;
+use std::thread;
+use std::time::Instant;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, BufReader};
+use tokio::process::Command;
+use tokio_util::sync::CancellationToken;
+
+/// The backend conversation a request continues, if any, and the directory `claude` runs in.
+/// Claude Code keeps conversations per project directory, so one started elsewhere (e.g. in
+/// Claude Code itself) can only be resumed from there.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Resume<'a> {
+    pub session_id: Option<&'a str>,
+    /// Holocron's own working directory when not set
+    pub dir: Option<&'a Path>,
+}
 
 /// Stream message types from Claude CLI JSON output.
 #[derive(Debug, Deserialize)]
@@ -9,20 +29,76 @@ use std::process::{Command, Stdio};
 #[serde(rename_all = "snake_case")]
 #[allow(dead_code)]
 pub enum StreamMessage {
-    /// System init message
-    System {},
+    /// System init message, which already carries the session id
+    System {
+        #[serde(default)]
+        session_id: Option<String>,
+    },
     /// Assistant response with full message
     Assistant { message: AssistantMessage },
+    /// Tool results handed back to the assistant
+    User { message: AssistantMessage },
     /// Final result with session_id
     Result {
+        #[serde(default)]
         result: String,
+        /// Set when the backend couldn't answer, e.g. because the conversation to resume is gone
+        #[serde(default)]
+        is_error: bool,
         session_id: String,
+        #[serde(default)]
+        total_cost_usd: f64,
+        #[serde(default)]
+        usage: TokenUsage,
     },
     /// Catch-all for other message types
     #[serde(other)]
     Unknown,
 }
 
+/// Token counts reported in the final result message
+#[derive(Debug, Default, Deserialize)]
+pub struct TokenUsage {
+    #[serde(default)]
+    pub input_tokens: u64,
+    #[serde(default)]
+    pub cache_creation_input_tokens: u64,
+    #[serde(default)]
+    pub cache_read_input_tokens: u64,
+    #[serde(default)]
+    pub output_tokens: u64,
+}
+
+/// Tokens and cost consumed by one or more backend calls
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Usage {
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cost_usd: f64,
+}
+
+impl Usage {
+    pub fn add(&mut self, other: &Usage) {
+        self.input_tokens += other.input_tokens;
+        self.output_tokens += other.output_tokens;
+        self.cost_usd += other.cost_usd;
+    }
+
+    pub fn total_tokens(&self) -> u64 {
+        self.input_tokens + self.output_tokens
+    }
+}
+
+/// The outcome of a backend call
+#[derive(Debug, Default)]
+pub struct ClaudeResponse {
+    pub text: String,
+    pub session_id: Option<String>,
+    pub usage: Usage,
+    /// URLs the backend fetched, or that came back in its tool results
+    pub sources: Vec<String>,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct AssistantMessage {
     pub content: Vec<ContentBlock>,
@@ -33,94 +109,745 @@ pub struct AssistantMessage {
 #[serde(rename_all = "snake_case")]
 pub enum ContentBlock {
     Text { text: String },
+    ToolUse {
+        #[serde(default)]
+        name: String,
+        #[serde(default)]
+        input: serde_json::Value,
+    },
+    ToolResult {
+        #[serde(default)]
+        content: serde_json::Value,
+    },
     #[serde(other)]
     Other,
 }
 
-/// Run a Claude command with the given prompt and stream the response
-fn run_claude_with_args<F>(args: Vec<&str>, mut on_text: F) -> Result<(String, Option<String>)>
+/// Something that streamed in from the backend
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StreamEvent<'a> {
+    Text(&'a str),
+    /// The backend called a tool, e.g. WebFetch with its URL
+    ToolUse { name: &'a str, input: &'a serde_json::Value },
+}
+
+/// Returned (as an error) when a request is cancelled before it finishes
+#[derive(Debug)]
+pub struct Cancelled {
+    /// Whatever had streamed in before the request was stopped
+    pub partial: ClaudeResponse,
+}
+
+impl std::fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Request cancelled")
+    }
+}
+
+impl std::error::Error for Cancelled {}
+
+/// Whether an error came from a cancelled request
+pub fn is_cancelled(error: &anyhow::Error) -> bool {
+    error.is::<Cancelled>()
+}
+
+/// Returned (as an error) when the conversation to resume no longer exists on the backend,
+/// e.g. because it expired or was cleaned up
+#[derive(Debug)]
+pub struct StaleSession;
+
+impl std::fmt::Display for StaleSession {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "The backend no longer has this conversation")
+    }
+}
+
+impl std::error::Error for StaleSession {}
+
+/// Whether an error came from resuming a conversation the backend no longer has
+pub fn is_stale_session(error: &anyhow::Error) -> bool {
+    error.is::<StaleSession>()
+}
+
+/// Whether the backend's error output says the conversation to resume doesn't exist
+fn is_missing_conversation(text: &str) -> bool {
+    let text = text.to_lowercase();
+    ["no conversation found", "session not found", "no session found"]
+        .iter()
+        .any(|phrase| text.contains(phrase))
+}
+
+/// Recover the partial response from a cancelled request, handing back any other error
+pub fn partial_response(error: anyhow::Error) -> Result<ClaudeResponse> {
+    error.downcast::<Cancelled>().map(|cancelled| cancelled.partial)
+}
+
+/// The request in flight, which Ctrl+C cancels instead of ending holocron
+static IN_FLIGHT: Mutex<Option<CancellationToken>> = Mutex::new(None);
+
+/// Exit status of a process ended by Ctrl+C (128 + SIGINT)
+const INTERRUPTED_EXIT: i32 = 130;
+
+/// Handle Ctrl+C for the rest of the run: it cancels the request in flight, if there is one, and
+/// otherwise exits as it would without a handler. Call once, at startup: tokio never removes a
+/// signal handler, so one installed per request would swallow Ctrl+C from then on.
+pub fn handle_interrupts() {
+    thread::spawn(|| {
+        let Ok(runtime) = tokio::runtime::Builder::new_current_thread().enable_all().build() else {
+            return;
+        };
+        runtime.block_on(async {
+            while tokio::signal::ctrl_c().await.is_ok() {
+                let in_flight = IN_FLIGHT.lock().ok().and_then(|request| request.clone());
+                match in_flight {
+                    Some(cancel) => cancel.cancel(),
+                    None => std::process::exit(INTERRUPTED_EXIT),
+                }
+            }
+        });
+    });
+}
+
+/// Makes `cancel` the request Ctrl+C stops until dropped, then hands that back to the one before
+struct InFlight(Option<CancellationToken>);
+
+impl InFlight {
+    fn start(cancel: &CancellationToken) -> Self {
+        let previous = IN_FLIGHT.lock().ok().and_then(|mut request| request.replace(cancel.clone()));
+        Self(previous)
+    }
+}
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        if let Ok(mut request) = IN_FLIGHT.lock() {
+            *request = self.0.take();
+        }
+    }
+}
+
+/// Send a message, continuing the conversation in `resume` if it has one, and stream the response.
+/// `settings` is a Claude settings override (JSON) applied on top of the user's own.
+/// The request stops early when `cancel` fires or on Ctrl+C.
+pub fn send<F>(
+    resume: Resume,
+    message: &str,
+    settings: Option<&str>,
+    mut on_text: F,
+    cancel: CancellationToken,
+) -> Result<ClaudeResponse>
+where
+    F: FnMut(&str),
+{
+    let on_event = |event: StreamEvent| {
+        if let StreamEvent::Text(text) = event {
+            on_text(text);
+        }
+    };
+    send_events(resume, message, settings, on_event, cancel)
+}
+
+/// Like [`send`], but also reports tool calls as they happen
+pub fn send_events<F>(
+    resume: Resume,
+    message: &str,
+    settings: Option<&str>,
+    on_event: F,
+    cancel: CancellationToken,
+) -> Result<ClaudeResponse>
+where
+    F: FnMut(StreamEvent),
+{
+    let tool_args = tool_args(settings);
+    let mut args = vec!["--print", "--output-format", "stream-json", "--verbose"];
+    args.extend(tool_args.iter().map(String::as_str));
+    if let Some(session_id) = resume.session_id {
+        args.extend(["--resume", session_id]);
+    }
+    if let Some(settings) = settings {
+        args.extend(["--settings", settings]);
+    }
+    args.push(message);
+
+    // Two holocron processes resuming the same conversation at once would each carry on from
+    // the same point and fork it; take turns instead
+    let _turn = resume.session_id.and_then(conversation_lock);
+
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?;
+
+    let _in_flight = InFlight::start(&cancel);
+    runtime.block_on(run_claude_with_args(&args, resume.dir, on_event, &cancel))
+}
+
+/// Hold the backend conversation `session_id` for one request. Locking is best effort: if the
+/// lock can't be taken the request goes ahead anyway.
+fn conversation_lock(session_id: &str) -> Option<files::FileLock> {
+    let path = Config::config_dir().ok()?.join(format!("conversation-{}", session_id));
+    files::lock(&path)
+        .inspect_err(|e| verbosity::log(&format!("couldn't lock conversation {}: {}", session_id, e)))
+        .ok()
+}
+
+/// Run a single Claude command, with no tools, and return the full response
+pub fn run_claude_command<F>(prompt: &str, on_text: F) -> Result<ClaudeResponse>
 where
     F: FnMut(&str),
 {
-    let mut child = Command::new("claude")
-        .args(&args)
-        .stdin(Stdio::inherit())  // Allow permission prompts
+    send(Resume::default(), prompt, Some(&no_tools_settings()), on_text, CancellationToken::new())
+}
+
+/// Tools a request without a settings override may use: the web, as sessions saved before
+/// each mode had its own settings did
+const DEFAULT_TOOLS: &[&str] = &["WebFetch", "WebSearch"];
+
+/// Command-line flags limiting a request to the tools its settings override allows. `--tools`
+/// makes only those exist, so tools left off it (new ones, MCP servers) are never on offer,
+/// whatever the user's own settings allow; `--allowedTools` lets them run without asking.
+fn tool_args(settings: Option<&str>) -> Vec<String> {
+    let rules: Vec<String> = match settings {
+        Some(settings) => serde_json::from_str::<serde_json::Value>(settings)
+            .ok()
+            .and_then(|settings| settings["permissions"]["allow"].as_array().cloned())
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|rule| rule.as_str().map(str::to_string))
+            .collect(),
+        None => DEFAULT_TOOLS.iter().map(|tool| tool.to_string()).collect(),
+    };
+    // A rule like `Read(//path)` needs the Read tool, limited to that path
+    let mut tools: Vec<&str> = rules.iter().map(|rule| rule.split('(').next().unwrap_or(rule)).collect();
+    tools.dedup();
+
+    let mut args = Vec::new();
+    if !rules.is_empty() {
+        args.push(format!("--allowedTools={}", rules.join(",")));
+    }
+    args.extend([format!("--tools={}", tools.join(",")), "--strict-mcp-config".to_string()]);
+    args
+}
+
+/// Every built-in tool the backend has, so an override can also deny the ones a request doesn't
+/// need. Only a second line of defense: [`tool_args`] is what keeps other tools out.
+const ALL_TOOLS: &[&str] = &[
+    "Bash", "Edit", "Glob", "Grep", "MultiEdit", "NotebookEdit", "Read", "Task", "TodoWrite", "WebFetch", "WebSearch",
+    "Write",
+];
+
+/// A settings override that lets the backend use `tools` and nothing else
+pub fn tools_only_settings(tools: &[&str]) -> String {
+    let denied: Vec<&str> = ALL_TOOLS.iter().copied().filter(|tool| !tools.contains(tool)).collect();
+    serde_json::json!({
+        "permissions": {
+            "allow": tools,
+            "deny": denied,
+        }
+    })
+    .to_string()
+}
+
+/// A settings override that lets the backend use WebFetch and nothing else
+pub fn webfetch_only_settings() -> String {
+    tools_only_settings(&["WebFetch"])
+}
+
+/// A settings override for writing from the conversation alone (TILs, notes, summaries): no tools
+pub fn no_tools_settings() -> String {
+    tools_only_settings(&[])
+}
+
+/// Image formats the backend can read and look at
+pub const IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "gif", "webp"];
+
+/// The largest image the backend accepts
+const MAX_IMAGE_BYTES: u64 = 5 * 1024 * 1024;
+
+/// Image input: a settings override letting the backend read this one image (and nothing else
+/// outside the working directory), after checking it's a format and size the backend can take
+pub fn image_input_settings(image: &Path) -> Result<String> {
+    let extension = image
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    if !IMAGE_EXTENSIONS.contains(&extension.as_str()) {
+        return Err(anyhow!(
+            "Unsupported image {:?}. Use one of: {}",
+            image,
+            IMAGE_EXTENSIONS.join(", ")
+        ));
+    }
+
+    let image = image
+        .canonicalize()
+        .map_err(|e| anyhow!("Can't read image {:?}: {}", image, e))?;
+    let size = image.metadata()?.len();
+    if size > MAX_IMAGE_BYTES {
+        return Err(anyhow!(
+            "{:?} is {:.1} MB; images over {} MB can't be sent. Crop or resize it first.",
+            image,
+            size as f64 / (1024.0 * 1024.0),
+            MAX_IMAGE_BYTES / (1024 * 1024)
+        ));
+    }
+
+    // Absolute paths in permission rules start with `//`
+    Ok(serde_json::json!({
+        "permissions": {
+            "allow": [format!("Read(/{})", image.display())],
+        }
+    })
+    .to_string())
+}
+
+/// Whether the `claude` CLI is on PATH (or mocked); without it only the archive commands work
+pub fn is_installed() -> bool {
+    MockBackend::from_env().is_some()
+        || std::env::var_os("PATH").is_some_and(|path| find_in_path("claude", &path).is_some())
+}
+
+fn find_in_path(program: &str, path: &OsStr) -> Option<PathBuf> {
+    std::env::split_paths(path)
+        .map(|dir| dir.join(program))
+        .find(|candidate| candidate.is_file())
+}
+
+/// Run a Claude command with the given arguments and stream the response
+async fn run_claude_with_args<F>(
+    args: &[&str],
+    dir: Option<&Path>,
+    mut on_event: F,
+    cancel: &CancellationToken,
+) -> Result<ClaudeResponse>
+where
+    F: FnMut(StreamEvent),
+{
+    let quoted: Vec<String> = args.iter().map(|arg| format!("{:?}", arg)).collect();
+    verbosity::log(&format!("claude {}", quoted.join(" ")));
+
+    if let Some(mock) = MockBackend::from_env() {
+        let fixture = mock.next_response(args)?;
+        verbosity::log(&format!("mock backend replaying {:?}", fixture));
+        let fixture = tokio::fs::File::open(fixture).await?;
+        return read_events(BufReader::new(fixture), &mut on_event, cancel).await;
+    }
+
+    let started = Instant::now();
+    let mut command = Command::new("claude");
+    if let Some(dir) = dir {
+        command.current_dir(dir);
+    }
+    let mut child = command
+        .args(args)
+        // `--print` never prompts, and holocron may be reading its own stdin (e.g. piped JSONL input)
+        .stdin(Stdio::null())
         .stdout(Stdio::piped())
-        .stderr(Stdio::inherit())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
         .spawn()?;
 
     let stdout = child
         .stdout
         .take()
         .ok_or_else(|| anyhow!("Failed to get stdout"))?;
+    let stderr = child.stderr.take().map(|stderr| tokio::spawn(forward_stderr(stderr)));
+
+    match read_events(BufReader::new(stdout), &mut on_event, cancel).await {
+        Ok(response) => {
+            let status = child.wait().await?;
+            verbosity::log(&format!("claude exited ({}) after {:.1}s", status, started.elapsed().as_secs_f64()));
+            let errors = match stderr {
+                Some(task) => task.await.unwrap_or_default(),
+                None => String::new(),
+            };
+            let resumed = args.contains(&"--resume");
+            if resumed && !status.success() && response.text.is_empty() && is_missing_conversation(&errors) {
+                return Err(StaleSession.into());
+            }
+            Ok(response)
+        }
+        Err(e) => {
+            child.kill().await.ok();
+            verbosity::log(&format!("claude stopped after {:.1}s: {}", started.elapsed().as_secs_f64(), e));
+            Err(e)
+        }
+    }
+}
 
-    let reader = BufReader::new(stdout);
-    let mut full_response = String::new();
-    let mut session_id = None;
+/// Pass the backend's error output through to ours, keeping its tail to tell why a call failed
+async fn forward_stderr(stderr: tokio::process::ChildStderr) -> String {
+    const KEEP: usize = 4096;
+    let mut lines = BufReader::new(stderr).lines();
+    let mut tail = String::new();
+    while let Ok(Some(line)) = lines.next_line().await {
+        eprintln!("{}", line);
+        tail.push_str(&line);
+        tail.push('\n');
+        if tail.len() > KEEP {
+            let cut = tail.ceil_char_boundary(tail.len() - KEEP);
+            tail.drain(..cut);
+        }
+    }
+    tail
+}
 
-    for line in reader.lines() {
-        let line = line?;
-        if line.is_empty() {
-            continue;
+/// Decode the line-framed JSON stream, passing on only its text
+#[cfg(test)]
+async fn read_stream<R, F>(reader: R, on_text: &mut F, cancel: &CancellationToken) -> Result<ClaudeResponse>
+where
+    R: AsyncBufRead + Unpin,
+    F: FnMut(&str),
+{
+    let mut on_event = |event: StreamEvent| {
+        if let StreamEvent::Text(text) = event {
+            on_text(text);
         }
+    };
+    read_events(reader, &mut on_event, cancel).await
+}
+
+/// Decode the line-framed JSON stream until it ends or `cancel` fires
+async fn read_events<R, F>(reader: R, on_event: &mut F, cancel: &CancellationToken) -> Result<ClaudeResponse>
+where
+    R: AsyncBufRead + Unpin,
+    F: FnMut(StreamEvent),
+{
+    let mut lines = reader.lines();
+    let mut response = ClaudeResponse::default();
+
+    loop {
+        let line = tokio::select! {
+            biased;
+            _ = cancel.cancelled() => {
+                return Err(Cancelled { partial: response }.into());
+            }
+            line = lines.next_line() => line?,
+        };
+        let Some(line) = line else {
+            return Ok(response);
+        };
+        verbosity::log(&line);
 
         if let Ok(msg) = serde_json::from_str::<StreamMessage>(&line) {
-            match msg {
-                StreamMessage::Assistant { message } => {
-                    // Extract text from content blocks
-                    for block in message.content {
-                        if let ContentBlock::Text { text } = block {
-                            on_text(&text);
-                            full_response.push_str(&text);
+            if let StreamMessage::Result { is_error: true, ref result, .. } = msg {
+                if response.text.is_empty() && is_missing_conversation(result) {
+                    return Err(StaleSession.into());
+                }
+            }
+            apply_message(msg, &mut response, on_event);
+        }
+    }
+}
+
+fn apply_message<F>(msg: StreamMessage, response: &mut ClaudeResponse, on_event: &mut F)
+where
+    F: FnMut(StreamEvent),
+{
+    match msg {
+        StreamMessage::System {
+            session_id: Some(session_id),
+        } => response.session_id = Some(session_id),
+        StreamMessage::Assistant { message } => {
+            // Extract text from content blocks
+            for block in message.content {
+                match block {
+                    ContentBlock::Text { text } => {
+                        on_event(StreamEvent::Text(&text));
+                        response.text.push_str(&text);
+                    }
+                    ContentBlock::ToolUse { name, input } => {
+                        if let Some(url) = input.get("url").and_then(|url| url.as_str()) {
+                            response.sources.push(url.to_string());
                         }
+                        on_event(StreamEvent::ToolUse {
+                            name: &name,
+                            input: &input,
+                        })
                     }
+                    ContentBlock::ToolResult { .. } | ContentBlock::Other => {}
                 }
-                StreamMessage::Result {
-                    result: _,
-                    session_id: sid,
-                } => {
-                    session_id = Some(sid);
+            }
+        }
+        StreamMessage::User { message } => {
+            for block in message.content {
+                if let ContentBlock::ToolResult { content } = block {
+                    response.sources.extend(markdown::extract_urls(&json_text(&content)));
                 }
-                _ => {}
             }
         }
+        StreamMessage::Result {
+            session_id,
+            total_cost_usd,
+            usage,
+            ..
+        } => {
+            response.session_id = Some(session_id);
+            response.usage = Usage {
+                input_tokens: usage.input_tokens
+                    + usage.cache_creation_input_tokens
+                    + usage.cache_read_input_tokens,
+                output_tokens: usage.output_tokens,
+                cost_usd: total_cost_usd,
+            };
+        }
+        _ => {}
     }
+}
 
-    child.wait()?;
-    Ok((full_response, session_id))
+/// All the text in a JSON value, e.g. a tool result given as a string or as content blocks
+fn json_text(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::String(text) => text.clone(),
+        serde_json::Value::Array(items) => items.iter().map(json_text).collect::<Vec<_>>().join("\n"),
+        serde_json::Value::Object(fields) => fields.values().map(json_text).collect::<Vec<_>>().join("\n"),
+        _ => String::new(),
+    }
 }
 
-/// Run a single Claude command and return the full response
-pub fn run_claude_command<F>(prompt: &str, on_text: F) -> Result<(String, Option<String>)>
-where
-    F: FnMut(&str),
-{
-    let args = vec![
-        "--print",
-        "--output-format",
-        "stream-json",
-        "--verbose",
-        "--allowedTools=WebFetch,WebSearch",
-        prompt,
-    ];
-    run_claude_with_args(args, on_text)
-}
-
-/// Continue a Claude conversation with an existing session
-pub fn continue_conversation<F>(session_id: &str, message: &str, on_text: F) -> Result<String>
-where
-    F: FnMut(&str),
-{
-    let args = vec![
-        "--print",
-        "--output-format",
-        "stream-json",
-        "--verbose",
-        "--allowedTools=WebFetch,WebSearch",
-        "--resume",
-        session_id,
-        message,
-    ];
-    let (response, _) = run_claude_with_args(args, on_text)?;
-    Ok(response)
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_webfetch_only_settings() -> Result<()> {
+        let settings: serde_json::Value = serde_json::from_str(&webfetch_only_settings())?;
+        assert_eq!(settings["permissions"]["allow"], serde_json::json!(["WebFetch"]));
+        let denied = settings["permissions"]["deny"].as_array().map(|d| d.len()).unwrap_or_default();
+        assert_eq!(denied, ALL_TOOLS.len() - 1);
+        assert!(!settings["permissions"]["deny"].as_array().is_some_and(|d| d.contains(&"WebFetch".into())));
+        Ok(())
+    }
+
+    #[test]
+    fn test_no_tools_settings_denies_everything() -> Result<()> {
+        let settings: serde_json::Value = serde_json::from_str(&no_tools_settings())?;
+        assert_eq!(settings["permissions"]["allow"], serde_json::json!([]));
+        assert_eq!(settings["permissions"]["deny"], serde_json::json!(ALL_TOOLS));
+        Ok(())
+    }
+
+    #[test]
+    fn test_tool_args_follow_settings() {
+        let flags = |settings: Option<&str>| tool_args(settings).join(" ");
+        assert_eq!(flags(Some(&no_tools_settings())), "--tools= --strict-mcp-config");
+        assert_eq!(
+            flags(Some(&webfetch_only_settings())),
+            "--allowedTools=WebFetch --tools=WebFetch --strict-mcp-config"
+        );
+        assert_eq!(
+            flags(Some(r#"{"permissions":{"allow":["Read(//tmp/a.png)"]}}"#)),
+            "--allowedTools=Read(//tmp/a.png) --tools=Read --strict-mcp-config"
+        );
+        assert_eq!(
+            flags(None),
+            "--allowedTools=WebFetch,WebSearch --tools=WebFetch,WebSearch --strict-mcp-config"
+        );
+    }
+
+    #[test]
+    fn test_image_input_settings() -> Result<()> {
+        let dir = tempfile::TempDir::new()?;
+        let image = dir.path().join("Slide.PNG");
+        std::fs::write(&image, [0u8; 16])?;
+
+        let settings: serde_json::Value = serde_json::from_str(&image_input_settings(&image)?)?;
+        let rule = format!("Read(/{})", image.canonicalize()?.display());
+        assert_eq!(settings["permissions"]["allow"], serde_json::json!([rule]));
+
+        std::fs::write(dir.path().join("notes.txt"), "")?;
+        assert!(image_input_settings(&dir.path().join("notes.txt")).is_err());
+        assert!(image_input_settings(&dir.path().join("missing.png")).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_find_in_path() -> Result<()> {
+        let empty = tempfile::TempDir::new()?;
+        let bin = tempfile::TempDir::new()?;
+        std::fs::write(bin.path().join("claude"), "")?;
+
+        let path = std::env::join_paths([empty.path(), bin.path()])?;
+        assert_eq!(find_in_path("claude", &path), Some(bin.path().join("claude")));
+        assert_eq!(find_in_path("claude", &std::env::join_paths([empty.path()])?), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_result_message_with_usage() -> Result<()> {
+        let line = r#"{"type":"result","result":"done","session_id":"abc","total_cost_usd":0.25,"usage":{"input_tokens":10,"cache_read_input_tokens":5,"output_tokens":20}}"#;
+
+        match serde_json::from_str::<StreamMessage>(line)? {
+            StreamMessage::Result {
+                session_id,
+                total_cost_usd,
+                usage,
+                ..
+            } => {
+                assert_eq!(session_id, "abc");
+                assert_eq!(total_cost_usd, 0.25);
+                assert_eq!(usage.input_tokens, 10);
+                assert_eq!(usage.cache_read_input_tokens, 5);
+                assert_eq!(usage.output_tokens, 20);
+            }
+            other => return Err(anyhow!("unexpected message: {:?}", other)),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_result_message_without_usage() -> Result<()> {
+        let line = r#"{"type":"result","result":"done","session_id":"abc"}"#;
+        assert!(matches!(
+            serde_json::from_str::<StreamMessage>(line)?,
+            StreamMessage::Result { total_cost_usd, .. } if total_cost_usd == 0.0
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_events_reports_stale_session() -> Result<()> {
+        let stream = r#"{"type":"result","is_error":true,"result":"No conversation found with session ID: abc","session_id":"abc"}"#;
+        let mut on_text = |_: &str| {};
+        let error = block_on(read_stream(stream.as_bytes(), &mut on_text, &CancellationToken::new()))?
+            .err()
+            .ok_or_else(|| anyhow!("expected an error"))?;
+        assert!(is_stale_session(&error));
+
+        assert!(is_missing_conversation("Error: No conversation found with session ID: abc\n"));
+        assert!(!is_missing_conversation("Error: rate limited"));
+        Ok(())
+    }
+
+    fn block_on<T>(future: impl std::future::Future<Output = T>) -> Result<T> {
+        Ok(tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()?
+            .block_on(future))
+    }
+
+    #[test]
+    fn test_read_stream_collects_sources() -> Result<()> {
+        let stream = concat!(
+            r#"{"type":"assistant","message":{"content":[{"type":"tool_use","name":"WebFetch","input":{"url":"https://a.com/post"}}]}}"#, "\n",
+            r#"{"type":"user","message":{"content":[{"type":"tool_result","content":[{"type":"text","text":"Results: https://b.com/docs."}]}]}}"#, "\n",
+            r#"{"type":"user","message":{"content":[{"type":"tool_result","content":"See http://c.org"}]}}"#, "\n",
+        );
+
+        let mut on_text = |_: &str| {};
+        let response = block_on(read_stream(stream.as_bytes(), &mut on_text, &CancellationToken::new()))??;
+        assert_eq!(response.sources, vec!["https://a.com/post", "https://b.com/docs", "http://c.org"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_stream_collects_text_and_result() -> Result<()> {
+        let stream = concat!(
+            r#"{"type":"system"}"#, "\n",
+            r#"{"type":"assistant","message":{"content":[{"type":"text","text":"Hello "},{"type":"tool_use"}]}}"#, "\n",
+            "not json\n",
+            "\n",
+            r#"{"type":"assistant","message":{"content":[{"type":"text","text":"world"}]}}"#, "\n",
+            r#"{"type":"result","result":"Hello world","session_id":"s1","total_cost_usd":0.1}"#, "\n",
+        );
+
+        let mut chunks = Vec::new();
+        let response = block_on(read_stream(
+            stream.as_bytes(),
+            &mut |text: &str| chunks.push(text.to_string()),
+            &CancellationToken::new(),
+        ))??;
+
+        assert_eq!(chunks, vec!["Hello ", "world"]);
+        assert_eq!(response.text, "Hello world");
+        assert_eq!(response.session_id.as_deref(), Some("s1"));
+        assert_eq!(response.usage.cost_usd, 0.1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_events_reports_tool_use() -> Result<()> {
+        let stream = concat!(
+            r#"{"type":"assistant","message":{"content":[{"type":"tool_use","id":"t1","name":"WebFetch","input":{"url":"https://a.com"}}]}}"#, "\n",
+            r#"{"type":"assistant","message":{"content":[{"type":"text","text":"Done"}]}}"#, "\n",
+        );
+
+        let mut events = Vec::new();
+        block_on(read_events(
+            stream.as_bytes(),
+            &mut |event: StreamEvent| {
+                events.push(match event {
+                    StreamEvent::Text(text) => format!("text:{}", text),
+                    StreamEvent::ToolUse { name, input } => format!("tool:{}:{}", name, input["url"]),
+                })
+            },
+            &CancellationToken::new(),
+        ))??;
+
+        assert_eq!(events, vec!["tool:WebFetch:\"https://a.com\"", "text:Done"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_stream_cancelled() -> Result<()> {
+        // The writer half stays open, so without cancellation this would wait forever
+        let (_writer, reader) = tokio::io::duplex(64);
+        let cancel = CancellationToken::new();
+        cancel.cancel();
+
+        let result = block_on(read_stream(BufReader::new(reader), &mut |_: &str| {}, &cancel))?;
+
+        assert!(result.as_ref().is_err_and(is_cancelled));
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_stream_cancelled_keeps_partial() -> Result<()> {
+        use tokio::io::AsyncWriteExt;
+
+        let (mut writer, reader) = tokio::io::duplex(1024);
+        let cancel = CancellationToken::new();
+
+        let result = block_on(async {
+            writer
+                .write_all(
+                    concat!(
+                        r#"{"type":"system","subtype":"init","session_id":"s1"}"#, "\n",
+                        r#"{"type":"assistant","message":{"content":[{"type":"text","text":"Partial"}]}}"#, "\n",
+                    )
+                    .as_bytes(),
+                )
+                .await?;
+
+            let stopper = cancel.clone();
+            let mut on_text = |_: &str| stopper.cancel();
+            read_stream(BufReader::new(reader), &mut on_text, &cancel).await
+        })?;
+
+        let partial = match result {
+            Ok(_) => return Err(anyhow!("expected the request to be cancelled")),
+            Err(e) => partial_response(e)?,
+        };
+        assert_eq!(partial.text, "Partial");
+        assert_eq!(partial.session_id.as_deref(), Some("s1"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_usage_add() {
+        let mut total = Usage::default();
+        total.add(&Usage {
+            input_tokens: 100,
+            output_tokens: 50,
+            cost_usd: 0.5,
+        });
+        total.add(&Usage {
+            input_tokens: 10,
+            output_tokens: 5,
+            cost_usd: 0.25,
+        });
+
+        assert_eq!(total.total_tokens(), 165);
+        assert_eq!(total.cost_usd, 0.75);
+    }
 }
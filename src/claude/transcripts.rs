@@ -0,0 +1,271 @@
+use crate::notes::org::new_id;
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Local, Utc};
+use serde_json::{json, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Claude Code keeps its settings and transcripts here unless this is set
+const CONFIG_DIR_ENV: &str = "CLAUDE_CONFIG_DIR";
+
+/// How much of a session's first prompt the picker shows
+const PREVIEW_CHARS: usize = 70;
+
+/// A Claude Code session on disk, as listed for picking one to resume
+#[derive(Debug, Clone)]
+pub struct Transcript {
+    pub id: String,
+    pub path: PathBuf,
+    pub modified: DateTime<Local>,
+}
+
+/// What holocron takes from a Claude Code session: the text of each turn, and the directory it ran in
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Conversation {
+    /// `(user message, assistant reply)` pairs, without tool calls or their results
+    pub exchanges: Vec<(String, String)>,
+    /// Claude Code only resumes a session from the project directory it belongs to
+    pub cwd: Option<PathBuf>,
+}
+
+impl Conversation {
+    /// The first thing the user asked, shortened for a one-line preview
+    pub fn preview(&self) -> String {
+        let first = self.exchanges.first().map(|(user, _)| user.as_str()).unwrap_or_default();
+        let line = first.lines().next().unwrap_or_default().trim();
+        match line.char_indices().nth(PREVIEW_CHARS) {
+            Some((cut, _)) => format!("{}…", &line[..cut]),
+            None => line.to_string(),
+        }
+    }
+}
+
+/// Where Claude Code stores transcripts, one directory per project
+pub fn projects_dir() -> Option<PathBuf> {
+    let config_dir = std::env::var_os(CONFIG_DIR_ENV)
+        .map(PathBuf::from)
+        .or_else(|| dirs::home_dir().map(|home| home.join(".claude")))?;
+    Some(config_dir.join("projects"))
+}
+
+/// Claude Code's directory for sessions run in `cwd`: the path with everything but letters and
+/// digits turned into dashes
+pub fn project_dir(cwd: &Path) -> Option<PathBuf> {
+    let name: String = cwd
+        .to_string_lossy()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect();
+    Some(projects_dir()?.join(name))
+}
+
+/// The transcript of session `id`, in whichever project it belongs to
+pub fn find(id: &str) -> Result<PathBuf> {
+    let id = id.trim();
+    all()?
+        .into_iter()
+        .find(|transcript| transcript.id == id)
+        .map(|transcript| transcript.path)
+        .ok_or_else(|| anyhow!("No Claude Code session {} found in {:?}", id, projects_dir().unwrap_or_default()))
+}
+
+/// Every Claude Code session, most recently active first
+pub fn all() -> Result<Vec<Transcript>> {
+    let Some(projects) = projects_dir().filter(|dir| dir.is_dir()) else {
+        return Ok(Vec::new());
+    };
+
+    let mut transcripts = Vec::new();
+    for project in fs::read_dir(&projects).with_context(|| format!("Failed to read {:?}", projects))? {
+        let Ok(entries) = fs::read_dir(project?.path()) else {
+            continue;
+        };
+        for path in entries.filter_map(|entry| entry.ok()).map(|entry| entry.path()) {
+            let is_transcript = path.extension().is_some_and(|ext| ext == "jsonl");
+            let (Some(id), Ok(modified)) = (path.file_stem(), fs::metadata(&path).and_then(|m| m.modified())) else {
+                continue;
+            };
+            if is_transcript {
+                transcripts.push(Transcript {
+                    id: id.to_string_lossy().to_string(),
+                    modified: modified.into(),
+                    path,
+                });
+            }
+        }
+    }
+    transcripts.sort_by_key(|transcript| std::cmp::Reverse(transcript.modified));
+    Ok(transcripts)
+}
+
+/// Read a transcript's conversation
+pub fn read(path: &Path) -> Result<Conversation> {
+    let raw = fs::read_to_string(path).with_context(|| format!("Failed to read {:?}", path))?;
+    Ok(parse(&raw))
+}
+
+/// Save `conversation` as a new Claude Code session belonging to the project at `cwd`, so
+/// `claude --resume` run there picks it up. Returns the new session's id and transcript path.
+pub fn write(conversation: &Conversation, cwd: &Path, at: DateTime<Local>) -> Result<(String, PathBuf)> {
+    let dir = project_dir(cwd).ok_or_else(|| anyhow!("Can't find the Claude Code config directory"))?;
+    fs::create_dir_all(&dir).with_context(|| format!("Failed to create {:?}", dir))?;
+    let id = new_id();
+    let path = dir.join(format!("{}.jsonl", id));
+    fs::write(&path, render(conversation, &id, cwd, at)).with_context(|| format!("Failed to write {:?}", path))?;
+    Ok((id, path))
+}
+
+/// A conversation as Claude Code writes it: one event per message, each chained to the one before
+pub fn render(conversation: &Conversation, session_id: &str, cwd: &Path, at: DateTime<Local>) -> String {
+    let timestamp = at.with_timezone(&Utc).to_rfc3339_opts(chrono::SecondsFormat::Millis, true);
+    let mut parent: Option<String> = None;
+    let mut lines = Vec::new();
+
+    for (user, assistant) in &conversation.exchanges {
+        let messages = [
+            ("user", json!({"role": "user", "content": user})),
+            (
+                "assistant",
+                json!({
+                    "id": format!("msg_{}", new_id().replace('-', "")),
+                    "type": "message",
+                    "role": "assistant",
+                    "model": "<synthetic>",
+                    "content": [{"type": "text", "text": assistant}],
+                    "stop_reason": "end_turn",
+                    "stop_sequence": null,
+                    "usage": {"input_tokens": 0, "output_tokens": 0},
+                }),
+            ),
+        ];
+        for (kind, message) in messages {
+            let uuid = new_id();
+            let event = json!({
+                "parentUuid": parent,
+                "isSidechain": false,
+                "userType": "external",
+                "cwd": cwd,
+                "sessionId": session_id,
+                "type": kind,
+                "message": message,
+                "uuid": uuid,
+                "timestamp": timestamp,
+            });
+            lines.push(event.to_string());
+            parent = Some(uuid);
+        }
+    }
+    lines.iter().map(|line| format!("{}\n", line)).collect()
+}
+
+/// One JSON event per line. A user turn starts an exchange and the assistant's text up to the
+/// next user turn answers it; tool results, subagent turns and slash command output are skipped.
+pub fn parse(raw: &str) -> Conversation {
+    let mut conversation = Conversation::default();
+    let mut current: Option<(String, Vec<String>)> = None;
+
+    for event in raw.lines().filter_map(|line| serde_json::from_str::<Value>(line).ok()) {
+        if event["isSidechain"] == true || event["isMeta"] == true {
+            continue;
+        }
+        if conversation.cwd.is_none() {
+            conversation.cwd = event["cwd"].as_str().map(PathBuf::from);
+        }
+        let text = content_text(&event["message"]["content"]);
+        let text = text.trim();
+        if text.is_empty() {
+            continue;
+        }
+        match event["type"].as_str() {
+            Some("user") if !text.starts_with('<') => {
+                conversation.exchanges.extend(current.take().and_then(finish));
+                current = Some((text.to_string(), Vec::new()));
+            }
+            Some("assistant") => {
+                if let Some((_, ref mut replies)) = current {
+                    replies.push(text.to_string());
+                }
+            }
+            _ => {}
+        }
+    }
+    conversation.exchanges.extend(current.and_then(finish));
+    conversation
+}
+
+/// An exchange the assistant never answered in text isn't worth keeping
+fn finish((user, replies): (String, Vec<String>)) -> Option<(String, String)> {
+    (!replies.is_empty()).then(|| (user, replies.join("\n\n")))
+}
+
+/// Message content given either as a string or as a list of blocks, of which the text ones count
+pub fn content_text(content: &Value) -> String {
+    match content {
+        Value::String(text) => text.clone(),
+        Value::Array(blocks) => blocks
+            .iter()
+            .filter(|block| block["type"] == "text")
+            .filter_map(|block| block["text"].as_str())
+            .collect::<Vec<_>>()
+            .join("\n"),
+        _ => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_pairs_turns_and_skips_tools() {
+        let raw = [
+            r#"{"type":"summary","summary":"Lifetimes"}"#,
+            r#"{"type":"user","cwd":"/home/me/proj","message":{"role":"user","content":"<command-name>/clear</command-name>"}}"#,
+            r#"{"type":"user","cwd":"/home/me/proj","message":{"role":"user","content":"Why does this borrow fail?"}}"#,
+            r#"{"type":"assistant","message":{"content":[{"type":"text","text":"Let me look."},{"type":"tool_use","name":"Read"}]}}"#,
+            r#"{"type":"user","message":{"content":[{"type":"tool_result","content":"fn main() {}"}]}}"#,
+            r#"{"type":"assistant","message":{"content":[{"type":"text","text":"The reference outlives the value."}]}}"#,
+            r#"{"type":"assistant","isSidechain":true,"message":{"content":[{"type":"text","text":"subagent"}]}}"#,
+            r#"{"type":"user","message":{"content":[{"type":"text","text":"Thanks, and with Rc?"}]}}"#,
+            r#"{"type":"assistant","message":{"content":[{"type":"text","text":"Rc shares ownership."}]}}"#,
+            r#"{"type":"user","message":{"content":"Unanswered"}}"#,
+        ]
+        .join("\n");
+
+        let conversation = parse(&raw);
+
+        assert_eq!(conversation.cwd, Some(PathBuf::from("/home/me/proj")));
+        assert_eq!(
+            conversation.exchanges,
+            vec![
+                (
+                    "Why does this borrow fail?".to_string(),
+                    "Let me look.\n\nThe reference outlives the value.".to_string()
+                ),
+                ("Thanks, and with Rc?".to_string(), "Rc shares ownership.".to_string()),
+            ]
+        );
+        assert_eq!(conversation.preview(), "Why does this borrow fail?");
+    }
+
+    #[test]
+    fn test_render_reads_back() -> Result<()> {
+        let conversation = Conversation {
+            exchanges: vec![
+                ("What is Pin?".to_string(), "A pointer that won't move.\n\n```rust\nBox::pin(x)\n```".to_string()),
+                ("And Unpin?".to_string(), "Types that don't care.".to_string()),
+            ],
+            cwd: Some(PathBuf::from("/home/me/proj")),
+        };
+
+        let raw = render(&conversation, "abc", Path::new("/home/me/proj"), Local::now());
+
+        assert_eq!(parse(&raw), conversation);
+        let events: Vec<Value> = raw.lines().map(serde_json::from_str).collect::<Result<_, _>>()?;
+        assert_eq!(events.len(), 4);
+        assert_eq!(events[0]["parentUuid"], Value::Null);
+        assert_eq!(events[1]["parentUuid"], events[0]["uuid"]);
+        assert!(events.iter().all(|event| event["sessionId"] == "abc"));
+        Ok(())
+    }
+}
@@ -1,4 +1,5 @@
-use clap::{Parser, Subcommand};
+use chrono::NaiveDate;
+use clap::{Args, Parser, Subcommand};
 use std::path::PathBuf;
 
 #[derive(Parser)]
@@ -7,6 +8,26 @@ use std::path::PathBuf;
 #[command(long_about = "Holocron is your personal learning companion. Start an interactive \
     session to deep dive into topics, analyze articles, and generate TIL entries or detailed notes.")]
 pub struct Cli {
+    /// Show what would change on disk without writing anything (not supported by init or tutorial)
+    #[arg(long, global = true)]
+    pub dry_run: bool,
+
+    /// No banners, spinners or decorative rules, for scripts and tmux panes
+    #[arg(short, long, global = true, conflicts_with = "verbose")]
+    pub quiet: bool,
+
+    /// Log backend calls (arguments, timing, raw stream events) to holocron.log in the config directory
+    #[arg(short, long, global = true)]
+    pub verbose: bool,
+
+    /// Plain-text stand-ins for emoji and symbols like ✓, for fonts that lack them
+    #[arg(long, global = true)]
+    pub no_emoji: bool,
+
+    // For the interactive session started without a command; a command takes its own
+    #[command(flatten)]
+    pub save: SaveArgs,
+
     #[command(subcommand)]
     pub command: Option<Commands>,
 }
@@ -21,6 +42,39 @@ pub enum Commands {
         /// Category for TIL generation (e.g., git, rust, sql)
         #[arg(short, long)]
         category: Option<String>,
+
+        /// Limit the session pomodoro-style, e.g. 25m or 1h; the prompt shows the time left
+        #[arg(long)]
+        timebox: Option<String>,
+
+        /// Output format: text, or jsonl for one JSON event per line (messages are read from stdin)
+        #[arg(long, default_value = "text")]
+        output: String,
+
+        /// Attach to a Claude Code session by id and carry on with it here; with no id, pick one
+        #[arg(long, value_name = "SESSION_ID", num_args = 0..=1, default_missing_value = "", conflicts_with_all = ["output", "timebox"])]
+        resume: Option<String>,
+
+        /// Grow this existing TIL with what the session taught instead of writing a new one
+        #[arg(long, value_name = "EXISTING_TIL")]
+        append_to: Option<PathBuf>,
+
+        #[command(flatten)]
+        save: SaveArgs,
+    },
+
+    /// Break a big topic into a syllabus, then work through it one deep dive at a time
+    #[command(args_conflicts_with_subcommands = true)]
+    Plan {
+        #[command(subcommand)]
+        action: Option<PlanAction>,
+
+        /// The topic to plan (e.g., "distributed systems"); without one, lists your plans
+        topic: Vec<String>,
+
+        /// Category for TILs written while following the plan
+        #[arg(short, long)]
+        category: Option<String>,
     },
 
     /// Analyze and summarize an article from a URL
@@ -31,30 +85,574 @@ pub enum Commands {
         /// Category for TIL generation (e.g., git, rust, sql)
         #[arg(short, long)]
         category: Option<String>,
+
+        /// Attach to a Claude Code session by id and carry on with it here; with no id, pick one
+        #[arg(long, value_name = "SESSION_ID", num_args = 0..=1, default_missing_value = "")]
+        resume: Option<String>,
+
+        #[command(flatten)]
+        save: SaveArgs,
+    },
+
+    /// Compare two technologies or approaches, e.g. `holocron compare tokio vs async-std`
+    Compare {
+        /// What to compare, in the form `<a> vs <b>`
+        #[arg(required = true, num_args = 1..)]
+        subjects: Vec<String>,
+
+        /// Category for TIL generation (e.g., git, rust, sql)
+        #[arg(short, long)]
+        category: Option<String>,
+
+        #[command(flatten)]
+        save: SaveArgs,
+    },
+
+    /// Analyze a video (e.g. a YouTube talk) from its captions
+    Video {
+        /// The video URL
+        url: String,
+
+        /// Category for TIL generation (e.g., git, rust, sql)
+        #[arg(short, long)]
+        category: Option<String>,
+
+        #[command(flatten)]
+        save: SaveArgs,
+    },
+
+    /// Transcribe a recording (talk, voice memo) and learn from it like an article
+    Audio {
+        /// The audio file to transcribe
+        file: PathBuf,
+
+        /// Category for TIL generation (e.g., git, rust, sql)
+        #[arg(short, long)]
+        category: Option<String>,
+
+        #[command(flatten)]
+        save: SaveArgs,
+    },
+
+    /// Have the non-obvious commands from your shell history explained, with TIL candidates
+    ShellRecap {
+        /// How far back to look: 12h, 2d, 1w or YYYY-MM-DD (default: since midnight)
+        #[arg(long)]
+        since: Option<String>,
+
+        /// Shell history file to read (default: the configured one, or the login shell's)
+        #[arg(long, value_name = "FILE")]
+        history: Option<PathBuf>,
+
+        /// Category for TIL generation (e.g., git, rust, sql)
+        #[arg(short, long)]
+        category: Option<String>,
+
+        #[command(flatten)]
+        save: SaveArgs,
+    },
+
+    /// Have a slide, diagram or screenshot explained, then ask about it
+    Image {
+        /// The image file (png, jpg, gif, webp)
+        file: PathBuf,
+
+        /// Category for TIL generation (e.g., git, rust, sql)
+        #[arg(short, long)]
+        category: Option<String>,
+
+        #[command(flatten)]
+        save: SaveArgs,
+    },
+
+    /// Ask a one-off question and print the answer, with no session, history or prompts
+    Ask {
+        /// The question
+        #[arg(required = true, num_args = 1..)]
+        question: Vec<String>,
+
+        /// Turn the answer into a TIL afterwards, going through the usual save prompt
+        #[arg(long)]
+        save_til: bool,
+
+        /// Category for the TIL with --save-til (asked for if left out)
+        #[arg(short, long, requires = "save_til")]
+        category: Option<String>,
+
+        #[command(flatten)]
+        save: SaveArgs,
+    },
+
+    /// List saved TILs and notes with reading time and difficulty
+    List {
+        /// Only show TILs in this category
+        #[arg(short, long)]
+        category: Option<String>,
+
+        /// Only show entries that take at most this many minutes to read
+        #[arg(long)]
+        max_minutes: Option<u32>,
+    },
+
+    /// List or search holocron's notes through the notes repo's notes.json index
+    Notes {
+        #[command(subcommand)]
+        action: NotesAction,
+    },
+
+    /// Search TILs with a regex, printing matching lines with their context
+    Grep {
+        /// Regular expression to search for
+        pattern: String,
+
+        /// Lines of context to show around each match
+        #[arg(short = 'C', long, default_value_t = 0)]
+        context: usize,
+
+        /// Only search TILs in this category
+        #[arg(short, long)]
+        category: Option<String>,
+
+        /// Match regardless of case
+        #[arg(short, long)]
+        ignore_case: bool,
+    },
+
+    /// Open a TIL or note by fuzzy-matching its title
+    Open {
+        /// Title (or part of it) to search for
+        query: String,
+
+        /// Open the containing folder instead of the file
+        #[arg(long, conflicts_with = "obsidian")]
+        reveal: bool,
+
+        /// Open via the obsidian:// URI scheme
+        #[arg(long)]
+        obsidian: bool,
+    },
+
+    /// Print one TIL, short enough for a shell's startup (the least recently shown, kept for the day)
+    TilOfTheDay {
+        /// Pick a random TIL instead of today's
+        #[arg(long)]
+        random: bool,
+
+        /// No colors or emoji
+        #[arg(long)]
+        plain: bool,
+
+        /// Cut the body off after this many lines
+        #[arg(long, default_value_t = 12)]
+        max_lines: usize,
+    },
+
+    /// Review drafted TILs, or draft TILs from past sessions with --from-sessions
+    Til(TilArgs),
+
+    /// Propose TILs from a conversation log: a Claude Code transcript (.jsonl), a claude.ai export (.json) or text
+    Extract {
+        /// The conversation log
+        file: PathBuf,
+
+        #[command(flatten)]
+        save: SaveArgs,
+    },
+
+    /// Browse the archive in a terminal UI (ctrl-p for all commands)
+    Browse,
+
+    /// Relink mentions of TIL titles across the whole archive
+    Relink,
+
+    /// Find near-duplicate TILs (similar titles or bodies) and offer to merge each group into one
+    Dedupe,
+
+    /// Revert the last change holocron made to the TIL repo (a saved TIL, README repair, relink, ...)
+    Undo,
+
+    /// Print an encrypted TIL or note (.md.age / .md.gpg)
+    Decrypt {
+        /// The encrypted file
+        file: PathBuf,
+    },
+
+    /// Check that the README index and the archive agree
+    Check {
+        /// Repair the README to match the archive
+        #[arg(long)]
+        fix: bool,
+    },
+
+    /// Share a stored session as a markdown file or a GitHub gist
+    Share {
+        /// Session to share: "last", or words from its topic
+        #[arg(default_value = "last")]
+        session: String,
+
+        /// Share the TIL saved from the session instead of the transcript
+        #[arg(long, conflicts_with = "note")]
+        til: bool,
+
+        /// Share the note saved from the session instead of the transcript
+        #[arg(long)]
+        note: bool,
+
+        /// Upload as a secret GitHub gist (needs share.github_token)
+        #[arg(long, conflicts_with = "out")]
+        gist: bool,
+
+        /// Directory to write the markdown file to (defaults to the current directory)
+        #[arg(short, long)]
+        out: Option<PathBuf>,
+    },
+
+    /// Show or manage the queue of articles waiting for a /link session
+    Queue {
+        #[command(subcommand)]
+        action: Option<QueueAction>,
+    },
+
+    /// Watch the clipboard (or a drop directory) for links, and analyze or queue each one
+    Watch {
+        /// Watch this directory for files containing links instead of the clipboard
+        #[arg(long)]
+        dir: Option<PathBuf>,
+
+        /// Seconds between checks
+        #[arg(long, default_value = "2")]
+        interval: u64,
+    },
+
+    /// Serve holocron over HTTP on your network
+    Serve {
+        /// Accept links and snippets at `POST /queue` (e.g. from a phone shortcut) and add them to the queue
+        #[arg(long)]
+        capture: bool,
+
+        /// Address and port to listen on
+        #[arg(long, default_value = "0.0.0.0:7878")]
+        listen: String,
+    },
+
+    /// Pull and push holocron's state (queue, sessions, drafts) through a git repo
+    Sync,
+
+    /// Export the archive to other formats
+    Export {
+        #[command(subcommand)]
+        target: ExportTarget,
+    },
+
+    /// Show trends in what you've been learning, from the archive and session history
+    Insights {
+        /// How many recent days to report on
+        #[arg(long, default_value = "30")]
+        days: u32,
+
+        /// Ask the backend to suggest topics to revisit
+        #[arg(long)]
+        suggest: bool,
+    },
+
+    /// Summarize the TILs and sessions of the past week, e.g. from cron with `--email`
+    Digest {
+        /// How many recent days to cover
+        #[arg(long, default_value = "7")]
+        days: u32,
+
+        /// Send it as HTML to the `[email]` address instead of printing it
+        #[arg(long)]
+        email: bool,
     },
 
+    /// List past sessions, newest first
+    History {
+        /// Only sessions with this tag (set with /tag)
+        #[arg(long)]
+        tag: Option<String>,
+
+        /// How far back to look: 12h, 2d, 1w or YYYY-MM-DD (default: all)
+        #[arg(long)]
+        since: Option<String>,
+    },
+
+    /// Walk through a short practice session, saving into a throwaway TIL repo
+    Tutorial,
+
     /// Initialize a new TIL repository
     Init {
         /// Path where the TIL repository should be created
         path: PathBuf,
+
+        /// Adopt an existing TIL repository: detect its layout and add skills without overwriting anything
+        #[arg(long)]
+        from_existing: bool,
     },
 
     /// View or update holocron configuration
-    Config {
-        /// Set the TIL repository path
+    Config(Box<ConfigArgs>),
+}
+
+#[derive(Subcommand)]
+pub enum QueueAction {
+    /// Import unread articles from a read-it-later service
+    Import {
+        /// Where to import from (readwise, pocket, instapaper)
+        #[arg(long, required_unless_present = "bookmarks", conflicts_with = "bookmarks")]
+        source: Option<String>,
+
+        /// Import a browser bookmarks export instead: Netscape HTML, or Chrome/Firefox JSON
+        #[arg(long, value_name = "FILE")]
+        bookmarks: Option<PathBuf>,
+
+        /// Only bookmarks in this folder or below it, e.g. "To Learn" or "Bookmarks bar/Rust"
+        #[arg(long, requires = "bookmarks")]
+        folder: Option<String>,
+    },
+
+    /// Start a /link session on the oldest queued article
+    Next {
+        #[command(flatten)]
+        save: SaveArgs,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum NotesAction {
+    /// List indexed notes, newest first
+    List {
+        /// Only notes with this tag
+        #[arg(short, long)]
+        tag: Option<String>,
+    },
+
+    /// Find notes whose title, tags or session topic contain every word of the query
+    Search {
+        query: String,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum PlanAction {
+    /// Start a deep dive on the next unchecked step; it's checked off once you save a TIL
+    Next {
+        /// Words from the plan's topic (defaults to the latest plan with steps left)
+        plan: Option<String>,
+
+        #[command(flatten)]
+        save: SaveArgs,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ExportTarget {
+    /// Combine the archive into a single PDF or EPUB (requires pandoc)
+    Book {
+        /// Output format (pdf, epub)
+        #[arg(long, default_value = "epub")]
+        format: String,
+
+        /// Output file (defaults to til.<format>)
+        #[arg(short, long)]
+        out: Option<PathBuf>,
+
+        /// Book title
+        #[arg(long, default_value = "Today I Learned")]
+        title: String,
+
+        /// Only include this category
+        #[arg(short, long)]
+        category: Option<String>,
+
+        /// Only include entries added on or after this date (YYYY-MM-DD)
         #[arg(long)]
-        til_path: Option<PathBuf>,
+        since: Option<NaiveDate>,
 
-        /// Set the notes repository path
+        /// Only include entries added on or before this date (YYYY-MM-DD)
         #[arg(long)]
-        notes_path: Option<PathBuf>,
+        until: Option<NaiveDate>,
+    },
+
+    /// Render one TIL or note as a self-contained HTML file, to attach to an email or drop into a wiki
+    Html {
+        /// Path to the TIL or note, or words from its title
+        entry: String,
+
+        /// Output file (defaults to the entry's name with .html, in the current directory)
+        #[arg(short, long)]
+        out: Option<PathBuf>,
+    },
+
+    /// Write an Atom feed of the most recent TILs, for subscribing to a public TIL repo
+    Feed {
+        /// Output file (defaults to [feed] path, or feed.xml in the TIL repo)
+        #[arg(short, long)]
+        out: Option<PathBuf>,
 
-        /// Set the notes format (obsidian, logseq, plain)
+        /// How many TILs to include (defaults to [feed] limit, or 20)
+        #[arg(short = 'n', long)]
+        limit: Option<usize>,
+
+        /// Feed title (defaults to [feed] title, or "Today I Learned")
         #[arg(long)]
-        notes_format: Option<String>,
+        title: Option<String>,
 
-        /// Set the archive directory name
+        /// Where the TIL repo can be browsed, to link entries (e.g. https://github.com/me/til/blob/main)
         #[arg(long)]
-        archive_dir: Option<String>,
+        url: Option<String>,
     },
 }
+
+#[derive(Args)]
+pub struct TilArgs {
+    /// Draft TILs for stored sessions that never produced one
+    #[arg(long)]
+    pub from_sessions: bool,
+
+    /// How far back to look for sessions (e.g. 12h, 7d, 2w, or YYYY-MM-DD)
+    #[arg(long, default_value = "7d", requires = "from_sessions")]
+    pub since: String,
+
+    /// Stop drafting once this much has been spent (USD)
+    #[arg(long, requires = "from_sessions")]
+    pub budget: Option<f64>,
+
+    /// Don't ask for confirmation before drafting
+    #[arg(short, long)]
+    pub yes: bool,
+
+    /// Merge the reviewed drafts into this existing TIL instead of saving them as new ones
+    #[arg(long, value_name = "EXISTING_TIL")]
+    pub append_to: Option<PathBuf>,
+
+    #[command(flatten)]
+    pub save: SaveArgs,
+}
+
+/// Flags for commands that save TILs and notes
+#[derive(Args, Clone, Default)]
+pub struct SaveArgs {
+    /// Encrypt saved TILs and notes with age or GPG (see `encrypt_notes` in the config)
+    #[arg(long)]
+    pub encrypt: bool,
+
+    /// Send a desktop notification when a slow response, TIL or note finishes
+    #[arg(long)]
+    pub notify: bool,
+
+    /// Credit saved TILs to this author instead of the config's `author`, e.g. when pairing
+    #[arg(long, value_name = "NAME")]
+    pub author: Option<String>,
+}
+
+#[derive(Args)]
+pub struct ConfigArgs {
+    /// Set the TIL repository path
+    #[arg(long)]
+    pub til_path: Option<PathBuf>,
+
+    /// Set the notes repository path
+    #[arg(long)]
+    pub notes_path: Option<PathBuf>,
+
+    /// Set the notes format (obsidian, logseq, org, plain)
+    #[arg(long)]
+    pub notes_format: Option<String>,
+
+    /// Set the archive directory name
+    #[arg(long)]
+    pub archive_dir: Option<String>,
+
+    /// Set how TILs are arranged in the archive (flat, by_year, by_year_month)
+    #[arg(long)]
+    pub archive_layout: Option<String>,
+
+    /// Set how filenames are made from titles (snake, kebab, date_prefixed_kebab, preserve)
+    #[arg(long)]
+    pub filename_style: Option<String>,
+
+    /// Set the categories listed first when starting a session, comma-separated (e.g. rust,git)
+    #[arg(long, value_delimiter = ',')]
+    pub favorite_categories: Option<Vec<String>>,
+
+    /// Write generated TILs and notes in this language, e.g. German ("" for English)
+    #[arg(long)]
+    pub language: Option<String>,
+
+    /// Send a desktop notification when a slow response, TIL or note finishes (true/false)
+    #[arg(long)]
+    pub notifications: Option<bool>,
+
+    /// Suggest /til when a session seems to be winding down (true/false)
+    #[arg(long)]
+    pub suggest_til: Option<bool>,
+
+    /// Explain the key concept in your own words before each TIL is generated (true/false)
+    #[arg(long)]
+    pub require_recall: Option<bool>,
+
+    /// Fact-check each generated TIL against the session before saving it (true/false)
+    #[arg(long)]
+    pub verify_tils: Option<bool>,
+
+    /// Minutes of idling at the prompt before a /til reminder (0 disables)
+    #[arg(long)]
+    pub til_reminder_minutes: Option<u32>,
+
+    /// Set who writes your TILs, recorded in their frontmatter for shared repos ("" to unset)
+    #[arg(long)]
+    pub author: Option<String>,
+
+    /// Credit the author after each new README entry, e.g. "by @jane" (true/false)
+    #[arg(long)]
+    pub readme_authors: Option<bool>,
+
+    /// Automatically link mentions of other TIL titles (true/false)
+    #[arg(long)]
+    pub auto_link: Option<bool>,
+
+    /// Suggest follow-up questions after each response (true/false)
+    #[arg(long)]
+    pub suggest_followups: Option<bool>,
+
+    /// Save a log note of every session to the vault's sessions/ folder (true/false)
+    #[arg(long)]
+    pub autosave_sessions: Option<bool>,
+
+    /// List existing TILs in the category when starting a deep dive (true/false)
+    #[arg(long)]
+    pub inject_archive_context: Option<bool>,
+
+    /// Days to replay a link's cached analysis instead of asking again (0 disables)
+    #[arg(long)]
+    pub link_cache_days: Option<u32>,
+
+    /// Estimated tokens of the session given to TIL and note generation
+    #[arg(long)]
+    pub til_context_tokens: Option<usize>,
+
+    /// Tokens of conversation after which it's summarized into a fresh backend session (0 disables)
+    #[arg(long)]
+    pub context_limit_tokens: Option<usize>,
+
+    /// Only allow WebFetch while analyzing links (true/false)
+    #[arg(long)]
+    pub link_webfetch_only: Option<bool>,
+
+    /// Set the shell history file shell-recap reads ("" for the login shell's)
+    #[arg(long)]
+    pub shell_history: Option<PathBuf>,
+
+    /// Set the color theme (default, solarized, no-color, high-contrast)
+    #[arg(long)]
+    pub theme: Option<String>,
+
+    /// Use emoji and symbols like ✓ in output (true/false)
+    #[arg(long)]
+    pub emoji: Option<bool>,
+
+    /// Set the local clone of the git repo `holocron sync` keeps state in
+    #[arg(long)]
+    pub sync_repo: Option<PathBuf>,
+}
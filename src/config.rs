@@ -1,10 +1,39 @@
+use crate::theme::Theme;
 use anyhow::{anyhow, Context, Result};
+use chrono::NaiveDate;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 const CONFIG_DIR: &str = "holocron";
-const CONFIG_FILE: &str = "config.toml";
+pub const CONFIG_FILE: &str = "config.toml";
+pub const CATEGORY_MEMORY_FILE: &str = "categories.toml";
+const TRANSCRIPTS_DIR: &str = "transcripts";
+pub const SESSIONS_DIR: &str = "sessions";
+const DRAFTS_DIR: &str = "drafts";
+pub const QUEUE_FILE: &str = "queue.toml";
+const AUDIO_TRANSCRIPTS_DIR: &str = "audio";
+const LINK_CACHE_DIR: &str = "link_cache";
+const SYNC_BASE_FILE: &str = "sync_base";
+pub const TIL_OF_THE_DAY_FILE: &str = "til_of_the_day.toml";
+const LOG_FILE: &str = "holocron.log";
+/// Overrides where holocron keeps its config and state, e.g. to isolate tests
+const CONFIG_DIR_ENV: &str = "HOLOCRON_CONFIG_DIR";
+
+/// State `holocron sync` shares between devices, relative to the config dir. Caches stay per-device,
+/// and so does the config, which holds tokens, passwords and this device's paths.
+pub const SYNCED_STATE: &[&str] = &[
+    CATEGORY_MEMORY_FILE,
+    QUEUE_FILE,
+    SESSIONS_DIR,
+    DRAFTS_DIR,
+    TRANSCRIPTS_DIR,
+    TIL_OF_THE_DAY_FILE,
+];
+
+/// Repo-local settings file at the root of a TIL repository
+pub const REPO_CONFIG_FILE: &str = ".holocron.toml";
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
@@ -15,28 +44,572 @@ pub struct Config {
     #[serde(default = "default_archive_dir")]
     pub archive_dir: String,
 
+    /// How TILs are arranged below the archive directory
+    #[serde(default)]
+    pub archive_layout: ArchiveLayout,
+
+    /// How TIL and note filenames are made from their titles
+    #[serde(default)]
+    pub filename_style: FilenameStyle,
+
+    /// Categories offered when saving a TIL (empty: any category)
+    #[serde(default)]
+    pub categories: Vec<String>,
+
+    /// Categories listed first when starting a session, before the archive's own
+    #[serde(default)]
+    pub favorite_categories: Vec<String>,
+
+    /// Markdown skeleton for TILs in categories without their own template
+    #[serde(default)]
+    pub til_template: Option<String>,
+
     /// Optional path to notes/knowledge base repository
     pub notes_path: Option<PathBuf>,
 
     /// Notes format: obsidian, logseq, or plain
     #[serde(default = "default_notes_format")]
     pub notes_format: NotesFormat,
+
+    /// Natural language TILs and notes are written in, e.g. "German" (unset: English)
+    #[serde(default)]
+    pub language: Option<String>,
+
+    /// Link mentions of other TIL titles when saving a new TIL
+    #[serde(default = "default_true")]
+    pub auto_link: bool,
+
+    /// Who writes the TILs, for shared team repos: recorded as `author` in each TIL's frontmatter
+    #[serde(default)]
+    pub author: Option<String>,
+
+    /// Credit the author after each new README entry, e.g. "by @jane"
+    #[serde(default)]
+    pub readme_authors: bool,
+
+    /// Suggest numbered follow-up questions after each response
+    #[serde(default)]
+    pub suggest_followups: bool,
+
+    /// Write a dated log note to the vault's `sessions/` folder whenever a session ends
+    #[serde(default)]
+    pub autosave_sessions: bool,
+
+    /// Tell deep dives which TILs already exist in their category, so they focus on gaps
+    #[serde(default)]
+    pub inject_archive_context: bool,
+
+    /// Replay a link's cached opening analysis for this many days (0 disables the cache)
+    #[serde(default = "default_link_cache_days")]
+    pub link_cache_days: u32,
+
+    /// Estimated tokens of conversation given to TIL and note generation; older exchanges are condensed to fit
+    #[serde(default = "default_til_context_tokens")]
+    pub til_context_tokens: usize,
+
+    /// Summarize the conversation and continue in a fresh backend session once it holds this many tokens (0 disables)
+    #[serde(default = "default_context_limit_tokens")]
+    pub context_limit_tokens: usize,
+
+    /// Send a desktop notification when a slow response, TIL or note finishes
+    #[serde(default)]
+    pub notifications: bool,
+
+    /// Suggest /til when a session seems to be winding down ("thanks", a run of short follow-ups)
+    #[serde(default = "default_true")]
+    pub suggest_til: bool,
+
+    /// Ask for the key concept in my own words (as with /recall) before generating a TIL
+    #[serde(default)]
+    pub require_recall: bool,
+
+    /// Fact-check each generated TIL against the session (and a link's source) before saving
+    #[serde(default)]
+    pub verify_tils: bool,
+
+    /// Remind about /til after the prompt has sat idle this long in a session with no TIL (0 disables)
+    #[serde(default = "default_til_reminder_minutes")]
+    pub til_reminder_minutes: u32,
+
+    /// Only let the backend use WebFetch while analyzing a link, so a page can't talk it into other tools
+    #[serde(default)]
+    pub link_webfetch_only: bool,
+
+    /// Shell history file `holocron shell-recap` reads (unset: the login shell's)
+    #[serde(default)]
+    pub shell_history: Option<PathBuf>,
+
+    /// Colors for terminal output: default, solarized, no-color or high-contrast
+    #[serde(default)]
+    pub theme: Theme,
+
+    /// Use emoji and symbols like ✓ in output (`--no-emoji` turns them off for one run)
+    #[serde(default = "default_true")]
+    pub emoji: bool,
+
+    /// Encrypt saved TILs and notes (stored as `.md.age` / `.md.gpg`)
+    #[serde(default)]
+    pub encrypt_notes: bool,
+
+    /// How saved TILs and notes are encrypted when `encrypt_notes` is on
+    #[serde(default)]
+    pub encryption: EncryptionConfig,
+
+    /// Credentials for importing read-it-later articles into the queue
+    #[serde(default)]
+    pub import: ImportConfig,
+
+    /// Settings for `/share` and `holocron share`
+    #[serde(default)]
+    pub share: ShareConfig,
+
+    /// Settings for `holocron serve --capture`
+    #[serde(default)]
+    pub capture: CaptureConfig,
+
+    /// Where `/publish` sends TILs
+    #[serde(default)]
+    pub publish: PublishConfig,
+
+    /// The Atom feed of recent TILs written by `holocron export feed`
+    #[serde(default)]
+    pub feed: FeedConfig,
+
+    /// Where `holocron digest --email` sends the digest
+    #[serde(default)]
+    pub email: EmailConfig,
+
+    /// Where saved TILs and notes are stored, if not the archive and notes directory
+    #[serde(default)]
+    pub storage: StorageConfig,
+
+    /// How `holocron audio` turns recordings into text
+    #[serde(default)]
+    pub transcription: TranscriptionConfig,
+
+    /// Vault subfolders for notes by tag, e.g. `database = "Engineering/Databases"`; a note's first mapped tag wins
+    #[serde(default)]
+    pub note_folders: BTreeMap<String, String>,
+
+    /// REPL commands that expand to a message, e.g. `why = "Explain why this design was chosen: {input}"`
+    #[serde(default)]
+    pub aliases: BTreeMap<String, String>,
+
+    /// Values added to the frontmatter of every TIL and note, e.g. `author = "Jo"`, and filled in for `{author}`
+    /// in templates, style hints and aliases
+    #[serde(default)]
+    pub variables: BTreeMap<String, String>,
+
+    /// Shell commands run at points in holocron's lifecycle
+    #[serde(default)]
+    pub hooks: HooksConfig,
+
+    /// Where `holocron sync` keeps state shared between devices
+    #[serde(default)]
+    pub sync: SyncConfig,
+
+    /// Preview changes without writing (set from the --dry-run flag, never saved)
+    #[serde(skip)]
+    pub dry_run: bool,
+
+    /// Existing TIL that /til grows instead of writing a new one (set from --append-to, never saved)
+    #[serde(skip)]
+    pub append_to: Option<PathBuf>,
+}
+
+/// Settings kept in a TIL repo's own `.holocron.toml`, shared by everyone using the repo.
+/// Each one set here overrides the global config.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RepoConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub archive_dir: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub archive_layout: Option<ArchiveLayout>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filename_style: Option<FilenameStyle>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub categories: Option<Vec<String>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub til_template: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub notes_format: Option<NotesFormat>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub auto_link: Option<bool>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub readme_authors: Option<bool>,
+
+    /// Merged into the global `[variables]`, e.g. `project = "billing"`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub variables: BTreeMap<String, String>,
+}
+
+impl RepoConfig {
+    /// Load the `.holocron.toml` at the root of `dir`, if it has one
+    pub fn load(dir: &Path) -> Result<Option<Self>> {
+        let path = dir.join(REPO_CONFIG_FILE);
+        if !path.is_file() {
+            return Ok(None);
+        }
+
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read repo config from {:?}", path))?;
+        let config = toml::from_str(&content)
+            .with_context(|| format!("Failed to parse repo config {:?}", path))?;
+
+        Ok(Some(config))
+    }
+
+    /// Override the settings this file sets
+    pub fn apply_to(&self, config: &mut Config) {
+        if let Some(ref dir) = self.archive_dir {
+            config.archive_dir = dir.clone();
+        }
+        if let Some(layout) = self.archive_layout {
+            config.archive_layout = layout;
+        }
+        if let Some(style) = self.filename_style {
+            config.filename_style = style;
+        }
+        if let Some(ref categories) = self.categories {
+            config.categories = categories.iter().map(|c| c.to_lowercase()).collect();
+        }
+        if let Some(ref template) = self.til_template {
+            config.til_template = Some(template.clone());
+        }
+        if let Some(ref format) = self.notes_format {
+            config.notes_format = format.clone();
+        }
+        if let Some(auto_link) = self.auto_link {
+            config.auto_link = auto_link;
+        }
+        if let Some(readme_authors) = self.readme_authors {
+            config.readme_authors = readme_authors;
+        }
+        config.variables.extend(self.variables.clone());
+    }
+
+    pub fn save(&self, repo_root: &Path) -> Result<()> {
+        let path = repo_root.join(REPO_CONFIG_FILE);
+        let content = toml::to_string_pretty(self).with_context(|| "Failed to serialize repo config")?;
+        fs::write(&path, content).with_context(|| format!("Failed to write repo config to {:?}", path))
+    }
+}
+
+/// Shell commands run via `sh -c`, with session details in `HOLOCRON_*` env vars
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct HooksConfig {
+    /// Run when a learning session ends
+    pub on_session_end: Option<String>,
+
+    /// Run in order after each TIL or note is saved, with `{path}` replaced by where it went
+    #[serde(default)]
+    pub post_save: Vec<String>,
+}
+
+/// A git repo holding holocron's state, for `holocron sync`
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct SyncConfig {
+    /// Local clone of the state repo; its upstream is pulled from and pushed to
+    pub repo: Option<PathBuf>,
+}
+
+/// Where new TILs go below the archive directory
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ArchiveLayout {
+    /// `archive/rust/…`
+    #[default]
+    Flat,
+    /// `archive/rust/2024/…`
+    ByYear,
+    /// `archive/2024/06/rust/…`
+    ByYearMonth,
+}
+
+impl ArchiveLayout {
+    pub fn parse(layout: &str) -> Result<Self> {
+        match layout.to_lowercase().replace('-', "_").as_str() {
+            "flat" => Ok(ArchiveLayout::Flat),
+            "by_year" => Ok(ArchiveLayout::ByYear),
+            "by_year_month" => Ok(ArchiveLayout::ByYearMonth),
+            _ => Err(anyhow!("Invalid archive layout. Use: flat, by_year, or by_year_month")),
+        }
+    }
+
+    /// Directory inside the archive for a TIL in `category` written on `date`
+    pub fn entry_dir(&self, category: &str, date: NaiveDate) -> String {
+        let category = category.to_lowercase();
+        match self {
+            ArchiveLayout::Flat => category,
+            ArchiveLayout::ByYear => format!("{}/{}", category, date.format("%Y")),
+            ArchiveLayout::ByYearMonth => format!("{}/{}", date.format("%Y/%m"), category),
+        }
+    }
+}
+
+impl std::fmt::Display for ArchiveLayout {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ArchiveLayout::Flat => write!(f, "flat"),
+            ArchiveLayout::ByYear => write!(f, "by_year"),
+            ArchiveLayout::ByYearMonth => write!(f, "by_year_month"),
+        }
+    }
+}
+
+/// How a title becomes a filename
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum FilenameStyle {
+    /// `how_to_use_onto.md`
+    #[default]
+    Snake,
+    /// `how-to-use-onto.md`
+    Kebab,
+    /// `2024-06-03-how-to-use-onto.md`
+    DatePrefixedKebab,
+    /// `How_to_Use_onto.md`: the title's case is kept
+    Preserve,
+}
+
+impl FilenameStyle {
+    pub fn parse(style: &str) -> Result<Self> {
+        match style.to_lowercase().replace('-', "_").as_str() {
+            "snake" => Ok(FilenameStyle::Snake),
+            "kebab" => Ok(FilenameStyle::Kebab),
+            "date_prefixed_kebab" => Ok(FilenameStyle::DatePrefixedKebab),
+            "preserve" => Ok(FilenameStyle::Preserve),
+            _ => Err(anyhow!(
+                "Invalid filename style. Use: snake, kebab, date_prefixed_kebab, or preserve"
+            )),
+        }
+    }
+
+    /// The markdown filename for a TIL or note titled `title`, written on `date`
+    pub fn filename(&self, title: &str, date: NaiveDate) -> String {
+        let stem = match self {
+            FilenameStyle::Snake => slug(&title.to_lowercase(), '_'),
+            FilenameStyle::Kebab => slug(&title.to_lowercase(), '-'),
+            FilenameStyle::DatePrefixedKebab => {
+                format!("{}-{}", date.format("%Y-%m-%d"), slug(&title.to_lowercase(), '-'))
+            }
+            FilenameStyle::Preserve => slug(title, '_'),
+        };
+        format!("{}.md", stem)
+    }
+}
+
+impl std::fmt::Display for FilenameStyle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FilenameStyle::Snake => write!(f, "snake"),
+            FilenameStyle::Kebab => write!(f, "kebab"),
+            FilenameStyle::DatePrefixedKebab => write!(f, "date_prefixed_kebab"),
+            FilenameStyle::Preserve => write!(f, "preserve"),
+        }
+    }
+}
+
+/// Alphanumeric runs of `text` joined by `separator`
+fn slug(text: &str, separator: char) -> String {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join(&separator.to_string())
 }
 
+/// API credentials for `holocron queue import`
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct ImportConfig {
+    /// Readwise access token (readwise.io/access_token)
+    pub readwise_token: Option<String>,
+
+    /// Pocket app consumer key
+    pub pocket_consumer_key: Option<String>,
+
+    /// Pocket user access token
+    pub pocket_access_token: Option<String>,
+
+    /// Private RSS feed URL of your Instapaper unread list
+    pub instapaper_feed: Option<String>,
+}
+
+/// Credentials for sharing sessions
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct ShareConfig {
+    /// GitHub token with the `gist` scope, for sharing as a gist
+    pub github_token: Option<String>,
+}
+
+/// The capture endpoint run by `holocron serve --capture`
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct CaptureConfig {
+    /// Token every capture request must send as `Authorization: Bearer <token>`; generated on first run
+    pub token: Option<String>,
+}
+
+/// Where `holocron digest --email` sends the digest: through a sendmail-style command if set,
+/// otherwise over SMTP
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct EmailConfig {
+    pub to: Option<String>,
+    /// Sender address (default: the `to` address)
+    pub from: Option<String>,
+    /// Run via `sh -c` with the message on stdin, e.g. `sendmail -t` or `msmtp -t`
+    pub command: Option<String>,
+    /// e.g. `smtps://smtp.fastmail.com:465`, or `smtp://localhost` for a local relay
+    pub smtp_url: Option<String>,
+    /// SMTP login; setting one also requires TLS
+    pub username: Option<String>,
+    pub password: Option<String>,
+}
+
+/// Storage for each kind of entry; unset ones are written to disk as usual
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct StorageConfig {
+    pub til: Option<TargetConfig>,
+    pub notes: Option<TargetConfig>,
+}
+
+/// A place to store entries, chosen with `type` in a `[storage.til]` or `[storage.notes]` table
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TargetConfig {
+    /// Another local directory
+    Filesystem { path: PathBuf },
+    /// An S3 bucket, via the `aws` CLI
+    S3 {
+        bucket: String,
+        #[serde(default)]
+        prefix: String,
+    },
+    /// A WebDAV share
+    Webdav {
+        url: String,
+        username: Option<String>,
+        password: Option<String>,
+    },
+    /// A SQLite database, via the `sqlite3` CLI
+    Sqlite { database: PathBuf },
+    /// A JSON POST to your own service
+    Http { url: String, token: Option<String> },
+}
+
+/// Where `/publish` puts TILs for the world to read
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct PublishConfig {
+    pub target: Option<PublishTarget>,
+    /// For `pages`, the folder GitHub Pages serves (default: `docs` in the TIL repo).
+    /// For `repo`, a local clone of the public repo, committed and pushed on each publish.
+    pub path: Option<PathBuf>,
+}
+
+/// An Atom feed of the most recent TILs, for subscribing to a public TIL repo
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct FeedConfig {
+    /// Regenerate the feed after every saved TIL
+    #[serde(default)]
+    pub auto: bool,
+    /// Where the feed is written (default: `feed.xml` in the TIL repo)
+    pub path: Option<PathBuf>,
+    pub title: Option<String>,
+    /// Where the TIL repo can be browsed, used to link entries, e.g. `https://github.com/me/til/blob/main`
+    pub url: Option<String>,
+    /// How many TILs the feed holds (default: 20)
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PublishTarget {
+    /// A public gist, using the `[share]` GitHub token
+    Gist,
+    /// A folder served by GitHub Pages
+    Pages,
+    /// A separate public repository
+    Repo,
+}
+
+/// Speech-to-text for `holocron audio`: a local command if set, otherwise a Whisper-compatible API
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct TranscriptionConfig {
+    /// Run via `sh -c` with the recording in `$HOLOCRON_AUDIO_FILE`; prints the transcript,
+    /// e.g. `whisper-cli -m ~/models/ggml-base.en.bin -nt -f "$HOLOCRON_AUDIO_FILE"`
+    pub command: Option<String>,
+    /// Transcription endpoint (default: OpenAI's `/v1/audio/transcriptions`)
+    pub api_url: Option<String>,
+    pub api_key: Option<String>,
+    /// Model name sent to the API (default: whisper-1)
+    pub model: Option<String>,
+}
+
+/// Encryption settings for sensitive TILs and notes
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct EncryptionConfig {
+    /// Tool used to encrypt: age or gpg
+    #[serde(default)]
+    pub tool: EncryptionTool,
+
+    /// age public key or GPG key id to encrypt to (GPG defaults to your own key)
+    pub recipient: Option<String>,
+
+    /// age identity file used to decrypt
+    pub identity: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum EncryptionTool {
+    #[default]
+    Age,
+    Gpg,
+}
+
+impl std::fmt::Display for EncryptionTool {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EncryptionTool::Age => write!(f, "age"),
+            EncryptionTool::Gpg => write!(f, "gpg"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
 #[serde(rename_all = "lowercase")]
 pub enum NotesFormat {
     #[default]
     Obsidian,
     Logseq,
+    /// `.org` files for org-roam
+    Org,
     Plain,
 }
 
+impl NotesFormat {
+    pub fn parse(format: &str) -> Result<Self> {
+        match format.to_lowercase().as_str() {
+            "obsidian" => Ok(NotesFormat::Obsidian),
+            "logseq" => Ok(NotesFormat::Logseq),
+            "org" => Ok(NotesFormat::Org),
+            "plain" => Ok(NotesFormat::Plain),
+            _ => Err(anyhow!("Invalid notes format. Use: obsidian, logseq, org, or plain")),
+        }
+    }
+}
+
 impl std::fmt::Display for NotesFormat {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             NotesFormat::Obsidian => write!(f, "obsidian"),
             NotesFormat::Logseq => write!(f, "logseq"),
+            NotesFormat::Org => write!(f, "org"),
             NotesFormat::Plain => write!(f, "plain"),
         }
     }
@@ -50,10 +623,59 @@ fn default_notes_format() -> NotesFormat {
     NotesFormat::Obsidian
 }
 
+fn default_link_cache_days() -> u32 {
+    7
+}
+
+fn default_til_context_tokens() -> usize {
+    crate::session::DEFAULT_CONTEXT_TOKENS
+}
+
+fn default_til_reminder_minutes() -> u32 {
+    10
+}
+
+fn default_context_limit_tokens() -> usize {
+    150_000
+}
+
+fn default_true() -> bool {
+    true
+}
+
 #[allow(dead_code)]
 impl Config {
-    /// Load config from the default location
+    /// Load the global config, then apply repo-local `.holocron.toml` overrides from the
+    /// TIL repo root and the current directory (in that order)
     pub fn load() -> Result<Option<Self>> {
+        let Some(mut config) = Self::load_global()? else {
+            return Ok(None);
+        };
+
+        for dir in config.repo_config_dirs() {
+            if let Some(repo_config) = RepoConfig::load(&dir)? {
+                repo_config.apply_to(&mut config);
+            }
+        }
+
+        Ok(Some(config))
+    }
+
+    /// Directories whose `.holocron.toml` overrides this config: the TIL repo root, then the
+    /// current directory unless it's the repo root or inside the archive (where that file is a
+    /// category style)
+    pub fn repo_config_dirs(&self) -> Vec<PathBuf> {
+        let mut dirs = vec![self.til_path.clone()];
+        if let Ok(cwd) = std::env::current_dir() {
+            if cwd != self.til_path && !cwd.starts_with(self.archive_path()) {
+                dirs.push(cwd);
+            }
+        }
+        dirs
+    }
+
+    /// Load config from the default location, without repo-local overrides
+    pub fn load_global() -> Result<Option<Self>> {
         let config_path = Self::config_path()?;
 
         if !config_path.exists() {
@@ -79,21 +701,81 @@ impl Config {
                 .with_context(|| format!("Failed to create config directory {:?}", parent))?;
         }
 
-        let content = toml::to_string_pretty(self)
-            .with_context(|| "Failed to serialize config")?;
-
-        fs::write(&config_path, content)
+        fs::write(&config_path, self.to_toml()?)
             .with_context(|| format!("Failed to write config to {:?}", config_path))?;
 
         Ok(())
     }
 
+    /// The config as it's written to the config file
+    pub fn to_toml(&self) -> Result<String> {
+        toml::to_string_pretty(self).with_context(|| "Failed to serialize config")
+    }
+
     /// Get the config file path
     pub fn config_path() -> Result<PathBuf> {
+        Ok(Self::config_dir()?.join(CONFIG_FILE))
+    }
+
+    /// Get the holocron config directory (also holds holocron's own state files)
+    pub fn config_dir() -> Result<PathBuf> {
+        if let Some(dir) = std::env::var_os(CONFIG_DIR_ENV) {
+            return Ok(PathBuf::from(dir));
+        }
         let config_dir = dirs::config_dir()
             .ok_or_else(|| anyhow!("Could not determine config directory"))?;
 
-        Ok(config_dir.join(CONFIG_DIR).join(CONFIG_FILE))
+        Ok(config_dir.join(CONFIG_DIR))
+    }
+
+    /// Get the path of the remembered topic → category mappings
+    pub fn category_memory_path() -> Result<PathBuf> {
+        Ok(Self::config_dir()?.join(CATEGORY_MEMORY_FILE))
+    }
+
+    /// Get the directory where session transcripts are saved
+    pub fn transcripts_dir() -> Result<PathBuf> {
+        Ok(Self::config_dir()?.join(TRANSCRIPTS_DIR))
+    }
+
+    /// Get the directory where learning sessions are stored
+    pub fn sessions_dir() -> Result<PathBuf> {
+        Ok(Self::config_dir()?.join(SESSIONS_DIR))
+    }
+
+    /// Get the directory where drafted TILs wait for review
+    pub fn drafts_dir() -> Result<PathBuf> {
+        Ok(Self::config_dir()?.join(DRAFTS_DIR))
+    }
+
+    /// Get the path of the learning queue
+    pub fn queue_path() -> Result<PathBuf> {
+        Ok(Self::config_dir()?.join(QUEUE_FILE))
+    }
+
+    /// Get the file recording the state-repo commit this device last synced with
+    pub fn sync_base_path() -> Result<PathBuf> {
+        Ok(Self::config_dir()?.join(SYNC_BASE_FILE))
+    }
+
+    /// Get the directory where transcribed recordings are cached
+    pub fn audio_transcripts_dir() -> Result<PathBuf> {
+        Ok(Self::config_dir()?.join(AUDIO_TRANSCRIPTS_DIR))
+    }
+
+    /// Get the directory where opening link analyses are cached
+    pub fn link_cache_dir() -> Result<PathBuf> {
+        Ok(Self::config_dir()?.join(LINK_CACHE_DIR))
+    }
+
+    /// Get the file recording which TILs `til-of-the-day` has shown, and when
+    pub fn til_of_the_day_path() -> Result<PathBuf> {
+        Ok(Self::config_dir()?.join(TIL_OF_THE_DAY_FILE))
+    }
+
+    /// Get the debug log written with `--verbose`
+    pub fn log_path() -> Result<PathBuf> {
+        Ok(Self::config_dir()?.join(LOG_FILE))
     }
 
     /// Check if config exists
@@ -106,8 +788,49 @@ impl Config {
         Self {
             til_path,
             archive_dir: default_archive_dir(),
+            archive_layout: ArchiveLayout::default(),
+            filename_style: FilenameStyle::default(),
+            categories: Vec::new(),
+            favorite_categories: Vec::new(),
+            til_template: None,
             notes_path: None,
             notes_format: default_notes_format(),
+            language: None,
+            auto_link: true,
+            author: None,
+            readme_authors: false,
+            suggest_followups: false,
+            autosave_sessions: false,
+            inject_archive_context: false,
+            link_cache_days: default_link_cache_days(),
+            til_context_tokens: default_til_context_tokens(),
+            context_limit_tokens: default_context_limit_tokens(),
+            notifications: false,
+            suggest_til: true,
+            require_recall: false,
+            verify_tils: false,
+            til_reminder_minutes: default_til_reminder_minutes(),
+            link_webfetch_only: false,
+            shell_history: None,
+            theme: Theme::default(),
+            emoji: true,
+            encrypt_notes: false,
+            encryption: EncryptionConfig::default(),
+            import: ImportConfig::default(),
+            share: ShareConfig::default(),
+            capture: CaptureConfig::default(),
+            publish: PublishConfig::default(),
+            feed: FeedConfig::default(),
+            email: EmailConfig::default(),
+            storage: StorageConfig::default(),
+            transcription: TranscriptionConfig::default(),
+            note_folders: BTreeMap::new(),
+            aliases: BTreeMap::new(),
+            variables: BTreeMap::new(),
+            hooks: HooksConfig::default(),
+            sync: SyncConfig::default(),
+            dry_run: false,
+            append_to: None,
         }
     }
 
@@ -136,8 +859,85 @@ mod tests {
         let config = Config {
             til_path: PathBuf::from("/path/to/til"),
             archive_dir: "archive".to_string(),
+            archive_layout: ArchiveLayout::ByYearMonth,
+            filename_style: FilenameStyle::Kebab,
+            categories: Vec::new(),
+            favorite_categories: vec!["rust".to_string(), "git".to_string()],
+            til_template: None,
             notes_path: Some(PathBuf::from("/path/to/notes")),
             notes_format: NotesFormat::Obsidian,
+            language: Some("German".to_string()),
+            auto_link: true,
+            author: Some("jane".to_string()),
+            readme_authors: true,
+            suggest_followups: false,
+            autosave_sessions: true,
+            inject_archive_context: true,
+            link_cache_days: 0,
+            til_context_tokens: 12000,
+            context_limit_tokens: 80_000,
+            notifications: true,
+            suggest_til: false,
+            require_recall: true,
+            verify_tils: true,
+            til_reminder_minutes: 25,
+            link_webfetch_only: true,
+            shell_history: Some(PathBuf::from("/home/me/.zsh_history")),
+            theme: Theme::Solarized,
+            emoji: false,
+            encrypt_notes: true,
+            encryption: EncryptionConfig {
+                tool: EncryptionTool::Gpg,
+                recipient: Some("me@example.com".to_string()),
+                identity: None,
+            },
+            import: ImportConfig {
+                readwise_token: Some("rw-token".to_string()),
+                ..ImportConfig::default()
+            },
+            share: ShareConfig {
+                github_token: Some("gh-token".to_string()),
+            },
+            capture: CaptureConfig {
+                token: Some("capture-token".to_string()),
+            },
+            publish: PublishConfig {
+                target: Some(PublishTarget::Repo),
+                path: Some(PathBuf::from("/path/to/public-til")),
+            },
+            feed: FeedConfig {
+                auto: true,
+                path: None,
+                title: Some("Jo's TIL".to_string()),
+                url: Some("https://github.com/jo/til/blob/main".to_string()),
+                limit: Some(50),
+            },
+            email: EmailConfig {
+                to: Some("jo@example.com".to_string()),
+                command: Some("sendmail -t".to_string()),
+                ..EmailConfig::default()
+            },
+            storage: StorageConfig {
+                til: None,
+                notes: Some(TargetConfig::S3 {
+                    bucket: "my-notes".to_string(),
+                    prefix: "holocron".to_string(),
+                }),
+            },
+            transcription: TranscriptionConfig {
+                command: Some("whisper-cli -f \"$HOLOCRON_AUDIO_FILE\"".to_string()),
+                ..TranscriptionConfig::default()
+            },
+            note_folders: BTreeMap::from([("database".to_string(), "Engineering/Databases".to_string())]),
+            aliases: BTreeMap::from([("tldr".to_string(), "Summarize that in one line.".to_string())]),
+            variables: BTreeMap::from([("author".to_string(), "Jo".to_string())]),
+            hooks: HooksConfig {
+                on_session_end: Some("notify-send done".to_string()),
+                post_save: vec!["./scripts/publish.sh {path}".to_string()],
+            },
+            sync: SyncConfig::default(),
+            dry_run: false,
+            append_to: None,
         };
 
         let toml_str = toml::to_string_pretty(&config).expect("serialize");
@@ -145,6 +945,28 @@ mod tests {
 
         assert_eq!(parsed.til_path, config.til_path);
         assert_eq!(parsed.archive_dir, config.archive_dir);
+        assert_eq!(parsed.archive_layout, ArchiveLayout::ByYearMonth);
+        assert_eq!(parsed.filename_style, FilenameStyle::Kebab);
+        assert_eq!(parsed.favorite_categories, vec!["rust", "git"]);
+        assert!(parsed.notifications);
+        assert!(!parsed.suggest_til);
+        assert_eq!(parsed.til_reminder_minutes, 25);
+        assert_eq!(parsed.language.as_deref(), Some("German"));
+        assert_eq!(parsed.publish.target, Some(PublishTarget::Repo));
+        assert_eq!(parsed.context_limit_tokens, 80_000);
+        assert_eq!(parsed.storage.notes, config.storage.notes);
+        assert!(parsed.storage.til.is_none());
+        assert!(parsed.autosave_sessions);
+        assert!(parsed.link_webfetch_only);
+        assert!(parsed.encrypt_notes);
+        assert_eq!(parsed.encryption.tool, EncryptionTool::Gpg);
+        assert_eq!(parsed.encryption.recipient.as_deref(), Some("me@example.com"));
+        assert_eq!(parsed.import.readwise_token.as_deref(), Some("rw-token"));
+        assert_eq!(parsed.share.github_token.as_deref(), Some("gh-token"));
+        assert!(parsed.transcription.command.is_some());
+        assert_eq!(parsed.hooks.on_session_end.as_deref(), Some("notify-send done"));
+        assert_eq!(parsed.aliases, config.aliases);
+        assert_eq!(parsed.note_folders, config.note_folders);
     }
 
     #[test]
@@ -153,12 +975,89 @@ mod tests {
         let config: Config = toml::from_str(toml_str).expect("deserialize");
 
         assert_eq!(config.archive_dir, "archive");
+        assert_eq!(config.archive_layout, ArchiveLayout::Flat);
+        assert!(config.auto_link);
+        assert!(!config.autosave_sessions);
+        assert_eq!(config.link_cache_days, 7);
+        assert_eq!(config.til_context_tokens, 6000);
+        assert_eq!(config.context_limit_tokens, 150_000);
+        assert!(config.suggest_til);
+        assert_eq!(config.til_reminder_minutes, 10);
+        assert!(!config.encrypt_notes);
+        assert_eq!(config.encryption.tool, EncryptionTool::Age);
+        assert!(config.hooks.on_session_end.is_none());
+        assert!(!config.feed.auto);
+    }
+
+    #[test]
+    fn test_archive_layout_entry_dir() -> Result<()> {
+        let date = NaiveDate::from_ymd_opt(2024, 6, 1).ok_or_else(|| anyhow!("bad date"))?;
+
+        assert_eq!(ArchiveLayout::Flat.entry_dir("Rust", date), "rust");
+        assert_eq!(ArchiveLayout::ByYear.entry_dir("rust", date), "rust/2024");
+        assert_eq!(ArchiveLayout::ByYearMonth.entry_dir("rust", date), "2024/06/rust");
+        assert_eq!(ArchiveLayout::parse("by-year-month")?, ArchiveLayout::ByYearMonth);
+        assert!(ArchiveLayout::parse("nested").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_filename_style() -> Result<()> {
+        let date = NaiveDate::from_ymd_opt(2024, 6, 3).ok_or_else(|| anyhow!("bad date"))?;
+        let title = "How to Use --onto";
+
+        assert_eq!(FilenameStyle::Snake.filename(title, date), "how_to_use_onto.md");
+        assert_eq!(FilenameStyle::Snake.filename("Git: The Basics", date), "git_the_basics.md");
+        assert_eq!(FilenameStyle::Snake.filename("What's New?", date), "what_s_new.md");
+        assert_eq!(FilenameStyle::Snake.filename("Test & More", date), "test_more.md");
+        assert_eq!(FilenameStyle::Kebab.filename(title, date), "how-to-use-onto.md");
+        assert_eq!(FilenameStyle::DatePrefixedKebab.filename(title, date), "2024-06-03-how-to-use-onto.md");
+        assert_eq!(FilenameStyle::Preserve.filename(title, date), "How_to_Use_onto.md");
+        assert_eq!(FilenameStyle::parse("date-prefixed-kebab")?, FilenameStyle::DatePrefixedKebab);
+        assert!(FilenameStyle::parse("camel").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_repo_config_overrides() -> Result<()> {
+        let temp_dir = tempfile::TempDir::new()?;
+        fs::write(
+            temp_dir.path().join(REPO_CONFIG_FILE),
+            "archive_dir = \"til\"\narchive_layout = \"by_year\"\ncategories = [\"Rust\", \"git\"]\nauto_link = false\n\
+             readme_authors = true\n[variables]\nproject = \"billing\"\n",
+        )?;
+        let mut config = Config::new(temp_dir.path().to_path_buf());
+        config.til_template = Some("# Title".to_string());
+        config.variables = BTreeMap::from([
+            ("author".to_string(), "Jo".to_string()),
+            ("project".to_string(), "home".to_string()),
+        ]);
+
+        RepoConfig::load(temp_dir.path())?
+            .ok_or_else(|| anyhow!("repo config not found"))?
+            .apply_to(&mut config);
+
+        assert_eq!(config.archive_dir, "til");
+        assert_eq!(config.archive_layout, ArchiveLayout::ByYear);
+        assert_eq!(config.categories, vec!["rust", "git"]);
+        assert!(!config.auto_link);
+        assert!(config.readme_authors);
+        assert_eq!(config.til_template.as_deref(), Some("# Title"));
+        assert_eq!(config.notes_format, NotesFormat::Obsidian);
+        assert_eq!(config.variables["author"], "Jo");
+        assert_eq!(config.variables["project"], "billing");
+        assert!(RepoConfig::load(&temp_dir.path().join("missing"))?.is_none());
+
+        Ok(())
     }
 
     #[test]
     fn test_notes_format_display() {
         assert_eq!(format!("{}", NotesFormat::Obsidian), "obsidian");
         assert_eq!(format!("{}", NotesFormat::Logseq), "logseq");
+        assert_eq!(format!("{}", NotesFormat::Org), "org");
         assert_eq!(format!("{}", NotesFormat::Plain), "plain");
     }
 
@@ -183,8 +1082,49 @@ mod tests {
         let config = Config {
             til_path: PathBuf::from("/test/til"),
             archive_dir: "entries".to_string(),
+            archive_layout: ArchiveLayout::Flat,
+            filename_style: FilenameStyle::Snake,
+            categories: Vec::new(),
+            favorite_categories: Vec::new(),
+            til_template: None,
             notes_path: None,
             notes_format: NotesFormat::Plain,
+            language: None,
+            auto_link: false,
+            author: None,
+            readme_authors: false,
+            suggest_followups: true,
+            autosave_sessions: false,
+            inject_archive_context: false,
+            link_cache_days: default_link_cache_days(),
+            til_context_tokens: default_til_context_tokens(),
+            context_limit_tokens: default_context_limit_tokens(),
+            notifications: false,
+            suggest_til: true,
+            require_recall: false,
+            verify_tils: false,
+            til_reminder_minutes: default_til_reminder_minutes(),
+            link_webfetch_only: false,
+            shell_history: None,
+            theme: Theme::default(),
+            emoji: true,
+            encrypt_notes: false,
+            encryption: EncryptionConfig::default(),
+            import: ImportConfig::default(),
+            share: ShareConfig::default(),
+            capture: CaptureConfig::default(),
+            publish: PublishConfig::default(),
+            feed: FeedConfig::default(),
+            email: EmailConfig::default(),
+            storage: StorageConfig::default(),
+            transcription: TranscriptionConfig::default(),
+            note_folders: BTreeMap::new(),
+            aliases: BTreeMap::new(),
+            variables: BTreeMap::new(),
+            hooks: HooksConfig::default(),
+            sync: SyncConfig::default(),
+            dry_run: false,
+            append_to: None,
         };
 
         assert_eq!(config.archive_path(), PathBuf::from("/test/til/entries"));
@@ -209,6 +1149,7 @@ mod tests {
         for (format_str, expected) in [
             ("obsidian", NotesFormat::Obsidian),
             ("logseq", NotesFormat::Logseq),
+            ("org", NotesFormat::Org),
             ("plain", NotesFormat::Plain),
         ] {
             let toml_str = format!(
@@ -0,0 +1,128 @@
+use crate::archive::Entry;
+use crate::search;
+use crate::session::Session;
+use anyhow::{Context, Result};
+use std::collections::HashSet;
+use std::fs;
+
+/// Share of topic words two topics need in common to count as the same subject
+const MIN_SIMILARITY: f64 = 0.5;
+
+/// How much of an earlier session is passed along
+const RECAP_TOKENS: usize = 1500;
+
+/// An earlier session or TIL on the topic a new session is about to study
+pub enum Prior {
+    Session(Box<Session>),
+    Til(Entry),
+}
+
+impl Prior {
+    /// Shown when offering to continue, and in the prompt
+    pub fn describe(&self) -> String {
+        match self {
+            Prior::Session(session) => format!(
+                "a session on \"{}\" from {}",
+                session.prompt_topic(),
+                session.started_at.format("%Y-%m-%d")
+            ),
+            Prior::Til(entry) => format!("the TIL \"{}\"", entry.title),
+        }
+    }
+
+    /// What was covered: a condensed transcript, or the TIL itself
+    pub fn context(&self) -> Result<String> {
+        match self {
+            Prior::Session(session) => Ok(session.recap(RECAP_TOKENS)),
+            Prior::Til(entry) => {
+                fs::read_to_string(&entry.path).with_context(|| format!("Failed to read TIL: {:?}", entry.path))
+            }
+        }
+    }
+}
+
+/// The saved session or TIL closest to `topic`, if one is close enough. Sessions are given
+/// oldest first, so on a tie the latest session wins, and a session wins over a TIL.
+pub fn find(topic: &str, sessions: Vec<Session>, entries: Vec<Entry>) -> Option<Prior> {
+    let mut best: Option<(f64, Prior)> = None;
+    let candidates = sessions
+        .into_iter()
+        .rev()
+        .filter(|session| session.exchanges.iter().any(|exchange| !exchange.excluded))
+        .map(|session| (similarity(topic, session.topic()), Prior::Session(Box::new(session))))
+        .chain(entries.into_iter().map(|entry| (similarity(topic, &entry.title), Prior::Til(entry))));
+
+    for (score, prior) in candidates {
+        if score >= MIN_SIMILARITY && best.as_ref().is_none_or(|(best_score, _)| score > *best_score) {
+            best = Some((score, prior));
+        }
+    }
+    best.map(|(_, prior)| prior)
+}
+
+/// Words in common over words in either, ignoring case and stopwords
+fn similarity(a: &str, b: &str) -> f64 {
+    let a: HashSet<String> = search::terms(a).into_iter().collect();
+    let b: HashSet<String> = search::terms(b).into_iter().collect();
+    let union = a.union(&b).count();
+    if union == 0 {
+        return 0.0;
+    }
+    a.intersection(&b).count() as f64 / union as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::archive::EntryKind;
+    use crate::session::LearningMode;
+    use std::path::PathBuf;
+
+    fn session(topic: &str, exchanges: usize) -> Session {
+        let mut session = Session::new(
+            LearningMode::DeepDive {
+                topic: topic.to_string(),
+            },
+            None,
+        );
+        for _ in 0..exchanges {
+            session.add_exchange("question".to_string(), "answer".to_string());
+        }
+        session
+    }
+
+    fn til(title: &str) -> Entry {
+        Entry {
+            kind: EntryKind::Til,
+            title: title.to_string(),
+            path: PathBuf::from("til.md"),
+            category: None,
+            reading_minutes: 1,
+            level: None,
+        }
+    }
+
+    #[test]
+    fn test_similarity() {
+        assert_eq!(similarity("Rust lifetimes", "rust Lifetimes"), 1.0);
+        assert_eq!(similarity("postgres locks", "postgres advisory locks"), 2.0 / 3.0);
+        assert_eq!(similarity("the", "a"), 0.0);
+    }
+
+    #[test]
+    fn test_find_prefers_closest_match() {
+        let sessions = vec![session("postgres advisory locks", 2), session("kubernetes", 3)];
+        let entries = vec![til("Postgres Locks"), til("Rust Lifetimes")];
+
+        let prior = find("postgres locks", sessions, entries);
+        assert!(matches!(prior, Some(Prior::Til(ref entry)) if entry.title == "Postgres Locks"));
+
+        let prior = find("advisory locks in postgres", vec![session("postgres advisory locks", 2)], vec![]);
+        assert!(matches!(prior, Some(Prior::Session(_))));
+    }
+
+    #[test]
+    fn test_find_skips_weak_matches_and_empty_sessions() {
+        assert!(find("rust async runtimes", vec![session("rust async runtimes", 0)], vec![til("Rust Traits")]).is_none());
+    }
+}
@@ -0,0 +1,173 @@
+use crate::config::{EncryptionConfig, EncryptionTool};
+use crate::files;
+use anyhow::{anyhow, Context, Result};
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+impl EncryptionTool {
+    /// Extension appended to encrypted files, e.g. `rebase.md.age`
+    pub fn extension(&self) -> &'static str {
+        match self {
+            EncryptionTool::Age => "age",
+            EncryptionTool::Gpg => "gpg",
+        }
+    }
+
+    /// Which tool a file was encrypted with, judging by its extension
+    pub fn for_path(path: &Path) -> Option<Self> {
+        let name = path.file_name()?.to_string_lossy();
+        [EncryptionTool::Age, EncryptionTool::Gpg]
+            .into_iter()
+            .find(|tool| name.ends_with(&format!(".md.{}", tool.extension())))
+    }
+}
+
+/// Whether the file is an encrypted TIL or note
+pub fn is_encrypted(path: &Path) -> bool {
+    EncryptionTool::for_path(path).is_some()
+}
+
+/// The filename encrypted content is stored under, e.g. `rebase.md` → `rebase.md.age`
+pub fn encrypted_filename(filename: &str, tool: &EncryptionTool) -> String {
+    format!("{}.{}", filename, tool.extension())
+}
+
+/// Encrypt text to ASCII-armored ciphertext with the configured tool
+pub fn encrypt(plaintext: &str, config: &EncryptionConfig) -> Result<Vec<u8>> {
+    let args = encrypt_args(config)?;
+    let program = config.tool.to_string();
+
+    let mut child = Command::new(&program)
+        .args(&args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::inherit())
+        .spawn()
+        .map_err(|e| anyhow!("Failed to run {} (is it installed?): {}", program, e))?;
+
+    child
+        .stdin
+        .take()
+        .ok_or_else(|| anyhow!("Failed to open {} stdin", program))?
+        .write_all(plaintext.as_bytes())
+        .with_context(|| format!("Failed to send content to {}", program))?;
+
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        return Err(anyhow!("{} exited with {}", program, output.status));
+    }
+    Ok(output.stdout)
+}
+
+/// Encrypt `content` and write it to `path`, creating parent directories as needed
+pub fn write_encrypted(path: &Path, content: &str, config: &EncryptionConfig) -> Result<()> {
+    let ciphertext = encrypt(content, config)?;
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).with_context(|| format!("Failed to create directory {:?}", parent))?;
+    }
+    files::write_atomic(path, ciphertext).with_context(|| format!("Failed to write encrypted file: {:?}", path))
+}
+
+/// Decrypt a `.md.age` or `.md.gpg` file back to its markdown
+pub fn decrypt(path: &Path, config: &EncryptionConfig) -> Result<String> {
+    let tool = EncryptionTool::for_path(path)
+        .ok_or_else(|| anyhow!("Not an encrypted file (expected .md.age or .md.gpg): {:?}", path))?;
+    let program = tool.to_string();
+
+    let output = Command::new(&program)
+        .args(decrypt_args(&tool, config)?)
+        .arg(path)
+        .stderr(Stdio::inherit())
+        .output()
+        .map_err(|e| anyhow!("Failed to run {} (is it installed?): {}", program, e))?;
+
+    if !output.status.success() {
+        return Err(anyhow!("{} exited with {}", program, output.status));
+    }
+    String::from_utf8(output.stdout).context("Decrypted content is not valid UTF-8")
+}
+
+fn encrypt_args(config: &EncryptionConfig) -> Result<Vec<String>> {
+    let mut args: Vec<String> = match config.tool {
+        EncryptionTool::Age => vec!["--encrypt".into(), "--armor".into()],
+        EncryptionTool::Gpg => vec!["--encrypt".into(), "--armor".into(), "--batch".into()],
+    };
+
+    match (&config.tool, &config.recipient) {
+        (_, Some(recipient)) => args.extend(["--recipient".to_string(), recipient.clone()]),
+        (EncryptionTool::Gpg, None) => args.push("--default-recipient-self".into()),
+        (EncryptionTool::Age, None) => {
+            return Err(anyhow!(
+                "No age recipient configured. Set `recipient` under [encryption] in the config file"
+            ))
+        }
+    }
+
+    Ok(args)
+}
+
+fn decrypt_args(tool: &EncryptionTool, config: &EncryptionConfig) -> Result<Vec<String>> {
+    match tool {
+        EncryptionTool::Age => {
+            let identity = config.identity.as_ref().ok_or_else(|| {
+                anyhow!("No age identity configured. Set `identity` under [encryption] in the config file")
+            })?;
+            let identity = shellexpand::tilde(identity.to_string_lossy().as_ref()).to_string();
+            Ok(vec!["--decrypt".into(), "--identity".into(), identity])
+        }
+        EncryptionTool::Gpg => Ok(vec!["--decrypt".into(), "--quiet".into(), "--batch".into()]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn age_config(recipient: Option<&str>) -> EncryptionConfig {
+        EncryptionConfig {
+            tool: EncryptionTool::Age,
+            recipient: recipient.map(|r| r.to_string()),
+            identity: Some(PathBuf::from("/keys/age.txt")),
+        }
+    }
+
+    #[test]
+    fn test_encrypted_filename_roundtrip() {
+        let name = encrypted_filename("rebase.md", &EncryptionTool::Age);
+        assert_eq!(name, "rebase.md.age");
+        assert_eq!(EncryptionTool::for_path(Path::new(&name)), Some(EncryptionTool::Age));
+        assert!(is_encrypted(Path::new("notes/secret.md.gpg")));
+        assert!(!is_encrypted(Path::new("notes/plain.md")));
+        assert!(!is_encrypted(Path::new("backup.tar.age")));
+    }
+
+    #[test]
+    fn test_encrypt_args() -> Result<()> {
+        assert_eq!(
+            encrypt_args(&age_config(Some("age1xyz")))?,
+            vec!["--encrypt", "--armor", "--recipient", "age1xyz"]
+        );
+        assert!(encrypt_args(&age_config(None)).is_err());
+
+        let gpg = EncryptionConfig {
+            tool: EncryptionTool::Gpg,
+            ..EncryptionConfig::default()
+        };
+        assert!(encrypt_args(&gpg)?.contains(&"--default-recipient-self".to_string()));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_decrypt_args_use_identity() -> Result<()> {
+        assert_eq!(
+            decrypt_args(&EncryptionTool::Age, &age_config(None))?,
+            vec!["--decrypt", "--identity", "/keys/age.txt"]
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,194 @@
+use crate::theme::Themed;
+use colored::*;
+
+/// A single line-level edit
+#[derive(Debug, Clone, PartialEq)]
+pub enum DiffLine<'a> {
+    Same(&'a str),
+    Added(&'a str),
+    Removed(&'a str),
+}
+
+/// Compute a line diff between two texts using a longest-common-subsequence table
+pub fn diff_lines<'a>(old: &'a str, new: &'a str) -> Vec<DiffLine<'a>> {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+
+    // lcs[i][j] = LCS length of old[i..] and new[j..]
+    let mut lcs = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut result = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() && j < new.len() {
+        if old[i] == new[j] {
+            result.push(DiffLine::Same(old[i]));
+            i += 1;
+            j += 1;
+        } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+            result.push(DiffLine::Removed(old[i]));
+            i += 1;
+        } else {
+            result.push(DiffLine::Added(new[j]));
+            j += 1;
+        }
+    }
+    result.extend(old[i..].iter().map(|line| DiffLine::Removed(line)));
+    result.extend(new[j..].iter().map(|line| DiffLine::Added(line)));
+
+    result
+}
+
+/// Render a unified diff with `context` unchanged lines around each change.
+/// Returns an empty string when the texts are identical.
+pub fn unified_diff(old: &str, new: &str, old_label: &str, new_label: &str, context: usize) -> String {
+    let lines = diff_lines(old, new);
+    let changed: Vec<usize> = lines
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| !matches!(line, DiffLine::Same(_)))
+        .map(|(i, _)| i)
+        .collect();
+
+    if changed.is_empty() {
+        return String::new();
+    }
+
+    let mut output = format!("--- {}\n+++ {}\n", old_label, new_label);
+    for (start, end) in hunk_ranges(&changed, lines.len(), context) {
+        output.push_str(&render_hunk(&lines, start, end));
+    }
+    output
+}
+
+/// Color a unified diff for the terminal: headers bold, hunk markers cyan, additions green
+/// and removals red
+pub fn colorize(diff: &str) -> String {
+    diff.lines()
+        .map(|line| {
+            let colored = if line.starts_with("+++") || line.starts_with("---") {
+                line.bold()
+            } else if line.starts_with("@@") {
+                line.info()
+            } else if line.starts_with('+') {
+                line.success()
+            } else if line.starts_with('-') {
+                line.failure()
+            } else {
+                line.normal()
+            };
+            format!("{}\n", colored)
+        })
+        .collect()
+}
+
+/// Group changed line indices into [start, end) hunks including context
+fn hunk_ranges(changed: &[usize], total: usize, context: usize) -> Vec<(usize, usize)> {
+    let mut ranges: Vec<(usize, usize)> = Vec::new();
+
+    for &idx in changed {
+        let start = idx.saturating_sub(context);
+        let end = (idx + context + 1).min(total);
+        match ranges.last_mut() {
+            Some(last) if start <= last.1 => last.1 = end,
+            _ => ranges.push((start, end)),
+        }
+    }
+
+    ranges
+}
+
+fn render_hunk(lines: &[DiffLine], start: usize, end: usize) -> String {
+    // Line numbers (1-based) at the hunk start in each file
+    let old_start = 1 + lines[..start].iter().filter(|l| !matches!(l, DiffLine::Added(_))).count();
+    let new_start = 1 + lines[..start].iter().filter(|l| !matches!(l, DiffLine::Removed(_))).count();
+    let hunk = &lines[start..end];
+    let old_len = hunk.iter().filter(|l| !matches!(l, DiffLine::Added(_))).count();
+    let new_len = hunk.iter().filter(|l| !matches!(l, DiffLine::Removed(_))).count();
+
+    // Empty ranges point at the line before the hunk, as in `diff -u`
+    let old_start = if old_len == 0 { old_start - 1 } else { old_start };
+    let new_start = if new_len == 0 { new_start - 1 } else { new_start };
+
+    let mut output = format!("@@ -{},{} +{},{} @@\n", old_start, old_len, new_start, new_len);
+    for line in hunk {
+        match line {
+            DiffLine::Same(text) => output.push_str(&format!(" {}\n", text)),
+            DiffLine::Added(text) => output.push_str(&format!("+{}\n", text)),
+            DiffLine::Removed(text) => output.push_str(&format!("-{}\n", text)),
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff_lines() {
+        let lines = diff_lines("a\nb\nc", "a\nc\nd");
+        assert_eq!(
+            lines,
+            vec![
+                DiffLine::Same("a"),
+                DiffLine::Removed("b"),
+                DiffLine::Same("c"),
+                DiffLine::Added("d"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_colorize_keeps_every_line() {
+        let diff = unified_diff("a\nb", "a\nc", "a/x.md", "b/x.md", 1);
+        let colored = colorize(&diff);
+        assert_eq!(colored.lines().count(), diff.lines().count());
+        for line in diff.lines() {
+            assert!(colored.contains(line));
+        }
+    }
+
+    #[test]
+    fn test_unified_diff_identical() {
+        assert_eq!(unified_diff("a\nb", "a\nb", "old", "new", 3), "");
+    }
+
+    #[test]
+    fn test_unified_diff_with_context() {
+        let old = "1\n2\n3\n4\n5\n6\n7\n8\n";
+        let new = "1\n2\n3\n4\nfive\n6\n7\n8\n";
+
+        let diff = unified_diff(old, new, "a/README.md", "b/README.md", 1);
+
+        assert_eq!(
+            diff,
+            "--- a/README.md\n+++ b/README.md\n@@ -4,3 +4,3 @@\n 4\n-5\n+five\n 6\n"
+        );
+    }
+
+    #[test]
+    fn test_unified_diff_new_file() {
+        let diff = unified_diff("", "# Title\nBody\n", "/dev/null", "b/til.md", 3);
+        assert!(diff.contains("@@ -0,0 +1,2 @@"));
+        assert!(diff.contains("+# Title\n+Body\n"));
+    }
+
+    #[test]
+    fn test_unified_diff_separate_hunks() {
+        let old = "a\nb\nc\nd\ne\nf\ng\nh\n";
+        let new = "A\nb\nc\nd\ne\nf\ng\nH\n";
+
+        let diff = unified_diff(old, new, "old", "new", 1);
+
+        assert_eq!(diff.matches("@@ -").count(), 2);
+    }
+}
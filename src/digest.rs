@@ -0,0 +1,142 @@
+use crate::config::Config;
+use crate::export::feed::{self, FeedEntry, FeedSettings};
+use crate::session::Session;
+use anyhow::Result;
+use chrono::{Duration, NaiveDate};
+
+/// What was learned over a stretch of days: the TILs added and the sessions held
+#[derive(Debug, Clone)]
+pub struct Digest {
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+    /// Newest first
+    pub tils: Vec<FeedEntry>,
+    /// `(date, topic, category)`, oldest first
+    pub sessions: Vec<(NaiveDate, String, Option<String>)>,
+}
+
+impl Digest {
+    /// The digest for the `days` days up to and including `today`
+    pub fn collect(config: &Config, sessions: &[Session], today: NaiveDate, days: u32) -> Result<Self> {
+        let start = today - Duration::days(i64::from(days.max(1)) - 1);
+        let sessions = sessions
+            .iter()
+            .filter(|session| session.started_at.date_naive() >= start)
+            .map(|session| (session.started_at.date_naive(), session.topic().to_string(), session.category.clone()))
+            .collect();
+
+        Ok(Self {
+            start,
+            end: today,
+            tils: feed::entries_since(config, start)?,
+            sessions,
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tils.is_empty() && self.sessions.is_empty()
+    }
+
+    pub fn subject(&self) -> String {
+        format!(
+            "Your learning digest: {} TIL(s), {} session(s), {} to {}",
+            self.tils.len(),
+            self.sessions.len(),
+            self.start.format("%b %-d"),
+            self.end.format("%b %-d")
+        )
+    }
+
+    /// The digest as a standalone HTML page, each TIL in full so it can be re-read in the inbox.
+    /// With a `[feed] url`, titles link to the TILs.
+    pub fn to_html(&self, settings: &FeedSettings) -> String {
+        let mut html = String::from("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+        html.push_str(&format!("<title>{}</title>\n</head>\n", feed::escape(&self.subject())));
+        html.push_str("<body style=\"font-family: sans-serif; max-width: 40em; margin: auto; line-height: 1.5\">\n");
+        html.push_str(&format!(
+            "<h1>{}</h1>\n<p style=\"color: #666\">{} to {}</p>\n",
+            feed::escape(&settings.title),
+            self.start.format("%A, %B %-d"),
+            self.end.format("%A, %B %-d")
+        ));
+
+        if self.tils.is_empty() {
+            html.push_str("<p>No new TILs this time.</p>\n");
+        }
+        for til in &self.tils {
+            let title = feed::escape(&til.title);
+            let title = match settings.url {
+                Some(ref url) => {
+                    let link = format!("{}/{}", url.trim_end_matches('/'), til.path);
+                    format!("<a href=\"{}\">{}</a>", feed::escape(&link), title)
+                }
+                None => title,
+            };
+            let details = match til.category {
+                Some(ref category) => format!("{} · {}", feed::escape(category), til.date.format("%b %-d")),
+                None => til.date.format("%b %-d").to_string(),
+            };
+            html.push_str(&format!(
+                "<hr>\n<h2>{}</h2>\n<p style=\"color: #666\">{}</p>\n{}",
+                title, details, til.html
+            ));
+        }
+
+        if !self.sessions.is_empty() {
+            html.push_str("<hr>\n<h2>Sessions</h2>\n<ul>\n");
+            for (date, topic, category) in &self.sessions {
+                let category = category.as_deref().map(|c| format!(" ({})", feed::escape(c))).unwrap_or_default();
+                html.push_str(&format!(
+                    "<li>{}: {}{}</li>\n",
+                    date.format("%a %b %-d"),
+                    feed::escape(topic),
+                    category
+                ));
+            }
+            html.push_str("</ul>\n");
+        }
+
+        html.push_str("</body>\n</html>\n");
+        html
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Context;
+
+    fn date(day: u32) -> Result<NaiveDate> {
+        NaiveDate::from_ymd_opt(2024, 6, day).context("invalid date")
+    }
+
+    #[test]
+    fn test_digest_html() -> Result<()> {
+        let digest = Digest {
+            start: date(3)?,
+            end: date(9)?,
+            tils: vec![FeedEntry {
+                title: "Vec<T> capacity".to_string(),
+                category: Some("rust".to_string()),
+                path: "archive/rust/vec-capacity.md".to_string(),
+                date: date(4)?,
+                html: "<p>Reserve up front.</p>\n".to_string(),
+            }],
+            sessions: vec![(date(5)?, "Postgres VACUUM".to_string(), None)],
+        };
+        let settings = FeedSettings {
+            title: "Today I Learned".to_string(),
+            url: Some("https://github.com/jo/til/blob/main/".to_string()),
+            limit: 20,
+        };
+
+        assert_eq!(digest.subject(), "Your learning digest: 1 TIL(s), 1 session(s), Jun 3 to Jun 9");
+        let html = digest.to_html(&settings);
+        assert!(html.contains("<a href=\"https://github.com/jo/til/blob/main/archive/rust/vec-capacity.md\">"));
+        assert!(html.contains(">Vec&lt;T&gt; capacity</a></h2>"));
+        assert!(html.contains("<p style=\"color: #666\">rust · Jun 4</p>\n<p>Reserve up front.</p>"));
+        assert!(html.contains("<li>Wed Jun 5: Postgres VACUUM</li>"));
+        assert!(!digest.is_empty());
+        Ok(())
+    }
+}
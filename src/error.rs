@@ -0,0 +1,141 @@
+use crate::claude;
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+
+/// Exit code for anything not covered by [`HolocronError`]
+pub const EXIT_FAILURE: u8 = 1;
+
+/// Failures scripts around holocron can tell apart by exit code. Everything else exits with
+/// [`EXIT_FAILURE`].
+#[derive(Debug)]
+pub enum HolocronError {
+    /// There's no config yet, and no terminal to run first-time setup in
+    ConfigMissing,
+    /// The `claude` CLI isn't installed, so sessions can't start
+    BackendUnavailable,
+    /// The configured TIL path doesn't exist
+    RepoNotInitialized(PathBuf),
+    /// A TIL, note or README couldn't be written
+    WriteFailed { path: PathBuf, source: io::Error },
+    /// It needed to ask something, but stdout isn't a terminal (a pipe, a file, cron)
+    NotInteractive,
+    /// The user stopped it with Ctrl+C
+    Aborted,
+}
+
+impl HolocronError {
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            HolocronError::ConfigMissing => 3,
+            HolocronError::BackendUnavailable => 4,
+            HolocronError::RepoNotInitialized(_) => 5,
+            HolocronError::WriteFailed { .. } => 6,
+            HolocronError::NotInteractive => 7,
+            HolocronError::Aborted => 130,
+        }
+    }
+
+    /// A command that would fix it, if there is one
+    pub fn suggestion(&self) -> Option<String> {
+        match self {
+            HolocronError::ConfigMissing => {
+                Some("holocron config --til-path <path>, or run holocron in a terminal to set up".to_string())
+            }
+            HolocronError::BackendUnavailable => Some("npm install -g @anthropic-ai/claude-code".to_string()),
+            HolocronError::RepoNotInitialized(path) => Some(format!("holocron init {}", path.display())),
+            HolocronError::WriteFailed { .. } | HolocronError::NotInteractive | HolocronError::Aborted => None,
+        }
+    }
+}
+
+impl fmt::Display for HolocronError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HolocronError::ConfigMissing => write!(f, "holocron isn't configured yet"),
+            HolocronError::BackendUnavailable => write!(
+                f,
+                "The claude CLI isn't installed or isn't on PATH, so sessions can't start. \
+                 The archive still works offline: list, open, browse, check, relink, til, notes, share, queue, watch, sync, \
+                 export, insights and plan."
+            ),
+            HolocronError::RepoNotInitialized(path) => write!(f, "There's no TIL repository at {:?}", path),
+            HolocronError::WriteFailed { path, .. } => write!(f, "Failed to write {:?}", path),
+            HolocronError::NotInteractive => write!(
+                f,
+                "This needs an answer, but output isn't going to a terminal. Run it in a terminal, or give the \
+                 choice with a flag"
+            ),
+            HolocronError::Aborted => write!(f, "Aborted"),
+        }
+    }
+}
+
+impl std::error::Error for HolocronError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            HolocronError::WriteFailed { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+/// The exit code for `error`: its [`HolocronError`]'s if it has one, [`HolocronError::Aborted`]'s
+/// for a cancelled request or an interrupted prompt, otherwise [`EXIT_FAILURE`]
+pub fn exit_code(error: &anyhow::Error) -> u8 {
+    if let Some(known) = find(error) {
+        known.exit_code()
+    } else if aborted(error) {
+        HolocronError::Aborted.exit_code()
+    } else {
+        EXIT_FAILURE
+    }
+}
+
+/// A command that would fix `error`, if it's a known failure with one
+pub fn suggestion(error: &anyhow::Error) -> Option<String> {
+    find(error).and_then(HolocronError::suggestion)
+}
+
+fn find(error: &anyhow::Error) -> Option<&HolocronError> {
+    error.chain().find_map(|cause| cause.downcast_ref::<HolocronError>())
+}
+
+fn aborted(error: &anyhow::Error) -> bool {
+    claude::is_cancelled(error)
+        || error
+            .chain()
+            .any(|cause| cause.downcast_ref::<io::Error>().is_some_and(|e| e.kind() == io::ErrorKind::Interrupted))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::anyhow;
+
+    #[test]
+    fn test_exit_code_finds_typed_error_behind_context() {
+        let error = anyhow::Error::new(HolocronError::WriteFailed {
+            path: PathBuf::from("/til/archive/rust/a.md"),
+            source: io::Error::new(io::ErrorKind::PermissionDenied, "Permission denied"),
+        })
+        .context("Failed to save the TIL");
+
+        assert_eq!(exit_code(&error), 6);
+        assert_eq!(
+            format!("{:#}", error),
+            "Failed to save the TIL: Failed to write \"/til/archive/rust/a.md\": Permission denied"
+        );
+        assert_eq!(exit_code(&anyhow!("something else")), EXIT_FAILURE);
+        assert_eq!(
+            suggestion(&HolocronError::RepoNotInitialized(PathBuf::from("/til")).into()).as_deref(),
+            Some("holocron init /til")
+        );
+    }
+
+    #[test]
+    fn test_interrupted_prompt_is_an_abort() {
+        let error = anyhow::Error::new(io::Error::new(io::ErrorKind::Interrupted, "read interrupted"));
+        assert_eq!(exit_code(&error), 130);
+    }
+}
@@ -0,0 +1,258 @@
+use crate::archive::{self, Entry};
+use crate::til::writer::category_heading;
+use anyhow::{anyhow, Context, Result};
+use chrono::NaiveDate;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+/// Output formats supported for the combined book
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BookFormat {
+    Pdf,
+    Epub,
+}
+
+impl BookFormat {
+    pub fn parse(format: &str) -> Result<Self> {
+        match format.to_lowercase().as_str() {
+            "pdf" => Ok(BookFormat::Pdf),
+            "epub" => Ok(BookFormat::Epub),
+            _ => Err(anyhow!("Invalid book format. Use: pdf or epub")),
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            BookFormat::Pdf => "pdf",
+            BookFormat::Epub => "epub",
+        }
+    }
+}
+
+/// Which TILs to include in the book
+#[derive(Debug, Default)]
+pub struct BookFilter {
+    pub category: Option<String>,
+    pub since: Option<NaiveDate>,
+    pub until: Option<NaiveDate>,
+}
+
+/// A TIL selected for the book
+#[derive(Debug, Clone)]
+pub struct BookEntry {
+    pub category: String,
+    pub title: String,
+    pub date: Option<NaiveDate>,
+    pub content: String,
+}
+
+/// Collect archive entries matching the filter, ordered by category then date
+pub fn collect_entries(archive_path: &Path, filter: &BookFilter) -> Result<Vec<BookEntry>> {
+    let mut entries = Vec::new();
+
+    for entry in archive::til_entries(archive_path)? {
+        if let Some(book_entry) = to_book_entry(entry, filter)? {
+            entries.push(book_entry);
+        }
+    }
+
+    entries.sort_by(|a, b| {
+        a.category
+            .cmp(&b.category)
+            .then_with(|| a.date.cmp(&b.date))
+            .then_with(|| a.title.cmp(&b.title))
+    });
+
+    Ok(entries)
+}
+
+fn to_book_entry(entry: Entry, filter: &BookFilter) -> Result<Option<BookEntry>> {
+    let category = entry.category.unwrap_or_else(|| "uncategorized".to_string());
+    if filter.category.as_ref().is_some_and(|c| !c.eq_ignore_ascii_case(&category)) {
+        return Ok(None);
+    }
+
+    let date = archive::entry_date(&entry.path);
+    let in_range = date.is_none_or(|d| {
+        filter.since.is_none_or(|since| d >= since) && filter.until.is_none_or(|until| d <= until)
+    });
+    if !in_range {
+        return Ok(None);
+    }
+
+    let content = fs::read_to_string(&entry.path)
+        .with_context(|| format!("Failed to read TIL: {:?}", entry.path))?;
+    super::require_alt_text(&entry.path, &content)?;
+
+    Ok(Some(BookEntry {
+        category,
+        title: entry.title,
+        date,
+        content,
+    }))
+}
+
+/// Concatenate entries into one markdown document with a section per category
+pub fn build_book_markdown(title: &str, entries: &[BookEntry]) -> String {
+    let mut book = format!("---\ntitle: \"{}\"\n---\n", title.replace('"', "'"));
+    let mut current_category: Option<&str> = None;
+
+    for entry in entries {
+        if current_category != Some(entry.category.as_str()) {
+            book.push_str(&format!("\n# {}\n", category_heading(&entry.category)));
+            current_category = Some(&entry.category);
+        }
+
+        book.push_str(&format!("\n## {}\n\n", entry.title));
+        if let Some(date) = entry.date {
+            book.push_str(&format!("*{}*\n\n", date.format("%B %-d, %Y")));
+        }
+        book.push_str(&demote_body(&entry.content));
+        book.push('\n');
+    }
+
+    book
+}
+
+/// Render the markdown to the requested format with pandoc (with a table of contents)
+pub fn render_book(markdown: &str, format: BookFormat, out: &Path) -> Result<()> {
+    let temp_dir = tempfile::tempdir().context("Failed to create temp directory")?;
+    let source = temp_dir.path().join("book.md");
+    fs::write(&source, markdown).context("Failed to write book source")?;
+
+    let status = Command::new("pandoc")
+        .arg(&source)
+        .args(["--toc", "--toc-depth=2", "--from", "markdown", "--to", format.extension()])
+        .arg("-o")
+        .arg(out)
+        .status()
+        .map_err(|e| anyhow!("Failed to run pandoc (is it installed?): {}", e))?;
+
+    if !status.success() {
+        return Err(anyhow!("pandoc exited with {}", status));
+    }
+    Ok(())
+}
+
+/// Drop the entry's own H1 (used as the section title) and push other headings down two levels
+fn demote_body(content: &str) -> String {
+    let mut in_code_block = false;
+    let mut skipped_title = false;
+    let mut lines = Vec::new();
+
+    for line in content.lines() {
+        if line.trim_start().starts_with("```") {
+            in_code_block = !in_code_block;
+        }
+
+        if !in_code_block && !skipped_title && line.trim_start().starts_with("# ") {
+            skipped_title = true;
+            continue;
+        }
+
+        if !in_code_block && line.starts_with('#') {
+            lines.push(format!("##{}", line));
+        } else {
+            lines.push(line.to_string());
+        }
+    }
+
+    lines.join("\n").trim().to_string() + "\n"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn book_entry(category: &str, title: &str, content: &str) -> BookEntry {
+        BookEntry {
+            category: category.to_string(),
+            title: title.to_string(),
+            date: NaiveDate::from_ymd_opt(2024, 6, 1),
+            content: content.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_book_format_parse() -> Result<()> {
+        assert_eq!(BookFormat::parse("PDF")?, BookFormat::Pdf);
+        assert_eq!(BookFormat::parse("epub")?, BookFormat::Epub);
+        assert!(BookFormat::parse("mobi").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_demote_body() {
+        let content = "# Title\n\nIntro\n\n## Section\n\n```bash\n# comment\n```\n";
+        assert_eq!(
+            demote_body(content),
+            "Intro\n\n#### Section\n\n```bash\n# comment\n```\n"
+        );
+    }
+
+    #[test]
+    fn test_build_book_markdown_groups_by_category() {
+        let entries = vec![
+            book_entry("git", "Rebase", "# Rebase\n\nBody one"),
+            book_entry("git", "Squash", "# Squash\n\nBody two"),
+            book_entry("rust", "Ownership", "# Ownership\n\nBody three"),
+        ];
+
+        let book = build_book_markdown("My TILs", &entries);
+
+        assert!(book.starts_with("---\ntitle: \"My TILs\"\n---\n"));
+        assert_eq!(book.matches("\n# Git\n").count(), 1);
+        assert!(book.contains("\n# Rust\n"));
+        assert!(book.contains("## Squash\n\n*June 1, 2024*\n\nBody two"));
+    }
+
+    #[test]
+    fn test_collect_entries_filters_category() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        fs::create_dir_all(temp_dir.path().join("git"))?;
+        fs::create_dir_all(temp_dir.path().join("rust"))?;
+        fs::write(temp_dir.path().join("git/rebase.md"), "# Rebase\n")?;
+        fs::write(temp_dir.path().join("rust/borrow.md"), "# Borrow\n")?;
+
+        let filter = BookFilter {
+            category: Some("rust".to_string()),
+            ..BookFilter::default()
+        };
+        let entries = collect_entries(temp_dir.path(), &filter)?;
+
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].title, "Borrow");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_collect_entries_filters_dates() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        fs::create_dir_all(temp_dir.path().join("git"))?;
+        fs::write(temp_dir.path().join("git/rebase.md"), "# Rebase\n")?;
+
+        let filter = BookFilter {
+            since: NaiveDate::from_ymd_opt(2999, 1, 1),
+            ..BookFilter::default()
+        };
+
+        assert!(collect_entries(temp_dir.path(), &filter)?.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_collect_entries_requires_alt_text() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        fs::create_dir_all(temp_dir.path().join("git"))?;
+        fs::write(temp_dir.path().join("git/rebase.md"), "# Rebase\n\n![](graph.png)\n")?;
+
+        let err = collect_entries(temp_dir.path(), &BookFilter::default()).err().map(|e| e.to_string());
+
+        assert!(err.is_some_and(|e| e.contains("rebase.md:3: image graph.png has no alt text")));
+        Ok(())
+    }
+}
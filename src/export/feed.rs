@@ -0,0 +1,244 @@
+use crate::archive;
+use crate::config::Config;
+use crate::{markdown, publish};
+use anyhow::{Context, Result};
+use chrono::{Local, NaiveDate};
+use pulldown_cmark::{html, Options, Parser};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+pub const DEFAULT_TITLE: &str = "Today I Learned";
+pub const DEFAULT_LIMIT: usize = 20;
+const DEFAULT_FILE: &str = "feed.xml";
+
+/// What the feed is called and where its entries link to
+#[derive(Debug, Clone)]
+pub struct FeedSettings {
+    pub title: String,
+    /// Where the TIL repo can be browsed; entry paths are appended to it
+    pub url: Option<String>,
+    pub limit: usize,
+}
+
+impl FeedSettings {
+    /// The `[feed]` settings, with the defaults filled in
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            title: config.feed.title.clone().unwrap_or_else(|| DEFAULT_TITLE.to_string()),
+            url: config.feed.url.clone(),
+            limit: config.feed.limit.unwrap_or(DEFAULT_LIMIT),
+        }
+    }
+}
+
+/// A TIL as it appears in the feed
+#[derive(Debug, Clone)]
+pub struct FeedEntry {
+    pub title: String,
+    pub category: Option<String>,
+    /// Path within the TIL repo, e.g. `archive/rust/borrowing.md`
+    pub path: String,
+    pub date: NaiveDate,
+    pub html: String,
+}
+
+/// Where the feed goes when no path is given: `[feed] path`, or `feed.xml` in the TIL repo
+pub fn default_path(config: &Config) -> PathBuf {
+    config.feed.path.clone().unwrap_or_else(|| config.til_path.join(DEFAULT_FILE))
+}
+
+/// The `limit` most recently added TILs, newest first
+pub fn recent_entries(config: &Config, limit: usize) -> Result<Vec<FeedEntry>> {
+    dated_entries(config, limit, None)
+}
+
+/// Every TIL added on or after `since`, newest first
+pub fn entries_since(config: &Config, since: NaiveDate) -> Result<Vec<FeedEntry>> {
+    dated_entries(config, usize::MAX, Some(since))
+}
+
+fn dated_entries(config: &Config, limit: usize, since: Option<NaiveDate>) -> Result<Vec<FeedEntry>> {
+    let archive_path = config.archive_path();
+    let added = archive::git_added_dates(&archive_path);
+
+    let mut dated: Vec<(NaiveDate, archive::Entry)> = archive::til_entries(&archive_path)?
+        .into_iter()
+        .filter_map(|entry| {
+            let date = added.get(&entry.path).copied().or_else(|| archive::entry_date(&entry.path))?;
+            Some((date, entry))
+        })
+        .filter(|(date, _)| since.is_none_or(|since| *date >= since))
+        .collect();
+    dated.sort_by(|(a_date, a), (b_date, b)| b_date.cmp(a_date).then_with(|| a.path.cmp(&b.path)));
+    dated.truncate(limit);
+
+    let mut entries = Vec::new();
+    for (date, entry) in dated {
+        let content =
+            fs::read_to_string(&entry.path).with_context(|| format!("Failed to read TIL: {:?}", entry.path))?;
+        let path = entry.path.strip_prefix(&config.til_path).unwrap_or(&entry.path);
+        entries.push(FeedEntry {
+            title: entry.title,
+            category: entry.category,
+            path: path.to_string_lossy().replace('\\', "/"),
+            date,
+            html: render_html(&content),
+        });
+    }
+    Ok(entries)
+}
+
+/// The TIL's public body as HTML, without frontmatter or the title heading (the entry has its own)
+pub fn render_html(content: &str) -> String {
+    let public = publish::public_content(content);
+    let body = markdown::split_frontmatter(&public).map_or(public.as_str(), |(_, body)| body);
+    let body = body.trim_start();
+    let body = match body.strip_prefix("# ") {
+        Some(heading) => heading.split_once('\n').map_or("", |(_, rest)| rest),
+        None => body,
+    };
+
+    let mut rendered = String::new();
+    html::push_html(&mut rendered, Parser::new_ext(body, Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH));
+    rendered
+}
+
+/// The Atom document for `entries`
+pub fn build_atom(settings: &FeedSettings, author: &str, base_id: &str, entries: &[FeedEntry]) -> String {
+    let base = settings.url.as_deref().unwrap_or(base_id).trim_end_matches('/');
+    let updated = entries.iter().map(|entry| entry.date).max().unwrap_or_else(|| Local::now().date_naive());
+
+    let mut feed = String::from("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n");
+    feed.push_str("<feed xmlns=\"http://www.w3.org/2005/Atom\">\n");
+    feed.push_str(&format!("  <title>{}</title>\n", escape(&settings.title)));
+    feed.push_str(&format!("  <id>{}</id>\n", escape(base)));
+    if let Some(ref url) = settings.url {
+        feed.push_str(&format!("  <link href=\"{}\"/>\n", escape(url)));
+    }
+    feed.push_str(&format!("  <updated>{}</updated>\n", timestamp(updated)));
+    feed.push_str(&format!("  <author><name>{}</name></author>\n", escape(author)));
+    feed.push_str("  <generator>holocron</generator>\n");
+
+    for entry in entries {
+        let id = format!("{}/{}", base, entry.path);
+        feed.push_str("  <entry>\n");
+        feed.push_str(&format!("    <title>{}</title>\n", escape(&entry.title)));
+        feed.push_str(&format!("    <id>{}</id>\n", escape(&id)));
+        if settings.url.is_some() {
+            feed.push_str(&format!("    <link href=\"{}\"/>\n", escape(&id)));
+        }
+        feed.push_str(&format!("    <published>{}</published>\n", timestamp(entry.date)));
+        feed.push_str(&format!("    <updated>{}</updated>\n", timestamp(entry.date)));
+        if let Some(ref category) = entry.category {
+            feed.push_str(&format!("    <category term=\"{}\"/>\n", escape(category)));
+        }
+        feed.push_str(&format!("    <content type=\"html\">{}</content>\n", escape(&entry.html)));
+        feed.push_str("  </entry>\n");
+    }
+
+    feed.push_str("</feed>\n");
+    feed
+}
+
+/// Build the feed from the archive and write it to `out`, returning how many TILs it holds
+pub fn write(config: &Config, settings: &FeedSettings, out: &Path) -> Result<usize> {
+    let entries = recent_entries(config, settings.limit)?;
+    let base_id = format!("file://{}", config.til_path.display());
+    let atom = build_atom(settings, &author(&config.til_path), &base_id, &entries);
+
+    if let Some(dir) = out.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        fs::create_dir_all(dir).with_context(|| format!("Failed to create directory: {:?}", dir))?;
+    }
+    fs::write(out, atom).with_context(|| format!("Failed to write {:?}", out))?;
+    Ok(entries.len())
+}
+
+/// The TIL repo's git author, which Atom requires a feed to name
+fn author(til_path: &Path) -> String {
+    Command::new("git")
+        .arg("-C")
+        .arg(til_path)
+        .args(["config", "user.name"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| "holocron".to_string())
+}
+
+/// Dates only come from git's `%cs`, so entries are stamped at midnight UTC
+fn timestamp(date: NaiveDate) -> String {
+    format!("{}T00:00:00Z", date.format("%Y-%m-%d"))
+}
+
+pub fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(day: u32) -> Result<NaiveDate> {
+        NaiveDate::from_ymd_opt(2024, 6, day).context("invalid date")
+    }
+
+    #[test]
+    fn test_render_html_drops_title_and_private_parts() {
+        let content = "---\ntags: [git]\n---\n\n# Rebase onto\n\nMove a *branch*.\n\nMy token is abc <!-- private -->\n\n```sh\ngit rebase --onto main\n```\n";
+
+        let html = render_html(content);
+
+        assert!(!html.contains("Rebase onto"));
+        assert!(!html.contains("tags"));
+        assert!(!html.contains("abc"));
+        assert!(html.contains("<p>Move a <em>branch</em>.</p>"));
+        assert!(html.contains("<code class=\"language-sh\">git rebase --onto main\n</code>"));
+    }
+
+    #[test]
+    fn test_build_atom_escapes_and_links() -> Result<()> {
+        let settings = FeedSettings {
+            title: "Jo's TIL & more".to_string(),
+            url: Some("https://github.com/jo/til/blob/main/".to_string()),
+            limit: 20,
+        };
+        let entries = vec![
+            FeedEntry {
+                title: "Vec<T> capacity".to_string(),
+                category: Some("rust".to_string()),
+                path: "archive/rust/vec-capacity.md".to_string(),
+                date: date(3)?,
+                html: "<p>Reserve up front.</p>\n".to_string(),
+            },
+            FeedEntry {
+                title: "Rebase onto".to_string(),
+                category: None,
+                path: "archive/rebase-onto.md".to_string(),
+                date: date(1)?,
+                html: String::new(),
+            },
+        ];
+
+        let atom = build_atom(&settings, "Jo", "file:///til", &entries);
+
+        assert!(atom.contains("<title>Jo's TIL &amp; more</title>"));
+        assert!(atom.contains("<updated>2024-06-03T00:00:00Z</updated>\n  <author>"));
+        assert!(atom.contains("<title>Vec&lt;T&gt; capacity</title>"));
+        assert!(atom.contains("<link href=\"https://github.com/jo/til/blob/main/archive/rust/vec-capacity.md\"/>"));
+        assert!(atom.contains("<category term=\"rust\"/>"));
+        assert!(atom.contains("<content type=\"html\">&lt;p&gt;Reserve up front.&lt;/p&gt;\n</content>"));
+        assert_eq!(atom.matches("<entry>").count(), 2);
+        assert_eq!(atom.matches("<category").count(), 1);
+
+        let unlinked = build_atom(&FeedSettings { url: None, ..settings }, "Jo", "file:///til", &entries);
+        assert!(unlinked.contains("<id>file:///til/archive/rebase-onto.md</id>"));
+        assert!(!unlinked.contains("<link"));
+        Ok(())
+    }
+}
@@ -0,0 +1,203 @@
+/// What the highlighter needs to know about a language
+struct Syntax {
+    keywords: &'static [&'static str],
+    line_comments: &'static [&'static str],
+    block_comments: bool,
+    quotes: &'static [char],
+    /// SQL keywords are written in any case
+    case_insensitive: bool,
+}
+
+const RUST: Syntax = Syntax {
+    keywords: &[
+        "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum", "extern", "false", "fn",
+        "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref", "return", "self",
+        "Self", "static", "struct", "super", "trait", "true", "type", "unsafe", "use", "where", "while",
+    ],
+    line_comments: &["//"],
+    block_comments: true,
+    quotes: &['"'],
+    case_insensitive: false,
+};
+
+const PYTHON: Syntax = Syntax {
+    keywords: &[
+        "and", "as", "assert", "async", "await", "break", "class", "continue", "def", "del", "elif", "else", "except",
+        "False", "finally", "for", "from", "global", "if", "import", "in", "is", "lambda", "None", "nonlocal", "not",
+        "or", "pass", "raise", "return", "True", "try", "while", "with", "yield",
+    ],
+    line_comments: &["#"],
+    block_comments: false,
+    quotes: &['"', '\''],
+    case_insensitive: false,
+};
+
+const JAVASCRIPT: Syntax = Syntax {
+    keywords: &[
+        "async", "await", "break", "case", "catch", "class", "const", "continue", "default", "delete", "do", "else",
+        "export", "extends", "false", "finally", "for", "from", "function", "if", "import", "in", "instanceof",
+        "interface", "let", "new", "null", "return", "static", "switch", "this", "throw", "true", "try", "type",
+        "typeof", "undefined", "var", "void", "while", "yield",
+    ],
+    line_comments: &["//"],
+    block_comments: true,
+    quotes: &['"', '\'', '`'],
+    case_insensitive: false,
+};
+
+const GO: Syntax = Syntax {
+    keywords: &[
+        "break", "case", "chan", "const", "continue", "default", "defer", "else", "false", "for", "func", "go",
+        "goto", "if", "import", "interface", "map", "nil", "package", "range", "return", "select", "struct",
+        "switch", "true", "type", "var",
+    ],
+    line_comments: &["//"],
+    block_comments: true,
+    quotes: &['"', '`'],
+    case_insensitive: false,
+};
+
+/// C, C++, Java, C# and the like
+const C_FAMILY: Syntax = Syntax {
+    keywords: &[
+        "auto", "bool", "break", "case", "char", "class", "const", "continue", "default", "do", "double", "else",
+        "enum", "extends", "false", "final", "float", "for", "if", "import", "int", "long", "namespace", "new",
+        "null", "nullptr", "private", "protected", "public", "return", "short", "static", "struct", "switch",
+        "this", "throw", "true", "try", "typedef", "unsigned", "using", "void", "while",
+    ],
+    line_comments: &["//"],
+    block_comments: true,
+    quotes: &['"', '\''],
+    case_insensitive: false,
+};
+
+const SHELL: Syntax = Syntax {
+    keywords: &[
+        "case", "do", "done", "elif", "else", "esac", "export", "fi", "for", "function", "if", "in", "local",
+        "return", "then", "until", "while",
+    ],
+    line_comments: &["#"],
+    block_comments: false,
+    quotes: &['"', '\''],
+    case_insensitive: false,
+};
+
+const SQL: Syntax = Syntax {
+    keywords: &[
+        "add", "alter", "and", "as", "asc", "begin", "by", "case", "commit", "create", "delete", "desc", "distinct",
+        "drop", "else", "end", "exists", "from", "group", "having", "in", "index", "inner", "insert", "into", "is",
+        "join", "left", "like", "limit", "not", "null", "on", "or", "order", "outer", "primary", "key", "right",
+        "rollback", "select", "set", "table", "then", "union", "update", "values", "when", "where", "with",
+    ],
+    line_comments: &["--"],
+    block_comments: true,
+    quotes: &['\''],
+    case_insensitive: true,
+};
+
+fn syntax_for(lang: &str) -> Option<&'static Syntax> {
+    match lang.trim().to_lowercase().as_str() {
+        "rust" | "rs" => Some(&RUST),
+        "python" | "py" => Some(&PYTHON),
+        "javascript" | "js" | "typescript" | "ts" | "jsx" | "tsx" => Some(&JAVASCRIPT),
+        "go" | "golang" => Some(&GO),
+        "c" | "cpp" | "c++" | "java" | "csharp" | "cs" | "kotlin" => Some(&C_FAMILY),
+        "sh" | "bash" | "shell" | "zsh" | "console" => Some(&SHELL),
+        "sql" | "psql" | "postgres" | "postgresql" | "sqlite" | "mysql" => Some(&SQL),
+        _ => None,
+    }
+}
+
+/// A fenced code block's code as HTML, with `hl-kw`, `hl-str`, `hl-com` and `hl-num` spans for the
+/// languages it knows. Anything else is only escaped.
+pub fn highlight(code: &str, lang: &str) -> String {
+    let Some(syntax) = syntax_for(lang) else {
+        return escape(code);
+    };
+
+    let chars: Vec<char> = code.chars().collect();
+    let mut html = String::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let rest: String = chars[i..chars.len().min(i + 2)].iter().collect();
+        let (class, end) = if syntax.line_comments.iter().any(|prefix| rest.starts_with(prefix)) {
+            ("hl-com", find_from(&chars, i, "\n").unwrap_or(chars.len()))
+        } else if syntax.block_comments && rest == "/*" {
+            ("hl-com", find_from(&chars, i + 2, "*/").map_or(chars.len(), |end| end + 2))
+        } else if syntax.quotes.contains(&chars[i]) {
+            ("hl-str", string_end(&chars, i))
+        } else if chars[i].is_ascii_digit() {
+            ("hl-num", word_end(&chars, i, |c| c.is_alphanumeric() || c == '.' || c == '_'))
+        } else if chars[i].is_alphabetic() || chars[i] == '_' {
+            let end = word_end(&chars, i, |c| c.is_alphanumeric() || c == '_');
+            let word: String = chars[i..end].iter().collect();
+            let keyword = syntax.keywords.iter().any(|keyword| match syntax.case_insensitive {
+                true => keyword.eq_ignore_ascii_case(&word),
+                false => *keyword == word,
+            });
+            (if keyword { "hl-kw" } else { "" }, end)
+        } else {
+            ("", i + 1)
+        };
+
+        let text = escape(&chars[i..end].iter().collect::<String>());
+        if class.is_empty() {
+            html.push_str(&text);
+        } else {
+            html.push_str(&format!("<span class=\"{}\">{}</span>", class, text));
+        }
+        i = end;
+    }
+    html
+}
+
+/// Where `needle` next starts at or after `from`
+fn find_from(chars: &[char], from: usize, needle: &str) -> Option<usize> {
+    let needle: Vec<char> = needle.chars().collect();
+    (from..chars.len()).find(|&i| chars[i..].starts_with(&needle))
+}
+
+/// Just past the quote closing the string that opens at `start`, skipping escaped quotes
+fn string_end(chars: &[char], start: usize) -> usize {
+    let quote = chars[start];
+    let mut i = start + 1;
+    while i < chars.len() {
+        match chars[i] {
+            '\\' => i += 2,
+            c if c == quote => return i + 1,
+            _ => i += 1,
+        }
+    }
+    chars.len()
+}
+
+fn word_end(chars: &[char], start: usize, in_word: impl Fn(char) -> bool) -> usize {
+    (start..chars.len()).find(|&i| !in_word(chars[i])).unwrap_or(chars.len())
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_highlight_rust() {
+        let html = highlight("let x = \"a<b\"; // done\nfn f() -> u8 { 42 }", "rust");
+        assert_eq!(
+            html,
+            "<span class=\"hl-kw\">let</span> x = <span class=\"hl-str\">\"a&lt;b\"</span>; \
+             <span class=\"hl-com\">// done</span>\n<span class=\"hl-kw\">fn</span> f() -&gt; u8 { \
+             <span class=\"hl-num\">42</span> }"
+        );
+    }
+
+    #[test]
+    fn test_highlight_other_languages() {
+        assert!(highlight("SELECT * FROM t -- all", "sql").starts_with("<span class=\"hl-kw\">SELECT</span>"));
+        assert!(highlight("echo 'it\\'s' # hi", "sh").contains("<span class=\"hl-com\"># hi</span>"));
+        assert_eq!(highlight("let <x>", "brainfuck"), "let &lt;x&gt;");
+    }
+}
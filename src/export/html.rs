@@ -0,0 +1,225 @@
+use super::highlight::highlight;
+use crate::til::writer::extract_title;
+use crate::{markdown, publish};
+use anyhow::{Context, Result};
+use pulldown_cmark::{html, CodeBlockKind, CowStr, Event, Options, Parser, Tag, TagEnd};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Styles for the page and its highlighted code, so it looks the same wherever it's opened
+const STYLE: &str = r#"body { font-family: -apple-system, "Segoe UI", Helvetica, Arial, sans-serif; color: #1f2328;
+       max-width: 46em; margin: 2em auto; padding: 0 1em; line-height: 1.6; }
+h1, h2, h3 { line-height: 1.25; }
+h1, h2 { border-bottom: 1px solid #d1d9e0; padding-bottom: .3em; }
+a { color: #0969da; }
+code { font-family: ui-monospace, Menlo, Consolas, monospace; font-size: 90%;
+       background: #f6f8fa; padding: .2em .4em; border-radius: 4px; }
+pre { background: #f6f8fa; padding: 1em; overflow-x: auto; border-radius: 6px; }
+pre code { background: none; padding: 0; }
+blockquote { margin: 0; padding: 0 1em; color: #59636e; border-left: .25em solid #d1d9e0; }
+table { border-collapse: collapse; }
+th, td { border: 1px solid #d1d9e0; padding: .4em .8em; }
+img { max-width: 100%; }
+.hl-kw { color: #cf222e; }
+.hl-str { color: #0a3069; }
+.hl-com { color: #6e7781; font-style: italic; }
+.hl-num { color: #0550ae; }"#;
+
+/// Where relative links in an entry point once it's been taken out of the repo
+pub struct LinkBase<'a> {
+    /// The entry's directory, which its relative links and images are relative to
+    pub dir: &'a Path,
+    /// The TIL repo and where it can be browsed (`[feed] url`): links to files in it go there
+    /// instead of to the local file
+    pub repo: Option<(&'a Path, &'a str)>,
+}
+
+impl LinkBase<'_> {
+    /// An absolute URL for a link written in the entry, or `None` to leave it as it is
+    fn resolve(&self, link: &str) -> Option<String> {
+        if link.is_empty() || link.starts_with('#') || link.contains("://") || link.starts_with("mailto:") {
+            return None;
+        }
+        let (target, fragment) = link.split_once('#').map_or((link, None), |(target, f)| (target, Some(f)));
+        let path = normalize(&self.dir.join(target));
+        let url = match self.repo.and_then(|(root, url)| Some((path.strip_prefix(root).ok()?, url))) {
+            Some((relative, url)) => {
+                format!("{}/{}", url.trim_end_matches('/'), relative.to_string_lossy().replace('\\', "/"))
+            }
+            None => format!("file://{}", path.display()),
+        };
+        Some(match fragment {
+            Some(fragment) => format!("{}#{}", url, fragment),
+            None => url,
+        })
+    }
+
+    /// A local image as a data URI, so the page doesn't need the file next to it
+    fn embed_image(&self, src: &str) -> Option<String> {
+        if src.contains("://") || src.starts_with("data:") {
+            return None;
+        }
+        let path = self.dir.join(src);
+        let mime = match path.extension()?.to_string_lossy().to_lowercase().as_str() {
+            "png" => "image/png",
+            "jpg" | "jpeg" => "image/jpeg",
+            "gif" => "image/gif",
+            "webp" => "image/webp",
+            "svg" => "image/svg+xml",
+            _ => return None,
+        };
+        let bytes = fs::read(&path).ok()?;
+        Some(format!("data:{};base64,{}", mime, base64(&bytes)))
+    }
+}
+
+/// A TIL or note as a single HTML file that needs nothing else: styles and code highlighting
+/// inline, local images embedded and relative links made absolute. Frontmatter and private parts
+/// are left out.
+pub fn render_page(content: &str, links: &LinkBase) -> String {
+    let public = publish::public_content(content);
+    let body = markdown::split_frontmatter(&public).map_or(public.as_str(), |(_, body)| body);
+    let title = extract_title(body).unwrap_or_else(|| "Untitled".to_string());
+
+    let mut events = Vec::new();
+    let mut code: Option<(String, String)> = None;
+    for event in Parser::new_ext(body, Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH) {
+        match event {
+            Event::Start(Tag::CodeBlock(kind)) => {
+                let lang = match kind {
+                    CodeBlockKind::Fenced(info) => info.split_whitespace().next().unwrap_or_default().to_string(),
+                    CodeBlockKind::Indented => String::new(),
+                };
+                code = Some((lang, String::new()));
+            }
+            Event::Text(text) if code.is_some() => {
+                if let Some((_, ref mut block)) = code {
+                    block.push_str(&text);
+                }
+            }
+            Event::End(TagEnd::CodeBlock) => {
+                let (lang, block) = code.take().unwrap_or_default();
+                let class = match lang.as_str() {
+                    "" => String::new(),
+                    lang => format!(" class=\"language-{}\"", escape(lang)),
+                };
+                events.push(Event::Html(CowStr::from(format!(
+                    "<pre><code{}>{}</code></pre>\n",
+                    class,
+                    highlight(&block, &lang)
+                ))));
+            }
+            Event::Start(Tag::Image { link_type, dest_url, title, id }) => {
+                let dest_url = links.embed_image(&dest_url).map_or(dest_url, CowStr::from);
+                events.push(Event::Start(Tag::Image { link_type, dest_url, title, id }));
+            }
+            Event::Start(Tag::Link { link_type, dest_url, title, id }) => {
+                let dest_url = links.resolve(&dest_url).map_or(dest_url, CowStr::from);
+                events.push(Event::Start(Tag::Link { link_type, dest_url, title, id }));
+            }
+            event => events.push(event),
+        }
+    }
+
+    let mut rendered = String::new();
+    html::push_html(&mut rendered, events.into_iter());
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n\
+         <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n\
+         <title>{}</title>\n<style>\n{}\n</style>\n</head>\n<body>\n{}</body>\n</html>\n",
+        escape(&title),
+        STYLE,
+        rendered
+    )
+}
+
+/// Write `html` to `out`, creating its directory if needed
+pub fn write(out: &Path, html: &str) -> Result<()> {
+    if let Some(dir) = out.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        fs::create_dir_all(dir).with_context(|| format!("Failed to create directory: {:?}", dir))?;
+    }
+    fs::write(out, html).with_context(|| format!("Failed to write {:?}", out))
+}
+
+/// `..` and `.` worked out without touching disk, so links to missing files still resolve
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            std::path::Component::ParentDir => {
+                normalized.pop();
+            }
+            std::path::Component::CurDir => {}
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = chunk.iter().enumerate().fold(0u32, |n, (i, &byte)| n | u32::from(byte) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(char::from(ALPHABET[(n >> (18 - 6 * i) & 63) as usize]));
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_base64() {
+        assert_eq!(base64(b""), "");
+        assert_eq!(base64(b"f"), "Zg==");
+        assert_eq!(base64(b"fo"), "Zm8=");
+        assert_eq!(base64(b"foobar"), "Zm9vYmFy");
+    }
+
+    #[test]
+    fn test_render_page() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let dir = temp_dir.path().join("archive/rust");
+        fs::create_dir_all(&dir)?;
+        fs::write(dir.join("diagram.png"), b"png")?;
+        let content = "---\ntags: [rust]\n---\n\n# Pin & Unpin\n\n\
+                       ![Diagram](diagram.png) ![Remote](https://a.com/x.png)\n\n\
+                       See [borrowing](../rust/borrow.md#rules) and [docs](https://doc.rust-lang.org).\n\n\
+                       ```rust\nlet x = 1;\n```\n\nSecret <!-- private -->\n";
+        let links = LinkBase {
+            dir: &dir,
+            repo: Some((temp_dir.path(), "https://github.com/jo/til/blob/main")),
+        };
+
+        let page = render_page(content, &links);
+
+        assert!(page.contains("<title>Pin &amp; Unpin</title>"));
+        assert!(page.contains("<img src=\"data:image/png;base64,cG5n\" alt=\"Diagram\" />"));
+        assert!(page.contains("<img src=\"https://a.com/x.png\""));
+        assert!(page.contains("href=\"https://github.com/jo/til/blob/main/archive/rust/borrow.md#rules\""));
+        assert!(page.contains("href=\"https://doc.rust-lang.org\""));
+        assert!(page.contains("<pre><code class=\"language-rust\"><span class=\"hl-kw\">let</span> x = "));
+        assert!(!page.contains("tags:"));
+        assert!(!page.contains("Secret"));
+
+        let local = render_page("[b](b.md)", &LinkBase { dir: &dir, repo: None });
+        assert!(local.contains(&format!("href=\"file://{}\"", dir.join("b.md").display())));
+        Ok(())
+    }
+}
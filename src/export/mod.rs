@@ -0,0 +1,39 @@
+pub mod book;
+pub mod feed;
+pub mod highlight;
+pub mod html;
+
+use crate::markdown;
+use anyhow::{anyhow, Result};
+use std::path::Path;
+
+/// Refuse to export a page whose images have no alt text, naming the first one missing it
+pub fn require_alt_text(path: &Path, content: &str) -> Result<()> {
+    match markdown::images_missing_alt(content).first() {
+        Some(image) => Err(anyhow!(
+            "{}:{}: image {} has no alt text. Add a description before exporting",
+            path.display(),
+            image.line,
+            image.target
+        )),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_require_alt_text() -> Result<()> {
+        let path = Path::new("archive/rust/pin.md");
+        require_alt_text(path, "# Pin\n\n![Diagram](pin.png)\n")?;
+
+        let err = require_alt_text(path, "# Pin\n\n![Diagram](pin.png)\n\n<img src=\"heap.png\" alt=\" \">\n")
+            .err()
+            .map(|e| e.to_string())
+            .unwrap_or_default();
+        assert!(err.starts_with("archive/rust/pin.md:5: image heap.png has no alt text"));
+        Ok(())
+    }
+}
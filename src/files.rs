@@ -0,0 +1,121 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Lock files live here, next to the file they guard (the TIL or notes repo root)
+const LOCK_DIR: &str = ".holocron/locks";
+
+/// Tells apart temp files written at once by threads of one process
+static TEMP_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// An exclusive advisory lock on a file, held until it's dropped. Other holocron processes
+/// wait for it; editors and other tools don't, which is what [`write_atomic`] is for.
+pub struct FileLock {
+    _file: File,
+}
+
+/// Wait until no other holocron process is changing `path`, for a read-modify-write. The lock is
+/// on a separate file, since the locked file itself gets replaced by [`write_atomic`].
+pub fn lock(path: &Path) -> io::Result<FileLock> {
+    let lock_path = lock_path(path);
+    if let Some(dir) = lock_path.parent() {
+        fs::create_dir_all(dir)?;
+        fs::write(dir.join(".gitignore"), "*\n")?;
+    }
+    let file = OpenOptions::new().create(true).truncate(false).write(true).open(&lock_path)?;
+    file.lock()?;
+    Ok(FileLock { _file: file })
+}
+
+/// Write `contents` to a temporary file beside `path` and rename it into place, so anyone
+/// reading `path` (or a crash halfway) sees the old contents or the new, never a mix
+pub fn write_atomic(path: &Path, contents: impl AsRef<[u8]>) -> io::Result<()> {
+    let temp_path = temp_path(path);
+    let result = write_temp(path, &temp_path, contents.as_ref()).and_then(|()| fs::rename(&temp_path, path));
+    if result.is_err() {
+        let _ = fs::remove_file(&temp_path);
+    }
+    result
+}
+
+fn write_temp(path: &Path, temp_path: &Path, contents: &[u8]) -> io::Result<()> {
+    let mut file = OpenOptions::new().write(true).create_new(true).open(temp_path)?;
+    file.write_all(contents)?;
+    file.sync_all()?;
+    // Keep the replaced file's mode rather than the temp file's
+    if let Ok(metadata) = fs::metadata(path) {
+        fs::set_permissions(temp_path, metadata.permissions())?;
+    }
+    Ok(())
+}
+
+fn lock_path(path: &Path) -> PathBuf {
+    let dir = path.parent().unwrap_or_else(|| Path::new("."));
+    let name = path.file_name().map(|name| name.to_string_lossy()).unwrap_or_default();
+    dir.join(LOCK_DIR).join(format!("{}.lock", name))
+}
+
+/// Hidden and unique to this write, in the same directory so the rename stays on one filesystem
+fn temp_path(path: &Path) -> PathBuf {
+    let dir = path.parent().unwrap_or_else(|| Path::new("."));
+    let name = path.file_name().map(|name| name.to_string_lossy()).unwrap_or_default();
+    let count = TEMP_COUNTER.fetch_add(1, Ordering::Relaxed);
+    dir.join(format!(".{}.{}-{}.tmp", name, std::process::id(), count))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use std::sync::{Arc, Barrier};
+    use std::thread;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_write_atomic_replaces_and_leaves_no_temp_file() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let path = temp_dir.path().join("README.md");
+        fs::write(&path, "old")?;
+
+        write_atomic(&path, "new")?;
+
+        assert_eq!(fs::read_to_string(&path)?, "new");
+        let names: Vec<String> = fs::read_dir(temp_dir.path())?
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.file_name().to_string_lossy().to_string())
+            .collect();
+        assert_eq!(names, vec!["README.md"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_lock_serializes_read_modify_write() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let path = Arc::new(temp_dir.path().join("count"));
+        fs::write(path.as_ref(), "0")?;
+        let barrier = Arc::new(Barrier::new(8));
+
+        let workers: Vec<_> = (0..8)
+            .map(|_| {
+                let (path, barrier) = (Arc::clone(&path), Arc::clone(&barrier));
+                thread::spawn(move || -> io::Result<()> {
+                    barrier.wait();
+                    for _ in 0..10 {
+                        let _lock = lock(&path)?;
+                        let count: u32 = fs::read_to_string(path.as_ref())?.parse().unwrap_or_default();
+                        write_atomic(&path, (count + 1).to_string())?;
+                    }
+                    Ok(())
+                })
+            })
+            .collect();
+        for worker in workers {
+            worker.join().map_err(|_| anyhow::anyhow!("worker panicked"))??;
+        }
+
+        assert_eq!(fs::read_to_string(path.as_ref())?, "80");
+        assert!(temp_dir.path().join(".holocron/locks/count.lock").exists());
+        Ok(())
+    }
+}
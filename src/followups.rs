@@ -0,0 +1,76 @@
+use crate::claude::run_claude_command;
+use crate::session::Session;
+use anyhow::Result;
+
+/// How many follow-up questions to suggest after each response
+pub const MAX_FOLLOWUPS: usize = 3;
+
+/// Ask the backend for follow-up questions based on the conversation so far
+pub fn suggest_followups(session: &Session) -> Result<Vec<String>> {
+    let prompt = build_followup_prompt(session);
+    let response = run_claude_command(&prompt, |_| {})?.text;
+    Ok(parse_followups(&response))
+}
+
+fn build_followup_prompt(session: &Session) -> String {
+    format!(
+        r#"Here is a learning session so far:
+
+{}
+
+Suggest {} short follow-up questions the learner could ask next to deepen their understanding. Build on the most recent answer and avoid repeating what was already covered.
+
+Return ONLY the questions, one per line."#,
+        session.build_til_context(),
+        MAX_FOLLOWUPS
+    )
+}
+
+fn parse_followups(response: &str) -> Vec<String> {
+    response
+        .lines()
+        .map(|line| {
+            line.trim()
+                .trim_start_matches(|c: char| c.is_ascii_digit() || c == '.' || c == ')' || c == '-' || c == '*')
+                .trim()
+                .to_string()
+        })
+        .filter(|line| !line.is_empty())
+        .take(MAX_FOLLOWUPS)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session::LearningMode;
+
+    #[test]
+    fn test_parse_followups() {
+        let response = "1. How does Rc differ?\n\n2) What about Arc?\n- When do lifetimes matter?\n4. Extra";
+        assert_eq!(
+            parse_followups(response),
+            vec![
+                "How does Rc differ?".to_string(),
+                "What about Arc?".to_string(),
+                "When do lifetimes matter?".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_build_followup_prompt() {
+        let mut session = Session::new(
+            LearningMode::DeepDive {
+                topic: "Rust".to_string(),
+            },
+            None,
+        );
+        session.add_exchange("What is ownership?".to_string(), "Ownership is...".to_string());
+
+        let prompt = build_followup_prompt(&session);
+
+        assert!(prompt.contains("What is ownership?"));
+        assert!(prompt.contains("Suggest 3"));
+    }
+}
@@ -0,0 +1,116 @@
+use crate::http;
+use crate::modes::GithubRepo;
+use anyhow::{anyhow, Context, Result};
+use serde_json::Value;
+
+const API_URL: &str = "https://api.github.com/repos";
+const RAW_URL: &str = "https://raw.githubusercontent.com";
+
+/// Longest README, diff or file put in a prompt, in characters; the rest is cut
+const MAX_CHARS: usize = 60_000;
+
+/// Most file paths listed for a repository
+const MAX_FILES: usize = 400;
+
+/// What a repository's opening prompt is built from
+#[derive(Debug, Default)]
+pub struct RepoContents {
+    pub readme: String,
+    /// Paths of the files in its default branch
+    pub files: Vec<String>,
+}
+
+/// What a pull request's opening prompt is built from
+#[derive(Debug, Default)]
+pub struct PullRequest {
+    pub title: String,
+    pub description: String,
+    pub diff: String,
+}
+
+/// Fetch a repository's README and file list. A repo without a README still has files to look at.
+pub fn fetch_repo(repo: &GithubRepo, token: Option<&str>) -> Result<RepoContents> {
+    let info = api_json(&format!("{}/{}", API_URL, repo), token)?;
+    let branch = info["default_branch"].as_str().unwrap_or("HEAD");
+    let readme = api_get(&format!("{}/{}/readme", API_URL, repo), "application/vnd.github.raw", token)
+        .unwrap_or_default();
+    let tree = api_json(&format!("{}/{}/git/trees/{}?recursive=1", API_URL, repo, branch), token)?;
+    let files = tree["tree"]
+        .as_array()
+        .map(|entries| {
+            entries
+                .iter()
+                .filter(|entry| entry["type"] == "blob")
+                .filter_map(|entry| entry["path"].as_str().map(str::to_string))
+                .take(MAX_FILES)
+                .collect()
+        })
+        .unwrap_or_default();
+
+    Ok(RepoContents {
+        readme: truncate(&readme),
+        files,
+    })
+}
+
+/// Fetch a pull request's title, description and diff
+pub fn fetch_pull_request(repo: &GithubRepo, number: u64, token: Option<&str>) -> Result<PullRequest> {
+    let url = format!("{}/{}/pulls/{}", API_URL, repo, number);
+    let info = api_json(&url, token)?;
+    let diff = api_get(&url, "application/vnd.github.diff", token)?;
+    Ok(PullRequest {
+        title: info["title"].as_str().unwrap_or_default().to_string(),
+        description: info["body"].as_str().unwrap_or_default().to_string(),
+        diff: truncate(&diff),
+    })
+}
+
+/// Fetch one file's content at `git_ref`
+pub fn fetch_file(repo: &GithubRepo, git_ref: &str, path: &str, token: Option<&str>) -> Result<String> {
+    let url = format!("{}/{}/{}/{}", RAW_URL, repo, git_ref, path);
+    let auth = token.map(|token| format!("Bearer {}", token));
+    let mut headers = vec![("User-Agent", "holocron")];
+    if let Some(ref auth) = auth {
+        headers.push(("Authorization", auth));
+    }
+    Ok(truncate(&http::get(&url, &headers)?))
+}
+
+fn api_json(url: &str, token: Option<&str>) -> Result<Value> {
+    let body = api_get(url, "application/vnd.github+json", token)?;
+    serde_json::from_str(&body).with_context(|| format!("Invalid response from {}", url))
+}
+
+fn api_get(url: &str, accept: &str, token: Option<&str>) -> Result<String> {
+    let auth = token.map(|token| format!("Bearer {}", token));
+    let mut headers = vec![("Accept", accept), ("User-Agent", "holocron")];
+    if let Some(ref auth) = auth {
+        headers.push(("Authorization", auth));
+    }
+    http::get(url, &headers).map_err(|e| anyhow!("GitHub request failed: {}", e))
+}
+
+/// Cut text longer than `MAX_CHARS` at a line break, saying that it was cut
+fn truncate(text: &str) -> String {
+    let Some((cut, _)) = text.char_indices().nth(MAX_CHARS) else {
+        return text.to_string();
+    };
+    let cut = text[..cut].rfind('\n').unwrap_or(cut);
+    format!("{}\n[… cut here: the rest is too long to include]", &text[..cut])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_truncate_at_a_line_break() {
+        assert_eq!(truncate("short\ntext"), "short\ntext");
+
+        let long = format!("{}\n{}", "a".repeat(MAX_CHARS - 10), "b".repeat(20));
+        let cut = truncate(&long);
+        assert!(cut.starts_with(&"a".repeat(MAX_CHARS - 10)));
+        assert!(!cut.contains('b'));
+        assert!(cut.ends_with("too long to include]"));
+    }
+}
@@ -0,0 +1,126 @@
+use crate::archive;
+use crate::theme::Themed;
+use anyhow::Result;
+use regex::Regex;
+use std::path::{Path, PathBuf};
+
+/// A line of a TIL, numbered from 1
+#[derive(Debug, Clone, PartialEq)]
+pub struct Line {
+    pub number: usize,
+    pub text: String,
+    pub is_match: bool,
+}
+
+/// The TILs to search: markdown files in the archive, skipping whatever the repo's .gitignore excludes
+pub fn files(archive_path: &Path, category: Option<&str>) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    if !archive_path.is_dir() {
+        return Ok(files);
+    }
+
+    for entry in ignore::WalkBuilder::new(archive_path).require_git(false).build() {
+        let path = entry?.into_path();
+        if path.extension().is_none_or(|ext| ext != "md") || !path.is_file() {
+            continue;
+        }
+        if category.is_some() && archive::entry_category(archive_path, &path).as_deref() != category {
+            continue;
+        }
+        files.push(path);
+    }
+
+    files.sort();
+    Ok(files)
+}
+
+/// The matching lines of a file and `context` lines around each, grouped into hunks
+/// where their context overlaps
+pub fn search(content: &str, regex: &Regex, context: usize) -> Vec<Vec<Line>> {
+    let lines: Vec<&str> = content.lines().collect();
+    let mut hunks: Vec<Vec<Line>> = Vec::new();
+    let mut shown_until = 0;
+
+    for (index, text) in lines.iter().enumerate() {
+        if !regex.is_match(text) {
+            continue;
+        }
+        let start = index.saturating_sub(context).max(shown_until);
+        let end = (index + context + 1).min(lines.len());
+
+        if hunks.is_empty() || start > shown_until {
+            hunks.push(Vec::new());
+        }
+        if let Some(hunk) = hunks.last_mut() {
+            for (number, text) in lines.iter().enumerate().take(end).skip(start) {
+                hunk.push(Line {
+                    number: number + 1,
+                    text: text.to_string(),
+                    is_match: regex.is_match(text),
+                });
+            }
+        }
+        shown_until = end;
+    }
+
+    hunks
+}
+
+/// A line with its matches highlighted
+pub fn highlight(text: &str, regex: &Regex) -> String {
+    let mut highlighted = String::new();
+    let mut last = 0;
+    for found in regex.find_iter(text) {
+        highlighted.push_str(&text[last..found.start()]);
+        highlighted.push_str(&found.as_str().matched().to_string());
+        last = found.end();
+    }
+    highlighted.push_str(&text[last..]);
+    highlighted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn numbers(hunks: &[Vec<Line>]) -> Vec<Vec<usize>> {
+        hunks.iter().map(|hunk| hunk.iter().map(|line| line.number).collect()).collect()
+    }
+
+    #[test]
+    fn test_search_merges_overlapping_context() -> Result<()> {
+        let content = "one\nmatch\nthree\nfour\nmatch\nsix\nseven\neight\nnine\nmatch";
+        let regex = Regex::new("match")?;
+
+        assert_eq!(numbers(&search(content, &regex, 0)), vec![vec![2], vec![5], vec![10]]);
+        assert_eq!(numbers(&search(content, &regex, 1)), vec![vec![1, 2, 3, 4, 5, 6], vec![9, 10]]);
+
+        let hunks = search(content, &regex, 1);
+        assert!(hunks[0][1].is_match);
+        assert!(!hunks[0][2].is_match);
+        assert!(search(content, &Regex::new("absent")?, 2).is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_files_respects_gitignore_and_category() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let archive = temp_dir.path().join("archive");
+        fs::create_dir_all(archive.join("postgres"))?;
+        fs::create_dir_all(archive.join("git/2024"))?;
+        fs::create_dir_all(archive.join("scratch"))?;
+        fs::write(archive.join("postgres/upsert.md"), "# Upsert")?;
+        fs::write(archive.join("git/2024/rebase.md"), "# Rebase")?;
+        fs::write(archive.join("scratch/wip.md"), "# WIP")?;
+        fs::write(temp_dir.path().join(".gitignore"), "scratch/\n")?;
+
+        assert_eq!(
+            files(&archive, None)?,
+            vec![archive.join("git/2024/rebase.md"), archive.join("postgres/upsert.md")]
+        );
+        assert_eq!(files(&archive, Some("git"))?, vec![archive.join("git/2024/rebase.md")]);
+        Ok(())
+    }
+}
@@ -0,0 +1,157 @@
+use crate::session::{format_duration, Session};
+use anyhow::{anyhow, Context, Result};
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+/// What was just saved, for `post_save` hooks
+pub struct SavedFile<'a> {
+    /// "til" or "note"
+    pub kind: &'a str,
+    /// A local path, or where remote storage put it
+    pub path: &'a str,
+    pub title: &'a str,
+    pub category: &'a str,
+    /// The session's mode, empty when the TIL didn't come from a session (e.g. a draft)
+    pub mode: &'a str,
+}
+
+/// Run a hook command through the shell with the given environment
+pub fn run_hook(command: &str, env: &[(String, String)]) -> Result<()> {
+    run(command, env, Stdio::inherit())
+}
+
+/// Run a `post_save` hook with `{path}` filled in. Its output goes to stderr, so it never mixes
+/// into `--output jsonl`.
+pub fn run_post_save_hook(command: &str, saved: &SavedFile) -> Result<()> {
+    let command = command.replace("{path}", &shell_quote(saved.path));
+    run(&command, &post_save_env(saved), Stdio::from(std::io::stderr()))
+}
+
+fn run(command: &str, env: &[(String, String)], stdout: Stdio) -> Result<()> {
+    let status = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .envs(env.iter().map(|(k, v)| (k, v)))
+        .stdout(stdout)
+        .status()
+        .with_context(|| format!("Failed to run hook: {}", command))?;
+
+    if !status.success() {
+        return Err(anyhow!("Hook exited with {}: {}", status, command));
+    }
+    Ok(())
+}
+
+/// Environment passed to `post_save` hooks
+pub fn post_save_env(saved: &SavedFile) -> Vec<(String, String)> {
+    vec![
+        ("HOLOCRON_KIND".to_string(), saved.kind.to_string()),
+        ("HOLOCRON_PATH".to_string(), saved.path.to_string()),
+        ("HOLOCRON_TITLE".to_string(), saved.title.to_string()),
+        ("HOLOCRON_CATEGORY".to_string(), saved.category.to_string()),
+        ("HOLOCRON_MODE".to_string(), saved.mode.to_string()),
+    ]
+}
+
+/// Single-quote a value for `sh`, so paths with spaces or quotes stay one argument
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
+
+/// Environment passed to `on_session_end` hooks
+pub fn session_end_env(session: &Session, transcript: Option<&Path>) -> Vec<(String, String)> {
+    let artifacts: Vec<String> = session
+        .artifacts
+        .iter()
+        .map(|p| p.display().to_string())
+        .collect();
+
+    vec![
+        ("HOLOCRON_TOPIC".to_string(), session.topic().to_string()),
+        ("HOLOCRON_MODE".to_string(), session.mode.to_string()),
+        ("HOLOCRON_CATEGORY".to_string(), session.category.clone().unwrap_or_default()),
+        ("HOLOCRON_EXCHANGES".to_string(), session.exchanges.len().to_string()),
+        ("HOLOCRON_DURATION".to_string(), format_duration(session.duration())),
+        ("HOLOCRON_TOKENS".to_string(), session.usage.total_tokens().to_string()),
+        ("HOLOCRON_COST_USD".to_string(), format!("{:.4}", session.usage.cost_usd)),
+        // One path per line
+        ("HOLOCRON_ARTIFACTS".to_string(), artifacts.join("\n")),
+        (
+            "HOLOCRON_TRANSCRIPT".to_string(),
+            transcript.map(|p| p.display().to_string()).unwrap_or_default(),
+        ),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session::LearningMode;
+    use std::fs;
+    use std::path::PathBuf;
+    use tempfile::TempDir;
+
+    fn env_value<'a>(env: &'a [(String, String)], key: &str) -> Option<&'a str> {
+        env.iter().find(|(k, _)| k == key).map(|(_, v)| v.as_str())
+    }
+
+    #[test]
+    fn test_session_end_env() {
+        let mode = LearningMode::DeepDive {
+            topic: "git rebase".to_string(),
+        };
+        let mut session = Session::new(mode, Some("git".to_string()));
+        session.add_exchange("q".to_string(), "a".to_string());
+        session.add_artifact(PathBuf::from("/til/archive/git/a.md"));
+        session.add_artifact(PathBuf::from("/notes/b.md"));
+
+        let env = session_end_env(&session, None);
+
+        assert_eq!(env_value(&env, "HOLOCRON_TOPIC"), Some("git rebase"));
+        assert_eq!(env_value(&env, "HOLOCRON_CATEGORY"), Some("git"));
+        assert_eq!(env_value(&env, "HOLOCRON_EXCHANGES"), Some("1"));
+        assert_eq!(
+            env_value(&env, "HOLOCRON_ARTIFACTS"),
+            Some("/til/archive/git/a.md\n/notes/b.md")
+        );
+        assert_eq!(env_value(&env, "HOLOCRON_TRANSCRIPT"), Some(""));
+    }
+
+    #[test]
+    fn test_run_hook_passes_env() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let out = temp_dir.path().join("out.txt");
+        let env = vec![("HOLOCRON_TOPIC".to_string(), "rust".to_string())];
+
+        run_hook(&format!("printf %s \"$HOLOCRON_TOPIC\" > {:?}", out), &env)?;
+
+        assert_eq!(fs::read_to_string(&out)?, "rust");
+        Ok(())
+    }
+
+    #[test]
+    fn test_run_post_save_hook() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let saved_path = temp_dir.path().join("it's saved.md");
+        fs::write(&saved_path, "# Rebase")?;
+        let out = temp_dir.path().join("out.txt");
+        let saved = SavedFile {
+            kind: "til",
+            path: &saved_path.display().to_string(),
+            title: "Rebase",
+            category: "git",
+            mode: "Deep Dive: git rebase",
+        };
+
+        let command = format!("cat {{path}} > {:?}; printf ' %s/%s' \"$HOLOCRON_KIND\" \"$HOLOCRON_CATEGORY\" >> {:?}", out, out);
+        run_post_save_hook(&command, &saved)?;
+
+        assert_eq!(fs::read_to_string(&out)?, "# Rebase til/git");
+        Ok(())
+    }
+
+    #[test]
+    fn test_run_hook_failure() {
+        assert!(run_hook("exit 3", &[]).is_err());
+    }
+}
@@ -0,0 +1,158 @@
+use anyhow::{anyhow, Context, Result};
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+/// Marker curl appends after the body so the status code can be split off
+const STATUS_MARKER: &str = "\n__HOLOCRON_STATUS__:";
+
+/// GET a URL and return the response body
+pub fn get(url: &str, headers: &[(&str, &str)]) -> Result<String> {
+    request("GET", url, headers, None, &[])
+}
+
+/// POST a JSON body and return the response body
+pub fn post_json(url: &str, headers: &[(&str, &str)], body: &serde_json::Value) -> Result<String> {
+    let mut headers = headers.to_vec();
+    headers.push(("Content-Type", "application/json"));
+    request("POST", url, &headers, Some(body.to_string().as_bytes()), &[])
+}
+
+/// POST a multipart form with text fields and one file upload, returning the response body
+pub fn post_file(
+    url: &str,
+    headers: &[(&str, &str)],
+    fields: &[(&str, &str)],
+    file_field: &str,
+    file: &Path,
+) -> Result<String> {
+    let mut form = Vec::new();
+    for (name, value) in fields {
+        form.push("--form-string".to_string());
+        form.push(format!("{}={}", name, value));
+    }
+    form.push("--form".to_string());
+    form.push(format!("{}=@{}", file_field, file.display()));
+    request("POST", url, headers, None, &form)
+}
+
+/// Run a request through curl, failing on non-2xx responses
+pub fn request(
+    method: &str,
+    url: &str,
+    headers: &[(&str, &str)],
+    body: Option<&[u8]>,
+    extra_args: &[String],
+) -> Result<String> {
+    let header_lines: Vec<String> = headers.iter().map(|(name, value)| format!("{}: {}", name, value)).collect();
+    let options: Vec<(&str, &str)> = header_lines.iter().map(|line| ("header", line.as_str())).collect();
+    run(method, url, &options, body, extra_args)
+}
+
+/// Like [`request`] without extra headers, logging in with `user:password` when given
+pub fn request_as(user: Option<&str>, method: &str, url: &str, body: Option<&[u8]>) -> Result<String> {
+    let options: Vec<(&str, &str)> = user.map(|user| ("user", user)).into_iter().collect();
+    run(method, url, &options, body, &[])
+}
+
+/// A curl config file (mode 0600) holding `options`, for passing secrets to curl with `--config`
+/// rather than on its command line, where other local users could read them. It's removed on drop.
+pub fn curl_config(options: &[(&str, &str)]) -> Result<tempfile::NamedTempFile> {
+    let mut file = tempfile::NamedTempFile::new().context("Failed to create a curl config file")?;
+    for (name, value) in options {
+        writeln!(file, "{} = \"{}\"", name, quote(value)).context("Failed to write a curl config file")?;
+    }
+    Ok(file)
+}
+
+/// Escape a value for a double-quoted curl config string
+fn quote(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+}
+
+/// Run curl with `options` (headers, credentials) in a config file
+fn run(
+    method: &str,
+    url: &str,
+    options: &[(&str, &str)],
+    body: Option<&[u8]>,
+    extra_args: &[String],
+) -> Result<String> {
+    let mut command = Command::new("curl");
+    command
+        .args(["--silent", "--show-error", "--location", "--request", method])
+        .args(["--write-out", &format!("{}%{{http_code}}", STATUS_MARKER)]);
+    let _options = if options.is_empty() {
+        None
+    } else {
+        let file = curl_config(options)?;
+        command.arg("--config").arg(file.path());
+        Some(file)
+    };
+    if body.is_some() {
+        command.args(["--data-binary", "@-"]);
+    }
+    command.args(extra_args);
+
+    let mut child = command
+        .arg(url)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| anyhow!("Failed to run curl (is it installed?): {}", e))?;
+
+    if let (Some(body), Some(mut stdin)) = (body, child.stdin.take()) {
+        stdin.write_all(body).context("Failed to send request body to curl")?;
+    }
+
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        return Err(anyhow!(
+            "Request to {} failed: {}",
+            url,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    let (body, status) = split_status(&String::from_utf8_lossy(&output.stdout))?;
+    if !(200..300).contains(&status) {
+        return Err(anyhow!("Request to {} returned HTTP {}: {}", url, status, body.trim()));
+    }
+    Ok(body)
+}
+
+fn split_status(output: &str) -> Result<(String, u16)> {
+    let (body, status) = output
+        .rsplit_once(STATUS_MARKER)
+        .ok_or_else(|| anyhow!("curl didn't report a status code"))?;
+    let status = status.trim().parse().context("Invalid status code from curl")?;
+    Ok((body.to_string(), status))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_status() -> Result<()> {
+        let (body, status) = split_status("{\"ok\":true}\n__HOLOCRON_STATUS__:201")?;
+        assert_eq!(body, "{\"ok\":true}");
+        assert_eq!(status, 201);
+
+        assert!(split_status("no marker").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_curl_config_quotes_values() -> Result<()> {
+        let file = curl_config(&[("header", "Authorization: Bearer a\"b\\c"), ("user", "me:pw")])?;
+        let content = std::fs::read_to_string(file.path())?;
+        assert_eq!(content, "header = \"Authorization: Bearer a\\\"b\\\\c\"\nuser = \"me:pw\"\n");
+        Ok(())
+    }
+}
@@ -1,6 +1,10 @@
-use anyhow::{Context, Result};
+use crate::archive;
+use crate::config::{ArchiveLayout, FilenameStyle, RepoConfig, REPO_CONFIG_FILE};
+use anyhow::{anyhow, Context, Result};
+use serde_json::{json, Value};
+use std::collections::HashMap;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 const TIL_SKILL: &str = r#"# /til - Generate TIL Entry
 
@@ -58,6 +62,7 @@ title: [Descriptive title]
 date: [YYYY-MM-DD]
 tags: [relevant, tags, as, list]
 aliases: [alternative, names]
+level: [beginner | intermediate | advanced]
 ---
 ```
 
@@ -104,44 +109,307 @@ A collection of concise write-ups on things I learn day to day.
 ---
 "#;
 
+/// Allowed tools holocron's skills need, merged into any existing `settings.json`
+const ALLOWED_TOOLS: [&str; 2] = ["WebFetch", "WebSearch"];
+
+/// Files init created, updated in place, or left alone because they already existed
+#[derive(Debug, Default)]
+pub struct InitReport {
+    pub created: Vec<String>,
+    pub updated: Vec<String>,
+    pub kept: Vec<String>,
+}
+
 /// Initialize a new TIL repository at the given path
-pub fn init_til_repo(path: &Path, archive_dir: &str) -> Result<()> {
+pub fn init_til_repo(path: &Path, archive_dir: &str) -> Result<InitReport> {
+    let mut report = InitReport::default();
+
     // Create main directory
     fs::create_dir_all(path)
         .with_context(|| format!("Failed to create TIL directory: {:?}", path))?;
 
     // Create archive directory
     let archive_path = path.join(archive_dir);
-    fs::create_dir_all(&archive_path)
-        .with_context(|| format!("Failed to create archive directory: {:?}", archive_path))?;
+    if archive_path.exists() {
+        report.kept.push(format!("{}/", archive_dir));
+    } else {
+        fs::create_dir_all(&archive_path)
+            .with_context(|| format!("Failed to create archive directory: {:?}", archive_path))?;
+        report.created.push(format!("{}/", archive_dir));
+    }
+
+    write_new(path, "README.md", README_TEMPLATE, &mut report)?;
+    install_skills(path, &mut report)?;
+
+    Ok(report)
+}
+
+/// How an existing TIL repository is organised
+#[derive(Debug, Clone, PartialEq)]
+pub struct RepoLayout {
+    pub archive_dir: String,
+    pub archive_layout: ArchiveLayout,
+    pub filename_style: FilenameStyle,
+    pub readme: ReadmeFormat,
+}
+
+/// The parts of a README holocron edits when saving a TIL
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReadmeFormat {
+    /// Has an "N TILs & Counting" line
+    pub counter: bool,
+    /// Has a "### Categories" list linking to each section
+    pub category_index: bool,
+    /// Heading level of the category sections (holocron writes `###`)
+    pub section_level: Option<usize>,
+}
+
+impl ReadmeFormat {
+    fn detect(readme: &str) -> Self {
+        let mut levels: HashMap<usize, usize> = HashMap::new();
+        for line in readme.lines() {
+            let level = line.chars().take_while(|&c| c == '#').count();
+            if level > 1 && line[level..].starts_with(' ') && !line.trim().ends_with("Categories") {
+                *levels.entry(level).or_default() += 1;
+            }
+        }
+
+        ReadmeFormat {
+            counter: readme.contains("TILs & Counting"),
+            category_index: readme.lines().any(|line| line.trim() == "### Categories"),
+            section_level: levels.into_iter().max_by_key(|&(level, count)| (count, level)).map(|(level, _)| level),
+        }
+    }
+
+    /// Ways this README differs from what holocron expects
+    pub fn warnings(&self) -> Vec<String> {
+        let mut warnings = Vec::new();
+        if !self.counter {
+            warnings.push("No \"N TILs & Counting\" line, so the count won't be kept up to date".to_string());
+        }
+        if !self.category_index {
+            warnings.push("No \"### Categories\" list, so new categories won't be listed at the top".to_string());
+        }
+        if let Some(level) = self.section_level.filter(|&level| level != 3) {
+            warnings.push(format!(
+                "Category sections use {} headings; new entries go under ### headings",
+                "#".repeat(level)
+            ));
+        }
+        warnings
+    }
+}
+
+/// Adopt an existing TIL repository: detect its layout, record it in `.holocron.toml`
+/// and install the skills, without overwriting anything already there
+pub fn adopt_til_repo(path: &Path) -> Result<(RepoLayout, InitReport)> {
+    if !path.is_dir() {
+        return Err(anyhow!("{:?} isn't a directory. Use `holocron init` to create a new repository", path));
+    }
+
+    let layout = detect_layout(path)?;
+    let mut report = InitReport::default();
+
+    if path.join(REPO_CONFIG_FILE).exists() {
+        report.kept.push(REPO_CONFIG_FILE.to_string());
+    } else {
+        RepoConfig {
+            archive_dir: Some(layout.archive_dir.clone()),
+            archive_layout: Some(layout.archive_layout),
+            filename_style: Some(layout.filename_style),
+            ..RepoConfig::default()
+        }
+        .save(path)?;
+        report.created.push(REPO_CONFIG_FILE.to_string());
+    }
+
+    install_skills(path, &mut report)?;
+
+    Ok((layout, report))
+}
+
+/// Work out where an existing repo keeps its TILs and how they're arranged
+pub fn detect_layout(path: &Path) -> Result<RepoLayout> {
+    let readme = fs::read_to_string(path.join("README.md")).unwrap_or_default();
+
+    let archive_dir = match archive_dir_from_links(&readme) {
+        Some(dir) if path.join(&dir).is_dir() => dir,
+        _ => archive_dir_from_tree(path)?,
+    };
+    let files = archive::markdown_files(&path.join(&archive_dir))?;
+
+    Ok(RepoLayout {
+        archive_layout: detect_archive_layout(&path.join(&archive_dir), &files),
+        filename_style: detect_filename_style(&files),
+        archive_dir,
+        readme: ReadmeFormat::detect(&readme),
+    })
+}
+
+/// The most common top-level directory among README links of the form `dir/category/file.md`
+fn archive_dir_from_links(readme: &str) -> Option<String> {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+
+    for link in readme.split("](").skip(1).filter_map(|rest| rest.split(')').next()) {
+        let link = link.trim_start_matches("./");
+        if !link.ends_with(".md") || link.contains("://") || link.split('/').count() < 3 {
+            continue;
+        }
+        if let Some(dir) = link.split('/').next() {
+            *counts.entry(dir).or_default() += 1;
+        }
+    }
+
+    counts
+        .into_iter()
+        .max_by_key(|&(dir, count)| (count, std::cmp::Reverse(dir)))
+        .map(|(dir, _)| dir.to_string())
+}
+
+/// The top-level directory holding the most markdown files inside subdirectories
+fn archive_dir_from_tree(path: &Path) -> Result<String> {
+    let mut best: Option<(usize, String)> = None;
+
+    for entry in fs::read_dir(path).with_context(|| format!("Failed to read {:?}", path))? {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().to_string();
+        if !entry.path().is_dir() || name.starts_with('.') {
+            continue;
+        }
+
+        let nested = archive::markdown_files(&entry.path())?
+            .iter()
+            .filter(|file| file.parent() != Some(entry.path().as_path()))
+            .count();
+        if nested > 0 && best.as_ref().is_none_or(|(count, _)| nested > *count) {
+            best = Some((nested, name));
+        }
+    }
+
+    best.map(|(_, name)| name).ok_or_else(|| {
+        anyhow!(
+            "Couldn't find TILs filed as <dir>/<category>/<file>.md in {:?}. Use `holocron init` to start fresh",
+            path
+        )
+    })
+}
+
+/// The layout most of the archive's files follow
+fn detect_archive_layout(archive_path: &Path, files: &[PathBuf]) -> ArchiveLayout {
+    let mut counts: HashMap<ArchiveLayout, usize> = HashMap::new();
+
+    for file in files {
+        let Some(dir) = file.parent().and_then(|dir| dir.strip_prefix(archive_path).ok()) else {
+            continue;
+        };
+        let numeric: Vec<bool> = dir
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy().chars().all(|c| c.is_ascii_digit()))
+            .collect();
+
+        let layout = match numeric.as_slice() {
+            [true, true, false, ..] => ArchiveLayout::ByYearMonth,
+            [false, true, ..] => ArchiveLayout::ByYear,
+            _ => ArchiveLayout::Flat,
+        };
+        *counts.entry(layout).or_default() += 1;
+    }
 
-    // Create .claude/commands directory
+    counts
+        .into_iter()
+        .max_by_key(|&(layout, count)| (count, layout == ArchiveLayout::Flat))
+        .map(|(layout, _)| layout)
+        .unwrap_or_default()
+}
+
+/// The filename style most of the archive's files follow
+fn detect_filename_style(files: &[PathBuf]) -> FilenameStyle {
+    let mut counts: HashMap<FilenameStyle, usize> = HashMap::new();
+
+    for file in files {
+        let Some(stem) = file.file_stem().map(|stem| stem.to_string_lossy()) else {
+            continue;
+        };
+        let dated = stem.len() > 11
+            && stem.as_bytes()[10] == b'-'
+            && chrono::NaiveDate::parse_from_str(&stem[..10], "%Y-%m-%d").is_ok();
+
+        let style = if dated {
+            FilenameStyle::DatePrefixedKebab
+        } else if stem.chars().any(|c| c.is_uppercase()) {
+            FilenameStyle::Preserve
+        } else if stem.contains('-') && !stem.contains('_') {
+            FilenameStyle::Kebab
+        } else {
+            FilenameStyle::Snake
+        };
+        *counts.entry(style).or_default() += 1;
+    }
+
+    counts
+        .into_iter()
+        .max_by_key(|&(style, count)| (count, style == FilenameStyle::Snake))
+        .map(|(style, _)| style)
+        .unwrap_or_default()
+}
+
+/// Write the /til and /note skills and Claude settings, keeping whatever is already there
+fn install_skills(path: &Path, report: &mut InitReport) -> Result<()> {
     let commands_path = path.join(".claude").join("commands");
     fs::create_dir_all(&commands_path)
         .with_context(|| format!("Failed to create commands directory: {:?}", commands_path))?;
 
-    // Write README.md
-    let readme_path = path.join("README.md");
-    if !readme_path.exists() {
-        fs::write(&readme_path, README_TEMPLATE)
-            .with_context(|| "Failed to write README.md")?;
+    write_new(path, ".claude/commands/til.md", TIL_SKILL, report)?;
+    write_new(path, ".claude/commands/note.md", NOTE_SKILL, report)?;
+
+    let settings = ".claude/settings.json";
+    let settings_path = path.join(settings);
+    if !settings_path.exists() {
+        return write_new(path, settings, CLAUDE_SETTINGS, report);
+    }
+
+    let existing = fs::read_to_string(&settings_path)
+        .with_context(|| format!("Failed to read {:?}", settings_path))?;
+    match merge_settings(&existing) {
+        Some(merged) => {
+            fs::write(&settings_path, merged).with_context(|| "Failed to write settings.json")?;
+            report.updated.push(settings.to_string());
+        }
+        None => report.kept.push(settings.to_string()),
     }
 
-    // Write /til skill
-    let til_skill_path = commands_path.join("til.md");
-    fs::write(&til_skill_path, TIL_SKILL)
-        .with_context(|| "Failed to write til.md skill")?;
+    Ok(())
+}
 
-    // Write /note skill
-    let note_skill_path = commands_path.join("note.md");
-    fs::write(&note_skill_path, NOTE_SKILL)
-        .with_context(|| "Failed to write note.md skill")?;
+/// Add holocron's allowed tools to existing Claude settings. `None` if nothing needs adding
+/// or the settings can't be parsed (in which case they're left untouched).
+fn merge_settings(existing: &str) -> Option<String> {
+    let mut settings: Value = serde_json::from_str(existing).ok()?;
+    let permissions = settings.as_object_mut()?.entry("permissions").or_insert_with(|| json!({}));
+    let allowed = permissions.as_object_mut()?.entry("allowedTools").or_insert_with(|| json!([]));
+    let allowed = allowed.as_array_mut()?;
+
+    let missing: Vec<&str> = ALLOWED_TOOLS
+        .into_iter()
+        .filter(|tool| !allowed.iter().any(|t| t == tool))
+        .collect();
+    if missing.is_empty() {
+        return None;
+    }
+    allowed.extend(missing.into_iter().map(Value::from));
 
-    // Write settings.json with allowed tools
-    let settings_path = path.join(".claude").join("settings.json");
-    fs::write(&settings_path, CLAUDE_SETTINGS)
-        .with_context(|| "Failed to write settings.json")?;
+    serde_json::to_string_pretty(&settings).ok().map(|s| format!("{}\n", s))
+}
 
+fn write_new(root: &Path, relative: &str, content: &str, report: &mut InitReport) -> Result<()> {
+    let path = root.join(relative);
+    if path.exists() {
+        report.kept.push(relative.to_string());
+        return Ok(());
+    }
+
+    fs::write(&path, content).with_context(|| format!("Failed to write {}", relative))?;
+    report.created.push(relative.to_string());
     Ok(())
 }
 
@@ -165,4 +433,85 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_adopt_til_repo_keeps_existing_files() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let repo = temp_dir.path();
+        fs::create_dir_all(repo.join("til/rust/2023"))?;
+        fs::create_dir_all(repo.join("til/git/2024"))?;
+        fs::create_dir_all(repo.join(".claude/commands"))?;
+        fs::write(repo.join("til/rust/2023/borrow.md"), "# Borrowing\n")?;
+        fs::write(repo.join("til/git/2024/rebase.md"), "# Rebase\n")?;
+        fs::write(
+            repo.join("README.md"),
+            "# TIL\n\n## Rust\n\n- [Borrowing](til/rust/2023/borrow.md)\n\n## Git\n\n- [Rebase](./til/git/2024/rebase.md)\n",
+        )?;
+        fs::write(repo.join(".claude/commands/til.md"), "my own skill")?;
+        fs::write(
+            repo.join(".claude/settings.json"),
+            r#"{"model":"opus","permissions":{"allowedTools":["WebFetch"]}}"#,
+        )?;
+
+        let (layout, report) = adopt_til_repo(repo)?;
+
+        assert_eq!(layout.archive_dir, "til");
+        assert_eq!(layout.archive_layout, ArchiveLayout::ByYear);
+        assert_eq!(layout.readme.warnings().len(), 3);
+        assert_eq!(fs::read_to_string(repo.join(".claude/commands/til.md"))?, "my own skill");
+        assert_eq!(report.created, vec![REPO_CONFIG_FILE, ".claude/commands/note.md"]);
+        assert_eq!(report.updated, vec![".claude/settings.json"]);
+
+        let settings: Value = serde_json::from_str(&fs::read_to_string(repo.join(".claude/settings.json"))?)?;
+        assert_eq!(settings["model"], "opus");
+        assert_eq!(settings["permissions"]["allowedTools"], json!(["WebFetch", "WebSearch"]));
+
+        let repo_config = fs::read_to_string(repo.join(REPO_CONFIG_FILE))?;
+        assert!(repo_config.contains("archive_dir = \"til\""));
+        assert!(repo_config.contains("archive_layout = \"by_year\""));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_detect_layout_without_readme_links() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let repo = temp_dir.path();
+        fs::create_dir_all(repo.join("docs"))?;
+        fs::create_dir_all(repo.join("entries/2024/06/rust"))?;
+        fs::write(repo.join("docs/index.md"), "")?;
+        fs::write(repo.join("entries/2024/06/rust/borrow.md"), "# Borrowing\n")?;
+        fs::write(repo.join("README.md"), "# TIL\n\n1 TILs & Counting\n\n### Categories\n")?;
+
+        let layout = detect_layout(repo)?;
+
+        assert_eq!(layout.archive_dir, "entries");
+        assert_eq!(layout.archive_layout, ArchiveLayout::ByYearMonth);
+        assert!(layout.readme.warnings().is_empty());
+        assert!(detect_layout(&repo.join("docs")).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_detect_filename_style() {
+        let style = |names: &[&str]| {
+            let files: Vec<PathBuf> = names.iter().map(|name| PathBuf::from(format!("til/rust/{}.md", name))).collect();
+            detect_filename_style(&files)
+        };
+        assert_eq!(style(&["how-to-rebase", "stash-untracked", "borrow_checker"]), FilenameStyle::Kebab);
+        assert_eq!(style(&["2024-06-03-how-to-rebase", "2024-06-04-lifetimes"]), FilenameStyle::DatePrefixedKebab);
+        assert_eq!(style(&["How_To_Rebase", "Lifetimes"]), FilenameStyle::Preserve);
+        assert_eq!(style(&["borrow_checker", "lifetimes"]), FilenameStyle::Snake);
+        // Ties and empty archives keep the default
+        assert_eq!(style(&["how-to-rebase", "borrow_checker"]), FilenameStyle::Snake);
+        assert_eq!(style(&[]), FilenameStyle::Snake);
+    }
+
+    #[test]
+    fn test_merge_settings() {
+        assert_eq!(merge_settings(CLAUDE_SETTINGS), None);
+        assert_eq!(merge_settings("not json"), None);
+        assert!(merge_settings("{}").is_some_and(|s| s.contains("WebSearch")));
+    }
 }
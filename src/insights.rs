@@ -0,0 +1,312 @@
+use crate::archive::{self, Entry};
+use crate::markdown;
+use crate::search;
+use crate::session::Session;
+use chrono::NaiveDate;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+
+/// A category with nothing new for this long is reported as dormant
+const DORMANT_DAYS: i64 = 60;
+
+/// How many categories and keywords the report lists
+const TOP_LIMIT: usize = 5;
+
+/// Stands in for the author of TILs that don't name one
+const UNATTRIBUTED: &str = "(no author)";
+
+/// One thing learned: a saved TIL, or a session from the history
+#[derive(Debug, Clone, PartialEq)]
+pub struct Record {
+    pub date: NaiveDate,
+    pub category: Option<String>,
+    /// Title words and tags
+    pub keywords: Vec<String>,
+    /// Who wrote it, from the TIL's `author` frontmatter
+    pub author: Option<String>,
+}
+
+impl Record {
+    /// A TIL as a record, dated by when it was added. Undated entries are skipped.
+    pub fn from_entry(entry: &Entry) -> Option<Self> {
+        let date = archive::entry_date(&entry.path)?;
+        let mut keywords = search::terms(&entry.title);
+        let mut author = None;
+        if let Ok(content) = fs::read_to_string(&entry.path) {
+            keywords.extend(markdown::tags(&content));
+            author = markdown::frontmatter_field(&content, "author").map(|a| a.trim_start_matches('@').to_string());
+        }
+        keywords.sort();
+        keywords.dedup();
+
+        Some(Self {
+            date,
+            category: entry.category.clone(),
+            keywords,
+            author,
+        })
+    }
+
+    pub fn from_session(session: &Session) -> Self {
+        Self {
+            date: session.started_at.date_naive(),
+            category: session.category.clone(),
+            keywords: search::terms(session.topic()),
+            author: None,
+        }
+    }
+}
+
+/// Trends in what's been learned, over the last `window_days` and all time
+#[derive(Debug, Default, PartialEq)]
+pub struct Insights {
+    pub window_days: i64,
+    pub total: usize,
+    /// TILs added in the window
+    pub recent: usize,
+    /// TILs added in the window before that, to show momentum
+    pub previous: usize,
+    /// Categories of recent TILs, most frequent first
+    pub recent_categories: Vec<(String, usize)>,
+    /// Title words and tags of recent TILs and sessions, most frequent first
+    pub recent_keywords: Vec<(String, usize)>,
+    /// Categories with nothing new for a while, with days since their latest entry
+    pub dormant: Vec<(String, i64)>,
+    /// Sessions started in the window
+    pub recent_sessions: usize,
+    /// TILs per author, all time and in the window, most prolific first. Empty unless some TIL
+    /// names its author, as in a shared team repo.
+    pub authors: Vec<(String, usize, usize)>,
+}
+
+/// Analyze TILs and past sessions as of `today`
+pub fn analyze(tils: &[Record], sessions: &[Record], today: NaiveDate, window_days: i64) -> Insights {
+    let age = |record: &Record| (today - record.date).num_days();
+    let recent: Vec<&Record> = tils.iter().filter(|r| age(r) < window_days).collect();
+    let recent_sessions: Vec<&Record> = sessions.iter().filter(|r| age(r) < window_days).collect();
+
+    let recent_categories = ranked(recent.iter().filter_map(|r| r.category.clone()));
+    let recent_keywords = ranked(
+        recent
+            .iter()
+            .chain(&recent_sessions)
+            .flat_map(|r| r.keywords.iter().cloned()),
+    );
+
+    let mut latest: BTreeMap<String, NaiveDate> = BTreeMap::new();
+    for record in tils {
+        if let Some(ref category) = record.category {
+            let date = latest.entry(category.clone()).or_insert(record.date);
+            *date = (*date).max(record.date);
+        }
+    }
+    let mut dormant: Vec<(String, i64)> = latest
+        .into_iter()
+        .map(|(category, date)| (category, (today - date).num_days()))
+        .filter(|(_, days)| *days >= DORMANT_DAYS)
+        .collect();
+    dormant.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+    let author = |record: &Record| record.author.clone().unwrap_or_else(|| UNATTRIBUTED.to_string());
+    let authors = match tils.iter().any(|record| record.author.is_some()) {
+        true => {
+            let recent_authors = ranked(recent.iter().map(|r| author(r)));
+            ranked(tils.iter().map(author))
+                .into_iter()
+                .map(|(name, total)| {
+                    let in_window = recent_authors.iter().find(|(n, _)| *n == name).map_or(0, |(_, count)| *count);
+                    (name, total, in_window)
+                })
+                .collect()
+        }
+        false => Vec::new(),
+    };
+
+    Insights {
+        window_days,
+        total: tils.len(),
+        recent: recent.len(),
+        previous: tils
+            .iter()
+            .filter(|r| (window_days..window_days * 2).contains(&age(r)))
+            .count(),
+        recent_categories,
+        recent_keywords,
+        dormant,
+        recent_sessions: recent_sessions.len(),
+        authors,
+    }
+}
+
+impl Insights {
+    /// Plain-language trends, e.g. "60% of recent TILs are about postgres"
+    pub fn trends(&self) -> Vec<String> {
+        let mut trends = Vec::new();
+
+        if self.recent == 0 {
+            trends.push(format!("No TILs in the last {} days", self.window_days));
+        } else {
+            let direction = match self.recent.cmp(&self.previous) {
+                std::cmp::Ordering::Greater => "up from",
+                std::cmp::Ordering::Less => "down from",
+                std::cmp::Ordering::Equal => "the same as",
+            };
+            trends.push(format!(
+                "{} TIL(s) in the last {} days, {} {} the {} days before",
+                self.recent, self.window_days, direction, self.previous, self.window_days
+            ));
+        }
+
+        if let Some((category, count)) = self.recent_categories.first() {
+            let percent = count * 100 / self.recent;
+            if self.recent >= 3 && percent >= 40 {
+                trends.push(format!("{}% of recent TILs are about {}", percent, category));
+            }
+        }
+
+        for (category, days) in &self.dormant {
+            trends.push(format!("No {} entries in {} months", category, days / 30));
+        }
+
+        trends
+    }
+}
+
+/// Build the prompt asking the backend which topics are worth revisiting
+pub fn build_suggestions_prompt(insights: &Insights) -> String {
+    let list = |items: &[(String, usize)]| {
+        items
+            .iter()
+            .take(TOP_LIMIT * 2)
+            .map(|(name, count)| format!("{} ({})", name, count))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    let dormant = insights
+        .dormant
+        .iter()
+        .map(|(category, days)| format!("{} ({} days ago)", category, days))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    format!(
+        r#"Here is a summary of my Today I Learned archive ({} entries).
+
+Trends:
+- {}
+
+Categories in the last {} days: {}
+Recurring keywords: {}
+Categories I haven't touched in a while, with when I last added to them: {}
+
+Suggest 3-5 topics I should revisit or learn next, and why. Favour gaps next to what I've been studying and dormant areas worth refreshing. Keep each suggestion to one or two sentences."#,
+        insights.total,
+        insights.trends().join("\n- "),
+        insights.window_days,
+        list(&insights.recent_categories),
+        list(&insights.recent_keywords),
+        if dormant.is_empty() { "none".to_string() } else { dormant },
+    )
+}
+
+/// The top of a ranked list, for display
+pub fn top(items: &[(String, usize)]) -> &[(String, usize)] {
+    &items[..items.len().min(TOP_LIMIT)]
+}
+
+/// Count occurrences, most frequent first (ties alphabetically)
+fn ranked(items: impl Iterator<Item = String>) -> Vec<(String, usize)> {
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for item in items {
+        *counts.entry(item).or_insert(0) += 1;
+    }
+    let mut ranked: Vec<(String, usize)> = counts.into_iter().collect();
+    ranked.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    ranked
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::{anyhow, Result};
+
+    fn record(date: &str, category: &str, keywords: &[&str]) -> Result<Record> {
+        Ok(Record {
+            date: NaiveDate::parse_from_str(date, "%Y-%m-%d")?,
+            category: Some(category.to_string()),
+            keywords: keywords.iter().map(|k| k.to_string()).collect(),
+            author: None,
+        })
+    }
+
+    #[test]
+    fn test_analyze_reports_trends() -> Result<()> {
+        let today = NaiveDate::from_ymd_opt(2024, 6, 30).ok_or_else(|| anyhow!("bad date"))?;
+        let tils = vec![
+            record("2024-06-25", "postgres", &["vacuum"])?,
+            record("2024-06-20", "postgres", &["indexes"])?,
+            record("2024-06-10", "postgres", &["vacuum", "autovacuum"])?,
+            record("2024-06-05", "git", &["rebase"])?,
+            record("2024-05-20", "postgres", &["explain"])?,
+            record("2024-03-01", "rust", &["lifetimes"])?,
+        ];
+        let sessions = vec![record("2024-06-29", "postgres", &["vacuum"])?];
+
+        let insights = analyze(&tils, &sessions, today, 30);
+        assert_eq!(insights.recent, 4);
+        assert_eq!(insights.previous, 1);
+        assert_eq!(insights.recent_sessions, 1);
+        assert_eq!(insights.recent_categories[0], ("postgres".to_string(), 3));
+        assert_eq!(insights.recent_keywords[0], ("vacuum".to_string(), 3));
+        assert_eq!(insights.dormant, vec![("rust".to_string(), 121)]);
+        assert!(insights.authors.is_empty(), "no TIL names its author");
+
+        assert_eq!(
+            insights.trends(),
+            vec![
+                "4 TIL(s) in the last 30 days, up from 1 the 30 days before".to_string(),
+                "75% of recent TILs are about postgres".to_string(),
+                "No rust entries in 4 months".to_string(),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_analyze_counts_authors() -> Result<()> {
+        let today = NaiveDate::from_ymd_opt(2024, 6, 30).ok_or_else(|| anyhow!("bad date"))?;
+        let by = |author: Option<&str>, record: Record| Record {
+            author: author.map(str::to_string),
+            ..record
+        };
+        let tils = vec![
+            by(Some("jane"), record("2024-06-25", "postgres", &["vacuum"])?),
+            by(Some("jane"), record("2024-03-01", "rust", &["lifetimes"])?),
+            by(Some("omar"), record("2024-06-20", "git", &["rebase"])?),
+            record("2024-06-10", "git", &["bisect"])?,
+        ];
+
+        let insights = analyze(&tils, &[], today, 30);
+        assert_eq!(
+            insights.authors,
+            vec![
+                ("jane".to_string(), 2, 1),
+                (UNATTRIBUTED.to_string(), 1, 1),
+                ("omar".to_string(), 1, 1),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_build_suggestions_prompt() -> Result<()> {
+        let today = NaiveDate::from_ymd_opt(2024, 6, 30).ok_or_else(|| anyhow!("bad date"))?;
+        let insights = analyze(&[record("2024-06-25", "sql", &["joins"])?], &[], today, 30);
+        let prompt = build_suggestions_prompt(&insights);
+        assert!(prompt.contains("Categories in the last 30 days: sql (1)"));
+        assert!(prompt.contains("Recurring keywords: joins (1)"));
+        assert!(prompt.contains("haven't touched in a while, with when I last added to them: none"));
+        Ok(())
+    }
+
+}
@@ -0,0 +1,103 @@
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+use std::io::{self, Write};
+use std::path::Path;
+
+/// How a session reports what's happening
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OutputFormat {
+    /// Colored terminal output with interactive prompts
+    Text,
+    /// One JSON event per line on stdout, for other programs to drive
+    Jsonl,
+}
+
+impl OutputFormat {
+    pub fn parse(format: &str) -> Result<Self> {
+        match format.to_lowercase().as_str() {
+            "text" => Ok(OutputFormat::Text),
+            "jsonl" => Ok(OutputFormat::Jsonl),
+            _ => Err(anyhow!("Invalid output format. Use: text or jsonl")),
+        }
+    }
+}
+
+/// A machine-readable session event
+#[derive(Debug, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Event<'a> {
+    SessionStarted {
+        mode: String,
+        topic: &'a str,
+        category: Option<&'a str>,
+    },
+    TextDelta {
+        text: &'a str,
+    },
+    ToolUse {
+        name: &'a str,
+        input: &'a serde_json::Value,
+    },
+    /// A response finished; the next line on stdin is read as the following message
+    ResponseDone {
+        cost_usd: f64,
+    },
+    /// The conversation was summarized into a fresh backend session to stay within the context window
+    ContextCompacted {
+        tokens_before: u64,
+    },
+    /// The backend conversation had expired, so the session went on in a new one seeded with it
+    ConversationRestarted,
+    TilGenerated {
+        content: &'a str,
+    },
+    Saved {
+        kind: &'a str,
+        path: &'a Path,
+    },
+    Error {
+        message: String,
+    },
+    SessionEnded {
+        exchanges: usize,
+        cost_usd: f64,
+    },
+}
+
+/// Write one event as a line of JSON to stdout
+pub fn emit(event: &Event) {
+    if let Ok(line) = serde_json::to_string(event) {
+        let mut stdout = io::stdout().lock();
+        writeln!(stdout, "{}", line).ok();
+        stdout.flush().ok();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_output_format_parse() -> Result<()> {
+        assert_eq!(OutputFormat::parse("JSONL")?, OutputFormat::Jsonl);
+        assert_eq!(OutputFormat::parse("text")?, OutputFormat::Text);
+        assert!(OutputFormat::parse("xml").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_event_serialization() -> Result<()> {
+        assert_eq!(
+            serde_json::to_string(&Event::TextDelta { text: "Hi" })?,
+            r#"{"event":"text_delta","text":"Hi"}"#
+        );
+        assert_eq!(
+            serde_json::to_string(&Event::Saved {
+                kind: "til",
+                path: Path::new("/til/archive/rust/borrow.md"),
+            })?,
+            r#"{"event":"saved","kind":"til","path":"/til/archive/rust/borrow.md"}"#
+        );
+        Ok(())
+    }
+}
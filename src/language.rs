@@ -0,0 +1,47 @@
+/// How dates are written in languages whose order differs from ISO, keyed by the names and
+/// codes `config.language` is likely to hold
+const DATE_FORMATS: &[(&[&str], &str)] = &[
+    (&["german", "deutsch", "de"], "%d.%m.%Y"),
+    (&["polish", "polski", "pl"], "%d.%m.%Y"),
+    (&["russian", "русский", "ru"], "%d.%m.%Y"),
+    (&["french", "français", "francais", "fr"], "%d/%m/%Y"),
+    (&["spanish", "español", "espanol", "es"], "%d/%m/%Y"),
+    (&["italian", "italiano", "it"], "%d/%m/%Y"),
+    (&["portuguese", "português", "portugues", "pt"], "%d/%m/%Y"),
+    (&["dutch", "nederlands", "nl"], "%d-%m-%Y"),
+    (&["japanese", "日本語", "ja"], "%Y/%m/%d"),
+    (&["chinese", "中文", "zh"], "%Y/%m/%d"),
+];
+
+/// The chrono format for dates shown to a reader of `language`: ISO unless it's one we know
+pub fn date_format(language: Option<&str>) -> &'static str {
+    let Some(language) = language.map(|language| language.trim().to_lowercase()) else {
+        return "%Y-%m-%d";
+    };
+    DATE_FORMATS
+        .iter()
+        .find(|(names, _)| names.contains(&language.as_str()))
+        .map_or("%Y-%m-%d", |(_, format)| format)
+}
+
+/// The prompt instruction asking for generated TILs and notes to be written in `language`
+pub fn instruction(language: &str) -> String {
+    format!(
+        "Write it in {}: the title, headings, prose and code comments. Keep code, commands, identifiers and any \
+         frontmatter keys as they are, and frontmatter dates as YYYY-MM-DD.",
+        language.trim()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_date_format() {
+        assert_eq!(date_format(Some("German")), "%d.%m.%Y");
+        assert_eq!(date_format(Some(" fr ")), "%d/%m/%Y");
+        assert_eq!(date_format(Some("Klingon")), "%Y-%m-%d");
+        assert_eq!(date_format(None), "%Y-%m-%d");
+    }
+}
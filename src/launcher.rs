@@ -0,0 +1,92 @@
+use anyhow::{anyhow, Context, Result};
+use std::path::Path;
+use std::process::Command;
+
+/// Open a file in the user's `$VISUAL`/`$EDITOR` (falls back to `vi`)
+pub fn open_in_editor(path: &Path) -> Result<()> {
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .unwrap_or_else(|_| "vi".to_string());
+
+    // Support editors configured with arguments, e.g. "code --wait"
+    let mut parts = editor.split_whitespace();
+    let program = parts.next().ok_or_else(|| anyhow!("$EDITOR is empty"))?;
+
+    let status = Command::new(program)
+        .args(parts)
+        .arg(path)
+        .status()
+        .with_context(|| format!("Failed to launch editor: {}", editor))?;
+
+    if !status.success() {
+        return Err(anyhow!("Editor exited with {}", status));
+    }
+    Ok(())
+}
+
+/// Let the user edit text in their editor via a temp file, returning the edited text
+pub fn edit_text(content: &str, extension: &str) -> Result<String> {
+    let file = tempfile::Builder::new()
+        .prefix("holocron-")
+        .suffix(&format!(".{}", extension))
+        .tempfile()
+        .context("Failed to create temp file")?;
+
+    std::fs::write(file.path(), content).context("Failed to write temp file")?;
+    open_in_editor(file.path())?;
+    std::fs::read_to_string(file.path()).context("Failed to read edited file")
+}
+
+/// Open a path or URI with the platform's default handler
+pub fn open_with_system(target: &str) -> Result<()> {
+    let program = if cfg!(target_os = "macos") {
+        "open"
+    } else if cfg!(target_os = "windows") {
+        "explorer"
+    } else {
+        "xdg-open"
+    };
+
+    Command::new(program)
+        .arg(target)
+        .spawn()
+        .with_context(|| format!("Failed to run {}", program))?;
+    Ok(())
+}
+
+/// Build an `obsidian://open` URI for a file on disk
+pub fn obsidian_uri(path: &Path) -> String {
+    format!("obsidian://open?path={}", percent_encode(&path.to_string_lossy()))
+}
+
+/// Percent-encode everything outside the URI unreserved set
+pub fn percent_encode(s: &str) -> String {
+    let mut encoded = String::new();
+    for byte in s.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_percent_encode() {
+        assert_eq!(percent_encode("a b/c"), "a%20b%2Fc");
+        assert_eq!(percent_encode("safe-_.~"), "safe-_.~");
+    }
+
+    #[test]
+    fn test_obsidian_uri() {
+        let uri = obsidian_uri(&PathBuf::from("/vault/My Note.md"));
+        assert_eq!(uri, "obsidian://open?path=%2Fvault%2FMy%20Note.md");
+    }
+}
@@ -0,0 +1,101 @@
+use crate::claude::{ClaudeResponse, Usage};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Local};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// The opening analysis of a link, kept so revisiting the article doesn't pay for it again
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedAnalysis {
+    pub url: String,
+    pub text: String,
+    /// Backend session the analysis came from, so follow-ups keep its context
+    pub session_id: Option<String>,
+    pub cached_at: DateTime<Local>,
+    /// Sources the analysis cited, so a replayed session still collects them
+    #[serde(default)]
+    pub sources: Vec<String>,
+}
+
+impl CachedAnalysis {
+    /// The cached analysis as a response costing nothing
+    pub fn into_response(self) -> ClaudeResponse {
+        ClaudeResponse {
+            text: self.text,
+            session_id: self.session_id,
+            usage: Usage::default(),
+            sources: self.sources,
+        }
+    }
+}
+
+/// The cached analysis for this URL and prompt, if it's younger than `max_age_days`
+pub fn lookup(dir: &Path, url: &str, prompt: &str, max_age_days: u32) -> Option<CachedAnalysis> {
+    let content = fs::read_to_string(cache_path(dir, url, prompt)).ok()?;
+    let cached: CachedAnalysis = serde_json::from_str(&content).ok()?;
+    let fresh = Local::now() - cached.cached_at < Duration::days(i64::from(max_age_days));
+    fresh.then_some(cached)
+}
+
+/// Cache the analysis a link session opened with
+pub fn store(dir: &Path, url: &str, prompt: &str, response: &ClaudeResponse) -> Result<()> {
+    fs::create_dir_all(dir).with_context(|| format!("Failed to create {:?}", dir))?;
+
+    let cached = CachedAnalysis {
+        url: url.to_string(),
+        text: response.text.clone(),
+        session_id: response.session_id.clone(),
+        cached_at: Local::now(),
+        sources: response.sources.clone(),
+    };
+    let path = cache_path(dir, url, prompt);
+    let content = serde_json::to_string_pretty(&cached)?;
+    fs::write(&path, content).with_context(|| format!("Failed to write {:?}", path))
+}
+
+/// One file per URL + prompt, so a changed prompt misses the cache
+fn cache_path(dir: &Path, url: &str, prompt: &str) -> PathBuf {
+    dir.join(format!("{:016x}.json", fnv1a(&format!("{}\n{}", url.trim(), prompt))))
+}
+
+/// FNV-1a: stable across runs and Rust versions, unlike `DefaultHasher`
+fn fnv1a(text: &str) -> u64 {
+    text.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x100000001b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn response(text: &str) -> ClaudeResponse {
+        ClaudeResponse {
+            text: text.to_string(),
+            session_id: Some("abc".to_string()),
+            usage: Usage::default(),
+            sources: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_store_and_lookup() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        store(temp_dir.path(), "https://a.com", "Analyze https://a.com", &response("Summary"))?;
+
+        let cached = lookup(temp_dir.path(), "https://a.com", "Analyze https://a.com", 7);
+        assert_eq!(cached.map(|c| c.into_response().text).as_deref(), Some("Summary"));
+        assert!(lookup(temp_dir.path(), "https://a.com", "A new prompt", 7).is_none());
+        assert!(lookup(temp_dir.path(), "https://b.com", "Analyze https://a.com", 7).is_none());
+        assert!(lookup(temp_dir.path(), "https://a.com", "Analyze https://a.com", 0).is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_fnv1a_is_stable() {
+        assert_eq!(fnv1a(""), 0xcbf29ce484222325);
+        assert_eq!(fnv1a("a"), 0xaf63dc4c8601ec8c);
+    }
+}
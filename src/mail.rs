@@ -0,0 +1,148 @@
+use crate::config::EmailConfig;
+use crate::http;
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Local};
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+/// Longest encoded line before a soft break, as quoted-printable requires
+const MAX_LINE: usize = 76;
+
+/// An HTML email ready to send, with CRLF line endings and a quoted-printable body so long
+/// lines of HTML survive SMTP
+pub fn html_message(from: &str, to: &str, subject: &str, html: &str, date: DateTime<Local>) -> String {
+    let headers = [
+        format!("From: {}", from),
+        format!("To: {}", to),
+        format!("Subject: {}", subject),
+        format!("Date: {}", date.to_rfc2822()),
+        "MIME-Version: 1.0".to_string(),
+        "Content-Type: text/html; charset=utf-8".to_string(),
+        "Content-Transfer-Encoding: quoted-printable".to_string(),
+    ];
+    format!("{}\r\n\r\n{}\r\n", headers.join("\r\n"), quoted_printable(html))
+}
+
+/// Send a message with the configured command, or over SMTP with curl
+pub fn send(config: &EmailConfig, from: &str, to: &str, message: &str) -> Result<()> {
+    match (&config.command, &config.smtp_url) {
+        (Some(command), _) => run_command(command, message),
+        (None, Some(url)) => send_smtp(config, url, from, to, message),
+        (None, None) => Err(anyhow!(
+            "No way to send email configured. Set `command` (e.g. \"sendmail -t\") or `smtp_url` under [email] \
+             in the config file"
+        )),
+    }
+}
+
+fn run_command(command: &str, message: &str) -> Result<()> {
+    let mut child = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .with_context(|| format!("Failed to run mail command: {}", command))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(message.as_bytes()).context("Failed to send the message to the mail command")?;
+    }
+
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        return Err(anyhow!(
+            "Mail command exited with {}: {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(())
+}
+
+/// Hand the message to curl's SMTP client. Credentials go through a curl config file rather than
+/// the command line, where other users could read them, and require TLS.
+fn send_smtp(config: &EmailConfig, url: &str, from: &str, to: &str, message: &str) -> Result<()> {
+    let mut command = Command::new("curl");
+    command
+        .args(["--silent", "--show-error", "--url", url])
+        .args(["--mail-from", from, "--mail-rcpt", to, "--upload-file", "-"]);
+
+    let _credentials = match config.username {
+        Some(ref username) => {
+            let user = format!("{}:{}", username, config.password.as_deref().unwrap_or_default());
+            let file = http::curl_config(&[("user", &user)])?;
+            command.arg("--ssl-reqd").arg("--config").arg(file.path());
+            Some(file)
+        }
+        None => None,
+    };
+
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| anyhow!("Failed to run curl (is it installed?): {}", e))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(message.as_bytes()).context("Failed to send the message to curl")?;
+    }
+
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        return Err(anyhow!(
+            "Sending mail through {} failed: {}",
+            url,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(())
+}
+
+/// Quoted-printable encoding (RFC 2045): printable ASCII as is, everything else as `=XX`, with
+/// soft line breaks keeping lines within `MAX_LINE`
+fn quoted_printable(text: &str) -> String {
+    let mut lines = Vec::new();
+    for line in text.lines() {
+        let mut encoded = String::new();
+        let mut length = 0;
+        let bytes = line.as_bytes();
+        for (i, &byte) in bytes.iter().enumerate() {
+            let last = i + 1 == bytes.len();
+            let plain = (byte.is_ascii_graphic() && byte != b'=') || (byte == b' ' && !last);
+            let piece = if plain {
+                (byte as char).to_string()
+            } else {
+                format!("={:02X}", byte)
+            };
+            if length + piece.len() > MAX_LINE - 1 {
+                encoded.push_str("=\r\n");
+                length = 0;
+            }
+            length += piece.len();
+            encoded.push_str(&piece);
+        }
+        lines.push(encoded);
+    }
+    lines.join("\r\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quoted_printable() {
+        assert_eq!(quoted_printable("a=b café \n<p>x</p>"), "a=3Db caf=C3=A9=20\r\n<p>x</p>");
+
+        let long = quoted_printable(&"x".repeat(200));
+        assert!(long.split("\r\n").all(|line| line.len() <= MAX_LINE));
+        assert_eq!(long.replace("=\r\n", ""), "x".repeat(200));
+    }
+
+    #[test]
+    fn test_html_message() {
+        let message = html_message("me@a.com", "me@a.com", "Digest", "<p>Hi</p>", Local::now());
+        assert!(message.starts_with("From: me@a.com\r\nTo: me@a.com\r\nSubject: Digest\r\nDate: "));
+        assert!(message.contains("Content-Transfer-Encoding: quoted-printable\r\n\r\n<p>Hi</p>\r\n"));
+    }
+}
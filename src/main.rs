@@ -1,80 +1,412 @@
+mod alias;
+mod archive;
+mod backlinks;
+mod capture;
 mod claude;
 mod cli;
+mod compact;
 mod config;
+mod continuation;
+mod crypto;
+mod diff;
+mod digest;
+mod error;
+mod export;
+mod files;
+mod followups;
+mod github;
+mod grep;
+mod hooks;
+mod http;
 mod init;
+mod insights;
+mod jsonl;
+mod language;
+mod launcher;
+mod link_cache;
+mod mail;
+mod markdown;
 mod modes;
 mod notes;
+mod notify;
+mod output;
+mod plan;
+mod publish;
+mod queue;
+mod runner;
+mod save_prompt;
+mod search;
 mod session;
+mod session_store;
+mod settings;
+mod share;
+mod shell_history;
+mod stop_keys;
+mod storage;
+mod sync;
 mod til;
+mod timebox;
+mod transcribe;
+mod tui;
+mod theme;
+mod tutorial;
+mod undo;
+mod variables;
+mod verbosity;
+mod video;
+mod watch;
+mod wind_down;
 
 use anyhow::{anyhow, Result};
 use clap::Parser;
-use cli::{Cli, Commands};
+use cli::{Cli, Commands, ConfigArgs, ExportTarget, NotesAction, PlanAction, QueueAction, SaveArgs, TilArgs};
 use colored::*;
-use config::{Config, NotesFormat};
-use dialoguer::{theme::ColorfulTheme, Input, Select};
-use indicatif::{ProgressBar, ProgressStyle};
-use modes::{build_deep_dive_prompt, build_link_prompt};
+use config::{ArchiveLayout, Config, FilenameStyle, NotesFormat};
+use dialoguer::theme::Theme;
+use dialoguer::{FuzzySelect, Input, MultiSelect, Select};
+use error::HolocronError;
+use indicatif::ProgressBar;
+use modes::{
+    build_ask_prompt, build_audio_prompt, build_compare_prompt, build_deep_dive_prompt, build_file_prompt,
+    build_image_prompt, build_link_prompt, build_pull_request_prompt, build_repo_prompt, build_search_prompt,
+    build_shell_recap_prompt, build_video_prompt, link_target, parse_comparison, with_archive_context,
+    with_prior_context, LinkTarget, MAX_KNOWN_TITLES,
+};
+use save_prompt::SaveAction;
 use session::{LearningMode, Session};
-use std::io::{self, Write};
+use storage::Saved;
+use theme::Themed;
+use std::collections::HashSet;
+use std::io::{self, BufRead, IsTerminal, Write};
 use std::path::PathBuf;
+use std::process::ExitCode;
+use std::sync::mpsc;
 
-fn main() -> Result<()> {
+fn main() -> ExitCode {
+    match run() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{} {:#}", "Error:".failure().bold(), e);
+            if let Some(suggestion) = error::suggestion(&e) {
+                eprintln!("{} {}", "Try:".warning(), suggestion);
+            }
+            verbosity::log(&format!("exiting after error: {:?}", e));
+            ExitCode::from(error::exit_code(&e))
+        }
+    }
+}
+
+fn run() -> Result<()> {
     let cli = Cli::parse();
+    claude::handle_interrupts();
+    // Before anything is printed; a config that fails to load is reported by the command itself
+    match Config::load() {
+        Ok(Some(config)) => theme::set(config.theme, config.emoji && !cli.no_emoji),
+        _ => theme::set(theme::Theme::default(), !cli.no_emoji),
+    }
+    output::set(output::Output::detect());
+    if cli.quiet {
+        verbosity::set(verbosity::Verbosity::Quiet);
+    } else if cli.verbose {
+        verbosity::set(verbosity::Verbosity::Verbose);
+        if let Ok(path) = Config::log_path() {
+            eprintln!("{}", format!("Logging backend calls to {}", path.display()).muted());
+        }
+    }
+    let flags = config_flags(&cli)?;
+
+    if flags.dry_run && !supports_dry_run(&cli.command) {
+        return Err(anyhow!("--dry-run isn't supported by this command, so nothing was run"));
+    }
+    if needs_backend(&cli.command) {
+        require_backend()?;
+    }
 
-    match cli.command {
-        Some(Commands::Init { path }) => {
-            run_init(path)?;
+    // Commands that work without a TIL repo; everything else loads the config first
+    let command = match cli.command {
+        Some(Commands::Tutorial) => return run_tutorial(),
+        Some(Commands::Init { path, from_existing }) => return run_init(path, from_existing),
+        Some(Commands::Config(args)) => return run_config(*args, flags.dry_run),
+        Some(Commands::TilOfTheDay {
+            random,
+            plain,
+            max_lines,
+        }) => return run_til_of_the_day(random, plain, max_lines, flags.dry_run),
+        Some(Commands::History { tag, since }) => return run_history(tag.as_deref(), since.as_deref()),
+        // With --save-til, the config and repo are checked below, before the answer is paid for
+        Some(Commands::Ask {
+            question,
+            save_til: false,
+            category,
+            ..
+        }) => return ask(&question.join(" "), category).map(|_| ()),
+        command => command,
+    };
+    let config = load_config(&flags)?;
+    run_command(command, &config)
+}
+
+/// Run a command that works on the TIL repo
+fn run_command(command: Option<Commands>, config: &Config) -> Result<()> {
+    match command {
+        Some(Commands::Til(args)) => run_til(args, config),
+        Some(Commands::Extract { file, .. }) => run_extract(&file, config),
+        Some(Commands::Decrypt { file }) => {
+            print!("{}", crypto::decrypt(&file, &config.encryption)?);
+            Ok(())
         }
-        Some(Commands::Config {
-            til_path,
-            notes_path,
-            notes_format,
-            archive_dir,
+        Some(Commands::Check { fix }) => run_check(fix, config),
+        Some(Commands::Browse) => run_browse(config),
+        Some(Commands::Relink) => run_relink(config),
+        Some(Commands::Dedupe) => run_dedupe(config),
+        Some(Commands::Undo) => run_undo(config),
+        Some(Commands::Grep {
+            pattern,
+            context,
+            category,
+            ignore_case,
+        }) => run_grep(&pattern, context, category.as_deref(), ignore_case, config),
+        Some(Commands::List {
+            category,
+            max_minutes,
+        }) => run_list(category, max_minutes, config),
+        Some(Commands::Open {
+            query,
+            reveal,
+            obsidian,
+        }) => run_open(&query, reveal, obsidian, config),
+        Some(Commands::Share {
+            session,
+            til,
+            note,
+            gist,
+            out,
         }) => {
-            run_config(til_path, notes_path, notes_format, archive_dir)?;
-        }
-        Some(Commands::Learn { topic, category }) => {
-            let config = ensure_config()?;
-            let mode = LearningMode::DeepDive {
-                topic: topic.clone(),
+            let content = match (til, note) {
+                (true, _) => share::ShareContent::Til,
+                (_, true) => share::ShareContent::Note,
+                _ => share::ShareContent::Transcript,
             };
-            let session = Session::new(mode, category);
-            run_learning_session(session, build_deep_dive_prompt(&topic), &config)?;
+            let target = if gist { share::ShareTarget::Gist } else { share::ShareTarget::File };
+            run_share(&session, content, target, out, config)
+        }
+        Some(Commands::Notes { action }) => run_notes(action, config),
+        Some(Commands::Queue { action }) => run_queue(action, config),
+        Some(Commands::Watch { dir, interval }) => run_watch(dir, interval, config),
+        Some(Commands::Serve { capture, listen }) => run_serve(capture, &listen, config),
+        Some(Commands::Sync) => run_sync(config),
+        Some(Commands::Export { target }) => run_export(target, config),
+        Some(Commands::Digest { days, email }) => run_digest(days, email, config),
+        Some(Commands::Insights { days, suggest }) => run_insights(days, suggest, config),
+        Some(Commands::Learn {
+            topic,
+            category,
+            timebox,
+            output,
+            resume,
+            ..
+        }) => run_learn(&topic, category, timebox.as_deref(), &output, resume, config),
+        Some(Commands::Plan {
+            action,
+            topic,
+            category,
+        }) => run_plan(action, &topic.join(" "), category, config),
+        Some(Commands::Link { url, category, resume, .. }) => {
+            let session = new_link_session(&url, None, category, config);
+            match resume {
+                Some(id) => resume_learning_session(session, &id, config),
+                None => run_learning_session(session, link_prompt(&url, config), config),
+            }
         }
-        Some(Commands::Link { url, category }) => {
-            let config = ensure_config()?;
-            let mode = LearningMode::Link { url: url.clone() };
-            let session = Session::new(mode, category);
-            run_learning_session(session, build_link_prompt(&url), &config)?;
+        Some(Commands::Compare { subjects, category, .. }) => {
+            let (session, prompt) = start_compare_session(&subjects.join(" "), category)?;
+            run_learning_session(session, prompt, config)
         }
-        None => {
-            let config = ensure_config()?;
-            run_interactive_mode(&config)?;
+        Some(Commands::Video { url, category, .. }) => {
+            let (session, prompt) = start_video_session(&url, category)?;
+            run_learning_session(session, prompt, config)
+        }
+        Some(Commands::Audio { file, category, .. }) => run_audio(&file, category, config),
+        Some(Commands::ShellRecap {
+            since,
+            history,
+            category,
+            ..
+        }) => run_shell_recap(since.as_deref(), history, category, config),
+        Some(Commands::Image { file, category, .. }) => run_image(&file, category, config),
+        Some(Commands::Ask {
+            question,
+            save_til,
+            category,
+            ..
+        }) => {
+            let mut session = ask(&question.join(" "), category)?;
+            if save_til {
+                generate_and_save_til(&mut session, config)?;
+            }
+            Ok(())
         }
+        // Handled before the config is loaded
+        Some(Commands::Tutorial)
+        | Some(Commands::Init { .. })
+        | Some(Commands::Config(_))
+        | Some(Commands::TilOfTheDay { .. })
+        | Some(Commands::History { .. }) => Ok(()),
+        None => run_interactive_mode(config),
     }
+}
 
-    Ok(())
+/// Run the learn command: a deep dive on `topic`, or pick up a Claude Code session with `resume`
+fn run_learn(
+    topic: &str,
+    category: Option<String>,
+    timebox: Option<&str>,
+    output: &str,
+    resume: Option<String>,
+    config: &Config,
+) -> Result<()> {
+    let output = jsonl::OutputFormat::parse(output)?;
+    let timebox = timebox.map(timebox::parse_minutes).transpose()?;
+    let mode = LearningMode::DeepDive {
+        topic: topic.to_string(),
+    };
+    let mut session = Session::new(mode, category);
+    if let Some(id) = resume {
+        return resume_learning_session(session, &id, config);
+    }
+    session.timebox = timebox.map(timebox::Timebox::new);
+    let prompt = deep_dive_prompt(&mut session, config);
+    match output {
+        jsonl::OutputFormat::Text => {
+            if !review_overlapping_tils(topic, config)? {
+                return Ok(());
+            }
+            let prompt = offer_continuation(&session, prompt, config)?;
+            run_learning_session(session, prompt, config)
+        }
+        jsonl::OutputFormat::Jsonl => run_jsonl_session(session, prompt, config),
+    }
 }
 
-/// Ensure config exists, running first-time setup if needed
-fn ensure_config() -> Result<Config> {
+/// Whether a command talks to the backend; everything else works offline against the archive
+fn needs_backend(command: &Option<Commands>) -> bool {
+    match command {
+        None
+        | Some(Commands::Learn { .. })
+        | Some(Commands::Link { .. })
+        | Some(Commands::Compare { .. })
+        | Some(Commands::Video { .. })
+        | Some(Commands::Audio { .. })
+        | Some(Commands::Image { .. })
+        | Some(Commands::Ask { .. })
+        | Some(Commands::ShellRecap { .. })
+        | Some(Commands::Tutorial) => true,
+        Some(Commands::Til(args)) => args.from_sessions || args.append_to.is_some(),
+        Some(Commands::Extract { .. }) => true,
+        Some(Commands::Queue { action }) => matches!(action, Some(QueueAction::Next { .. })),
+        Some(Commands::Insights { suggest, .. }) => *suggest,
+        Some(Commands::Plan { action, topic, .. }) => action.is_some() || !topic.is_empty(),
+        _ => false,
+    }
+}
+
+/// Whether a command can preview its changes with `--dry-run`. The others write as they go
+/// (`init` creates the repo, the tutorial its own sandbox), so they refuse the flag instead.
+fn supports_dry_run(command: &Option<Commands>) -> bool {
+    !matches!(command, Some(Commands::Init { .. }) | Some(Commands::Tutorial))
+}
+
+/// Fail with a pointer to the offline commands when the `claude` CLI isn't installed
+fn require_backend() -> Result<()> {
+    if claude::is_installed() {
+        return Ok(());
+    }
+    Err(HolocronError::BackendUnavailable.into())
+}
+
+/// Flags given on the command line that override the config for this run
+struct ConfigFlags {
+    dry_run: bool,
+    save: SaveArgs,
+    append_to: Option<PathBuf>,
+}
+
+/// The config overrides given with the command. Only commands that save TILs take them.
+fn config_flags(cli: &Cli) -> Result<ConfigFlags> {
+    let top_level = &cli.save;
+    if cli.command.is_some() && (top_level.encrypt || top_level.notify || top_level.author.is_some()) {
+        return Err(anyhow!("--encrypt, --notify and --author go after the command, e.g. holocron learn <topic> --encrypt"));
+    }
+    let (save, append_to) = match &cli.command {
+        None => (cli.save.clone(), None),
+        Some(Commands::Learn { save, append_to, .. }) => (save.clone(), append_to.clone()),
+        Some(Commands::Til(args)) => (args.save.clone(), args.append_to.clone()),
+        Some(Commands::Link { save, .. })
+        | Some(Commands::Compare { save, .. })
+        | Some(Commands::Video { save, .. })
+        | Some(Commands::Audio { save, .. })
+        | Some(Commands::ShellRecap { save, .. })
+        | Some(Commands::Image { save, .. })
+        | Some(Commands::Ask { save, .. })
+        | Some(Commands::Extract { save, .. })
+        | Some(Commands::Queue {
+            action: Some(QueueAction::Next { save }),
+        })
+        | Some(Commands::Plan {
+            action: Some(PlanAction::Next { save, .. }),
+            ..
+        }) => (save.clone(), None),
+        _ => (SaveArgs::default(), None),
+    };
+    Ok(ConfigFlags {
+        dry_run: cli.dry_run,
+        save,
+        append_to,
+    })
+}
+
+/// Load config (running first-time setup if needed) and apply CLI flags
+fn load_config(flags: &ConfigFlags) -> Result<Config> {
+    let mut config = ensure_config(flags.dry_run)?;
+    if !config.til_path.is_dir() {
+        return Err(HolocronError::RepoNotInitialized(config.til_path).into());
+    }
+    config.dry_run = flags.dry_run;
+    config.encrypt_notes |= flags.save.encrypt;
+    config.notifications |= flags.save.notify;
+    config.append_to = flags
+        .append_to
+        .as_deref()
+        .map(|target| til::append::resolve_target(&config.archive_path(), target))
+        .transpose()?;
+    if let Some(ref author) = flags.save.author {
+        config.author = Some(author.clone());
+    }
+    Ok(config)
+}
+
+/// Ensure config exists, running first-time setup if needed. Setup saves the config as it
+/// goes, so a dry run can't do it.
+fn ensure_config(dry_run: bool) -> Result<Config> {
     if let Some(config) = Config::load()? {
         return Ok(config);
     }
+    if dry_run || !io::stdin().is_terminal() || !output::get().terminal {
+        return Err(HolocronError::ConfigMissing.into());
+    }
 
     // First-time setup
-    println!("{}", "═".repeat(60).bright_cyan());
+    println!("{}", theme::banner_rule());
     println!(
         "{}",
-        "  Welcome to Holocron!  ".bold().bright_cyan()
+        "  Welcome to Holocron!  ".bold().accent()
     );
-    println!("{}", "═".repeat(60).bright_cyan());
+    println!("{}", theme::banner_rule());
     println!();
     println!("Let's set up your configuration.");
     println!();
 
-    let til_path: String = Input::with_theme(&ColorfulTheme::default())
+    let til_path: String = Input::with_theme(&output::prompts()?)
         .with_prompt("Path to your TIL repository")
         .interact_text()?;
 
@@ -82,7 +414,7 @@ fn ensure_config() -> Result<Config> {
 
     // Check if it exists, offer to create or install skills
     if !til_path.exists() {
-        let create = Select::with_theme(&ColorfulTheme::default())
+        let create = Select::with_theme(&output::prompts()?)
             .with_prompt("TIL repository doesn't exist. Create it?")
             .items(&["Yes, initialize a new TIL repo", "No, I'll create it manually"])
             .default(0)
@@ -90,11 +422,11 @@ fn ensure_config() -> Result<Config> {
 
         if create == 0 {
             init::init_til_repo(&til_path, "archive")?;
-            println!("{} Created TIL repository at {:?}", "✓".green(), til_path);
+            println!("{} Created TIL repository at {:?}", theme::done(), til_path);
         }
     } else if !til_path.join(".claude").join("commands").exists() {
         // Existing repo without skills - offer to install them
-        let install = Select::with_theme(&ColorfulTheme::default())
+        let install = Select::with_theme(&output::prompts()?)
             .with_prompt("Install Claude Code skills (/til, /note) in this repo?")
             .items(&["Yes, install skills", "No, skip"])
             .default(0)
@@ -102,13 +434,13 @@ fn ensure_config() -> Result<Config> {
 
         if install == 0 {
             init::init_til_repo(&til_path, "archive")?;
-            println!("{} Installed skills at {:?}", "✓".green(), til_path.join(".claude/commands"));
+            println!("{} Installed skills at {:?}", theme::done(), til_path.join(".claude/commands"));
         }
     }
 
     // Ask about notes path
     println!();
-    let setup_notes = Select::with_theme(&ColorfulTheme::default())
+    let setup_notes = Select::with_theme(&output::prompts()?)
         .with_prompt("Set up a notes/knowledge base path? (for Obsidian, Logseq, etc.)")
         .items(&["Yes", "No, skip for now"])
         .default(1)
@@ -117,21 +449,22 @@ fn ensure_config() -> Result<Config> {
     let mut config = Config::new(til_path);
 
     if setup_notes == 0 {
-        let notes_path: String = Input::with_theme(&ColorfulTheme::default())
+        let notes_path: String = Input::with_theme(&output::prompts()?)
             .with_prompt("Path to your notes repository")
             .interact_text()?;
 
         config.notes_path = Some(PathBuf::from(shellexpand::tilde(&notes_path).to_string()));
 
-        let format = Select::with_theme(&ColorfulTheme::default())
+        let format = Select::with_theme(&output::prompts()?)
             .with_prompt("Notes format")
-            .items(&["Obsidian", "Logseq", "Plain markdown"])
+            .items(&["Obsidian", "Logseq", "Org-mode (org-roam)", "Plain markdown"])
             .default(0)
             .interact()?;
 
         config.notes_format = match format {
             0 => NotesFormat::Obsidian,
             1 => NotesFormat::Logseq,
+            2 => NotesFormat::Org,
             _ => NotesFormat::Plain,
         };
     }
@@ -140,385 +473,4119 @@ fn ensure_config() -> Result<Config> {
 
     let config_path = Config::config_path()?;
     println!();
-    println!("{} Config saved to {:?}", "✓".green(), config_path);
+    println!("{} Config saved to {:?}", theme::done(), config_path);
     println!();
 
     Ok(config)
 }
 
-/// Run the init command
-fn run_init(path: PathBuf) -> Result<()> {
-    let path = PathBuf::from(shellexpand::tilde(path.to_string_lossy().as_ref()).to_string());
-
-    let readme_existed = path.join("README.md").exists();
-    let archive_existed = path.join("archive").exists();
+/// The opening deep dive prompt, listing the category's existing TILs when
+/// `inject_archive_context` is on (remembered on the session for /knowledge)
+fn deep_dive_prompt(session: &mut Session, config: &Config) -> String {
+    let prompt = build_deep_dive_prompt(session.topic());
+    let Some(category) = session.category.clone().filter(|_| config.inject_archive_context) else {
+        return prompt;
+    };
 
-    init::init_til_repo(&path, "archive")?;
+    session.known_titles = archive::til_entries(&config.archive_path())
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|entry| entry.category.as_deref() == Some(category.as_str()))
+        .map(|entry| entry.title)
+        .take(MAX_KNOWN_TITLES)
+        .collect();
+    with_archive_context(prompt, &category, &session.known_titles)
+}
 
-    println!("{} Initialized TIL repository at {:?}", "✓".green(), path);
-    println!();
-    println!("Created:");
-    if readme_existed {
-        println!("  - README.md {}", "(already existed, skipped)".dimmed());
-    } else {
-        println!("  - README.md");
-    }
-    if archive_existed {
-        println!("  - archive/ {}", "(already existed)".dimmed());
+/// Seed `session` with a Claude Code conversation and carry on with it. With an empty `id`, pick one
+/// of the recent sessions.
+fn resume_learning_session(mut session: Session, id: &str, config: &Config) -> Result<()> {
+    let path = if id.is_empty() {
+        pick_claude_session()?
     } else {
-        println!("  - archive/");
+        claude::transcripts::find(id)?
+    };
+    let conversation = claude::transcripts::read(&path)?;
+    if conversation.exchanges.is_empty() {
+        return Err(anyhow!("Claude Code session {:?} has no conversation to pick up", path));
     }
-    println!("  - .claude/commands/til.md");
-    println!("  - .claude/commands/note.md");
-    println!("  - .claude/settings.json");
-    println!();
-    println!("Run {} to set this as your TIL path.", "holocron config --til-path <path>".cyan());
+    let id = path.file_stem().map(|stem| stem.to_string_lossy().to_string()).unwrap_or_default();
+    let cwd = conversation.cwd.clone();
+    let count = session.import_conversation(conversation);
+    session.set_session_id(id.clone());
+    // Claude Code looks sessions up by project, so keep talking to it from the one this ran in
+    session.claude_dir = cwd.filter(|cwd| cwd.is_dir());
 
-    Ok(())
+    print_learning_banner(&session);
+    println!(
+        "{} Attached to Claude Code session {} ({} exchange{})",
+        theme::done(),
+        id.muted(),
+        count,
+        if count == 1 { "" } else { "s" }
+    );
+    continue_learning_session(session, config)
 }
 
-/// Run the config command
-fn run_config(
-    til_path: Option<PathBuf>,
-    notes_path: Option<PathBuf>,
-    notes_format: Option<String>,
-    archive_dir: Option<String>,
-) -> Result<()> {
-    let mut config = Config::load()?.unwrap_or_else(|| Config::new(PathBuf::new()));
-    let mut changed = false;
-
-    if let Some(path) = til_path {
-        config.til_path = PathBuf::from(shellexpand::tilde(path.to_string_lossy().as_ref()).to_string());
-        changed = true;
+/// Choose from the most recent Claude Code sessions
+fn pick_claude_session() -> Result<PathBuf> {
+    if !io::stdin().is_terminal() {
+        return Err(anyhow!("Pass a session id to --resume when not running in a terminal"));
     }
-
-    if let Some(path) = notes_path {
-        config.notes_path = Some(PathBuf::from(shellexpand::tilde(path.to_string_lossy().as_ref()).to_string()));
-        changed = true;
+    let mut choices = Vec::new();
+    for transcript in claude::transcripts::all()?.into_iter().take(20) {
+        let conversation = claude::transcripts::read(&transcript.path)?;
+        if !conversation.exchanges.is_empty() {
+            choices.push((transcript, conversation.preview()));
+        }
+    }
+    if choices.is_empty() {
+        return Err(anyhow!("No Claude Code sessions found to resume"));
     }
 
-    if let Some(format) = notes_format {
-        config.notes_format = match format.to_lowercase().as_str() {
-            "obsidian" => NotesFormat::Obsidian,
-            "logseq" => NotesFormat::Logseq,
-            "plain" => NotesFormat::Plain,
-            _ => return Err(anyhow!("Invalid notes format. Use: obsidian, logseq, or plain")),
-        };
-        changed = true;
+    let items: Vec<String> = choices
+        .iter()
+        .map(|(transcript, preview)| {
+            format!("{}  {}  {}", transcript.modified.format("%Y-%m-%d %H:%M"), preview, transcript.id.muted())
+        })
+        .collect();
+    let choice = Select::with_theme(&output::prompts()?)
+        .with_prompt("Resume which Claude Code session?")
+        .items(&items)
+        .default(0)
+        .interact()?;
+    Ok(choices.swap_remove(choice).0.path)
+}
+
+/// Before a deep dive, list README entries that look like they cover `topic` already and offer
+/// to open one for review. Returns whether to go ahead with the session.
+fn review_overlapping_tils(topic: &str, config: &Config) -> Result<bool> {
+    if !io::stdin().is_terminal() {
+        return Ok(true);
+    }
+    let overlaps = til::overlap::find_in_repo(topic, &config.til_path, &config.archive_dir);
+    if overlaps.is_empty() {
+        return Ok(true);
     }
 
-    if let Some(dir) = archive_dir {
-        config.archive_dir = dir;
-        changed = true;
+    let titles: Vec<String> = overlaps.iter().map(|overlap| format!("'{}'", overlap.title)).collect();
+    println!("{} You already have: {}", "!".warning(), titles.join(", "));
+
+    let mut items = vec!["Start the session anyway".to_string()];
+    items.extend(overlaps.iter().map(|overlap| format!("Open '{}'", overlap.title)));
+    items.push("Cancel".to_string());
+    loop {
+        let choice = Select::with_theme(&output::prompts()?)
+            .with_prompt("Review these first?")
+            .items(&items)
+            .default(0)
+            .interact()?;
+        match choice {
+            0 => return Ok(true),
+            i if i <= overlaps.len() => launcher::open_in_editor(&overlaps[i - 1].path)?,
+            _ => return Ok(false),
+        }
     }
+}
 
-    if changed {
-        config.save()?;
-        println!("{} Configuration updated.", "✓".green());
+/// If an earlier session or TIL covered the same topic, offer to build on it instead of
+/// starting from the basics
+fn offer_continuation(session: &Session, prompt: String, config: &Config) -> Result<String> {
+    if !io::stdin().is_terminal() {
+        return Ok(prompt);
     }
 
-    // Display current config
-    println!();
-    println!("{}", "Current Configuration:".bold());
-    println!("  TIL path:     {:?}", config.til_path);
-    println!("  Archive dir:  {}", config.archive_dir);
-    if let Some(ref notes) = config.notes_path {
-        println!("  Notes path:   {:?}", notes);
-        println!("  Notes format: {}", config.notes_format);
-    } else {
-        println!("  Notes path:   (not configured)");
+    let sessions = Config::sessions_dir()
+        .and_then(|dir| session_store::load_all(&dir))
+        .unwrap_or_default();
+    let entries = archive::til_entries(&config.archive_path()).unwrap_or_default();
+    let Some(prior) = continuation::find(session.topic(), sessions, entries) else {
+        return Ok(prompt);
+    };
+
+    let choice = Select::with_theme(&output::prompts()?)
+        .with_prompt(format!("You've studied this before, in {}. Build on it?", prior.describe()))
+        .items(&["Yes, pick up where it left off", "No, start fresh"])
+        .default(0)
+        .interact()?;
+    if choice != 0 {
+        return Ok(prompt);
     }
-    println!();
-    println!("Config file: {:?}", Config::config_path()?);
 
-    Ok(())
+    println!("{} Continuing from {}", theme::done(), prior.describe());
+    Ok(with_prior_context(prompt, &prior.describe(), &prior.context()?))
 }
 
-fn print_welcome_banner() {
-    println!("{}", "═".repeat(60).bright_cyan());
-    println!(
-        "{}",
-        "  HOLOCRON - Your Learning Assistant  "
-            .bold()
-            .bright_cyan()
-    );
-    println!("{}", "═".repeat(60).bright_cyan());
-    println!();
-    println!("Commands:");
-    println!(
-        "  {} - Start a deep dive on a topic",
-        "/learn <topic>".green()
-    );
-    println!("  {}    - Analyze an article from URL", "/link <url>".green());
-    println!("  {}          - Generate TIL from session", "/til".green());
-    println!("  {}         - Generate detailed note", "/note".green());
-    println!("  {}         - Exit holocron", "/exit".green());
+/// Run the latest code example from the conversation, then offer to discuss its output
+fn run_code_example(session: &mut Session, config: &Config) -> Result<()> {
+    let block = session
+        .exchanges
+        .iter()
+        .rev()
+        .find_map(|exchange| runner::last_code_block(&exchange.assistant_response))
+        .ok_or_else(|| anyhow!("No code example in the conversation yet"))?;
+
     println!();
-    println!("Or just type to continue the conversation.");
+    println!("{}", block.code.muted());
     println!();
-}
-
-fn run_interactive_mode(config: &Config) -> Result<()> {
-    print_welcome_banner();
-
-    let mut session: Option<Session> = None;
-
-    loop {
-        let input: String = Input::with_theme(&ColorfulTheme::default())
-            .with_prompt("holocron")
-            .allow_empty(false)
-            .interact_text()?;
 
-        let input = input.trim();
+    let mut items: Vec<String> = runner::RUNNERS.iter().map(|r| format!("Run with {}", r)).collect();
+    items.push("Cancel".to_string());
+    let suggested = runner::Runner::for_language(&block.language)
+        .and_then(|suggested| runner::RUNNERS.iter().position(|r| *r == suggested));
+    let choice = Select::with_theme(&output::prompts()?)
+        .with_prompt("Run this on your machine, in a temp directory?")
+        .items(&items)
+        .default(suggested.unwrap_or(runner::RUNNERS.len()))
+        .interact()?;
+    let Some(&chosen) = runner::RUNNERS.get(choice) else {
+        return Ok(());
+    };
 
-        if input.eq_ignore_ascii_case("/quit") || input.eq_ignore_ascii_case("/exit") {
-            println!("{}", "May the Force be with you.".bright_cyan());
-            break;
-        }
+    if config.dry_run {
+        println!("{}", format!("Dry run: would run the example with {}", chosen).warning());
+        return Ok(());
+    }
 
-        if let Some(handled) = handle_command(input, &mut session, config)? {
-            if handled {
-                continue;
-            }
-        }
+    let spinner = create_spinner(&format!("Running with {}...", chosen));
+    let output = runner::run(chosen, &block.code);
+    spinner.finish_and_clear();
+    let output = output?;
 
-        // Regular conversation continuation
-        if let Some(ref mut sess) = session {
-            send_and_display(input, sess)?;
-        } else {
-            println!(
-                "{}",
-                "Start a session with /learn <topic> or /link <url>".yellow()
-            );
-        }
+    print!("{}", output.stdout);
+    print!("{}", output.stderr.failure());
+    let status = format!("The example {}", output.describe_status());
+    match output.status {
+        Some(status_code) if status_code.success() => println!("{}", status.success()),
+        _ => println!("{}", status.warning()),
     }
 
+    let share = Select::with_theme(&output::prompts()?)
+        .with_prompt("Send the output to Claude?")
+        .items(&["Yes, discuss it", "No"])
+        .default(0)
+        .interact()?;
+    if share == 0 {
+        send_and_display(&output.build_feedback_prompt(chosen), session, config)?;
+    }
     Ok(())
 }
 
-fn handle_command(input: &str, session: &mut Option<Session>, config: &Config) -> Result<Option<bool>> {
-    if let Some(topic) = input.strip_prefix("/learn ") {
-        let topic = topic.trim();
-        if topic.is_empty() {
-            println!("{}", "Please provide a topic.".yellow());
-            return Ok(Some(true));
-        }
-
-        let category = prompt_for_category()?;
-        let mode = LearningMode::DeepDive {
-            topic: topic.to_string(),
-        };
-        *session = Some(Session::new(mode, category));
-
-        let prompt = build_deep_dive_prompt(topic);
-        if let Some(ref mut sess) = session {
-            send_and_display(&prompt, sess)?;
-        }
-        return Ok(Some(true));
+/// Print the sources collected so far, which /note lists at the end of the note
+fn print_sources(session: &Session) {
+    if session.sources.is_empty() {
+        println!("{}", "No sources cited yet.".muted());
+        return;
     }
+    println!("{}", "Sources:".bold());
+    for (i, url) in session.sources.iter().enumerate() {
+        println!("  {:>2}. {}", i + 1, url.info());
+    }
+}
 
-    if let Some(url) = input.strip_prefix("/link ") {
-        let url = url.trim();
-        if url.is_empty() {
-            println!("{}", "Please provide a URL.".yellow());
-            return Ok(Some(true));
+/// Print what /history shows: each exchange's question, numbered for /exclude
+fn print_history(session: &Session) {
+    if session.exchanges.is_empty() {
+        println!("{}", "No exchanges yet.".muted());
+        return;
+    }
+    for (i, exchange) in session.exchanges.iter().enumerate() {
+        let question: String = exchange.user_message.lines().next().unwrap_or_default().chars().take(70).collect();
+        if exchange.excluded {
+            println!("  {:>2}. {} {}", i + 1, question.muted().strikethrough(), "(excluded)".muted());
+        } else {
+            println!("  {:>2}. {}", i + 1, question);
         }
+    }
+}
 
-        let category = prompt_for_category()?;
-        let mode = LearningMode::Link {
-            url: url.to_string(),
+/// Print what /knowledge shows: the TIL titles the session was told about
+fn print_known_titles(session: &Session, config: &Config) {
+    if session.known_titles.is_empty() {
+        let hint = if config.inject_archive_context {
+            "No existing TILs were shared with this session."
+        } else {
+            "Archive context is off. Enable it with `holocron config --inject-archive-context true`."
         };
-        *session = Some(Session::new(mode, category));
+        println!("{}", hint.warning());
+        return;
+    }
 
-        let prompt = build_link_prompt(url);
-        if let Some(ref mut sess) = session {
-            send_and_display(&prompt, sess)?;
-        }
-        return Ok(Some(true));
+    println!("{}", "Existing TILs shared with this session:".bold());
+    for title in &session.known_titles {
+        println!("  - {}", title);
     }
+}
 
-    if input.eq_ignore_ascii_case("/til") {
-        if let Some(ref sess) = session {
-            generate_and_save_til(sess, config)?;
-        } else {
-            println!(
-                "{}",
-                "No active session. Start with /learn or /link first.".yellow()
-            );
-        }
-        return Ok(Some(true));
+/// A session analyzing a link, limited to WebFetch (no WebSearch) when `link_webfetch_only` is on.
+/// Without a known `title`, the page's own is fetched so the session can go by it.
+fn new_link_session(url: &str, title: Option<String>, category: Option<String>, config: &Config) -> Session {
+    let mut session = Session::new(LearningMode::Link { url: url.to_string() }, category);
+    session.page_title = title.or_else(|| {
+        let spinner = create_spinner("Fetching the page title...");
+        let title = queue::title::fetch(url);
+        spinner.finish_and_clear();
+        title
+    });
+    if config.link_webfetch_only {
+        session.tool_settings = Some(claude::webfetch_only_settings());
     }
+    session
+}
 
-    if input.eq_ignore_ascii_case("/note") {
-        if let Some(ref sess) = session {
-            generate_and_save_note(sess, config)?;
-        } else {
-            println!(
-                "{}",
-                "No active session. Start with /learn or /link first.".yellow()
-            );
-        }
-        return Ok(Some(true));
+/// The opening prompt for a link. GitHub repos, pull requests and files are fetched so the analysis
+/// covers the code itself; anything else, or a GitHub fetch that fails, is left to WebFetch.
+fn link_prompt(url: &str, config: &Config) -> String {
+    let target = link_target(url);
+    if target == LinkTarget::Page {
+        return build_link_prompt(url);
     }
 
-    Ok(None)
+    let token = config.share.github_token.as_deref();
+    let spinner = create_spinner("Fetching from GitHub...");
+    let prompt = match target {
+        LinkTarget::Page => Ok(build_link_prompt(url)),
+        LinkTarget::Repo(ref repo) => github::fetch_repo(repo, token)
+            .map(|contents| build_repo_prompt(url, repo, &contents.readme, &contents.files)),
+        LinkTarget::PullRequest { ref repo, number } => github::fetch_pull_request(repo, number, token)
+            .map(|pull| build_pull_request_prompt(url, &pull.title, &pull.description, &pull.diff)),
+        LinkTarget::File {
+            ref repo,
+            ref git_ref,
+            ref path,
+        } => github::fetch_file(repo, git_ref, path, token).map(|code| build_file_prompt(url, repo, path, &code)),
+    };
+    spinner.finish_and_clear();
+
+    prompt.unwrap_or_else(|e| {
+        eprintln!("{}", format!("Couldn't fetch the code ({}); reading the page instead.", e).warning());
+        build_link_prompt(url)
+    })
 }
 
-fn run_learning_session(mut session: Session, initial_prompt: String, config: &Config) -> Result<()> {
-    println!("{}", "═".repeat(60).bright_cyan());
-    println!(
-        "{}",
-        format!("  Learning: {}  ", session.topic())
-            .bold()
-            .bright_cyan()
-    );
-    println!("{}", "═".repeat(60).bright_cyan());
-    println!();
+/// Build a comparison session and its opening prompt from "a vs b"
+fn start_compare_session(input: &str, category: Option<String>) -> Result<(Session, String)> {
+    let (left, right) = parse_comparison(input)?;
+    let mode = LearningMode::Compare {
+        topic: format!("{} vs {}", left, right),
+    };
+    Ok((Session::new(mode, category), build_compare_prompt(&left, &right)))
+}
 
-    send_and_display(&initial_prompt, &mut session)?;
+/// Fetch a video's captions and build its session and opening prompt
+fn start_video_session(url: &str, category: Option<String>) -> Result<(Session, String)> {
+    let spinner = create_spinner("Fetching captions...");
+    let video = video::fetch(url);
+    spinner.finish_and_clear();
+    let video = video?;
 
-    println!();
+    let title = if video.title.is_empty() { url.to_string() } else { video.title };
+    let prompt = build_video_prompt(&title, url, &video::chunk(&video.captions));
+    let mode = LearningMode::Video {
+        url: url.to_string(),
+        title,
+    };
+    Ok((Session::new(mode, category), prompt))
+}
+
+/// Transcribe a recording and start a session on it
+fn run_audio(file: &std::path::Path, category: Option<String>, config: &Config) -> Result<()> {
+    let file = PathBuf::from(shellexpand::tilde(file.to_string_lossy().as_ref()).to_string());
+    let name = file
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .ok_or_else(|| anyhow!("Not an audio file: {:?}", file))?;
+
+    let spinner = create_spinner(&format!("Transcribing {}...", name));
+    let transcript = transcribe::transcribe_cached(&file, &config.transcription, &Config::audio_transcripts_dir()?);
+    spinner.finish_and_clear();
+    let transcript = transcript?;
     println!(
-        "Commands: {} | {} | {}",
-        "/til".green(),
-        "/note".green(),
-        "/exit".green()
+        "{} Transcribed {} ({} words)",
+        theme::done(),
+        name,
+        transcript.split_whitespace().count()
     );
-    println!();
-
-    loop {
-        let input: String = Input::with_theme(&ColorfulTheme::default())
-            .with_prompt("holocron")
-            .allow_empty(false)
-            .interact_text()?;
 
-        let input = input.trim();
+    let session = Session::new(LearningMode::Audio { file: name.clone() }, category);
+    run_learning_session(session, build_audio_prompt(&name, &transcript), config)
+}
 
-        if input.eq_ignore_ascii_case("/quit") || input.eq_ignore_ascii_case("/exit") {
-            println!("{}", "May the Force be with you.".bright_cyan());
-            break;
-        }
+/// Start a session explaining the non-obvious commands in the shell history since `since`
+/// (default: midnight)
+fn run_shell_recap(
+    since: Option<&str>,
+    history: Option<PathBuf>,
+    category: Option<String>,
+    config: &Config,
+) -> Result<()> {
+    let now = chrono::Local::now();
+    let (start, window) = match since {
+        Some(since) => (session_store::parse_since(since, now)?, format!("since {}", since)),
+        None => (session_store::parse_since(&now.format("%Y-%m-%d").to_string(), now)?, "today".to_string()),
+    };
 
-        if input.eq_ignore_ascii_case("/til") {
-            generate_and_save_til(&session, config)?;
-            continue;
-        }
+    let (path, shell) = shell_history::locate(history.as_deref().or(config.shell_history.as_deref()))?;
+    let entries = shell_history::read(&path, shell)?;
+    let commands = shell_history::recent(&entries, start);
+    if commands.is_empty() {
+        println!("{}", format!("No commands worth a recap {} in {}.", window, path.display()).warning());
+        return Ok(());
+    }
+    if entries.iter().all(|entry| entry.ran_at.is_none()) {
+        let note = format!(
+            "{} has no timestamps, so the latest {} commands stand in for {}.",
+            path.display(),
+            commands.len(),
+            window
+        );
+        println!("{}", note.warning());
+    }
+    println!("{} Recapping {} command(s) from {}", theme::done(), commands.len(), path.display());
 
-        if input.eq_ignore_ascii_case("/note") {
-            generate_and_save_note(&session, config)?;
-            continue;
-        }
+    let mode = LearningMode::ShellRecap {
+        topic: format!("commands run {}", window),
+    };
+    let session = Session::new(mode, category);
+    run_learning_session(session, build_shell_recap_prompt(&window, &commands), config)
+}
 
-        send_and_display(input, &mut session)?;
-    }
+/// Start a session explaining an image, letting the backend read just that file
+fn run_image(file: &std::path::Path, category: Option<String>, config: &Config) -> Result<()> {
+    let file = PathBuf::from(shellexpand::tilde(file.to_string_lossy().as_ref()).to_string());
+    let name = file
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .ok_or_else(|| anyhow!("Not an image file: {:?}", file))?;
+    let settings = claude::image_input_settings(&file)?;
+    let file = file.canonicalize()?;
 
-    Ok(())
+    let mut session = Session::new(LearningMode::Image { file: name }, category);
+    session.tool_settings = Some(settings);
+    run_learning_session(session, build_image_prompt(&file.to_string_lossy()), config)
 }
 
-fn create_spinner(message: &str) -> ProgressBar {
-    let spinner = ProgressBar::new_spinner();
-    if let Ok(style) = ProgressStyle::default_spinner().template("{spinner:.cyan} {msg}") {
-        spinner.set_style(style);
+/// Answer one question and print the reply as plain text, returning the one-exchange session
+/// in case `--save-til` wants it. Nothing is stored and no config is needed.
+fn ask(question: &str, category: Option<String>) -> Result<Session> {
+    let mut session = Session::new(
+        LearningMode::DeepDive {
+            topic: question.to_string(),
+        },
+        category,
+    );
+    let spinner = create_spinner("Thinking...");
+
+    let mut answer = String::new();
+    let response = claude::send(
+        claude::Resume::default(),
+        &build_ask_prompt(question),
+        Some(&claude::no_tools_settings()),
+        |text| {
+            spinner.finish_and_clear();
+            print!("{}", text);
+            io::stdout().flush().ok();
+            answer.push_str(text);
+        },
+        claude::CancellationToken::new(),
+    )?;
+    spinner.finish_and_clear();
+    if !answer.ends_with('\n') {
+        println!();
     }
-    spinner.set_message(message.to_string());
-    spinner.enable_steady_tick(std::time::Duration::from_millis(100));
-    spinner
+
+    session.record_response(question.to_string(), response);
+    Ok(session)
 }
 
-fn send_and_display(message: &str, session: &mut Session) -> Result<()> {
-    let spinner = create_spinner("Consulting the archives...");
+/// Walk a new user through a scripted mini session. Nothing outside a temp TIL repo is written.
+fn run_tutorial() -> Result<()> {
+    let sandbox = tutorial::Sandbox::create()?;
+    let config = &sandbox.config;
 
-    let mut response = String::new();
-    let mut first_chunk = true;
+    print_tutorial_step(1, "Learning", tutorial::INTRO)?;
+    let topic = tutorial::TOPIC.to_string();
+    let mut session = Session::new(LearningMode::DeepDive { topic }, Some(tutorial::CATEGORY.to_string()));
+    send_and_display(&tutorial::build_tutorial_prompt(), &mut session, config)?;
 
-    let result = if let Some(ref session_id) = session.claude_session_id {
-        claude::continue_conversation(session_id, message, |text| {
-            if first_chunk {
-                spinner.finish_and_clear();
-                first_chunk = false;
-            }
-            print!("{}", text);
-            io::stdout().flush().ok();
-            response.push_str(text);
-        })
+    print_tutorial_step(2, "Asking follow-ups", tutorial::FOLLOW_UPS)?;
+    let question: String = Input::with_theme(&output::prompts()?)
+        .with_prompt("You")
+        .allow_empty(true)
+        .interact_text()?;
+    if !question.trim().is_empty() {
+        send_and_display(question.trim(), &mut session, config)?;
+    }
+
+    print_tutorial_step(3, "Saving a TIL", tutorial::SAVING)?;
+    generate_and_save_til(&mut session, config)?;
+    if let Some(ref path) = session.saved_til {
+        let relative = path.strip_prefix(&config.til_path).unwrap_or(path);
+        println!();
+        println!(
+            "In your own repo this would be {}, listed under its category in README.md.",
+            relative.display().to_string().info()
+        );
+    }
+
+    println!();
+    println!("{}", "Step 4: What's next".bold().accent());
+    println!();
+    println!("{}", tutorial::NEXT);
+    println!();
+    Ok(())
+}
+
+/// Explain the next tutorial step, then wait for Enter
+fn print_tutorial_step(number: usize, title: &str, text: &str) -> Result<()> {
+    println!();
+    println!("{}", format!("Step {}: {}", number, title).bold().accent());
+    println!();
+    println!("{}", text);
+    println!();
+    print!("{}", "Press Enter to continue...".muted());
+    io::stdout().flush()?;
+    io::stdin().lock().read_line(&mut String::new())?;
+    println!();
+    Ok(())
+}
+
+/// Run the init command
+fn run_init(path: PathBuf, from_existing: bool) -> Result<()> {
+    let path = PathBuf::from(shellexpand::tilde(path.to_string_lossy().as_ref()).to_string());
+
+    let report = if from_existing {
+        let (layout, report) = init::adopt_til_repo(&path)?;
+        println!("{} Adopted TIL repository at {:?}", theme::done(), path);
+        println!();
+        println!("Detected:");
+        println!("  - archive directory: {}/", layout.archive_dir);
+        println!("  - archive layout:    {}", layout.archive_layout);
+        println!("  - filename style:    {}", layout.filename_style);
+        for warning in layout.readme.warnings() {
+            println!("  {} {}", "!".warning(), warning);
+        }
+        report
+    } else {
+        let report = init::init_til_repo(&path, "archive")?;
+        println!("{} Initialized TIL repository at {:?}", theme::done(), path);
+        report
+    };
+
+    println!();
+    for file in &report.created {
+        println!("  - {}", file);
+    }
+    for file in &report.updated {
+        println!("  - {} {}", file, "(added holocron's allowed tools)".muted());
+    }
+    for file in &report.kept {
+        println!("  - {} {}", file, "(already existed, kept)".muted());
+    }
+    println!();
+    println!("Run {} to set this as your TIL path.", "holocron config --til-path <path>".info());
+
+    Ok(())
+}
+
+/// Run the config command
+fn run_config(args: ConfigArgs, dry_run: bool) -> Result<()> {
+    // Edit the global config only; repo-local overrides are listed below
+    let mut config = Config::load_global()?.unwrap_or_else(|| Config::new(PathBuf::new()));
+
+    if apply_config_args(args, &mut config)? {
+        if dry_run {
+            let config_path = Config::config_path()?;
+            let existing = std::fs::read_to_string(&config_path).unwrap_or_default();
+            print_diff(&diff::unified_diff(&existing, &config.to_toml()?, "a/config.toml", "b/config.toml", 3));
+            println!("{}", "Dry run: the configuration was not updated.".warning());
+        } else {
+            config.save()?;
+            println!("{} Configuration updated.", theme::done());
+        }
+    }
+
+    // Display current config
+    println!();
+    println!("{}", "Current Configuration:".bold());
+    println!("  TIL path:     {:?}", config.til_path);
+    println!("  Archive dir:  {}", config.archive_dir);
+    println!("  Layout:       {}", config.archive_layout);
+    println!("  Filenames:    {}", config.filename_style);
+    if !config.favorite_categories.is_empty() {
+        println!("  Favorites:    {}", config.favorite_categories.join(", "));
+    }
+    println!("  Auto link:    {}", config.auto_link);
+    if let Some(ref author) = config.author {
+        println!("  Author:       {}{}", author, if config.readme_authors { " (credited in README)" } else { "" });
+    }
+    println!("  Follow-ups:   {}", config.suggest_followups);
+    println!("  Autosave:     {}", config.autosave_sessions);
+    println!("  Link cache:   {} days", config.link_cache_days);
+    println!("  TIL context:  {} tokens", config.til_context_tokens);
+    println!("  Context limit: {} tokens", config.context_limit_tokens);
+    println!("  Language:     {}", config.language.as_deref().unwrap_or("English"));
+    println!("  Notify:       {}", config.notifications);
+    println!("  Suggest /til: {}", config.suggest_til);
+    println!("  Recall first: {}", config.require_recall);
+    println!("  Fact-check:   {}", config.verify_tils);
+    match config.til_reminder_minutes {
+        0 => println!("  TIL reminder: off"),
+        minutes => println!("  TIL reminder: after {}m idle", minutes),
+    }
+    println!("  Link tools:   {}", if config.link_webfetch_only { "WebFetch only" } else { "default" });
+    println!("  Known TILs:   {}", config.inject_archive_context);
+    if let Some(ref history) = config.shell_history {
+        println!("  Shell history: {:?}", history);
+    }
+    println!("  Theme:        {}{}", config.theme, if config.emoji { "" } else { " (no emoji)" });
+    if let Some(ref repo) = config.sync.repo {
+        println!("  Sync repo:    {:?}", repo);
+    }
+    if let Some(ref notes) = config.notes_path {
+        println!("  Notes path:   {:?}", notes);
+        println!("  Notes format: {}", config.notes_format);
+    } else {
+        println!("  Notes path:   (not configured)");
+    }
+    println!();
+    println!("Config file: {:?}", Config::config_path()?);
+    for dir in config.repo_config_dirs() {
+        let path = dir.join(config::REPO_CONFIG_FILE);
+        if path.is_file() {
+            println!("Overridden by: {:?}", path);
+        }
+    }
+
+    Ok(())
+}
+
+/// Apply `holocron config` flags to the config, returning whether anything changed
+fn apply_config_args(args: ConfigArgs, config: &mut Config) -> Result<bool> {
+    let mut changed = apply_toggle_args(&args, config);
+
+    if let Some(path) = args.til_path {
+        config.til_path = PathBuf::from(shellexpand::tilde(path.to_string_lossy().as_ref()).to_string());
+        changed = true;
+    }
+
+    if let Some(path) = args.notes_path {
+        config.notes_path = Some(PathBuf::from(shellexpand::tilde(path.to_string_lossy().as_ref()).to_string()));
+        changed = true;
+    }
+
+    if let Some(format) = args.notes_format {
+        config.notes_format = NotesFormat::parse(&format)?;
+        changed = true;
+    }
+
+    if let Some(dir) = args.archive_dir {
+        config.archive_dir = dir;
+        changed = true;
+    }
+
+    if let Some(layout) = args.archive_layout {
+        config.archive_layout = ArchiveLayout::parse(&layout)?;
+        changed = true;
+    }
+
+    if let Some(style) = args.filename_style {
+        config.filename_style = FilenameStyle::parse(&style)?;
+        changed = true;
+    }
+
+    if let Some(categories) = args.favorite_categories {
+        config.favorite_categories = categories
+            .iter()
+            .map(|category| til::category::normalize(category))
+            .filter(|category| !category.is_empty())
+            .collect();
+        changed = true;
+    }
+
+    if let Some(days) = args.link_cache_days {
+        config.link_cache_days = days;
+        changed = true;
+    }
+
+    if let Some(tokens) = args.til_context_tokens {
+        config.til_context_tokens = tokens;
+        changed = true;
+    }
+
+    if let Some(tokens) = args.context_limit_tokens {
+        config.context_limit_tokens = tokens;
+        changed = true;
+    }
+
+    if let Some(language) = args.language {
+        let language = language.trim();
+        config.language = (!language.is_empty()).then(|| language.to_string());
+        changed = true;
+    }
+
+    if let Some(author) = args.author {
+        let author = author.trim();
+        config.author = (!author.is_empty()).then(|| author.to_string());
+        changed = true;
+    }
+
+    if let Some(minutes) = args.til_reminder_minutes {
+        config.til_reminder_minutes = minutes;
+        changed = true;
+    }
+
+    if let Some(path) = args.shell_history {
+        let path = path.to_string_lossy();
+        config.shell_history = (!path.trim().is_empty()).then(|| PathBuf::from(shellexpand::tilde(&path).to_string()));
+        changed = true;
+    }
+
+    if let Some(theme) = args.theme {
+        config.theme = theme::Theme::parse(&theme)?;
+        changed = true;
+    }
+
+    if let Some(path) = args.sync_repo {
+        config.sync.repo = Some(PathBuf::from(shellexpand::tilde(path.to_string_lossy().as_ref()).to_string()));
+        changed = true;
+    }
+
+    Ok(changed)
+}
+
+/// Apply the on/off `holocron config` flags, returning whether any was given
+fn apply_toggle_args(args: &ConfigArgs, config: &mut Config) -> bool {
+    let toggles = [
+        (args.auto_link, &mut config.auto_link),
+        (args.readme_authors, &mut config.readme_authors),
+        (args.suggest_followups, &mut config.suggest_followups),
+        (args.autosave_sessions, &mut config.autosave_sessions),
+        (args.inject_archive_context, &mut config.inject_archive_context),
+        (args.notifications, &mut config.notifications),
+        (args.suggest_til, &mut config.suggest_til),
+        (args.require_recall, &mut config.require_recall),
+        (args.verify_tils, &mut config.verify_tils),
+        (args.link_webfetch_only, &mut config.link_webfetch_only),
+        (args.emoji, &mut config.emoji),
+    ];
+    let mut changed = false;
+    for (flag, setting) in toggles {
+        if let Some(enabled) = flag {
+            *setting = enabled;
+            changed = true;
+        }
+    }
+    changed
+}
+
+/// Run the til command: optionally draft TILs from past sessions, then review drafts
+fn run_til(args: TilArgs, config: &Config) -> Result<()> {
+    if args.from_sessions {
+        draft_tils_from_sessions(&args, config)?;
+    }
+    review_drafts(config)
+}
+
+/// Draft a TIL for every stored session since `--since` that has nothing saved yet
+fn draft_tils_from_sessions(args: &TilArgs, config: &Config) -> Result<()> {
+    let since = session_store::parse_since(&args.since, chrono::Local::now())?;
+    let sessions_dir = Config::sessions_dir()?;
+    let pending = session_store::without_artifacts(session_store::load_all(&sessions_dir)?, since);
+
+    if pending.is_empty() {
+        println!("{}", "No sessions without a TIL in that period.".warning());
+        return Ok(());
+    }
+
+    println!("{}", format!("{} session(s) without a TIL:", pending.len()).bold());
+    for session in &pending {
+        println!(
+            "  {}  {} {}",
+            session.started_at.format("%Y-%m-%d %H:%M").to_string().muted(),
+            session.topic(),
+            format!("({} exchanges)", session.exchanges.len()).muted()
+        );
+    }
+    println!();
+
+    if config.dry_run {
+        println!("{}", "Dry run: no TILs were drafted.".warning());
+        return Ok(());
+    }
+
+    if !args.yes {
+        let proceed = Select::with_theme(&output::prompts()?)
+            .with_prompt("Draft TILs for these sessions?")
+            .items(&["Yes, draft them", "No, cancel"])
+            .default(0)
+            .interact()?;
+        if proceed != 0 {
+            return Ok(());
+        }
+    }
+
+    let memory = til::category::CategoryMemory::load(&Config::category_memory_path()?)?;
+    let drafts_dir = Config::drafts_dir()?;
+    let mut spent = claude::Usage::default();
+    let total = pending.len();
+
+    for (i, mut session) in pending.into_iter().enumerate() {
+        if args.budget.is_some_and(|budget| spent.cost_usd >= budget) {
+            println!("{}", format!("Budget reached; skipping {} remaining session(s).", total - i).warning());
+            break;
+        }
+
+        // Stored conversations may have expired on the backend, so rebuild from the saved exchanges
+        session.claude_session_id = None;
+        session.context_tokens = config.til_context_tokens;
+
+        let spinner = create_spinner(&format!("Drafting TIL for {}...", session.topic()));
+        let style = category_style(session.category.as_deref(), config)?;
+        let result = til::generate_til(&session, &style, |_| {});
+        spinner.finish_and_clear();
+
+        let response = match result {
+            Ok(response) => response,
+            Err(e) => {
+                println!("{} {}: {}", "Could not draft TIL for".warning(), session.topic(), e);
+                continue;
+            }
+        };
+        spent.add(&response.usage);
+        session.record_usage(&response.usage);
+
+        let category = session
+            .category
+            .clone()
+            .or_else(|| memory.lookup(session.topic()).map(|c| c.to_string()))
+            .unwrap_or_else(|| "uncategorized".to_string());
+        let title = til::writer::extract_title(&response.text).unwrap_or_else(|| "Untitled TIL".to_string());
+        let filename = til::writer::title_to_filename(&title);
+
+        let content = markdown::add_tags(&response.text, &session.tags);
+        let path = til::drafts::save_draft(&drafts_dir, &category, &filename, &content)?;
+        session.add_artifact(path);
+        session_store::save(&sessions_dir, &session)?;
+
+        println!("{} Drafted {}/{}", theme::done(), category, filename);
+    }
+
+    println!(
+        "{}",
+        format!("Spent {} tokens · ${:.4}", spent.total_tokens(), spent.cost_usd).muted()
+    );
+    println!();
+
+    Ok(())
+}
+
+/// Walk through drafted TILs one at a time, saving, editing, deferring or discarding each
+fn review_drafts(config: &Config) -> Result<()> {
+    let drafts = til::drafts::list_drafts(&Config::drafts_dir()?)?;
+    if drafts.is_empty() {
+        println!("{}", "No drafts to review.".warning());
+        return Ok(());
+    }
+
+    let total = drafts.len();
+    for (i, draft) in drafts.into_iter().enumerate() {
+        println!();
+        println!(
+            "{} {}",
+            format!("Draft {}/{}", i + 1, total).bold(),
+            format!("{}/{}", draft.category, draft.filename).muted()
+        );
+        print_rule();
+        println!("{}", draft.content.trim_end());
+        print_rule();
+
+        review_draft(draft, config)?;
+    }
+
+    Ok(())
+}
+
+fn review_draft(draft: til::drafts::Draft, config: &Config) -> Result<()> {
+    if let Some(ref target) = config.append_to {
+        return append_draft(&draft, target, config);
+    }
+    let mut content = draft.content.clone();
+    let mut category = draft.category.clone();
+
+    loop {
+        let (mut plan, filename) = plan_til_write(&content, &category, config)?;
+
+        if config.dry_run {
+            print_til_preview(&plan, config);
+            return Ok(());
+        }
+
+        match save_prompt::prompt_save_action(&format!("{}/{}", category, filename), save_prompt::DRAFT_ACTIONS)? {
+            SaveAction::Save => {
+                choose_readme_spot(&mut plan)?;
+                let saved = save_til(&plan, config)?;
+                til::drafts::remove_draft(&draft)?;
+                println!("{} {} {}", theme::done(), "TIL saved to:".success().bold(), saved);
+                run_post_save_hooks("til", &saved.to_string(), &content, &category, "", config);
+                return Ok(());
+            }
+            SaveAction::Edit => content = launcher::edit_text(&content, "md")?,
+            SaveAction::ChangeCategory => category = prompt_category_input()?,
+            SaveAction::Preview => print_til_preview(&plan, config),
+            SaveAction::Later => {
+                if content != draft.content {
+                    std::fs::write(&draft.path, &content)?;
+                }
+                println!("{}", "Kept as a draft.".muted());
+                return Ok(());
+            }
+            SaveAction::Discard => {
+                til::drafts::remove_draft(&draft)?;
+                println!("{}", "Draft discarded.".warning());
+                return Ok(());
+            }
+            SaveAction::Regenerate | SaveAction::Revise | SaveAction::Verify => {}
+        }
+    }
+}
+
+/// Merge a draft into the `--append-to` TIL instead of saving it as a new one
+fn append_draft(draft: &til::drafts::Draft, path: &std::path::Path, config: &Config) -> Result<()> {
+    let existing = read_til(path, config)?;
+    let drafted = std::slice::from_ref(&draft.content);
+    let mut merged = stream_merged_duplicates(&existing, drafted)?;
+    let label = path.strip_prefix(config.archive_path()).unwrap_or(path).display().to_string();
+
+    let mut shown_diff = None;
+    loop {
+        if config.dry_run {
+            print_append_preview(&existing, &merged, &label);
+            println!("{}", "Dry run: nothing was written.".warning());
+            return Ok(());
+        }
+        show_overwrite_diff(&existing, &merged, &label, &mut shown_diff);
+
+        match save_prompt::prompt_save_action(&label, save_prompt::APPEND_ACTIONS)? {
+            SaveAction::Save => {
+                write_til(path, &merged, config)?;
+                til::drafts::remove_draft(draft)?;
+                println!("{} {} {}", theme::done(), "TIL updated:".success().bold(), path.display());
+                run_post_save_hooks("til", &path.display().to_string(), &merged, &draft.category, "", config);
+                return Ok(());
+            }
+            SaveAction::Edit => merged = launcher::edit_text(&merged, "md")?,
+            SaveAction::Regenerate => merged = stream_merged_duplicates(&existing, drafted)?,
+            SaveAction::Preview => print_append_preview(&existing, &merged, &label),
+            SaveAction::Revise | SaveAction::Verify | SaveAction::ChangeCategory | SaveAction::Later => {}
+            SaveAction::Discard => {
+                println!("{}", "Merge discarded; kept as a draft.".warning());
+                return Ok(());
+            }
+        }
+    }
+}
+
+/// Propose a TIL for each learning in a conversation log, keep them as drafts and review each
+fn run_extract(file: &std::path::Path, config: &Config) -> Result<()> {
+    let file = PathBuf::from(shellexpand::tilde(file.to_string_lossy().as_ref()).to_string());
+    let conversation = til::extract::read_conversation(&file)?;
+    let categories = if config.categories.is_empty() {
+        til::category::existing_categories(&config.archive_path())?
+    } else {
+        config.categories.clone()
+    };
+
+    let spinner = create_spinner(&format!(
+        "Looking for learnings in {} words...",
+        conversation.split_whitespace().count()
+    ));
+    let result = til::extract::extract_learnings(&conversation, &categories);
+    spinner.finish_and_clear();
+    let (learnings, usage) = result?;
+
+    println!(
+        "{}",
+        format!("Spent {} tokens · ${:.4}", usage.total_tokens(), usage.cost_usd).muted()
+    );
+    if learnings.is_empty() {
+        println!("{}", "No learnings worth a TIL were found.".warning());
+        return Ok(());
+    }
+    println!("{} Found {} learning(s)", theme::done(), learnings.len());
+
+    // Kept as drafts first, so anything not reviewed now shows up in `holocron til` later
+    let drafts_dir = Config::drafts_dir()?;
+    let total = learnings.len();
+    for (i, learning) in learnings.into_iter().enumerate() {
+        let title = til::writer::extract_title(&learning.content).unwrap_or_else(|| "Untitled TIL".to_string());
+        let filename = til::writer::title_to_filename(&title);
+
+        println!();
+        println!(
+            "{} {}",
+            format!("Learning {}/{}", i + 1, total).bold(),
+            format!("{}/{}", learning.category, filename).muted()
+        );
+        print_rule();
+        println!("{}", learning.content.trim_end());
+        print_rule();
+
+        if config.dry_run {
+            println!("{}", "Dry run: not drafted.".warning());
+            continue;
+        }
+        let path = til::drafts::save_draft(&drafts_dir, &learning.category, &filename, &learning.content)?;
+        let draft = til::drafts::Draft {
+            category: learning.category,
+            filename: path.file_name().map_or(filename, |name| name.to_string_lossy().to_string()),
+            path,
+            content: learning.content,
+        };
+        review_draft(draft, config)?;
+    }
+
+    Ok(())
+}
+
+/// Run the browse command, running palette actions until the user quits
+fn run_browse(config: &Config) -> Result<()> {
+    output::require_terminal()?;
+    loop {
+        let action = tui::browse(archive::all_entries(config)?)?;
+        if action == tui::Action::Quit {
+            return Ok(());
+        }
+
+        let pause = !matches!(action, tui::Action::OpenEntry(_));
+        if let Err(e) = run_browse_action(action, config) {
+            println!("{} {}", "Error:".failure().bold(), e);
+        }
+
+        if pause {
+            let _: String = Input::with_theme(&output::prompts()?)
+                .with_prompt("Press enter to return to the browser")
+                .allow_empty(true)
+                .interact_text()?;
+        }
+    }
+}
+
+fn run_browse_action(action: tui::Action, config: &Config) -> Result<()> {
+    match action {
+        tui::Action::OpenEntry(path) => launcher::open_in_editor(&path),
+        tui::Action::StartSession => {
+            require_backend()?;
+            let topic: String = Input::with_theme(&output::prompts()?)
+                .with_prompt("Topic")
+                .interact_text()?;
+            let category = prompt_for_category(config)?;
+            let mut session = Session::new(LearningMode::DeepDive { topic }, category);
+            let prompt = deep_dive_prompt(&mut session, config);
+            run_tui_session(session, prompt, config)
+        }
+        tui::Action::AnalyzeLink => {
+            require_backend()?;
+            let url: String = Input::with_theme(&output::prompts()?)
+                .with_prompt("URL")
+                .interact_text()?;
+            let category = prompt_for_category(config)?;
+            let session = new_link_session(&url, None, category, config);
+            run_tui_session(session, link_prompt(&url, config), config)
+        }
+        tui::Action::DraftFromSessions => {
+            require_backend()?;
+            let args = TilArgs {
+                from_sessions: true,
+                since: "7d".to_string(),
+                budget: None,
+                yes: false,
+                append_to: None,
+                save: SaveArgs::default(),
+            };
+            run_til(args, config)
+        }
+        tui::Action::ReviewDrafts => review_drafts(config),
+        tui::Action::RegenerateReadme => run_check(true, config),
+        tui::Action::Relink => run_relink(config),
+        tui::Action::ExportBook => {
+            let target = ExportTarget::Book {
+                format: "epub".to_string(),
+                out: None,
+                title: "Today I Learned".to_string(),
+                category: None,
+                since: None,
+                until: None,
+            };
+            run_export(target, config)
+        }
+        tui::Action::Sync => run_sync(config),
+        // Handled inside the browser
+        tui::Action::Search | tui::Action::Quit => Ok(()),
+    }
+}
+
+/// Run the check command
+fn run_check(fix: bool, config: &Config) -> Result<()> {
+    let check = til::check::check_archive(&config.til_path, &config.archive_dir)?;
+
+    if check.issues.is_empty() {
+        println!("{} README and archive are in sync", theme::done());
+        return Ok(());
+    }
+
+    for issue in &check.issues {
+        println!("  {} {}", theme::failed(), issue);
+    }
+    println!();
+
+    if !fix {
+        println!("{} issue(s). Run {} to repair the README.", check.issues.len(), "holocron check --fix".info());
+        return Ok(());
+    }
+
+    let fixed = check.fixed_readme()?;
+    if config.dry_run {
+        print_diff(&diff::unified_diff(&check.readme, &fixed, "a/README.md", "b/README.md", 3));
+        println!("{}", "Dry run: README.md was not changed.".warning());
+        return Ok(());
+    }
+
+    let _lock = files::lock(&check.readme_path)?;
+    if std::fs::read_to_string(&check.readme_path)? != check.readme {
+        return Err(anyhow!("README.md changed while it was being checked. Run holocron check --fix again."));
+    }
+    record_undo(config, "Repair README.md", &[&check.readme_path])?;
+    files::write_atomic(&check.readme_path, fixed)?;
+    println!("{} Repaired {} issue(s) in {}", theme::done(), check.issues.len(), check.readme_path.display());
+
+    Ok(())
+}
+
+/// Run the relink command
+fn run_relink(config: &Config) -> Result<()> {
+    let mut changed = til::linker::relink_archive(&config.archive_path(), true)?;
+    if !config.dry_run && !changed.is_empty() {
+        let paths: Vec<&std::path::Path> = changed.iter().map(PathBuf::as_path).collect();
+        record_undo(config, "Relink the archive", &paths)?;
+        changed = til::linker::relink_archive(&config.archive_path(), false)?;
+    }
+
+    for path in &changed {
+        println!("  {} {}", "linked".success(), path.display());
+    }
+    if config.dry_run {
+        println!("{}", format!("Dry run: {} TIL(s) would be relinked.", changed.len()).warning());
+    } else {
+        println!("{} Relinked {} TIL(s)", theme::done(), changed.len());
+    }
+
+    Ok(())
+}
+
+/// Report groups of near-duplicate TILs, then offer to merge each one
+fn run_dedupe(config: &Config) -> Result<()> {
+    let archive_path = config.archive_path();
+    let mut tils = Vec::new();
+    for entry in archive::til_entries(&archive_path)? {
+        let content = std::fs::read_to_string(&entry.path)?;
+        tils.push((entry, content));
+    }
+
+    let groups = til::dedupe::find_groups(&tils);
+    if groups.is_empty() {
+        println!("No near-duplicate TILs found in {} TIL(s).", tils.len());
+        return Ok(());
+    }
+    println!("{}", format!("{} group(s) of near-duplicate TILs:", groups.len()).bold());
+    for (i, group) in groups.iter().enumerate() {
+        println!();
+        let similarity = format!("{:.0}% alike", group.similarity * 100.0);
+        println!("{} {}", format!("{}.", i + 1).info(), similarity.muted());
+        for entry in &group.entries {
+            let path = entry.path.strip_prefix(&archive_path).unwrap_or(&entry.path);
+            println!("   {}  {}", entry.title, path.display().to_string().muted());
+        }
+    }
+    println!();
+
+    if config.dry_run {
+        println!("{}", "Dry run: nothing was merged.".warning());
+        return Ok(());
+    }
+    if !io::stdin().is_terminal() {
+        return Ok(());
+    }
+    require_backend()?;
+    for (i, group) in groups.iter().enumerate() {
+        if !merge_duplicates(i + 1, group, config)? {
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// Offer to merge one group of duplicates into the TIL picked to keep, removing the others and
+/// their README entries. Returns false to stop going through groups.
+fn merge_duplicates(number: usize, group: &til::dedupe::Group, config: &Config) -> Result<bool> {
+    let mut choices: Vec<String> = group.entries.iter().map(|entry| format!("Merge into \"{}\"", entry.title)).collect();
+    choices.push("Skip this group".to_string());
+    choices.push("Stop".to_string());
+    let choice = Select::with_theme(&output::prompts()?)
+        .with_prompt(format!("Group {}", number))
+        .items(&choices)
+        .default(0)
+        .interact()?;
+    if choice == group.entries.len() {
+        return Ok(true);
+    }
+    let Some(keep) = group.entries.get(choice) else {
+        return Ok(false);
+    };
+
+    let existing = std::fs::read_to_string(&keep.path)?;
+    let others: Vec<&archive::Entry> = group.entries.iter().filter(|entry| entry.path != keep.path).collect();
+    let contents = others
+        .iter()
+        .map(|entry| std::fs::read_to_string(&entry.path))
+        .collect::<io::Result<Vec<String>>>()?;
+    let label = keep.path.strip_prefix(config.archive_path()).unwrap_or(&keep.path).display().to_string();
+
+    let mut merged = stream_merged_duplicates(&existing, &contents)?;
+    let mut shown_diff = None;
+    loop {
+        show_overwrite_diff(&existing, &merged, &label, &mut shown_diff);
+        match save_prompt::prompt_save_action(&label, save_prompt::APPEND_ACTIONS)? {
+            SaveAction::Save => {
+                let dropped: Vec<&std::path::Path> = others.iter().map(|entry| entry.path.as_path()).collect();
+                save_merged_duplicates(&keep.path, &merged, &dropped, config)?;
+                println!("{} Merged {} TIL(s) into {}", theme::done(), dropped.len(), label);
+                let category = keep.category.as_deref().unwrap_or_default();
+                run_post_save_hooks("til", &keep.path.display().to_string(), &merged, category, "", config);
+                return Ok(true);
+            }
+            SaveAction::Edit => merged = launcher::edit_text(&merged, "md")?,
+            SaveAction::Regenerate => merged = stream_merged_duplicates(&existing, &contents)?,
+            SaveAction::Preview => print_append_preview(&existing, &merged, &label),
+            SaveAction::Revise | SaveAction::Verify | SaveAction::ChangeCategory | SaveAction::Later => {}
+            SaveAction::Discard => {
+                println!("{}", "Merge discarded.".warning());
+                return Ok(true);
+            }
+        }
+    }
+}
+
+/// Merge duplicates into `existing` with the backend, streaming the result. The kept TIL's title stays.
+fn stream_merged_duplicates(existing: &str, others: &[String]) -> Result<String> {
+    println!();
+    let spinner = create_spinner("Merging duplicates...");
+    let mut merged = String::new();
+    til::dedupe::merge(existing, others, |text| {
+        if merged.is_empty() {
+            spinner.finish_and_clear();
+            println!("{}", "Merged TIL:".success().bold());
+            print_rule();
+        }
+        print!("{}", text);
+        io::stdout().flush().ok();
+        merged.push_str(text);
+    })?;
+    spinner.finish_and_clear();
+    println!();
+    print_rule();
+    Ok(til::append::keep_title(existing, &merged))
+}
+
+/// Write the merged TIL, delete the duplicates and drop their README entries, recorded for undo
+fn save_merged_duplicates(
+    keep: &std::path::Path,
+    merged: &str,
+    dropped: &[&std::path::Path],
+    config: &Config,
+) -> Result<()> {
+    let readme_path = config.til_path.join("README.md");
+    let mut paths = vec![keep, readme_path.as_path()];
+    paths.extend_from_slice(dropped);
+    let label = keep.strip_prefix(config.archive_path()).unwrap_or(keep);
+    record_undo(config, &format!("Merge duplicates into {}", label.display()), &paths)?;
+
+    let _lock = files::lock(&readme_path)?;
+    files::write_atomic(keep, format!("{}\n", merged.trim_end()))?;
+    for path in dropped {
+        std::fs::remove_file(path).map_err(|e| anyhow!("Failed to remove {:?}: {}", path, e))?;
+    }
+    let links: Vec<String> = dropped
+        .iter()
+        .filter_map(|path| path.strip_prefix(&config.til_path).ok())
+        .map(|path| path.to_string_lossy().replace('\\', "/"))
+        .collect();
+    let readme = std::fs::read_to_string(&readme_path)?;
+    files::write_atomic(&readme_path, til::check::remove_entries(&readme, &links))?;
+    Ok(())
+}
+
+/// Run the undo command: put back the files the last recorded operation changed
+fn run_undo(config: &Config) -> Result<()> {
+    let Some(operation) = undo::last(&config.til_path)? else {
+        println!("{}", "Nothing to undo.".warning());
+        return Ok(());
+    };
+    if config.dry_run {
+        println!("{}", format!("Dry run: would undo \"{}\"", operation.description).warning());
+        for file in &operation.files {
+            let action = if file.existed { "restore" } else { "remove" };
+            println!("  {} {}", action.info(), file.path.display());
+        }
+        return Ok(());
+    }
+
+    undo::undo_last(&config.til_path)?;
+    for file in &operation.files {
+        let action = if file.existed { "restored" } else { "removed" };
+        println!("  {} {}", action.success(), file.path.display());
+    }
+    println!(
+        "{} Undid \"{}\" from {}",
+        theme::done(),
+        operation.description,
+        operation.recorded_at.format("%Y-%m-%d %H:%M")
+    );
+    Ok(())
+}
+
+/// Keep the files an operation is about to change, so `holocron undo` can revert it
+fn record_undo(config: &Config, description: &str, paths: &[&std::path::Path]) -> Result<()> {
+    let mut snapshot = undo::Snapshot::new(&config.til_path, description);
+    for path in paths {
+        snapshot.capture(path)?;
+    }
+    snapshot.save()
+}
+
+/// Run the share command on the stored session best matching `query`
+fn run_share(
+    query: &str,
+    content: share::ShareContent,
+    target: share::ShareTarget,
+    out: Option<PathBuf>,
+    config: &Config,
+) -> Result<()> {
+    let sessions = session_store::load_all(&Config::sessions_dir()?)?;
+    let session = if query.eq_ignore_ascii_case("last") {
+        sessions.last()
+    } else {
+        // Later sessions win ties, so the most recent match is shared
+        sessions
+            .iter()
+            .filter_map(|s| archive::fuzzy_score(query, s.topic()).map(|score| (score, s)))
+            .max_by_key(|(score, _)| *score)
+            .map(|(_, s)| s)
+    };
+    let session = session.ok_or_else(|| anyhow!("No stored session matches '{}'", query))?;
+
+    let out_dir = out.unwrap_or_else(|| PathBuf::from("."));
+    share_session(session, content, target, &out_dir, config)
+}
+
+/// Share a session (or what was saved from it) as a file or gist and print where it went
+fn share_session(
+    session: &Session,
+    content: share::ShareContent,
+    target: share::ShareTarget,
+    out_dir: &std::path::Path,
+    config: &Config,
+) -> Result<()> {
+    let shareable = share::build(session, content, &config.archive_path(), config.notes_path.as_deref())?;
+
+    match target {
+        share::ShareTarget::File if config.dry_run => {
+            let path = out_dir.join(&shareable.filename);
+            println!("{}", format!("Dry run: would write {}", path.display()).warning());
+        }
+        share::ShareTarget::File => {
+            let path = share::write_file(&shareable, out_dir)?;
+            println!("{} {} {}", theme::done(), "Shared to:".success().bold(), path.display());
+        }
+        share::ShareTarget::Gist => {
+            let token = config.share.github_token.as_deref().ok_or_else(|| {
+                anyhow!("No GitHub token configured. Set `github_token` under [share] in the config file")
+            })?;
+            if config.dry_run {
+                println!("{}", format!("Dry run: would create a gist named {}", shareable.filename).warning());
+                return Ok(());
+            }
+
+            let spinner = create_spinner("Creating gist...");
+            let url = share::create_gist(&shareable, &format!("holocron: {}", session.topic()), token, false);
+            spinner.finish_and_clear();
+            println!("{} {} {}", theme::done(), "Shared to:".success().bold(), url?);
+        }
+    }
+
+    Ok(())
+}
+
+/// Publish the session's saved TIL without its private parts, and note where in its frontmatter
+fn publish_saved_til(session: &Session, config: &Config) -> Result<()> {
+    let til = session
+        .saved_til
+        .as_ref()
+        .ok_or_else(|| anyhow!("No TIL saved from this session yet. Save one with /til first"))?;
+    if crypto::is_encrypted(til) {
+        return Err(anyhow!("The TIL is encrypted; publishing it would expose it"));
+    }
+
+    let content = std::fs::read_to_string(til)?;
+    if let Some(previous) = publish::published_location(&content) {
+        println!("{}", format!("Already published to {}; publishing it again.", previous).muted());
+    }
+    if config.dry_run {
+        println!("{}", format!("Dry run: would publish {}", til.display()).warning());
+        return Ok(());
+    }
+
+    let category = archive::entry_category(&config.archive_path(), til);
+    let spinner = create_spinner("Publishing...");
+    let location = publish::publish(
+        til,
+        category.as_deref(),
+        &config.publish,
+        &config.til_path,
+        config.share.github_token.as_deref(),
+    );
+    spinner.finish_and_clear();
+    let location = location?;
+
+    // Re-read under the lock: the TIL may have been edited while it was being published
+    let _lock = files::lock(til)?;
+    let content = std::fs::read_to_string(til)?;
+    record_undo(config, &format!("Mark {} published", til.display()), &[til])?;
+    files::write_atomic(til, publish::mark_published(&content, &location, chrono::Local::now().date_naive()))?;
+    println!("{} {} {}", theme::done(), "Published to:".success().bold(), location);
+    Ok(())
+}
+
+/// The bookmarks in a browser export, narrowed to `folder`, or to a folder picked from a
+/// list when none is given and there's a terminal to ask in
+fn import_bookmarks(path: &std::path::Path, folder: Option<&str>) -> Result<Vec<queue::import::Article>> {
+    let content = std::fs::read_to_string(path).map_err(|e| anyhow!("Failed to read {:?}: {}", path, e))?;
+    let bookmarks = queue::bookmarks::parse(&content)?;
+    let folders = queue::bookmarks::folders(&bookmarks);
+
+    let folder = match folder {
+        Some(folder) => Some(folder.to_string()),
+        None if !folders.is_empty() && io::stdin().is_terminal() => {
+            let mut items = vec![format!("All bookmarks ({})", bookmarks.len())];
+            items.extend(folders.iter().cloned());
+            let choice = Select::with_theme(&output::prompts()?)
+                .with_prompt("Which folder should be queued?")
+                .items(&items)
+                .default(0)
+                .interact()?;
+            choice.checked_sub(1).map(|index| folders[index].clone())
+        }
+        None => None,
+    };
+
+    let Some(folder) = folder else {
+        return Ok(bookmarks.into_iter().map(Into::into).collect());
+    };
+    let selected: Vec<queue::import::Article> =
+        bookmarks.into_iter().filter(|bookmark| bookmark.is_in(&folder)).map(Into::into).collect();
+    if selected.is_empty() {
+        return Err(anyhow!("No bookmarks in a folder called {:?}. Folders: {}", folder, folders.join(", ")));
+    }
+    Ok(selected)
+}
+
+/// Run the queue command: list the queue, import into it, or start the next session
+fn run_notes(action: NotesAction, config: &Config) -> Result<()> {
+    let notes_path = config.notes_path.as_ref().ok_or_else(|| {
+        anyhow!("Notes path not configured. Run: holocron config --notes-path <path>")
+    })?;
+    let index = notes::index::NotesIndex::load(notes_path)?;
+    let notes: Vec<&notes::index::IndexedNote> = index
+        .existing(notes_path)
+        .into_iter()
+        .filter(|note| match action {
+            NotesAction::List { ref tag } => tag.as_deref().is_none_or(|tag| note.has_tag(tag)),
+            NotesAction::Search { ref query } => note.matches(query),
+        })
+        .collect();
+
+    if notes.is_empty() {
+        let hint = if index.notes.is_empty() {
+            "No notes indexed yet. Notes are added to notes.json as you save them with /note."
+        } else {
+            "No matching notes."
+        };
+        println!("{}", hint.warning());
+        return Ok(());
+    }
+
+    for note in notes {
+        let tags = if note.tags.is_empty() {
+            String::new()
+        } else {
+            format!(" #{}", note.tags.join(" #"))
+        };
+        let details: Vec<String> = note
+            .reading_minutes
+            .map(|minutes| format!("{} min", minutes))
+            .into_iter()
+            .chain(note.level.clone())
+            .collect();
+        let details = if details.is_empty() {
+            String::new()
+        } else {
+            format!("  {}", details.join(" · "))
+        };
+        println!(
+            "{}  {}{}{}",
+            note.title.bold(),
+            note.date.format("%Y-%m-%d").to_string().muted(),
+            details.info(),
+            tags.info()
+        );
+        println!("     {}", note.path.muted());
+        if let Some(ref til) = note.til {
+            println!("     {} {}", "TIL:".muted(), til.display().to_string().muted());
+        }
+    }
+    Ok(())
+}
+
+/// How many untitled links listing the queue looks up at a time, so a big import doesn't stall it
+const TITLE_LOOKUPS: usize = 10;
+
+fn run_queue(action: Option<QueueAction>, config: &Config) -> Result<()> {
+    let queue_path = Config::queue_path()?;
+    let mut queue = queue::Queue::load(&queue_path)?;
+
+    match action {
+        None => {
+            if queue.items.is_empty() {
+                println!("{}", "The queue is empty. Fill it with: holocron queue import --source <service>".warning());
+            }
+            let spinner = create_spinner("Looking up page titles...");
+            let found = queue.fill_titles(TITLE_LOOKUPS);
+            spinner.finish_and_clear();
+            if found > 0 && !config.dry_run {
+                queue.save(&queue_path)?;
+            }
+            for (i, item) in queue.items.iter().enumerate() {
+                println!("{:>3}. {} {}", i + 1, item.label().bold(), format!("({})", item.source).muted());
+                if item.title.is_some() {
+                    println!("     {}", item.url.muted());
+                }
+            }
+        }
+        Some(QueueAction::Import {
+            source,
+            bookmarks,
+            folder,
+        }) => {
+            let (articles, source) = match (source, bookmarks) {
+                (_, Some(path)) => (import_bookmarks(&path, folder.as_deref())?, "bookmarks".to_string()),
+                (Some(source), None) => {
+                    let source = queue::import::ImportSource::parse(&source)?;
+                    let spinner = create_spinner(&format!("Fetching articles from {}...", source));
+                    let articles = queue::import::fetch(source, &config.import);
+                    spinner.finish_and_clear();
+                    (articles?, source.to_string())
+                }
+                (None, None) => return Err(anyhow!("Give --source <service> or --bookmarks <file>")),
+            };
+
+            let processed = queue::processed_urls(&session_store::load_all(&Config::sessions_dir()?)?);
+            let found = articles.len();
+            let now = chrono::Local::now();
+            let items = articles
+                .into_iter()
+                .map(|article| queue::QueueItem {
+                    url: article.url,
+                    title: article.title,
+                    source: source.clone(),
+                    added: now,
+                    text: None,
+                })
+                .collect();
+            let added = queue.enqueue(items, &processed);
+
+            if config.dry_run {
+                println!("{}", format!("Dry run: would queue {} of {} article(s)", added, found).warning());
+                return Ok(());
+            }
+            queue.save(&queue_path)?;
+            println!(
+                "{} Queued {} new article(s) from {} ({} already queued or studied)",
+                theme::done(),
+                added,
+                source,
+                found - added
+            );
+        }
+        Some(QueueAction::Next { .. }) => {
+            let item = queue.pop().ok_or_else(|| anyhow!("The queue is empty"))?;
+            if !config.dry_run {
+                queue.save(&queue_path)?;
+            }
+
+            match item.text {
+                // A captured snippet is something to dig into rather than a page to read
+                Some(topic) if item.url.is_empty() => {
+                    let mut session = Session::new(LearningMode::DeepDive { topic }, None);
+                    let prompt = deep_dive_prompt(&mut session, config);
+                    run_learning_session(session, prompt, config)?;
+                }
+                _ => {
+                    let session = new_link_session(&item.url, item.title.clone(), None, config);
+                    run_learning_session(session, link_prompt(&item.url, config), config)?;
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Run the serve command. `--capture` is the only endpoint: links and snippets sent to it land in
+/// the learning queue.
+fn run_serve(capture: bool, listen: &str, config: &Config) -> Result<()> {
+    if !capture {
+        return Err(anyhow!("Nothing to serve. Run: holocron serve --capture"));
+    }
+    let token = capture_token(config)?;
+    let listener = std::net::TcpListener::bind(listen).map_err(|e| anyhow!("Failed to listen on {}: {}", listen, e))?;
+
+    println!("{}", theme::banner_rule());
+    println!("{}", "  Capture endpoint".accent().bold());
+    println!("{}", theme::banner_rule());
+    println!("Listening on {}", format!("http://{}/queue", listen).info());
+    println!("Send links or text with the header {}", format!("Authorization: Bearer {}", token).info());
+    println!("{}", "Plain HTTP: use it on a network you trust, or behind a tunnel. Press Ctrl+C to stop.".muted());
+    println!();
+
+    for stream in listener.incoming() {
+        let result = stream
+            .map_err(anyhow::Error::from)
+            .and_then(|stream| answer_capture(stream, &token, config));
+        if let Err(e) = result {
+            eprintln!("{}", format!("Capture request failed: {}", e).warning());
+        }
+    }
+    Ok(())
+}
+
+/// The capture endpoint's token, generated and saved to the config file the first time
+fn capture_token(config: &Config) -> Result<String> {
+    if let Some(ref token) = config.capture.token {
+        return Ok(token.clone());
+    }
+    let token = capture::new_token()?;
+    if !config.dry_run {
+        // Only the token goes in; the loaded config carries repo-local overrides
+        let Some(mut saved) = Config::load_global()? else {
+            return Err(anyhow!("No config file to save a capture token to. Run: holocron config"));
+        };
+        saved.capture.token = Some(token.clone());
+        saved.save()?;
+        println!("{} Generated a capture token and saved it to the config file", theme::done());
+    }
+    Ok(token)
+}
+
+/// Read one request from a connection, queue what it captured, and reply
+fn answer_capture(stream: std::net::TcpStream, token: &str, config: &Config) -> Result<()> {
+    stream.set_read_timeout(Some(std::time::Duration::from_secs(10)))?;
+    let mut reader = io::BufReader::new(&stream);
+    let response = match capture::read_request(&mut reader) {
+        Ok(request) => match capture::capture(&request, token) {
+            Ok(item) => queue_captured(item, config).unwrap_or_else(|e| {
+                // Still answer, so the sender knows it didn't land
+                eprintln!("{}", format!("Couldn't queue a capture: {:#}", e).warning());
+                capture::Response::error(500, "Couldn't add it to the queue")
+            }),
+            Err(response) => response,
+        },
+        Err(e) => capture::Response::new(400, serde_json::json!({ "error": e.to_string() })),
+    };
+    let mut writer = &stream;
+    response.write_to(&mut writer)?;
+    Ok(())
+}
+
+fn queue_captured(mut item: queue::QueueItem, config: &Config) -> Result<capture::Response> {
+    item.fetch_title();
+    let queue_path = Config::queue_path()?;
+    let mut queue = queue::Queue::load(&queue_path)?;
+    let processed = queue::processed_urls(&session_store::load_all(&Config::sessions_dir()?)?);
+    let label = item.label().to_string();
+
+    if queue.enqueue(vec![item], &processed) == 0 {
+        println!("{} {}", "Already queued or studied:".warning(), label);
+        return Ok(capture::Response::new(200, serde_json::json!({ "queued": false, "reason": "already queued or studied" })));
+    }
+    if config.dry_run {
+        println!("{} {}", "Dry run: would queue".warning(), label);
+    } else {
+        queue.save(&queue_path)?;
+        println!("{} Queued {} ({} waiting)", theme::done(), label.info(), queue.items.len());
+    }
+    Ok(capture::Response::new(201, serde_json::json!({ "queued": true, "waiting": queue.items.len() })))
+}
+
+fn run_plan(action: Option<PlanAction>, topic: &str, category: Option<String>, config: &Config) -> Result<()> {
+    match action {
+        Some(PlanAction::Next { plan, .. }) => start_next_step(plan.as_deref(), config),
+        None if topic.trim().is_empty() => {
+            let plans = plan::list_plans(&config.til_path)?;
+            if plans.is_empty() {
+                println!("{}", "No plans yet. Start one with: holocron plan <topic>".warning());
+            }
+            for (_, plan) in plans {
+                let next = plan.next_step().map_or("done".to_string(), |step| format!("next: {}", step.title));
+                println!(
+                    "{} {} {}",
+                    plan.topic.bold(),
+                    format!("({}/{})", plan.done_count(), plan.steps.len()).muted(),
+                    next.muted()
+                );
+            }
+            Ok(())
+        }
+        None => create_plan(topic.trim(), category, config),
+    }
+}
+
+/// Generate a syllabus for the topic and, once accepted, save it as a checklist
+fn create_plan(topic: &str, category: Option<String>, config: &Config) -> Result<()> {
+    let path = plan::plan_path(&config.til_path, topic);
+    if path.exists() {
+        return Err(anyhow!(
+            "There's already a plan for \"{}\" at {:?}. Continue it with: holocron plan next",
+            topic,
+            path
+        ));
+    }
+
+    loop {
+        let spinner = create_spinner(&format!("Planning {}...", topic));
+        let steps = plan::generate_steps(topic);
+        spinner.finish_and_clear();
+        let new_plan = plan::Plan::new(topic, category.clone(), steps?);
+
+        println!("{}", format!("Plan: {}", topic).bold());
+        for (i, step) in new_plan.steps.iter().enumerate() {
+            println!("  {:>2}. {}", i + 1, step.title);
+        }
+        println!();
+
+        if config.dry_run {
+            println!("{}", format!("Dry run: would save the plan to {:?}", path).warning());
+            return Ok(());
+        }
+
+        let choice = Select::with_theme(&output::prompts()?)
+            .with_prompt("Save this plan?")
+            .items(&["Yes, save it", "Regenerate", "Cancel"])
+            .default(0)
+            .interact()?;
+        match choice {
+            0 => {
+                if let Some(parent) = path.parent() {
+                    std::fs::create_dir_all(parent)?;
+                }
+                std::fs::write(&path, new_plan.render())?;
+                println!("{} Saved the plan to {:?}", theme::done(), path);
+                println!("Start the first step with: holocron plan next");
+                return Ok(());
+            }
+            1 => continue,
+            _ => return Ok(()),
+        }
+    }
+}
+
+/// Start a deep dive on the next unchecked step of a plan
+fn start_next_step(query: Option<&str>, config: &Config) -> Result<()> {
+    let (path, plan) = plan::find_plan(plan::list_plans(&config.til_path)?, query)?;
+    let step = plan
+        .next_step()
+        .ok_or_else(|| anyhow!("Every step of \"{}\" is done", plan.topic))?;
+
+    println!(
+        "{} step {} of {}",
+        plan.topic.bold(),
+        plan.done_count() + 1,
+        plan.steps.len()
+    );
+    let mode = LearningMode::DeepDive {
+        topic: step.title.clone(),
+    };
+    let mut session = Session::new(mode, plan.category.clone());
+    session.plan_step = Some(plan::PlanStep {
+        plan: path,
+        title: step.title.clone(),
+    });
+    let prompt = deep_dive_prompt(&mut session, config);
+    run_learning_session(session, prompt, config)
+}
+
+fn run_sync(config: &Config) -> Result<()> {
+    let repo = config
+        .sync
+        .repo
+        .as_ref()
+        .ok_or_else(|| anyhow!("No state repo configured. Clone one and run: holocron config --sync-repo <path>"))?;
+    let base_path = Config::sync_base_path()?;
+    let base = std::fs::read_to_string(&base_path).ok();
+
+    let spinner = create_spinner("Syncing state...");
+    let report = sync::sync(repo, &Config::config_dir()?, base.as_deref().map(str::trim), config.dry_run);
+    spinner.finish_and_clear();
+    let report = report?;
+
+    if config.dry_run {
+        println!(
+            "{}",
+            format!(
+                "Dry run: would update {} file(s) here and {} in the state repo",
+                report.local_changes, report.repo_changes
+            )
+            .warning()
+        );
+        return Ok(());
+    }
+    if let Some(ref commit) = report.commit {
+        std::fs::write(&base_path, commit)?;
+    }
+
+    println!(
+        "{} Synced: {} file(s) updated here, {} in the state repo",
+        theme::done(),
+        report.local_changes,
+        report.repo_changes
+    );
+    if report.repo_changes > 0 && !report.pushed {
+        println!("{}", "The state repo has no remote yet, so nothing was pushed. Add one with: git remote add origin <url>".warning());
+    }
+    Ok(())
+}
+
+fn run_watch(dir: Option<PathBuf>, interval: u64, config: &Config) -> Result<()> {
+    let mut source = match dir {
+        Some(dir) => watch::Source::drop_dir(PathBuf::from(
+            shellexpand::tilde(dir.to_string_lossy().as_ref()).to_string(),
+        ))?,
+        None => watch::Source::clipboard()?,
+    };
+    println!("Watching {} for links. Press Ctrl+C to stop.", source.to_string().bold());
+
+    // Each link is offered once per watch, however often it's copied
+    let mut offered: HashSet<String> = HashSet::new();
+    loop {
+        for text in source.poll()? {
+            for url in markdown::extract_urls(&text) {
+                if offered.insert(queue::normalize_url(&url)) {
+                    offer_watched_link(&url, config)?;
+                }
+            }
+        }
+        std::thread::sleep(std::time::Duration::from_secs(interval.max(1)));
+    }
+}
+
+/// Ask what to do with a link `holocron watch` spotted: analyze it now, queue it, or skip it
+fn offer_watched_link(url: &str, config: &Config) -> Result<()> {
+    println!();
+    println!("{} {}", "Link:".bold(), url.info());
+    let choice = Select::with_theme(&output::prompts()?)
+        .with_prompt("Analyze this link?")
+        .items(&["Yes, start a session now", "Queue it for later", "No, skip"])
+        .default(1)
+        .interact()?;
+
+    match choice {
+        0 => match require_backend() {
+            Ok(()) => {
+                let session = new_link_session(url, None, None, config);
+                run_learning_session(session, link_prompt(url, config), config)?;
+                println!("Back to watching. Press Ctrl+C to stop.");
+                Ok(())
+            }
+            Err(e) => {
+                println!("{}", e.to_string().warning());
+                queue_watched_link(url, config)
+            }
+        },
+        1 => queue_watched_link(url, config),
+        _ => Ok(()),
+    }
+}
+
+fn queue_watched_link(url: &str, config: &Config) -> Result<()> {
+    let queue_path = Config::queue_path()?;
+    let mut queue = queue::Queue::load(&queue_path)?;
+    let processed = queue::processed_urls(&session_store::load_all(&Config::sessions_dir()?)?);
+    let mut item = queue::QueueItem {
+        url: url.to_string(),
+        title: None,
+        source: "watch".to_string(),
+        added: chrono::Local::now(),
+        text: None,
+    };
+    item.fetch_title();
+
+    if queue.enqueue(vec![item], &processed) == 0 {
+        println!("{}", "Already queued or studied.".warning());
+    } else if config.dry_run {
+        println!("{}", "Dry run: would queue it".warning());
+    } else {
+        queue.save(&queue_path)?;
+        println!("{} Queued ({} waiting). Start it with: holocron queue next", theme::done(), queue.items.len());
+    }
+    Ok(())
+}
+
+/// Run the export command
+fn run_export(target: ExportTarget, config: &Config) -> Result<()> {
+    match target {
+        ExportTarget::Book {
+            format,
+            out,
+            title,
+            category,
+            since,
+            until,
+        } => {
+            let format = export::book::BookFormat::parse(&format)?;
+            let out = out.unwrap_or_else(|| PathBuf::from(format!("til.{}", format.extension())));
+            let filter = export::book::BookFilter {
+                category,
+                since,
+                until,
+            };
+
+            let entries = export::book::collect_entries(&config.archive_path(), &filter)?;
+            if entries.is_empty() {
+                return Err(anyhow!("No TILs match the given filters"));
+            }
+
+            let markdown = export::book::build_book_markdown(&title, &entries);
+            if config.dry_run {
+                println!("{}", format!("Dry run: would export {} TIL(s) to {}", entries.len(), out.display()).warning());
+                return Ok(());
+            }
+
+            let spinner = create_spinner("Rendering book...");
+            let result = export::book::render_book(&markdown, format, &out);
+            spinner.finish_and_clear();
+            result?;
+
+            println!("{} Exported {} TIL(s) to {}", theme::done(), entries.len(), out.display());
+        }
+        ExportTarget::Html { entry, out } => export_html(&entry, out, config)?,
+        ExportTarget::Feed { out, limit, title, url } => {
+            let defaults = export::feed::FeedSettings::from_config(config);
+            let settings = export::feed::FeedSettings {
+                title: title.unwrap_or(defaults.title),
+                url: url.or(defaults.url),
+                limit: limit.unwrap_or(defaults.limit),
+            };
+            let out = out.unwrap_or_else(|| export::feed::default_path(config));
+            if config.dry_run {
+                println!("{}", format!("Dry run: would write the feed to {}", out.display()).warning());
+                return Ok(());
+            }
+
+            let count = export::feed::write(config, &settings, &out)?;
+            println!("{} Wrote {} TIL(s) to {}", theme::done(), count, out.display());
+        }
+    }
+
+    Ok(())
+}
+
+/// Run the history command: stored sessions, newest first, optionally only those with a tag
+fn run_history(tag: Option<&str>, since: Option<&str>) -> Result<()> {
+    let since = since
+        .map(|since| session_store::parse_since(since, chrono::Local::now()))
+        .transpose()?;
+    let tag = tag.map(|tag| tag.trim_start_matches('#').to_lowercase());
+    let sessions: Vec<Session> = session_store::load_all(&Config::sessions_dir()?)?
+        .into_iter()
+        .filter(|s| since.is_none_or(|since| s.started_at >= since))
+        .filter(|s| tag.as_ref().is_none_or(|tag| s.tags.contains(tag)))
+        .collect();
+
+    if sessions.is_empty() {
+        println!("{}", "No sessions found.".warning());
+        return Ok(());
+    }
+
+    for session in sessions.iter().rev() {
+        let mut details = Vec::new();
+        if let Some(ref category) = session.category {
+            details.push(category.clone());
+        }
+        details.extend(session.tags.iter().map(|tag| format!("#{}", tag)));
+        if !session.artifacts.is_empty() {
+            details.push(format!("{} saved", session.artifacts.len()));
+        }
+        println!(
+            "{}  {}  {}",
+            session.started_at.format("%Y-%m-%d %H:%M").to_string().muted(),
+            session.topic(),
+            details.join(" · ").info()
+        );
+    }
+    println!();
+    println!("{}", format!("{} session(s)", sessions.len()).muted());
+
+    Ok(())
+}
+
+/// Render a TIL or note (a path, or words from its title) as a self-contained HTML file
+fn export_html(entry: &str, out: Option<PathBuf>, config: &Config) -> Result<()> {
+    let given = PathBuf::from(shellexpand::tilde(entry).to_string());
+    let path = if given.is_file() {
+        std::path::absolute(&given)?
+    } else {
+        find_entry(entry, "Export which entry?", config)?.path
+    };
+    let content = if crypto::is_encrypted(&path) {
+        crypto::decrypt(&path, &config.encryption)?
+    } else {
+        std::fs::read_to_string(&path).map_err(|e| anyhow!("Failed to read {:?}: {}", path, e))?
+    };
+    export::require_alt_text(&path, &content)?;
+
+    let dir = path.parent().unwrap_or(std::path::Path::new("."));
+    let feed = export::feed::FeedSettings::from_config(config);
+    let links = export::html::LinkBase {
+        dir,
+        repo: feed.url.as_deref().map(|url| (config.til_path.as_path(), url)),
+    };
+    let page = export::html::render_page(&content, &links);
+
+    let out = out.unwrap_or_else(|| {
+        let name = path.file_name().map_or("entry".into(), |name| name.to_string_lossy().to_string());
+        let stem = name.trim_end_matches(".age").trim_end_matches(".gpg").trim_end_matches(".md");
+        PathBuf::from(format!("{}.html", stem))
+    });
+    if config.dry_run {
+        println!("{}", format!("Dry run: would export {} to {}", path.display(), out.display()).warning());
+        return Ok(());
+    }
+    export::html::write(&out, &page)?;
+    println!("{} Exported {} to {}", theme::done(), path.display(), out.display());
+    Ok(())
+}
+
+/// Run the digest command: print what was learned lately, or email it as HTML
+fn run_digest(days: u32, email: bool, config: &Config) -> Result<()> {
+    let sessions = session_store::load_all(&Config::sessions_dir()?)?;
+    let now = chrono::Local::now();
+    let digest = digest::Digest::collect(config, &sessions, now.date_naive(), days)?;
+
+    if !email {
+        println!("{}", digest.subject().bold());
+        for til in &digest.tils {
+            let category = til.category.as_deref().map(|c| format!(" ({})", c)).unwrap_or_default();
+            println!("  {} {}{}", til.date.format("%a %b %-d").to_string().muted(), til.title, category.info());
+        }
+        for (date, topic, _) in &digest.sessions {
+            println!("  {} {}", date.format("%a %b %-d").to_string().muted(), format!("Session: {}", topic).muted());
+        }
+        return Ok(());
+    }
+
+    if digest.is_empty() {
+        println!("{}", format!("Nothing learned in the last {} day(s); no digest sent.", days).muted());
+        return Ok(());
+    }
+    let Some(ref to) = config.email.to else {
+        return Err(anyhow::anyhow!("No address to send the digest to. Set `to` under [email] in the config file"));
+    };
+    let from = config.email.from.as_deref().unwrap_or(to);
+    let html = digest.to_html(&export::feed::FeedSettings::from_config(config));
+    let message = mail::html_message(from, to, &digest.subject(), &html, now);
+    if config.dry_run {
+        println!("{}", format!("Dry run: would send \"{}\" to {}", digest.subject(), to).warning());
+        return Ok(());
+    }
+    mail::send(&config.email, from, to, &message)?;
+    println!("{} Sent the digest to {}", theme::done(), to);
+    Ok(())
+}
+
+/// Run the insights command: trends across the archive and session history, read locally
+fn run_insights(days: u32, suggest: bool, config: &Config) -> Result<()> {
+    let spinner = create_spinner("Reading the archive...");
+    let tils: Vec<insights::Record> = archive::til_entries(&config.archive_path())?
+        .iter()
+        .filter_map(insights::Record::from_entry)
+        .collect();
+    let history = session_store::load_all(&Config::sessions_dir()?)?;
+    let sessions: Vec<insights::Record> = history.iter().map(insights::Record::from_session).collect();
+    let timeboxed: Vec<(chrono::NaiveDate, u32)> = history
+        .iter()
+        .filter_map(|s| Some((s.started_at.date_naive(), s.timebox.as_ref()?.spent_minutes?)))
+        .collect();
+    spinner.finish_and_clear();
+
+    if tils.is_empty() {
+        println!("{}", "No TILs yet; nothing to analyze.".warning());
+        return Ok(());
+    }
+
+    let report = insights::analyze(&tils, &sessions, chrono::Local::now().date_naive(), i64::from(days));
+
+    println!("{}", "Trends".bold());
+    for trend in report.trends() {
+        println!("  {} {}", theme::glyph("•", "-"), trend);
+    }
+    println!("  {} {} session(s) in the last {} days", theme::glyph("•", "-"), report.recent_sessions, days);
+    let mut times_to_til: Vec<chrono::Duration> = history.iter().filter_map(Session::time_to_til).collect();
+    times_to_til.sort();
+    if let Some(median) = times_to_til.get(times_to_til.len() / 2) {
+        println!(
+            "  {} Median time to TIL: {} across {} session(s)",
+            theme::glyph("•", "-"),
+            session::format_duration(*median),
+            times_to_til.len()
+        );
+    }
+
+    for (heading, items) in [
+        ("Recent categories", &report.recent_categories),
+        ("Recent keywords", &report.recent_keywords),
+    ] {
+        if items.is_empty() {
+            continue;
+        }
+        println!();
+        println!("{}", heading.bold());
+        for (name, count) in insights::top(items) {
+            println!("  {:<24} {}", name, theme::glyph("▇", "#").repeat((*count).min(40)).info());
+        }
+    }
+
+    if !report.authors.is_empty() {
+        println!();
+        println!("{}", "Contributors".bold());
+        for (author, total, recent) in &report.authors {
+            println!("  {:<24} {:>4} TIL(s), {} in the last {} days", author, total, recent, days);
+        }
+    }
+
+    let since = chrono::Local::now().date_naive() - chrono::Duration::days(i64::from(days));
+    let weeks: Vec<(chrono::NaiveDate, u32)> = timebox::minutes_per_week(&timeboxed)
+        .into_iter()
+        .filter(|(monday, _)| *monday + chrono::Duration::days(6) >= since)
+        .collect();
+    if !weeks.is_empty() {
+        println!();
+        println!("{}", "Timeboxed learning per week".bold());
+        for (monday, minutes) in weeks {
+            let label = format!("Week of {}", monday.format("%b %d"));
+            let time = format!("{}h {:02}m", minutes / 60, minutes % 60);
+            let bar = theme::glyph("▇", "#").repeat((minutes as usize / 5).min(40));
+            println!("  {:<16} {:>7} {}", label, time, bar.info());
+        }
+    }
+
+    if suggest {
+        println!();
+        println!("{}", "Worth revisiting".bold());
+        let prompt = insights::build_suggestions_prompt(&report);
+        let spinner = create_spinner("Asking for suggestions...");
+        let mut first_chunk = true;
+        claude::run_claude_command(&prompt, |text| {
+            if first_chunk {
+                spinner.finish_and_clear();
+                first_chunk = false;
+            }
+            print!("{}", text);
+            io::stdout().flush().ok();
+        })?;
+        spinner.finish_and_clear();
+        println!();
+    }
+
+    Ok(())
+}
+
+/// Run the list command
+fn run_list(category: Option<String>, max_minutes: Option<u32>, config: &Config) -> Result<()> {
+    let entries: Vec<archive::Entry> = archive::all_entries(config)?
+        .into_iter()
+        .filter(|e| category.is_none() || e.category.as_deref() == category.as_deref())
+        .filter(|e| max_minutes.is_none_or(|max| e.reading_minutes <= max))
+        .collect();
+
+    if entries.is_empty() {
+        println!("{}", "No matching entries.".warning());
+        return Ok(());
+    }
+
+    for entry in &entries {
+        let location = match entry.category {
+            Some(ref cat) => format!("{} {}", entry.kind, cat),
+            None => entry.kind.to_string(),
+        };
+        let level = entry
+            .level
+            .as_ref()
+            .map(|level| format!(" · {}", level))
+            .unwrap_or_default();
+
+        println!(
+            "{}  {} {}",
+            entry.title.bold(),
+            format!("[{}]", location).muted(),
+            format!("{} min{}", entry.reading_minutes, level).info()
+        );
+    }
+
+    println!();
+    println!("{} entries", entries.len());
+
+    Ok(())
+}
+
+/// Run the grep command, printing each file's matches as soon as it's searched
+/// Print a TIL for a shell's startup. Stays quiet without a config or any TILs, so a new
+/// shell is never held up by setup prompts.
+fn run_til_of_the_day(random: bool, plain: bool, max_lines: usize, dry_run: bool) -> Result<()> {
+    let Some(config) = Config::load()? else {
+        return Ok(());
+    };
+    let archive_path = config.archive_path();
+    let files: Vec<String> = grep::files(&archive_path, None)?
+        .iter()
+        .filter_map(|path| path.strip_prefix(&archive_path).ok())
+        .map(|path| path.to_string_lossy().to_string())
+        .collect();
+
+    let history_path = Config::til_of_the_day_path()?;
+    let mut history = til::of_the_day::History::load(&history_path)?;
+    let today = chrono::Local::now().date_naive();
+    let seed = til::of_the_day::seed();
+    let picked = if random {
+        files.get(seed as usize % files.len().max(1))
+    } else {
+        history.pick(&files, today, seed)
+    };
+    let Some(picked) = picked.cloned() else {
+        return Ok(());
+    };
+
+    let content = std::fs::read_to_string(archive_path.join(&picked))?;
+    let width = console::Term::stdout().size_checked().map_or(80, |(_, columns)| usize::from(columns).min(80));
+    print!("{}", til::of_the_day::render(&content, width, max_lines, plain));
+    if plain {
+        println!("({})", picked);
+    } else {
+        println!("{}", format!("({})", picked).muted());
+    }
+
+    if dry_run {
+        return Ok(());
+    }
+    history.record(&picked, today, !random);
+    history.save(&history_path)
+}
+
+fn run_grep(pattern: &str, context: usize, category: Option<&str>, ignore_case: bool, config: &Config) -> Result<()> {
+    let regex = regex::RegexBuilder::new(pattern)
+        .case_insensitive(ignore_case)
+        .build()
+        .map_err(|e| anyhow!("Invalid pattern: {}", e))?;
+
+    let archive_path = config.archive_path();
+    let (mut matches, mut matched_files) = (0, 0);
+    for path in grep::files(&archive_path, category)? {
+        let content = std::fs::read_to_string(&path)?;
+        let hunks = grep::search(&content, &regex, context);
+        if hunks.is_empty() {
+            continue;
+        }
+
+        if matched_files > 0 {
+            println!();
+        }
+        matched_files += 1;
+        println!("{}", path.strip_prefix(&config.til_path).unwrap_or(&path).display().to_string().highlight());
+        for (i, hunk) in hunks.iter().enumerate() {
+            if i > 0 {
+                println!("{}", "--".muted());
+            }
+            for line in hunk {
+                if line.is_match {
+                    matches += 1;
+                    println!("{}:{}", line.number.to_string().success(), grep::highlight(&line.text, &regex));
+                } else {
+                    println!("{}-{}", line.number.to_string().success(), line.text.muted());
+                }
+            }
+        }
+    }
+
+    if matched_files == 0 {
+        println!("{}", "No matches.".warning());
+    } else {
+        println!();
+        println!("{} matching lines in {} files", matches, matched_files);
+    }
+    Ok(())
+}
+
+/// Run the open command
+fn run_open(query: &str, reveal: bool, obsidian: bool, config: &Config) -> Result<()> {
+    let entry = find_entry(query, "Open which entry?", config)?;
+
+    if reveal {
+        let folder = entry.path.parent().unwrap_or(&entry.path);
+        launcher::open_with_system(&folder.to_string_lossy())?;
+    } else if obsidian {
+        launcher::open_with_system(&launcher::obsidian_uri(&entry.path))?;
+    } else {
+        launcher::open_in_editor(&entry.path)?;
+    }
+
+    Ok(())
+}
+
+/// The TIL or note whose title best matches `query`, asking which one when several do
+fn find_entry(query: &str, prompt: &str, config: &Config) -> Result<archive::Entry> {
+    let entries = archive::all_entries(config)?;
+    let matches = archive::fuzzy_find(&entries, query);
+
+    let entry = match matches.len() {
+        0 => return Err(anyhow!("No TIL or note matches {:?}", query)),
+        1 => matches[0],
+        _ => {
+            let items: Vec<String> = matches
+                .iter()
+                .take(10)
+                .map(|e| match e.category {
+                    Some(ref cat) => format!("{} ({}, {})", e.title, e.kind, cat),
+                    None => format!("{} ({})", e.title, e.kind),
+                })
+                .collect();
+            let selection = Select::with_theme(&output::prompts()?)
+                .with_prompt(prompt)
+                .items(&items)
+                .default(0)
+                .interact()?;
+            matches[selection]
+        }
+    };
+
+    Ok(entry.clone())
+}
+
+fn print_welcome_banner(config: &Config) {
+    if verbosity::quiet() {
+        return;
+    }
+    println!("{}", theme::banner_rule());
+    println!(
+        "{}",
+        "  HOLOCRON - Your Learning Assistant  "
+            .bold()
+            .accent()
+    );
+    println!("{}", theme::banner_rule());
+    println!();
+    print_status(config);
+    println!();
+    println!("Commands:");
+    println!(
+        "  {} - Start a deep dive on a topic",
+        "/learn <topic>".success()
+    );
+    println!("  {}    - Analyze an article from URL", "/link <url>".success());
+    println!("  {}   - Analyze a video from its captions", "/video <url>".success());
+    println!("  {} - Compare two technologies", "/compare <a> vs <b>".success());
+    println!("  {}    - Show the TILs the session was told you know", "/knowledge".success());
+    println!("  {}      - List the URLs cited so far (added to /note)", "/sources".success());
+    println!("  {}      - List the exchanges so far, numbered", "/history".success());
+    println!("  {}         - Leave the last exchange out of TILs and notes", "/undo".success());
+    println!("  {}  - Leave exchange n out of TILs and notes", "/exclude <n>".success());
+    println!("  {} - Change the session's category", "/category <name>".success());
+    println!("  {}   - Tag the session; tags go in the TIL's and note's frontmatter", "/tag <tags>".success());
+    println!("  {} - Remove tags from the session", "/untag <tags>".success());
+    println!("  {}   - Jot down a note of your own; it goes in the TIL and note as written", "/jot <text>".success());
+    println!("  {} - Save the session for `claude --resume` in Claude Code", "/export-claude".success());
+    println!("  {} - Add a Claude Code session's exchanges (id or file)", "/import-claude [id]".success());
+    println!("  {} - Change the session's topic", "/rename-topic <text>".success());
+    println!("  {} - Search the web within the session", "/search <query>".success());
+    println!("  {}          - Run the last code example (bash, python, cargo script)", "/run".success());
+    println!("  {}       - Explain it back in your own words, corrected and added to the TIL", "/recall".success());
+    println!("  {}          - Generate TIL from session", "/til".success());
+    println!("  {}      - Plan the note's sections with Claude before /note writes it", "/outline".success());
+    println!("  {}         - Generate detailed note", "/note".success());
+    println!("  {}        - Share the session as a file (or: /share [til|note] [gist])", "/share".success());
+    println!("  {}        - Show archive totals, the last TIL and the queue", "/status".success());
+    println!("  {}      - Change settings for this session or save them", "/settings".success());
+    println!("  {}      - Publish the saved TIL, minus <!-- private --> parts", "/publish".success());
+    println!("  {}         - Exit holocron", "/exit".success());
+    println!();
+    println!("Or just type to continue the conversation.");
+    println!();
+}
+
+fn run_interactive_mode(config: &Config) -> Result<()> {
+    print_welcome_banner(config);
+    if !config.aliases.is_empty() {
+        println!("Your aliases: {}", alias::names(&config.aliases).join(", ").success());
+        println!();
+    }
+    run_repl(None, config)
+}
+
+/// Read and dispatch input until the user exits
+fn run_repl(mut session: Option<Session>, config: &Config) -> Result<()> {
+    // /settings changes this copy; changes saved to the config file are written separately
+    let mut config = config.clone();
+    loop {
+        let prompt = match session {
+            Some(ref mut sess) => timebox_prompt(sess),
+            None => "holocron".to_string(),
+        };
+        let reminder = session.as_ref().and_then(|sess| idle_reminder(sess, &prompt, &config));
+        let input: String = Input::with_theme(&output::prompts()?)
+            .with_prompt(prompt)
+            .allow_empty(false)
+            .interact_text()?;
+        drop(reminder);
+
+        let input = input.trim();
+
+        if input.eq_ignore_ascii_case("/quit") || input.eq_ignore_ascii_case("/exit") {
+            if let Some(sess) = session.take() {
+                end_session(sess, &config)?;
+            }
+            println!("{}", "May the Force be with you.".accent());
+            break;
+        }
+
+        if input.eq_ignore_ascii_case("/settings") {
+            edit_settings(&mut config)?;
+            continue;
+        }
+
+        match handle_command(input, &mut session, &config) {
+            Ok(Some(true)) => continue,
+            Ok(_) => {}
+            // Ctrl+C while generating stops the request, not holocron
+            Err(e) if claude::is_cancelled(&e) => {
+                println!("{}", "Stopped.".warning());
+                continue;
+            }
+            Err(e) => return Err(e),
+        }
+
+        // Regular conversation continuation
+        if let Some(ref mut sess) = session {
+            let message = sess.followup(input).unwrap_or(input).to_string();
+            send_and_display(&message, sess, &config)?;
+            suggest_til(sess, &config);
+        } else {
+            println!(
+                "{}",
+                "Start a session with /learn <topic> or /link <url>".warning()
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Change settings from inside the REPL, for the rest of this run or saved to the config file
+fn edit_settings(config: &mut Config) -> Result<()> {
+    loop {
+        let items: Vec<String> = settings::SETTINGS
+            .iter()
+            .map(|setting| {
+                let value = settings::value(config, setting.key);
+                let value = if value.is_empty() { "default".muted().to_string() } else { value.info().to_string() };
+                format!("{:<26} {}", setting.label, value)
+            })
+            .collect();
+        let Some(choice) = Select::with_theme(&output::prompts()?)
+            .with_prompt("Settings (esc when done)")
+            .items(&items)
+            .default(0)
+            .interact_opt()?
+        else {
+            return Ok(());
+        };
+        let setting = settings::SETTINGS[choice];
+        let Some(value) = prompt_setting_value(&setting, config)? else {
+            continue;
+        };
+
+        let Some(scope) = Select::with_theme(&output::prompts()?)
+            .with_prompt(format!("{}: {}", setting.label, if value.is_empty() { "default" } else { &value }))
+            .items(&["For this session", "For this session, and save it to the config"])
+            .default(0)
+            .interact_opt()?
+        else {
+            continue;
+        };
+
+        if let Err(e) = settings::set(config, setting.key, &value) {
+            println!("{} {}", "Error:".failure().bold(), e);
+            continue;
+        }
+        if scope == 1 && config.dry_run {
+            println!("{}", format!("Dry run: {} changed until you exit, not saved.", setting.label).warning());
+        } else if scope == 1 {
+            let mut saved = Config::load_global()?.unwrap_or_else(|| config.clone());
+            settings::set(&mut saved, setting.key, &value)?;
+            saved.save()?;
+            println!("{} {} saved to {}", theme::done(), setting.label, Config::config_path()?.display());
+        } else {
+            println!("{} {} changed until you exit", theme::done(), setting.label);
+        }
+    }
+}
+
+/// The new value for a setting: flipped for a toggle, picked for a choice, typed otherwise
+fn prompt_setting_value(setting: &settings::Setting, config: &Config) -> Result<Option<String>> {
+    let current = settings::value(config, setting.key);
+    match setting.kind {
+        settings::Kind::Toggle => Ok(Some((current != "true").to_string())),
+        settings::Kind::Choice(options) => {
+            let selected = Select::with_theme(&output::prompts()?)
+                .with_prompt(setting.label)
+                .items(options)
+                .default(options.iter().position(|option| *option == current).unwrap_or(0))
+                .interact_opt()?;
+            Ok(selected.map(|index| options[index].to_string()))
+        }
+        settings::Kind::Number | settings::Kind::Text => {
+            let value: String = Input::with_theme(&output::prompts()?)
+                .with_prompt(setting.label)
+                .with_initial_text(current)
+                .allow_empty(setting.kind == settings::Kind::Text)
+                .interact_text()?;
+            Ok(Some(value))
+        }
+    }
+}
+
+/// Remind about /til if the prompt sits idle for `til_reminder_minutes` in a session that
+/// hasn't saved one. Dropping the returned sender, once input arrives, cancels the reminder.
+fn idle_reminder(session: &Session, prompt: &str, config: &Config) -> Option<mpsc::Sender<()>> {
+    if config.til_reminder_minutes == 0 || session.saved_til.is_some() || session.exchanges.is_empty() {
+        return None;
+    }
+    let wait = std::time::Duration::from_secs(u64::from(config.til_reminder_minutes) * 60);
+    let mut redrawn_prompt = String::new();
+    theme::prompts().format_input_prompt(&mut redrawn_prompt, prompt, None).ok()?;
+    let notifications = config.notifications;
+    let topic = session.topic().to_string();
+    let tip = theme::glyph("💡", "Tip:");
+
+    let (cancel, cancelled) = mpsc::channel();
+    std::thread::spawn(move || {
+        if cancelled.recv_timeout(wait) != Err(mpsc::RecvTimeoutError::Timeout) {
+            return;
+        }
+        // The prompt is on stderr; print above it and draw it again
+        let reminder = format!("{} Still there? Capture what you learned with /til before you go.", tip);
+        eprint!("\r\x1b[2K{}\n{}", reminder.warning(), redrawn_prompt);
+        io::stderr().flush().ok();
+        notify::remind(notifications, "Save a TIL?", &topic);
+    });
+    Some(cancel)
+}
+
+/// Suggest /til once per session, when it seems to be winding down without one
+fn suggest_til(session: &mut Session, config: &Config) {
+    if !config.suggest_til || session.til_suggested || !wind_down::winding_down(session) {
+        return;
+    }
+    session.til_suggested = true;
+    println!(
+        "{}",
+        format!("{} Sounds like you're wrapping up. Capture it with /til before you go.", theme::glyph("💡", "Tip:"))
+            .muted()
+    );
+}
+
+/// The REPL prompt for a session, showing the time left in its timebox. A due warning or
+/// wrap-up nudge is printed first, so it shows up between answers rather than mid-stream.
+fn timebox_prompt(session: &mut Session) -> String {
+    let started_at = session.started_at;
+    let Some(ref mut timebox) = session.timebox else {
+        return "holocron".to_string();
+    };
+
+    let now = chrono::Local::now();
+    match timebox.check(started_at, now) {
+        Some(timebox::Alert::Warning) => {
+            println!("{}", format!("{} 5 minutes left in this timebox.", theme::glyph("⏳", "!")).warning());
+        }
+        Some(timebox::Alert::Expired) => {
+            println!(
+                "{}",
+                format!(
+                    "{} Time's up ({}m). Capture what you learned with /til, then /exit.",
+                    theme::glyph("⏰", "!"),
+                    timebox.minutes
+                )
+                    .warning()
+                    .bold()
+            );
+        }
+        None => {}
+    }
+    format!("holocron [{}]", timebox.label(started_at, now))
+}
+
+fn handle_command(input: &str, session: &mut Option<Session>, config: &Config) -> Result<Option<bool>> {
+    if let Some(topic) = input.strip_prefix("/learn ") {
+        let topic = topic.trim();
+        if topic.is_empty() {
+            println!("{}", "Please provide a topic.".warning());
+            return Ok(Some(true));
+        }
+        if !review_overlapping_tils(topic, config)? {
+            return Ok(Some(true));
+        }
+
+        if let Some(previous) = session.take() {
+            end_session(previous, config)?;
+        }
+
+        let category = prompt_for_category(config)?;
+        let mode = LearningMode::DeepDive {
+            topic: topic.to_string(),
+        };
+        let mut deep_dive = Session::new(mode, category);
+        let prompt = deep_dive_prompt(&mut deep_dive, config);
+        let prompt = offer_continuation(&deep_dive, prompt, config)?;
+        send_and_display(&prompt, &mut deep_dive, config)?;
+        *session = Some(deep_dive);
+        return Ok(Some(true));
+    }
+
+    if let Some(url) = input.strip_prefix("/link ") {
+        let url = url.trim();
+        if url.is_empty() {
+            println!("{}", "Please provide a URL.".warning());
+            return Ok(Some(true));
+        }
+
+        if let Some(previous) = session.take() {
+            end_session(previous, config)?;
+        }
+
+        let category = prompt_for_category(config)?;
+        *session = Some(new_link_session(url, None, category, config));
+
+        let prompt = link_prompt(url, config);
+        if let Some(ref mut sess) = session {
+            send_and_display(&prompt, sess, config)?;
+        }
+        return Ok(Some(true));
+    }
+
+    if let Some(subjects) = input.strip_prefix("/compare ") {
+        match start_compare_session(subjects, None) {
+            Ok((mut compare_session, prompt)) => {
+                if let Some(previous) = session.take() {
+                    end_session(previous, config)?;
+                }
+                compare_session.category = prompt_for_category(config)?;
+                send_and_display(&prompt, &mut compare_session, config)?;
+                *session = Some(compare_session);
+            }
+            Err(e) => println!("{}", e.to_string().warning()),
+        }
+        return Ok(Some(true));
+    }
+
+    if let Some(url) = input.strip_prefix("/video ") {
+        start_repl_video(url.trim(), session, config)?;
+        return Ok(Some(true));
+    }
+
+    if input.eq_ignore_ascii_case("/run") {
+        match session {
+            Some(ref mut sess) => {
+                if let Err(e) = run_code_example(sess, config) {
+                    println!("{} {}", "Error:".failure().bold(), e);
+                }
+            }
+            None => println!("{}", "No active session. Start with /learn or /link first.".warning()),
+        }
+        return Ok(Some(true));
+    }
+
+    if let Some(query) = input.strip_prefix("/search ") {
+        let query = query.trim();
+        if query.is_empty() {
+            println!("{}", "Please provide a search query.".warning());
+        } else if let Some(ref mut sess) = session {
+            send_and_display(&build_search_prompt(query), sess, config)?;
+        } else {
+            println!(
+                "{}",
+                "No active session. Start with /learn or /link first.".warning()
+            );
+        }
+        return Ok(Some(true));
+    }
+
+    if input.eq_ignore_ascii_case("/recall") {
+        if let Some(ref mut sess) = session {
+            recall_key_concept(sess, config)?;
+            persist_session(sess, config);
+        } else {
+            println!(
+                "{}",
+                "No active session. Start with /learn or /link first.".warning()
+            );
+        }
+        return Ok(Some(true));
+    }
+
+    if input.eq_ignore_ascii_case("/til") {
+        if let Some(ref mut sess) = session {
+            generate_and_save_til(sess, config)?;
+            persist_session(sess, config);
+        } else {
+            println!(
+                "{}",
+                "No active session. Start with /learn or /link first.".warning()
+            );
+        }
+        return Ok(Some(true));
+    }
+
+    if input.eq_ignore_ascii_case("/outline") {
+        if let Some(ref mut sess) = session {
+            outline_note(sess)?;
+            persist_session(sess, config);
+        } else {
+            println!(
+                "{}",
+                "No active session. Start with /learn or /link first.".warning()
+            );
+        }
+        return Ok(Some(true));
+    }
+
+    if input.eq_ignore_ascii_case("/note") {
+        if let Some(ref mut sess) = session {
+            generate_and_save_note(sess, config)?;
+            persist_session(sess, config);
+        } else {
+            println!(
+                "{}",
+                "No active session. Start with /learn or /link first.".warning()
+            );
+        }
+        return Ok(Some(true));
+    }
+
+    if input.eq_ignore_ascii_case("/share") || input.starts_with("/share ") {
+        let args = input.trim_start_matches("/share");
+        match session {
+            Some(ref sess) => {
+                // A bad option or failed upload shouldn't end the session
+                let shared = share::parse_args(args).and_then(|(content, target)| {
+                    share_session(sess, content, target, std::path::Path::new("."), config)
+                });
+                if let Err(e) = shared {
+                    println!("{} {}", "Error:".failure().bold(), e);
+                }
+            }
+            None => println!("{}", "No active session. Start with /learn or /link first.".warning()),
+        }
+        return Ok(Some(true));
+    }
+
+    if show_session_info(input, session.as_ref(), config) {
+        return Ok(Some(true));
+    }
+
+    if input.eq_ignore_ascii_case("/undo") {
+        match session {
+            Some(ref mut sess) => match sess.undo() {
+                Some(number) => {
+                    println!("{} Exchange {} won't be used for TILs or notes", theme::done(), number);
+                    persist_session(sess, config);
+                }
+                None => println!("{}", "Nothing to undo.".warning()),
+            },
+            None => println!("{}", "No active session. Start with /learn or /link first.".warning()),
+        }
+        return Ok(Some(true));
+    }
+
+    if input.eq_ignore_ascii_case("/exclude") || input.starts_with("/exclude ") {
+        match session {
+            Some(ref mut sess) => {
+                let excluded = input
+                    .trim_start_matches("/exclude")
+                    .trim()
+                    .parse::<usize>()
+                    .map_err(|_| anyhow!("Usage: /exclude <n>, with n from /history"))
+                    .and_then(|number| sess.exclude(number).map(|_| number));
+                match excluded {
+                    Ok(number) => {
+                        println!("{} Exchange {} won't be used for TILs or notes", theme::done(), number);
+                        persist_session(sess, config);
+                    }
+                    Err(e) => println!("{}", e.to_string().warning()),
+                }
+            }
+            None => println!("{}", "No active session. Start with /learn or /link first.".warning()),
+        }
+        return Ok(Some(true));
+    }
+
+    if input.eq_ignore_ascii_case("/category") || input.starts_with("/category ") {
+        match session {
+            Some(ref mut sess) => set_session_category(sess, input.trim_start_matches("/category"), config),
+            None => println!("{}", "No active session. Start with /learn or /link first.".warning()),
+        }
+        return Ok(Some(true));
+    }
+
+    if input.eq_ignore_ascii_case("/tag") || input.starts_with("/tag ") || input.starts_with("/untag ") {
+        match session {
+            Some(ref mut sess) => tag_session(sess, input, config),
+            None => println!("{}", "No active session. Start with /learn or /link first.".warning()),
+        }
+        return Ok(Some(true));
+    }
+
+    if input.eq_ignore_ascii_case("/jot") || input.starts_with("/jot ") {
+        match session {
+            Some(ref mut sess) => jot(sess, input.trim_start_matches("/jot"), config),
+            None => println!("{}", "No active session. Start with /learn or /link first.".warning()),
+        }
+        return Ok(Some(true));
+    }
+
+    if move_to_claude_code(input, session, config) {
+        return Ok(Some(true));
+    }
+
+    if input.eq_ignore_ascii_case("/rename-topic") || input.starts_with("/rename-topic ") {
+        let topic = input.trim_start_matches("/rename-topic").trim();
+        match session {
+            Some(_) if topic.is_empty() => println!("{}", "Please provide the new topic.".warning()),
+            Some(ref mut sess) => {
+                sess.rename_topic(topic);
+                persist_session(sess, config);
+                println!("{} {}", "Topic is now:".success(), sess.topic());
+            }
+            None => println!("{}", "No active session. Start with /learn or /link first.".warning()),
+        }
+        return Ok(Some(true));
+    }
+
+    if input.eq_ignore_ascii_case("/status") {
+        print_status(config);
+        return Ok(Some(true));
+    }
+
+    if input.eq_ignore_ascii_case("/stop") {
+        println!("{}", "Nothing to stop. Press Esc or s while a response is streaming.".warning());
+        return Ok(Some(true));
+    }
+
+    // Aliases come last, so a built-in command can't be shadowed by one
+    if send_alias(input, session, config)? {
+        return Ok(Some(true));
+    }
+
+    Ok(None)
+}
+
+/// TIL count, the last TIL saved, queue length and where TILs go
+fn print_status(config: &Config) {
+    match archive::summary(&config.archive_path()) {
+        Ok(summary) => {
+            let last = match summary.latest {
+                Some((title, date)) => format!(" (last: {}, {})", title, days_ago(date)),
+                None => String::new(),
+            };
+            println!("TILs:     {}{}", summary.total.to_string().bold(), last);
+        }
+        Err(e) => println!("TILs:     {}", format!("couldn't read the archive: {}", e).warning()),
+    }
+
+    let queued = Config::queue_path().and_then(|path| queue::Queue::load(&path));
+    match queued {
+        Ok(queue) if queue.items.is_empty() => println!("Queue:    empty"),
+        Ok(queue) => println!("Queue:    {} to read (holocron queue next)", queue.items.len()),
+        Err(e) => println!("Queue:    {}", format!("couldn't read the queue: {}", e).warning()),
+    }
+
+    println!("TIL repo: {}", config.til_path.display());
+}
+
+fn days_ago(date: chrono::NaiveDate) -> String {
+    match (chrono::Local::now().date_naive() - date).num_days() {
+        ..=0 => "today".to_string(),
+        1 => "yesterday".to_string(),
+        days => format!("{} days ago", days),
+    }
+}
+
+/// Commands that show something about the session, or send it elsewhere, without changing it.
+/// Returns false if `input` isn't one of them.
+fn show_session_info(input: &str, session: Option<&Session>, config: &Config) -> bool {
+    let command = input.to_lowercase();
+    if !["/sources", "/history", "/knowledge", "/publish"].contains(&command.as_str()) {
+        return false;
+    }
+    let Some(sess) = session else {
+        println!("{}", "No active session. Start with /learn or /link first.".warning());
+        return true;
+    };
+
+    match command.as_str() {
+        "/sources" => print_sources(sess),
+        "/history" => print_history(sess),
+        "/knowledge" => print_known_titles(sess, config),
+        _ => {
+            if let Err(e) = publish_saved_til(sess, config) {
+                println!("{} {}", "Error:".failure().bold(), e);
+            }
+        }
+    }
+    true
+}
+
+/// `/export-claude` and `/import-claude`, which move the session to and from Claude Code.
+/// Returns false if `input` isn't one of them.
+fn move_to_claude_code(input: &str, session: &mut Option<Session>, config: &Config) -> bool {
+    let export = input.eq_ignore_ascii_case("/export-claude");
+    let import = input.eq_ignore_ascii_case("/import-claude") || input.starts_with("/import-claude ");
+    if !export && !import {
+        return false;
+    }
+    let Some(sess) = session else {
+        println!("{}", "No active session. Start with /learn or /link first.".warning());
+        return true;
+    };
+
+    // A missing transcript or unwritable directory shouldn't end the session
+    let moved = if export {
+        export_claude(sess)
     } else {
-        let (resp, maybe_session_id) = claude::run_claude_command(message, |text| {
-            if first_chunk {
-                spinner.finish_and_clear();
-                first_chunk = false;
+        import_claude(sess, input.trim_start_matches("/import-claude").trim(), config)
+    };
+    if let Err(e) = moved {
+        println!("{} {}", "Error:".failure().bold(), e);
+    }
+    true
+}
+
+/// Send the message a configured alias expands to. Returns false if `input` isn't an alias.
+fn send_alias(input: &str, session: &mut Option<Session>, config: &Config) -> Result<bool> {
+    let topic = session.as_ref().map(|sess| sess.prompt_topic().to_string()).unwrap_or_default();
+    let Some(message) = alias::expand(&config.aliases, input, &topic) else {
+        return Ok(false);
+    };
+    let message = variables::fill(&message, &config.variables);
+    match session {
+        Some(_) if message.is_empty() => println!("{}", "That alias expands to an empty message.".warning()),
+        Some(ref mut sess) => send_and_display(&message, sess, config)?,
+        None => println!("{}", "No active session. Start with /learn or /link first.".warning()),
+    }
+    Ok(true)
+}
+
+/// Handle `/category <name>`: file the session's TIL under a different category.
+/// With no name, show the current one.
+fn set_session_category(session: &mut Session, name: &str, config: &Config) {
+    let name = name.trim().to_lowercase();
+    if name.is_empty() {
+        match session.category {
+            Some(ref category) => println!("Category: {}", category),
+            None => println!("{}", "No category yet; you'll be asked when saving.".muted()),
+        }
+        return;
+    }
+    if !config.categories.is_empty() && !config.categories.contains(&name) {
+        println!(
+            "{} {}",
+            "This repo only allows:".warning(),
+            config.categories.join(", ")
+        );
+        return;
+    }
+
+    println!("{} {}", "Category is now:".success(), name);
+    session.category = Some(name);
+    persist_session(session, config);
+}
+
+/// Handle `/tag <tags>` and `/untag <tags>`, or show the session's tags for a bare `/tag`
+fn tag_session(session: &mut Session, input: &str, config: &Config) {
+    if let Some(tags) = input.strip_prefix("/untag ") {
+        session.remove_tags(tags);
+    } else {
+        session.add_tags(input.trim_start_matches("/tag"));
+    }
+    if session.tags.is_empty() {
+        println!("{}", "No tags yet. Add some with /tag <tags>.".muted());
+        return;
+    }
+    println!("{} {}", "Tags:".success(), session.tags.join(", "));
+    persist_session(session, config);
+}
+
+/// Handle `/jot <text>`: keep the user's own note with the session, without sending it.
+/// With no text, list what's been jotted so far.
+fn jot(session: &mut Session, text: &str, config: &Config) {
+    if !text.trim().is_empty() {
+        session.jot(text);
+        persist_session(session, config);
+        println!("{}", "Jotted. It'll go in the TIL and note as you wrote it.".success());
+        return;
+    }
+    if session.jots.is_empty() {
+        println!("{}", "Nothing jotted yet. Add a note of your own with /jot <text>.".muted());
+        return;
+    }
+    for (i, jot) in session.jots.iter().enumerate() {
+        println!("  {}. {}", i + 1, jot);
+    }
+}
+
+/// Handle `/export-claude`: save the session as a Claude Code session that `claude --resume` can pick up
+fn export_claude(session: &Session) -> Result<()> {
+    let conversation = session.claude_conversation();
+    if conversation.exchanges.is_empty() {
+        println!("{}", "Nothing to export yet.".warning());
+        return Ok(());
+    }
+    let cwd = session.claude_dir.clone().or_else(|| std::env::current_dir().ok()).unwrap_or_default();
+    let (id, path) = claude::transcripts::write(&conversation, &cwd, session.started_at)?;
+    let count = conversation.exchanges.len();
+    println!(
+        "{} Exported {} exchange{} to {:?}",
+        theme::done(),
+        count,
+        if count == 1 { "" } else { "s" },
+        path
+    );
+    println!("  Carry on in Claude Code from {:?} with: {}", cwd, format!("claude --resume {}", id).info());
+    Ok(())
+}
+
+/// Handle `/import-claude [<session id or transcript file>]`: add a Claude Code conversation's exchanges
+/// to the session. Without an argument, pick one of the recent sessions.
+fn import_claude(session: &mut Session, source: &str, config: &Config) -> Result<()> {
+    let path = match source {
+        "" => pick_claude_session()?,
+        file if std::path::Path::new(file).is_file() => PathBuf::from(file),
+        id => claude::transcripts::find(id)?,
+    };
+    let conversation = claude::transcripts::read(&path)?;
+    if conversation.exchanges.is_empty() {
+        return Err(anyhow!("Claude Code session {:?} has no conversation to import", path));
+    }
+    // With no conversation of its own yet, the session can carry on with the imported one
+    if session.claude_session_id.is_none() {
+        if let Some(id) = path.file_stem() {
+            session.set_session_id(id.to_string_lossy().to_string());
+        }
+        session.claude_dir = conversation.cwd.clone().filter(|cwd| cwd.is_dir());
+    }
+    let count = session.import_conversation(conversation);
+    persist_session(session, config);
+    println!(
+        "{} Imported {} exchange{}; they'll go in the TIL and note",
+        theme::done(),
+        count,
+        if count == 1 { "" } else { "s" }
+    );
+    Ok(())
+}
+
+/// Handle `/video <url>`: replace the current session with one on the video
+fn start_repl_video(url: &str, session: &mut Option<Session>, config: &Config) -> Result<()> {
+    if url.is_empty() {
+        println!("{}", "Please provide a video URL.".warning());
+        return Ok(());
+    }
+
+    if let Some(previous) = session.take() {
+        end_session(previous, config)?;
+    }
+
+    let category = prompt_for_category(config)?;
+    // A video without captions shouldn't end the REPL
+    match start_video_session(url, category) {
+        Ok((mut video_session, prompt)) => {
+            send_and_display(&prompt, &mut video_session, config)?;
+            *session = Some(video_session);
+        }
+        Err(e) => println!("{} {}", "Error:".failure().bold(), e),
+    }
+    Ok(())
+}
+
+fn print_learning_banner(session: &Session) {
+    if !verbosity::quiet() {
+        println!("{}", theme::banner_rule());
+        println!(
+            "{}",
+            format!("  Learning: {}  ", session.topic())
+                .bold()
+                .accent()
+        );
+        println!("{}", theme::banner_rule());
+        println!();
+    }
+}
+
+fn run_learning_session(mut session: Session, initial_prompt: String, config: &Config) -> Result<()> {
+    print_learning_banner(&session);
+    send_and_display(&initial_prompt, &mut session, config)?;
+    continue_learning_session(session, config)
+}
+
+/// Pick up a session that already has its opening exchange, e.g. one attached with `--resume`
+fn continue_learning_session(session: Session, config: &Config) -> Result<()> {
+    println!();
+    println!(
+        "Commands: {} | {} | {} | {}",
+        "/search <query>".success(),
+        "/til".success(),
+        "/note".success(),
+        "/exit".success()
+    );
+    println!();
+
+    run_repl(Some(session), config)
+}
+
+/// Run a session as JSON lines: events go to stdout, and each line on stdin is the next
+/// message (or `/til` to generate and save a TIL, `/exit` to finish)
+fn run_jsonl_session(mut session: Session, initial_prompt: String, config: &Config) -> Result<()> {
+    jsonl::emit(&jsonl::Event::SessionStarted {
+        mode: session.mode.to_string(),
+        topic: session.topic(),
+        category: session.category.as_deref(),
+    });
+    send_jsonl(&initial_prompt, &mut session, config);
+
+    for line in io::stdin().lock().lines() {
+        let line = line?;
+        let result = match line.trim() {
+            "" => Ok(()),
+            "/exit" | "/quit" => break,
+            "/til" => save_til_jsonl(&mut session, config),
+            jot if jot.starts_with("/jot ") => {
+                session.jot(&jot["/jot ".len()..]);
+                persist_session(&session, config);
+                Ok(())
+            }
+            message => {
+                send_jsonl(message, &mut session, config);
+                Ok(())
+            }
+        };
+        if let Err(e) = result {
+            jsonl::emit(&jsonl::Event::Error { message: e.to_string() });
+        }
+    }
+
+    persist_session(&session, config);
+    jsonl::emit(&jsonl::Event::SessionEnded {
+        exchanges: session.exchanges.len(),
+        cost_usd: session.usage.cost_usd,
+    });
+    Ok(())
+}
+
+fn send_jsonl(message: &str, session: &mut Session, config: &Config) {
+    let on_event = |event: claude::StreamEvent| match event {
+        claude::StreamEvent::Text(text) => jsonl::emit(&jsonl::Event::TextDelta { text }),
+        claude::StreamEvent::ToolUse { name, input } => jsonl::emit(&jsonl::Event::ToolUse { name, input }),
+    };
+    if compact::due(session, config.context_limit_tokens) {
+        let tokens_before = session.context_used;
+        match compact::compact(session) {
+            Ok(()) => jsonl::emit(&jsonl::Event::ContextCompacted { tokens_before }),
+            Err(e) => jsonl::emit(&jsonl::Event::Error { message: e.to_string() }),
+        }
+    }
+    let settings = session.tool_settings.clone();
+    let mut on_event = on_event;
+    let cancel = claude::CancellationToken::new();
+    let mut result = claude::send_events(session.resume(), message, settings.as_deref(), &mut on_event, cancel);
+    if result.as_ref().is_err_and(claude::is_stale_session) {
+        result = compact::restart(session).and_then(|()| {
+            jsonl::emit(&jsonl::Event::ConversationRestarted);
+            let cancel = claude::CancellationToken::new();
+            claude::send_events(session.resume(), message, settings.as_deref(), on_event, cancel)
+        });
+    }
+    match result {
+        Ok(response) => {
+            let cost_usd = response.usage.cost_usd;
+            session.record_response(message.to_string(), response);
+            persist_session(session, config);
+            jsonl::emit(&jsonl::Event::ResponseDone { cost_usd });
+        }
+        Err(e) => jsonl::emit(&jsonl::Event::Error { message: e.to_string() }),
+    }
+}
+
+/// Generate a TIL and save it under the session's category, without prompting
+fn save_til_jsonl(session: &mut Session, config: &Config) -> Result<()> {
+    if let Some(ref path) = config.append_to {
+        let existing = read_til(path, config)?;
+        let response = til::integrate_til(session, &existing, |_| {})?;
+        session.record_usage(&response.usage);
+        let merged = til::append::keep_title(&existing, &response.text);
+        jsonl::emit(&jsonl::Event::TilGenerated { content: &merged });
+
+        if config.dry_run {
+            return Ok(());
+        }
+        write_til(path, &merged, config)?;
+        jsonl::emit(&jsonl::Event::Saved { kind: "til", path });
+        let category = session.category.as_deref().unwrap_or_default();
+        let mode = session.mode.to_string();
+        run_post_save_hooks("til", &path.display().to_string(), &merged, category, &mode, config);
+        session.record_til(path.clone());
+        persist_session(session, config);
+        return Ok(());
+    }
+
+    let category = session
+        .category
+        .clone()
+        .ok_or_else(|| anyhow!("Start the session with --category to save TILs"))?;
+    let style = category_style(Some(&category), config)?;
+
+    let response = til::generate_til(session, &style, |_| {})?;
+    session.record_usage(&response.usage);
+    let mut til_content = response.text;
+    if config.verify_tils {
+        let response = til::verify::verify(session, &til_content, |_| {})?;
+        session.record_usage(&response.usage);
+        til_content = til::verify::Verification::from_reply(&til_content, &response.text).til;
+    }
+    jsonl::emit(&jsonl::Event::TilGenerated { content: &til_content });
+
+    let content = markdown::add_tags(&til_content, &session.tags);
+    let (plan, _) = plan_til_write(&content, &category, config)?;
+    if config.dry_run {
+        return Ok(());
+    }
+    let saved = save_til(&plan, config)?;
+    jsonl::emit(&jsonl::Event::Saved {
+        kind: "til",
+        path: std::path::Path::new(&saved.to_string()),
+    });
+    run_post_save_hooks("til", &saved.to_string(), &content, &category, &session.mode.to_string(), config);
+    if let Saved::Local(path) = saved {
+        session.record_til(path);
+    }
+    persist_session(session, config);
+    Ok(())
+}
+
+/// Run a session in the split-pane TUI, dropping back to the terminal for /til, /outline and /note
+fn run_tui_session(mut session: Session, initial_prompt: String, config: &Config) -> Result<()> {
+    output::require_terminal()?;
+    let index = search::SearchIndex::build(archive::all_entries(config)?);
+    let mut prompt = Some(initial_prompt);
+
+    loop {
+        let exit = tui::run_session(&mut session, &index, prompt.take(), config.context_limit_tokens, |sess| {
+            persist_session(sess, config)
+        })?;
+
+        match exit {
+            tui::SessionExit::Exit => return end_session(session, config),
+            tui::SessionExit::Command(command) => {
+                match command.as_str() {
+                    "/note" => generate_and_save_note(&mut session, config)?,
+                    "/outline" => outline_note(&mut session)?,
+                    _ => generate_and_save_til(&mut session, config)?,
+                }
+                persist_session(&session, config);
+
+                let _: String = Input::with_theme(&output::prompts()?)
+                    .with_prompt("Press enter to return to the session")
+                    .allow_empty(true)
+                    .interact_text()?;
+            }
+        }
+    }
+}
+
+fn create_spinner(message: &str) -> ProgressBar {
+    if verbosity::quiet() || !output::get().terminal {
+        return ProgressBar::hidden();
+    }
+    let spinner = ProgressBar::new_spinner();
+    spinner.set_style(theme::spinner());
+    spinner.set_message(message.to_string());
+    spinner.enable_steady_tick(std::time::Duration::from_millis(100));
+    spinner
+}
+
+/// A divider around generated TILs and notes, left out with `--quiet`
+fn print_rule() {
+    if !verbosity::quiet() {
+        println!("{}", theme::glyph("─", "-").repeat(40));
+    }
+}
+
+fn send_and_display(message: &str, session: &mut Session, config: &Config) -> Result<()> {
+    session.followups.clear();
+    session.context_tokens = config.til_context_tokens;
+    if replay_cached_analysis(message, session, config) {
+        return Ok(());
+    }
+    compact_if_due(session, config);
+    let spinner = create_spinner("Consulting the archives... (esc to stop)");
+    let started = std::time::Instant::now();
+
+    let cancel = claude::CancellationToken::new();
+    let stop_keys = stop_keys::StopKeys::watch(cancel.clone());
+
+    let mut first_chunk = true;
+    let mut on_text = |text: &str| {
+        if first_chunk {
+            spinner.finish_and_clear();
+            first_chunk = false;
+        }
+        print!("{}", stop_keys.text(text));
+        io::stdout().flush().ok();
+    };
+
+    let settings = session.tool_settings.clone();
+    let mut result = claude::send(session.resume(), message, settings.as_deref(), &mut on_text, cancel.clone());
+    if result.as_ref().is_err_and(claude::is_stale_session) {
+        result = restart_conversation(session, config, &spinner).and_then(|()| {
+            claude::send(session.resume(), message, settings.as_deref(), on_text, cancel)
+        });
+    }
+
+    if first_chunk {
+        spinner.finish_and_clear();
+    }
+    drop(stop_keys);
+
+    println!();
+    println!();
+
+    // A stopped response keeps whatever had streamed in so far
+    let (response, stopped) = match result {
+        Ok(response) => (response, false),
+        Err(e) => match claude::partial_response(e) {
+            Ok(partial) => (partial, true),
+            Err(e) => {
+                println!("{} {}", "Error:".failure().bold(), e);
+                return Err(e);
+            }
+        },
+    };
+
+    if stopped {
+        println!("{}", "Stopped.".warning());
+        if response.text.trim().is_empty() {
+            return Ok(());
+        }
+    }
+
+    if !stopped {
+        cache_link_analysis(message, session, &response, config);
+    }
+    session.record_response(message.to_string(), response);
+    persist_session(session, config);
+    notify::finished(config.notifications, started, "Response ready", session.topic());
+    if config.suggest_followups && !stopped {
+        show_followups(session);
+    }
+    Ok(())
+}
+
+/// Carry the session on in a fresh backend conversation when its old one can't be resumed any
+/// more, so the message can be sent again there
+fn restart_conversation(session: &mut Session, config: &Config, spinner: &ProgressBar) -> Result<()> {
+    spinner.suspend(|| {
+        println!(
+            "{}",
+            "(the backend conversation has expired; continuing in a new one seeded with this session)".warning()
+        )
+    });
+    spinner.set_message("Catching a new conversation up on the session...");
+    compact::restart(session)?;
+    persist_session(session, config);
+    spinner.set_message("Consulting the archives... (esc to stop)");
+    Ok(())
+}
+
+/// Summarize a conversation that's nearing the backend's context window into a fresh one.
+/// If that fails, the session carries on in the old conversation.
+fn compact_if_due(session: &mut Session, config: &Config) {
+    if !compact::due(session, config.context_limit_tokens) {
+        return;
+    }
+    let spinner = create_spinner("Summarizing the conversation so far to free up context...");
+    let result = compact::compact(session);
+    spinner.finish_and_clear();
+
+    match result {
+        Ok(()) => {
+            println!("{}", "(long conversation summarized; continuing in a fresh context)".muted());
+            persist_session(session, config);
+        }
+        Err(e) => println!("{} {}", "Couldn't summarize the conversation:".warning(), e),
+    }
+}
+
+/// The URL when `message` opens a link session and the link cache is on
+fn opening_link<'a>(session: &'a Session, config: &Config) -> Option<&'a str> {
+    match session.mode {
+        LearningMode::Link { ref url } if session.exchanges.is_empty() && config.link_cache_days > 0 => Some(url),
+        _ => None,
+    }
+}
+
+/// Show a recently cached opening analysis instead of asking for it again. Follow-ups
+/// continue the backend session it came from.
+fn replay_cached_analysis(message: &str, session: &mut Session, config: &Config) -> bool {
+    let Some(url) = opening_link(session, config) else {
+        return false;
+    };
+    let Some(cached) = Config::link_cache_dir()
+        .ok()
+        .and_then(|dir| link_cache::lookup(&dir, url, message, config.link_cache_days))
+    else {
+        return false;
+    };
+
+    println!("{}", cached.text);
+    println!();
+    println!(
+        "{}",
+        format!("(cached analysis from {})", cached.cached_at.format("%Y-%m-%d")).muted()
+    );
+    println!();
+
+    session.record_response(message.to_string(), cached.into_response());
+    persist_session(session, config);
+    true
+}
+
+fn cache_link_analysis(message: &str, session: &Session, response: &claude::ClaudeResponse, config: &Config) {
+    let Some(url) = opening_link(session, config) else {
+        return;
+    };
+    if let Err(e) = Config::link_cache_dir().and_then(|dir| link_cache::store(&dir, url, message, response)) {
+        println!("{} {}", "Could not cache the analysis:".warning(), e);
+    }
+}
+
+/// Wrap up a session: print a recap, offer to keep the transcript if nothing was saved, and run the end hook
+fn end_session(mut session: Session, config: &Config) -> Result<()> {
+    let started_at = session.started_at;
+    if let Some(ref mut timebox) = session.timebox {
+        timebox.finish(started_at, chrono::Local::now());
+    }
+    if config.autosave_sessions && !session.exchanges.is_empty() {
+        autosave_session(&mut session, config);
+    }
+    if let (Some(step), Some(_)) = (&session.plan_step, &session.saved_til) {
+        match plan::complete_step(step) {
+            Ok(true) => println!("{} Checked off \"{}\" in the plan", theme::done(), step.title),
+            Ok(false) => {}
+            Err(e) => println!("{} {}", "Could not update the plan:".warning(), e),
+        }
+    }
+    persist_session(&session, config);
+    print_session_recap(&session);
+
+    let mut transcript = None;
+    if session.artifacts.is_empty() && !session.exchanges.is_empty() {
+        println!(
+            "{}",
+            format!(
+                "{} Nothing was saved from this session — what you learned will be lost.",
+                theme::glyph("⚠", "!")
+            )
+            .warning()
+            .bold()
+        );
+
+        let save = Select::with_theme(&output::prompts()?)
+            .with_prompt("Save the transcript?")
+            .items(&["Yes, save transcript", "No, discard"])
+            .default(0)
+            .interact()?;
+
+        if save == 0 {
+            transcript = save_transcript(&session, config)?;
+        }
+    }
+
+    if let Some(ref command) = config.hooks.on_session_end {
+        if config.dry_run {
+            println!("{}", format!("Dry run: would run on_session_end hook: {}", command).warning());
+        } else if let Err(e) = hooks::run_hook(command, &hooks::session_end_env(&session, transcript.as_deref())) {
+            println!("{} {}", "on_session_end hook failed:".warning(), e);
+        }
+    }
+
+    Ok(())
+}
+
+/// Run the `post_save` hooks on a TIL or note that was just saved. A failing hook is reported but
+/// doesn't undo the save.
+fn run_post_save_hooks(kind: &str, path: &str, content: &str, category: &str, mode: &str, config: &Config) {
+    let title = til::writer::extract_title(content).unwrap_or_default();
+    let saved = hooks::SavedFile {
+        kind,
+        path,
+        title: &title,
+        category,
+        mode,
+    };
+    for command in &config.hooks.post_save {
+        if let Err(e) = hooks::run_post_save_hook(command, &saved) {
+            eprintln!("{} {}", "post_save hook failed:".warning(), e);
+        }
+    }
+}
+
+/// Write the session's log note to the vault without asking.
+/// Failures are shown but never interrupt shutdown.
+fn autosave_session(session: &mut Session, config: &Config) {
+    let Some(ref notes_path) = config.notes_path else {
+        println!("{}", "autosave_sessions is on but no notes path is configured.".warning());
+        return;
+    };
+
+    if config.dry_run {
+        let path = notes::session_log::session_log_path(notes_path, session);
+        println!("{}", format!("Dry run: would save session log to {}", path.display()).warning());
+        return;
+    }
+
+    match notes::session_log::write_session_log(notes_path, session, &config.notes_format, config.language.as_deref()) {
+        Ok(path) => {
+            println!("{} {} {}", theme::done(), "Session log saved to:".success().bold(), path.display());
+            session.add_artifact(path);
+        }
+        Err(e) => println!("{} {}", "Could not save session log:".warning(), e),
+    }
+}
+
+/// Store the session so it can be revisited later (e.g. `holocron til --from-sessions`).
+/// Failures are shown but never interrupt the session.
+fn persist_session(session: &Session, config: &Config) {
+    if config.dry_run {
+        return;
+    }
+    if let Err(e) = Config::sessions_dir().and_then(|dir| session_store::save(&dir, session)) {
+        println!("{} {}", "Could not store session:".warning(), e);
+    }
+}
+
+fn print_session_recap(session: &Session) {
+    println!();
+    println!("{}", "Session recap".bold());
+    println!("  Topic:      {}", session.topic());
+    println!("  Duration:   {}", session::format_duration(session.duration()));
+    if let Some(ref timebox) = session.timebox {
+        println!("  Timebox:    {}m", timebox.minutes);
+    }
+    println!("  Exchanges:  {}", session.exchanges.len());
+    if let Some(elapsed) = session.time_to_til() {
+        println!("  TIL after:  {}", session::format_duration(elapsed));
+    }
+    println!(
+        "  Usage:      {} tokens · ${:.4}",
+        session.usage.total_tokens(),
+        session.usage.cost_usd
+    );
+    if session.artifacts.is_empty() {
+        println!("  Saved:      {}", "nothing".failure());
+    } else {
+        for path in &session.artifacts {
+            println!("  Saved:      {}", path.display().to_string().success());
+        }
+    }
+    println!();
+}
+
+/// Write the session transcript to the transcripts directory, returning its path
+fn save_transcript(session: &Session, config: &Config) -> Result<Option<PathBuf>> {
+    let filename = format!(
+        "{}-{}",
+        session.started_at.format("%Y-%m-%d-%H%M"),
+        til::writer::title_to_filename(session.topic())
+    );
+    let path = Config::transcripts_dir()?.join(filename);
+
+    if config.dry_run {
+        println!("{}", format!("Dry run: would save transcript to {}", path.display()).warning());
+        return Ok(None);
+    }
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(&path, session.transcript())?;
+    println!("{} {} {}", theme::done(), "Transcript saved to:".success().bold(), path.display());
+
+    Ok(Some(path))
+}
+
+/// Suggest numbered follow-up questions; failures are shown but never interrupt the session
+fn show_followups(session: &mut Session) {
+    let spinner = create_spinner("Thinking of follow-ups...");
+    let result = followups::suggest_followups(session);
+    spinner.finish_and_clear();
+
+    match result {
+        Ok(questions) if !questions.is_empty() => {
+            println!("{}", "Follow-ups:".muted());
+            for (i, question) in questions.iter().enumerate() {
+                println!("  {} {}", format!("{}.", i + 1).info(), question);
             }
-            print!("{}", text);
-            io::stdout().flush().ok();
-            response.push_str(text);
-        })?;
+            println!();
+            session.followups = questions;
+        }
+        Ok(_) => {}
+        Err(e) => println!("{} {}", "Could not suggest follow-ups:".warning(), e),
+    }
+}
 
-        if let Some(sid) = maybe_session_id {
-            session.set_session_id(sid);
+/// Pick the session's category, typing to filter: favorites first, then the archive's
+/// categories with how many TILs each has
+fn prompt_for_category(config: &Config) -> Result<Option<String>> {
+    let counts = til::category::category_counts(&config.archive_path()).unwrap_or_default();
+    let options = til::category::picker_options(&config.favorite_categories, &counts, &config.categories);
+    let fixed = !config.categories.is_empty();
+
+    let mut items: Vec<String> = options
+        .iter()
+        .map(|(category, count)| {
+            // Sub-categories are indented under their parent
+            let indent = "  ".repeat(category.matches('/').count());
+            match count {
+                0 => format!("{}{}", indent, category),
+                _ => format!("{}{} ({})", indent, category, count),
+            }
+        })
+        .collect();
+    if !fixed {
+        items.push("Other (type custom)".to_string());
+    }
+    items.push("Skip (decide later)".to_string());
+
+    let selection = FuzzySelect::with_theme(&output::prompts()?)
+        .with_prompt("Category for TIL (type to filter)")
+        .items(&items)
+        .default(0)
+        .interact()?;
+
+    match options.get(selection) {
+        Some((category, _)) => Ok(Some(category.clone())),
+        None if !fixed && selection == options.len() => {
+            let custom: String = Input::with_theme(&output::prompts()?)
+                .with_prompt("Enter category")
+                .interact_text()?;
+            Ok(Some(til::category::normalize(&custom)))
         }
-        Ok(resp)
+        None => Ok(None),
+    }
+}
+
+fn generate_and_save_til(session: &mut Session, config: &Config) -> Result<()> {
+    if let Some(ref target) = config.append_to {
+        return append_to_til(session, target, config);
+    }
+
+    if config.require_recall && session.recall.is_none() && io::stdin().is_terminal() {
+        recall_key_concept(session, config)?;
+    }
+
+    let initial_category = session.category.clone();
+    // The conversation that wrote the TIL, kept open so revisions build on it
+    let (mut til_content, mut conversation) = stream_til(session, initial_category.as_deref(), config)?;
+    if config.verify_tils {
+        til_content = fact_check_til(session, &til_content, config)?;
+    }
+
+    let mut category = match session.category.clone() {
+        Some(category) => category,
+        None => choose_til_category(session, config)?,
     };
 
-    if first_chunk {
-        spinner.finish_and_clear();
+    if let Some(existing) = offer_append(&til_content, &category, config)? {
+        return append_to_til(session, &existing, config);
+    }
+
+    let mut shown_diff = None;
+    loop {
+        let content = match session.saved_note {
+            Some(ref note) => backlinks::link_til_to_note(&til_content, note),
+            None => til_content.clone(),
+        };
+        let content = markdown::add_tags(&content, &session.tags);
+        let (mut plan, filename) = plan_til_write(&content, &category, config)?;
+
+        if config.dry_run {
+            print_til_preview(&plan, config);
+            println!("{}", "Dry run: nothing was written.".warning());
+            return Ok(());
+        }
+
+        let label = format!("{}/{}", category, filename);
+        if let Ok(existing) = std::fs::read_to_string(&plan.file_path) {
+            show_overwrite_diff(&existing, &plan.content, &label, &mut shown_diff);
+        }
+
+        match save_prompt::prompt_save_action(&label, save_prompt::TIL_ACTIONS)? {
+            SaveAction::Save => {
+                choose_readme_spot(&mut plan)?;
+                let saved = save_til(&plan, config)?;
+                println!();
+                println!("{} {} {}", theme::done(), "TIL saved to:".success().bold(), saved);
+                run_post_save_hooks("til", &saved.to_string(), &content, &category, &session.mode.to_string(), config);
+                if let Saved::Local(path) = saved {
+                    if path == plan.file_path {
+                        println!("{}", "  README.md updated".muted());
+                    }
+                    if let Some(note) = session.saved_note.clone() {
+                        report_backlink(backlinks::update_saved_note(&note, &path), &note);
+                    }
+                    let first = session.til_saved_at.is_none();
+                    session.record_til(path);
+                    if let (true, Some(elapsed)) = (first, session.time_to_til()) {
+                        println!("{}", format!("  Time to TIL: {}", session::format_duration(elapsed)).muted());
+                    }
+                }
+                return Ok(());
+            }
+            SaveAction::Edit => til_content = launcher::edit_text(&til_content, "md")?,
+            SaveAction::Regenerate => {
+                (til_content, conversation) = stream_til(session, Some(&category), config)?;
+                if config.verify_tils {
+                    til_content = fact_check_til(session, &til_content, config)?;
+                }
+            }
+            SaveAction::Verify => til_content = fact_check_til(session, &til_content, config)?,
+            SaveAction::Revise => {
+                let feedback: String = Input::with_theme(&output::prompts()?)
+                    .with_prompt("What should change?")
+                    .allow_empty(true)
+                    .interact_text()?;
+                if !feedback.trim().is_empty() {
+                    (til_content, conversation) =
+                        stream_revised_til(session, conversation.as_deref(), &til_content, &feedback, config)?;
+                }
+            }
+            SaveAction::ChangeCategory => category = prompt_category_input()?,
+            SaveAction::Preview => print_til_preview(&plan, config),
+            SaveAction::Later => {}
+            SaveAction::Discard => {
+                println!("{}", "TIL discarded.".warning());
+                return Ok(());
+            }
+        }
+    }
+}
+
+/// Fact-check a generated TIL against the session, listing unsupported claims and showing how the
+/// checked version differs. Returns the checked TIL.
+fn fact_check_til(session: &mut Session, til_content: &str, config: &Config) -> Result<String> {
+    println!();
+    let spinner = create_spinner("Fact-checking the TIL against the session...");
+    let started = std::time::Instant::now();
+    let response = til::verify::verify(session, til_content, |_| {});
+    spinner.finish_and_clear();
+    let response = response?;
+    session.record_usage(&response.usage);
+    notify::finished(config.notifications, started, "Fact-check done", session.topic());
+
+    let verification = til::verify::Verification::from_reply(til_content, &response.text);
+    if verification.flags.is_empty() {
+        println!("{} {}", theme::done(), "Every claim is backed by the session.".success());
+        return Ok(til_content.to_string());
+    }
+
+    println!("{}", "Claims the session doesn't back up:".warning().bold());
+    for flag in &verification.flags {
+        println!("  {} {}", theme::glyph("•", "-"), flag);
+    }
+    let diff = diff::unified_diff(til_content, &verification.til, "a/TIL", "b/TIL (checked)", 1);
+    if !diff.is_empty() {
+        println!();
+        print_diff(&diff);
+    }
+    Ok(verification.til)
+}
+
+/// Ask for the session's key concept in my own words and have the backend correct it; the
+/// corrected explanation goes into the TIL. An empty answer skips it.
+fn recall_key_concept(session: &mut Session, config: &Config) -> Result<()> {
+    let explanation: String = Input::with_theme(&output::prompts()?)
+        .with_prompt(format!("Explain the key idea of {} in your own words (empty to skip)", session.topic()))
+        .allow_empty(true)
+        .interact_text()?;
+    if explanation.trim().is_empty() {
+        return Ok(());
     }
 
+    println!();
+    let spinner = create_spinner("Checking your explanation...");
+    let mut reply = String::new();
+    let response = til::recall::check(session, &explanation, |text| {
+        if reply.is_empty() {
+            spinner.finish_and_clear();
+        }
+        print!("{}", text);
+        io::stdout().flush().ok();
+        reply.push_str(text);
+    })?;
+    spinner.finish_and_clear();
     println!();
     println!();
 
-    match result {
-        Ok(resp) => {
-            session.add_exchange(message.to_string(), resp);
-            Ok(())
+    session.record_usage(&response.usage);
+    session.recall = Some(til::recall::Recall::from_reply(&explanation, &reply));
+    if !config.dry_run {
+        println!("{}", "Your corrected explanation will go into the TIL.".muted());
+    }
+    Ok(())
+}
+
+/// Handle /outline: have the backend propose the note's sections, shape them, and keep the
+/// approved outline for /note to follow
+fn outline_note(session: &mut Session) -> Result<()> {
+    let mut outline = propose_outline(session)?;
+    loop {
+        println!();
+        if outline.is_empty() {
+            println!("{}", "The outline is empty. Add a section or edit it.".warning());
+        } else {
+            println!("{}", "Outline:".success().bold());
+            print_rule();
+            print!("{}", outline.render());
+            print_rule();
         }
-        Err(e) => {
-            println!("{} {}", "Error:".red().bold(), e);
-            Err(e)
+
+        let choice = Select::with_theme(&output::prompts()?)
+            .with_prompt("Use this outline for the note?")
+            .items(&["Approve", "Remove sections", "Add a section", "Edit in editor", "Propose another", "Discard"])
+            .default(0)
+            .interact()?;
+        match choice {
+            0 if outline.is_empty() => {}
+            0 => {
+                session.outline = Some(outline);
+                println!("{} /note will follow this outline.", theme::done());
+                return Ok(());
+            }
+            1 => remove_outline_sections(&mut outline)?,
+            2 => add_outline_section(&mut outline)?,
+            3 => outline = notes::outline::Outline::parse(&launcher::edit_text(&outline.render(), "md")?),
+            4 => outline = propose_outline(session)?,
+            _ => {
+                println!("{}", "Outline discarded.".warning());
+                return Ok(());
+            }
         }
     }
 }
 
-fn prompt_for_category() -> Result<Option<String>> {
-    let categories = vec![
-        "git",
-        "rust",
-        "sql",
-        "postgres",
-        "python",
-        "javascript",
-        "Other (type custom)",
-        "Skip (decide later)",
-    ];
+fn propose_outline(session: &mut Session) -> Result<notes::outline::Outline> {
+    println!();
+    let spinner = create_spinner("Proposing an outline...");
+    let response = notes::outline::propose(session, |_| {});
+    spinner.finish_and_clear();
+    let response = response?;
+    session.record_usage(&response.usage);
+    Ok(notes::outline::Outline::parse(&response.text))
+}
+
+fn remove_outline_sections(outline: &mut notes::outline::Outline) -> Result<()> {
+    let titles: Vec<&str> = outline.sections.iter().map(|section| section.title.as_str()).collect();
+    if titles.is_empty() {
+        return Ok(());
+    }
+    let mut removed = MultiSelect::with_theme(&output::prompts()?)
+        .with_prompt("Sections to remove (space to select)")
+        .items(&titles)
+        .interact()?;
+    removed.sort_unstable();
+    for index in removed.into_iter().rev() {
+        outline.sections.remove(index);
+    }
+    Ok(())
+}
+
+fn add_outline_section(outline: &mut notes::outline::Outline) -> Result<()> {
+    let title: String = Input::with_theme(&output::prompts()?)
+        .with_prompt("Section heading")
+        .interact_text()?;
+    let points: String = Input::with_theme(&output::prompts()?)
+        .with_prompt("Key points, separated by ;")
+        .allow_empty(true)
+        .interact_text()?;
+
+    let mut places = vec!["At the start".to_string()];
+    places.extend(outline.sections.iter().map(|section| format!("After {}", section.title)));
+    let place = Select::with_theme(&output::prompts()?)
+        .with_prompt("Where does it go?")
+        .items(&places)
+        .default(places.len() - 1)
+        .interact()?;
+
+    let section = notes::outline::Section {
+        title: title.trim().to_string(),
+        points: points.split(';').map(str::trim).filter(|p| !p.is_empty()).map(str::to_string).collect(),
+    };
+    outline.sections.insert(place, section);
+    Ok(())
+}
+
+/// Plan writing a TIL into the archive (auto-linking it if enabled), returning the plan and filename
+fn plan_til_write(til_content: &str, category: &str, config: &Config) -> Result<(til::writer::TilWrite, String)> {
+    let title = til::writer::extract_title(til_content).unwrap_or_else(|| "Untitled TIL".to_string());
+    let today = chrono::Local::now().date_naive();
+    let mut filename = config.filename_style.filename(&title, today);
+    if config.encrypt_notes {
+        filename = crypto::encrypted_filename(&filename, &config.encryption.tool);
+    }
+    let entry_dir = config.archive_layout.entry_dir(category, today);
+
+    let mut til_content = variables::add_to_frontmatter(til_content, &config.variables);
+    if let Some(ref author) = config.author {
+        til_content = markdown::set_frontmatter_field(&til_content, "author", &variables::yaml_scalar(author));
+    }
+    let content = if config.auto_link {
+        let til_path = config.archive_path().join(&entry_dir).join(&filename);
+        let targets = til::linker::archive_targets(&config.archive_path())?;
+        til::linker::link_mentions(&til_content, &til_path, &targets)
+    } else {
+        til_content
+    };
+
+    let credit = config.author.as_deref().filter(|_| config.readme_authors);
+    let plan =
+        til::writer::plan_til(&config.til_path, &config.archive_dir, &entry_dir, &filename, &content, &title, credit)?;
+    Ok((plan, filename))
+}
+
+/// Ask where the README entry goes when README.md doesn't make it clear (the category's header
+/// appears twice, or its section isn't a plain list of links), rather than trusting the guess
+fn choose_readme_spot(plan: &mut til::writer::TilWrite) -> Result<()> {
+    let Some(choice) = plan.insertion_choice.clone() else {
+        return Ok(());
+    };
+    if !io::stdin().is_terminal() {
+        return Ok(());
+    }
+    let lines: Vec<&str> = plan.readme_before.lines().collect();
+    let items: Vec<String> = choice
+        .lines
+        .iter()
+        .map(|&spot| format!("{:>4} │ {}", spot, lines.get(spot - 1).copied().unwrap_or_default()))
+        .collect();
+    println!("{}", "README.md doesn't make it clear where this TIL's entry belongs.".warning());
+    let picked = Select::with_theme(&output::prompts()?)
+        .with_prompt("Add the entry after which line?")
+        .items(&items)
+        .default(choice.suggested)
+        .interact()?;
+    plan.insert_entry_at(choice.lines[picked]);
+    Ok(())
+}
+
+/// Store a planned TIL where TILs go (the archive, unless `[storage.til]` says otherwise),
+/// encrypting it first if `encrypt_notes` is on
+fn save_til(plan: &til::writer::TilWrite, config: &Config) -> Result<Saved> {
+    if config.storage.til.is_none() {
+        let feed = export::feed::default_path(config);
+        let mut paths = vec![plan.file_path.as_path(), plan.readme_path.as_path()];
+        if config.feed.auto {
+            paths.push(&feed);
+        }
+        record_undo(config, &format!("Save TIL {}", plan.entry_path), &paths)?;
+    }
+    let target = storage::target(config.storage.til.as_ref(), &plan.archive_path);
+    let saved = if config.encrypt_notes {
+        plan.save_to(target.as_ref(), |content| crypto::encrypt(content, &config.encryption))?
+    } else {
+        plan.save_to(target.as_ref(), |content| Ok(content.as_bytes().to_vec()))?
+    };
+
+    if config.feed.auto && matches!(saved, Saved::Local(_)) {
+        let settings = export::feed::FeedSettings::from_config(config);
+        if let Err(e) = export::feed::write(config, &settings, &export::feed::default_path(config)) {
+            eprintln!("{}", format!("Couldn't update the feed: {}", e).warning());
+        }
+    }
+    Ok(saved)
+}
+
+/// Offer to grow an existing TIL when the new one looks like a duplicate of it
+fn offer_append(til_content: &str, category: &str, config: &Config) -> Result<Option<PathBuf>> {
+    let Some(title) = til::writer::extract_title(til_content) else {
+        return Ok(None);
+    };
+    let entries = archive::til_entries(&config.archive_path())?;
+    let Some(similar) = til::append::find_similar(&entries, &title, category) else {
+        return Ok(None);
+    };
 
-    let selection = Select::with_theme(&ColorfulTheme::default())
-        .with_prompt("Category for TIL")
-        .items(&categories)
+    println!();
+    let choice = Select::with_theme(&output::prompts()?)
+        .with_prompt(format!("\"{}\" already covers this topic", similar.title))
+        .items(&["Append to it", "Save as a new TIL"])
         .default(0)
         .interact()?;
+    Ok((choice == 0).then(|| similar.path.clone()))
+}
 
-    match categories[selection] {
-        "Skip (decide later)" => Ok(None),
-        "Other (type custom)" => {
-            let custom: String = Input::with_theme(&ColorfulTheme::default())
-                .with_prompt("Enter category")
-                .interact_text()?;
-            Ok(Some(custom.to_lowercase()))
+/// Grow an existing TIL with what this session taught. The README is left untouched.
+fn append_to_til(session: &mut Session, path: &std::path::Path, config: &Config) -> Result<()> {
+    let existing = read_til(path, config)?;
+    let mut merged = stream_merged_til(session, &existing, config)?;
+    let label = path.strip_prefix(config.archive_path()).unwrap_or(path).display().to_string();
+
+    let mut shown_diff = None;
+    loop {
+        if config.dry_run {
+            print_append_preview(&existing, &merged, &label);
+            println!("{}", "Dry run: nothing was written.".warning());
+            return Ok(());
+        }
+        show_overwrite_diff(&existing, &merged, &label, &mut shown_diff);
+
+        match save_prompt::prompt_save_action(&label, save_prompt::APPEND_ACTIONS)? {
+            SaveAction::Save => {
+                write_til(path, &merged, config)?;
+                println!();
+                println!("{} {} {}", theme::done(), "TIL updated:".success().bold(), path.display());
+                println!("{}", "  README.md unchanged".muted());
+                let category = session.category.as_deref().unwrap_or_default();
+                let mode = session.mode.to_string();
+                run_post_save_hooks("til", &path.display().to_string(), &merged, category, &mode, config);
+                session.record_til(path.to_path_buf());
+                return Ok(());
+            }
+            SaveAction::Edit => merged = launcher::edit_text(&merged, "md")?,
+            SaveAction::Regenerate => merged = stream_merged_til(session, &existing, config)?,
+            SaveAction::Preview => print_append_preview(&existing, &merged, &label),
+            SaveAction::Revise | SaveAction::Verify | SaveAction::ChangeCategory | SaveAction::Later => {}
+            SaveAction::Discard => {
+                println!("{}", "TIL discarded.".warning());
+                return Ok(());
+            }
         }
-        cat => Ok(Some(cat.to_string())),
     }
 }
 
-fn generate_and_save_til(session: &Session, config: &Config) -> Result<()> {
+/// Read a TIL from the archive, decrypting it if needed
+fn read_til(path: &std::path::Path, config: &Config) -> Result<String> {
+    if crypto::is_encrypted(path) {
+        crypto::decrypt(path, &config.encryption)
+    } else {
+        std::fs::read_to_string(path).map_err(|e| anyhow!("Failed to read {:?}: {}", path, e))
+    }
+}
+
+/// Overwrite a TIL in place, keeping it encrypted if it was
+fn write_til(path: &std::path::Path, content: &str, config: &Config) -> Result<()> {
+    let content = format!("{}\n", content.trim_end());
+    let _lock = files::lock(path)?;
+    record_undo(config, &format!("Update TIL {}", path.display()), &[path])?;
+    if crypto::is_encrypted(path) {
+        crypto::write_encrypted(path, &content, &config.encryption)
+    } else {
+        files::write_atomic(path, content).map_err(|e| anyhow!("Failed to write {:?}: {}", path, e))
+    }
+}
+
+fn print_append_preview(existing: &str, merged: &str, label: &str) {
+    println!();
+    print_diff(&diff::unified_diff(existing, merged, &format!("a/{}", label), &format!("b/{}", label), 3));
+}
+
+/// Generate a TIL, streaming it to the terminal. The category's style hints are applied if it has any.
+/// Returns the TIL and the backend conversation that wrote it.
+fn stream_til(session: &mut Session, category: Option<&str>, config: &Config) -> Result<(String, Option<String>)> {
+    let style = category_style(category, config)?;
+    stream_generated(session, "Generating TIL...", config, |session, on_text| {
+        til::generate_til(session, &style, on_text)
+    })
+}
+
+/// Revise a TIL from feedback in the conversation that wrote it, streaming the new version
+fn stream_revised_til(
+    session: &mut Session,
+    conversation: Option<&str>,
+    current: &str,
+    feedback: &str,
+    config: &Config,
+) -> Result<(String, Option<String>)> {
+    stream_generated(session, "Revising TIL...", config, |session, on_text| {
+        let resume = claude::Resume { session_id: conversation, dir: session.claude_dir.as_deref() };
+        til::refine_til(resume, current, feedback, on_text)
+    })
+}
+
+/// Merge this session into an existing TIL, streaming the result. Its title is kept.
+fn stream_merged_til(session: &mut Session, existing: &str, config: &Config) -> Result<String> {
+    let (merged, _) = stream_generated(session, "Merging into the existing TIL...", config, |session, on_text| {
+        til::integrate_til(session, existing, on_text)
+    })?;
+    Ok(til::append::keep_title(existing, &merged))
+}
+
+/// Stream a generated TIL to the terminal and record what it cost.
+/// Returns the TIL and the backend conversation it came from.
+fn stream_generated<G>(
+    session: &mut Session,
+    progress: &str,
+    config: &Config,
+    generate: G,
+) -> Result<(String, Option<String>)>
+where
+    G: FnOnce(&Session, &mut dyn FnMut(&str)) -> Result<claude::ClaudeResponse>,
+{
     println!();
-    let spinner = create_spinner("Generating TIL...");
+    let spinner = create_spinner(progress);
+    let started = std::time::Instant::now();
 
     let mut til_content = String::new();
     let mut first_chunk = true;
 
-    til::generate_til(session, |text| {
+    let response = generate(session, &mut |text| {
         if first_chunk {
             spinner.finish_and_clear();
-            println!("{}", "Generated TIL:".green().bold());
-            println!("{}", "─".repeat(40));
+            println!("{}", "Generated TIL:".success().bold());
+            print_rule();
             first_chunk = false;
         }
         print!("{}", text);
@@ -531,51 +4598,200 @@ fn generate_and_save_til(session: &Session, config: &Config) -> Result<()> {
     }
 
     println!();
-    println!("{}", "─".repeat(40));
+    print_rule();
 
-    let title = til::writer::extract_title(&til_content).unwrap_or_else(|| "Untitled TIL".to_string());
+    session.record_usage(&response.usage);
+    notify::finished(config.notifications, started, "TIL ready", &til_content);
+    Ok((til_content, response.session_id))
+}
 
-    let category = session
-        .category
-        .clone()
-        .map_or_else(prompt_category_input, Ok)?;
+/// Load the generation style for a category (empty when there's no category yet)
+fn category_style(category: Option<&str>, config: &Config) -> Result<til::style::CategoryStyle> {
+    let mut style = match category {
+        Some(category) => til::style::CategoryStyle::load(&config.archive_path(), category)?,
+        None => til::style::CategoryStyle::default(),
+    };
+    if style.template.is_none() {
+        style.template = config.til_template.clone();
+    }
+    if style.language.is_none() {
+        style.language = config.language.clone();
+    }
+    style.hints = style.hints.map(|hints| variables::fill(&hints, &config.variables));
+    style.template = style.template.map(|template| variables::fill(&template, &config.variables));
+    Ok(style)
+}
 
-    let filename = til::writer::title_to_filename(&title);
+/// Show the README diff and the new TIL file as unified diffs
+fn print_til_preview(plan: &til::writer::TilWrite, config: &Config) {
+    let relative = plan
+        .file_path
+        .strip_prefix(&config.til_path)
+        .unwrap_or(&plan.file_path)
+        .display()
+        .to_string();
+    let existing = std::fs::read_to_string(&plan.file_path).unwrap_or_default();
+    let old_label = if existing.is_empty() {
+        "/dev/null".to_string()
+    } else {
+        format!("a/{}", relative)
+    };
 
-    let confirm = Select::with_theme(&ColorfulTheme::default())
-        .with_prompt(format!("Save as {}/{}?", category, filename))
-        .items(&["Yes, save it", "No, discard"])
-        .default(0)
-        .interact()?;
+    println!();
+    print_diff(&diff::unified_diff(&existing, &plan.content, &old_label, &format!("b/{}", relative), 3));
+    print_diff(&diff::unified_diff(&plan.readme_before, &plan.readme_after, "a/README.md", "b/README.md", 3));
+}
 
-    if confirm == 0 {
-        let path = til::write_til(&config.til_path, &config.archive_dir, &category, &filename, &til_content, &title)?;
-        println!();
-        println!("{} {}", "✓ TIL saved to:".green().bold(), path.display());
-        println!("{}", "  README.md updated".dimmed());
-    } else {
-        println!("{}", "TIL discarded.".yellow());
+/// Print a unified diff with added/removed lines colored
+fn print_diff(diff: &str) {
+    print!("{}", diff::colorize(diff));
+}
+
+/// Before asking to save over an existing file, show how it would change. Each version is
+/// shown once, so picking another action doesn't print the same diff again.
+fn show_overwrite_diff(existing: &str, content: &str, label: &str, shown: &mut Option<String>) {
+    if shown.as_deref() == Some(content) {
+        return;
     }
+    *shown = Some(content.to_string());
 
-    Ok(())
+    let diff = diff::unified_diff(existing, content, &format!("a/{}", label), &format!("b/{}", label), 3);
+    if diff.is_empty() {
+        return;
+    }
+    println!();
+    println!("{}", format!("{} already exists. Saving changes it like this:", label).warning());
+    print_diff(&diff);
 }
 
-fn generate_and_save_note(session: &Session, config: &Config) -> Result<()> {
+fn generate_and_save_note(session: &mut Session, config: &Config) -> Result<()> {
     let notes_path = config.notes_path.as_ref().ok_or_else(|| {
         anyhow!("Notes path not configured. Run: holocron config --notes-path <path>")
     })?;
 
+    let generated = stream_note(session, config)?;
+    let mut note_content = prepare_note(session, generated, config)?;
+    // Encrypted notes stay markdown, since decrypting expects `.md.age` / `.md.gpg`
+    let org_id = (config.notes_format == NotesFormat::Org && !config.encrypt_notes).then(notes::org::new_id);
+
+    let mut shown_diff = None;
+    let mut filed = None;
+    loop {
+        if let Err(e) = notes::writer::validate_note(&note_content) {
+            println!("{} {}", "Note can't be saved yet:".failure().bold(), e);
+        }
+
+        let title = notes::writer::extract_title(&note_content).unwrap_or_else(|| "Untitled Note".to_string());
+        let mut filename = config.filename_style.filename(&title, chrono::Local::now().date_naive());
+        if config.encrypt_notes {
+            filename = crypto::encrypted_filename(&filename, &config.encryption.tool);
+        } else if org_id.is_some() {
+            filename = notes::org::org_filename(&filename);
+        }
+        let folder = choose_note_folder(&note_content, config, &mut filed)?;
+        let label = notes::writer::note_file(folder.as_deref(), &filename);
+        let note_path = notes::writer::note_path(notes_path, folder.as_deref(), &filename);
+        let content = match org_id {
+            Some(ref id) => notes::org::to_org(&note_content, id),
+            None => note_content.clone(),
+        };
+        let content = match session.saved_til {
+            Some(ref til) => backlinks::link_note_to_til(&content, &note_path, til),
+            None => content,
+        };
+
+        if config.dry_run {
+            print_note_preview(&note_path, &content);
+            println!("{}", "Dry run: nothing was written.".warning());
+            return Ok(());
+        }
+
+        if let Ok(existing) = std::fs::read_to_string(&note_path) {
+            show_overwrite_diff(&existing, &content, &label, &mut shown_diff);
+        }
+
+        match save_prompt::prompt_save_action(&label, save_prompt::NOTE_ACTIONS)? {
+            SaveAction::Save => {
+                if notes::writer::validate_note(&note_content).is_err() {
+                    continue;
+                }
+                let target = storage::target(config.storage.notes.as_ref(), notes_path);
+                let saved = if config.encrypt_notes {
+                    let ciphertext = crypto::encrypt(&content, &config.encryption)?;
+                    target.save(&label, &ciphertext)?
+                } else {
+                    notes::write_note(target.as_ref(), folder.as_deref(), &filename, &content)?
+                };
+                println!();
+                println!("{} {} {}", theme::done(), "Note saved to:".success().bold(), saved);
+                let category = session.category.as_deref().unwrap_or_default();
+                let mode = session.mode.to_string();
+                run_post_save_hooks("note", &saved.to_string(), &note_content, category, &mode, config);
+                if let Saved::Local(path) = saved {
+                    if let Some(til) = session.saved_til.clone() {
+                        report_backlink(backlinks::update_saved_til(&til, &path), &til);
+                    }
+                    // An encrypted note's title and tags would be readable in the index
+                    if !config.encrypt_notes {
+                        if let Err(e) = index_note(session, notes_path, &path, &note_content) {
+                            println!("{} {}", "Could not update notes.json:".warning(), e);
+                        }
+                    }
+                    session.record_note(path);
+                }
+                return Ok(());
+            }
+            SaveAction::Edit => note_content = launcher::edit_text(&note_content, "md")?,
+            SaveAction::Regenerate => {
+                let generated = stream_note(session, config)?;
+                note_content = prepare_note(session, generated, config)?;
+            }
+            SaveAction::Preview => print_note_preview(&note_path, &content),
+            SaveAction::Revise | SaveAction::Verify | SaveAction::ChangeCategory | SaveAction::Later => {}
+            SaveAction::Discard => {
+                println!("{}", "Note discarded.".warning());
+                return Ok(());
+            }
+        }
+    }
+}
+
+/// Add a just-saved note to the notes repo's `notes.json`
+fn index_note(session: &Session, notes_path: &std::path::Path, path: &std::path::Path, content: &str) -> Result<()> {
+    let today = chrono::Local::now().date_naive();
+    let mut note = notes::index::IndexedNote::new(notes_path, path, content, today, session.topic());
+    note.session = Config::sessions_dir().ok().and_then(|dir| {
+        let file = session_store::session_path(&dir, session);
+        file.file_name().map(|name| name.to_string_lossy().to_string())
+    });
+    note.til = session.saved_til.clone();
+
+    notes::index::NotesIndex::add(notes_path, note)
+}
+
+/// Show the outcome of linking an already-saved TIL or note to its new counterpart
+fn report_backlink(result: Result<bool>, path: &std::path::Path) {
+    match result {
+        Ok(true) => println!("{}", format!("  Linked from {}", path.display()).muted()),
+        Ok(false) => {}
+        Err(e) => println!("{} {}", "Could not add backlink:".warning(), e),
+    }
+}
+
+/// Generate a note, streaming it to the terminal
+fn stream_note(session: &mut Session, config: &Config) -> Result<String> {
     println!();
     let spinner = create_spinner("Generating note...");
+    let started = std::time::Instant::now();
 
     let mut note_content = String::new();
     let mut first_chunk = true;
 
-    notes::generate_note(session, |text| {
+    let response = notes::generate_note(session, config.language.as_deref(), |text| {
         if first_chunk {
             spinner.finish_and_clear();
-            println!("{}", "Generated Note:".green().bold());
-            println!("{}", "─".repeat(40));
+            println!("{}", "Generated Note:".success().bold());
+            print_rule();
             first_chunk = false;
         }
         print!("{}", text);
@@ -588,31 +4804,179 @@ fn generate_and_save_note(session: &Session, config: &Config) -> Result<()> {
     }
 
     println!();
-    println!("{}", "─".repeat(40));
+    print_rule();
+
+    session.record_usage(&response.usage);
+    notify::finished(config.notifications, started, "Note ready", session.topic());
+    Ok(note_content)
+}
+
+/// Fill in alt text and frontmatter metadata, including the session's tags and the config's variables,
+/// on a freshly generated note
+fn prepare_note(session: &Session, content: String, config: &Config) -> Result<String> {
+    let content = notes::writer::add_sources(&content, &session.sources);
+    let content = fill_missing_alt_text(session, content)?;
+    let content = add_note_metadata(session, content)?;
+    let content = markdown::add_tags(&content, &session.tags);
+    Ok(variables::add_to_frontmatter(&content, &config.variables))
+}
 
-    let title = notes::writer::extract_title(&note_content).unwrap_or_else(|| "Untitled Note".to_string());
-    let filename = notes::writer::title_to_filename(&title);
+/// The vault folder a note is filed in: the one its tags map to, once confirmed. Asked again
+/// only if edits send it somewhere else.
+fn choose_note_folder(
+    content: &str,
+    config: &Config,
+    confirmed: &mut Option<(String, Option<String>)>,
+) -> Result<Option<String>> {
+    let Some((tag, folder)) = notes::writer::folder_for(content, &config.note_folders) else {
+        return Ok(None);
+    };
+    if let Some((ref routed, ref chosen)) = *confirmed {
+        if *routed == folder {
+            return Ok(chosen.clone());
+        }
+    }
+    if config.dry_run {
+        return Ok(Some(folder));
+    }
 
-    let confirm = Select::with_theme(&ColorfulTheme::default())
-        .with_prompt(format!("Save as {}?", filename))
-        .items(&["Yes, save it", "No, discard"])
+    let choice = Select::with_theme(&output::prompts()?)
+        .with_prompt(format!("Tagged `{}`, so the note goes in {}/", tag, folder.trim_end_matches('/')))
+        .items(&["Save it there", "Save it in the vault root"])
         .default(0)
         .interact()?;
+    let chosen = (choice == 0).then(|| folder.clone());
+    *confirmed = Some((folder, chosen.clone()));
+    Ok(chosen)
+}
 
-    if confirm == 0 {
-        let path = notes::write_note(notes_path, &filename, &note_content)?;
-        println!();
-        println!("{} {}", "✓ Note saved to:".green().bold(), path.display());
+/// Show the note as a diff against any existing file at the same path
+fn print_note_preview(note_path: &std::path::Path, content: &str) {
+    let existing = std::fs::read_to_string(note_path).unwrap_or_default();
+    let label = format!("b/{}", note_path.file_name().unwrap_or_default().to_string_lossy());
+    println!();
+    print_diff(&diff::unified_diff(&existing, content, "a/note", &label, 3));
+}
+
+/// Add reading time to the note and make sure it carries a difficulty level, asking the backend
+/// to rate it if the note came back without one, and the user if that doesn't work either
+fn add_note_metadata(session: &Session, content: String) -> Result<String> {
+    let mut content = notes::writer::add_reading_time(&content);
+
+    if notes::writer::difficulty(&content).is_none() {
+        let spinner = create_spinner("Rating the note's difficulty...");
+        let rated = notes::rate_difficulty(session, &content, |_| {});
+        spinner.finish_and_clear();
+        match rated {
+            Ok(Some(level)) => content = notes::writer::set_difficulty(&content, &level),
+            Ok(None) => {}
+            Err(e) => println!("{} {}", "Could not rate the note's difficulty:".warning(), e),
+        }
+    }
+
+    if notes::writer::difficulty(&content).is_none() {
+        let mut items: Vec<&str> = notes::writer::DIFFICULTY_LEVELS.to_vec();
+        items.push("Skip");
+
+        let selection = Select::with_theme(&output::prompts()?)
+            .with_prompt("Difficulty level for this note")
+            .items(&items)
+            .default(1)
+            .interact()?;
+
+        if let Some(level) = notes::writer::DIFFICULTY_LEVELS.get(selection) {
+            content = notes::writer::set_difficulty(&content, level);
+        }
+    }
+
+    Ok(content)
+}
+
+/// Ask the backend to supply alt text for any images that lack it
+fn fill_missing_alt_text(session: &Session, content: String) -> Result<String> {
+    let missing = markdown::images_missing_alt(&content);
+    if missing.is_empty() {
+        return Ok(content);
+    }
+
+    let spinner = create_spinner(&format!("Generating alt text for {} image(s)...", missing.len()));
+    let alt_texts = notes::generate_alt_text(session, &missing, |_| {});
+    spinner.finish_and_clear();
+
+    let mut content = content;
+    for (target, alt) in alt_texts? {
+        content = markdown::set_alt_text(&content, &target, &alt);
+    }
+
+    if markdown::images_missing_alt(&content).is_empty() {
+        println!("{} Added alt text to {} image(s)", theme::done(), missing.len());
+    }
+
+    Ok(content)
+}
+
+/// Suggest categories for a session without one, remembering the choice for similar topics
+fn choose_til_category(session: &Session, config: &Config) -> Result<String> {
+    let memory_path = Config::category_memory_path()?;
+    let mut memory = til::category::CategoryMemory::load(&memory_path)?;
+    let existing = if config.categories.is_empty() {
+        til::category::existing_categories(&config.archive_path())?
+    } else {
+        config.categories.clone()
+    };
+
+    let spinner = create_spinner("Suggesting categories...");
+    let suggested = til::category::suggest_categories(session, &existing);
+    spinner.finish_and_clear();
+
+    let mut options: Vec<String> = Vec::new();
+    if let Some(remembered) = memory.lookup(session.topic()) {
+        options.push(remembered.to_string());
+    }
+    match suggested {
+        Ok(suggested) => options.extend(suggested),
+        Err(e) => println!("{} {}", "Could not suggest categories:".warning(), e),
+    }
+    // A repo with a fixed category list only offers those, suggestions first
+    let fixed = !config.categories.is_empty();
+    if fixed {
+        options.retain(|option| config.categories.contains(option));
+        options.extend(config.categories.iter().cloned());
+    }
+    let mut seen = HashSet::new();
+    options.retain(|option| seen.insert(option.clone()));
+
+    let category = if options.is_empty() {
+        prompt_category_input()?
     } else {
-        println!("{}", "Note discarded.".yellow());
+        let mut items = options.clone();
+        if !fixed {
+            items.push("Other (type custom)".to_string());
+        }
+
+        let selection = Select::with_theme(&output::prompts()?)
+            .with_prompt("Category for this TIL")
+            .items(&items)
+            .default(0)
+            .interact()?;
+
+        match options.get(selection) {
+            Some(category) => category.clone(),
+            None => prompt_category_input()?,
+        }
+    };
+
+    if !config.dry_run {
+        memory.remember(session.topic(), &category);
+        memory.save(&memory_path)?;
     }
 
-    Ok(())
+    Ok(category)
 }
 
 fn prompt_category_input() -> Result<String> {
-    let input: String = Input::with_theme(&ColorfulTheme::default())
+    let input: String = Input::with_theme(&output::prompts()?)
         .with_prompt("Enter category for this TIL")
         .interact_text()?;
-    Ok(input.to_lowercase())
+    Ok(til::category::normalize(&input))
 }
@@ -0,0 +1,433 @@
+use regex::Regex;
+use std::sync::LazyLock;
+
+/// Average adult reading speed used for reading-time estimates
+const WORDS_PER_MINUTE: usize = 200;
+
+/// Characters that end a URL found in running text
+const URL_TERMINATORS: &[char] = &['<', '>', '"', '\'', '`', '|'];
+
+/// An alt attribute in an HTML tag, however it's quoted and spaced
+#[allow(clippy::expect_used)]
+static ALT_ATTR: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"(?i)\salt\s*=\s*("[^"]*"|'[^']*')"#).expect("valid alt attribute pattern"));
+
+/// An image reference found in markdown content
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImageRef {
+    pub alt: String,
+    pub target: String,
+    pub line: usize,
+}
+
+/// Every http(s) URL in a piece of text, in order, without trailing punctuation
+pub fn extract_urls(text: &str) -> Vec<String> {
+    let mut urls = Vec::new();
+    let mut rest = text;
+
+    while let Some(start) = find_scheme(rest) {
+        let candidate = &rest[start..];
+        let end = candidate
+            .find(|c: char| c.is_whitespace() || URL_TERMINATORS.contains(&c))
+            .unwrap_or(candidate.len());
+        let url = trim_url(&candidate[..end]);
+        if url.len() > url.find("://").map_or(0, |i| i + 3) {
+            urls.push(url.to_string());
+        }
+        rest = &candidate[end..];
+    }
+    urls
+}
+
+fn find_scheme(text: &str) -> Option<usize> {
+    [text.find("https://"), text.find("http://")]
+        .into_iter()
+        .flatten()
+        .min()
+}
+
+/// Drop sentence punctuation after a URL, and closing brackets it didn't open (e.g. a markdown link)
+fn trim_url(url: &str) -> &str {
+    let mut url = url;
+    loop {
+        let trimmed = url.trim_end_matches(['.', ',', ';', ':', '!', '?']);
+        let trimmed = match trimmed.chars().last() {
+            Some(close @ (')' | ']')) => {
+                let open = if close == ')' { '(' } else { '[' };
+                if trimmed.matches(open).count() < trimmed.matches(close).count() {
+                    &trimmed[..trimmed.len() - 1]
+                } else {
+                    trimmed
+                }
+            }
+            _ => trimmed,
+        };
+        if trimmed == url {
+            return url;
+        }
+        url = trimmed;
+    }
+}
+
+/// Find all image references (markdown `![alt](src)` and HTML `<img>` tags)
+pub fn find_images(content: &str) -> Vec<ImageRef> {
+    let mut images = Vec::new();
+
+    for (i, line) in content.lines().enumerate() {
+        images.extend(find_markdown_images(line, i + 1));
+        images.extend(find_html_images(line, i + 1));
+    }
+
+    images
+}
+
+/// Find image references that have no (or only whitespace) alt text
+pub fn images_missing_alt(content: &str) -> Vec<ImageRef> {
+    find_images(content)
+        .into_iter()
+        .filter(|img| img.alt.trim().is_empty())
+        .collect()
+}
+
+/// Fill in alt text for every image pointing at `target` that currently lacks it
+pub fn set_alt_text(content: &str, target: &str, alt: &str) -> String {
+    let alt = alt.replace(['[', ']'], "").replace('"', "'");
+    let mut result = content.to_string();
+
+    let empty_alts: Vec<String> = content
+        .lines()
+        .enumerate()
+        .flat_map(|(i, line)| find_markdown_images(line, i + 1))
+        .filter(|img| img.target == target && img.alt.trim().is_empty())
+        .map(|img| img.alt)
+        .collect();
+    for empty in empty_alts {
+        let markdown_empty = format!("![{}]({}", empty, target);
+        let markdown_filled = format!("![{}]({}", alt, target);
+        result = result.replace(&markdown_empty, &markdown_filled);
+    }
+
+    for tag in find_html_tags(&result) {
+        let src = html_attr(&tag, "src").unwrap_or_default();
+        let existing_alt = html_attr(&tag, "alt").unwrap_or_default();
+        if src == target && existing_alt.trim().is_empty() {
+            let filled = format!("alt=\"{}\"", alt);
+            let fixed = match ALT_ATTR.find(&tag) {
+                Some(existing) => tag.replacen(existing.as_str(), &format!(" {}", filled), 1),
+                None => tag.replacen("<img", &format!("<img {}", filled), 1),
+            };
+            result = result.replacen(&tag, &fixed, 1);
+        }
+    }
+
+    result
+}
+
+/// Estimated reading time in whole minutes (at least 1)
+pub fn reading_minutes(content: &str) -> u32 {
+    let words = content.split_whitespace().count();
+    words.div_ceil(WORDS_PER_MINUTE).max(1) as u32
+}
+
+/// Read a scalar field from YAML frontmatter
+pub fn frontmatter_field(content: &str, key: &str) -> Option<String> {
+    let (frontmatter, _) = split_frontmatter(content)?;
+    let prefix = format!("{}:", key);
+
+    frontmatter.lines().find_map(|line| {
+        let value = line.trim().strip_prefix(&prefix)?;
+        let value = value.trim().trim_matches('"').trim_matches('\'');
+        (!value.is_empty()).then(|| value.to_string())
+    })
+}
+
+/// Tags from frontmatter, written as `tags: [a, b]`, `tags: a, b` or a YAML list, lowercased
+pub fn tags(content: &str) -> Vec<String> {
+    let Some((frontmatter, _)) = split_frontmatter(content) else {
+        return Vec::new();
+    };
+    let mut lines = frontmatter.lines().skip_while(|line| !line.starts_with("tags:"));
+    let Some(inline) = lines.next().and_then(|line| line.strip_prefix("tags:")).map(str::trim) else {
+        return Vec::new();
+    };
+
+    let raw: Vec<&str> = if inline.is_empty() {
+        lines.map_while(|line| line.trim().strip_prefix('-')).collect()
+    } else {
+        inline.trim_matches(|c| c == '[' || c == ']').split(',').collect()
+    };
+    raw.into_iter()
+        .map(|tag| tag.trim().trim_matches(|c| c == '"' || c == '\'' || c == '#').to_lowercase())
+        .filter(|tag| !tag.is_empty())
+        .collect()
+}
+
+/// Merge `extra` into the frontmatter's tags, rewritten as `tags: [a, b]` where they were, creating
+/// the frontmatter if needed
+pub fn add_tags(content: &str, extra: &[String]) -> String {
+    let mut merged = tags(content);
+    let before = merged.len();
+    for tag in extra {
+        if !merged.contains(tag) {
+            merged.push(tag.clone());
+        }
+    }
+    if merged.len() == before {
+        return content.to_string();
+    }
+    let field = format!("tags: [{}]", merged.join(", "));
+
+    let Some((frontmatter, body)) = split_frontmatter(content) else {
+        return format!("---\n{}\n---\n\n{}", field, content);
+    };
+    let mut lines: Vec<&str> = Vec::new();
+    let mut in_tags = false;
+    for line in frontmatter.lines() {
+        if in_tags && line.trim_start().starts_with('-') {
+            continue;
+        }
+        in_tags = line.starts_with("tags:");
+        lines.push(if in_tags { &field } else { line });
+    }
+    if !lines.contains(&field.as_str()) {
+        lines.push(&field);
+    }
+
+    format!("---\n{}\n---{}", lines.join("\n"), body)
+}
+
+/// Set (or add) a scalar field in YAML frontmatter, creating the frontmatter if needed
+pub fn set_frontmatter_field(content: &str, key: &str, value: &str) -> String {
+    let field = format!("{}: {}", key, value);
+    let prefix = format!("{}:", key);
+
+    let Some((frontmatter, body)) = split_frontmatter(content) else {
+        return format!("---\n{}\n---\n\n{}", field, content);
+    };
+
+    let mut lines: Vec<String> = frontmatter.lines().map(|l| l.to_string()).collect();
+    match lines.iter().position(|l| l.trim_start().starts_with(&prefix)) {
+        Some(i) => lines[i] = field,
+        None => lines.push(field),
+    }
+
+    format!("---\n{}\n---{}", lines.join("\n"), body)
+}
+
+/// Split content into (frontmatter, rest after the closing `---`)
+pub fn split_frontmatter(content: &str) -> Option<(&str, &str)> {
+    let rest = content.strip_prefix("---\n")?;
+    let end = rest.find("\n---")?;
+    Some((&rest[..end], &rest[end + 4..]))
+}
+
+fn find_markdown_images(line: &str, line_no: usize) -> Vec<ImageRef> {
+    let mut images = Vec::new();
+    let mut rest = line;
+
+    while let Some(start) = rest.find("![") {
+        let after = &rest[start + 2..];
+        let Some(alt_end) = after.find("](") else {
+            break;
+        };
+        let target_part = &after[alt_end + 2..];
+        let Some(target_end) = target_part.find(')') else {
+            break;
+        };
+
+        let target = target_part[..target_end]
+            .split_whitespace()
+            .next()
+            .unwrap_or_default()
+            .to_string();
+
+        images.push(ImageRef {
+            alt: after[..alt_end].to_string(),
+            target,
+            line: line_no,
+        });
+
+        rest = &target_part[target_end + 1..];
+    }
+
+    images
+}
+
+fn find_html_images(line: &str, line_no: usize) -> Vec<ImageRef> {
+    find_html_tags(line)
+        .into_iter()
+        .map(|tag| ImageRef {
+            alt: html_attr(&tag, "alt").unwrap_or_default(),
+            target: html_attr(&tag, "src").unwrap_or_default(),
+            line: line_no,
+        })
+        .collect()
+}
+
+fn find_html_tags(content: &str) -> Vec<String> {
+    let mut tags = Vec::new();
+    let mut rest = content;
+
+    while let Some(start) = rest.find("<img") {
+        let after = &rest[start..];
+        let Some(end) = after.find('>') else {
+            break;
+        };
+        tags.push(after[..=end].to_string());
+        rest = &after[end + 1..];
+    }
+
+    tags
+}
+
+fn html_attr(tag: &str, name: &str) -> Option<String> {
+    for quote in ['"', '\''] {
+        let needle = format!(" {}={}", name, quote);
+        if let Some(start) = tag.find(&needle) {
+            let value = &tag[start + needle.len()..];
+            let end = value.find(quote)?;
+            return Some(value[..end].to_string());
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_urls() {
+        assert_eq!(
+            extract_urls("Read https://example.com/post, then (see http://a.org/x).\nhttps:// alone"),
+            vec!["https://example.com/post", "http://a.org/x"]
+        );
+        assert_eq!(
+            extract_urls("[Rust](https://en.wikipedia.org/wiki/Rust_(programming_language))"),
+            vec!["https://en.wikipedia.org/wiki/Rust_(programming_language)"]
+        );
+        assert_eq!(
+            extract_urls("<string>https://example.com/a?b=1</string>"),
+            vec!["https://example.com/a?b=1"]
+        );
+        assert!(extract_urls("no links here").is_empty());
+    }
+
+    #[test]
+    fn test_find_markdown_images() {
+        let content = "Intro\n![A diagram](img/flow.png) and ![](img/raw.png \"Title\")";
+        let images = find_images(content);
+
+        assert_eq!(images.len(), 2);
+        assert_eq!(images[0].alt, "A diagram");
+        assert_eq!(images[0].target, "img/flow.png");
+        assert_eq!(images[1].alt, "");
+        assert_eq!(images[1].target, "img/raw.png");
+        assert_eq!(images[1].line, 2);
+    }
+
+    #[test]
+    fn test_find_html_images() {
+        let content = r#"<img src="a.png" alt="Alpha"> <img src='b.png'>"#;
+        let images = find_images(content);
+
+        assert_eq!(images.len(), 2);
+        assert_eq!(images[0].alt, "Alpha");
+        assert_eq!(images[1].target, "b.png");
+        assert_eq!(images[1].alt, "");
+    }
+
+    #[test]
+    fn test_images_missing_alt() {
+        let content = "![ok](a.png)\n![ ](b.png)\n<img src=\"c.png\" alt=\"\">";
+        let missing: Vec<String> = images_missing_alt(content)
+            .into_iter()
+            .map(|img| img.target)
+            .collect();
+
+        assert_eq!(missing, vec!["b.png".to_string(), "c.png".to_string()]);
+    }
+
+    #[test]
+    fn test_set_alt_text_markdown() {
+        let content = "See ![](diagram.png) here.\n![ ](diagram.png)";
+        let fixed = set_alt_text(content, "diagram.png", "Ownership [diagram]");
+
+        assert_eq!(
+            fixed,
+            "See ![Ownership diagram](diagram.png) here.\n![Ownership diagram](diagram.png)"
+        );
+    }
+
+    #[test]
+    fn test_set_alt_text_html() {
+        let content = r#"<img src="c.png" alt="">"#;
+        let fixed = set_alt_text(content, "c.png", "Chart of \"results\"");
+
+        assert_eq!(fixed, r#"<img src="c.png" alt="Chart of 'results'">"#);
+        assert!(images_missing_alt(&fixed).is_empty());
+
+        let blank = set_alt_text(r#"<img data-alt="" src="c.png" ALT = ' '>"#, "c.png", "Chart");
+        assert_eq!(blank, r#"<img data-alt="" src="c.png" alt="Chart">"#);
+        assert_eq!(set_alt_text(r#"<img src="c.png">"#, "c.png", "Chart"), r#"<img alt="Chart" src="c.png">"#);
+    }
+
+    #[test]
+    fn test_reading_minutes() {
+        assert_eq!(reading_minutes(""), 1);
+        assert_eq!(reading_minutes(&"word ".repeat(200)), 1);
+        assert_eq!(reading_minutes(&"word ".repeat(201)), 2);
+    }
+
+    #[test]
+    fn test_tags() {
+        assert_eq!(tags("---\ntags: [Postgres, \"mvcc\"]\n---\n# Vacuum"), vec!["postgres", "mvcc"]);
+        assert_eq!(tags("---\ntags: git, rebase\n---\n"), vec!["git", "rebase"]);
+        assert_eq!(tags("---\ntitle: Locks\ntags:\n  - database\n  - '#postgres'\nlevel: advanced\n---\n"), vec!["database", "postgres"]);
+        assert!(tags("# No frontmatter").is_empty());
+    }
+
+    #[test]
+    fn test_add_tags() {
+        let extra = vec!["consensus".to_string(), "database".to_string()];
+        assert_eq!(
+            add_tags("---\ntitle: Raft\ntags:\n  - database\nlevel: advanced\n---\n# Raft\n", &extra),
+            "---\ntitle: Raft\ntags: [database, consensus]\nlevel: advanced\n---\n# Raft\n"
+        );
+        assert_eq!(add_tags("---\ndate: 2024-01-01\n---\n# Raft", &extra), "---\ndate: 2024-01-01\ntags: [consensus, database]\n---\n# Raft");
+        assert_eq!(add_tags("# Raft\n", &extra), "---\ntags: [consensus, database]\n---\n\n# Raft\n");
+        assert_eq!(add_tags("---\ntags: [database, consensus]\n---\n", &extra), "---\ntags: [database, consensus]\n---\n");
+    }
+
+    #[test]
+    fn test_frontmatter_field() {
+        let content = "---\ntitle: \"Note\"\nlevel: beginner\n---\n\n# Note";
+        assert_eq!(frontmatter_field(content, "title"), Some("Note".to_string()));
+        assert_eq!(frontmatter_field(content, "level"), Some("beginner".to_string()));
+        assert_eq!(frontmatter_field(content, "missing"), None);
+        assert_eq!(frontmatter_field("# No frontmatter", "title"), None);
+    }
+
+    #[test]
+    fn test_set_frontmatter_field_replaces_and_adds() {
+        let content = "---\ntitle: Note\nlevel: easy\n---\n\n# Note\n";
+        let updated = set_frontmatter_field(content, "level", "beginner");
+        let updated = set_frontmatter_field(&updated, "reading_time", "3 min");
+
+        assert_eq!(
+            updated,
+            "---\ntitle: Note\nlevel: beginner\nreading_time: 3 min\n---\n\n# Note\n"
+        );
+    }
+
+    #[test]
+    fn test_set_frontmatter_field_creates_frontmatter() {
+        let updated = set_frontmatter_field("# Note\n", "level", "advanced");
+        assert_eq!(updated, "---\nlevel: advanced\n---\n\n# Note\n");
+    }
+
+    #[test]
+    fn test_no_images() {
+        assert!(find_images("Plain text with [a link](https://example.com)").is_empty());
+    }
+}
@@ -0,0 +1,21 @@
+/// Build the prompt for a one-shot question, answered without a follow-up session
+pub fn build_ask_prompt(question: &str) -> String {
+    format!(
+        r#"{}
+
+Answer directly and concisely: lead with the answer, then only the explanation or example needed to use it. This is a one-off question with no follow-ups, so don't offer to go deeper or ask me anything back."#,
+        question.trim()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_ask_prompt() {
+        let prompt = build_ask_prompt("  How do I undo the last commit?\n");
+        assert!(prompt.starts_with("How do I undo the last commit?\n\nAnswer directly"));
+        assert!(prompt.contains("no follow-ups"));
+    }
+}
@@ -0,0 +1,34 @@
+use super::untrusted::{delimit, UNTRUSTED_NOTICE};
+
+/// Build the initial prompt for learning from a transcribed recording
+pub fn build_audio_prompt(name: &str, transcript: &str) -> String {
+    format!(
+        r#"Here is the transcript of a recording ({}), such as a conference talk or voice memo. It was transcribed automatically, so expect some misheard words. {}
+
+{}
+
+Provide:
+1. A brief summary of the main points
+2. Key technical concepts explained
+3. Practical takeaways or code examples if applicable
+4. Your assessment of what's most valuable to learn from this
+
+I'll ask follow-up questions about specific parts."#,
+        name,
+        UNTRUSTED_NOTICE,
+        delimit("transcript", transcript)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_audio_prompt() {
+        let prompt = build_audio_prompt("talk.mp3", "Today we cover lifetimes.");
+        assert!(prompt.contains("(talk.mp3)"));
+        assert!(prompt.contains("<transcript>\nToday we cover lifetimes.\n</transcript>"));
+        assert!(prompt.contains("Key technical concepts"));
+    }
+}
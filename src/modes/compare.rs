@@ -0,0 +1,77 @@
+use anyhow::{anyhow, Result};
+
+/// TIL skeleton for comparison sessions, used when the category has no template of its own
+pub const COMPARISON_TIL_TEMPLATE: &str = r#"# <A> vs <B>
+
+One or two sentences on the decision this helps you make.
+
+| | <A> | <B> |
+|---|---|---|
+| <dimension> | ... | ... |
+
+Use <A> when ... Use <B> when ...
+
+A short example showing the difference in practice.
+
+One line on migrating from one to the other, if it comes up."#;
+
+/// Split "a vs b" (or "a vs. b" / "a versus b") into its two sides
+pub fn parse_comparison(input: &str) -> Result<(String, String)> {
+    let words: Vec<&str> = input.split_whitespace().collect();
+    let split = words
+        .iter()
+        .position(|word| matches!(word.to_lowercase().as_str(), "vs" | "vs." | "versus"))
+        .ok_or_else(|| anyhow!("Use the form: <a> vs <b>"))?;
+
+    let left = words[..split].join(" ");
+    let right = words[split + 1..].join(" ");
+    if left.is_empty() || right.is_empty() {
+        return Err(anyhow!("Use the form: <a> vs <b>"));
+    }
+    Ok((left, right))
+}
+
+/// Build the initial prompt for a session comparing two technologies or approaches
+pub fn build_compare_prompt(left: &str, right: &str) -> String {
+    format!(
+        r#"I want to compare: {left} vs {right}
+
+Structure your answer as:
+1. What each one is, in a sentence or two
+2. A tradeoffs table comparing them across the dimensions that matter most (performance, ergonomics, ecosystem, operational cost, learning curve, ...)
+3. When to use which: concrete situations where {left} is the better choice, and where {right} is
+4. Side-by-side examples of the same task in each, with code where applicable
+5. Migration notes: what changes and what bites you when moving from one to the other
+
+Be opinionated where the evidence supports it. I'll ask follow-up questions about specific tradeoffs."#
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_comparison() -> Result<()> {
+        assert_eq!(
+            parse_comparison("tokio vs async-std")?,
+            ("tokio".to_string(), "async-std".to_string())
+        );
+        assert_eq!(
+            parse_comparison("REST APIs Versus gRPC services")?,
+            ("REST APIs".to_string(), "gRPC services".to_string())
+        );
+        assert!(parse_comparison("tokio and async-std").is_err());
+        assert!(parse_comparison("vs gRPC").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_build_compare_prompt() {
+        let prompt = build_compare_prompt("Postgres", "MySQL");
+        assert!(prompt.contains("I want to compare: Postgres vs MySQL"));
+        assert!(prompt.contains("tradeoffs table"));
+        assert!(prompt.contains("where Postgres is the better choice, and where MySQL is"));
+        assert!(prompt.contains("Migration notes"));
+    }
+}
@@ -1,3 +1,6 @@
+/// Most existing TIL titles listed in a deep dive's opening prompt
+pub const MAX_KNOWN_TITLES: usize = 50;
+
 /// Build the initial prompt for a deep dive learning session
 pub fn build_deep_dive_prompt(topic: &str) -> String {
     format!(
@@ -14,6 +17,32 @@ Be thorough but focused. I'll ask follow-up questions to go deeper on specific a
     )
 }
 
+/// Tell the backend which TILs already exist in the session's category, so it covers the gaps
+pub fn with_archive_context(prompt: String, category: &str, known_titles: &[String]) -> String {
+    if known_titles.is_empty() {
+        return prompt;
+    }
+
+    let titles: Vec<String> = known_titles.iter().map(|title| format!("- {}", title)).collect();
+    format!(
+        "{}\n\nI've already written up these {} TILs:\n{}\n\nSkip what they cover and focus on the gaps.",
+        prompt,
+        category,
+        titles.join("\n")
+    )
+}
+
+/// Pass along what an earlier session or TIL on the same topic covered, so the answer builds on it
+pub fn with_prior_context(prompt: String, prior: &str, context: &str) -> String {
+    format!(
+        "{}\n\nI've studied this before ({}). This is what we covered:\n\n{}\n\nBuild on it: skip the basics it \
+         already explains and go deeper from there.",
+        prompt,
+        prior,
+        context.trim()
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -24,6 +53,24 @@ mod tests {
         assert!(prompt.contains("Rust ownership"));
     }
 
+    #[test]
+    fn test_with_archive_context() {
+        let titles = vec!["Borrowing".to_string(), "Lifetimes".to_string()];
+        let prompt = with_archive_context("Teach me".to_string(), "rust", &titles);
+        assert_eq!(
+            prompt,
+            "Teach me\n\nI've already written up these rust TILs:\n- Borrowing\n- Lifetimes\n\nSkip what they cover and focus on the gaps."
+        );
+        assert_eq!(with_archive_context("Teach me".to_string(), "rust", &[]), "Teach me");
+    }
+
+    #[test]
+    fn test_with_prior_context() {
+        let prompt = with_prior_context("Teach me".to_string(), "the TIL \"Borrowing\"", "# Borrowing\n\nUse &.\n");
+        assert!(prompt.starts_with("Teach me\n\nI've studied this before (the TIL \"Borrowing\")"));
+        assert!(prompt.contains("# Borrowing\n\nUse &.\n\nBuild on it"));
+    }
+
     #[test]
     fn test_build_deep_dive_prompt_contains_sections() {
         let prompt = build_deep_dive_prompt("test");
@@ -0,0 +1,31 @@
+use super::untrusted::UNTRUSTED_NOTICE;
+
+/// Build the initial prompt for learning from an image the backend has been allowed to read
+pub fn build_image_prompt(path: &str) -> String {
+    format!(
+        r#"Read the image at {} and explain it. It may be a presentation slide, a diagram, or a screenshot of code or an error. {}
+
+Provide:
+1. What the image shows, including any text or code in it
+2. The key technical concepts it illustrates, explained
+3. For an error or a failing command: what it means, the likely cause, and how to fix it
+4. What's most valuable to learn from it
+
+I'll ask follow-up questions about specific parts."#,
+        path,
+        UNTRUSTED_NOTICE
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_image_prompt() {
+        let prompt = build_image_prompt("/home/me/slide.png");
+        assert!(prompt.starts_with("Read the image at /home/me/slide.png and explain it."));
+        assert!(prompt.contains(UNTRUSTED_NOTICE));
+        assert!(prompt.contains("the likely cause"));
+    }
+}
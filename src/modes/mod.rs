@@ -1,5 +1,24 @@
+mod ask;
+mod audio;
+mod compare;
 mod deep_dive;
+mod image;
 mod link;
+mod search;
+mod shell;
+mod untrusted;
+mod video;
 
-pub use deep_dive::build_deep_dive_prompt;
-pub use link::build_link_prompt;
+pub use ask::build_ask_prompt;
+pub use audio::build_audio_prompt;
+pub use compare::{build_compare_prompt, parse_comparison, COMPARISON_TIL_TEMPLATE};
+pub use deep_dive::{build_deep_dive_prompt, with_archive_context, with_prior_context, MAX_KNOWN_TITLES};
+pub use image::build_image_prompt;
+pub use link::{
+    build_file_prompt, build_link_prompt, build_pull_request_prompt, build_repo_prompt, link_target, GithubRepo,
+    LinkTarget,
+};
+pub use search::build_search_prompt;
+pub use shell::build_shell_recap_prompt;
+pub use untrusted::{delimit, UNTRUSTED_NOTICE};
+pub use video::build_video_prompt;
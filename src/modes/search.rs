@@ -0,0 +1,27 @@
+/// Build the prompt for an in-session web search
+pub fn build_search_prompt(query: &str) -> String {
+    format!(
+        r#"Search the web for: {}
+
+Use WebSearch to find current, authoritative sources, then:
+1. Summarize what the results say, relating it to what we've discussed so far
+2. Call out anything that is new, changed recently, or contradicts earlier explanations
+3. List the sources you used as markdown links
+
+Keep it focused on what's relevant to this session."#,
+        query
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_search_prompt_contains_query() {
+        let prompt = build_search_prompt("tokio 1.40 release notes");
+        assert!(prompt.contains("Search the web for: tokio 1.40 release notes"));
+        assert!(prompt.contains("WebSearch"));
+        assert!(prompt.contains("sources"));
+    }
+}
@@ -1,28 +1,37 @@
-use crate::claude::{continue_conversation, run_claude_command};
+use crate::claude::{no_tools_settings, send, CancellationToken, ClaudeResponse};
+use crate::language;
+use crate::markdown::ImageRef;
+use crate::notes::writer::DIFFICULTY_LEVELS;
 use crate::session::Session;
 use anyhow::Result;
 
 /// Generate a comprehensive note from the current session using the /note skill
-pub fn generate_note<F>(session: &Session, on_text: F) -> Result<String>
+pub fn generate_note<F>(session: &Session, language: Option<&str>, on_text: F) -> Result<ClaudeResponse>
 where
     F: FnMut(&str),
 {
-    let prompt = build_generation_prompt(session);
-
-    // If we have an existing session, continue it to maintain context
-    if let Some(ref session_id) = session.claude_session_id {
-        continue_conversation(session_id, &prompt, on_text)
-    } else {
-        // Start fresh with full context
-        let (response, _) = run_claude_command(&prompt, on_text)?;
-        Ok(response)
-    }
+    let prompt = build_generation_prompt(session, language);
+    send_for_session(session, &prompt, on_text)
+}
+
+/// Continue the session's conversation (or start fresh) without tools
+fn send_for_session<F>(session: &Session, prompt: &str, on_text: F) -> Result<ClaudeResponse>
+where
+    F: FnMut(&str),
+{
+    send(
+        session.resume(),
+        prompt,
+        Some(&no_tools_settings()),
+        on_text,
+        CancellationToken::new(),
+    )
 }
 
-fn build_generation_prompt(session: &Session) -> String {
+fn build_generation_prompt(session: &Session, language: Option<&str>) -> String {
     let context = session.build_til_context();
 
-    format!(
+    let mut prompt = format!(
         r#"Based on our learning session, generate a comprehensive knowledge base note.
 
 {}
@@ -30,11 +39,169 @@ fn build_generation_prompt(session: &Session) -> String {
 Use /note to generate the markdown content. The note should be thorough and detailed - this is for a personal knowledge base, not a quick reference.
 
 Include:
-- YAML frontmatter with title, date, tags, and aliases
+- YAML frontmatter with title, date, tags, aliases, and level (beginner, intermediate, or advanced)
 - Detailed explanations of concepts
 - Code examples with annotations
 - Key insights from our Q&A
 - Related topics as wiki-links"#,
         context
+    );
+
+    if let Some(ref outline) = session.outline {
+        prompt.push_str("\n\n");
+        prompt.push_str(&outline.prompt_section());
+    }
+
+    if let Some(language) = language.filter(|language| !language.trim().is_empty()) {
+        prompt.push_str("\n\n");
+        prompt.push_str(&language::instruction(language));
+    }
+    prompt
+}
+
+/// Ask the backend to rate a note that came back without a `level`. Returns the level, or `None`
+/// if the reply isn't one of the known levels.
+pub fn rate_difficulty<F>(session: &Session, note: &str, on_text: F) -> Result<Option<String>>
+where
+    F: FnMut(&str),
+{
+    let prompt = build_difficulty_prompt(note);
+    let response = send_for_session(session, &prompt, on_text)?;
+    Ok(parse_difficulty(&response.text))
+}
+
+fn build_difficulty_prompt(note: &str) -> String {
+    format!(
+        r#"Rate how hard this note is for someone re-reading it later:
+
+{}
+
+Reply with exactly one word: {}."#,
+        note,
+        DIFFICULTY_LEVELS.join(", ")
+    )
+}
+
+fn parse_difficulty(reply: &str) -> Option<String> {
+    let word = reply
+        .split(|c: char| !c.is_alphabetic())
+        .find(|word| !word.is_empty())?
+        .to_lowercase();
+    DIFFICULTY_LEVELS.contains(&word.as_str()).then_some(word)
+}
+
+/// Ask the backend for alt text for images that are missing it.
+/// Returns (image target, alt text) pairs.
+pub fn generate_alt_text<F>(
+    session: &Session,
+    images: &[ImageRef],
+    on_text: F,
+) -> Result<Vec<(String, String)>>
+where
+    F: FnMut(&str),
+{
+    let prompt = build_alt_text_prompt(images);
+
+    let response = send_for_session(session, &prompt, on_text)?;
+
+    Ok(parse_alt_text_response(&response.text))
+}
+
+fn build_alt_text_prompt(images: &[ImageRef]) -> String {
+    let list: Vec<String> = images.iter().map(|img| format!("- {}", img.target)).collect();
+
+    format!(
+        r#"The note you generated contains images without alt text:
+
+{}
+
+Write concise, descriptive alt text (under 125 characters) for each image based on what it shows in the context of our session.
+
+Return ONLY one line per image in the form:
+<image path> :: <alt text>"#,
+        list.join("\n")
     )
 }
+
+fn parse_alt_text_response(response: &str) -> Vec<(String, String)> {
+    response
+        .lines()
+        .filter_map(|line| {
+            let line = line.trim().trim_start_matches("- ");
+            let (target, alt) = line.split_once("::")?;
+            let (target, alt) = (target.trim().trim_matches('`'), alt.trim());
+            if target.is_empty() || alt.is_empty() {
+                None
+            } else {
+                Some((target.to_string(), alt.to_string()))
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_generation_prompt_language() {
+        let session = Session::new(
+            crate::session::LearningMode::DeepDive {
+                topic: "rust lifetimes".to_string(),
+            },
+            None,
+        );
+        assert!(build_generation_prompt(&session, Some("German")).ends_with(&language::instruction("German")));
+        assert!(!build_generation_prompt(&session, None).contains("Write it in"));
+    }
+
+    #[test]
+    fn test_build_generation_prompt_follows_outline() {
+        let mut session = Session::new(
+            crate::session::LearningMode::DeepDive {
+                topic: "postgres vacuum".to_string(),
+            },
+            None,
+        );
+        assert!(!build_generation_prompt(&session, None).contains("<outline>"));
+
+        session.outline = Some(crate::notes::outline::Outline::parse("## Overview\n- Dead tuples\n"));
+        assert!(build_generation_prompt(&session, None).contains("<outline>\n## Overview\n- Dead tuples\n</outline>"));
+    }
+
+    #[test]
+    fn test_build_alt_text_prompt_lists_images() {
+        let images = vec![ImageRef {
+            alt: String::new(),
+            target: "img/flow.png".to_string(),
+            line: 3,
+        }];
+        let prompt = build_alt_text_prompt(&images);
+
+        assert!(prompt.contains("- img/flow.png"));
+        assert!(prompt.contains("<image path> :: <alt text>"));
+    }
+
+    #[test]
+    fn test_parse_difficulty() {
+        assert_eq!(parse_difficulty("Intermediate."), Some("intermediate".to_string()));
+        assert_eq!(parse_difficulty("**advanced**"), Some("advanced".to_string()));
+        assert_eq!(parse_difficulty("Hard to say"), None);
+        assert_eq!(parse_difficulty(""), None);
+        assert!(build_difficulty_prompt("# Note").ends_with("beginner, intermediate, advanced."));
+    }
+
+    #[test]
+    fn test_parse_alt_text_response() {
+        let response = "img/flow.png :: Diagram of ownership transfer\n\nnoise\n- `b.png` :: Chart";
+        let parsed = parse_alt_text_response(response);
+
+        assert_eq!(
+            parsed,
+            vec![
+                ("img/flow.png".to_string(), "Diagram of ownership transfer".to_string()),
+                ("b.png".to_string(), "Chart".to_string()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,165 @@
+use crate::{files, markdown};
+use anyhow::{Context, Result};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// The index file, at the root of the notes repo
+pub const INDEX_FILE: &str = "notes.json";
+
+/// Every note holocron saved to the notes repo, so tooling can query them without reading the vault
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct NotesIndex {
+    pub notes: Vec<IndexedNote>,
+}
+
+/// One saved note's metadata
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IndexedNote {
+    /// Relative to the notes repo, with forward slashes
+    pub path: String,
+    pub title: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    pub date: NaiveDate,
+    /// What the session that produced it studied
+    pub topic: String,
+    /// The session's file in holocron's session history
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session: Option<String>,
+    /// The TIL saved from the same session, if there was one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub til: Option<PathBuf>,
+    /// Estimated reading time; missing for notes indexed before it was recorded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reading_minutes: Option<u32>,
+    /// Difficulty level from the `level` frontmatter field
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub level: Option<String>,
+}
+
+impl IndexedNote {
+    /// Metadata for the note at `path` (inside `notes_path`), with title, tags, reading time and
+    /// difficulty read from its markdown
+    pub fn new(notes_path: &Path, path: &Path, content: &str, date: NaiveDate, topic: &str) -> Self {
+        let relative = path.strip_prefix(notes_path).unwrap_or(path);
+        Self {
+            path: relative.to_string_lossy().replace('\\', "/"),
+            title: super::writer::extract_title(content).unwrap_or_else(|| "Untitled Note".to_string()),
+            tags: markdown::tags(content),
+            date,
+            topic: topic.to_string(),
+            session: None,
+            til: None,
+            reading_minutes: Some(markdown::reading_minutes(content)),
+            level: super::writer::difficulty(content),
+        }
+    }
+
+    /// Whether every word of `query` appears in its title, tags or topic, ignoring case
+    pub fn matches(&self, query: &str) -> bool {
+        let haystack = format!("{} {} {}", self.title, self.tags.join(" "), self.topic).to_lowercase();
+        let mut words = query.split_whitespace().peekable();
+        words.peek().is_some() && words.all(|word| haystack.contains(&word.to_lowercase()))
+    }
+
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t.eq_ignore_ascii_case(tag))
+    }
+}
+
+impl NotesIndex {
+    /// The index in `notes_path`, or an empty one if no note has been indexed yet
+    pub fn load(notes_path: &Path) -> Result<Self> {
+        let path = notes_path.join(INDEX_FILE);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = fs::read_to_string(&path).with_context(|| format!("Failed to read {:?}", path))?;
+        serde_json::from_str(&content).with_context(|| format!("Failed to parse {:?}", path))
+    }
+
+    pub fn save(&self, notes_path: &Path) -> Result<()> {
+        let path = notes_path.join(INDEX_FILE);
+        let content = serde_json::to_string_pretty(self).context("Failed to serialize the notes index")?;
+        files::write_atomic(&path, content + "\n").with_context(|| format!("Failed to write {:?}", path))
+    }
+
+    /// Record `note` in the index in `notes_path`, holding a lock so concurrent saves don't drop entries
+    pub fn add(notes_path: &Path, note: IndexedNote) -> Result<()> {
+        let _lock = files::lock(&notes_path.join(INDEX_FILE)).context("Failed to lock the notes index")?;
+        let mut index = Self::load(notes_path)?;
+        index.upsert(note);
+        index.save(notes_path)
+    }
+
+    /// Add a note, replacing the entry for the same path if it was saved before
+    pub fn upsert(&mut self, note: IndexedNote) {
+        self.notes.retain(|existing| existing.path != note.path);
+        self.notes.push(note);
+    }
+
+    /// Notes newest first, dropping entries whose file has since been deleted
+    pub fn existing(&self, notes_path: &Path) -> Vec<&IndexedNote> {
+        let mut notes: Vec<&IndexedNote> =
+            self.notes.iter().filter(|note| notes_path.join(&note.path).exists()).collect();
+        notes.sort_by(|a, b| b.date.cmp(&a.date).then_with(|| a.title.cmp(&b.title)));
+        notes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn date(day: u32) -> Result<NaiveDate> {
+        NaiveDate::from_ymd_opt(2024, 6, day).context("invalid date")
+    }
+
+    #[test]
+    fn test_indexed_note_reads_title_and_tags() -> Result<()> {
+        let content = "---\ntags: [Postgres, mvcc]\n---\n# Vacuum Internals\n\nDead tuples.";
+        let note = IndexedNote::new(Path::new("/notes"), Path::new("/notes/db/vacuum.md"), content, date(1)?, "vacuum");
+
+        assert_eq!(note.path, "db/vacuum.md");
+        assert_eq!(note.title, "Vacuum Internals");
+        assert_eq!(note.tags, vec!["postgres", "mvcc"]);
+        assert!(note.matches("postgres VACUUM"));
+        assert!(!note.matches("postgres locks"));
+        assert!(!note.matches("  "));
+        assert!(note.has_tag("MVCC"));
+        assert_eq!(note.reading_minutes, Some(1));
+        assert_eq!(note.level, None);
+
+        let content = "---\nlevel: Advanced\n---\n# A";
+        let leveled = IndexedNote::new(Path::new("/n"), Path::new("/n/a.md"), content, date(1)?, "a");
+        assert_eq!(leveled.level.as_deref(), Some("advanced"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_index_roundtrips_and_replaces_by_path() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let notes_path = temp_dir.path();
+        fs::write(notes_path.join("a.md"), "# A")?;
+        fs::write(notes_path.join("b.md"), "# B")?;
+
+        let mut index = NotesIndex::load(notes_path)?;
+        index.upsert(IndexedNote::new(notes_path, &notes_path.join("a.md"), "# A", date(1)?, "a"));
+        index.upsert(IndexedNote::new(notes_path, &notes_path.join("b.md"), "# B", date(2)?, "b"));
+        index.upsert(IndexedNote::new(notes_path, &notes_path.join("gone.md"), "# Gone", date(3)?, "c"));
+        let mut again = IndexedNote::new(notes_path, &notes_path.join("a.md"), "# A again", date(3)?, "a");
+        again.til = Some(PathBuf::from("/til/archive/a.md"));
+        index.upsert(again);
+        index.save(notes_path)?;
+
+        let index = NotesIndex::load(notes_path)?;
+        let titles: Vec<&str> = index.existing(notes_path).iter().map(|note| note.title.as_str()).collect();
+        assert_eq!(index.notes.len(), 3);
+        assert_eq!(titles, vec!["A again", "B"]);
+        assert_eq!(index.existing(notes_path)[0].til, Some(PathBuf::from("/til/archive/a.md")));
+        Ok(())
+    }
+}
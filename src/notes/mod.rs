@@ -1,5 +1,9 @@
 mod generator;
+pub mod index;
+pub mod org;
+pub mod outline;
+pub mod session_log;
 pub mod writer;
 
-pub use generator::generate_note;
+pub use generator::{generate_alt_text, generate_note, rate_difficulty};
 pub use writer::write_note;
@@ -0,0 +1,369 @@
+use crate::markdown::split_frontmatter;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+
+/// Convert a generated markdown note into an org-roam file: an `:ID:` property drawer,
+/// `#+TITLE:`/`#+FILETAGS:` keywords instead of YAML frontmatter, `*` headings and source blocks
+pub fn to_org(markdown: &str, id: &str) -> String {
+    let (frontmatter, body) = match split_frontmatter(markdown) {
+        Some((frontmatter, body)) => (parse_frontmatter(frontmatter), body),
+        None => (Vec::new(), markdown),
+    };
+    let field = |key: &str| frontmatter.iter().find(|(k, _)| k == key).map(|(_, v)| v);
+
+    let h1 = body.lines().find_map(|line| line.trim().strip_prefix("# ").map(str::trim));
+    let title = field("title").and_then(|v| v.first().cloned()).or_else(|| h1.map(str::to_string));
+
+    let mut org = format!(":PROPERTIES:\n:ID:       {}\n", id);
+    if let Some(aliases) = field("aliases").filter(|a| !a.is_empty()) {
+        let quoted: Vec<String> = aliases.iter().map(|a| format!("\"{}\"", a)).collect();
+        org.push_str(&format!(":ROAM_ALIASES: {}\n", quoted.join(" ")));
+    }
+    org.push_str(":END:\n");
+    if let Some(ref title) = title {
+        org.push_str(&format!("#+TITLE: {}\n", title));
+    }
+    if let Some(tags) = field("tags").filter(|t| !t.is_empty()) {
+        let tags: Vec<String> = tags.iter().map(|t| t.replace([' ', '-'], "_")).collect();
+        org.push_str(&format!("#+FILETAGS: :{}:\n", tags.join(":")));
+    }
+    for (key, values) in &frontmatter {
+        if !matches!(key.as_str(), "title" | "tags" | "aliases") {
+            org.push_str(&format!("#+{}: {}\n", key.to_uppercase(), values.join(" ")));
+        }
+    }
+
+    org.push('\n');
+    org.push_str(convert_body(body, h1).trim());
+    org.push('\n');
+    org
+}
+
+/// Set (or add) a `#+KEY:` keyword in the file's header
+pub fn set_keyword(content: &str, key: &str, value: &str) -> String {
+    let prefix = format!("#+{}:", key.to_uppercase());
+    let line = format!("{} {}", prefix, value);
+    let mut lines: Vec<String> = content.lines().map(|l| l.to_string()).collect();
+
+    if let Some(existing) = lines.iter_mut().find(|l| l.starts_with(&prefix)) {
+        *existing = line;
+    } else {
+        let header_end = lines
+            .iter()
+            .position(|l| !(l.starts_with(':') || l.starts_with("#+")))
+            .unwrap_or(lines.len());
+        lines.insert(header_end, line);
+    }
+
+    format!("{}\n", lines.join("\n"))
+}
+
+/// The value of a `#+KEY:` keyword, e.g. the note's title
+pub fn keyword(content: &str, key: &str) -> Option<String> {
+    let prefix = format!("#+{}:", key.to_uppercase());
+    content
+        .lines()
+        .find_map(|line| line.strip_prefix(&prefix))
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+/// `title.md` becomes `title.org`
+pub fn org_filename(filename: &str) -> String {
+    format!("{}.org", filename.strip_suffix(".md").unwrap_or(filename))
+}
+
+/// A fresh org-roam node id: a version 4 UUID from the process's random hasher keys
+pub fn new_id() -> String {
+    let random = || {
+        let mut hasher = RandomState::new().build_hasher();
+        hasher.write_i64(chrono::Local::now().timestamp_nanos_opt().unwrap_or_default());
+        u128::from(hasher.finish())
+    };
+    format_uuid((random() << 64) | random())
+}
+
+fn format_uuid(bits: u128) -> String {
+    let bits = (bits & !(0xf << 76)) | (0x4 << 76);
+    let bits = (bits & !(0x3 << 62)) | (0x2 << 62);
+    let hex = format!("{:032x}", bits);
+    format!("{}-{}-{}-{}-{}", &hex[..8], &hex[8..12], &hex[12..16], &hex[16..20], &hex[20..])
+}
+
+/// Frontmatter as (key, values); `[a, b]` and `- a` lists become several values
+fn parse_frontmatter(frontmatter: &str) -> Vec<(String, Vec<String>)> {
+    let mut fields: Vec<(String, Vec<String>)> = Vec::new();
+
+    for line in frontmatter.lines() {
+        let trimmed = line.trim();
+        if let Some(item) = trimmed.strip_prefix("- ") {
+            if let Some((_, values)) = fields.last_mut() {
+                values.push(unquote(item));
+            }
+            continue;
+        }
+        let Some((key, value)) = trimmed.split_once(':') else {
+            continue;
+        };
+
+        let value = value.trim();
+        let values = match value.strip_prefix('[').and_then(|v| v.strip_suffix(']')) {
+            Some(list) => list.split(',').map(unquote).filter(|v| !v.is_empty()).collect(),
+            None if value.is_empty() => Vec::new(),
+            None => vec![unquote(value)],
+        };
+        fields.push((key.trim().to_lowercase(), values));
+    }
+
+    fields
+}
+
+fn unquote(value: &str) -> String {
+    value.trim().trim_matches('"').trim_matches('\'').to_string()
+}
+
+/// Convert the markdown body line by line. The H1 matching the title is dropped and the
+/// remaining headings are shifted so the shallowest becomes `*`.
+fn convert_body(body: &str, h1: Option<&str>) -> String {
+    let mut lines: Vec<&str> = body.lines().collect();
+    if let Some(index) = h1.and_then(|_| lines.iter().position(|l| l.trim().starts_with("# "))) {
+        lines.remove(index);
+    }
+    let shallowest = lines.iter().filter_map(|l| heading_level(l)).min().unwrap_or(1);
+
+    let mut org = Vec::new();
+    let mut in_code: Option<&str> = None;
+    let mut in_quote = false;
+
+    for line in lines {
+        let trimmed = line.trim_start();
+
+        if let Some(end) = in_code {
+            if trimmed.starts_with("```") {
+                org.push(end.to_string());
+                in_code = None;
+            } else if trimmed.starts_with('*') || trimmed.starts_with("#+") {
+                // Org's escape for lines that would otherwise read as headings or keywords
+                org.push(format!(",{}", line));
+            } else {
+                org.push(line.to_string());
+            }
+            continue;
+        }
+
+        let quoted = trimmed.strip_prefix('>').map(str::trim_start);
+        if in_quote && quoted.is_none() {
+            org.push("#+END_QUOTE".to_string());
+            in_quote = false;
+        }
+
+        if let Some(lang) = trimmed.strip_prefix("```") {
+            let lang = lang.trim();
+            if lang.is_empty() {
+                org.push("#+BEGIN_EXAMPLE".to_string());
+                in_code = Some("#+END_EXAMPLE");
+            } else {
+                org.push(format!("#+BEGIN_SRC {}", lang));
+                in_code = Some("#+END_SRC");
+            }
+        } else if let Some(quoted) = quoted {
+            if !in_quote {
+                org.push("#+BEGIN_QUOTE".to_string());
+                in_quote = true;
+            }
+            org.push(inline(quoted));
+        } else if let Some(level) = heading_level(line) {
+            let text = trimmed.trim_start_matches('#').trim();
+            org.push(format!("{} {}", "*".repeat(level + 1 - shallowest), inline(text)));
+        } else if matches!(trimmed, "---" | "***" | "___") {
+            org.push("-----".to_string());
+        } else if is_table_separator(trimmed) {
+            let cells = trimmed.trim_matches('|').split('|').count();
+            org.push(format!("|{}|", vec!["---"; cells].join("+")));
+        } else {
+            let indent = &line[..line.len() - trimmed.len()];
+            let bullet = trimmed.strip_prefix("* ").or_else(|| trimmed.strip_prefix("+ "));
+            match bullet {
+                Some(item) => org.push(format!("{}- {}", indent, inline(item))),
+                None => org.push(format!("{}{}", indent, inline(trimmed))),
+            }
+        }
+    }
+
+    if let Some(end) = in_code {
+        org.push(end.to_string());
+    }
+    if in_quote {
+        org.push("#+END_QUOTE".to_string());
+    }
+
+    org.join("\n")
+}
+
+fn heading_level(line: &str) -> Option<usize> {
+    let hashes = line.chars().take_while(|c| *c == '#').count();
+    (hashes > 0 && line[hashes..].starts_with(' ')).then_some(hashes)
+}
+
+fn is_table_separator(line: &str) -> bool {
+    line.starts_with('|') && line.contains('-') && line.chars().all(|c| matches!(c, '|' | '-' | ':' | ' '))
+}
+
+/// Inline markup: `code` becomes ~code~, **bold** becomes *bold*, and links become [[target][text]]
+fn inline(text: &str) -> String {
+    if text.matches('`').count() % 2 == 1 {
+        return links(&text.replace("**", "*"));
+    }
+
+    text.split('`')
+        .enumerate()
+        .map(|(i, part)| {
+            if i % 2 == 1 {
+                format!("~{}~", part)
+            } else {
+                links(&part.replace("**", "*"))
+            }
+        })
+        .collect()
+}
+
+fn links(text: &str) -> String {
+    let mut org = String::new();
+    let mut rest = text;
+
+    while let Some(start) = rest.find('[') {
+        let after = &rest[start + 1..];
+        let link = after.find(']').and_then(|label_end| {
+            let target = after[label_end + 1..].strip_prefix('(')?;
+            let target_end = target.find(')')?;
+            Some((&after[..label_end], &target[..target_end], label_end + 1 + target_end + 2))
+        });
+
+        let Some((label, target, len)) = link.filter(|(label, _, _)| !label.contains('[')) else {
+            org.push_str(&rest[..=start]);
+            rest = after;
+            continue;
+        };
+
+        let image = rest[..start].ends_with('!');
+        org.push_str(&rest[..start - usize::from(image)]);
+        let target = if target.contains("://") || target.starts_with("mailto:") {
+            target.to_string()
+        } else {
+            format!("file:{}", target)
+        };
+        if image || label.is_empty() {
+            org.push_str(&format!("[[{}]]", target));
+        } else {
+            org.push_str(&format!("[[{}][{}]]", target, label));
+        }
+        rest = &after[len..];
+    }
+
+    org.push_str(rest);
+    org
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOTE: &str = r#"---
+title: "Rust Ownership"
+tags: [rust, memory-safety]
+aliases:
+  - Ownership
+level: beginner
+---
+
+# Rust Ownership
+
+Each value has **one owner**; see [the book](https://doc.rust-lang.org/book/) and [[Borrowing]].
+
+## Moves
+
+* Assigning moves `String` values
+
+```rust
+let s = String::from("hi");
+```
+
+> Borrow, don't clone.
+
+| Kind | Copy |
+|------|:----:|
+| i32 | yes |
+"#;
+
+    #[test]
+    fn test_to_org() {
+        let org = to_org(NOTE, "abc");
+        assert_eq!(
+            org,
+            r#":PROPERTIES:
+:ID:       abc
+:ROAM_ALIASES: "Ownership"
+:END:
+#+TITLE: Rust Ownership
+#+FILETAGS: :rust:memory_safety:
+#+LEVEL: beginner
+
+Each value has *one owner*; see [[https://doc.rust-lang.org/book/][the book]] and [[Borrowing]].
+
+* Moves
+
+- Assigning moves ~String~ values
+
+#+BEGIN_SRC rust
+let s = String::from("hi");
+#+END_SRC
+
+#+BEGIN_QUOTE
+Borrow, don't clone.
+#+END_QUOTE
+
+| Kind | Copy |
+|---+---|
+| i32 | yes |
+"#
+        );
+    }
+
+    #[test]
+    fn test_code_lines_are_escaped() {
+        let org = to_org("# T\n\n```\n* not a heading\n```\n", "id");
+        assert!(org.contains("#+BEGIN_EXAMPLE\n,* not a heading\n#+END_EXAMPLE"));
+    }
+
+    #[test]
+    fn test_links() {
+        assert_eq!(links("![diagram](img/flow.png)"), "[[file:img/flow.png]]");
+        assert_eq!(links("see [til](../til/rebase.md)"), "see [[file:../til/rebase.md][til]]");
+        assert_eq!(links("[[Wiki Link]] and [not a link]"), "[[Wiki Link]] and [not a link]");
+    }
+
+    #[test]
+    fn test_set_keyword() {
+        let org = to_org("# T\n\nBody", "id");
+        let linked = set_keyword(&org, "quick_reference", "../til/t.md");
+        assert!(linked.contains("#+TITLE: T\n#+QUICK_REFERENCE: ../til/t.md\n\nBody"));
+        assert_eq!(keyword(&linked, "quick_reference").as_deref(), Some("../til/t.md"));
+        let relinked = set_keyword(&linked, "QUICK_REFERENCE", "../til/u.md");
+        assert_eq!(relinked.matches("QUICK_REFERENCE").count(), 1);
+    }
+
+    #[test]
+    fn test_new_id_is_a_v4_uuid() {
+        let id = new_id();
+        assert_eq!(id.len(), 36);
+        assert_eq!(&id[14..15], "4");
+        assert!(matches!(&id[19..20], "8" | "9" | "a" | "b"));
+        assert_ne!(id, new_id());
+        assert_eq!(format_uuid(0), "00000000-0000-4000-8000-000000000000");
+    }
+
+    #[test]
+    fn test_org_filename() {
+        assert_eq!(org_filename("rust_ownership.md"), "rust_ownership.org");
+    }
+}
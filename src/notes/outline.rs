@@ -0,0 +1,125 @@
+use crate::claude::{no_tools_settings, send, CancellationToken, ClaudeResponse};
+use crate::session::Session;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+/// One section of a note outline: its heading and the points it should make
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Section {
+    pub title: String,
+    #[serde(default)]
+    pub points: Vec<String>,
+}
+
+/// The structure agreed with /outline, which the next /note follows
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Outline {
+    pub sections: Vec<Section>,
+}
+
+impl Outline {
+    /// Read an outline written as `## Section` headings with `- point` bullets under them.
+    /// Bullets before the first heading are dropped.
+    pub fn parse(text: &str) -> Self {
+        let mut sections: Vec<Section> = Vec::new();
+        for line in text.lines().map(str::trim) {
+            if let Some(title) = line.strip_prefix("## ") {
+                sections.push(Section {
+                    title: title.trim().to_string(),
+                    points: Vec::new(),
+                });
+            } else if let Some(point) = line.strip_prefix("- ").or_else(|| line.strip_prefix("* ")) {
+                if let Some(section) = sections.last_mut() {
+                    section.points.push(point.trim().to_string());
+                }
+            }
+        }
+        sections.retain(|section| !section.title.is_empty());
+        Self { sections }
+    }
+
+    /// The outline in the form `parse` reads, for showing and editing
+    pub fn render(&self) -> String {
+        self.sections
+            .iter()
+            .map(|section| {
+                let mut text = format!("## {}\n", section.title);
+                for point in &section.points {
+                    text.push_str(&format!("- {}\n", point));
+                }
+                text
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sections.is_empty()
+    }
+
+    /// The part of the note generation prompt that asks for this structure
+    pub fn prompt_section(&self) -> String {
+        format!(
+            r#"We agreed on this outline for the note. Use these sections as its headings, in this order, and cover each point listed under them. Don't add or drop sections:
+
+<outline>
+{}
+</outline>"#,
+            self.render().trim_end()
+        )
+    }
+}
+
+/// Have the backend propose an outline for the session's note, in the session's conversation
+pub fn propose<F>(session: &Session, on_text: F) -> Result<ClaudeResponse>
+where
+    F: FnMut(&str),
+{
+    send(
+        session.resume(),
+        &build_outline_prompt(&session.build_til_context()),
+        Some(&no_tools_settings()),
+        on_text,
+        CancellationToken::new(),
+    )
+}
+
+fn build_outline_prompt(context: &str) -> String {
+    format!(
+        r###"Before writing a knowledge base note from our learning session, propose an outline for it.
+
+{}
+
+Reply with only the outline: one "## " heading per section, in reading order, each followed by "- " bullets for the key points that section should make. Aim for 4 to 8 sections. Don't write the note itself."###,
+        context
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_render_outline() {
+        let reply = "Here's an outline:\n- stray\n\n## Overview\n- What VACUUM does\n* Why it exists\n\n## Tuning\n\
+                     ##  \n- autovacuum thresholds\n";
+        let outline = Outline::parse(reply);
+
+        assert_eq!(outline.sections.len(), 2);
+        assert_eq!(outline.sections[0].points, vec!["What VACUUM does", "Why it exists"]);
+        assert_eq!(
+            outline.render(),
+            "## Overview\n- What VACUUM does\n- Why it exists\n\n## Tuning\n- autovacuum thresholds\n"
+        );
+        assert_eq!(Outline::parse(&outline.render()), outline);
+        assert!(Outline::parse("No headings here").is_empty());
+    }
+
+    #[test]
+    fn test_prompt_section() {
+        let outline = Outline::parse("## Overview\n- What VACUUM does\n");
+        let section = outline.prompt_section();
+        assert!(section.contains("<outline>\n## Overview\n- What VACUUM does\n</outline>"));
+        assert!(build_outline_prompt("Q: vacuum?").contains("Q: vacuum?"));
+    }
+}
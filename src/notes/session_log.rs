@@ -0,0 +1,189 @@
+use crate::config::NotesFormat;
+use crate::{files, language};
+use crate::notes::org;
+use crate::session::{self, LearningMode, Session};
+use crate::til::writer::title_to_filename;
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Vault folder that autosaved session logs are written to
+pub const SESSIONS_FOLDER: &str = "sessions";
+
+/// Where the log note for this session goes, e.g. `sessions/2024-06-01-1430-git_rebase.md`
+pub fn session_log_path(notes_path: &Path, session: &Session) -> PathBuf {
+    let filename = format!(
+        "{}-{}",
+        session.started_at.format("%Y-%m-%d-%H%M"),
+        title_to_filename(session.topic())
+    );
+    notes_path.join(SESSIONS_FOLDER).join(filename)
+}
+
+/// Render a minimal dated log note: metadata, what was saved, and the conversation.
+/// The date under the heading is written the way readers of `language` expect.
+pub fn build_session_log(session: &Session, format: &NotesFormat, notes_path: &Path, language: Option<&str>) -> String {
+    let mut log = String::new();
+
+    if !matches!(format, NotesFormat::Plain) {
+        let kind = match session.mode {
+            LearningMode::DeepDive { .. } => "deep-dive",
+            LearningMode::Link { .. } => "link",
+            LearningMode::Audio { .. } => "audio",
+            LearningMode::Video { .. } => "video",
+            LearningMode::Compare { .. } => "compare",
+            LearningMode::Image { .. } => "image",
+            LearningMode::ShellRecap { .. } => "shell-recap",
+        };
+        log.push_str(&format!(
+            "---\ndate: {}\ntags: [holocron, session-log]\nmode: {}\n",
+            session.started_at.format("%Y-%m-%d"),
+            kind
+        ));
+        if let Some(ref category) = session.category {
+            log.push_str(&format!("category: {}\n", category));
+        }
+        log.push_str("---\n\n");
+    }
+
+    log.push_str(&format!("# {}\n\n", session.mode));
+    log.push_str(&format!(
+        "*{} · {} · {} exchanges*\n",
+        session.started_at.format(&format!("{} %H:%M", language::date_format(language))),
+        session::format_duration(session.duration()),
+        session.exchanges.len()
+    ));
+
+    if !session.artifacts.is_empty() {
+        log.push_str("\n## Saved\n\n");
+        for path in &session.artifacts {
+            log.push_str(&format!("- {}\n", artifact_link(path, format, notes_path)));
+        }
+    }
+
+    log.push_str("\n## Conversation\n");
+    for exchange in &session.exchanges {
+        log.push_str(&format!("\n### You\n\n{}\n", exchange.user_message.trim()));
+        log.push_str(&format!("\n### Holocron\n\n{}\n", exchange.assistant_response.trim()));
+    }
+
+    match format {
+        NotesFormat::Org => org::to_org(&log, &org::new_id()),
+        _ => log,
+    }
+}
+
+/// Write the session's log note, returning its path. An existing log is never overwritten.
+pub fn write_session_log(
+    notes_path: &Path,
+    session: &Session,
+    format: &NotesFormat,
+    language: Option<&str>,
+) -> Result<PathBuf> {
+    let extension = if matches!(format, NotesFormat::Org) { "org" } else { "md" };
+    let base = session_log_path(notes_path, session).with_extension(extension);
+    let stem = base.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+    let mut path = base.clone();
+    let mut suffix = 2;
+    while path.exists() {
+        path = base.with_file_name(format!("{}_{}.{}", stem, suffix, extension));
+        suffix += 1;
+    }
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create sessions folder: {:?}", parent))?;
+    }
+    files::write_atomic(&path, build_session_log(session, format, notes_path, language))
+        .with_context(|| format!("Failed to write session log: {:?}", path))?;
+
+    Ok(path)
+}
+
+/// Wikilink notes inside the vault (org-roam gets file links); anything else is shown as a path
+fn artifact_link(path: &Path, format: &NotesFormat, notes_path: &Path) -> String {
+    let in_vault = path.starts_with(notes_path) && !matches!(format, NotesFormat::Plain);
+    match path.file_stem() {
+        Some(stem) if in_vault && matches!(format, NotesFormat::Org) => {
+            format!("[{}]({})", stem.to_string_lossy(), path.display())
+        }
+        Some(stem) if in_vault => format!("[[{}]]", stem.to_string_lossy()),
+        _ => format!("`{}`", path.display()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn session() -> Session {
+        let mut session = Session::new(
+            LearningMode::DeepDive {
+                topic: "git rebase".to_string(),
+            },
+            Some("git".to_string()),
+        );
+        session.add_exchange("What is rebase?".to_string(), "Replaying commits.".to_string());
+        session
+    }
+
+    #[test]
+    fn test_build_session_log_obsidian() {
+        let mut session = session();
+        session.add_artifact(PathBuf::from("/vault/Git Rebase.md"));
+        session.add_artifact(PathBuf::from("/til/archive/git/rebase.md"));
+
+        let log = build_session_log(&session, &NotesFormat::Obsidian, Path::new("/vault"), None);
+
+        assert!(log.starts_with("---\ndate: "));
+        assert!(log.contains("tags: [holocron, session-log]\nmode: deep-dive\ncategory: git\n---"));
+        assert!(log.contains("# Deep Dive: git rebase"));
+        assert!(log.contains("- [[Git Rebase]]\n- `/til/archive/git/rebase.md`\n"));
+        assert!(log.contains("### You\n\nWhat is rebase?\n\n### Holocron\n\nReplaying commits.\n"));
+    }
+
+    #[test]
+    fn test_build_session_log_plain_has_no_frontmatter() {
+        let log = build_session_log(&session(), &NotesFormat::Plain, Path::new("/vault"), None);
+
+        assert!(log.starts_with("# Deep Dive: git rebase"));
+        assert!(!log.contains("## Saved"));
+    }
+
+    #[test]
+    fn test_build_session_log_localizes_date() {
+        let session = session();
+        let log = build_session_log(&session, &NotesFormat::Obsidian, Path::new("/vault"), Some("German"));
+        assert!(log.contains(&format!("date: {}\n", session.started_at.format("%Y-%m-%d"))));
+        assert!(log.contains(&format!("*{} · ", session.started_at.format("%d.%m.%Y %H:%M"))));
+    }
+
+    #[test]
+    fn test_build_session_log_org() {
+        let mut session = session();
+        session.add_artifact(PathBuf::from("/roam/git_rebase.org"));
+
+        let log = build_session_log(&session, &NotesFormat::Org, Path::new("/roam"), None);
+
+        assert!(log.starts_with(":PROPERTIES:\n:ID:       "));
+        assert!(log.contains("#+TITLE: Deep Dive: git rebase\n#+FILETAGS: :holocron:session_log:\n"));
+        assert!(log.contains("* Saved\n\n- [[file:/roam/git_rebase.org][git_rebase]]\n"));
+        assert!(log.contains("** You\n\nWhat is rebase?"));
+    }
+
+    #[test]
+    fn test_write_session_log_never_overwrites() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let session = session();
+
+        let first = write_session_log(temp_dir.path(), &session, &NotesFormat::Obsidian, None)?;
+        let second = write_session_log(temp_dir.path(), &session, &NotesFormat::Obsidian, None)?;
+
+        assert!(first.starts_with(temp_dir.path().join(SESSIONS_FOLDER)));
+        assert!(first.to_string_lossy().ends_with("git_rebase.md"));
+        assert!(second.to_string_lossy().ends_with("git_rebase_2.md"));
+
+        Ok(())
+    }
+}
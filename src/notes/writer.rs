@@ -1,24 +1,121 @@
-use anyhow::{Context, Result};
-use std::fs;
+use crate::crypto;
+use crate::markdown::{self, frontmatter_field, images_missing_alt, reading_minutes, set_frontmatter_field};
+use crate::storage::{SaveTarget, Saved};
+use anyhow::{anyhow, Result};
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 
-/// Write a note to the notes repository
-pub fn write_note(notes_path: &Path, filename: &str, content: &str) -> Result<PathBuf> {
-    // Create notes directory if it doesn't exist
-    if !notes_path.exists() {
-        fs::create_dir_all(notes_path)
-            .with_context(|| format!("Failed to create notes directory: {:?}", notes_path))?;
-    }
-
-    let filename = sanitize_filename(filename);
-    let file_path = notes_path.join(&filename);
+/// Save a note with `target` (the notes directory, unless storage is configured otherwise),
+/// in `folder` if it's been sorted into one
+pub fn write_note(target: &dyn SaveTarget, folder: Option<&str>, filename: &str, content: &str) -> Result<Saved> {
+    validate_note(content)?;
 
     // Ensure trailing newline
     let content = ensure_trailing_newline(content);
-    fs::write(&file_path, &content)
-        .with_context(|| format!("Failed to write note file: {:?}", file_path))?;
+    target.save(&note_file(folder, filename), content.as_bytes())
+}
+
+/// Get the path a note with this filename is written to
+pub fn note_path(notes_path: &Path, folder: Option<&str>, filename: &str) -> PathBuf {
+    notes_path.join(note_file(folder, filename))
+}
+
+/// The note's path inside the notes directory, e.g. `Engineering/Databases/mvcc.md`
+pub fn note_file(folder: Option<&str>, filename: &str) -> String {
+    match folder.map(|folder| folder.trim_matches('/')).filter(|folder| !folder.is_empty()) {
+        Some(folder) => format!("{}/{}", folder, sanitize_filename(filename)),
+        None => sanitize_filename(filename),
+    }
+}
+
+/// The vault subfolder for a note, from the first of its tags that `folders` maps to one.
+/// A nested tag like `database/postgres` falls back to its parent's folder. Returns the tag too.
+pub fn folder_for(content: &str, folders: &BTreeMap<String, String>) -> Option<(String, String)> {
+    let folders: BTreeMap<String, &String> = folders.iter().map(|(tag, folder)| (tag.to_lowercase(), folder)).collect();
+    markdown::tags(content).into_iter().find_map(|tag| {
+        let folder = folders
+            .get(&tag)
+            .or_else(|| tag.split_once('/').and_then(|(parent, _)| folders.get(parent)))?;
+        Some((tag, folder.to_string()))
+    })
+}
+
+/// Validate note content before it is written to disk
+pub fn validate_note(content: &str) -> Result<()> {
+    let missing = images_missing_alt(content);
+    if missing.is_empty() {
+        return Ok(());
+    }
+
+    let targets: Vec<String> = missing
+        .iter()
+        .map(|img| format!("{} (line {})", img.target, img.line))
+        .collect();
+    Err(anyhow!(
+        "Note has {} image(s) without alt text: {}",
+        missing.len(),
+        targets.join(", ")
+    ))
+}
+
+/// Difficulty levels accepted in the `level` frontmatter field
+pub const DIFFICULTY_LEVELS: [&str; 3] = ["beginner", "intermediate", "advanced"];
+
+/// Get the note's difficulty level, if it is one of the known levels
+pub fn difficulty(content: &str) -> Option<String> {
+    let level = frontmatter_field(content, "level")?.to_lowercase();
+    DIFFICULTY_LEVELS.contains(&level.as_str()).then_some(level)
+}
+
+/// Set the note's difficulty level in its frontmatter
+pub fn set_difficulty(content: &str, level: &str) -> String {
+    set_frontmatter_field(content, "level", level)
+}
 
-    Ok(file_path)
+/// Add (or refresh) the estimated reading time in the note's frontmatter
+pub fn add_reading_time(content: &str) -> String {
+    let minutes = reading_minutes(content);
+    set_frontmatter_field(content, "reading_time", &format!("{} min", minutes))
+}
+
+/// List the session's sources at the end of the note, skipping any the note already links to.
+/// They go under the note's own "Sources" heading if it has one.
+pub fn add_sources(content: &str, sources: &[String]) -> String {
+    let missing: Vec<String> = sources
+        .iter()
+        .filter(|url| !content.contains(url.as_str()))
+        .map(|url| format!("- <{}>", url))
+        .collect();
+    if missing.is_empty() {
+        return content.to_string();
+    }
+
+    let lines: Vec<&str> = content.trim_end().lines().collect();
+    let heading = lines.iter().position(|line| {
+        let line = line.trim();
+        line.starts_with("## ") && line.trim_start_matches('#').trim().eq_ignore_ascii_case("sources")
+    });
+    let Some(heading) = heading else {
+        return format!("{}\n\n## Sources\n\n{}\n", content.trim_end(), missing.join("\n"));
+    };
+
+    // The section runs until the next heading of the same or a higher level
+    let end = lines[heading + 1..]
+        .iter()
+        .position(|line| line.starts_with("# ") || line.starts_with("## "))
+        .map_or(lines.len(), |offset| heading + 1 + offset);
+    let mut section_end = end;
+    while section_end > heading + 1 && lines[section_end - 1].trim().is_empty() {
+        section_end -= 1;
+    }
+
+    let mut result: Vec<String> = lines[..section_end].iter().map(|line| line.to_string()).collect();
+    result.extend(missing);
+    if end < lines.len() {
+        result.push(String::new());
+        result.extend(lines[end..].iter().map(|line| line.to_string()));
+    }
+    format!("{}\n", result.join("\n"))
 }
 
 /// Extract title from note content (from frontmatter or first H1)
@@ -54,37 +151,6 @@ pub fn extract_title(content: &str) -> Option<String> {
     None
 }
 
-/// Generate a filename from a title
-pub fn title_to_filename(title: &str) -> String {
-    let filename: String = title
-        .to_lowercase()
-        .chars()
-        .map(|c| if c.is_alphanumeric() { c } else { '_' })
-        .collect();
-
-    let result = collapse_underscores(&filename);
-    format!("{}.md", result)
-}
-
-fn collapse_underscores(s: &str) -> String {
-    let mut result = String::new();
-    let mut last_was_underscore = false;
-
-    for c in s.chars() {
-        if c == '_' {
-            if !last_was_underscore {
-                result.push(c);
-            }
-            last_was_underscore = true;
-        } else {
-            result.push(c);
-            last_was_underscore = false;
-        }
-    }
-
-    result.trim_matches('_').to_string()
-}
-
 fn ensure_trailing_newline(s: &str) -> String {
     if s.ends_with('\n') {
         s.to_string()
@@ -94,20 +160,38 @@ fn ensure_trailing_newline(s: &str) -> String {
 }
 
 fn sanitize_filename(filename: &str) -> String {
-    let name = if filename.ends_with(".md") {
+    let name = if filename.ends_with(".md") || filename.ends_with(".org") || crypto::is_encrypted(Path::new(filename)) {
         filename.to_string()
     } else {
         format!("{}.md", filename)
     };
 
-    name.replace(' ', "_").to_lowercase()
+    // Case is left alone, since the `preserve` filename style keeps it
+    name.replace(' ', "_")
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::storage::Filesystem;
+    use std::fs;
     use tempfile::TempDir;
 
+    #[test]
+    fn test_add_sources() {
+        let sources = vec!["https://a.com/post".to_string(), "https://b.com".to_string()];
+
+        assert_eq!(
+            add_sources("# Note\n\nSee [a](https://a.com/post).\n", &sources),
+            "# Note\n\nSee [a](https://a.com/post).\n\n## Sources\n\n- <https://b.com>\n"
+        );
+        assert_eq!(
+            add_sources("# Note\n\n## Sources\n\n- [C](https://c.com)\n\n## Related\n\n- [[x]]\n", &sources),
+            "# Note\n\n## Sources\n\n- [C](https://c.com)\n- <https://a.com/post>\n- <https://b.com>\n\n## Related\n\n- [[x]]\n"
+        );
+        assert_eq!(add_sources("# Note\n", &[]), "# Note\n");
+    }
+
     #[test]
     fn test_extract_title_from_frontmatter() {
         let content = r#"---
@@ -162,30 +246,31 @@ title:
     }
 
     #[test]
-    fn test_title_to_filename() {
-        assert_eq!(title_to_filename("Rust Clippy"), "rust_clippy.md");
-        assert_eq!(title_to_filename("Git: The Basics"), "git_the_basics.md");
+    fn test_difficulty() {
+        assert_eq!(
+            difficulty("---\nlevel: Advanced\n---\n"),
+            Some("advanced".to_string())
+        );
+        assert_eq!(difficulty("---\nlevel: expert\n---\n"), None);
+        assert_eq!(difficulty("# No frontmatter"), None);
     }
 
     #[test]
-    fn test_title_to_filename_special_chars() {
-        assert_eq!(title_to_filename("What's New?"), "what_s_new.md");
-        assert_eq!(title_to_filename("Test & More"), "test_more.md");
-    }
+    fn test_add_reading_time() {
+        let content = "---\ntitle: Test\n---\n\n# Test\n";
+        let updated = add_reading_time(content);
+        assert_eq!(frontmatter_field(&updated, "reading_time"), Some("1 min".to_string()));
 
-    #[test]
-    fn test_collapse_underscores() {
-        assert_eq!(collapse_underscores("a__b"), "a_b");
-        assert_eq!(collapse_underscores("___test___"), "test");
-        assert_eq!(collapse_underscores("no_change"), "no_change");
+        let updated = set_difficulty(&updated, "beginner");
+        assert_eq!(difficulty(&updated), Some("beginner".to_string()));
     }
 
     #[test]
     fn test_sanitize_filename() {
         assert_eq!(sanitize_filename("test"), "test.md");
         assert_eq!(sanitize_filename("test.md"), "test.md");
-        assert_eq!(sanitize_filename("Test File"), "test_file.md");
-        assert_eq!(sanitize_filename("UPPER.md"), "upper.md");
+        assert_eq!(sanitize_filename("Test File"), "Test_File.md");
+        assert_eq!(sanitize_filename("UPPER.md"), "UPPER.md");
     }
 
     #[test]
@@ -193,11 +278,12 @@ title:
         let temp_dir = TempDir::new()?;
         let content = "# Test Note\n\nContent here.";
 
-        let path = write_note(temp_dir.path(), "test_note.md", content)?;
+        let saved = write_note(&Filesystem::new(temp_dir.path()), None, "test_note.md", content)?;
 
+        let path = saved.local_path().ok_or_else(|| anyhow!("not saved locally"))?;
         assert!(path.exists());
         // Should have trailing newline added
-        assert_eq!(fs::read_to_string(&path)?, format!("{}\n", content));
+        assert_eq!(fs::read_to_string(path)?, format!("{}\n", content));
 
         Ok(())
     }
@@ -207,21 +293,70 @@ title:
         let temp_dir = TempDir::new()?;
         let notes_path = temp_dir.path().join("new_notes_dir");
 
-        let path = write_note(&notes_path, "note.md", "# Note")?;
+        let saved = write_note(&Filesystem::new(&notes_path), None, "note.md", "# Note")?;
 
-        assert!(path.exists());
+        assert_eq!(saved, Saved::Local(notes_path.join("note.md")));
         assert!(notes_path.exists());
 
         Ok(())
     }
 
+    #[test]
+    fn test_write_note_into_folder() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+
+        let saved = write_note(&Filesystem::new(temp_dir.path()), Some("Engineering/Databases/"), "mvcc", "# MVCC")?;
+
+        assert_eq!(saved, Saved::Local(temp_dir.path().join("Engineering/Databases/mvcc.md")));
+        Ok(())
+    }
+
+    #[test]
+    fn test_folder_for() {
+        let folders = BTreeMap::from([
+            ("Database".to_string(), "Engineering/Databases".to_string()),
+            ("rust".to_string(), "Engineering/Rust".to_string()),
+        ]);
+        let folder = |content: &str| folder_for(content, &folders);
+
+        assert_eq!(
+            folder("---\ntags: [til, database, rust]\n---\n# Locks"),
+            Some(("database".to_string(), "Engineering/Databases".to_string()))
+        );
+        assert_eq!(
+            folder("---\ntags: [rust/async]\n---\n"),
+            Some(("rust/async".to_string(), "Engineering/Rust".to_string()))
+        );
+        assert_eq!(folder("---\ntags: [cooking]\n---\n"), None);
+        assert_eq!(folder("# No tags"), None);
+    }
+
+    #[test]
+    fn test_validate_note_with_alt_text() {
+        let content = "# Note\n\n![Borrow checker flow](flow.png)";
+        assert!(validate_note(content).is_ok());
+    }
+
+    #[test]
+    fn test_write_note_rejects_missing_alt_text() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let content = "# Note\n\n![](flow.png)";
+
+        let result = write_note(&Filesystem::new(temp_dir.path()), None, "note.md", content);
+
+        assert!(result.is_err());
+        assert!(!temp_dir.path().join("note.md").exists());
+
+        Ok(())
+    }
+
     #[test]
     fn test_write_note_without_extension() -> Result<()> {
         let temp_dir = TempDir::new()?;
 
-        let path = write_note(temp_dir.path(), "my_note", "# Note")?;
+        let saved = write_note(&Filesystem::new(temp_dir.path()), None, "my_note", "# Note")?;
 
-        assert!(path.to_string_lossy().ends_with(".md"));
+        assert_eq!(saved, Saved::Local(temp_dir.path().join("my_note.md")));
 
         Ok(())
     }
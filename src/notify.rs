@@ -0,0 +1,53 @@
+use notify_rust::Notification;
+use std::time::{Duration, Instant};
+
+/// Responses quicker than this finish while you're still watching, so they don't notify
+const MIN_WAIT: Duration = Duration::from_secs(15);
+
+/// Longest notification body, so a long topic doesn't fill the screen
+const MAX_BODY_CHARS: usize = 100;
+
+/// Tell the desktop that a response or generation finished, if it took long enough that you may
+/// have switched windows. Failures (no notification daemon, a headless machine) are ignored.
+pub fn finished(enabled: bool, started: Instant, summary: &str, body: &str) {
+    if started.elapsed() < MIN_WAIT {
+        return;
+    }
+    remind(enabled, summary, body);
+}
+
+/// Show a notification whenever it's enabled, e.g. a reminder while the prompt sits idle
+pub fn remind(enabled: bool, summary: &str, body: &str) {
+    if !enabled {
+        return;
+    }
+    Notification::new()
+        .appname("holocron")
+        .summary(summary)
+        .body(&shorten(body))
+        .show()
+        .ok();
+}
+
+/// The first line of `text`, cut to `MAX_BODY_CHARS`
+fn shorten(text: &str) -> String {
+    let line = text.trim().lines().next().unwrap_or_default();
+    if line.chars().count() <= MAX_BODY_CHARS {
+        return line.to_string();
+    }
+    let cut: String = line.chars().take(MAX_BODY_CHARS - 1).collect();
+    format!("{}…", cut.trim_end())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shorten() {
+        assert_eq!(shorten("  Rust lifetimes\nmore  "), "Rust lifetimes");
+        let long = "a".repeat(150);
+        assert_eq!(shorten(&long).chars().count(), MAX_BODY_CHARS);
+        assert!(shorten(&long).ends_with('…'));
+    }
+}
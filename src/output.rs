@@ -0,0 +1,72 @@
+use crate::error::HolocronError;
+use crate::theme;
+use anyhow::Result;
+use dialoguer::theme::ColorfulTheme;
+use std::io::{self, IsTerminal};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Where holocron's output goes, worked out once at startup. Piped into another program, a file
+/// or cron, output is plain text: no colors, spinners or progress bars, and anything that would
+/// stop to ask a question fails instead of waiting for an answer nobody can give.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Output {
+    /// Stdout is a terminal someone is looking at
+    pub terminal: bool,
+}
+
+impl Output {
+    pub fn detect() -> Self {
+        Self {
+            terminal: io::stdout().is_terminal(),
+        }
+    }
+}
+
+static TERMINAL: AtomicBool = AtomicBool::new(true);
+
+/// Make `output` the context every command prints in, turning colors off when it isn't a terminal
+pub fn set(output: Output) {
+    TERMINAL.store(output.terminal, Ordering::Relaxed);
+    if !output.terminal {
+        colored::control::set_override(false);
+        console::set_colors_enabled(false);
+        console::set_colors_enabled_stderr(false);
+    }
+}
+
+pub fn get() -> Output {
+    Output {
+        terminal: TERMINAL.load(Ordering::Relaxed),
+    }
+}
+
+/// Fail when there's no terminal for something interactive (a prompt, the TUI) to run in
+pub fn require_terminal() -> Result<()> {
+    if get().terminal {
+        Ok(())
+    } else {
+        Err(HolocronError::NotInteractive.into())
+    }
+}
+
+/// The theme for a dialoguer prompt, or an error when stdout isn't a terminal to ask in
+pub fn prompts() -> Result<ColorfulTheme> {
+    require_terminal()?;
+    Ok(theme::prompts())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prompts_fail_without_a_terminal() {
+        // Only the flag: `set` would also turn colors off for every other test
+        TERMINAL.store(false, Ordering::Relaxed);
+        let error = prompts().err().map(|e| crate::error::exit_code(&e));
+        TERMINAL.store(true, Ordering::Relaxed);
+
+        assert_eq!(error, Some(HolocronError::NotInteractive.exit_code()));
+        assert!(prompts().is_ok());
+    }
+}
@@ -0,0 +1,239 @@
+use crate::claude::run_claude_command;
+use crate::markdown;
+use crate::til::writer::title_to_filename;
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Plans are kept in this directory of the TIL repo
+pub const PLANS_DIR: &str = "plans";
+
+/// The most sub-topics kept from a generated syllabus
+const MAX_STEPS: usize = 20;
+
+/// A syllabus for a big topic, stored as a markdown checklist
+#[derive(Debug, Clone, PartialEq)]
+pub struct Plan {
+    pub topic: String,
+    /// Category TILs from this plan are filed under, if chosen when planning
+    pub category: Option<String>,
+    pub steps: Vec<Step>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Step {
+    pub title: String,
+    pub done: bool,
+}
+
+/// The plan item a session is working through, checked off once a TIL is saved from it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlanStep {
+    pub plan: PathBuf,
+    pub title: String,
+}
+
+impl Plan {
+    pub fn new(topic: &str, category: Option<String>, titles: Vec<String>) -> Self {
+        Self {
+            topic: topic.to_string(),
+            category,
+            steps: titles.into_iter().map(|title| Step { title, done: false }).collect(),
+        }
+    }
+
+    /// Read a plan back from its checklist
+    pub fn parse(content: &str) -> Self {
+        let body = markdown::split_frontmatter(content).map_or(content, |(_, rest)| rest);
+        let topic = body
+            .lines()
+            .find_map(|line| line.trim().strip_prefix("# "))
+            .map(|title| title.trim_start_matches("Plan:").trim().to_string())
+            .unwrap_or_default();
+        let steps = body
+            .lines()
+            .filter_map(|line| {
+                let line = line.trim();
+                let (done, title) = if let Some(title) = line.strip_prefix("- [ ] ") {
+                    (false, title)
+                } else {
+                    (true, line.strip_prefix("- [x] ").or_else(|| line.strip_prefix("- [X] "))?)
+                };
+                Some(Step {
+                    title: title.trim().to_string(),
+                    done,
+                })
+            })
+            .collect();
+
+        Self {
+            topic,
+            category: markdown::frontmatter_field(content, "category"),
+            steps,
+        }
+    }
+
+    pub fn render(&self) -> String {
+        let mut content = String::new();
+        if let Some(ref category) = self.category {
+            content.push_str(&format!("---\ncategory: {}\n---\n\n", category));
+        }
+        content.push_str(&format!("# Plan: {}\n\n", self.topic));
+        for step in &self.steps {
+            content.push_str(&format!("- [{}] {}\n", if step.done { "x" } else { " " }, step.title));
+        }
+        content
+    }
+
+    /// The first step not yet checked off
+    pub fn next_step(&self) -> Option<&Step> {
+        self.steps.iter().find(|step| !step.done)
+    }
+
+    pub fn done_count(&self) -> usize {
+        self.steps.iter().filter(|step| step.done).count()
+    }
+}
+
+/// Where the plan for a topic is saved
+pub fn plan_path(til_path: &Path, topic: &str) -> PathBuf {
+    til_path.join(PLANS_DIR).join(title_to_filename(topic))
+}
+
+/// Every saved plan, most recently updated first
+pub fn list_plans(til_path: &Path) -> Result<Vec<(PathBuf, Plan)>> {
+    let dir = til_path.join(PLANS_DIR);
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+
+    let mut plans: Vec<(PathBuf, Plan)> = fs::read_dir(&dir)
+        .with_context(|| format!("Failed to read {:?}", dir))?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "md"))
+        .filter_map(|path| {
+            let plan = Plan::parse(&fs::read_to_string(&path).ok()?);
+            Some((path, plan))
+        })
+        .collect();
+    plans.sort_by_key(|(path, _)| std::cmp::Reverse(fs::metadata(path).and_then(|m| m.modified()).ok()));
+    Ok(plans)
+}
+
+/// The plan to continue: one whose topic contains `query`, or else the latest with steps left
+pub fn find_plan(plans: Vec<(PathBuf, Plan)>, query: Option<&str>) -> Result<(PathBuf, Plan)> {
+    match query {
+        Some(query) => {
+            let query = query.to_lowercase();
+            plans
+                .into_iter()
+                .find(|(_, plan)| plan.topic.to_lowercase().contains(&query))
+                .ok_or_else(|| anyhow!("No plan matches \"{}\"", query))
+        }
+        None => plans
+            .into_iter()
+            .find(|(_, plan)| plan.next_step().is_some())
+            .ok_or_else(|| anyhow!("No plan has steps left. Start one with: holocron plan <topic>")),
+    }
+}
+
+/// Check off a step in its plan file. Returns false if the step is gone or already done.
+pub fn complete_step(step: &PlanStep) -> Result<bool> {
+    let content = fs::read_to_string(&step.plan).with_context(|| format!("Failed to read {:?}", step.plan))?;
+    let mut plan = Plan::parse(&content);
+    let Some(found) = plan.steps.iter_mut().find(|s| s.title == step.title && !s.done) else {
+        return Ok(false);
+    };
+    found.done = true;
+    fs::write(&step.plan, plan.render()).with_context(|| format!("Failed to write {:?}", step.plan))?;
+    Ok(true)
+}
+
+/// Ask the backend to break a big topic into an ordered syllabus
+pub fn generate_steps(topic: &str) -> Result<Vec<String>> {
+    let response = run_claude_command(&build_plan_prompt(topic), |_| {})?.text;
+    let steps = parse_steps(&response);
+    if steps.is_empty() {
+        return Err(anyhow!("The backend didn't return a syllabus for \"{}\"", topic));
+    }
+    Ok(steps)
+}
+
+fn build_plan_prompt(topic: &str) -> String {
+    format!(
+        r#"I want to learn {} properly, one focused session at a time.
+
+Break it into an ordered syllabus of 10-20 sub-topics. Start with the foundations and build up, so each sub-topic only relies on earlier ones. Each should be narrow enough to study in a single session and write one "Today I Learned" note about.
+
+Return ONLY the numbered list, one sub-topic per line, each a short title (no descriptions)."#,
+        topic
+    )
+}
+
+/// The numbered or bulleted lines of a syllabus, without their markers or emphasis
+fn parse_steps(response: &str) -> Vec<String> {
+    response
+        .lines()
+        .map(str::trim)
+        .filter(|line| line.starts_with(|c: char| c.is_ascii_digit() || c == '-' || c == '*'))
+        .map(|line| {
+            line.trim_start_matches(|c: char| c.is_ascii_digit() || matches!(c, '.' | ')' | '-' | '*' | ' '))
+                .trim_matches('*')
+                .trim()
+                .to_string()
+        })
+        .filter(|title| !title.is_empty())
+        .take(MAX_STEPS)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_parse_steps() {
+        let response = "Here's a syllabus:\n\n1. **Failure models**\n2) Clocks and ordering\n- Consensus\n\nGood luck!";
+        assert_eq!(parse_steps(response), vec!["Failure models", "Clocks and ordering", "Consensus"]);
+    }
+
+    #[test]
+    fn test_plan_roundtrip() {
+        let mut plan = Plan::new(
+            "Distributed Systems",
+            Some("distsys".to_string()),
+            vec!["Failure models".to_string(), "Clocks".to_string()],
+        );
+        plan.steps[0].done = true;
+
+        let content = plan.render();
+        assert_eq!(
+            content,
+            "---\ncategory: distsys\n---\n\n# Plan: Distributed Systems\n\n- [x] Failure models\n- [ ] Clocks\n"
+        );
+        assert_eq!(Plan::parse(&content), plan);
+        assert_eq!(plan.next_step().map(|s| s.title.as_str()), Some("Clocks"));
+    }
+
+    #[test]
+    fn test_complete_step() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let path = plan_path(temp_dir.path(), "Distributed Systems");
+        fs::create_dir_all(temp_dir.path().join(PLANS_DIR))?;
+        fs::write(&path, Plan::new("Distributed Systems", None, vec!["Clocks".to_string()]).render())?;
+
+        let step = PlanStep {
+            plan: path.clone(),
+            title: "Clocks".to_string(),
+        };
+        assert!(complete_step(&step)?);
+        assert!(!complete_step(&step)?);
+
+        let plans = list_plans(temp_dir.path())?;
+        assert_eq!(plans[0].1.done_count(), 1);
+        assert!(find_plan(plans, None).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,180 @@
+use crate::config::{PublishConfig, PublishTarget};
+use crate::{files, markdown, share, sync};
+use anyhow::{anyhow, Context, Result};
+use chrono::NaiveDate;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Put this on its own line to keep everything up to `<!-- /private -->` (or the end) out of
+/// the published copy, or at the end of a line to keep just that line out
+pub const PRIVATE_MARKER: &str = "<!-- private -->";
+const PRIVATE_END: &str = "<!-- /private -->";
+
+/// Frontmatter fields recording where a TIL was published
+const PUBLISHED_FIELD: &str = "published";
+const PUBLISHED_URL_FIELD: &str = "published_url";
+
+/// A TIL as the public sees it: no private parts and no publish bookkeeping
+pub fn public_content(content: &str) -> String {
+    let content = without_publish_fields(content);
+    let mut public = Vec::new();
+    let mut private = false;
+
+    for line in content.lines() {
+        let trimmed = line.trim();
+        if private {
+            private = trimmed != PRIVATE_END;
+        } else if trimmed == PRIVATE_MARKER {
+            private = true;
+        } else if !trimmed.ends_with(PRIVATE_MARKER) {
+            public.push(line);
+        }
+    }
+
+    format!("{}\n", public.join("\n").trim())
+}
+
+/// Record in the TIL's frontmatter when and where it was published
+pub fn mark_published(content: &str, location: &str, date: NaiveDate) -> String {
+    let content = markdown::set_frontmatter_field(content, PUBLISHED_FIELD, &date.format("%Y-%m-%d").to_string());
+    markdown::set_frontmatter_field(&content, PUBLISHED_URL_FIELD, location)
+}
+
+/// Where the TIL was last published, if it has been
+pub fn published_location(content: &str) -> Option<String> {
+    markdown::frontmatter_field(content, PUBLISHED_URL_FIELD)
+}
+
+/// Publish a TIL's public content to the configured target, returning where it went:
+/// the gist's URL or the file written
+pub fn publish(
+    til: &Path,
+    category: Option<&str>,
+    config: &PublishConfig,
+    til_repo: &Path,
+    github_token: Option<&str>,
+) -> Result<String> {
+    let content = fs::read_to_string(til).with_context(|| format!("Failed to read {:?}", til))?;
+    let filename = til
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .ok_or_else(|| anyhow!("{:?} isn't a file", til))?;
+    let public = public_content(&content);
+
+    match config.target {
+        None => Err(anyhow!(
+            "Nowhere to publish to. Set `target` (gist, pages or repo) under [publish] in the config file"
+        )),
+        Some(PublishTarget::Gist) => {
+            let token = github_token.ok_or_else(|| {
+                anyhow!("No GitHub token configured. Set `github_token` under [share] in the config file")
+            })?;
+            let shareable = share::Shareable {
+                filename,
+                markdown: public,
+            };
+            let title = crate::til::writer::extract_title(&content).unwrap_or_default();
+            share::create_gist(&shareable, &format!("TIL: {}", title), token, true)
+        }
+        Some(PublishTarget::Pages) => {
+            let root = config.path.clone().unwrap_or_else(|| til_repo.join("docs"));
+            let path = write_public(&root, category, &filename, &public)?;
+            Ok(path.display().to_string())
+        }
+        Some(PublishTarget::Repo) => {
+            let repo = config
+                .path
+                .as_deref()
+                .ok_or_else(|| anyhow!("Set `path` under [publish] to a local clone of the public repo"))?;
+            let path = write_public(repo, category, &filename, &public)?;
+            let relative = path.strip_prefix(repo).unwrap_or(&path).to_string_lossy().to_string();
+            sync::git(repo, &["add", "--", &relative])?;
+            let title = crate::til::writer::extract_title(&public).unwrap_or(filename);
+            sync::git(repo, &["commit", "--quiet", "-m", &format!("Publish {}", title)])?;
+            if !sync::git(repo, &["remote"])?.trim().is_empty() {
+                sync::git(repo, &["push", "--quiet"])?;
+            }
+            Ok(path.display().to_string())
+        }
+    }
+}
+
+/// Write the public copy to `<root>/<category>/<filename>`
+fn write_public(root: &Path, category: Option<&str>, filename: &str, content: &str) -> Result<PathBuf> {
+    let dir = match category {
+        Some(category) => root.join(category),
+        None => root.to_path_buf(),
+    };
+    fs::create_dir_all(&dir).with_context(|| format!("Failed to create {:?}", dir))?;
+    let path = dir.join(filename);
+    files::write_atomic(&path, content).with_context(|| format!("Failed to write {:?}", path))?;
+    Ok(path)
+}
+
+/// The content without the `published` fields, and without its frontmatter if nothing else is left in it
+fn without_publish_fields(content: &str) -> String {
+    let Some((frontmatter, body)) = markdown::split_frontmatter(content) else {
+        return content.to_string();
+    };
+    let kept: Vec<&str> = frontmatter
+        .lines()
+        .filter(|line| {
+            let key = line.split(':').next().unwrap_or_default().trim();
+            key != PUBLISHED_FIELD && key != PUBLISHED_URL_FIELD
+        })
+        .collect();
+
+    if kept.iter().all(|line| line.trim().is_empty()) {
+        body.trim_start().to_string()
+    } else {
+        format!("---\n{}\n---{}", kept.join("\n"), body)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_public_content_strips_private_parts() {
+        let content = "# Rebase\n\nUse `git rebase -i`.\nOur fork is at ~/work/acme <!-- private -->\n\n<!-- private -->\nInternal notes\n<!-- /private -->\n\nThat's it!\n";
+        assert_eq!(public_content(content), "# Rebase\n\nUse `git rebase -i`.\n\n\nThat's it!\n");
+
+        let unterminated = "# Rebase\n\nPublic.\n<!-- private -->\nAll of this stays home.\n";
+        assert_eq!(public_content(unterminated), "# Rebase\n\nPublic.\n");
+    }
+
+    #[test]
+    fn test_mark_published_roundtrip() -> Result<()> {
+        let date = NaiveDate::from_ymd_opt(2024, 6, 3).ok_or_else(|| anyhow!("bad date"))?;
+        let marked = mark_published("# Rebase\n\nBody.\n", "https://gist.github.com/me/1", date);
+
+        assert!(marked.starts_with("---\npublished: 2024-06-03\npublished_url: https://gist.github.com/me/1\n---\n"));
+        assert_eq!(published_location(&marked).as_deref(), Some("https://gist.github.com/me/1"));
+        assert_eq!(public_content(&marked), "# Rebase\n\nBody.\n");
+
+        let tagged = mark_published("---\ntags: [git]\n---\n\n# Rebase\n", "docs/git/rebase.md", date);
+        assert_eq!(public_content(&tagged), "---\ntags: [git]\n---\n\n# Rebase\n");
+        Ok(())
+    }
+
+    #[test]
+    fn test_publish_to_pages_folder() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let til = temp_dir.path().join("archive/git/rebase.md");
+        fs::create_dir_all(temp_dir.path().join("archive/git"))?;
+        fs::write(&til, "# Rebase\n\nPublic.\nSecret <!-- private -->\n")?;
+        let config = PublishConfig {
+            target: Some(PublishTarget::Pages),
+            path: None,
+        };
+
+        let location = publish(&til, Some("git"), &config, temp_dir.path(), None)?;
+
+        assert_eq!(PathBuf::from(&location), temp_dir.path().join("docs/git/rebase.md"));
+        assert_eq!(fs::read_to_string(&location)?, "# Rebase\n\nPublic.\n");
+        assert!(publish(&til, None, &PublishConfig::default(), temp_dir.path(), None).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,244 @@
+use super::import::{unescape, Article};
+use anyhow::{anyhow, Context, Result};
+use regex::Regex;
+use serde_json::Value;
+
+/// A bookmark from a browser export, with the folders it's filed under, outermost first
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bookmark {
+    pub url: String,
+    pub title: Option<String>,
+    pub folders: Vec<String>,
+}
+
+impl Bookmark {
+    /// Whether it's in `folder` or a folder below it. `folder` is a name like `Rust`, or a
+    /// path like `Bookmarks bar/Rust` when the name alone is ambiguous.
+    pub fn is_in(&self, folder: &str) -> bool {
+        let wanted: Vec<String> = folder.split('/').map(|part| part.trim().to_lowercase()).collect();
+        let folders: Vec<String> = self.folders.iter().map(|name| name.to_lowercase()).collect();
+        folders.windows(wanted.len()).any(|window| window == wanted.as_slice())
+    }
+}
+
+impl From<Bookmark> for Article {
+    fn from(bookmark: Bookmark) -> Self {
+        Article {
+            url: bookmark.url,
+            title: bookmark.title,
+        }
+    }
+}
+
+/// Web bookmarks in a browser export: a Netscape bookmarks file (what every browser's
+/// "Export bookmarks to HTML" writes), Chrome's `Bookmarks` JSON or a Firefox JSON backup
+pub fn parse(content: &str) -> Result<Vec<Bookmark>> {
+    let bookmarks = if content.trim_start().starts_with(['{', '[']) {
+        let json: Value = serde_json::from_str(content).context("Failed to parse the bookmarks JSON")?;
+        let mut bookmarks = Vec::new();
+        collect_json(&json, &mut Vec::new(), &mut bookmarks);
+        bookmarks
+    } else {
+        parse_netscape(content)?
+    };
+
+    if bookmarks.is_empty() && !content.trim().is_empty() && !looks_like_bookmarks(content) {
+        return Err(anyhow!("That doesn't look like a bookmarks export (HTML or JSON)"));
+    }
+    Ok(bookmarks.into_iter().filter(|bookmark| is_web(&bookmark.url)).collect())
+}
+
+/// Every folder that holds bookmarks, as `/`-separated paths, in the order they appear
+pub fn folders(bookmarks: &[Bookmark]) -> Vec<String> {
+    let mut folders: Vec<String> = Vec::new();
+    for bookmark in bookmarks {
+        for depth in 1..=bookmark.folders.len() {
+            let path = bookmark.folders[..depth].join("/");
+            if !folders.contains(&path) {
+                folders.push(path);
+            }
+        }
+    }
+    folders
+}
+
+fn parse_netscape(html: &str) -> Result<Vec<Bookmark>> {
+    let folder = Regex::new(r"(?i)<H3[^>]*>(.*?)</H3>")?;
+    let link = Regex::new(r#"(?i)<A\s[^>]*HREF="([^"]*)"[^>]*>(.*?)</A>"#)?;
+    let list_start = Regex::new(r"(?i)<DL[\s>]")?;
+    let list_end = Regex::new(r"(?i)</DL>")?;
+
+    let mut bookmarks = Vec::new();
+    let mut path: Vec<String> = Vec::new();
+    // A folder's heading comes just before the list holding its contents
+    let mut heading: Option<String> = None;
+    let mut depth = 0usize;
+    let mut folder_depths: Vec<usize> = Vec::new();
+
+    for line in html.lines() {
+        if let Some(captures) = folder.captures(line) {
+            heading = Some(unescape(&captures[1]).trim().to_string());
+        } else if let Some(captures) = link.captures(line) {
+            bookmarks.push(Bookmark {
+                url: unescape(&captures[1]),
+                title: non_empty(&unescape(&captures[2])),
+                folders: path.clone(),
+            });
+        }
+
+        for _ in list_start.find_iter(line) {
+            depth += 1;
+            if let Some(name) = heading.take() {
+                path.push(name);
+                folder_depths.push(depth);
+            }
+        }
+        for _ in list_end.find_iter(line) {
+            if folder_depths.last() == Some(&depth) {
+                folder_depths.pop();
+                path.pop();
+            }
+            depth = depth.saturating_sub(1);
+        }
+    }
+    Ok(bookmarks)
+}
+
+/// Walk Chrome's (`type: folder/url`, `name`, `url`) or Firefox's (`type: text/x-moz-place*`,
+/// `title`, `uri`) bookmark tree
+fn collect_json(node: &Value, path: &mut Vec<String>, bookmarks: &mut Vec<Bookmark>) {
+    match node {
+        Value::Array(nodes) => {
+            for node in nodes {
+                collect_json(node, path, bookmarks);
+            }
+            return;
+        }
+        Value::Object(_) => {}
+        _ => return,
+    }
+
+    let name = node["name"].as_str().or_else(|| node["title"].as_str()).unwrap_or_default();
+    if let Some(url) = node["url"].as_str().or_else(|| node["uri"].as_str()) {
+        bookmarks.push(Bookmark {
+            url: url.to_string(),
+            title: non_empty(name),
+            folders: path.clone(),
+        });
+        return;
+    }
+
+    // Chrome keeps its top-level folders under `roots`
+    if let Some(roots) = node["roots"].as_object() {
+        for root in roots.values() {
+            collect_json(root, path, bookmarks);
+        }
+        return;
+    }
+
+    if let Some(children) = node["children"].as_array() {
+        // Firefox's unnamed root isn't a folder anyone would pick
+        let named = !name.trim().is_empty();
+        if named {
+            path.push(name.trim().to_string());
+        }
+        for child in children {
+            collect_json(child, path, bookmarks);
+        }
+        if named {
+            path.pop();
+        }
+    }
+}
+
+fn looks_like_bookmarks(content: &str) -> bool {
+    content.to_uppercase().contains("NETSCAPE-BOOKMARK-FILE")
+}
+
+/// Only pages can be studied; `javascript:` bookmarklets, `place:` queries and the like are skipped
+fn is_web(url: &str) -> bool {
+    let url = url.to_lowercase();
+    url.starts_with("http://") || url.starts_with("https://")
+}
+
+fn non_empty(text: &str) -> Option<String> {
+    let text = text.trim();
+    (!text.is_empty()).then(|| text.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NETSCAPE: &str = r#"<!DOCTYPE NETSCAPE-Bookmark-file-1>
+<META HTTP-EQUIV="Content-Type" CONTENT="text/html; charset=UTF-8">
+<TITLE>Bookmarks</TITLE>
+<H1>Bookmarks</H1>
+<DL><p>
+    <DT><H3 ADD_DATE="1700000000" PERSONAL_TOOLBAR_FOLDER="true">Bookmarks bar</H3>
+    <DL><p>
+        <DT><A HREF="https://news.ycombinator.com/" ADD_DATE="1700000000">Hacker News</A>
+        <DT><H3>To Learn</H3>
+        <DL><p>
+            <DT><A HREF="https://doc.rust-lang.org/nomicon/?a=1&amp;b=2">The Rustonomicon &amp; more</A>
+            <DT><A HREF="javascript:alert(1)">Bookmarklet</A>
+        </DL><p>
+    </DL><p>
+    <DT><A HREF="https://example.com/loose">Loose</A>
+</DL><p>
+"#;
+
+    #[test]
+    fn test_parse_netscape_keeps_folders() -> Result<()> {
+        let bookmarks = parse(NETSCAPE)?;
+
+        assert_eq!(bookmarks.len(), 3);
+        assert_eq!(bookmarks[0].folders, vec!["Bookmarks bar"]);
+        assert_eq!(
+            bookmarks[1],
+            Bookmark {
+                url: "https://doc.rust-lang.org/nomicon/?a=1&b=2".to_string(),
+                title: Some("The Rustonomicon & more".to_string()),
+                folders: vec!["Bookmarks bar".to_string(), "To Learn".to_string()],
+            }
+        );
+        assert!(bookmarks[2].folders.is_empty());
+        assert_eq!(folders(&bookmarks), vec!["Bookmarks bar", "Bookmarks bar/To Learn"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_chrome_and_firefox_json() -> Result<()> {
+        let chrome = r#"{"roots": {"bookmark_bar": {"type": "folder", "name": "Bookmarks bar", "children": [
+            {"type": "folder", "name": "To Learn", "children": [
+                {"type": "url", "name": "Tokio tutorial", "url": "https://tokio.rs/tokio/tutorial"}
+            ]}
+        ]}}, "version": 1}"#;
+        let firefox = r#"{"title": "", "type": "text/x-moz-place-container", "children": [
+            {"title": "menu", "type": "text/x-moz-place-container", "children": [
+                {"title": "To Learn", "type": "text/x-moz-place-container", "children": [
+                    {"title": "Tokio tutorial", "type": "text/x-moz-place", "uri": "https://tokio.rs/tokio/tutorial"},
+                    {"title": "Recent", "type": "text/x-moz-place", "uri": "place:sort=8"}
+                ]}
+            ]}
+        ]}"#;
+
+        let chrome = parse(chrome)?;
+        let firefox = parse(firefox)?;
+
+        assert_eq!(chrome.len(), 1);
+        assert_eq!(chrome[0].title.as_deref(), Some("Tokio tutorial"));
+        assert_eq!(chrome[0].folders, vec!["Bookmarks bar", "To Learn"]);
+        assert_eq!(firefox.len(), 1);
+        assert_eq!(firefox[0].folders, vec!["menu", "To Learn"]);
+        assert!(firefox[0].is_in("to learn"));
+        assert!(firefox[0].is_in("menu/To Learn"));
+        assert!(!firefox[0].is_in("Bookmarks bar/To Learn"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_rejects_other_files() {
+        assert!(parse("just some notes\nwith lines").is_err());
+    }
+}
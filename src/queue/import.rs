@@ -0,0 +1,254 @@
+use crate::config::ImportConfig;
+use crate::http;
+use anyhow::{anyhow, Context, Result};
+use serde_json::Value;
+
+const READWISE_LIST_URL: &str = "https://readwise.io/api/v3/list/?location=later";
+const POCKET_GET_URL: &str = "https://getpocket.com/v3/get";
+
+/// Read-it-later services articles can be imported from
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ImportSource {
+    Readwise,
+    Pocket,
+    Instapaper,
+}
+
+impl ImportSource {
+    pub fn parse(source: &str) -> Result<Self> {
+        match source.to_lowercase().as_str() {
+            "readwise" => Ok(ImportSource::Readwise),
+            "pocket" => Ok(ImportSource::Pocket),
+            "instapaper" => Ok(ImportSource::Instapaper),
+            _ => Err(anyhow!("Invalid import source. Use: readwise, pocket, or instapaper")),
+        }
+    }
+}
+
+impl std::fmt::Display for ImportSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ImportSource::Readwise => write!(f, "readwise"),
+            ImportSource::Pocket => write!(f, "pocket"),
+            ImportSource::Instapaper => write!(f, "instapaper"),
+        }
+    }
+}
+
+/// A saved article
+#[derive(Debug, Clone, PartialEq)]
+pub struct Article {
+    pub url: String,
+    pub title: Option<String>,
+}
+
+/// Fetch the unread articles saved in a read-it-later service
+pub fn fetch(source: ImportSource, config: &ImportConfig) -> Result<Vec<Article>> {
+    match source {
+        ImportSource::Readwise => {
+            let token = require(&config.readwise_token, "readwise_token")?;
+            fetch_readwise(token)
+        }
+        ImportSource::Pocket => {
+            let consumer_key = require(&config.pocket_consumer_key, "pocket_consumer_key")?;
+            let access_token = require(&config.pocket_access_token, "pocket_access_token")?;
+            fetch_pocket(consumer_key, access_token)
+        }
+        ImportSource::Instapaper => {
+            let feed = require(&config.instapaper_feed, "instapaper_feed")?;
+            Ok(parse_rss(&http::get(feed, &[])?))
+        }
+    }
+}
+
+fn require<'a>(value: &'a Option<String>, key: &str) -> Result<&'a str> {
+    value
+        .as_deref()
+        .ok_or_else(|| anyhow!("Missing `{}` under [import] in the config file", key))
+}
+
+fn fetch_readwise(token: &str) -> Result<Vec<Article>> {
+    let auth = format!("Token {}", token);
+    let mut articles = Vec::new();
+    let mut cursor: Option<String> = None;
+
+    loop {
+        let url = match cursor {
+            Some(ref cursor) => format!("{}&pageCursor={}", READWISE_LIST_URL, cursor),
+            None => READWISE_LIST_URL.to_string(),
+        };
+        let body = http::get(&url, &[("Authorization", &auth)])?;
+        let (page, next) = parse_readwise(&body)?;
+        articles.extend(page);
+
+        match next {
+            Some(next) => cursor = Some(next),
+            None => return Ok(articles),
+        }
+    }
+}
+
+/// Articles on one page of the Readwise Reader list API, plus the next page cursor
+fn parse_readwise(body: &str) -> Result<(Vec<Article>, Option<String>)> {
+    let json: Value = serde_json::from_str(body).context("Failed to parse Readwise response")?;
+
+    let articles = json["results"]
+        .as_array()
+        .ok_or_else(|| anyhow!("Unexpected Readwise response: no results"))?
+        .iter()
+        .filter_map(|doc| {
+            let url = doc["source_url"].as_str().or_else(|| doc["url"].as_str())?;
+            Some(Article {
+                url: url.to_string(),
+                title: non_empty(doc["title"].as_str()),
+            })
+        })
+        .collect();
+
+    let next = non_empty(json["nextPageCursor"].as_str());
+    Ok((articles, next))
+}
+
+fn fetch_pocket(consumer_key: &str, access_token: &str) -> Result<Vec<Article>> {
+    let request = serde_json::json!({
+        "consumer_key": consumer_key,
+        "access_token": access_token,
+        "state": "unread",
+        "detailType": "simple",
+        "sort": "oldest",
+    });
+    let body = http::post_json(POCKET_GET_URL, &[("X-Accept", "application/json")], &request)?;
+    parse_pocket(&body)
+}
+
+/// Articles in a Pocket `/v3/get` response
+fn parse_pocket(body: &str) -> Result<Vec<Article>> {
+    let json: Value = serde_json::from_str(body).context("Failed to parse Pocket response")?;
+
+    // An empty list comes back as `[]` rather than `{}`
+    let Some(list) = json["list"].as_object() else {
+        return Ok(Vec::new());
+    };
+
+    let mut items: Vec<&Value> = list.values().collect();
+    items.sort_by_key(|item| item["sort_id"].as_u64().unwrap_or(u64::MAX));
+
+    Ok(items
+        .into_iter()
+        .filter_map(|item| {
+            let url = non_empty(item["resolved_url"].as_str()).or_else(|| non_empty(item["given_url"].as_str()))?;
+            let title = non_empty(item["resolved_title"].as_str()).or_else(|| non_empty(item["given_title"].as_str()));
+            Some(Article { url, title })
+        })
+        .collect())
+}
+
+/// Articles in an RSS feed, such as Instapaper's private unread feed
+fn parse_rss(feed: &str) -> Vec<Article> {
+    feed.split("<item>")
+        .skip(1)
+        .filter_map(|item| {
+            let item = item.split("</item>").next()?;
+            let url = non_empty(tag_text(item, "link").as_deref())?;
+            Some(Article {
+                url,
+                title: non_empty(tag_text(item, "title").as_deref()),
+            })
+        })
+        .collect()
+}
+
+fn tag_text(xml: &str, tag: &str) -> Option<String> {
+    let start = xml.find(&format!("<{}>", tag))? + tag.len() + 2;
+    let end = start + xml[start..].find(&format!("</{}>", tag))?;
+    let text = xml[start..end].trim();
+    let text = text
+        .strip_prefix("<![CDATA[")
+        .and_then(|t| t.strip_suffix("]]>"))
+        .unwrap_or(text);
+
+    Some(unescape(text))
+}
+
+/// Decode the entities HTML and XML exports escape text with
+pub(super) fn unescape(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
+
+fn non_empty(value: Option<&str>) -> Option<String> {
+    value.map(str::trim).filter(|v| !v.is_empty()).map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_import_source_parse() -> Result<()> {
+        assert_eq!(ImportSource::parse("Pocket")?, ImportSource::Pocket);
+        assert!(ImportSource::parse("delicious").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_readwise() -> Result<()> {
+        let body = r#"{"count":2,"nextPageCursor":"abc","results":[
+            {"source_url":"https://a.com/post","url":"https://read.readwise.io/1","title":"A Post"},
+            {"url":"https://read.readwise.io/2","title":""}
+        ]}"#;
+
+        let (articles, next) = parse_readwise(body)?;
+
+        assert_eq!(
+            articles,
+            vec![
+                Article {
+                    url: "https://a.com/post".to_string(),
+                    title: Some("A Post".to_string()),
+                },
+                Article {
+                    url: "https://read.readwise.io/2".to_string(),
+                    title: None,
+                },
+            ]
+        );
+        assert_eq!(next.as_deref(), Some("abc"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_pocket() -> Result<()> {
+        let body = r#"{"status":1,"list":{
+            "2":{"sort_id":1,"given_url":"http://b.com","given_title":"B","resolved_url":"","resolved_title":""},
+            "1":{"sort_id":0,"given_url":"http://a.com","resolved_url":"https://a.com/","resolved_title":"A"}
+        }}"#;
+
+        let articles = parse_pocket(body)?;
+
+        assert_eq!(articles.len(), 2);
+        assert_eq!(articles[0].url, "https://a.com/");
+        assert_eq!(articles[1].title.as_deref(), Some("B"));
+        assert!(parse_pocket(r#"{"status":2,"list":[]}"#)?.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_rss() {
+        let feed = "<rss><channel><title>Instapaper: Unread</title>\
+            <item><title><![CDATA[Tips & Tricks]]></title><link>https://a.com/?x=1&amp;y=2</link></item>\
+            <item><title>No link</title></item>\
+            </channel></rss>";
+
+        assert_eq!(
+            parse_rss(feed),
+            vec![Article {
+                url: "https://a.com/?x=1&y=2".to_string(),
+                title: Some("Tips & Tricks".to_string()),
+            }]
+        );
+    }
+}
@@ -0,0 +1,234 @@
+pub mod bookmarks;
+pub mod import;
+pub mod title;
+
+use crate::session::{LearningMode, Session};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+/// An article waiting to be turned into a `/link` session, or a captured snippet waiting to be
+/// turned into a deep dive
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QueueItem {
+    /// Empty for a snippet
+    #[serde(default)]
+    pub url: String,
+    pub title: Option<String>,
+    pub source: String,
+    pub added: DateTime<Local>,
+    /// Text sent to `holocron serve --capture` instead of a link
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+}
+
+impl QueueItem {
+    /// What the queue lists it as: its title, its URL, or the start of its snippet
+    pub fn label(&self) -> &str {
+        match (self.title.as_deref(), self.text.as_deref()) {
+            (Some(title), _) => title,
+            (None, Some(text)) => text.lines().next().unwrap_or_default(),
+            (None, None) => &self.url,
+        }
+    }
+
+    /// Look up the page's title if the item has a link but no title yet
+    pub fn fetch_title(&mut self) {
+        if self.title.is_none() && !self.url.is_empty() {
+            self.title = title::fetch(&self.url);
+        }
+    }
+
+    /// What tells two items apart: the normalized URL, or the snippet itself
+    pub fn key(&self) -> String {
+        match self.text {
+            Some(ref text) if self.url.is_empty() => text.trim().to_lowercase(),
+            _ => normalize_url(&self.url),
+        }
+    }
+}
+
+/// The learning queue, oldest first
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Queue {
+    #[serde(default)]
+    pub items: Vec<QueueItem>,
+}
+
+impl Queue {
+    /// Load the queue, returning an empty queue if the file doesn't exist
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read queue from {:?}", path))?;
+        toml::from_str(&content).with_context(|| "Failed to parse queue")
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create directory {:?}", parent))?;
+        }
+
+        let content = toml::to_string_pretty(self).with_context(|| "Failed to serialize queue")?;
+        fs::write(path, content).with_context(|| format!("Failed to write queue to {:?}", path))
+    }
+
+    /// Add items whose URL isn't already queued or processed, returning how many were added
+    pub fn enqueue(&mut self, items: Vec<QueueItem>, processed: &HashSet<String>) -> usize {
+        let mut seen: HashSet<String> = self.items.iter().map(QueueItem::key).collect();
+        seen.extend(processed.iter().map(|url| normalize_url(url)));
+
+        let before = self.items.len();
+        for item in items {
+            if seen.insert(item.key()) {
+                self.items.push(item);
+            }
+        }
+        self.items.len() - before
+    }
+
+    /// Fetch titles for up to `limit` links queued without one, so the queue file keeps them.
+    /// Returns how many were found.
+    pub fn fill_titles(&mut self, limit: usize) -> usize {
+        let mut found = 0;
+        for item in self.items.iter_mut().filter(|item| item.title.is_none() && !item.url.is_empty()).take(limit) {
+            item.fetch_title();
+            found += usize::from(item.title.is_some());
+        }
+        found
+    }
+
+    /// Take the oldest queued item
+    pub fn pop(&mut self) -> Option<QueueItem> {
+        if self.items.is_empty() {
+            None
+        } else {
+            Some(self.items.remove(0))
+        }
+    }
+}
+
+/// URLs already studied in a `/link` session
+pub fn processed_urls(sessions: &[Session]) -> HashSet<String> {
+    sessions
+        .iter()
+        .filter_map(|session| match session.mode {
+            LearningMode::Link { ref url } | LearningMode::Video { ref url, .. } => Some(url.clone()),
+            LearningMode::DeepDive { .. }
+            | LearningMode::Audio { .. }
+            | LearningMode::Compare { .. }
+            | LearningMode::Image { .. }
+            | LearningMode::ShellRecap { .. } => None,
+        })
+        .collect()
+}
+
+/// Reduce a URL to a form that compares equal across trivial differences
+pub fn normalize_url(url: &str) -> String {
+    let url = url.trim();
+    let url = url.split_once('#').map_or(url, |(base, _)| base);
+    let url = url.trim_end_matches('/');
+    let url = url
+        .strip_prefix("https://")
+        .or_else(|| url.strip_prefix("http://"))
+        .unwrap_or(url);
+    let url = url.strip_prefix("www.").unwrap_or(url);
+    url.to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn item(url: &str) -> QueueItem {
+        QueueItem {
+            url: url.to_string(),
+            title: None,
+            source: "pocket".to_string(),
+            added: Local::now(),
+            text: None,
+        }
+    }
+
+    fn snippet(text: &str) -> QueueItem {
+        QueueItem {
+            url: String::new(),
+            text: Some(text.to_string()),
+            source: "capture".to_string(),
+            ..item("")
+        }
+    }
+
+    #[test]
+    fn test_normalize_url() {
+        assert_eq!(normalize_url("https://www.Example.com/post/#intro"), "example.com/post");
+        assert_eq!(normalize_url("http://example.com/post"), "example.com/post");
+    }
+
+    #[test]
+    fn test_enqueue_dedupes() {
+        let mut queue = Queue::default();
+        queue.enqueue(vec![item("https://a.com/1")], &HashSet::new());
+
+        let processed: HashSet<String> = ["https://b.com/2/".to_string()].into();
+        let added = queue.enqueue(
+            vec![
+                item("http://a.com/1"),
+                item("https://b.com/2"),
+                item("https://c.com/3"),
+                item("https://c.com/3#again"),
+            ],
+            &processed,
+        );
+
+        assert_eq!(added, 1);
+        assert_eq!(queue.items.len(), 2);
+        assert_eq!(queue.pop().map(|i| i.url), Some("https://a.com/1".to_string()));
+    }
+
+    #[test]
+    fn test_enqueue_snippets() {
+        let mut queue = Queue::default();
+        let added = queue.enqueue(
+            vec![snippet("Why does VACUUM exist?\nSaw it in a talk"), snippet("why does vacuum exist?\nsaw it in a talk ")],
+            &HashSet::new(),
+        );
+        let added = added + queue.enqueue(vec![snippet("Raft log compaction")], &HashSet::new());
+
+        assert_eq!(added, 2);
+        assert_eq!(queue.items[0].label(), "Why does VACUUM exist?");
+        assert_eq!(item("https://a.com/1").label(), "https://a.com/1");
+    }
+
+    #[test]
+    fn test_processed_urls() {
+        let sessions = vec![
+            Session::new(LearningMode::Link { url: "https://a.com".to_string() }, None),
+            Session::new(LearningMode::DeepDive { topic: "rust".to_string() }, None),
+        ];
+        assert_eq!(processed_urls(&sessions), ["https://a.com".to_string()].into());
+    }
+
+    #[test]
+    fn test_queue_roundtrip() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let path = temp_dir.path().join("queue.toml");
+
+        let mut queue = Queue::default();
+        queue.enqueue(vec![item("https://a.com/1")], &HashSet::new());
+        queue.save(&path)?;
+
+        let loaded = Queue::load(&path)?;
+        assert_eq!(loaded.items, queue.items);
+
+        Ok(())
+    }
+}
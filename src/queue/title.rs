@@ -0,0 +1,67 @@
+use super::import::unescape;
+use crate::http;
+use regex::Regex;
+
+/// Give up on a slow page rather than hold up the queue or a session
+const TIMEOUT_SECS: &str = "5";
+
+/// Pages bigger than this are skipped; the title is near the top of any sensible page
+const MAX_BYTES: &str = "2000000";
+
+/// Longer titles are cut, since some pages stuff their whole pitch into them
+const MAX_CHARS: usize = 120;
+
+/// A page's title, fetched with a short timeout. `None` when it isn't a web page, can't be
+/// fetched in time or has no title.
+pub fn fetch(url: &str) -> Option<String> {
+    if !url.starts_with("http://") && !url.starts_with("https://") {
+        return None;
+    }
+    let limits = ["--max-time", TIMEOUT_SECS, "--max-filesize", MAX_BYTES].map(str::to_string);
+    let html = http::request("GET", url, &[("Accept", "text/html")], None, &limits).ok()?;
+    parse(&html)
+}
+
+/// The page's `<title>`, or its `og:title` when the title is missing or empty, on one line
+pub fn parse(html: &str) -> Option<String> {
+    let title = Regex::new(r"(?is)<title[^>]*>(.*?)</title>").ok()?;
+    let og_title = Regex::new(r#"(?is)<meta\s[^>]*property=["']og:title["'][^>]*content=["']([^"']*)["']"#).ok()?;
+
+    [title, og_title]
+        .iter()
+        .filter_map(|pattern| pattern.captures(html))
+        .map(|captures| clean(&captures[1]))
+        .find(|title| !title.is_empty())
+}
+
+fn clean(raw: &str) -> String {
+    let title = unescape(raw).split_whitespace().collect::<Vec<_>>().join(" ");
+    match title.char_indices().nth(MAX_CHARS) {
+        Some((end, _)) => format!("{}…", title[..end].trim_end()),
+        None => title,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_title() {
+        let html = "<html><head>\n<TITLE lang=\"en\">\n  Pin &amp; Unpin\n  in Rust </TITLE></head>";
+        assert_eq!(parse(html).as_deref(), Some("Pin & Unpin in Rust"));
+
+        let og = r#"<title> </title><meta property="og:title" content="Async &quot;Rust&quot;">"#;
+        assert_eq!(parse(og).as_deref(), Some("Async \"Rust\""));
+
+        assert_eq!(parse("<p>No title</p>"), None);
+        let long = parse(&format!("<title>{}</title>", "word ".repeat(50))).unwrap_or_default();
+        assert_eq!(long.chars().count(), MAX_CHARS);
+        assert!(long.ends_with("word…"));
+    }
+
+    #[test]
+    fn test_fetch_skips_non_web_urls() {
+        assert_eq!(fetch("file:///etc/hosts"), None);
+    }
+}
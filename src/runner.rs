@@ -0,0 +1,221 @@
+use anyhow::{anyhow, Context, Result};
+use std::fmt;
+use std::fs::{self, File};
+use std::path::Path;
+use std::process::{Command, ExitStatus};
+use std::time::{Duration, Instant};
+
+/// Examples that run longer than this are stopped
+const RUN_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// How much output is sent back into the conversation
+const MAX_SHARED_OUTPUT: usize = 4000;
+
+/// A fenced code block from a response
+#[derive(Debug, Clone, PartialEq)]
+pub struct CodeBlock {
+    /// The fence's language tag, lowercased (empty if none)
+    pub language: String,
+    pub code: String,
+}
+
+/// What `/run` can execute an example with
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Runner {
+    Bash,
+    Python,
+    /// A single-file Rust program via `cargo +nightly -Zscript`
+    CargoScript,
+}
+
+pub const RUNNERS: [Runner; 3] = [Runner::Bash, Runner::Python, Runner::CargoScript];
+
+impl Runner {
+    /// The runner a fence's language tag suggests
+    pub fn for_language(language: &str) -> Option<Self> {
+        match language {
+            "bash" | "sh" | "shell" | "zsh" | "console" => Some(Runner::Bash),
+            "python" | "py" | "python3" => Some(Runner::Python),
+            "rust" | "rs" => Some(Runner::CargoScript),
+            _ => None,
+        }
+    }
+
+    fn file_name(&self) -> &'static str {
+        match self {
+            Runner::Bash => "example.sh",
+            Runner::Python => "example.py",
+            Runner::CargoScript => "example.rs",
+        }
+    }
+
+    fn command(&self, script: &Path) -> Command {
+        let mut command = match self {
+            Runner::Bash => Command::new("bash"),
+            Runner::Python => Command::new("python3"),
+            Runner::CargoScript => {
+                let mut cargo = Command::new("cargo");
+                cargo.args(["+nightly", "-Zscript", "--quiet"]);
+                cargo
+            }
+        };
+        command.arg(script);
+        command
+    }
+}
+
+impl fmt::Display for Runner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Runner::Bash => write!(f, "bash"),
+            Runner::Python => write!(f, "python"),
+            Runner::CargoScript => write!(f, "cargo script"),
+        }
+    }
+}
+
+/// The result of running an example
+#[derive(Debug)]
+pub struct RunOutput {
+    /// None if the run was stopped for taking too long
+    pub status: Option<ExitStatus>,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+impl RunOutput {
+    pub fn describe_status(&self) -> String {
+        match self.status {
+            Some(status) if status.success() => "exited successfully".to_string(),
+            Some(status) => format!("failed ({})", status),
+            None => format!("was stopped after {} seconds", RUN_TIMEOUT.as_secs()),
+        }
+    }
+
+    /// A message reporting the run back to the backend
+    pub fn build_feedback_prompt(&self, runner: Runner) -> String {
+        let mut output = format!("{}{}", self.stdout, self.stderr);
+        if output.len() > MAX_SHARED_OUTPUT {
+            let mut cut = MAX_SHARED_OUTPUT;
+            while !output.is_char_boundary(cut) {
+                cut -= 1;
+            }
+            output.truncate(cut);
+            output.push_str("\n[output truncated]");
+        }
+
+        format!(
+            r#"I ran your last code example with {} and it {}. This was the output:
+
+```
+{}
+```
+
+Explain what the output shows. If it failed or differs from what you expected, explain why and how to fix it."#,
+            runner,
+            self.describe_status(),
+            output.trim_end()
+        )
+    }
+}
+
+/// The last fenced code block in the text, if any
+pub fn last_code_block(text: &str) -> Option<CodeBlock> {
+    let mut last = None;
+    let mut open: Option<(String, Vec<&str>)> = None;
+
+    for line in text.lines() {
+        let trimmed = line.trim_start();
+        match open.take() {
+            None => {
+                if let Some(tag) = trimmed.strip_prefix("```") {
+                    open = Some((tag.trim().to_lowercase(), Vec::new()));
+                }
+            }
+            Some((language, lines)) if trimmed.trim_end() == "```" => {
+                last = Some(CodeBlock {
+                    language,
+                    code: lines.join("\n"),
+                });
+            }
+            Some((language, mut lines)) => {
+                lines.push(line);
+                open = Some((language, lines));
+            }
+        }
+    }
+    last
+}
+
+/// Run an example in a fresh temp directory, stopping it after a minute
+pub fn run(runner: Runner, code: &str) -> Result<RunOutput> {
+    let dir = tempfile::TempDir::new()?;
+    let script = dir.path().join(runner.file_name());
+    fs::write(&script, code).with_context(|| format!("Failed to write {:?}", script))?;
+
+    // Output goes to files so a chatty example can't block on a full pipe
+    let (stdout_path, stderr_path) = (dir.path().join(".stdout"), dir.path().join(".stderr"));
+    let mut child = runner
+        .command(&script)
+        .current_dir(dir.path())
+        .stdin(std::process::Stdio::null())
+        .stdout(File::create(&stdout_path)?)
+        .stderr(File::create(&stderr_path)?)
+        .spawn()
+        .map_err(|e| anyhow!("Couldn't start {}: {}", runner, e))?;
+
+    let started = Instant::now();
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break Some(status);
+        }
+        if started.elapsed() > RUN_TIMEOUT {
+            child.kill().ok();
+            child.wait().ok();
+            break None;
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    };
+
+    Ok(RunOutput {
+        status,
+        stdout: String::from_utf8_lossy(&fs::read(&stdout_path)?).to_string(),
+        stderr: String::from_utf8_lossy(&fs::read(&stderr_path)?).to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_last_code_block() {
+        let text = "First:\n```rust\nfn main() {}\n```\nThen:\n```Python\nprint('hi')\n  x = 1\n```\nDone.";
+        assert_eq!(
+            last_code_block(text),
+            Some(CodeBlock {
+                language: "python".to_string(),
+                code: "print('hi')\n  x = 1".to_string(),
+            })
+        );
+        assert_eq!(last_code_block("no code here"), None);
+        assert_eq!(last_code_block("```bash\nunterminated"), None);
+    }
+
+    #[test]
+    fn test_runner_for_language() {
+        assert_eq!(Runner::for_language("sh"), Some(Runner::Bash));
+        assert_eq!(Runner::for_language("rust"), Some(Runner::CargoScript));
+        assert_eq!(Runner::for_language("haskell"), None);
+    }
+
+    #[test]
+    fn test_run_bash() -> Result<()> {
+        let output = run(Runner::Bash, "echo out; echo err >&2; exit 3")?;
+        assert_eq!(output.stdout, "out\n");
+        assert_eq!(output.stderr, "err\n");
+        assert_eq!(output.status.and_then(|s| s.code()), Some(3));
+        assert!(output.build_feedback_prompt(Runner::Bash).contains("it failed"));
+        Ok(())
+    }
+}
@@ -0,0 +1,170 @@
+use crate::output;
+use crate::theme::Themed;
+use anyhow::Result;
+use colored::*;
+use console::Term;
+
+/// What to do with a generated TIL or note
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SaveAction {
+    Save,
+    Edit,
+    Regenerate,
+    /// Regenerate with typed feedback, e.g. "shorter"
+    Revise,
+    /// Fact-check against the session
+    Verify,
+    ChangeCategory,
+    Preview,
+    Later,
+    Discard,
+}
+
+/// Actions offered for a freshly generated TIL
+pub const TIL_ACTIONS: &[SaveAction] = &[
+    SaveAction::Save,
+    SaveAction::Edit,
+    SaveAction::Regenerate,
+    SaveAction::Revise,
+    SaveAction::Verify,
+    SaveAction::ChangeCategory,
+    SaveAction::Preview,
+    SaveAction::Discard,
+];
+
+/// Actions offered for a freshly generated note
+pub const NOTE_ACTIONS: &[SaveAction] = &[
+    SaveAction::Save,
+    SaveAction::Edit,
+    SaveAction::Regenerate,
+    SaveAction::Preview,
+    SaveAction::Discard,
+];
+
+/// Actions offered for an existing TIL grown with a session's learning
+pub const APPEND_ACTIONS: &[SaveAction] = &[
+    SaveAction::Save,
+    SaveAction::Edit,
+    SaveAction::Regenerate,
+    SaveAction::Preview,
+    SaveAction::Discard,
+];
+
+/// Actions offered when reviewing a drafted TIL
+pub const DRAFT_ACTIONS: &[SaveAction] = &[
+    SaveAction::Save,
+    SaveAction::Edit,
+    SaveAction::ChangeCategory,
+    SaveAction::Preview,
+    SaveAction::Later,
+    SaveAction::Discard,
+];
+
+impl SaveAction {
+    /// Map a keypress to one of the available actions. Enter means save.
+    pub fn from_key(key: char, available: &[SaveAction]) -> Option<Self> {
+        let key = match key {
+            '\n' | '\r' => 's',
+            key => key.to_ascii_lowercase(),
+        };
+        available.iter().copied().find(|action| action.key() == key)
+    }
+
+    fn key(self) -> char {
+        match self {
+            SaveAction::Save => 's',
+            SaveAction::Edit => 'e',
+            SaveAction::Regenerate => 'r',
+            SaveAction::Revise => 'v',
+            SaveAction::Verify => 'f',
+            SaveAction::ChangeCategory => 'c',
+            SaveAction::Preview => 'p',
+            SaveAction::Later => 'l',
+            SaveAction::Discard => 'd',
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            SaveAction::Save => "[s]ave",
+            SaveAction::Edit => "[e]dit",
+            SaveAction::Regenerate => "[r]egenerate",
+            SaveAction::Revise => "re[v]ise",
+            SaveAction::Verify => "[f]act-check",
+            SaveAction::ChangeCategory => "[c]ategory",
+            SaveAction::Preview => "[p]review",
+            SaveAction::Later => "[l]ater",
+            SaveAction::Discard => "[d]iscard",
+        }
+    }
+}
+
+/// The one-line key legend, e.g. "[s]ave  [e]dit  [r]egenerate  [p]review  [d]iscard"
+pub fn legend(available: &[SaveAction]) -> String {
+    available
+        .iter()
+        .map(|action| action.label())
+        .collect::<Vec<_>>()
+        .join("  ")
+}
+
+/// Show the target and key legend on one line and wait for a single keypress
+pub fn prompt_save_action(target: &str, available: &[SaveAction]) -> Result<SaveAction> {
+    output::require_terminal()?;
+    let term = Term::stdout();
+
+    loop {
+        print!(
+            "{} {} {} ",
+            "?".warning().bold(),
+            target.bold(),
+            legend(available).muted()
+        );
+        std::io::Write::flush(&mut std::io::stdout()).ok();
+
+        let key = term.read_char()?;
+        println!("{}", key);
+
+        if let Some(action) = SaveAction::from_key(key, available) {
+            return Ok(action);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_key() {
+        assert_eq!(SaveAction::from_key('s', NOTE_ACTIONS), Some(SaveAction::Save));
+        assert_eq!(SaveAction::from_key('\n', NOTE_ACTIONS), Some(SaveAction::Save));
+        assert_eq!(SaveAction::from_key('E', NOTE_ACTIONS), Some(SaveAction::Edit));
+        assert_eq!(SaveAction::from_key('r', NOTE_ACTIONS), Some(SaveAction::Regenerate));
+        assert_eq!(SaveAction::from_key('p', NOTE_ACTIONS), Some(SaveAction::Preview));
+        assert_eq!(SaveAction::from_key('d', NOTE_ACTIONS), Some(SaveAction::Discard));
+        assert_eq!(SaveAction::from_key('x', NOTE_ACTIONS), None);
+    }
+
+    #[test]
+    fn test_from_key_category_only_when_allowed() {
+        assert_eq!(SaveAction::from_key('c', NOTE_ACTIONS), None);
+        assert_eq!(SaveAction::from_key('c', TIL_ACTIONS), Some(SaveAction::ChangeCategory));
+        assert_eq!(SaveAction::from_key('v', TIL_ACTIONS), Some(SaveAction::Revise));
+        assert_eq!(SaveAction::from_key('v', NOTE_ACTIONS), None);
+        assert_eq!(SaveAction::from_key('f', TIL_ACTIONS), Some(SaveAction::Verify));
+        assert_eq!(SaveAction::from_key('f', DRAFT_ACTIONS), None);
+    }
+
+    #[test]
+    fn test_from_key_draft_actions() {
+        assert_eq!(SaveAction::from_key('l', DRAFT_ACTIONS), Some(SaveAction::Later));
+        assert_eq!(SaveAction::from_key('r', DRAFT_ACTIONS), None);
+    }
+
+    #[test]
+    fn test_legend() {
+        assert_eq!(legend(NOTE_ACTIONS), "[s]ave  [e]dit  [r]egenerate  [p]review  [d]iscard");
+        assert!(legend(TIL_ACTIONS).contains("[c]ategory"));
+    }
+}
@@ -0,0 +1,128 @@
+use crate::archive::Entry;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+
+/// Common words that say nothing about a topic
+const STOPWORDS: &[&str] = &[
+    "the", "and", "for", "are", "but", "not", "you", "your", "with", "this", "that", "from", "have",
+    "has", "was", "were", "will", "can", "how", "what", "when", "where", "which", "who", "why",
+    "use", "using", "into", "its", "it's", "then", "than", "them", "they", "there", "these",
+    "those", "also", "just", "like", "more", "most", "some", "such", "only", "other", "about",
+    "over", "out", "all", "any", "each", "one", "two", "get", "set", "let", "our", "his", "her",
+    "she", "him", "been", "being", "would", "could", "should", "does", "did", "doing", "here",
+    "very", "make", "way", "want", "need",
+];
+
+/// Keyword index over archive entries (titles and bodies) for relevance lookups
+pub struct SearchIndex {
+    docs: Vec<Doc>,
+}
+
+struct Doc {
+    entry: Entry,
+    title_terms: HashSet<String>,
+    body_terms: HashMap<String, u32>,
+}
+
+impl SearchIndex {
+    /// Index the given entries, reading each file's body. Unreadable files are indexed by title only.
+    pub fn build(entries: Vec<Entry>) -> Self {
+        let docs = entries
+            .into_iter()
+            .map(|entry| {
+                let body = fs::read_to_string(&entry.path).unwrap_or_default();
+                let mut body_terms = HashMap::new();
+                for term in terms(&body) {
+                    *body_terms.entry(term).or_insert(0) += 1;
+                }
+
+                Doc {
+                    title_terms: terms(&entry.title).into_iter().collect(),
+                    body_terms,
+                    entry,
+                }
+            })
+            .collect();
+
+        Self { docs }
+    }
+
+    /// Entries most related to `text`, best first. Title matches count more than body matches.
+    pub fn related(&self, text: &str, limit: usize) -> Vec<&Entry> {
+        let query: HashSet<String> = terms(text).into_iter().collect();
+
+        let mut scored: Vec<(u32, &Doc)> = self
+            .docs
+            .iter()
+            .map(|doc| {
+                let score = query
+                    .iter()
+                    .map(|term| {
+                        let title = if doc.title_terms.contains(term) { 5 } else { 0 };
+                        let body = doc.body_terms.get(term).copied().unwrap_or(0).min(3);
+                        title + body
+                    })
+                    .sum();
+                (score, doc)
+            })
+            .filter(|(score, _)| *score > 0)
+            .collect();
+
+        scored.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.entry.title.cmp(&b.1.entry.title)));
+        scored.into_iter().take(limit).map(|(_, doc)| &doc.entry).collect()
+    }
+}
+
+/// Lowercased words of at least three characters, minus stopwords
+pub fn terms(text: &str) -> Vec<String> {
+    text.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| w.len() >= 3 && !STOPWORDS.contains(w))
+        .map(|w| w.to_string())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::archive::EntryKind;
+    use anyhow::Result;
+    use std::path::Path;
+    use tempfile::TempDir;
+
+    fn entry(dir: &Path, title: &str, body: &str) -> Result<Entry> {
+        let path = dir.join(format!("{}.md", title.replace(' ', "_")));
+        fs::write(&path, body)?;
+        Ok(Entry {
+            kind: EntryKind::Til,
+            title: title.to_string(),
+            path,
+            category: None,
+            reading_minutes: 1,
+            level: None,
+        })
+    }
+
+    #[test]
+    fn test_terms() {
+        assert_eq!(terms("How to use the Git rebase --onto flag"), vec!["git", "rebase", "onto", "flag"]);
+    }
+
+    #[test]
+    fn test_related_ranks_title_matches_first() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let index = SearchIndex::build(vec![
+            entry(temp_dir.path(), "Squash Commits", "Use an interactive rebase to squash.")?,
+            entry(temp_dir.path(), "Rebase Onto", "Move a branch with rebase --onto.")?,
+            entry(temp_dir.path(), "Postgres Indexes", "Create an index concurrently.")?,
+        ]);
+
+        let related = index.related("I'm confused about rebase", 5);
+        let titles: Vec<&str> = related.iter().map(|e| e.title.as_str()).collect();
+
+        assert_eq!(titles, vec!["Rebase Onto", "Squash Commits"]);
+        assert!(index.related("kubernetes", 5).is_empty());
+
+        Ok(())
+    }
+}
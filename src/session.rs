@@ -1,9 +1,62 @@
+use crate::claude::transcripts::Conversation;
+use crate::claude::{self, ClaudeResponse, Usage};
+use crate::markdown;
+use crate::notes::outline::Outline;
+use crate::plan::PlanStep;
+use crate::queue;
+use crate::til::recall::Recall;
+use crate::timebox::Timebox;
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
 use std::fmt;
+use std::path::PathBuf;
 
-#[derive(Debug, Clone, PartialEq)]
+/// Default size of the conversation handed to TIL and note generation, in estimated tokens
+pub const DEFAULT_CONTEXT_TOKENS: usize = 6000;
+
+/// Exchanges too old to fit verbatim are condensed to about this many tokens each
+const CONDENSED_TOKENS: usize = 80;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum LearningMode {
     DeepDive { topic: String },
     Link { url: String },
+    /// A transcribed recording, by file name
+    Audio { file: String },
+    /// A video analyzed from its captions
+    Video { url: String, title: String },
+    /// Two technologies or approaches side by side, as "a vs b"
+    Compare { topic: String },
+    /// A slide, diagram or screenshot, by file name
+    Image { file: String },
+    /// Commands from the shell history, e.g. "commands run today"
+    ShellRecap { topic: String },
+}
+
+impl LearningMode {
+    /// What the session was started on, before any rename or page title
+    pub fn subject(&self) -> &str {
+        match self {
+            LearningMode::DeepDive { topic } => topic,
+            LearningMode::Link { url } => url,
+            LearningMode::Audio { file } => file,
+            LearningMode::Video { title, .. } => title,
+            LearningMode::Compare { topic } => topic,
+            LearningMode::Image { file } => file,
+            LearningMode::ShellRecap { topic } => topic,
+        }
+    }
+
+    /// The tools the backend may use in this mode: the web for links, search to back up
+    /// explanations otherwise. Image sessions get their own settings to read just that file.
+    pub fn tools(&self) -> &'static [&'static str] {
+        match self {
+            LearningMode::Link { .. } => &["WebFetch", "WebSearch"],
+            LearningMode::Image { .. } => &[],
+            _ => &["WebSearch"],
+        }
+    }
 }
 
 impl fmt::Display for LearningMode {
@@ -11,31 +64,153 @@ impl fmt::Display for LearningMode {
         match self {
             LearningMode::DeepDive { topic } => write!(f, "Deep Dive: {}", topic),
             LearningMode::Link { url } => write!(f, "Link Analysis: {}", url),
+            LearningMode::Audio { file } => write!(f, "Audio: {}", file),
+            LearningMode::Video { title, .. } => write!(f, "Video: {}", title),
+            LearningMode::Compare { topic } => write!(f, "Compare: {}", topic),
+            LearningMode::Image { file } => write!(f, "Image: {}", file),
+            LearningMode::ShellRecap { topic } => write!(f, "Shell Recap: {}", topic),
         }
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Exchange {
     pub user_message: String,
     pub assistant_response: String,
+    /// Left out of TIL and note generation, via /undo or /exclude
+    #[serde(default)]
+    pub excluded: bool,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Session {
     pub mode: LearningMode,
     pub category: Option<String>,
     pub exchanges: Vec<Exchange>,
     pub claude_session_id: Option<String>,
+    /// Directory the backend conversation lives under, since Claude Code keeps conversations per project
+    #[serde(default)]
+    pub claude_dir: Option<PathBuf>,
+    /// Suggested follow-up questions for the latest response
+    #[serde(skip)]
+    pub followups: Vec<String>,
+    pub started_at: DateTime<Local>,
+    /// Tokens and cost of every backend call made for this session
+    pub usage: Usage,
+    /// TILs and notes saved during this session
+    pub artifacts: Vec<PathBuf>,
+    /// The latest TIL saved from this session, so a note can link back to it
+    #[serde(default)]
+    pub saved_til: Option<PathBuf>,
+    /// The latest note saved from this session, so a TIL can link back to it
+    #[serde(default)]
+    pub saved_note: Option<PathBuf>,
+    /// Existing TIL titles included in the opening prompt (shown by /knowledge)
+    #[serde(default)]
+    pub known_titles: Vec<String>,
+    /// Topic set with /rename-topic, shown and used for the TIL instead of the mode's
+    #[serde(default)]
+    pub renamed_topic: Option<String>,
+    /// The `<title>` of a link session's page, which it goes by instead of the bare URL
+    #[serde(default)]
+    pub page_title: Option<String>,
+    /// Claude settings override sent with every request in the conversation, allowing only the mode's tools
+    #[serde(default)]
+    pub tool_settings: Option<String>,
+    /// URLs cited or fetched during the session, in the order they first came up (shown by /sources)
+    #[serde(default)]
+    pub sources: Vec<String>,
+    /// The plan step this session is studying, checked off when a TIL is saved
+    #[serde(default)]
+    pub plan_step: Option<PlanStep>,
+    /// Time limit set with `learn --timebox`, with the time actually spent once the session ends
+    #[serde(default)]
+    pub timebox: Option<Timebox>,
+    /// When the session's first TIL was saved, for "time to TIL"
+    #[serde(default)]
+    pub til_saved_at: Option<DateTime<Local>>,
+    /// The key concept explained back with /recall, included in the TIL
+    #[serde(default)]
+    pub recall: Option<Recall>,
+    /// Tags set with /tag, finer-grained than the category; added to the TIL's and note's frontmatter
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// The note structure agreed with /outline, followed by the next /note
+    #[serde(default)]
+    pub outline: Option<Outline>,
+    /// The user's own annotations added with /jot. They're never sent in the conversation, only
+    /// with the session's context when a TIL or note is written, which keeps them verbatim.
+    #[serde(default)]
+    pub jots: Vec<String>,
+    /// Whether the session has already been nudged to save a TIL as it wound down
+    #[serde(skip)]
+    pub til_suggested: bool,
+    /// Roughly how many tokens the backend conversation held at its latest reply
+    #[serde(default)]
+    pub context_used: u64,
+    /// Budget for the conversation in `build_til_context`, from the config's `til_context_tokens`
+    #[serde(skip, default = "default_context_tokens")]
+    pub context_tokens: usize,
+}
+
+/// Tags as written in frontmatter: lowercase, with only letters, digits, `-`, `_` and `/`
+fn parse_tags(input: &str) -> Vec<String> {
+    input
+        .split([',', ' ', '\t'])
+        .map(|tag| {
+            tag.trim_start_matches('#')
+                .to_lowercase()
+                .chars()
+                .filter(|c| c.is_alphanumeric() || matches!(c, '-' | '_' | '/'))
+                .collect::<String>()
+        })
+        .filter(|tag| !tag.is_empty())
+        .collect()
+}
+
+fn default_context_tokens() -> usize {
+    DEFAULT_CONTEXT_TOKENS
 }
 
 impl Session {
     pub fn new(mode: LearningMode, category: Option<String>) -> Self {
+        let tool_settings = claude::tools_only_settings(mode.tools());
+        // An article or video is the first source of its own session
+        let sources = match mode {
+            LearningMode::Link { ref url } | LearningMode::Video { ref url, .. } => vec![url.clone()],
+            LearningMode::DeepDive { .. }
+            | LearningMode::Audio { .. }
+            | LearningMode::Compare { .. }
+            | LearningMode::Image { .. }
+            | LearningMode::ShellRecap { .. } => Vec::new(),
+        };
         Self {
             mode,
             category,
             exchanges: Vec::new(),
             claude_session_id: None,
+            claude_dir: None,
+            followups: Vec::new(),
+            started_at: Local::now(),
+            usage: Usage::default(),
+            artifacts: Vec::new(),
+            saved_til: None,
+            saved_note: None,
+            known_titles: Vec::new(),
+            renamed_topic: None,
+            page_title: None,
+            tool_settings: Some(tool_settings),
+            sources,
+            plan_step: None,
+            timebox: None,
+            til_saved_at: None,
+            recall: None,
+            tags: Vec::new(),
+            outline: None,
+            jots: Vec::new(),
+            til_suggested: false,
+            context_used: 0,
+            context_tokens: DEFAULT_CONTEXT_TOKENS,
         }
     }
 
@@ -43,51 +218,318 @@ impl Session {
         self.exchanges.push(Exchange {
             user_message,
             assistant_response,
+            excluded: false,
         });
     }
 
+    /// The exchanges still in use, as a Claude Code conversation for `claude --resume`
+    pub fn claude_conversation(&self) -> Conversation {
+        Conversation {
+            exchanges: self
+                .exchanges
+                .iter()
+                .filter(|exchange| !exchange.excluded)
+                .map(|exchange| (exchange.user_message.clone(), exchange.assistant_response.clone()))
+                .collect(),
+            cwd: None,
+        }
+    }
+
+    /// Add the exchanges of a Claude Code conversation. Returns how many were added.
+    pub fn import_conversation(&mut self, conversation: Conversation) -> usize {
+        let count = conversation.exchanges.len();
+        for (user, assistant) in conversation.exchanges {
+            self.add_exchange(user, assistant);
+        }
+        count
+    }
+
     pub fn set_session_id(&mut self, session_id: String) {
         self.claude_session_id = Some(session_id);
     }
 
-    /// Build context summary for TIL generation
+    /// Where a backend call should pick up this session's conversation
+    pub fn resume(&self) -> claude::Resume<'_> {
+        claude::Resume { session_id: self.claude_session_id.as_deref(), dir: self.claude_dir.as_deref() }
+    }
+
+    pub fn record_usage(&mut self, usage: &Usage) {
+        self.usage.add(usage);
+    }
+
+    /// Record a backend reply to `message`, adopting its session id if this is the first
+    pub fn record_response(&mut self, message: String, response: ClaudeResponse) {
+        if let (None, Some(session_id)) = (&self.claude_session_id, response.session_id) {
+            self.set_session_id(session_id);
+        }
+        // A reply's input covers the whole conversation so far, so it and the reply are what the backend holds
+        if response.usage.total_tokens() > 0 {
+            self.context_used = response.usage.total_tokens();
+        }
+        self.record_usage(&response.usage);
+        self.add_sources(markdown::extract_urls(&response.text));
+        self.add_sources(response.sources);
+        self.add_exchange(message, response.text);
+    }
+
+    /// Remember sources not already collected, ignoring trivial URL differences
+    pub fn add_sources(&mut self, urls: Vec<String>) {
+        for url in urls {
+            let key = queue::normalize_url(&url);
+            if !self.sources.iter().any(|source| queue::normalize_url(source) == key) {
+                self.sources.push(url);
+            }
+        }
+    }
+
+    pub fn add_artifact(&mut self, path: PathBuf) {
+        self.artifacts.push(path);
+    }
+
+    /// Record a TIL saved from this session
+    pub fn record_til(&mut self, path: PathBuf) {
+        self.til_saved_at.get_or_insert_with(Local::now);
+        self.saved_til = Some(path.clone());
+        self.add_artifact(path);
+    }
+
+    /// How long into the session the first TIL was saved
+    pub fn time_to_til(&self) -> Option<chrono::Duration> {
+        self.til_saved_at.map(|saved_at| saved_at - self.started_at)
+    }
+
+    /// Record a note saved from this session
+    pub fn record_note(&mut self, path: PathBuf) {
+        self.saved_note = Some(path.clone());
+        self.add_artifact(path);
+    }
+
+    /// Change what the session is about, e.g. once a deep dive has drifted to a subtopic
+    pub fn rename_topic(&mut self, topic: &str) {
+        self.renamed_topic = Some(topic.trim().to_string());
+    }
+
+    /// Add tags from "consensus, Distributed Systems" style input: split on commas and spaces,
+    /// lowercased, without a leading `#`, skipping ones the session already has
+    pub fn add_tags(&mut self, input: &str) {
+        for tag in parse_tags(input) {
+            if !self.tags.contains(&tag) {
+                self.tags.push(tag);
+            }
+        }
+    }
+
+    /// Add an annotation of the user's own to the session
+    pub fn jot(&mut self, text: &str) {
+        let text = text.trim();
+        if !text.is_empty() {
+            self.jots.push(text.to_string());
+        }
+    }
+
+    pub fn remove_tags(&mut self, input: &str) {
+        let removed = parse_tags(input);
+        self.tags.retain(|tag| !removed.contains(tag));
+    }
+
+    /// Time elapsed since the session started
+    pub fn duration(&self) -> chrono::Duration {
+        Local::now() - self.started_at
+    }
+
+    /// Render the full conversation as markdown, for saving when nothing else was captured
+    pub fn transcript(&self) -> String {
+        let mut transcript = format!(
+            "# {}
+
+*{}*
+",
+            self.mode,
+            self.started_at.format("%Y-%m-%d %H:%M")
+        );
+
+        for exchange in &self.exchanges {
+            transcript.push_str(&format!("\n## You\n\n{}\n", exchange.user_message.trim()));
+            transcript.push_str(&format!("\n## Holocron\n\n{}\n", exchange.assistant_response.trim()));
+        }
+
+        transcript
+    }
+
+    /// Resolve a numbered choice ("1", "2", ...) to a suggested follow-up question
+    pub fn followup(&self, input: &str) -> Option<&str> {
+        let index: usize = input.trim().parse().ok()?;
+        self.followups.get(index.checked_sub(1)?).map(|q| q.as_str())
+    }
+
+    /// Exclude the latest exchange that's still included. Returns its number (1-based).
+    pub fn undo(&mut self) -> Option<usize> {
+        let index = self.exchanges.iter().rposition(|exchange| !exchange.excluded)?;
+        self.exchanges[index].excluded = true;
+        Some(index + 1)
+    }
+
+    /// Exclude an exchange by its number (1-based, as /history shows it)
+    pub fn exclude(&mut self, number: usize) -> Result<()> {
+        let exchange = number
+            .checked_sub(1)
+            .and_then(|index| self.exchanges.get_mut(index))
+            .ok_or_else(|| anyhow!("There's no exchange {}. See /history.", number))?;
+        if exchange.excluded {
+            return Err(anyhow!("Exchange {} is already excluded.", number));
+        }
+        exchange.excluded = true;
+        Ok(())
+    }
+
+    /// Build context summary for TIL generation. Recent exchanges are kept verbatim and older ones
+    /// condensed, so the whole thing stays within `context_tokens`.
     pub fn build_til_context(&self) -> String {
         let mut context = String::new();
 
         context.push_str(&format!("Learning Session: {}\n\n", self.mode));
 
+        if let Some(ref topic) = self.renamed_topic {
+            context.push_str(&format!("Topic: {}\n\n", topic));
+        }
+
         if let Some(ref cat) = self.category {
             context.push_str(&format!("Category: {}\n\n", cat));
         }
 
-        context.push_str("Conversation Summary:\n");
-        for (i, exchange) in self.exchanges.iter().enumerate() {
-            context.push_str(&format!("\n--- Exchange {} ---\n", i + 1));
-            context.push_str(&format!("User: {}\n", exchange.user_message));
-            context.push_str(&format!(
-                "Assistant: {}\n",
-                truncate_for_context(&exchange.assistant_response, 500)
-            ));
+        if !self.tags.is_empty() {
+            context.push_str(&format!("Tags: {}\n\n", self.tags.join(", ")));
+        }
+
+        if !self.jots.is_empty() {
+            context.push_str("User's own notes (work these into the write-up verbatim, in the user's words):\n");
+            for jot in &self.jots {
+                context.push_str(&format!("- {}\n", jot));
+            }
+            context.push('\n');
         }
 
+        context.push_str("Conversation Summary:\n");
+        let budget = self.context_tokens.saturating_sub(estimate_tokens(&context));
+        context.push_str(&self.recap(budget));
+
         context
     }
 
+    /// The session's conversation cut down to `budget` tokens, for picking it up in a later session
+    pub fn recap(&self, budget: usize) -> String {
+        let included: Vec<&Exchange> = self.exchanges.iter().filter(|exchange| !exchange.excluded).collect();
+        budget_exchanges(&included, budget)
+    }
+
     /// Get the main topic/subject of this session
     pub fn topic(&self) -> &str {
+        if let Some(ref topic) = self.renamed_topic {
+            return topic;
+        }
         match &self.mode {
-            LearningMode::DeepDive { topic } => topic,
-            LearningMode::Link { url } => url,
+            LearningMode::Link { url } => self.page_title.as_deref().unwrap_or(url),
+            mode => mode.subject(),
+        }
+    }
+
+    /// The topic as it may go into a prompt. A link session's page title came from the page
+    /// itself, so prompts name the link by its URL instead.
+    pub fn prompt_topic(&self) -> &str {
+        match (&self.renamed_topic, &self.mode) {
+            (None, LearningMode::Link { url }) => url,
+            _ => self.topic(),
+        }
+    }
+}
+
+/// Format a duration compactly, e.g. "42s", "5m 03s" or "1h 12m"
+pub fn format_duration(duration: chrono::Duration) -> String {
+    let secs = duration.num_seconds().max(0);
+    if secs < 60 {
+        format!("{}s", secs)
+    } else if secs < 3600 {
+        format!("{}m {:02}s", secs / 60, secs % 60)
+    } else {
+        format!("{}h {:02}m", secs / 3600, (secs % 3600) / 60)
+    }
+}
+
+/// Roughly how many tokens a text takes, at about four characters per token
+pub fn estimate_tokens(text: &str) -> usize {
+    text.chars().count().div_ceil(4)
+}
+
+/// Fit the exchanges into `budget` tokens. Working back from the newest, exchanges are kept
+/// verbatim until one doesn't fit; that one and everything before it is condensed, and the
+/// oldest are left out once even the condensed versions don't fit.
+fn budget_exchanges(exchanges: &[&Exchange], budget: usize) -> String {
+    let mut blocks = Vec::new();
+    let mut remaining = budget;
+    let mut condensing = false;
+    let mut omitted = 0;
+
+    for (i, exchange) in exchanges.iter().enumerate().rev() {
+        let mut block = format_exchange(i, &exchange.user_message, &exchange.assistant_response);
+        if condensing || estimate_tokens(&block) > remaining {
+            block = if blocks.is_empty() {
+                // The latest exchange matters most, so it gets whatever room there is
+                let header = format_exchange(i, &exchange.user_message, "");
+                let room = remaining.saturating_sub(estimate_tokens(&header) + 1);
+                format_exchange(i, &exchange.user_message, &truncate_for_context(&exchange.assistant_response, room * 4))
+            } else {
+                condensing = true;
+                format_exchange(
+                    i,
+                    &truncate_for_context(&exchange.user_message, CONDENSED_TOKENS * 4),
+                    &condense(&exchange.assistant_response),
+                )
+            };
         }
+
+        let tokens = estimate_tokens(&block);
+        if tokens > remaining {
+            omitted = i + 1;
+            break;
+        }
+        remaining -= tokens;
+        blocks.push(block);
+    }
+
+    let mut text = String::new();
+    if omitted > 0 {
+        text.push_str(&format!("\n({} earlier exchanges left out)\n", omitted));
+    }
+    for block in blocks.iter().rev() {
+        text.push_str(block);
     }
+    text
+}
+
+fn format_exchange(index: usize, user: &str, assistant: &str) -> String {
+    format!("\n--- Exchange {} ---\nUser: {}\nAssistant: {}\n", index + 1, user, assistant)
+}
+
+/// An older answer cut down to its opening paragraph
+fn condense(response: &str) -> String {
+    let opening = response
+        .trim()
+        .split("\n\n")
+        .find(|paragraph| !paragraph.trim().is_empty())
+        .unwrap_or_default();
+    truncate_for_context(opening.trim(), CONDENSED_TOKENS * 4)
 }
 
+/// Cut text to at most `max_len` bytes (plus "..."), on a char boundary
 fn truncate_for_context(s: &str, max_len: usize) -> String {
     if s.len() <= max_len {
-        s.to_string()
-    } else {
-        format!("{}...", &s[..max_len])
+        return s.to_string();
+    }
+    let mut cut = max_len;
+    while !s.is_char_boundary(cut) {
+        cut -= 1;
     }
+    format!("{}...", &s[..cut])
 }
 
 #[cfg(test)]
@@ -137,6 +579,25 @@ mod tests {
         assert_eq!(session.exchanges[0].assistant_response, "Hi there");
     }
 
+    #[test]
+    fn test_session_claude_conversation_round_trip() {
+        let mode = LearningMode::DeepDive {
+            topic: "test".to_string(),
+        };
+        let mut session = Session::new(mode.clone(), None);
+        session.add_exchange("Kept".to_string(), "Yes".to_string());
+        session.add_exchange("Dropped".to_string(), "No".to_string());
+        session.undo();
+
+        let conversation = session.claude_conversation();
+        assert_eq!(conversation.exchanges, vec![("Kept".to_string(), "Yes".to_string())]);
+
+        let mut imported = Session::new(mode, None);
+        assert_eq!(imported.import_conversation(conversation), 1);
+        assert_eq!(imported.exchanges[0].user_message, "Kept");
+        assert_eq!(imported.exchanges[0].assistant_response, "Yes");
+    }
+
     #[test]
     fn test_session_set_session_id() {
         let mode = LearningMode::DeepDive {
@@ -149,6 +610,100 @@ mod tests {
         assert_eq!(session.claude_session_id, Some("abc123".to_string()));
     }
 
+    #[test]
+    fn test_session_resumes_from_its_own_dir() {
+        let mode = LearningMode::DeepDive {
+            topic: "test".to_string(),
+        };
+        let mut first = Session::new(mode.clone(), None);
+        first.set_session_id("abc123".to_string());
+        first.claude_dir = Some(PathBuf::from("/work/project"));
+        let second = Session::new(mode, None);
+
+        assert_eq!(first.resume().session_id, Some("abc123"));
+        assert_eq!(first.resume().dir, Some(std::path::Path::new("/work/project")));
+        assert_eq!(second.resume().dir, None);
+    }
+
+    #[test]
+    fn test_session_collects_sources() {
+        let mode = LearningMode::Link {
+            url: "https://a.com/post".to_string(),
+        };
+        let mut session = Session::new(mode, None);
+        session.record_response(
+            "Q".to_string(),
+            ClaudeResponse {
+                text: "As https://www.a.com/post/ and https://b.com explain...".to_string(),
+                sources: vec!["https://c.com".to_string(), "https://b.com".to_string()],
+                ..ClaudeResponse::default()
+            },
+        );
+        assert_eq!(session.sources, vec!["https://a.com/post", "https://b.com", "https://c.com"]);
+    }
+
+    #[test]
+    fn test_session_record_response_keeps_first_session_id() {
+        let mode = LearningMode::DeepDive {
+            topic: "test".to_string(),
+        };
+        let mut session = Session::new(mode, None);
+        let response = |text: &str, session_id: &str| ClaudeResponse {
+            text: text.to_string(),
+            session_id: Some(session_id.to_string()),
+            usage: Usage {
+                input_tokens: 10,
+                output_tokens: 5,
+                cost_usd: 0.01,
+            },
+            sources: Vec::new(),
+        };
+
+        session.record_response("Q1".to_string(), response("A1", "first"));
+        session.record_response("Q2".to_string(), response("A2", "second"));
+
+        assert_eq!(session.claude_session_id.as_deref(), Some("first"));
+        assert_eq!(session.exchanges.len(), 2);
+        assert_eq!(session.usage.total_tokens(), 30);
+        assert_eq!(session.context_used, 15);
+    }
+
+    #[test]
+    fn test_session_followup() {
+        let mode = LearningMode::DeepDive {
+            topic: "test".to_string(),
+        };
+        let mut session = Session::new(mode, None);
+        session.followups = vec!["First?".to_string(), "Second?".to_string()];
+
+        assert_eq!(session.followup("2"), Some("Second?"));
+        assert_eq!(session.followup("0"), None);
+        assert_eq!(session.followup("3"), None);
+        assert_eq!(session.followup("hello"), None);
+    }
+
+    #[test]
+    fn test_session_transcript() {
+        let mode = LearningMode::DeepDive {
+            topic: "Git".to_string(),
+        };
+        let mut session = Session::new(mode, None);
+        session.add_exchange("What is reflog?".to_string(), "A log of ref updates.\n".to_string());
+
+        let transcript = session.transcript();
+
+        assert!(transcript.starts_with("# Deep Dive: Git\n"));
+        assert!(transcript.contains("\n## You\n\nWhat is reflog?\n"));
+        assert!(transcript.ends_with("\n## Holocron\n\nA log of ref updates.\n"));
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(chrono::Duration::seconds(42)), "42s");
+        assert_eq!(format_duration(chrono::Duration::seconds(303)), "5m 03s");
+        assert_eq!(format_duration(chrono::Duration::seconds(4320)), "1h 12m");
+    }
+
     #[test]
     fn test_session_topic_deep_dive() {
         let mode = LearningMode::DeepDive {
@@ -185,6 +740,55 @@ mod tests {
         assert!(context.contains("Rebase replays commits..."));
     }
 
+    #[test]
+    fn test_renamed_topic_and_category_flow_into_til_context() {
+        let mode = LearningMode::DeepDive {
+            topic: "Git".to_string(),
+        };
+        let mut session = Session::new(mode, Some("git".to_string()));
+        session.rename_topic(" Git reflog ");
+        session.category = Some("tools".to_string());
+
+        assert_eq!(session.topic(), "Git reflog");
+        let context = session.build_til_context();
+        assert!(context.contains("Topic: Git reflog"));
+        assert!(context.contains("Category: tools"));
+        assert!(!context.contains("Category: git"));
+    }
+
+    #[test]
+    fn test_tags() {
+        let mode = LearningMode::DeepDive {
+            topic: "Raft".to_string(),
+        };
+        let mut session = Session::new(mode, Some("distributed".to_string()));
+        session.add_tags("Distributed-Systems, #consensus  raft");
+        session.add_tags("consensus log:replication");
+        assert_eq!(session.tags, vec!["distributed-systems", "consensus", "raft", "logreplication"]);
+
+        session.remove_tags("#raft logreplication");
+        assert_eq!(session.tags, vec!["distributed-systems", "consensus"]);
+        assert!(session.build_til_context().contains("Tags: distributed-systems, consensus\n"));
+    }
+
+    #[test]
+    fn test_jots_go_in_til_context_verbatim() {
+        let mode = LearningMode::DeepDive {
+            topic: "Git".to_string(),
+        };
+        let mut session = Session::new(mode, None);
+        session.jot("  rebase -i saved me twice this week ");
+        session.jot("   ");
+        session.add_exchange("How does rebase work?".to_string(), "Rebase replays commits...".to_string());
+
+        assert_eq!(session.jots, vec!["rebase -i saved me twice this week"]);
+        let context = session.build_til_context();
+        assert!(context.contains("User's own notes"));
+        assert!(context.contains("- rebase -i saved me twice this week\n"));
+        assert!(context.find("rebase -i saved") < context.find("Conversation Summary:"));
+        assert!(!session.exchanges.iter().any(|exchange| exchange.user_message.contains("saved me")));
+    }
+
     #[test]
     fn test_build_til_context_without_category() {
         let mode = LearningMode::Link {
@@ -212,4 +816,58 @@ mod tests {
         assert_eq!(result.len(), 503); // 500 + "..."
         assert!(result.ends_with("..."));
     }
+
+    #[test]
+    fn test_undo_and_exclude() -> Result<()> {
+        let mut session = Session::new(LearningMode::DeepDive { topic: "Rust".to_string() }, None);
+        session.add_exchange("About lifetimes".to_string(), "Lifetimes are...".to_string());
+        session.add_exchange("Off-topic question".to_string(), "Unrelated answer".to_string());
+        session.add_exchange("About borrowing".to_string(), "Borrowing is...".to_string());
+
+        assert_eq!(session.undo(), Some(3));
+        session.exclude(2)?;
+        assert!(session.exclude(2).is_err());
+        assert!(session.exclude(4).is_err());
+        assert!(session.exclude(0).is_err());
+
+        let context = session.build_til_context();
+        assert!(context.contains("About lifetimes"));
+        assert!(!context.contains("Off-topic"));
+        assert!(!context.contains("About borrowing"));
+
+        assert_eq!(session.undo(), Some(1));
+        assert_eq!(session.undo(), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_truncate_for_context_multibyte() {
+        // 'é' is two bytes, so byte 5 falls inside a char
+        assert_eq!(truncate_for_context("ééééé", 5), "éé...");
+    }
+
+    #[test]
+    fn test_build_til_context_budget() {
+        let mut session = Session::new(LearningMode::DeepDive { topic: "Rust".to_string() }, None);
+        for i in 1..=30 {
+            session.add_exchange(
+                format!("Question {}", i),
+                format!("Opening of answer {}.\n\n{}", i, "detail ".repeat(200)),
+            );
+        }
+        session.context_tokens = 2000;
+
+        let context = session.build_til_context();
+        assert!(estimate_tokens(&context) <= 2000);
+        // The newest answers survive whole, older ones are cut to their opening paragraph
+        assert!(context.contains(&format!("Opening of answer 30.\n\n{}", "detail ".repeat(200).trim_end())));
+        assert!(context.contains("Assistant: Opening of answer 20.\n"));
+        assert!(context.contains("earlier exchanges left out"));
+        assert!(!context.contains("Question 1\n"));
+
+        session.context_tokens = DEFAULT_CONTEXT_TOKENS * 10;
+        let context = session.build_til_context();
+        assert!(context.contains("Question 1\n"));
+        assert!(!context.contains("left out"));
+    }
 }
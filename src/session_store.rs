@@ -0,0 +1,207 @@
+use crate::session::Session;
+use crate::til::writer::title_to_filename;
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Duration, Local, NaiveDate, TimeZone};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// File a session is stored under, derived from its start time and what it was started on, so
+/// renaming the topic or finding a page's title doesn't move it
+pub fn session_path(sessions_dir: &Path, session: &Session) -> PathBuf {
+    let slug = title_to_filename(session.mode.subject());
+    let slug = slug.trim_end_matches(".md");
+    sessions_dir.join(format!("{}{}.json", file_prefix(session), slug))
+}
+
+fn file_prefix(session: &Session) -> String {
+    format!("{}-", session.started_at.format("%Y-%m-%d-%H%M%S"))
+}
+
+/// Remove other snapshots of `session`, e.g. ones named after a topic it had before a rename
+fn remove_stale(sessions_dir: &Path, session: &Session, current: &Path) -> Result<()> {
+    let prefix = file_prefix(session);
+    for entry in fs::read_dir(sessions_dir).with_context(|| format!("Failed to read {:?}", sessions_dir))? {
+        let path = entry?.path();
+        let candidate = path.file_name().is_some_and(|name| name.to_string_lossy().starts_with(&prefix));
+        if !candidate || path == current {
+            continue;
+        }
+        let same_session = fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str::<Session>(&content).ok())
+            .is_some_and(|stored| stored.started_at == session.started_at);
+        if same_session {
+            fs::remove_file(&path).with_context(|| format!("Failed to remove {:?}", path))?;
+        }
+    }
+    Ok(())
+}
+
+/// Write the session to the store, overwriting any earlier snapshot of it
+pub fn save(sessions_dir: &Path, session: &Session) -> Result<PathBuf> {
+    fs::create_dir_all(sessions_dir)
+        .with_context(|| format!("Failed to create sessions directory {:?}", sessions_dir))?;
+
+    let path = session_path(sessions_dir, session);
+    let content = serde_json::to_string_pretty(session).context("Failed to serialize session")?;
+    fs::write(&path, content).with_context(|| format!("Failed to write session to {:?}", path))?;
+    remove_stale(sessions_dir, session, &path)?;
+
+    Ok(path)
+}
+
+/// Load every stored session, oldest first. Unreadable files are skipped.
+pub fn load_all(sessions_dir: &Path) -> Result<Vec<Session>> {
+    if !sessions_dir.is_dir() {
+        return Ok(Vec::new());
+    }
+
+    let mut sessions: Vec<Session> = fs::read_dir(sessions_dir)
+        .with_context(|| format!("Failed to read sessions directory {:?}", sessions_dir))?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .filter_map(|path| fs::read_to_string(path).ok())
+        .filter_map(|content| serde_json::from_str(&content).ok())
+        .collect();
+    // Snapshots left behind by older versions after a rename are the same session: keep the fullest
+    sessions.sort_by_key(|s| (s.started_at, std::cmp::Reverse(s.exchanges.len())));
+    sessions.dedup_by_key(|s| s.started_at);
+
+    Ok(sessions)
+}
+
+/// Sessions started at or after `since` that have exchanges but nothing saved from them
+pub fn without_artifacts(sessions: Vec<Session>, since: DateTime<Local>) -> Vec<Session> {
+    sessions
+        .into_iter()
+        .filter(|s| s.started_at >= since && s.artifacts.is_empty() && !s.exchanges.is_empty())
+        .collect()
+}
+
+/// Parse a relative age ("12h", "7d", "2w") or a date (YYYY-MM-DD) into a starting point
+pub fn parse_since(since: &str, now: DateTime<Local>) -> Result<DateTime<Local>> {
+    let since = since.trim();
+
+    if let Ok(date) = NaiveDate::parse_from_str(since, "%Y-%m-%d") {
+        return date
+            .and_hms_opt(0, 0, 0)
+            .and_then(|dt| Local.from_local_datetime(&dt).earliest())
+            .ok_or_else(|| anyhow!("Invalid date: {}", since));
+    }
+
+    let (amount, unit) = since.split_at(since.len().saturating_sub(1));
+    let amount: i64 = amount
+        .parse()
+        .map_err(|_| anyhow!("Invalid --since value {:?}. Use e.g. 12h, 7d, 2w or YYYY-MM-DD", since))?;
+
+    let age = match unit {
+        "h" => Duration::hours(amount),
+        "d" => Duration::days(amount),
+        "w" => Duration::weeks(amount),
+        _ => return Err(anyhow!("Invalid --since unit {:?}. Use h, d or w", unit)),
+    };
+
+    Ok(now - age)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session::LearningMode;
+    use tempfile::TempDir;
+
+    fn session(topic: &str) -> Session {
+        Session::new(
+            LearningMode::DeepDive {
+                topic: topic.to_string(),
+            },
+            None,
+        )
+    }
+
+    #[test]
+    fn test_save_and_load_roundtrip() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let mut sess = session("Git Rebase");
+        sess.add_exchange("q".to_string(), "a".to_string());
+
+        let path = save(temp_dir.path(), &sess)?;
+        assert!(path.to_string_lossy().ends_with("-git_rebase.json"));
+
+        // Saving again overwrites the same snapshot
+        sess.add_exchange("q2".to_string(), "a2".to_string());
+        save(temp_dir.path(), &sess)?;
+
+        let loaded = load_all(temp_dir.path())?;
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded[0].topic(), "Git Rebase");
+        assert_eq!(loaded[0].exchanges.len(), 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_renamed_session_keeps_one_snapshot() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let mut sess = session("Git Rebase");
+        let path = save(temp_dir.path(), &sess)?;
+
+        sess.rename_topic("Interactive rebase");
+        assert_eq!(save(temp_dir.path(), &sess)?, path);
+
+        // A snapshot written under the old topic-based name goes away on the next save
+        let old = temp_dir.path().join(format!("{}old_name.json", file_prefix(&sess)));
+        fs::copy(&path, &old)?;
+        assert_eq!(load_all(temp_dir.path())?.len(), 1);
+        save(temp_dir.path(), &sess)?;
+        assert!(!old.exists());
+
+        let loaded = load_all(temp_dir.path())?;
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded[0].topic(), "Interactive rebase");
+        Ok(())
+    }
+
+    #[test]
+    fn test_load_all_missing_dir() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        assert!(load_all(&temp_dir.path().join("nope"))?.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_without_artifacts() {
+        let mut empty = session("empty");
+        empty.started_at = Local::now();
+
+        let mut saved = session("saved");
+        saved.add_exchange("q".to_string(), "a".to_string());
+        saved.add_artifact(PathBuf::from("/til/a.md"));
+
+        let mut old = session("old");
+        old.add_exchange("q".to_string(), "a".to_string());
+        old.started_at = Local::now() - Duration::days(30);
+
+        let mut pending = session("pending");
+        pending.add_exchange("q".to_string(), "a".to_string());
+
+        let since = Local::now() - Duration::days(7);
+        let result = without_artifacts(vec![empty, saved, old, pending], since);
+
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].topic(), "pending");
+    }
+
+    #[test]
+    fn test_parse_since() -> Result<()> {
+        let now = Local::now();
+        assert_eq!(parse_since("7d", now)?, now - Duration::days(7));
+        assert_eq!(parse_since("12h", now)?, now - Duration::hours(12));
+        assert_eq!(parse_since("2w", now)?, now - Duration::weeks(2));
+        assert_eq!(parse_since("2024-06-01", now)?.format("%Y-%m-%d").to_string(), "2024-06-01");
+        assert!(parse_since("7x", now).is_err());
+        assert!(parse_since("soon", now).is_err());
+        Ok(())
+    }
+}
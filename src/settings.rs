@@ -0,0 +1,145 @@
+use crate::config::{ArchiveLayout, Config, FilenameStyle, NotesFormat};
+use anyhow::{anyhow, Result};
+
+/// How a setting is edited in `/settings`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Kind {
+    /// On or off; choosing it flips it
+    Toggle,
+    /// One of a few values
+    Choice(&'static [&'static str]),
+    /// A whole number, 0 usually meaning off
+    Number,
+    /// Free text, empty for the default
+    Text,
+}
+
+/// A config value that can be changed from inside the REPL
+#[derive(Debug, Clone, Copy)]
+pub struct Setting {
+    /// The key in config.toml
+    pub key: &'static str,
+    pub label: &'static str,
+    pub kind: Kind,
+}
+
+pub const SETTINGS: &[Setting] = &[
+    Setting { key: "notes_format", label: "Notes format", kind: Kind::Choice(&["obsidian", "logseq", "org", "plain"]) },
+    Setting { key: "language", label: "Language", kind: Kind::Text },
+    Setting {
+        key: "filename_style",
+        label: "Filenames",
+        kind: Kind::Choice(&["snake", "kebab", "date_prefixed_kebab", "preserve"]),
+    },
+    Setting { key: "archive_layout", label: "Layout", kind: Kind::Choice(&["flat", "by_year", "by_year_month"]) },
+    Setting { key: "auto_link", label: "Auto link", kind: Kind::Toggle },
+    Setting { key: "suggest_followups", label: "Follow-ups", kind: Kind::Toggle },
+    Setting { key: "inject_archive_context", label: "Known TILs", kind: Kind::Toggle },
+    Setting { key: "suggest_til", label: "Suggest /til", kind: Kind::Toggle },
+    Setting { key: "require_recall", label: "Recall before /til", kind: Kind::Toggle },
+    Setting { key: "verify_tils", label: "Fact-check TILs", kind: Kind::Toggle },
+    Setting { key: "til_reminder_minutes", label: "TIL reminder (minutes)", kind: Kind::Number },
+    Setting { key: "autosave_sessions", label: "Autosave", kind: Kind::Toggle },
+    Setting { key: "notifications", label: "Notify", kind: Kind::Toggle },
+    Setting { key: "encrypt_notes", label: "Encrypt", kind: Kind::Toggle },
+    Setting { key: "link_webfetch_only", label: "Link tools WebFetch only", kind: Kind::Toggle },
+    Setting { key: "til_context_tokens", label: "TIL context (tokens)", kind: Kind::Number },
+    Setting { key: "context_limit_tokens", label: "Context limit (tokens)", kind: Kind::Number },
+];
+
+/// The setting's current value, as shown in the list and accepted by [`set`]
+pub fn value(config: &Config, key: &str) -> String {
+    match key {
+        "notes_format" => config.notes_format.to_string(),
+        "language" => config.language.clone().unwrap_or_default(),
+        "filename_style" => config.filename_style.to_string(),
+        "archive_layout" => config.archive_layout.to_string(),
+        "auto_link" => config.auto_link.to_string(),
+        "suggest_followups" => config.suggest_followups.to_string(),
+        "inject_archive_context" => config.inject_archive_context.to_string(),
+        "suggest_til" => config.suggest_til.to_string(),
+        "require_recall" => config.require_recall.to_string(),
+        "verify_tils" => config.verify_tils.to_string(),
+        "til_reminder_minutes" => config.til_reminder_minutes.to_string(),
+        "autosave_sessions" => config.autosave_sessions.to_string(),
+        "notifications" => config.notifications.to_string(),
+        "encrypt_notes" => config.encrypt_notes.to_string(),
+        "link_webfetch_only" => config.link_webfetch_only.to_string(),
+        "til_context_tokens" => config.til_context_tokens.to_string(),
+        "context_limit_tokens" => config.context_limit_tokens.to_string(),
+        _ => String::new(),
+    }
+}
+
+/// Change a setting from its text form
+pub fn set(config: &mut Config, key: &str, value: &str) -> Result<()> {
+    let value = value.trim();
+    match key {
+        "notes_format" => config.notes_format = NotesFormat::parse(value)?,
+        "language" => config.language = (!value.is_empty()).then(|| value.to_string()),
+        "filename_style" => config.filename_style = FilenameStyle::parse(value)?,
+        "archive_layout" => config.archive_layout = ArchiveLayout::parse(value)?,
+        "auto_link" => config.auto_link = parse_bool(value)?,
+        "suggest_followups" => config.suggest_followups = parse_bool(value)?,
+        "inject_archive_context" => config.inject_archive_context = parse_bool(value)?,
+        "suggest_til" => config.suggest_til = parse_bool(value)?,
+        "require_recall" => config.require_recall = parse_bool(value)?,
+        "verify_tils" => config.verify_tils = parse_bool(value)?,
+        "til_reminder_minutes" => config.til_reminder_minutes = parse_number(value)?,
+        "autosave_sessions" => config.autosave_sessions = parse_bool(value)?,
+        "notifications" => config.notifications = parse_bool(value)?,
+        "encrypt_notes" => config.encrypt_notes = parse_bool(value)?,
+        "link_webfetch_only" => config.link_webfetch_only = parse_bool(value)?,
+        "til_context_tokens" => config.til_context_tokens = parse_number(value)?,
+        "context_limit_tokens" => config.context_limit_tokens = parse_number(value)?,
+        _ => return Err(anyhow!("Unknown setting: {}", key)),
+    }
+    Ok(())
+}
+
+fn parse_bool(value: &str) -> Result<bool> {
+    match value.to_lowercase().as_str() {
+        "true" | "on" | "yes" => Ok(true),
+        "false" | "off" | "no" => Ok(false),
+        _ => Err(anyhow!("Expected true or false, got {:?}", value)),
+    }
+}
+
+fn parse_number<T: std::str::FromStr>(value: &str) -> Result<T> {
+    value.parse().map_err(|_| anyhow!("Expected a whole number, got {:?}", value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_every_setting_roundtrips_its_value() -> Result<()> {
+        let mut config = Config::new(PathBuf::from("/til"));
+        for setting in SETTINGS {
+            let current = value(&config, setting.key);
+            set(&mut config, setting.key, &current)?;
+            assert_eq!(value(&config, setting.key), current, "{}", setting.key);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_set_parses_and_rejects() -> Result<()> {
+        let mut config = Config::new(PathBuf::from("/til"));
+
+        set(&mut config, "notes_format", "Org")?;
+        set(&mut config, "suggest_til", "off")?;
+        set(&mut config, "til_reminder_minutes", "25")?;
+        set(&mut config, "language", "  ")?;
+        assert_eq!(config.notes_format, NotesFormat::Org);
+        assert!(!config.suggest_til);
+        assert_eq!(config.til_reminder_minutes, 25);
+        assert!(config.language.is_none());
+
+        assert!(set(&mut config, "til_reminder_minutes", "soon").is_err());
+        assert!(set(&mut config, "til_path", "/elsewhere").is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,238 @@
+use crate::crypto;
+use crate::http;
+use crate::session::{self, Session};
+use anyhow::{anyhow, Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const GISTS_URL: &str = "https://api.github.com/gists";
+
+/// What part of a session to share
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ShareContent {
+    /// The whole conversation, with anything saved from it
+    Transcript,
+    /// The TIL saved from the session
+    Til,
+    /// The note saved from the session
+    Note,
+}
+
+/// Where to send it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ShareTarget {
+    Gist,
+    File,
+}
+
+/// Parse `/share` arguments, e.g. "til gist" or "" (transcript to a file)
+pub fn parse_args(args: &str) -> Result<(ShareContent, ShareTarget)> {
+    let mut content = ShareContent::Transcript;
+    let mut target = ShareTarget::File;
+
+    for word in args.split_whitespace() {
+        match word.to_lowercase().as_str() {
+            "transcript" => content = ShareContent::Transcript,
+            "til" => content = ShareContent::Til,
+            "note" => content = ShareContent::Note,
+            "gist" => target = ShareTarget::Gist,
+            "file" => target = ShareTarget::File,
+            other => return Err(anyhow!("Unknown /share option '{}'. Use: [til|note] [gist]", other)),
+        }
+    }
+
+    Ok((content, target))
+}
+
+/// A document ready to share: a suggested filename and its markdown
+#[derive(Debug, Clone)]
+pub struct Shareable {
+    pub filename: String,
+    pub markdown: String,
+}
+
+/// Build the document to share. TILs and notes are taken from the session's saved artifacts,
+/// which live under `archive_path` and `notes_path` respectively.
+pub fn build(
+    session: &Session,
+    content: ShareContent,
+    archive_path: &Path,
+    notes_path: Option<&Path>,
+) -> Result<Shareable> {
+    match content {
+        ShareContent::Transcript => Ok(Shareable {
+            filename: format!(
+                "{}-{}",
+                session.started_at.format("%Y-%m-%d"),
+                crate::til::writer::title_to_filename(session.topic())
+            ),
+            markdown: bundle(session)?,
+        }),
+        ShareContent::Til => artifact(session, "TIL", |path| path.starts_with(archive_path)),
+        ShareContent::Note => artifact(session, "note", |path| notes_path.is_some_and(|notes| path.starts_with(notes))),
+    }
+}
+
+/// A self-contained markdown summary: metadata, saved TILs/notes inlined, then the conversation
+pub fn bundle(session: &Session) -> Result<String> {
+    let mut bundle = format!(
+        "# {}\n\n*{} · {} · {} exchanges*\n",
+        session.mode,
+        session.started_at.format("%Y-%m-%d"),
+        session::format_duration(session.duration()),
+        session.exchanges.len()
+    );
+
+    for path in &session.artifacts {
+        if crypto::is_encrypted(path) {
+            continue;
+        }
+        let content = fs::read_to_string(path).with_context(|| format!("Failed to read {:?}", path))?;
+        bundle.push_str(&format!("\n---\n\n{}\n", content.trim()));
+    }
+
+    bundle.push_str("\n---\n\n## Conversation\n");
+    for exchange in &session.exchanges {
+        bundle.push_str(&format!("\n**You:** {}\n", exchange.user_message.trim()));
+        bundle.push_str(&format!("\n{}\n", exchange.assistant_response.trim()));
+    }
+
+    Ok(bundle)
+}
+
+fn artifact<F>(session: &Session, kind: &str, is_kind: F) -> Result<Shareable>
+where
+    F: Fn(&Path) -> bool,
+{
+    let path = session
+        .artifacts
+        .iter()
+        .rev()
+        .find(|path| is_kind(path))
+        .ok_or_else(|| anyhow!("No {} was saved from this session", kind))?;
+
+    if crypto::is_encrypted(path) {
+        return Err(anyhow!("The {} is encrypted; decrypt it first to share it", kind));
+    }
+
+    Ok(Shareable {
+        filename: path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| "shared.md".to_string()),
+        markdown: fs::read_to_string(path).with_context(|| format!("Failed to read {:?}", path))?,
+    })
+}
+
+/// Write the document into `dir`, returning its path
+pub fn write_file(shareable: &Shareable, dir: &Path) -> Result<PathBuf> {
+    let path = dir.join(&shareable.filename);
+    fs::write(&path, &shareable.markdown).with_context(|| format!("Failed to write {:?}", path))?;
+    Ok(path)
+}
+
+/// Upload the document as a GitHub gist (secret unless `public`), returning its URL
+pub fn create_gist(shareable: &Shareable, description: &str, token: &str, public: bool) -> Result<String> {
+    let request = serde_json::json!({
+        "description": description,
+        "public": public,
+        "files": { &shareable.filename: { "content": &shareable.markdown } },
+    });
+    let auth = format!("Bearer {}", token);
+    let body = http::post_json(
+        GISTS_URL,
+        &[
+            ("Authorization", &auth),
+            ("Accept", "application/vnd.github+json"),
+            ("User-Agent", "holocron"),
+        ],
+        &request,
+    )?;
+    gist_url(&body)
+}
+
+fn gist_url(body: &str) -> Result<String> {
+    let json: serde_json::Value = serde_json::from_str(body).context("Failed to parse GitHub response")?;
+    json["html_url"]
+        .as_str()
+        .map(str::to_string)
+        .ok_or_else(|| anyhow!("GitHub didn't return a gist URL"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session::LearningMode;
+    use tempfile::TempDir;
+
+    fn session() -> Session {
+        let mut session = Session::new(
+            LearningMode::DeepDive {
+                topic: "git rebase".to_string(),
+            },
+            None,
+        );
+        session.add_exchange("What is rebase?".to_string(), "Replaying commits.".to_string());
+        session
+    }
+
+    #[test]
+    fn test_parse_args() -> Result<()> {
+        assert_eq!(parse_args("")?, (ShareContent::Transcript, ShareTarget::File));
+        assert_eq!(parse_args("gist TIL")?, (ShareContent::Til, ShareTarget::Gist));
+        assert!(parse_args("email").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_bundle_inlines_saved_til() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let til = temp_dir.path().join("rebase.md");
+        fs::write(&til, "# Rebase\n\nReplay commits.\n")?;
+        let mut session = session();
+        session.add_artifact(til);
+        session.add_artifact(temp_dir.path().join("secret.md.age"));
+
+        let bundle = bundle(&session)?;
+
+        assert!(bundle.starts_with("# Deep Dive: git rebase\n"));
+        assert!(bundle.contains("---\n\n# Rebase\n\nReplay commits.\n"));
+        assert!(bundle.contains("**You:** What is rebase?\n\nReplaying commits.\n"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_build_picks_artifact_kind() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let archive = temp_dir.path().join("archive");
+        let notes = temp_dir.path().join("notes");
+        fs::create_dir_all(&archive)?;
+        fs::create_dir_all(&notes)?;
+        fs::write(archive.join("rebase.md"), "# Rebase TIL\n")?;
+        fs::write(notes.join("rebase_note.md"), "# Rebase Note\n")?;
+
+        let mut session = session();
+        session.add_artifact(archive.join("rebase.md"));
+        session.add_artifact(notes.join("rebase_note.md"));
+
+        let til = build(&session, ShareContent::Til, &archive, Some(&notes))?;
+        assert_eq!(til.filename, "rebase.md");
+        assert_eq!(til.markdown, "# Rebase TIL\n");
+
+        let note = build(&session, ShareContent::Note, &archive, Some(&notes))?;
+        assert_eq!(note.markdown, "# Rebase Note\n");
+
+        assert!(build(&session, ShareContent::Note, &archive, None).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_gist_url() -> Result<()> {
+        assert_eq!(
+            gist_url(r#"{"id":"1","html_url":"https://gist.github.com/me/1"}"#)?,
+            "https://gist.github.com/me/1"
+        );
+        assert!(gist_url(r#"{"message":"Bad credentials"}"#).is_err());
+        Ok(())
+    }
+}
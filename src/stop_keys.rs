@@ -0,0 +1,99 @@
+use crate::claude::CancellationToken;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::crossterm::terminal;
+use std::io::IsTerminal;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+/// How often the watcher checks whether it should stop
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Watches the keyboard while a response streams and cancels it on Esc, `s` or Ctrl+C.
+///
+/// The terminal is in raw mode while the watcher runs, so printed text needs
+/// [`StopKeys::text`] to keep line breaks intact. Dropping it restores the terminal.
+pub struct StopKeys {
+    done: Arc<AtomicBool>,
+    watcher: Option<JoinHandle<()>>,
+}
+
+impl StopKeys {
+    /// Start watching, unless stdin isn't an interactive terminal
+    pub fn watch(cancel: CancellationToken) -> Self {
+        let done = Arc::new(AtomicBool::new(false));
+        let interactive = std::io::stdin().is_terminal() && std::io::stdout().is_terminal();
+        if !interactive || terminal::enable_raw_mode().is_err() {
+            return Self { done, watcher: None };
+        }
+
+        let watching = done.clone();
+        let watcher = thread::spawn(move || {
+            while !watching.load(Ordering::Relaxed) {
+                match event::poll(POLL_INTERVAL) {
+                    Ok(true) => {}
+                    Ok(false) => continue,
+                    Err(_) => break,
+                }
+                if let Ok(Event::Key(key)) = event::read() {
+                    if is_stop_key(key) {
+                        cancel.cancel();
+                        break;
+                    }
+                }
+            }
+        });
+
+        Self {
+            done,
+            watcher: Some(watcher),
+        }
+    }
+
+    /// Adapt streamed text for printing while the terminal is in raw mode
+    pub fn text(&self, text: &str) -> String {
+        if self.watcher.is_some() {
+            text.replace('\n', "\r\n")
+        } else {
+            text.to_string()
+        }
+    }
+}
+
+impl Drop for StopKeys {
+    fn drop(&mut self) {
+        self.done.store(true, Ordering::Relaxed);
+        if let Some(watcher) = self.watcher.take() {
+            watcher.join().ok();
+            terminal::disable_raw_mode().ok();
+        }
+    }
+}
+
+/// Esc or `s` stops a response; Ctrl+C does too, since raw mode swallows the signal
+fn is_stop_key(key: KeyEvent) -> bool {
+    if key.kind != KeyEventKind::Press {
+        return false;
+    }
+    match key.code {
+        KeyCode::Esc => true,
+        KeyCode::Char('c') => key.modifiers.contains(KeyModifiers::CONTROL),
+        KeyCode::Char('s') | KeyCode::Char('S') => !key.modifiers.contains(KeyModifiers::CONTROL),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_stop_key() {
+        assert!(is_stop_key(KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE)));
+        assert!(is_stop_key(KeyEvent::new(KeyCode::Char('s'), KeyModifiers::NONE)));
+        assert!(is_stop_key(KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL)));
+        assert!(!is_stop_key(KeyEvent::new(KeyCode::Char('c'), KeyModifiers::NONE)));
+        assert!(!is_stop_key(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE)));
+    }
+}
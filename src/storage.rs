@@ -0,0 +1,250 @@
+use crate::config::TargetConfig;
+use crate::error::HolocronError;
+use crate::{files, http};
+use anyhow::{anyhow, Context, Result};
+use std::fmt;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+/// Where a saved TIL or note ended up
+#[derive(Debug, Clone, PartialEq)]
+pub enum Saved {
+    /// A file on this machine, which holocron can read back, link and search
+    Local(PathBuf),
+    /// Somewhere else, described for the user (a URL, a bucket key, a database row)
+    Remote(String),
+}
+
+impl Saved {
+    pub fn local_path(&self) -> Option<&Path> {
+        match self {
+            Saved::Local(path) => Some(path),
+            Saved::Remote(_) => None,
+        }
+    }
+}
+
+impl fmt::Display for Saved {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Saved::Local(path) => write!(f, "{}", path.display()),
+            Saved::Remote(location) => write!(f, "{}", location),
+        }
+    }
+}
+
+/// Somewhere generated TILs and notes can be stored
+pub trait SaveTarget {
+    /// Store `content` at `path`, given relative to the target's root (e.g. `rust/borrowing.md`)
+    fn save(&self, path: &str, content: &[u8]) -> Result<Saved>;
+}
+
+/// The target configured for a destination, or files under `root` when there's none
+pub fn target(config: Option<&TargetConfig>, root: &Path) -> Box<dyn SaveTarget> {
+    match config {
+        None => Box::new(Filesystem::new(root)),
+        Some(TargetConfig::Filesystem { path }) => Box::new(Filesystem::new(path)),
+        Some(TargetConfig::S3 { bucket, prefix }) => Box::new(S3 {
+            bucket: bucket.clone(),
+            prefix: prefix.clone(),
+        }),
+        Some(TargetConfig::Webdav { url, username, password }) => Box::new(WebDav {
+            url: url.clone(),
+            username: username.clone(),
+            password: password.clone(),
+        }),
+        Some(TargetConfig::Sqlite { database }) => Box::new(Sqlite {
+            database: database.clone(),
+        }),
+        Some(TargetConfig::Http { url, token }) => Box::new(HttpPost {
+            url: url.clone(),
+            token: token.clone(),
+        }),
+    }
+}
+
+/// Files in a directory: the archive or notes directory by default
+pub struct Filesystem {
+    root: PathBuf,
+}
+
+impl Filesystem {
+    pub fn new(root: &Path) -> Self {
+        Self {
+            root: root.to_path_buf(),
+        }
+    }
+}
+
+impl SaveTarget for Filesystem {
+    fn save(&self, path: &str, content: &[u8]) -> Result<Saved> {
+        let file_path = self.root.join(path);
+        if let Some(dir) = file_path.parent() {
+            fs::create_dir_all(dir).map_err(|source| HolocronError::WriteFailed {
+                path: dir.to_path_buf(),
+                source,
+            })?;
+        }
+        files::write_atomic(&file_path, content).map_err(|source| HolocronError::WriteFailed {
+            path: file_path.clone(),
+            source,
+        })?;
+        Ok(Saved::Local(file_path))
+    }
+}
+
+/// An S3 bucket, uploaded to with the `aws` CLI and whatever credentials it's set up with
+pub struct S3 {
+    bucket: String,
+    prefix: String,
+}
+
+impl S3 {
+    fn url(&self, path: &str) -> String {
+        let prefix = self.prefix.trim_matches('/');
+        if prefix.is_empty() {
+            format!("s3://{}/{}", self.bucket, path)
+        } else {
+            format!("s3://{}/{}/{}", self.bucket, prefix, path)
+        }
+    }
+}
+
+impl SaveTarget for S3 {
+    fn save(&self, path: &str, content: &[u8]) -> Result<Saved> {
+        let url = self.url(path);
+        pipe_to(Command::new("aws").args(["s3", "cp", "-", &url]), content)
+            .with_context(|| format!("Failed to upload to {}", url))?;
+        Ok(Saved::Remote(url))
+    }
+}
+
+/// A WebDAV share (Nextcloud, ownCloud, Apache mod_dav, ...)
+pub struct WebDav {
+    url: String,
+    username: Option<String>,
+    password: Option<String>,
+}
+
+impl SaveTarget for WebDav {
+    fn save(&self, path: &str, content: &[u8]) -> Result<Saved> {
+        let base = self.url.trim_end_matches('/');
+        let credentials = self
+            .username
+            .as_ref()
+            .map(|user| format!("{}:{}", user, self.password.as_deref().unwrap_or_default()));
+
+        // PUT won't create folders, so make each one first. Existing ones answer 405, which is fine.
+        let mut folder = base.to_string();
+        for part in Path::new(path).parent().into_iter().flat_map(Path::iter) {
+            folder = format!("{}/{}", folder, part.to_string_lossy());
+            http::request_as(credentials.as_deref(), "MKCOL", &format!("{}/", folder), None).ok();
+        }
+
+        let url = format!("{}/{}", base, path);
+        http::request_as(credentials.as_deref(), "PUT", &url, Some(content))?;
+        Ok(Saved::Remote(url))
+    }
+}
+
+/// A table in a SQLite database, written with the `sqlite3` CLI
+pub struct Sqlite {
+    database: PathBuf,
+}
+
+impl SaveTarget for Sqlite {
+    fn save(&self, path: &str, content: &[u8]) -> Result<Saved> {
+        pipe_to(Command::new("sqlite3").arg(&self.database), upsert_sql(path, content).as_bytes())
+            .with_context(|| format!("Failed to save to {:?}", self.database))?;
+        Ok(Saved::Remote(format!("{} in {}", path, self.database.display())))
+    }
+}
+
+/// Insert or replace the row for `path`. The content goes in as a hex blob literal, so nothing
+/// in it needs escaping.
+fn upsert_sql(path: &str, content: &[u8]) -> String {
+    let hex: String = content.iter().map(|byte| format!("{:02x}", byte)).collect();
+    format!(
+        "CREATE TABLE IF NOT EXISTS entries (path TEXT PRIMARY KEY, content TEXT NOT NULL, saved_at TEXT NOT NULL);\n\
+         INSERT OR REPLACE INTO entries (path, content, saved_at) VALUES ('{}', CAST(X'{}' AS TEXT), datetime('now'));\n",
+        path.replace('\'', "''"),
+        hex
+    )
+}
+
+/// A service of your own, sent `{"path": ..., "content": ...}` as a JSON POST
+pub struct HttpPost {
+    url: String,
+    token: Option<String>,
+}
+
+impl SaveTarget for HttpPost {
+    fn save(&self, path: &str, content: &[u8]) -> Result<Saved> {
+        let authorization = self.token.as_ref().map(|token| format!("Bearer {}", token));
+        let headers: Vec<(&str, &str)> = authorization.iter().map(|value| ("Authorization", value.as_str())).collect();
+        let body = serde_json::json!({
+            "path": path,
+            "content": String::from_utf8_lossy(content),
+        });
+        http::post_json(&self.url, &headers, &body)?;
+        Ok(Saved::Remote(format!("{} ({})", self.url, path)))
+    }
+}
+
+/// Run a command with `input` on its stdin, failing with its stderr if it fails
+fn pipe_to(command: &mut Command, input: &[u8]) -> Result<()> {
+    let program = command.get_program().to_string_lossy().to_string();
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| anyhow!("Failed to run {} (is it installed?): {}", program, e))?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(input).with_context(|| format!("Failed to send input to {}", program))?;
+    }
+
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        return Err(anyhow!("{} failed: {}", program, String::from_utf8_lossy(&output.stderr).trim()));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_filesystem_creates_directories() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let target = Filesystem::new(&temp_dir.path().join("archive"));
+
+        let saved = target.save("rust/2024/borrowing.md", b"# Borrowing\n")?;
+
+        let expected = temp_dir.path().join("archive/rust/2024/borrowing.md");
+        assert_eq!(saved, Saved::Local(expected.clone()));
+        assert_eq!(fs::read_to_string(expected)?, "# Borrowing\n");
+        Ok(())
+    }
+
+    #[test]
+    fn test_s3_url() {
+        let s3 = |prefix: &str| S3 {
+            bucket: "notes".to_string(),
+            prefix: prefix.to_string(),
+        };
+        assert_eq!(s3("").url("rust/a.md"), "s3://notes/rust/a.md");
+        assert_eq!(s3("/til/").url("rust/a.md"), "s3://notes/til/rust/a.md");
+    }
+
+    #[test]
+    fn test_upsert_sql_escapes_path_and_hex_encodes_content() {
+        let sql = upsert_sql("it's.md", b"# A'b");
+        assert!(sql.contains("VALUES ('it''s.md', CAST(X'2320412762' AS TEXT)"));
+    }
+}
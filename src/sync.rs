@@ -0,0 +1,421 @@
+use crate::config::{CATEGORY_MEMORY_FILE, CONFIG_FILE, QUEUE_FILE, SESSIONS_DIR, SYNCED_STATE, TIL_OF_THE_DAY_FILE};
+use crate::queue::{self, Queue};
+use crate::session::Session;
+use crate::til::category::CategoryMemory;
+use crate::til::of_the_day::History;
+use anyhow::{anyhow, Context, Result};
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Synced files by path relative to the config dir (or the state repo's root)
+pub type Snapshot = BTreeMap<PathBuf, Vec<u8>>;
+
+/// What a sync changed
+#[derive(Debug, Default, PartialEq)]
+pub struct SyncReport {
+    /// Files updated or removed on this device
+    pub local_changes: usize,
+    /// Files updated or removed in the state repo
+    pub repo_changes: usize,
+    /// Whether the state repo was pushed to its upstream
+    pub pushed: bool,
+    /// The state-repo commit this device is now in sync with (none on a dry run)
+    pub commit: Option<String>,
+}
+
+/// Merge this device's state with the state repo's, write the result to both, and push it.
+/// The repo's upstream (if it has one) is fetched first, so changes from other devices come in.
+pub fn sync(repo: &Path, config_dir: &Path, base_commit: Option<&str>, dry_run: bool) -> Result<SyncReport> {
+    if !repo.join(".git").exists() {
+        return Err(anyhow!(
+            "{:?} isn't a git repository. Clone your state repo there (or run git init) first",
+            repo
+        ));
+    }
+
+    if !git(repo, &["remote"])?.trim().is_empty() {
+        git(repo, &["fetch", "--quiet"])?;
+    }
+    let upstream = has_upstream(repo);
+    let remote_rev = if upstream { "@{u}" } else { "HEAD" };
+
+    let base = base_commit.map_or_else(|| Ok(Snapshot::new()), |commit| snapshot_at(repo, commit))?;
+    let remote = snapshot_at(repo, remote_rev)?;
+    let local = snapshot(config_dir)?;
+    let merged = merge(&base, &local, &remote);
+
+    let mut report = SyncReport {
+        local_changes: changed_paths(&local, &merged).len(),
+        repo_changes: changed_paths(&remote, &merged).len(),
+        pushed: false,
+        commit: None,
+    };
+    if dry_run {
+        return Ok(report);
+    }
+
+    // The repo only ever holds synced state, so it can be moved to the upstream's latest
+    if upstream {
+        git(repo, &["reset", "--hard", "--quiet", "@{u}"])?;
+    }
+    // Earlier versions synced the config too; take it out of the repo from here on
+    if repo.join(CONFIG_FILE).exists() {
+        git(repo, &["rm", "--quiet", "--", CONFIG_FILE])?;
+    }
+    apply(repo, &snapshot(repo)?, &merged)?;
+    apply(config_dir, &local, &merged)?;
+
+    git(repo, &["add", "--all"])?;
+    if !git(repo, &["status", "--porcelain"])?.trim().is_empty() {
+        let message = format!("Sync state {}", chrono::Local::now().format("%Y-%m-%d %H:%M"));
+        git(repo, &["commit", "--quiet", "-m", &message])?;
+        report.pushed = push(repo, upstream)?;
+    }
+
+    report.commit = Some(git(repo, &["rev-parse", "HEAD"])?.trim().to_string());
+    Ok(report)
+}
+
+/// Read the synced files under `root`
+pub fn snapshot(root: &Path) -> Result<Snapshot> {
+    let mut files = Snapshot::new();
+    for name in SYNCED_STATE {
+        collect(root, &root.join(name), &mut files)?;
+    }
+    Ok(files)
+}
+
+fn collect(root: &Path, path: &Path, files: &mut Snapshot) -> Result<()> {
+    if path.is_dir() {
+        for entry in fs::read_dir(path).with_context(|| format!("Failed to read {:?}", path))? {
+            collect(root, &entry?.path(), files)?;
+        }
+    } else if path.is_file() {
+        let content = fs::read(path).with_context(|| format!("Failed to read {:?}", path))?;
+        files.insert(path.strip_prefix(root)?.to_path_buf(), content);
+    }
+    Ok(())
+}
+
+/// Read the synced files as of a commit in the state repo (nothing, if the repo has no commits yet)
+fn snapshot_at(repo: &Path, rev: &str) -> Result<Snapshot> {
+    if git(repo, &["rev-parse", "--verify", "--quiet", rev]).is_err() {
+        return Ok(Snapshot::new());
+    }
+
+    let mut files = Snapshot::new();
+    for path in git(repo, &["ls-tree", "-r", "--name-only", rev])?.lines() {
+        let synced = Path::new(path)
+            .components()
+            .next()
+            .is_some_and(|first| SYNCED_STATE.iter().any(|name| first.as_os_str() == *name));
+        if synced {
+            files.insert(PathBuf::from(path), git_bytes(repo, &["show", &format!("{}:{}", rev, path)])?);
+        }
+    }
+    Ok(files)
+}
+
+/// Three-way merge, file by file. `base` is the state this device last synced: a side that
+/// still matches it takes the other's version (including deletions), and files changed on
+/// both sides are merged by [`merge_file`].
+pub fn merge(base: &Snapshot, local: &Snapshot, remote: &Snapshot) -> Snapshot {
+    let paths: BTreeSet<&PathBuf> = base.keys().chain(local.keys()).chain(remote.keys()).collect();
+
+    paths
+        .into_iter()
+        .filter_map(|path| {
+            let (ours, theirs, before) = (local.get(path), remote.get(path), base.get(path));
+            let merged = if ours == before {
+                theirs.cloned()
+            } else if theirs == before || ours == theirs {
+                ours.cloned()
+            } else {
+                merge_file(path, before, ours, theirs)
+            };
+            merged.map(|content| (path.clone(), content))
+        })
+        .collect()
+}
+
+/// Merge a file changed on both devices:
+/// - the queue keeps every item added on either, minus those taken off on either
+/// - remembered categories keep every mapping, this device's winning on conflict
+/// - a session keeps whichever copy has more exchanges
+/// - the TIL of the day history keeps the latest date each TIL was shown
+/// - anything else (drafts, transcripts) keeps this device's copy
+///
+/// A file deleted on one device and changed on the other is kept.
+fn merge_file(path: &Path, base: Option<&Vec<u8>>, local: Option<&Vec<u8>>, remote: Option<&Vec<u8>>) -> Option<Vec<u8>> {
+    let (Some(local), Some(remote)) = (local, remote) else {
+        return local.or(remote).cloned();
+    };
+    let text = |bytes: &Vec<u8>| String::from_utf8_lossy(bytes).to_string();
+
+    let merged = if path == Path::new(QUEUE_FILE) {
+        merge_queues(base.map(text).as_deref(), &text(local), &text(remote))
+    } else if path == Path::new(CATEGORY_MEMORY_FILE) {
+        merge_category_memory(&text(local), &text(remote))
+    } else if path.starts_with(SESSIONS_DIR) {
+        longer_session(&text(local), &text(remote))
+    } else if path == Path::new(TIL_OF_THE_DAY_FILE) {
+        merge_review_history(&text(local), &text(remote))
+    } else {
+        return Some(local.clone());
+    };
+    // Unparseable files fall back to this device's copy
+    Some(merged.map(String::into_bytes).unwrap_or_else(|_| local.clone()))
+}
+
+fn merge_queues(base: Option<&str>, local: &str, remote: &str) -> Result<String> {
+    let base: Queue = base.map(toml::from_str).transpose()?.unwrap_or_default();
+    let local: Queue = toml::from_str(local)?;
+    let remote: Queue = toml::from_str(remote)?;
+
+    let urls = |queue: &Queue| -> HashSet<String> { queue.items.iter().map(queue::QueueItem::key).collect() };
+    let (local_urls, remote_urls) = (urls(&local), urls(&remote));
+    // An item taken off the queue on either device stays off
+    let taken: HashSet<String> = urls(&base)
+        .into_iter()
+        .filter(|url| !local_urls.contains(url) || !remote_urls.contains(url))
+        .collect();
+
+    let mut items: Vec<queue::QueueItem> = local.items.into_iter().chain(remote.items).collect();
+    items.sort_by_key(|item| item.added);
+    let mut merged = Queue::default();
+    merged.enqueue(items, &HashSet::new());
+    merged.items.retain(|item| !taken.contains(&item.key()));
+    Ok(toml::to_string_pretty(&merged)?)
+}
+
+fn merge_category_memory(local: &str, remote: &str) -> Result<String> {
+    let mut merged: CategoryMemory = toml::from_str(local)?;
+    let remote: CategoryMemory = toml::from_str(remote)?;
+
+    for mapping in remote.mappings {
+        if !merged.mappings.iter().any(|existing| existing.topic == mapping.topic) {
+            merged.mappings.push(mapping);
+        }
+    }
+    Ok(toml::to_string_pretty(&merged)?)
+}
+
+fn merge_review_history(local: &str, remote: &str) -> Result<String> {
+    let mut merged: History = toml::from_str(local)?;
+    let remote: History = toml::from_str(remote)?;
+
+    for (file, shown) in remote.shown {
+        let latest = merged.shown.get(&file).map_or(shown, |&ours| ours.max(shown));
+        merged.shown.insert(file, latest);
+    }
+    // Today's pick is whichever device picked most recently
+    if remote.today.as_ref().map(|(date, _)| date) > merged.today.as_ref().map(|(date, _)| date) {
+        merged.today = remote.today;
+    }
+    Ok(toml::to_string_pretty(&merged)?)
+}
+
+fn longer_session(local: &str, remote: &str) -> Result<String> {
+    let ours: Session = serde_json::from_str(local)?;
+    let theirs: Session = serde_json::from_str(remote)?;
+    Ok(if theirs.exchanges.len() > ours.exchanges.len() { remote } else { local }.to_string())
+}
+
+/// Paths whose content differs between two snapshots
+pub fn changed_paths<'a>(current: &'a Snapshot, merged: &'a Snapshot) -> Vec<&'a PathBuf> {
+    let paths: BTreeSet<&PathBuf> = current.keys().chain(merged.keys()).collect();
+    paths
+        .into_iter()
+        .filter(|path| current.get(*path) != merged.get(*path))
+        .collect()
+}
+
+/// Make the synced files under `root` match `merged`
+fn apply(root: &Path, current: &Snapshot, merged: &Snapshot) -> Result<()> {
+    for path in changed_paths(current, merged) {
+        let target = root.join(path);
+        match merged.get(path) {
+            Some(content) => {
+                if let Some(parent) = target.parent() {
+                    fs::create_dir_all(parent).with_context(|| format!("Failed to create {:?}", parent))?;
+                }
+                fs::write(&target, content).with_context(|| format!("Failed to write {:?}", target))?;
+            }
+            None => fs::remove_file(&target).with_context(|| format!("Failed to remove {:?}", target))?,
+        }
+    }
+    Ok(())
+}
+
+/// Push to the upstream, or set `origin` as the upstream on the first sync.
+/// Returns false when there's nowhere to push.
+fn push(repo: &Path, upstream: bool) -> Result<bool> {
+    if upstream {
+        git(repo, &["push", "--quiet"])?;
+        return Ok(true);
+    }
+    if git(repo, &["remote"])?.lines().any(|remote| remote == "origin") {
+        git(repo, &["push", "--quiet", "--set-upstream", "origin", "HEAD"])?;
+        return Ok(true);
+    }
+    Ok(false)
+}
+
+fn has_upstream(repo: &Path) -> bool {
+    git(repo, &["rev-parse", "--abbrev-ref", "@{u}"]).is_ok()
+}
+
+/// Run git in `repo`, returning its output
+pub fn git(repo: &Path, args: &[&str]) -> Result<String> {
+    git_bytes(repo, args).map(|output| String::from_utf8_lossy(&output).to_string())
+}
+
+fn git_bytes(repo: &Path, args: &[&str]) -> Result<Vec<u8>> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .args(args)
+        .output()
+        .context("Failed to run git")?;
+
+    if !output.status.success() {
+        return Err(anyhow!(
+            "git {} failed: {}",
+            args.first().unwrap_or(&""),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(output.stdout)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session::LearningMode;
+    use chrono::NaiveDate;
+
+    fn files(entries: &[(&str, &str)]) -> Snapshot {
+        entries
+            .iter()
+            .map(|(path, content)| (PathBuf::from(path), content.as_bytes().to_vec()))
+            .collect()
+    }
+
+    fn queue_toml(urls: &[&str]) -> Result<String> {
+        let mut queue = Queue::default();
+        for (i, url) in urls.iter().enumerate() {
+            queue.items.push(queue::QueueItem {
+                url: url.to_string(),
+                title: None,
+                source: "watch".to_string(),
+                text: None,
+                added: chrono::Local::now() + chrono::Duration::seconds(i as i64),
+            });
+        }
+        Ok(toml::to_string_pretty(&queue)?)
+    }
+
+    #[test]
+    fn test_merge_takes_changes_from_each_side() {
+        let base = files(&[("categories.toml", "a"), ("drafts/rust/x.md", "x")]);
+        let local = files(&[("categories.toml", "a"), ("sessions/1.json", "{}")]);
+        let remote = files(&[("categories.toml", "b"), ("drafts/rust/x.md", "x"), ("drafts/git/y.md", "y")]);
+
+        // The draft reviewed here stays gone; the other device's categories and draft come in
+        assert_eq!(
+            merge(&base, &local, &remote),
+            files(&[("categories.toml", "b"), ("drafts/git/y.md", "y"), ("sessions/1.json", "{}")])
+        );
+    }
+
+    #[test]
+    fn test_merge_first_sync_keeps_both_devices_state() {
+        let local = files(&[("categories.toml", "mine"), ("sessions/1.json", "{}")]);
+        let remote = files(&[("categories.toml", "theirs"), ("sessions/2.json", "{}")]);
+        assert_eq!(
+            merge(&Snapshot::new(), &local, &remote),
+            files(&[("categories.toml", "mine"), ("sessions/1.json", "{}"), ("sessions/2.json", "{}")])
+        );
+    }
+
+    #[test]
+    fn test_merge_queues() -> Result<()> {
+        let base = queue_toml(&["https://a.com", "https://b.com"])?;
+        let local = queue_toml(&["https://b.com", "https://c.com"])?;
+        let remote = queue_toml(&["https://a.com", "https://d.com"])?;
+
+        let merged: Queue = toml::from_str(&merge_queues(Some(&base), &local, &remote)?)?;
+        let urls: Vec<&str> = merged.items.iter().map(|item| item.url.as_str()).collect();
+        assert_eq!(urls, vec!["https://c.com", "https://d.com"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_merge_review_history() -> Result<()> {
+        let day = |d| NaiveDate::from_ymd_opt(2026, 3, d).ok_or_else(|| anyhow!("bad date"));
+        let mut local = History::default();
+        local.record("a.md", day(1)?, false);
+        local.record("b.md", day(5)?, true);
+        let mut remote = History::default();
+        remote.record("a.md", day(3)?, false);
+        remote.record("c.md", day(6)?, true);
+
+        let merged: History = toml::from_str(&merge_review_history(
+            &toml::to_string_pretty(&local)?,
+            &toml::to_string_pretty(&remote)?,
+        )?)?;
+        assert_eq!(merged.shown.get("a.md"), Some(&day(3)?));
+        assert_eq!(merged.shown.get("b.md"), Some(&day(5)?));
+        assert_eq!(merged.today, Some((day(6)?, "c.md".to_string())));
+        Ok(())
+    }
+
+    #[test]
+    fn test_longer_session_wins() -> Result<()> {
+        let mut session = Session::new(LearningMode::DeepDive { topic: "rust".to_string() }, None);
+        let short = serde_json::to_string(&session)?;
+        session.add_exchange("Q".to_string(), "A".to_string());
+        let long = serde_json::to_string(&session)?;
+
+        assert_eq!(longer_session(&short, &long)?, long);
+        assert_eq!(longer_session(&long, &short)?, long);
+        Ok(())
+    }
+
+    #[test]
+    fn test_sync_round_trip_between_devices() -> Result<()> {
+        let temp_dir = tempfile::TempDir::new()?;
+        let origin = temp_dir.path().join("origin.git");
+        let (laptop, desktop) = (temp_dir.path().join("laptop"), temp_dir.path().join("desktop"));
+        git(temp_dir.path(), &["init", "--quiet", "--bare", &origin.to_string_lossy()])?;
+        for clone in [&laptop, &desktop] {
+            git(temp_dir.path(), &["clone", "--quiet", &origin.to_string_lossy(), &clone.to_string_lossy()])?;
+            git(clone, &["config", "user.email", "me@example.com"])?;
+            git(clone, &["config", "user.name", "Me"])?;
+        }
+
+        let laptop_state = temp_dir.path().join("laptop-state");
+        fs::create_dir_all(laptop_state.join("sessions"))?;
+        fs::write(laptop_state.join("sessions/1.json"), "{}")?;
+        fs::write(laptop_state.join(CONFIG_FILE), "til_path = \"/home/me/til\"")?;
+        let report = sync(&laptop, &laptop_state, None, false)?;
+        assert_eq!((report.repo_changes, report.pushed), (1, true));
+        let laptop_base = report.commit;
+
+        let desktop_state = temp_dir.path().join("desktop-state");
+        let report = sync(&desktop, &desktop_state, None, false)?;
+        assert_eq!(report.local_changes, 1);
+        assert_eq!(fs::read_to_string(desktop_state.join("sessions/1.json"))?, "{}");
+        assert!(!desktop_state.join(CONFIG_FILE).exists());
+
+        // Removing it on the desktop removes it from the laptop on its next sync
+        fs::remove_file(desktop_state.join("sessions/1.json"))?;
+        sync(&desktop, &desktop_state, report.commit.as_deref(), false)?;
+        let report = sync(&laptop, &laptop_state, laptop_base.as_deref(), false)?;
+        assert_eq!(report.local_changes, 1);
+        assert!(!laptop_state.join("sessions/1.json").exists());
+        Ok(())
+    }
+}
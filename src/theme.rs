@@ -0,0 +1,290 @@
+use anyhow::{anyhow, Result};
+use colored::{Color, ColoredString, Colorize};
+use console::Style;
+use dialoguer::theme::ColorfulTheme;
+use indicatif::ProgressStyle;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+
+/// A named palette for terminal output, set once from the config
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum Theme {
+    #[default]
+    Default,
+    /// The Solarized accent colors, readable on its light and dark backgrounds alike
+    Solarized,
+    /// No colors or text styles at all
+    NoColor,
+    /// Bright, bold colors and no dimmed text
+    HighContrast,
+}
+
+/// What a piece of output is for; each theme picks how it looks
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Role {
+    /// Banners and headings
+    Accent,
+    /// Commands to run, URLs, paths and other details
+    Info,
+    Success,
+    Warning,
+    Failure,
+    /// Hints and secondary details
+    Muted,
+    /// Search results
+    Highlight,
+    /// The matched text within a search result
+    Matched,
+}
+
+static THEME: AtomicU8 = AtomicU8::new(Theme::Default as u8);
+static EMOJI: AtomicBool = AtomicBool::new(true);
+
+impl Theme {
+    pub fn parse(theme: &str) -> Result<Self> {
+        match theme.to_lowercase().replace('_', "-").as_str() {
+            "default" => Ok(Theme::Default),
+            "solarized" => Ok(Theme::Solarized),
+            "no-color" | "none" => Ok(Theme::NoColor),
+            "high-contrast" => Ok(Theme::HighContrast),
+            _ => Err(anyhow!("Invalid theme. Use: default, solarized, no-color, or high-contrast")),
+        }
+    }
+
+    /// Style `text` for `role` in this theme
+    pub fn paint(self, text: ColoredString, role: Role) -> ColoredString {
+        match (self, role) {
+            (Theme::NoColor, _) => text.clear(),
+            (_, Role::Matched) => text.color(self.color(role)).bold(),
+            (Theme::Default, Role::Muted) => text.dimmed(),
+            (Theme::HighContrast, Role::Muted) => text,
+            (Theme::HighContrast, Role::Accent) => text.bold(),
+            (Theme::HighContrast, _) => text.color(self.color(role)).bold(),
+            _ => text.color(self.color(role)),
+        }
+    }
+
+    fn color(self, role: Role) -> Color {
+        match self {
+            Theme::Solarized => match role {
+                Role::Accent => Color::TrueColor { r: 38, g: 139, b: 210 },
+                Role::Info => Color::TrueColor { r: 42, g: 161, b: 152 },
+                Role::Success => Color::TrueColor { r: 133, g: 153, b: 0 },
+                Role::Warning => Color::TrueColor { r: 181, g: 137, b: 0 },
+                Role::Failure | Role::Matched => Color::TrueColor { r: 220, g: 50, b: 47 },
+                Role::Muted => Color::TrueColor { r: 101, g: 123, b: 131 },
+                Role::Highlight => Color::TrueColor { r: 211, g: 54, b: 130 },
+            },
+            Theme::HighContrast => match role {
+                Role::Accent | Role::Info => Color::BrightCyan,
+                Role::Success => Color::BrightGreen,
+                Role::Warning => Color::BrightYellow,
+                Role::Failure | Role::Matched => Color::BrightRed,
+                Role::Muted => Color::White,
+                Role::Highlight => Color::BrightMagenta,
+            },
+            Theme::Default | Theme::NoColor => match role {
+                Role::Accent => Color::BrightCyan,
+                Role::Info => Color::Cyan,
+                Role::Success => Color::Green,
+                Role::Warning => Color::Yellow,
+                Role::Failure | Role::Matched => Color::Red,
+                Role::Muted => Color::White,
+                Role::Highlight => Color::Magenta,
+            },
+        }
+    }
+
+    /// Info, success, warning, failure and muted styles for the prompts, which draw with console
+    /// rather than colored. Solarized uses the nearest 256-color codes.
+    fn prompt_styles(self) -> [Style; 5] {
+        match self {
+            Theme::Default => [
+                Style::new().cyan(),
+                Style::new().green(),
+                Style::new().yellow(),
+                Style::new().red(),
+                Style::new().black().bright(),
+            ],
+            Theme::Solarized => [37, 100, 136, 160, 66].map(|code| Style::new().color256(code)),
+            Theme::NoColor => std::array::from_fn(|_| Style::new()),
+            Theme::HighContrast => [
+                Style::new().cyan().bright().bold(),
+                Style::new().green().bright().bold(),
+                Style::new().yellow().bright().bold(),
+                Style::new().red().bright().bold(),
+                Style::new(),
+            ],
+        }
+    }
+
+    /// The spinner's color, in indicatif's template syntax
+    fn spinner_color(self) -> &'static str {
+        match self {
+            Theme::Default => ":.cyan",
+            Theme::Solarized => ":.37",
+            Theme::NoColor => "",
+            Theme::HighContrast => ":.cyan.bold",
+        }
+    }
+}
+
+impl std::fmt::Display for Theme {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Theme::Default => write!(f, "default"),
+            Theme::Solarized => write!(f, "solarized"),
+            Theme::NoColor => write!(f, "no-color"),
+            Theme::HighContrast => write!(f, "high-contrast"),
+        }
+    }
+}
+
+/// Use `theme` for the rest of the run, with or without emoji
+pub fn set(theme: Theme, emoji: bool) {
+    THEME.store(theme as u8, Ordering::Relaxed);
+    EMOJI.store(emoji, Ordering::Relaxed);
+    if theme == Theme::NoColor {
+        colored::control::set_override(false);
+        console::set_colors_enabled(false);
+    }
+}
+
+pub fn get() -> Theme {
+    match THEME.load(Ordering::Relaxed) {
+        theme if theme == Theme::Solarized as u8 => Theme::Solarized,
+        theme if theme == Theme::NoColor as u8 => Theme::NoColor,
+        theme if theme == Theme::HighContrast as u8 => Theme::HighContrast,
+        _ => Theme::Default,
+    }
+}
+
+/// `emoji` unless `--no-emoji` is on, in which case the plain-text stand-in
+pub fn glyph(emoji: &'static str, plain: &'static str) -> &'static str {
+    if EMOJI.load(Ordering::Relaxed) {
+        emoji
+    } else {
+        plain
+    }
+}
+
+/// Marks something that worked
+pub fn done() -> ColoredString {
+    glyph("✓", "*").success()
+}
+
+/// Marks something that went wrong
+pub fn failed() -> ColoredString {
+    glyph("✗", "x").failure()
+}
+
+/// The double rule above and below banners
+pub fn banner_rule() -> ColoredString {
+    glyph("═", "=").repeat(60).accent()
+}
+
+/// Styles for the interactive prompts, in dialoguer's layout
+pub fn prompts() -> ColorfulTheme {
+    let [info, success, warning, failure, muted] = get().prompt_styles().map(Style::for_stderr);
+    let styled = |style: &Style, emoji: &'static str, plain: &'static str| {
+        style.apply_to(glyph(emoji, plain).to_string())
+    };
+    ColorfulTheme {
+        defaults_style: info.clone(),
+        prompt_prefix: styled(&warning, "?", "?"),
+        prompt_suffix: styled(&muted, "›", ">"),
+        success_prefix: styled(&success, "✔", "*"),
+        success_suffix: styled(&muted, "·", ":"),
+        error_prefix: styled(&failure, "✘", "x"),
+        error_style: failure,
+        hint_style: muted.clone(),
+        values_style: success.clone(),
+        active_item_style: info,
+        active_item_prefix: styled(&success, "❯", ">"),
+        checked_item_prefix: styled(&success, "✔", "[x]"),
+        unchecked_item_prefix: styled(&muted, "⬚", "[ ]"),
+        picked_item_prefix: styled(&success, "❯", ">"),
+        ..ColorfulTheme::default()
+    }
+}
+
+/// The spinner shown while waiting on the backend
+pub fn spinner() -> ProgressStyle {
+    let template = format!("{{spinner{}}} {{msg}}", get().spinner_color());
+    let style = ProgressStyle::default_spinner()
+        .template(&template)
+        .unwrap_or_else(|_| ProgressStyle::default_spinner());
+    if EMOJI.load(Ordering::Relaxed) {
+        style
+    } else {
+        style.tick_chars("|/-\\ ")
+    }
+}
+
+/// Style output by what it's for rather than by color, so the theme can choose
+pub trait Themed: Colorize + Sized {
+    fn accent(self) -> ColoredString {
+        get().paint(self.normal(), Role::Accent)
+    }
+
+    fn info(self) -> ColoredString {
+        get().paint(self.normal(), Role::Info)
+    }
+
+    fn success(self) -> ColoredString {
+        get().paint(self.normal(), Role::Success)
+    }
+
+    fn warning(self) -> ColoredString {
+        get().paint(self.normal(), Role::Warning)
+    }
+
+    fn failure(self) -> ColoredString {
+        get().paint(self.normal(), Role::Failure)
+    }
+
+    fn muted(self) -> ColoredString {
+        get().paint(self.normal(), Role::Muted)
+    }
+
+    fn highlight(self) -> ColoredString {
+        get().paint(self.normal(), Role::Highlight)
+    }
+
+    fn matched(self) -> ColoredString {
+        get().paint(self.normal(), Role::Matched)
+    }
+}
+
+impl<T: Colorize> Themed for T {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_display_roundtrip() -> Result<()> {
+        for theme in [Theme::Default, Theme::Solarized, Theme::NoColor, Theme::HighContrast] {
+            assert_eq!(Theme::parse(&theme.to_string())?, theme);
+        }
+        assert_eq!(Theme::parse("High_Contrast")?, Theme::HighContrast);
+        assert!(Theme::parse("neon").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_paint_by_role() {
+        let paint = |theme: Theme, role| theme.paint("text".normal(), role);
+
+        assert_eq!(paint(Theme::Default, Role::Success).fgcolor, Some(Color::Green));
+        assert_eq!(paint(Theme::Default, Role::Accent).fgcolor, Some(Color::BrightCyan));
+        assert_eq!(
+            paint(Theme::Solarized, Role::Warning).fgcolor,
+            Some(Color::TrueColor { r: 181, g: 137, b: 0 })
+        );
+        assert_eq!(paint(Theme::HighContrast, Role::Muted).fgcolor, None);
+        assert_eq!(paint(Theme::NoColor, Role::Failure).fgcolor, None);
+        assert!(paint(Theme::NoColor, Role::Matched).is_plain());
+    }
+}
@@ -0,0 +1,119 @@
+use crate::archive::Entry;
+use crate::search;
+use crate::til::writer;
+use anyhow::{anyhow, Result};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+/// Titles sharing at least this fraction of their keywords (out of the longer one's) cover the same topic
+const SIMILAR_TITLE_OVERLAP: f64 = 0.5;
+
+/// Resolve `--append-to`: a path as given, or relative to the archive (e.g. `git/rebase.md`)
+pub fn resolve_target(archive_path: &Path, target: &Path) -> Result<PathBuf> {
+    [target.to_path_buf(), archive_path.join(target)]
+        .into_iter()
+        .find(|path| path.is_file())
+        .ok_or_else(|| anyhow!("No TIL to append to at {:?}", target))
+}
+
+/// The existing TIL in `category` whose title looks most like `title`, if any is close enough
+pub fn find_similar<'a>(entries: &'a [Entry], title: &str, category: &str) -> Option<&'a Entry> {
+    let terms: HashSet<String> = search::terms(title).into_iter().collect();
+    if terms.is_empty() {
+        return None;
+    }
+
+    entries
+        .iter()
+        .filter(|entry| entry.category.as_deref() == Some(category))
+        .map(|entry| {
+            let existing: HashSet<String> = search::terms(&entry.title).into_iter().collect();
+            let shared = terms.intersection(&existing).count() as f64;
+            (shared / terms.len().max(existing.len()) as f64, entry)
+        })
+        .filter(|(overlap, _)| *overlap >= SIMILAR_TITLE_OVERLAP)
+        .max_by(|a, b| a.0.total_cmp(&b.0))
+        .map(|(_, entry)| entry)
+}
+
+/// Keep the existing title on the merged TIL, so the README's link to it stays accurate
+pub fn keep_title(existing: &str, merged: &str) -> String {
+    let Some(title) = writer::extract_title(existing) else {
+        return merged.to_string();
+    };
+
+    let mut replaced = false;
+    let lines: Vec<String> = merged
+        .lines()
+        .map(|line| {
+            if !replaced && line.trim().starts_with("# ") {
+                replaced = true;
+                format!("# {}", title)
+            } else {
+                line.to_string()
+            }
+        })
+        .collect();
+
+    if replaced {
+        lines.join("\n")
+    } else {
+        format!("# {}\n\n{}", title, merged.trim_start())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::archive::EntryKind;
+    use tempfile::TempDir;
+
+    fn entry(title: &str, category: &str) -> Entry {
+        Entry {
+            kind: EntryKind::Til,
+            title: title.to_string(),
+            path: PathBuf::from(format!("{}/{}.md", category, title)),
+            category: Some(category.to_string()),
+            reading_minutes: 1,
+            level: None,
+        }
+    }
+
+    #[test]
+    fn test_find_similar() {
+        let entries = vec![
+            entry("Git Rebase Onto", "git"),
+            entry("Squash Commits", "git"),
+            entry("Rebase Strategies", "history"),
+        ];
+
+        let similar = find_similar(&entries, "Interactive Rebase Onto Main", "git");
+        assert_eq!(similar.map(|e| e.title.as_str()), Some("Git Rebase Onto"));
+        assert!(find_similar(&entries, "Git Worktrees", "git").is_none());
+        assert!(find_similar(&entries, "Rebase Strategies", "postgres").is_none());
+    }
+
+    #[test]
+    fn test_keep_title() {
+        let existing = "# Git Rebase\n\nOld body";
+        assert_eq!(
+            keep_title(existing, "# Rebase Tricks\n\nOld body\n\nNew trick"),
+            "# Git Rebase\n\nOld body\n\nNew trick"
+        );
+        assert_eq!(keep_title(existing, "Old body"), "# Git Rebase\n\nOld body");
+    }
+
+    #[test]
+    fn test_resolve_target() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        std::fs::create_dir_all(temp_dir.path().join("git"))?;
+        std::fs::write(temp_dir.path().join("git/rebase.md"), "# Rebase")?;
+
+        assert_eq!(
+            resolve_target(temp_dir.path(), Path::new("git/rebase.md"))?,
+            temp_dir.path().join("git/rebase.md")
+        );
+        assert!(resolve_target(temp_dir.path(), Path::new("git/missing.md")).is_err());
+        Ok(())
+    }
+}
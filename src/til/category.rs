@@ -0,0 +1,385 @@
+use crate::archive;
+use crate::claude::run_claude_command;
+use crate::session::Session;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+
+const MAX_SUGGESTIONS: usize = 3;
+
+/// Offered when starting a session before there are favorites or an archive to go by
+const STARTER_CATEGORIES: &[&str] = &["git", "rust", "sql", "postgres", "python", "javascript"];
+
+/// Minimum word overlap (Jaccard) for two topics to count as similar
+const SIMILAR_TOPIC_THRESHOLD: f64 = 0.5;
+
+/// Remembered topic → category choices, used to pre-select categories for similar topics
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct CategoryMemory {
+    #[serde(default)]
+    pub mappings: Vec<CategoryMapping>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CategoryMapping {
+    pub topic: String,
+    pub category: String,
+}
+
+impl CategoryMemory {
+    /// Load remembered mappings, returning an empty memory if the file doesn't exist
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read category memory from {:?}", path))?;
+        toml::from_str(&content).with_context(|| "Failed to parse category memory")
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create directory {:?}", parent))?;
+        }
+
+        let content = toml::to_string_pretty(self).with_context(|| "Failed to serialize category memory")?;
+        fs::write(path, content)
+            .with_context(|| format!("Failed to write category memory to {:?}", path))
+    }
+
+    /// Find the category chosen for the most similar previously seen topic
+    pub fn lookup(&self, topic: &str) -> Option<&str> {
+        self.mappings
+            .iter()
+            .map(|m| (topic_similarity(topic, &m.topic), m))
+            .filter(|(score, _)| *score >= SIMILAR_TOPIC_THRESHOLD)
+            .max_by(|a, b| a.0.total_cmp(&b.0))
+            .map(|(_, m)| m.category.as_str())
+    }
+
+    /// Remember a topic → category choice, replacing any mapping for the same topic
+    pub fn remember(&mut self, topic: &str, category: &str) {
+        self.mappings.retain(|m| !m.topic.eq_ignore_ascii_case(topic));
+        self.mappings.push(CategoryMapping {
+            topic: topic.to_string(),
+            category: category.to_string(),
+        });
+    }
+}
+
+/// List the category directories that already exist in the archive
+pub fn existing_categories(archive_path: &Path) -> Result<Vec<String>> {
+    if !archive_path.is_dir() {
+        return Ok(Vec::new());
+    }
+
+    let mut categories = Vec::new();
+    collect_categories(archive_path, None, &mut categories)?;
+    categories.sort();
+    categories.dedup();
+
+    Ok(categories)
+}
+
+/// Each category in the archive with its number of TILs, most used first
+pub fn category_counts(archive_path: &Path) -> Result<Vec<(String, usize)>> {
+    let mut counts: HashMap<String, usize> = existing_categories(archive_path)?
+        .into_iter()
+        .map(|category| (category, 0))
+        .collect();
+    for path in archive::markdown_files(archive_path)? {
+        if let Some(category) = archive::entry_category(archive_path, &path) {
+            *counts.entry(category).or_default() += 1;
+        }
+    }
+
+    let mut counts: Vec<(String, usize)> = counts.into_iter().collect();
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    Ok(counts)
+}
+
+/// The categories to pick from when starting a session, with their TIL counts: favorites first,
+/// then the archive's. A repo with a fixed category list (`allowed`) only offers those.
+pub fn picker_options(favorites: &[String], counts: &[(String, usize)], allowed: &[String]) -> Vec<(String, usize)> {
+    let count_of = |category: &str| counts.iter().find(|(name, _)| name == category).map_or(0, |(_, count)| *count);
+
+    let mut names: Vec<&str> = favorites.iter().map(String::as_str).collect();
+    names.extend(counts.iter().map(|(name, _)| name.as_str()));
+    if !allowed.is_empty() {
+        names.retain(|name| allowed.iter().any(|category| category == name));
+        names.extend(allowed.iter().map(String::as_str));
+    } else if names.is_empty() {
+        names.extend(STARTER_CATEGORIES);
+    }
+
+    let mut seen = HashSet::new();
+    let names: Vec<&str> = names.into_iter().filter(|name| seen.insert(*name)).collect();
+
+    // Sub-categories follow their top-level category, wherever that ranks
+    let root = |name: &str| name.split('/').next().unwrap_or(name).to_string();
+    let mut roots: Vec<String> = Vec::new();
+    for name in &names {
+        if !roots.contains(&root(name)) {
+            roots.push(root(name));
+        }
+    }
+    roots
+        .iter()
+        .flat_map(|top| {
+            let mut family: Vec<&str> = names.iter().copied().filter(|name| root(name) == *top).collect();
+            family.sort_by_key(|name| name != top);
+            family
+        })
+        .map(|name| (name.to_string(), count_of(name)))
+        .collect()
+}
+
+/// Tidy a typed category: lowercase, with empty parts of a `parent/child` path dropped
+pub fn normalize(category: &str) -> String {
+    category
+        .split('/')
+        .map(str::trim)
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("/")
+        .to_lowercase()
+}
+
+/// Collect category directory names, looking through year/month folders of dated layouts.
+/// Directories inside a category are its sub-categories, e.g. `rust/async`.
+fn collect_categories(dir: &Path, parent: Option<&str>, categories: &mut Vec<String>) -> Result<()> {
+    let entries = fs::read_dir(dir).with_context(|| format!("Failed to read archive directory: {:?}", dir))?;
+
+    for entry in entries.filter_map(|entry| entry.ok()) {
+        let name = entry.file_name().to_string_lossy().to_string();
+        if !entry.path().is_dir() || name.starts_with('.') {
+            continue;
+        }
+        if name.chars().all(|c| c.is_ascii_digit()) {
+            collect_categories(&entry.path(), parent, categories)?;
+        } else {
+            let category = parent.map_or(name.clone(), |parent| format!("{}/{}", parent, name));
+            collect_categories(&entry.path(), Some(&category), categories)?;
+            categories.push(category);
+        }
+    }
+
+    Ok(())
+}
+
+/// Ask the backend to suggest categories for the session
+pub fn suggest_categories(session: &Session, existing: &[String]) -> Result<Vec<String>> {
+    let prompt = build_suggestion_prompt(session, existing);
+    let response = run_claude_command(&prompt, |_| {})?.text;
+    Ok(parse_suggestions(&response))
+}
+
+fn build_suggestion_prompt(session: &Session, existing: &[String]) -> String {
+    let existing = if existing.is_empty() {
+        "(none yet)".to_string()
+    } else {
+        existing.join(", ")
+    };
+
+    format!(
+        r#"Suggest {} categories for filing a TIL (Today I Learned) entry from this learning session.
+
+{}
+
+Existing categories in the archive: {}
+
+Prefer an existing category when one fits. Categories are short, lowercase, single words (e.g. git, rust, postgres). A narrower area of a broad category can be a sub-category written parent/child (e.g. rust/async, sql/indexes).
+
+Return ONLY the categories, one per line, best first."#,
+        MAX_SUGGESTIONS,
+        session.build_til_context(),
+        existing
+    )
+}
+
+fn parse_suggestions(response: &str) -> Vec<String> {
+    let mut seen = HashSet::new();
+
+    response
+        .lines()
+        .map(|line| {
+            let cat = line
+                .trim()
+                .trim_start_matches(|c: char| c.is_ascii_digit() || c == '.' || c == '-' || c == '*')
+                .trim()
+                .trim_matches('`');
+            normalize(cat).replace(' ', "-")
+        })
+        .filter(|cat| !cat.is_empty() && cat.chars().all(|c| c.is_alphanumeric() || "-_/".contains(c)))
+        .filter(|cat| seen.insert(cat.clone()))
+        .take(MAX_SUGGESTIONS)
+        .collect()
+}
+
+fn topic_words(topic: &str) -> HashSet<String> {
+    topic
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(|w| w.to_string())
+        .collect()
+}
+
+fn topic_similarity(a: &str, b: &str) -> f64 {
+    let a = topic_words(a);
+    let b = topic_words(b);
+    let union = a.union(&b).count();
+    if union == 0 {
+        return 0.0;
+    }
+    a.intersection(&b).count() as f64 / union as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session::LearningMode;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_parse_suggestions() {
+        let response = "1. git\n2. Version Control\n- `git`\nHere are some thoughts!\n3. Rust / Async";
+        assert_eq!(
+            parse_suggestions(response),
+            vec!["git".to_string(), "version-control".to_string(), "rust/async".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_build_suggestion_prompt_lists_existing() {
+        let session = Session::new(
+            LearningMode::DeepDive {
+                topic: "git rebase".to_string(),
+            },
+            None,
+        );
+        let prompt = build_suggestion_prompt(&session, &["git".to_string(), "rust".to_string()]);
+
+        assert!(prompt.contains("git, rust"));
+        assert!(prompt.contains("Deep Dive: git rebase"));
+    }
+
+    #[test]
+    fn test_memory_lookup_similar_topic() {
+        let mut memory = CategoryMemory::default();
+        memory.remember("git rebase onto", "git");
+        memory.remember("postgres indexes", "postgres");
+
+        assert_eq!(memory.lookup("git rebase"), Some("git"));
+        assert_eq!(memory.lookup("kubernetes pods"), None);
+    }
+
+    #[test]
+    fn test_memory_remember_replaces_topic() {
+        let mut memory = CategoryMemory::default();
+        memory.remember("Rust lifetimes", "rust");
+        memory.remember("rust lifetimes", "rust-advanced");
+
+        assert_eq!(memory.mappings.len(), 1);
+        assert_eq!(memory.lookup("rust lifetimes"), Some("rust-advanced"));
+    }
+
+    #[test]
+    fn test_memory_roundtrip() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let path = temp_dir.path().join("categories.toml");
+
+        let mut memory = CategoryMemory::default();
+        memory.remember("sql joins", "sql");
+        memory.save(&path)?;
+
+        let loaded = CategoryMemory::load(&path)?;
+        assert_eq!(loaded.lookup("sql joins"), Some("sql"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_existing_categories() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        fs::create_dir_all(temp_dir.path().join("rust"))?;
+        fs::create_dir_all(temp_dir.path().join("git"))?;
+        fs::create_dir_all(temp_dir.path().join("rust/async/2024"))?;
+        fs::write(temp_dir.path().join("stray.md"), "")?;
+
+        assert_eq!(
+            existing_categories(temp_dir.path())?,
+            vec!["git".to_string(), "rust".to_string(), "rust/async".to_string()]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_existing_categories_dated_layout() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        fs::create_dir_all(temp_dir.path().join("2024/06/rust"))?;
+        fs::create_dir_all(temp_dir.path().join("2024/07/rust"))?;
+        fs::create_dir_all(temp_dir.path().join("2025/01/git"))?;
+        fs::create_dir_all(temp_dir.path().join("2025/02/rust/async"))?;
+
+        assert_eq!(
+            existing_categories(temp_dir.path())?,
+            vec!["git".to_string(), "rust".to_string(), "rust/async".to_string()]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_category_counts() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        fs::create_dir_all(temp_dir.path().join("rust/2024"))?;
+        fs::create_dir_all(temp_dir.path().join("git"))?;
+        fs::create_dir_all(temp_dir.path().join("sql"))?;
+        fs::write(temp_dir.path().join("rust/2024/borrowing.md"), "# Borrowing")?;
+        fs::write(temp_dir.path().join("git/rebase.md"), "# Rebase")?;
+        fs::write(temp_dir.path().join("git/stash.md"), "# Stash")?;
+
+        assert_eq!(
+            category_counts(temp_dir.path())?,
+            vec![("git".to_string(), 2), ("rust".to_string(), 1), ("sql".to_string(), 0)]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_picker_options() {
+        let strings = |names: &[&str]| names.iter().map(|name| name.to_string()).collect::<Vec<_>>();
+        let counts = vec![("git".to_string(), 4), ("rust".to_string(), 2)];
+
+        assert_eq!(
+            picker_options(&strings(&["k8s", "rust"]), &counts, &[]),
+            vec![("k8s".to_string(), 0), ("rust".to_string(), 2), ("git".to_string(), 4)]
+        );
+        assert_eq!(
+            picker_options(&strings(&["k8s"]), &counts, &strings(&["sql", "git"])),
+            vec![("git".to_string(), 4), ("sql".to_string(), 0)]
+        );
+        assert_eq!(picker_options(&[], &[], &[]).len(), STARTER_CATEGORIES.len());
+    }
+
+    #[test]
+    fn test_picker_options_groups_sub_categories() {
+        let counts = vec![
+            ("rust/async".to_string(), 5),
+            ("git".to_string(), 3),
+            ("rust".to_string(), 1),
+            ("git/rebase".to_string(), 1),
+        ];
+        let names: Vec<String> = picker_options(&[], &counts, &[]).into_iter().map(|(name, _)| name).collect();
+
+        assert_eq!(names, vec!["rust", "rust/async", "git", "git/rebase"]);
+        assert_eq!(normalize(" Rust//Async/ "), "rust/async");
+    }
+}
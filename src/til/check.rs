@@ -0,0 +1,463 @@
+use crate::archive;
+use crate::til::writer::{
+    add_entry_to_category, anchor_for, capitalize_first, category_link, find_categories_end, without_author,
+};
+use anyhow::{Context, Result};
+use std::collections::HashSet;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A discrepancy between the README index and the archive on disk
+#[derive(Debug, Clone, PartialEq)]
+pub enum Issue {
+    /// A README entry links to a file that doesn't exist
+    MissingFile { title: String, link: String },
+    /// An archive file has no README entry
+    UnlistedFile { path: PathBuf, title: String },
+    /// The "N TILs & Counting" line disagrees with the archive
+    WrongCount { stated: usize, actual: usize },
+    /// A Categories link points at the wrong anchor
+    BadAnchor { category: String, anchor: String, expected: String },
+    /// A Categories link has no matching category section
+    StaleCategoryLink { category: String },
+    /// A category section isn't linked from the Categories list
+    MissingCategoryLink { category: String },
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Issue::MissingFile { title, link } => {
+                write!(f, "README entry \"{}\" points at missing file {}", title, link)
+            }
+            Issue::UnlistedFile { path, title } => {
+                write!(f, "{} (\"{}\") is not listed in the README", path.display(), title)
+            }
+            Issue::WrongCount { stated, actual } => {
+                write!(f, "README says {} TILs but the archive has {}", stated, actual)
+            }
+            Issue::BadAnchor {
+                category,
+                anchor,
+                expected,
+            } => write!(f, "Category link \"{}\" uses #{} instead of #{}", category, anchor, expected),
+            Issue::StaleCategoryLink { category } => {
+                write!(f, "Category link \"{}\" has no matching section", category)
+            }
+            Issue::MissingCategoryLink { category } => {
+                write!(f, "Category \"{}\" is missing from the Categories list", category)
+            }
+        }
+    }
+}
+
+/// Everything needed to check (and repair) a TIL repository's README
+#[derive(Debug)]
+pub struct Check {
+    pub readme_path: PathBuf,
+    pub readme: String,
+    pub issues: Vec<Issue>,
+    archive_dir: String,
+    files: Vec<ArchiveFile>,
+}
+
+#[derive(Debug, Clone)]
+struct ArchiveFile {
+    /// Path relative to the repo root with forward slashes, as the README links it
+    link: String,
+    path: PathBuf,
+    category: String,
+    /// Path relative to the archive directory, e.g. `rust/2024/borrow.md`
+    entry_path: String,
+    title: String,
+}
+
+impl Check {
+    /// The README with every issue repaired
+    pub fn fixed_readme(&self) -> Result<String> {
+        fix_readme(&self.readme, &self.archive_dir, &self.files)
+    }
+}
+
+/// Compare the README against the archive directory
+pub fn check_archive(repo_root: &Path, archive_dir: &str) -> Result<Check> {
+    let readme_path = repo_root.join("README.md");
+    let readme = fs::read_to_string(&readme_path).context("Failed to read README.md")?;
+    let files = archive_files(repo_root, archive_dir)?;
+    let issues = find_issues(&readme, archive_dir, &files);
+
+    Ok(Check {
+        readme_path,
+        readme,
+        issues,
+        archive_dir: archive_dir.to_string(),
+        files,
+    })
+}
+
+fn archive_files(repo_root: &Path, archive_dir: &str) -> Result<Vec<ArchiveFile>> {
+    let archive_path = repo_root.join(archive_dir);
+    let mut files = Vec::new();
+
+    for entry in archive::til_entries(&archive_path)? {
+        // Files directly in the archive root can't be filed under a category section
+        let Some(category) = entry.category else {
+            continue;
+        };
+        let entry_path = relative_entry_path(&archive_path, &entry.path);
+
+        files.push(ArchiveFile {
+            link: format!("{}/{}", archive_dir, entry_path),
+            path: entry.path,
+            category,
+            entry_path,
+            title: entry.title,
+        });
+    }
+
+    // Encrypted TILs can't be read for a title, so they're only matched by path
+    for path in archive::encrypted_files(&archive_path)? {
+        let Some(category) = archive::entry_category(&archive_path, &path) else {
+            continue;
+        };
+        let entry_path = relative_entry_path(&archive_path, &path);
+        let filename = path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let title = filename.split(".md.").next().unwrap_or_default().replace('_', " ");
+
+        files.push(ArchiveFile {
+            link: format!("{}/{}", archive_dir, entry_path),
+            path,
+            category,
+            entry_path,
+            title,
+        });
+    }
+
+    Ok(files)
+}
+
+fn relative_entry_path(archive_path: &Path, path: &Path) -> String {
+    path.strip_prefix(archive_path)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .replace('\\', "/")
+}
+
+fn find_issues(readme: &str, archive_dir: &str, files: &[ArchiveFile]) -> Vec<Issue> {
+    let mut issues = Vec::new();
+    let existing: HashSet<&str> = files.iter().map(|f| f.link.as_str()).collect();
+    let entries = readme_entries(readme, archive_dir);
+    let listed: HashSet<&str> = entries.iter().map(|(_, link)| link.as_str()).collect();
+
+    for (title, link) in &entries {
+        if !existing.contains(link.as_str()) {
+            issues.push(Issue::MissingFile {
+                title: title.clone(),
+                link: link.clone(),
+            });
+        }
+    }
+
+    for file in files {
+        if !listed.contains(file.link.as_str()) {
+            issues.push(Issue::UnlistedFile {
+                path: file.path.clone(),
+                title: file.title.clone(),
+            });
+        }
+    }
+
+    if let Some(stated) = stated_count(readme) {
+        if stated != files.len() {
+            issues.push(Issue::WrongCount {
+                stated,
+                actual: files.len(),
+            });
+        }
+    }
+
+    issues.extend(category_link_issues(readme));
+    issues
+}
+
+/// `(title, link)` for every README entry that links into the archive
+pub fn readme_entries(readme: &str, archive_dir: &str) -> Vec<(String, String)> {
+    readme
+        .lines()
+        .filter_map(parse_list_link)
+        .filter(|(_, link)| link.starts_with(&format!("{}/", archive_dir)))
+        .collect()
+}
+
+/// The README without the entries linking to `links`, its count lowered to match
+pub fn remove_entries(readme: &str, links: &[String]) -> String {
+    let mut removed = 0;
+    let mut lines: Vec<String> = Vec::new();
+    for line in readme.lines() {
+        match parse_list_link(line) {
+            Some((_, link)) if links.contains(&link) => removed += 1,
+            _ => lines.push(line.to_string()),
+        }
+    }
+    if let Some(stated) = stated_count(readme) {
+        if let Some(line) = lines.iter_mut().find(|line| line.contains("TILs & Counting")) {
+            *line = format!("{} TILs & Counting", stated.saturating_sub(removed));
+        }
+    }
+    format!("{}\n", lines.join("\n"))
+}
+
+/// Parse `- [title](link)` or `* [title](link)`
+fn parse_list_link(line: &str) -> Option<(String, String)> {
+    let line = without_author(line.trim());
+    let rest = line.strip_prefix("- [").or_else(|| line.strip_prefix("* ["))?;
+    let (title, rest) = rest.split_once("](")?;
+    let link = rest.strip_suffix(')')?;
+    Some((title.to_string(), link.to_string()))
+}
+
+fn stated_count(readme: &str) -> Option<usize> {
+    readme
+        .lines()
+        .find(|line| line.contains("TILs & Counting"))
+        .and_then(|line| line.split_whitespace().next())
+        .and_then(|count| count.parse().ok())
+}
+
+/// Category section headings (`### Name`), excluding the Categories list itself
+fn category_sections(readme: &str) -> Vec<String> {
+    readme
+        .lines()
+        .filter_map(|line| line.trim().strip_prefix("### "))
+        .filter(|name| *name != "Categories")
+        .map(|name| name.trim().to_string())
+        .collect()
+}
+
+/// `(line index, name, anchor)` for each link in the Categories list
+fn category_links(lines: &[String]) -> Vec<(usize, String, String)> {
+    let Some(start) = lines.iter().position(|l| l.trim() == "### Categories") else {
+        return Vec::new();
+    };
+    let end = find_categories_end(lines).unwrap_or(lines.len());
+
+    (start + 1..end)
+        .filter_map(|i| {
+            let (name, link) = parse_list_link(&lines[i])?;
+            let anchor = link.strip_prefix('#')?;
+            Some((i, name, anchor.to_string()))
+        })
+        .collect()
+}
+
+fn category_link_issues(readme: &str) -> Vec<Issue> {
+    let lines: Vec<String> = readme.lines().map(|s| s.to_string()).collect();
+    let sections = category_sections(readme);
+    let links = category_links(&lines);
+    let mut issues = Vec::new();
+
+    for (_, name, anchor) in &links {
+        match sections.iter().find(|s| s.eq_ignore_ascii_case(name)) {
+            Some(section) if anchor_for(section) != *anchor => issues.push(Issue::BadAnchor {
+                category: name.clone(),
+                anchor: anchor.clone(),
+                expected: anchor_for(section),
+            }),
+            Some(_) => {}
+            None => issues.push(Issue::StaleCategoryLink {
+                category: name.clone(),
+            }),
+        }
+    }
+
+    for section in &sections {
+        if !links.iter().any(|(_, name, _)| name.eq_ignore_ascii_case(section)) {
+            issues.push(Issue::MissingCategoryLink {
+                category: section.clone(),
+            });
+        }
+    }
+
+    issues
+}
+
+fn fix_readme(readme: &str, archive_dir: &str, files: &[ArchiveFile]) -> Result<String> {
+    let existing: HashSet<&str> = files.iter().map(|f| f.link.as_str()).collect();
+    let prefix = format!("{}/", archive_dir);
+
+    // Drop entries whose file is gone
+    let mut lines: Vec<String> = readme
+        .lines()
+        .filter(|line| match parse_list_link(line) {
+            Some((_, link)) if link.starts_with(&prefix) => existing.contains(link.as_str()),
+            _ => true,
+        })
+        .map(|s| s.to_string())
+        .collect();
+
+    // List files the README doesn't know about (creating category sections as needed)
+    let listed: HashSet<String> = readme_entries(&lines.join("\n"), archive_dir)
+        .into_iter()
+        .map(|(_, link)| link)
+        .collect();
+    for file in files.iter().filter(|f| !listed.contains(&f.link)) {
+        add_entry_to_category(&mut lines, archive_dir, &file.category, &file.entry_path, &file.title, None)?;
+    }
+
+    fix_category_links(&mut lines);
+
+    for line in lines.iter_mut() {
+        if line.contains("TILs & Counting") {
+            *line = format!("{} TILs & Counting", files.len());
+            break;
+        }
+    }
+
+    Ok(format!("{}\n", lines.join("\n")))
+}
+
+fn fix_category_links(lines: &mut Vec<String>) {
+    let sections = category_sections(&lines.join("\n"));
+    let links = category_links(lines);
+
+    // Rewrite or drop existing links, bottom-up so indices stay valid
+    for (i, name, _) in links.iter().rev() {
+        match sections.iter().find(|s| s.eq_ignore_ascii_case(name)) {
+            Some(section) => {
+                let indent = &lines[*i][..lines[*i].len() - lines[*i].trim_start().len()];
+                lines[*i] = format!("{}* [{}](#{})", indent, name, anchor_for(section));
+            }
+            None => {
+                lines.remove(*i);
+            }
+        }
+    }
+
+    for section in &sections {
+        let linked = links.iter().any(|(_, name, _)| name.eq_ignore_ascii_case(section));
+        if !linked {
+            if let Some(end) = find_categories_end(lines) {
+                lines.insert(end, category_link(&capitalize_first(section)));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const README: &str = "# Today I Learned\n\n2 TILs & Counting\n\n---\n\n### Categories\n\n* [Git](#git)\n\n---\n\n### Git\n\n- [Rebase](archive/git/rebase.md) by @jane\n- [Gone](archive/git/gone.md)\n";
+
+    fn setup(readme: &str) -> Result<TempDir> {
+        let temp_dir = TempDir::new()?;
+        fs::create_dir_all(temp_dir.path().join("archive/git"))?;
+        fs::create_dir_all(temp_dir.path().join("archive/rust"))?;
+        fs::write(temp_dir.path().join("archive/git/rebase.md"), "# Rebase\n")?;
+        fs::write(temp_dir.path().join("archive/rust/borrow.md"), "# Borrowing\n")?;
+        fs::write(temp_dir.path().join("README.md"), readme)?;
+        Ok(temp_dir)
+    }
+
+    #[test]
+    fn test_anchor_for() {
+        assert_eq!(anchor_for("Git"), "git");
+        assert_eq!(anchor_for("Version Control"), "version-control");
+        assert_eq!(anchor_for("C++"), "c");
+        assert_eq!(anchor_for("Rust – Async"), "rust--async");
+    }
+
+    #[test]
+    fn test_check_finds_issues() -> Result<()> {
+        let temp_dir = setup(README)?;
+        let check = check_archive(temp_dir.path(), "archive")?;
+
+        assert_eq!(
+            check.issues,
+            vec![
+                Issue::MissingFile {
+                    title: "Gone".to_string(),
+                    link: "archive/git/gone.md".to_string(),
+                },
+                Issue::UnlistedFile {
+                    path: temp_dir.path().join("archive/rust/borrow.md"),
+                    title: "Borrowing".to_string(),
+                },
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_check_keeps_encrypted_entries() -> Result<()> {
+        let readme = "# Today I Learned\n\n1 TILs & Counting\n\n---\n\n### Categories\n\n* [Git](#git)\n\n---\n\n### Git\n\n- [Secret](archive/git/secret.md.age)\n";
+        let temp_dir = TempDir::new()?;
+        fs::create_dir_all(temp_dir.path().join("archive/git"))?;
+        fs::write(temp_dir.path().join("archive/git/secret.md.age"), "-----BEGIN AGE ENCRYPTED FILE-----\n")?;
+        fs::write(temp_dir.path().join("README.md"), readme)?;
+
+        let check = check_archive(temp_dir.path(), "archive")?;
+
+        assert!(check.issues.is_empty(), "unexpected issues: {:?}", check.issues);
+        Ok(())
+    }
+
+    #[test]
+    fn test_check_category_links() {
+        let readme = "### Categories\n\n* [Git](#gits)\n* [Old](#old)\n\n---\n\n### Git\n\n### Rust\n";
+        assert_eq!(
+            category_link_issues(readme),
+            vec![
+                Issue::BadAnchor {
+                    category: "Git".to_string(),
+                    anchor: "gits".to_string(),
+                    expected: "git".to_string(),
+                },
+                Issue::StaleCategoryLink {
+                    category: "Old".to_string(),
+                },
+                Issue::MissingCategoryLink {
+                    category: "Rust".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_fix_repairs_everything() -> Result<()> {
+        let readme = README.replace("(#git)", "(#gits)\n* [Old](#old)");
+        let temp_dir = setup(&readme)?;
+        let check = check_archive(temp_dir.path(), "archive")?;
+        assert!(check.issues.len() > 2);
+
+        let fixed = check.fixed_readme()?;
+        assert!(fixed.contains("2 TILs & Counting"));
+        assert!(fixed.contains("* [Git](#git)\n"));
+        assert!(fixed.contains("* [Rust](#rust)\n"));
+        assert!(!fixed.contains("gone.md"));
+        assert!(!fixed.contains("[Old]"));
+        assert!(fixed.contains("### Rust\n\n- [Borrowing](archive/rust/borrow.md)"));
+
+        fs::write(temp_dir.path().join("README.md"), &fixed)?;
+        assert!(check_archive(temp_dir.path(), "archive")?.issues.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_remove_entries() {
+        let readme = "# TIL\n3 TILs & Counting\n### Git\n\n- [Rebase](archive/git/rebase.md)\n\
+                      - [Rebasing](archive/git/rebasing.md)\n- [Stash](archive/git/stash.md)\n";
+        let removed = remove_entries(readme, &["archive/git/rebasing.md".to_string()]);
+        assert_eq!(
+            removed,
+            "# TIL\n2 TILs & Counting\n### Git\n\n- [Rebase](archive/git/rebase.md)\n- [Stash](archive/git/stash.md)\n"
+        );
+    }
+}
@@ -0,0 +1,208 @@
+use crate::archive::Entry;
+use crate::claude::{no_tools_settings, send, CancellationToken, ClaudeResponse, Resume};
+use crate::search;
+use anyhow::Result;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashSet;
+use std::hash::{Hash, Hasher};
+
+/// Titles sharing this fraction of their keywords (out of the longer one's) look like the same topic
+const TITLE_SIMILARITY: f64 = 0.6;
+
+/// Bodies sharing this fraction of their shingles (Jaccard) say much the same thing
+const BODY_SIMILARITY: f64 = 0.4;
+
+/// Words per shingle when comparing bodies
+const SHINGLE_WORDS: usize = 3;
+
+/// TILs that look like they cover the same thing
+#[derive(Debug, Clone)]
+pub struct Group {
+    pub entries: Vec<Entry>,
+    /// How alike the closest pair in the group is, from 0 to 1
+    pub similarity: f64,
+}
+
+/// Group near-duplicate TILs: a pair is near-duplicate when its titles share most keywords or its
+/// bodies most shingles, and groups join pairs that share an entry. Most alike first.
+pub fn find_groups(tils: &[(Entry, String)]) -> Vec<Group> {
+    let fingerprints: Vec<(HashSet<String>, HashSet<u64>)> = tils
+        .iter()
+        .map(|(entry, content)| (search::terms(&entry.title).into_iter().collect(), shingles(content)))
+        .collect();
+
+    // Union-find over the TILs, with the best similarity seen for each group's root
+    let mut parent: Vec<usize> = (0..tils.len()).collect();
+    let mut best = vec![0.0_f64; tils.len()];
+    for i in 0..tils.len() {
+        for j in i + 1..tils.len() {
+            let similarity = overlap(&fingerprints[i].0, &fingerprints[j].0, TITLE_SIMILARITY)
+                .max(jaccard(&fingerprints[i].1, &fingerprints[j].1, BODY_SIMILARITY));
+            if similarity == 0.0 {
+                continue;
+            }
+            let (root_i, root_j) = (find(&mut parent, i), find(&mut parent, j));
+            parent[root_j] = root_i;
+            best[root_i] = best[root_i].max(best[root_j]).max(similarity);
+        }
+    }
+
+    let mut groups: Vec<Group> = Vec::new();
+    let mut roots: Vec<usize> = Vec::new();
+    for (i, (entry, _)) in tils.iter().enumerate() {
+        let root = find(&mut parent, i);
+        match roots.iter().position(|r| *r == root) {
+            Some(index) => groups[index].entries.push(entry.clone()),
+            None => {
+                roots.push(root);
+                groups.push(Group {
+                    entries: vec![entry.clone()],
+                    similarity: best[root],
+                });
+            }
+        }
+    }
+    groups.retain(|group| group.entries.len() > 1);
+    groups.sort_by(|a, b| b.similarity.total_cmp(&a.similarity));
+    groups
+}
+
+/// Have the backend combine near-duplicate TILs into `keep`, streaming the result
+pub fn merge<F>(keep: &str, others: &[String], on_text: F) -> Result<ClaudeResponse>
+where
+    F: FnMut(&str),
+{
+    let prompt = build_merge_prompt(keep, others);
+    send(Resume::default(), &prompt, Some(&no_tools_settings()), on_text, CancellationToken::new())
+}
+
+fn build_merge_prompt(keep: &str, others: &[String]) -> String {
+    let duplicates: Vec<String> = others
+        .iter()
+        .map(|other| format!("<duplicate>\n{}\n</duplicate>", other.trim()))
+        .collect();
+    format!(
+        r#"These TIL (Today I Learned) entries in my archive overlap. Combine them into one entry.
+
+Keep this one, with its title and structure:
+
+<til>
+{}
+</til>
+
+Fold in what the others add:
+
+{}
+
+Keep every distinct trick, example and caveat, but say each thing once. Reply with only the complete, merged markdown."#,
+        keep.trim(),
+        duplicates.join("\n\n")
+    )
+}
+
+/// Shared keywords out of the longer title's, or 0 when below `threshold`
+fn overlap(a: &HashSet<String>, b: &HashSet<String>, threshold: f64) -> f64 {
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+    let score = a.intersection(b).count() as f64 / a.len().max(b.len()) as f64;
+    if score >= threshold {
+        score
+    } else {
+        0.0
+    }
+}
+
+/// Jaccard similarity, or 0 when below `threshold`
+fn jaccard(a: &HashSet<u64>, b: &HashSet<u64>, threshold: f64) -> f64 {
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+    let shared = a.intersection(b).count();
+    let score = shared as f64 / (a.len() + b.len() - shared) as f64;
+    if score >= threshold {
+        score
+    } else {
+        0.0
+    }
+}
+
+/// Hashes of every run of `SHINGLE_WORDS` words in the body, ignoring case and punctuation
+fn shingles(content: &str) -> HashSet<u64> {
+    let words: Vec<String> = content
+        .lines()
+        .filter(|line| !line.trim_start().starts_with("# "))
+        .flat_map(|line| line.split(|c: char| !c.is_alphanumeric()))
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect();
+    words
+        .windows(SHINGLE_WORDS)
+        .map(|window| {
+            let mut hasher = DefaultHasher::new();
+            window.hash(&mut hasher);
+            hasher.finish()
+        })
+        .collect()
+}
+
+fn find(parent: &mut [usize], i: usize) -> usize {
+    let mut root = i;
+    while parent[root] != root {
+        root = parent[root];
+    }
+    parent[i] = root;
+    root
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::archive::EntryKind;
+    use std::path::PathBuf;
+
+    fn til(title: &str, body: &str) -> (Entry, String) {
+        let entry = Entry {
+            kind: EntryKind::Til,
+            title: title.to_string(),
+            path: PathBuf::from(format!("{}.md", title)),
+            category: Some("git".to_string()),
+            reading_minutes: 1,
+            level: None,
+        };
+        (entry, format!("# {}\n\n{}", title, body))
+    }
+
+    #[test]
+    fn test_find_groups_by_title_or_body() {
+        let body = "Use git rebase with the interactive flag to squash fixup commits before you open a pull request";
+        let tils = vec![
+            til("Interactive Rebase", "Squash commits with rebase -i."),
+            til("Git Stash Basics", "Stash saves uncommitted work on a stack."),
+            til("Rebase Interactive Mode", "Reorder commits in the editor."),
+            til("Squashing Fixups", body),
+            til("Cleaning Up Before a PR", &format!("{}.", body)),
+            til("Bisect", "Binary search for the commit that broke the build."),
+        ];
+
+        let groups = find_groups(&tils);
+        let titles: Vec<Vec<&str>> = groups
+            .iter()
+            .map(|group| group.entries.iter().map(|entry| entry.title.as_str()).collect())
+            .collect();
+
+        assert_eq!(
+            titles,
+            vec![vec!["Squashing Fixups", "Cleaning Up Before a PR"], vec!["Interactive Rebase", "Rebase Interactive Mode"]]
+        );
+        assert!(groups[0].similarity > 0.9);
+        assert!(groups[1].similarity > 0.6 && groups[1].similarity < 0.7);
+    }
+
+    #[test]
+    fn test_build_merge_prompt() {
+        let prompt = build_merge_prompt("# Rebase\n\nUse -i.\n", &["# Rebasing\n\nAutosquash.".to_string()]);
+        assert!(prompt.contains("<til>\n# Rebase\n\nUse -i.\n</til>"));
+        assert!(prompt.contains("<duplicate>\n# Rebasing\n\nAutosquash.\n</duplicate>"));
+    }
+}
@@ -0,0 +1,94 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A generated TIL waiting for review before it goes into the archive
+#[derive(Debug, Clone)]
+pub struct Draft {
+    pub category: String,
+    pub filename: String,
+    pub path: PathBuf,
+    pub content: String,
+}
+
+/// Store a draft under `<drafts_dir>/<category>/<filename>`, never overwriting another draft
+pub fn save_draft(drafts_dir: &Path, category: &str, filename: &str, content: &str) -> Result<PathBuf> {
+    let category_dir = drafts_dir.join(category.to_lowercase());
+    fs::create_dir_all(&category_dir)
+        .with_context(|| format!("Failed to create drafts directory {:?}", category_dir))?;
+
+    let stem = filename.trim_end_matches(".md");
+    let mut path = category_dir.join(filename);
+    let mut n = 2;
+    while path.exists() {
+        path = category_dir.join(format!("{}_{}.md", stem, n));
+        n += 1;
+    }
+
+    fs::write(&path, content).with_context(|| format!("Failed to write draft {:?}", path))?;
+    Ok(path)
+}
+
+/// List all drafts, ordered by category then filename
+pub fn list_drafts(drafts_dir: &Path) -> Result<Vec<Draft>> {
+    if !drafts_dir.is_dir() {
+        return Ok(Vec::new());
+    }
+
+    let mut drafts = Vec::new();
+    for path in crate::archive::markdown_files(drafts_dir)? {
+        let category = path
+            .parent()
+            .and_then(|p| p.file_name())
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let filename = path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let content =
+            fs::read_to_string(&path).with_context(|| format!("Failed to read draft {:?}", path))?;
+
+        drafts.push(Draft {
+            category,
+            filename,
+            path,
+            content,
+        });
+    }
+
+    Ok(drafts)
+}
+
+pub fn remove_draft(draft: &Draft) -> Result<()> {
+    fs::remove_file(&draft.path).with_context(|| format!("Failed to remove draft {:?}", draft.path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_save_and_list_drafts() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+
+        let first = save_draft(temp_dir.path(), "Git", "rebase.md", "# Rebase\n")?;
+        let second = save_draft(temp_dir.path(), "git", "rebase.md", "# Rebase again\n")?;
+        save_draft(temp_dir.path(), "rust", "borrow.md", "# Borrow\n")?;
+
+        assert_eq!(first, temp_dir.path().join("git/rebase.md"));
+        assert_eq!(second, temp_dir.path().join("git/rebase_2.md"));
+
+        let drafts = list_drafts(temp_dir.path())?;
+        assert_eq!(drafts.len(), 3);
+        assert_eq!(drafts[0].category, "git");
+        assert_eq!(drafts[0].filename, "rebase.md");
+        assert_eq!(drafts[2].content, "# Borrow\n");
+
+        remove_draft(&drafts[0])?;
+        assert_eq!(list_drafts(temp_dir.path())?.len(), 2);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,231 @@
+use crate::claude::transcripts::content_text;
+use crate::claude::{run_claude_command, Usage};
+use crate::modes::{delimit, UNTRUSTED_NOTICE};
+use anyhow::{anyhow, Context, Result};
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+
+/// Conversations longer than this many characters are read in parts, one backend call each
+const MAX_PART_CHARS: usize = 300_000;
+
+/// Each proposed TIL starts with a line like `=== TIL: rust ===`
+const TIL_MARKER: &str = "=== TIL:";
+
+/// A TIL proposed from a conversation log
+#[derive(Debug, Clone, PartialEq)]
+pub struct Learning {
+    pub category: String,
+    pub content: String,
+}
+
+/// Read a conversation log as plain text. Understands Claude Code transcripts (`.jsonl`),
+/// claude.ai exports (`conversations.json`), and anything else as text (e.g. a saved transcript).
+pub fn read_conversation(path: &Path) -> Result<String> {
+    let raw = fs::read_to_string(path).with_context(|| format!("Failed to read {:?}", path))?;
+    let text = match path.extension().and_then(|ext| ext.to_str()) {
+        Some("jsonl") => from_claude_code(&raw),
+        Some("json") => from_claude_export(&raw)?,
+        _ => raw,
+    };
+
+    if text.trim().is_empty() {
+        return Err(anyhow!("No conversation found in {:?}", path));
+    }
+    Ok(text)
+}
+
+/// Ask the backend for the distinct learnings in a conversation, one proposed TIL each
+pub fn extract_learnings(conversation: &str, categories: &[String]) -> Result<(Vec<Learning>, Usage)> {
+    let mut learnings = Vec::new();
+    let mut usage = Usage::default();
+    for part in split_parts(conversation, MAX_PART_CHARS) {
+        let response = run_claude_command(&build_extract_prompt(part, categories), |_| {})?;
+        usage.add(&response.usage);
+        learnings.extend(parse_learnings(&response.text));
+    }
+    Ok((learnings, usage))
+}
+
+fn build_extract_prompt(conversation: &str, categories: &[String]) -> String {
+    let existing = if categories.is_empty() {
+        "none yet".to_string()
+    } else {
+        categories.join(", ")
+    };
+
+    format!(
+        r#"Below is a log of a past conversation in which I learned things, most of which I never wrote down. {}
+
+{}
+
+Identify the distinct, reusable learnings in it: a technique, a command, a gotcha, a concept that clicked. Skip small talk, dead ends and anything too specific to that one project to be useful again. Merge repeats of the same learning into one.
+
+Write one TIL (Today I Learned) entry per learning, in the format /til uses: a `# Title` heading, a short explanation, and a working code example where it helps.
+
+Start each entry with a line of the form `{} <category> ===`, where the category is short, lowercase and a single word. Prefer these existing categories when one fits: {}.
+
+If there's nothing worth a TIL, reply with just: NONE"#,
+        UNTRUSTED_NOTICE,
+        delimit("conversation", conversation),
+        TIL_MARKER,
+        existing
+    )
+}
+
+/// Split the response at its `=== TIL: <category> ===` markers
+fn parse_learnings(response: &str) -> Vec<Learning> {
+    let mut learnings = Vec::new();
+    let mut current: Option<(String, Vec<&str>)> = None;
+
+    for line in response.lines() {
+        if let Some(rest) = line.trim().strip_prefix(TIL_MARKER) {
+            learnings.extend(current.take().and_then(finish));
+            let category = rest.trim().trim_end_matches('=').trim().to_lowercase();
+            current = Some((category, Vec::new()));
+        } else if let Some((_, ref mut lines)) = current {
+            lines.push(line);
+        }
+    }
+    learnings.extend(current.and_then(finish));
+    learnings
+}
+
+fn finish((category, lines): (String, Vec<&str>)) -> Option<Learning> {
+    let content = lines.join("\n").trim().to_string();
+    if content.is_empty() {
+        return None;
+    }
+    let category = if category.is_empty() { "uncategorized".to_string() } else { category };
+    Some(Learning {
+        category,
+        content: format!("{}\n", content),
+    })
+}
+
+/// Break text into parts of at most `max` bytes, at line ends where possible
+fn split_parts(text: &str, max: usize) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut rest = text;
+    while rest.len() > max {
+        let mut cut = max;
+        while !rest.is_char_boundary(cut) {
+            cut -= 1;
+        }
+        let cut = rest[..cut].rfind('\n').map_or(cut, |newline| newline + 1);
+        parts.push(&rest[..cut]);
+        rest = &rest[cut..];
+    }
+    if !rest.trim().is_empty() {
+        parts.push(rest);
+    }
+    parts
+}
+
+/// A Claude Code session transcript: one JSON event per line. Only the text of user and
+/// assistant turns is kept, not tool calls or their results.
+fn from_claude_code(raw: &str) -> String {
+    raw.lines()
+        .filter_map(|line| serde_json::from_str::<Value>(line).ok())
+        .filter_map(|event| {
+            let speaker = match event["type"].as_str()? {
+                "user" => "User",
+                "assistant" => "Assistant",
+                _ => return None,
+            };
+            let text = content_text(&event["message"]["content"]);
+            (!text.trim().is_empty()).then(|| format!("{}: {}\n", speaker, text.trim()))
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// A claude.ai data export: an array of conversations, each with its `chat_messages`
+fn from_claude_export(raw: &str) -> Result<String> {
+    let export: Value = serde_json::from_str(raw).context("Not a valid JSON export")?;
+    let conversations = match export {
+        Value::Array(conversations) => conversations,
+        conversation => vec![conversation],
+    };
+
+    let mut text = String::new();
+    for conversation in &conversations {
+        let Some(messages) = conversation["chat_messages"].as_array() else {
+            continue;
+        };
+        if let Some(name) = conversation["name"].as_str().filter(|name| !name.is_empty()) {
+            text.push_str(&format!("## {}\n\n", name));
+        }
+        for message in messages {
+            let speaker = if message["sender"] == "human" { "User" } else { "Assistant" };
+            let body = match message["text"].as_str().filter(|body| !body.trim().is_empty()) {
+                Some(body) => body.to_string(),
+                None => content_text(&message["content"]),
+            };
+            if !body.trim().is_empty() {
+                text.push_str(&format!("{}: {}\n\n", speaker, body.trim()));
+            }
+        }
+    }
+    Ok(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_learnings() {
+        let response = "Here they are.\n\n=== TIL: Git ===\n# Stash untracked files\n\nUse `git stash -u`.\n\n=== TIL: ===\n# Something\n\nBody\n=== TIL: rust ===\n\n";
+        assert_eq!(
+            parse_learnings(response),
+            vec![
+                Learning {
+                    category: "git".to_string(),
+                    content: "# Stash untracked files\n\nUse `git stash -u`.\n".to_string(),
+                },
+                Learning {
+                    category: "uncategorized".to_string(),
+                    content: "# Something\n\nBody\n".to_string(),
+                },
+            ]
+        );
+        assert!(parse_learnings("NONE").is_empty());
+    }
+
+    #[test]
+    fn test_from_claude_code() {
+        let raw = [
+            r#"{"type":"summary","summary":"Fixing the build"}"#,
+            r#"{"type":"user","message":{"role":"user","content":"Why does cargo rebuild everything?"}}"#,
+            r#"{"type":"assistant","message":{"content":[{"type":"text","text":"Because RUSTFLAGS changed."},{"type":"tool_use","name":"Bash","input":{}}]}}"#,
+            r#"{"type":"user","message":{"content":[{"type":"tool_result","content":"ok"}]}}"#,
+        ]
+        .join("\n");
+        assert_eq!(
+            from_claude_code(&raw),
+            "User: Why does cargo rebuild everything?\n\nAssistant: Because RUSTFLAGS changed.\n"
+        );
+    }
+
+    #[test]
+    fn test_from_claude_export() -> Result<()> {
+        let raw = r#"[{"name":"Postgres locks","chat_messages":[
+            {"sender":"human","text":"What takes an ACCESS EXCLUSIVE lock?"},
+            {"sender":"assistant","text":"","content":[{"type":"text","text":"ALTER TABLE, mostly."}]}
+        ]}]"#;
+        assert_eq!(
+            from_claude_export(raw)?,
+            "## Postgres locks\n\nUser: What takes an ACCESS EXCLUSIVE lock?\n\nAssistant: ALTER TABLE, mostly.\n\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_split_parts() {
+        assert_eq!(split_parts("aaa\nbbb\nccc\n", 9), vec!["aaa\nbbb\n", "ccc\n"]);
+        assert_eq!(split_parts("short", 100), vec!["short"]);
+        // No newline to break at: cut mid-line, on a char boundary
+        assert_eq!(split_parts("ééé", 3), vec!["é", "é", "é"]);
+    }
+}
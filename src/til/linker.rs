@@ -0,0 +1,285 @@
+use crate::{archive, files};
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+/// Titles shorter than this are too generic to link automatically
+const MIN_TITLE_LEN: usize = 4;
+
+/// Another archive entry that mentions can be linked to
+#[derive(Debug, Clone)]
+pub struct LinkTarget {
+    pub title: String,
+    pub path: PathBuf,
+}
+
+/// Collect link targets for every TIL in the archive
+pub fn archive_targets(archive_path: &Path) -> Result<Vec<LinkTarget>> {
+    Ok(archive::til_entries(archive_path)?
+        .into_iter()
+        .map(|entry| LinkTarget {
+            title: entry.title,
+            path: entry.path,
+        })
+        .collect())
+}
+
+/// Link the first mention of each target's title in `content`, which will live at `file_path`
+pub fn link_mentions(content: &str, file_path: &Path, targets: &[LinkTarget]) -> String {
+    let from_dir = file_path.parent().unwrap_or(Path::new(""));
+
+    // Longest titles first so "Git Rebase Onto" wins over "Git Rebase"
+    let mut targets: Vec<&LinkTarget> = targets
+        .iter()
+        .filter(|t| t.path != file_path && t.title.len() >= MIN_TITLE_LEN)
+        .collect();
+    targets.sort_by_key(|t| std::cmp::Reverse(t.title.len()));
+
+    let mut content = content.to_string();
+    for target in targets {
+        let href = relative_path(from_dir, &target.path);
+        let href = href.to_string_lossy().replace('\\', "/");
+        if content.contains(&format!("]({})", href)) {
+            continue;
+        }
+        if let Some(linked) = link_first_mention(&content, &target.title, &href) {
+            content = linked;
+        }
+    }
+
+    content
+}
+
+/// Re-run linking over every TIL in the archive, returning the paths that changed.
+/// With `dry_run`, nothing is written.
+pub fn relink_archive(archive_path: &Path, dry_run: bool) -> Result<Vec<PathBuf>> {
+    let targets = archive_targets(archive_path)?;
+    let mut changed = Vec::new();
+
+    for target in &targets {
+        let content = fs::read_to_string(&target.path)
+            .with_context(|| format!("Failed to read TIL: {:?}", target.path))?;
+        let linked = link_mentions(&content, &target.path, &targets);
+        if linked == content {
+            continue;
+        }
+
+        if !dry_run {
+            files::write_atomic(&target.path, &linked)
+                .with_context(|| format!("Failed to write TIL: {:?}", target.path))?;
+        }
+        changed.push(target.path.clone());
+    }
+
+    Ok(changed)
+}
+
+fn link_first_mention(content: &str, title: &str, href: &str) -> Option<String> {
+    let needle = title.to_ascii_lowercase();
+    let mut in_code_block = false;
+    let mut offset = 0;
+
+    for line in content.split_inclusive('\n') {
+        if line.trim_start().starts_with("```") {
+            in_code_block = !in_code_block;
+        } else if !in_code_block && !line.trim_start().starts_with('#') {
+            if let Some(pos) = find_linkable(line, &needle) {
+                let start = offset + pos;
+                let end = start + title.len();
+                return Some(format!(
+                    "{}[{}]({}){}",
+                    &content[..start],
+                    &content[start..end],
+                    href,
+                    &content[end..]
+                ));
+            }
+        }
+        offset += line.len();
+    }
+
+    None
+}
+
+/// Find the first occurrence of `needle` in `line` at word boundaries, outside code spans and links
+fn find_linkable(line: &str, needle: &str) -> Option<usize> {
+    let lower = line.to_ascii_lowercase();
+    let protected = protected_ranges(line);
+    let bytes = lower.as_bytes();
+
+    let mut search_from = 0;
+    while let Some(found) = lower[search_from..].find(needle) {
+        let start = search_from + found;
+        let end = start + needle.len();
+
+        let boundary_before = start == 0 || !bytes[start - 1].is_ascii_alphanumeric();
+        let boundary_after = end == bytes.len() || !bytes[end].is_ascii_alphanumeric();
+        let is_protected = protected.iter().any(|&(s, e)| start < e && end > s);
+
+        if boundary_before && boundary_after && !is_protected {
+            return Some(start);
+        }
+        search_from = start + 1;
+        while !lower.is_char_boundary(search_from) {
+            search_from += 1;
+        }
+    }
+
+    None
+}
+
+/// Byte ranges of inline code spans and existing markdown links
+fn protected_ranges(line: &str) -> Vec<(usize, usize)> {
+    let mut ranges = Vec::new();
+    let bytes = line.as_bytes();
+    let mut i = 0;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            b'`' => {
+                let end = line[i + 1..].find('`').map_or(bytes.len(), |e| i + 1 + e + 1);
+                ranges.push((i, end));
+                i = end;
+            }
+            b'[' => {
+                let end = line[i..]
+                    .find("](")
+                    .and_then(|close| line[i + close..].find(')').map(|paren| i + close + paren + 1));
+                match end {
+                    Some(end) => {
+                        ranges.push((i, end));
+                        i = end;
+                    }
+                    None => i += 1,
+                }
+            }
+            _ => i += 1,
+        }
+    }
+
+    ranges
+}
+
+/// Compute a relative path from a directory to a target file
+pub fn relative_path(from_dir: &Path, to: &Path) -> PathBuf {
+    let from: Vec<Component> = from_dir.components().collect();
+    let to_components: Vec<Component> = to.components().collect();
+
+    let common = from
+        .iter()
+        .zip(to_components.iter())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let mut result = PathBuf::new();
+    for _ in common..from.len() {
+        result.push("..");
+    }
+    for component in &to_components[common..] {
+        result.push(component.as_os_str());
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn target(title: &str, path: &str) -> LinkTarget {
+        LinkTarget {
+            title: title.to_string(),
+            path: PathBuf::from(path),
+        }
+    }
+
+    #[test]
+    fn test_relative_path() {
+        assert_eq!(
+            relative_path(Path::new("/til/archive/git"), Path::new("/til/archive/git/rebase.md")),
+            PathBuf::from("rebase.md")
+        );
+        assert_eq!(
+            relative_path(Path::new("/til/archive/rust"), Path::new("/til/archive/git/rebase.md")),
+            PathBuf::from("../git/rebase.md")
+        );
+    }
+
+    #[test]
+    fn test_link_mentions_first_only() {
+        let content = "# Squash Commits\n\nUse git rebase here. Then git rebase again.\n";
+        let targets = vec![target("Git Rebase", "/a/git/git_rebase.md")];
+
+        let linked = link_mentions(content, Path::new("/a/git/squash.md"), &targets);
+
+        assert_eq!(
+            linked,
+            "# Squash Commits\n\nUse [git rebase](git_rebase.md) here. Then git rebase again.\n"
+        );
+    }
+
+    #[test]
+    fn test_link_mentions_skips_code_and_headings() {
+        let content = "# Git Rebase Tricks\n\n```bash\ngit rebase -i\n```\n\nRun `git rebase` now.\n";
+        let targets = vec![target("Git Rebase", "/a/git/git_rebase.md")];
+
+        let linked = link_mentions(content, Path::new("/a/git/tricks.md"), &targets);
+
+        assert_eq!(linked, content);
+    }
+
+    #[test]
+    fn test_link_mentions_skips_self_and_word_fragments() {
+        let content = "Rebasements are not git rebases.\n";
+        let targets = vec![
+            target("Git Rebase", "/a/git/git_rebase.md"),
+            target("Rebasements", "/a/git/self.md"),
+        ];
+
+        let linked = link_mentions(content, Path::new("/a/git/self.md"), &targets);
+
+        assert_eq!(linked, content);
+    }
+
+    #[test]
+    fn test_link_mentions_prefers_longest_title() {
+        let content = "Try git rebase onto for this.\n";
+        let targets = vec![
+            target("Git Rebase", "/a/git/git_rebase.md"),
+            target("Git Rebase Onto", "/a/git/onto.md"),
+        ];
+
+        let linked = link_mentions(content, Path::new("/a/rust/new.md"), &targets);
+
+        assert_eq!(linked, "Try [git rebase onto](../git/onto.md) for this.\n");
+    }
+
+    #[test]
+    fn test_relink_archive() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let archive = temp_dir.path().join("archive");
+        fs::create_dir_all(archive.join("git"))?;
+        fs::write(archive.join("git/rebase.md"), "# Interactive Rebase\n\nBody\n")?;
+        fs::write(archive.join("git/squash.md"), "# Squash\n\nSee interactive rebase.\n")?;
+
+        let preview = relink_archive(&archive, true)?;
+        assert_eq!(preview, vec![archive.join("git/squash.md")]);
+        assert_eq!(
+            fs::read_to_string(archive.join("git/squash.md"))?,
+            "# Squash\n\nSee interactive rebase.\n"
+        );
+
+        let changed = relink_archive(&archive, false)?;
+
+        assert_eq!(changed, vec![archive.join("git/squash.md")]);
+        assert_eq!(
+            fs::read_to_string(archive.join("git/squash.md"))?,
+            "# Squash\n\nSee [interactive rebase](rebase.md).\n"
+        );
+
+        // A second pass is a no-op
+        assert!(relink_archive(&archive, false)?.is_empty());
+
+        Ok(())
+    }
+}
@@ -1,5 +1,16 @@
+pub mod append;
+pub mod category;
+pub mod check;
+pub mod dedupe;
+pub mod drafts;
+pub mod extract;
 mod generator;
+pub mod linker;
+pub mod of_the_day;
+pub mod overlap;
+pub mod recall;
+pub mod style;
+pub mod verify;
 pub mod writer;
 
-pub use generator::generate_til;
-pub use writer::write_til;
+pub use generator::{generate_til, integrate_til, refine_til};
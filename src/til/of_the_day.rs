@@ -0,0 +1,200 @@
+use crate::markdown;
+use crate::theme::{self, Themed};
+use anyhow::{Context, Result};
+use chrono::NaiveDate;
+use colored::*;
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::RandomState;
+use std::collections::BTreeMap;
+use std::fs;
+use std::hash::{BuildHasher, Hasher};
+use std::path::Path;
+
+/// When each TIL was last shown, and today's pick so it stays the same all day
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct History {
+    pub today: Option<(NaiveDate, String)>,
+    #[serde(default)]
+    pub shown: BTreeMap<String, NaiveDate>,
+}
+
+impl History {
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = fs::read_to_string(path).with_context(|| format!("Failed to read {:?}", path))?;
+        toml::from_str(&content).with_context(|| format!("Failed to parse {:?}", path))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).with_context(|| format!("Failed to create directory {:?}", parent))?;
+        }
+        let content = toml::to_string_pretty(self).context("Failed to serialize TIL history")?;
+        fs::write(path, content).with_context(|| format!("Failed to write {:?}", path))
+    }
+
+    /// Today's TIL: the one already picked today if it's still there, otherwise the one shown
+    /// longest ago (never-shown ones first), with ties broken by `seed`
+    pub fn pick<'a>(&self, files: &'a [String], today: NaiveDate, seed: u64) -> Option<&'a String> {
+        if let Some((date, ref picked)) = self.today {
+            if date == today {
+                if let Some(file) = files.iter().find(|file| *file == picked) {
+                    return Some(file);
+                }
+            }
+        }
+        let oldest = files.iter().map(|file| self.shown.get(file)).min()?;
+        let candidates: Vec<&String> = files.iter().filter(|file| self.shown.get(*file) == oldest).collect();
+        candidates.get(seed as usize % candidates.len()).copied()
+    }
+
+    pub fn record(&mut self, file: &str, today: NaiveDate, keep_for_today: bool) {
+        self.shown.insert(file.to_string(), today);
+        if keep_for_today {
+            self.today = Some((today, file.to_string()));
+        }
+    }
+}
+
+/// A number that's different every run, for picking at random
+pub fn seed() -> u64 {
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_i64(chrono::Local::now().timestamp_nanos_opt().unwrap_or_default());
+    hasher.finish()
+}
+
+/// The TIL as a few lines for a terminal: its title, then the body wrapped to `width`, cut
+/// off after `max_lines`. Frontmatter and code fences are dropped; code lines aren't wrapped.
+pub fn render(content: &str, width: usize, max_lines: usize, plain: bool) -> String {
+    let body = markdown::split_frontmatter(content).map_or(content, |(_, body)| body);
+    let mut title = None;
+    let mut lines = Vec::new();
+    let mut in_code = false;
+
+    for line in body.lines() {
+        if line.trim_start().starts_with("```") {
+            in_code = !in_code;
+            continue;
+        }
+        if in_code {
+            lines.push(style_code(&truncate(line, width), plain));
+            continue;
+        }
+        if title.is_none() && lines.is_empty() {
+            if let Some(heading) = line.strip_prefix("# ") {
+                title = Some(heading.trim().to_string());
+                continue;
+            }
+        }
+        let text = line.trim_start_matches('#').trim();
+        if text.is_empty() {
+            if lines.last().is_some_and(|last: &String| !last.is_empty()) {
+                lines.push(String::new());
+            }
+            continue;
+        }
+        lines.extend(wrap(text, width));
+    }
+
+    while lines.last().is_some_and(|last| last.is_empty()) {
+        lines.pop();
+    }
+    if lines.len() > max_lines {
+        lines.truncate(max_lines);
+        lines.push("…".to_string());
+    }
+
+    let mut rendered = String::new();
+    if let Some(title) = title {
+        rendered.push_str(&if plain { title } else { format!("{} {}", theme::glyph("💡", "Tip:"), title.bold()) });
+        rendered.push('\n');
+    }
+    for line in lines {
+        rendered.push_str(&line);
+        rendered.push('\n');
+    }
+    rendered
+}
+
+fn style_code(line: &str, plain: bool) -> String {
+    if plain {
+        format!("    {}", line)
+    } else {
+        format!("    {}", line.muted())
+    }
+}
+
+/// Break text into lines of at most `width` characters, at spaces where possible
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut current = String::new();
+    for word in text.split_whitespace() {
+        let needed = current.chars().count() + word.chars().count() + usize::from(!current.is_empty());
+        if !current.is_empty() && needed > width {
+            lines.push(std::mem::take(&mut current));
+        }
+        if !current.is_empty() {
+            current.push(' ');
+        }
+        current.push_str(word);
+    }
+    if !current.is_empty() {
+        lines.push(current);
+    }
+    lines
+}
+
+fn truncate(line: &str, width: usize) -> String {
+    let width = width.saturating_sub(4);
+    if line.chars().count() <= width {
+        return line.to_string();
+    }
+    let kept: String = line.chars().take(width.saturating_sub(1)).collect();
+    format!("{}…", kept)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn files(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    fn date(day: u32) -> Result<NaiveDate> {
+        NaiveDate::from_ymd_opt(2024, 6, day).context("invalid date")
+    }
+
+    #[test]
+    fn test_pick_prefers_least_recently_shown_and_keeps_todays() -> Result<()> {
+        let files = files(&["git/rebase.md", "rust/borrowing.md", "sql/upsert.md"]);
+        let mut history = History::default();
+        history.record("git/rebase.md", date(1)?, true);
+        history.record("rust/borrowing.md", date(2)?, true);
+
+        // sql/upsert.md has never been shown
+        assert_eq!(history.pick(&files, date(3)?, 7).map(String::as_str), Some("sql/upsert.md"));
+
+        history.record("sql/upsert.md", date(3)?, true);
+        assert_eq!(history.pick(&files, date(3)?, 7).map(String::as_str), Some("sql/upsert.md"));
+        assert_eq!(history.pick(&files, date(4)?, 7).map(String::as_str), Some("git/rebase.md"));
+        assert!(history.pick(&[], date(4)?, 7).is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_wraps_and_cuts_off() {
+        let content = "---\ntags: [git]\n---\n\n# Rebase onto\n\nMove a branch to start from another one without replaying everything.\n\n```sh\ngit rebase --onto main feature~3 feature\n```\n\nMore.\n";
+
+        let rendered = render(content, 30, 10, true);
+        assert_eq!(
+            rendered,
+            "Rebase onto\nMove a branch to start from\nanother one without replaying\neverything.\n\n    git rebase --onto main fe…\n\nMore.\n"
+        );
+
+        let cut = render(content, 30, 2, true);
+        assert_eq!(cut, "Rebase onto\nMove a branch to start from\nanother one without replaying\n…\n");
+    }
+}
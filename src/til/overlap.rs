@@ -0,0 +1,89 @@
+use crate::search;
+use crate::til::check;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// How many existing TILs are shown before a session starts
+const MAX_SHOWN: usize = 3;
+
+/// Shortest word that still matches longer ones it begins, so "lock" finds "Locks"
+const MIN_STEM: usize = 4;
+
+/// A README entry whose title or category shares words with a new session's topic
+#[derive(Debug, Clone, PartialEq)]
+pub struct Overlap {
+    pub title: String,
+    pub path: PathBuf,
+}
+
+/// README entries that look like they cover `topic` already, best match first. Matches in the
+/// title count for more than a matching category directory.
+pub fn find(topic: &str, readme: &str, repo_root: &Path, archive_dir: &str) -> Vec<Overlap> {
+    let topic_terms = search::terms(topic);
+    if topic_terms.is_empty() {
+        return Vec::new();
+    }
+
+    let mut scored: Vec<(usize, Overlap)> = check::readme_entries(readme, archive_dir)
+        .into_iter()
+        .filter_map(|(title, link)| {
+            let title_terms = search::terms(&title);
+            let folders = link.rsplit_once('/').map_or("", |(dir, _)| dir);
+            let folder_terms = search::terms(folders.strip_prefix(archive_dir).unwrap_or(folders));
+
+            let score = 2 * matching(&topic_terms, &title_terms) + matching(&topic_terms, &folder_terms);
+            (score > 0).then(|| (score, Overlap { path: repo_root.join(&link), title }))
+        })
+        .collect();
+    scored.sort_by(|(a, _), (b, _)| b.cmp(a));
+    scored.into_iter().take(MAX_SHOWN).map(|(_, overlap)| overlap).collect()
+}
+
+/// [`find`] against the README in `repo_root`; a missing README just means nothing overlaps
+pub fn find_in_repo(topic: &str, repo_root: &Path, archive_dir: &str) -> Vec<Overlap> {
+    fs::read_to_string(repo_root.join("README.md"))
+        .map(|readme| find(topic, &readme, repo_root, archive_dir))
+        .unwrap_or_default()
+}
+
+/// How many of `topic` terms appear among `terms`
+fn matching(topic: &[String], terms: &[String]) -> usize {
+    topic.iter().filter(|wanted| terms.iter().any(|term| same_word(wanted, term))).count()
+}
+
+/// Equal, or one is the other with an ending, like "rebase" and "rebasing"
+fn same_word(a: &str, b: &str) -> bool {
+    let (shorter, longer) = if a.len() <= b.len() { (a, b) } else { (b, a) };
+    if shorter == longer {
+        return true;
+    }
+    let stem = shorter.strip_suffix('e').unwrap_or(shorter);
+    stem.len() >= MIN_STEM && longer.starts_with(stem)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const README: &str = "# TIL\n\n### git\n\n\
+        - [Update A Forked Repo](archive/git/update-a-forked-repo.md)\n\
+        - [Interactive Rebase Basics](archive/git/interactive-rebase-basics.md)\n\n\
+        ### rust\n\n\
+        - [Rebasing Iterators](archive/rust/rebasing-iterators.md)\n\
+        - [Borrowing](archive/rust/borrowing.md)\n";
+
+    #[test]
+    fn test_find_ranks_title_matches_first() {
+        let overlaps = find("git rebase", README, Path::new("/til"), "archive");
+
+        let titles: Vec<&str> = overlaps.iter().map(|overlap| overlap.title.as_str()).collect();
+        assert_eq!(titles, vec!["Interactive Rebase Basics", "Rebasing Iterators", "Update A Forked Repo"]);
+        assert_eq!(overlaps[0].path, PathBuf::from("/til/archive/git/interactive-rebase-basics.md"));
+    }
+
+    #[test]
+    fn test_find_ignores_unrelated_topics() {
+        assert!(find("kubernetes operators", README, Path::new("/til"), "archive").is_empty());
+        assert!(find("the", README, Path::new("/til"), "archive").is_empty());
+    }
+}
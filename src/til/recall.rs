@@ -0,0 +1,104 @@
+use crate::claude::{no_tools_settings, send, CancellationToken, ClaudeResponse};
+use crate::session::Session;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+/// The line the backend puts before its corrected version of my explanation
+const CORRECTED_MARKER: &str = "Corrected version:";
+
+/// The session's key idea explained back in my own words, and the backend's correction of it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Recall {
+    pub explanation: String,
+    /// My explanation with mistakes fixed and gaps filled, still in my voice
+    pub corrected: String,
+}
+
+impl Recall {
+    /// Take the corrected version from the backend's reply, or keep my explanation as it was if
+    /// the reply doesn't have one
+    pub fn from_reply(explanation: &str, reply: &str) -> Self {
+        let corrected = reply
+            .rsplit_once(CORRECTED_MARKER)
+            .map(|(_, corrected)| corrected.trim())
+            .filter(|corrected| !corrected.is_empty())
+            .unwrap_or_else(|| explanation.trim());
+        Self {
+            explanation: explanation.trim().to_string(),
+            corrected: corrected.to_string(),
+        }
+    }
+
+    /// The part of the TIL generation prompt that asks for the explanation to be included
+    pub fn prompt_section(&self) -> String {
+        format!(
+            r###"Before this, I explained the key concept back in my own words, and you corrected it:
+
+<my_explanation>
+{}
+</my_explanation>
+
+Include it in the TIL under a "## In my own words" heading, keeping my wording."###,
+            self.corrected
+        )
+    }
+}
+
+/// Have the backend check my explanation of the session's key concept, in the session's conversation
+pub fn check<F>(session: &Session, explanation: &str, on_text: F) -> Result<ClaudeResponse>
+where
+    F: FnMut(&str),
+{
+    let prompt = build_check_prompt(session.prompt_topic(), explanation);
+    send(
+        session.resume(),
+        &prompt,
+        Some(&no_tools_settings()),
+        on_text,
+        CancellationToken::new(),
+    )
+}
+
+fn build_check_prompt(topic: &str, explanation: &str) -> String {
+    format!(
+        r#"To check that I understood {}, here's the key concept explained back in my own words:
+
+<my_explanation>
+{}
+</my_explanation>
+
+In a few sentences, tell me what I got right and what's wrong or missing. Be direct; don't repeat the whole lesson.
+
+Then write a line with exactly "{}" followed by my explanation, corrected and completed. Keep my voice and phrasing where they're right, write in the first person, and keep it to a short paragraph."#,
+        topic,
+        explanation.trim(),
+        CORRECTED_MARKER
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_reply_takes_the_corrected_version() {
+        let reply = "Mostly right, but rebase rewrites commits.\n\nCorrected version:\nRebase replays my commits \
+                     onto the new base, so they get new hashes.\n";
+        let recall = Recall::from_reply(" Rebase moves commits. ", reply);
+
+        assert_eq!(recall.explanation, "Rebase moves commits.");
+        assert_eq!(recall.corrected, "Rebase replays my commits onto the new base, so they get new hashes.");
+        assert!(recall.prompt_section().contains("<my_explanation>\nRebase replays"));
+
+        let unmarked = Recall::from_reply("Rebase moves commits.", "Spot on.");
+        assert_eq!(unmarked.corrected, "Rebase moves commits.");
+    }
+
+    #[test]
+    fn test_build_check_prompt() {
+        let prompt = build_check_prompt("git rebase", "  It moves commits.\n");
+        assert!(prompt.starts_with("To check that I understood git rebase,"));
+        assert!(prompt.contains("<my_explanation>\nIt moves commits.\n</my_explanation>"));
+        assert!(prompt.contains("exactly \"Corrected version:\""));
+    }
+}
@@ -0,0 +1,104 @@
+use crate::language;
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::fs;
+use std::path::Path;
+
+/// Per-category style file, e.g. `archive/sql/.holocron.toml`
+pub const STYLE_FILE: &str = ".holocron.toml";
+
+/// Generation hints and an optional template for TILs in one category
+#[derive(Debug, Default, Deserialize)]
+pub struct CategoryStyle {
+    /// Extra instructions, e.g. "always include the exact psql command and expected output"
+    #[serde(default)]
+    pub hints: Option<String>,
+
+    /// Markdown skeleton the TIL should follow
+    #[serde(default)]
+    pub template: Option<String>,
+
+    /// Natural language to write the TIL in, e.g. "German"
+    #[serde(default)]
+    pub language: Option<String>,
+}
+
+impl CategoryStyle {
+    /// Load the style for a category, returning an empty style if it has none. A sub-category
+    /// without its own style (`rust/async`) uses its parent's.
+    pub fn load(archive_path: &Path, category: &str) -> Result<Self> {
+        let category = category.to_lowercase();
+        let mut dir = Some(category.as_str());
+        let path = loop {
+            let Some(current) = dir else {
+                return Ok(Self::default());
+            };
+            let path = archive_path.join(current).join(STYLE_FILE);
+            if path.exists() {
+                break path;
+            }
+            dir = current.rsplit_once('/').map(|(parent, _)| parent);
+        };
+
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read category style from {:?}", path))?;
+        toml::from_str(&content).with_context(|| format!("Failed to parse {:?}", path))
+    }
+
+    /// The prompt section describing this style, if there is anything to say
+    pub fn prompt_section(&self) -> Option<String> {
+        let mut section = String::new();
+
+        if let Some(hints) = self.hints.as_deref().map(str::trim).filter(|h| !h.is_empty()) {
+            section.push_str(&format!("Category guidelines:\n{}\n", hints));
+        }
+        if let Some(template) = self.template.as_deref().map(str::trim).filter(|t| !t.is_empty()) {
+            if !section.is_empty() {
+                section.push('\n');
+            }
+            section.push_str(&format!("Follow this template:\n```markdown\n{}\n```\n", template));
+        }
+        if let Some(language) = self.language.as_deref().filter(|l| !l.trim().is_empty()) {
+            if !section.is_empty() {
+                section.push('\n');
+            }
+            section.push_str(&format!("{}\n", language::instruction(language)));
+        }
+
+        (!section.is_empty()).then_some(section)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_load_missing_style() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let style = CategoryStyle::load(temp_dir.path(), "sql")?;
+        assert!(style.prompt_section().is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_load_style() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        fs::create_dir_all(temp_dir.path().join("sql"))?;
+        fs::write(
+            temp_dir.path().join("sql").join(STYLE_FILE),
+            "hints = \"Always include the exact psql command and expected output.\"\ntemplate = \"# Title\\n\\n## Command\"\n",
+        )?;
+
+        let style = CategoryStyle::load(temp_dir.path(), "SQL")?;
+        let section = style.prompt_section().unwrap_or_default();
+
+        assert!(section.starts_with("Category guidelines:\nAlways include the exact psql command"));
+        assert!(section.contains("```markdown\n# Title\n\n## Command\n```"));
+
+        let inherited = CategoryStyle::load(temp_dir.path(), "sql/indexes")?;
+        assert_eq!(inherited.hints, style.hints);
+        Ok(())
+    }
+}
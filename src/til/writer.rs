@@ -1,36 +1,159 @@
+use crate::error::HolocronError;
+use crate::storage::{SaveTarget, Saved};
+use crate::{archive, crypto, files};
 use anyhow::{Context, Result};
 use std::fs;
 use std::path::{Path, PathBuf};
 
-/// Write a TIL to the appropriate category folder and update the README
-pub fn write_til(
+/// A pending TIL save: the new file plus the README before and after the update
+#[derive(Debug, Clone)]
+pub struct TilWrite {
+    pub file_path: PathBuf,
+    /// The file's path inside the archive, e.g. `rust/borrowing.md`
+    pub entry_path: String,
+    pub archive_path: PathBuf,
+    pub content: String,
+    pub readme_path: PathBuf,
+    pub readme_before: String,
+    pub readme_after: String,
+    /// Set when the README doesn't make it clear where the entry belongs
+    pub insertion_choice: Option<InsertionChoice>,
+    /// The README line a chosen spot follows, and which occurrence of that line it is, so the
+    /// choice survives the README changing before the save
+    chosen_after: Option<(String, usize)>,
+    /// What the README entry is rendered from, in case the README changes before the save
+    archive_dir: String,
+    category: String,
+    title: String,
+    author: Option<String>,
+}
+
+/// Joins a category's parts in its README heading, e.g. `Rust – Async`
+const SUBCATEGORY_SEPARATOR: &str = " – ";
+
+/// Where a README entry could go when the usual spot is only a guess
+#[derive(Debug, Clone, PartialEq)]
+pub struct InsertionChoice {
+    /// README line indices the entry could be inserted at, each right after a line of a section
+    /// for its category
+    pub lines: Vec<usize>,
+    /// The index in `lines` of the spot the heuristic picked
+    pub suggested: usize,
+}
+
+impl TilWrite {
+    /// Put the README entry at line `index` of the README as planned, instead of where the
+    /// heuristic put it
+    pub fn insert_entry_at(&mut self, index: usize) {
+        let before: Vec<&str> = self.readme_before.lines().collect();
+        let index = index.min(before.len());
+        self.chosen_after = index.checked_sub(1).map(|previous| {
+            let line = before[previous];
+            let occurrence = before[..previous].iter().filter(|other| **other == line).count();
+            (line.to_string(), occurrence)
+        });
+        let mut lines: Vec<String> = before.iter().map(|s| s.to_string()).collect();
+        update_til_count(&mut lines);
+        lines.insert(index, self.entry_line());
+        self.readme_after = format!("{}\n", lines.join("\n"));
+    }
+
+    /// `readme` with the entry after the line the chosen spot followed, if it's still there
+    fn insert_at_chosen(&self, readme: &str) -> Option<String> {
+        let (after, occurrence) = self.chosen_after.as_ref()?;
+        let mut lines: Vec<String> = readme.lines().map(|s| s.to_string()).collect();
+        let index = lines.iter().enumerate().filter(|(_, line)| *line == after).nth(*occurrence)?.0 + 1;
+        update_til_count(&mut lines);
+        lines.insert(index, self.entry_line());
+        Some(format!("{}\n", lines.join("\n")))
+    }
+
+    fn entry_line(&self) -> String {
+        entry_line(&self.archive_dir, &self.entry_path, &self.title, self.author.as_deref())
+    }
+
+    /// Store the TIL with `target`. The README only links it when it lands where it was
+    /// planned, in the archive.
+    pub fn save_to<F>(&self, target: &dyn SaveTarget, encode: F) -> Result<Saved>
+    where
+        F: FnOnce(&str) -> Result<Vec<u8>>,
+    {
+        let encoded = encode(&self.content)?;
+        let saved = target.save(&self.entry_path, &encoded)?;
+        if saved.local_path() == Some(self.file_path.as_path()) {
+            self.update_readme()?;
+        }
+        Ok(saved)
+    }
+
+    /// Add the entry to the README as it is now. Another holocron (or an editor) may have changed
+    /// it since it was planned, so it's re-read under a lock and the entry re-rendered if so.
+    fn update_readme(&self) -> Result<()> {
+        let write_failed = |source| HolocronError::WriteFailed {
+            path: self.readme_path.clone(),
+            source,
+        };
+        let _lock = files::lock(&self.readme_path).map_err(write_failed)?;
+        let current = fs::read_to_string(&self.readme_path).context("Failed to read README.md")?;
+        let readme = if current == self.readme_before {
+            self.readme_after.clone()
+        } else if let Some(readme) = self.insert_at_chosen(&current) {
+            readme
+        } else {
+            render_readme(
+                &current,
+                &self.archive_dir,
+                &self.category,
+                &self.entry_path,
+                &self.title,
+                self.author.as_deref(),
+            )?
+        };
+        files::write_atomic(&self.readme_path, readme).map_err(write_failed)?;
+        Ok(())
+    }
+}
+
+/// Work out what saving a TIL would change, without touching disk.
+/// `entry_dir` is the directory inside the archive, e.g. `rust` or `2024/06/rust`;
+/// the README section is its category part. With an `author`, the README entry credits them.
+pub fn plan_til(
     repo_root: &Path,
     archive_dir: &str,
-    category: &str,
+    entry_dir: &str,
     filename: &str,
     content: &str,
     title: &str,
-) -> Result<PathBuf> {
-    let category_lower = category.to_lowercase();
-    let category_dir = repo_root.join(archive_dir).join(&category_lower);
-
-    // Create category directory if it doesn't exist
-    if !category_dir.exists() {
-        fs::create_dir_all(&category_dir)
-            .with_context(|| format!("Failed to create category directory: {:?}", category_dir))?;
-    }
-
-    // Write the TIL file (ensure trailing newline)
+    author: Option<&str>,
+) -> Result<TilWrite> {
+    let entry_dir = entry_dir.to_lowercase();
+    let category = archive::category_of(Path::new(&entry_dir)).unwrap_or_else(|| entry_dir.clone());
     let filename = sanitize_filename(filename);
-    let file_path = category_dir.join(&filename);
-    let content = ensure_trailing_newline(content);
-    fs::write(&file_path, &content)
-        .with_context(|| format!("Failed to write TIL file: {:?}", file_path))?;
+    let archive_path = repo_root.join(archive_dir);
+    let file_path = archive_path.join(&entry_dir).join(&filename);
+    let entry_path = format!("{}/{}", entry_dir, filename);
 
-    // Update README.md
-    update_readme(repo_root, archive_dir, &category_lower, &filename, title)?;
-
-    Ok(file_path)
+    let readme_path = repo_root.join("README.md");
+    let readme_before = fs::read_to_string(&readme_path).context("Failed to read README.md")?;
+    let readme_after = render_readme(&readme_before, archive_dir, &category, &entry_path, title, author)?;
+    let lines: Vec<String> = readme_before.lines().map(|s| s.to_string()).collect();
+    let insertion_choice = insertion_choice(&lines, &category);
+
+    Ok(TilWrite {
+        file_path,
+        entry_path,
+        archive_path,
+        content: ensure_trailing_newline(content),
+        readme_path,
+        readme_before,
+        readme_after,
+        insertion_choice,
+        chosen_after: None,
+        archive_dir: archive_dir.to_string(),
+        category,
+        title: title.to_string(),
+        author: author.map(str::to_string),
+    })
 }
 
 /// Extract title from TIL markdown content (first H1 heading)
@@ -77,38 +200,34 @@ fn collapse_underscores(s: &str) -> String {
 }
 
 fn sanitize_filename(filename: &str) -> String {
-    let name = if filename.ends_with(".md") {
+    let name = if filename.ends_with(".md") || crypto::is_encrypted(Path::new(filename)) {
         filename.to_string()
     } else {
         format!("{}.md", filename)
     };
 
-    name.replace(' ', "_").to_lowercase()
+    // Case is left alone, since the `preserve` filename style keeps it
+    name.replace(' ', "_")
 }
 
-fn update_readme(
-    repo_root: &Path,
+fn render_readme(
+    content: &str,
     archive_dir: &str,
     category: &str,
-    filename: &str,
+    entry_path: &str,
     title: &str,
-) -> Result<()> {
-    let readme_path = repo_root.join("README.md");
-    let content = fs::read_to_string(&readme_path).context("Failed to read README.md")?;
-
+    author: Option<&str>,
+) -> Result<String> {
     let mut lines: Vec<String> = content.lines().map(|s| s.to_string()).collect();
 
     // Update TIL count
     update_til_count(&mut lines);
 
     // Find or create category section and add entry
-    add_entry_to_category(&mut lines, archive_dir, category, filename, title)?;
+    add_entry_to_category(&mut lines, archive_dir, category, entry_path, title, author)?;
 
-    // Write back (ensure trailing newline)
-    let new_content = format!("{}\n", lines.join("\n"));
-    fs::write(&readme_path, new_content).context("Failed to write README.md")?;
-
-    Ok(())
+    // Ensure trailing newline
+    Ok(format!("{}\n", lines.join("\n")))
 }
 
 fn update_til_count(lines: &mut [String]) {
@@ -125,38 +244,140 @@ fn update_til_count(lines: &mut [String]) {
     }
 }
 
-fn add_entry_to_category(
+/// Add a README entry linking to `entry_path` (relative to the archive) under the category's section
+pub(super) fn add_entry_to_category(
     lines: &mut Vec<String>,
     archive_dir: &str,
     category: &str,
-    filename: &str,
+    entry_path: &str,
     title: &str,
+    author: Option<&str>,
 ) -> Result<()> {
-    let category_header = format!("### {}", capitalize_first(category));
-    let entry = format!("- [{}]({}/{}/{})", title, archive_dir, category, filename);
+    let entry = entry_line(archive_dir, entry_path, title, author);
 
     // Find the category section
-    let category_idx = find_category_index(lines, &category_header, category);
+    let category_idx = find_category_index(lines, category);
 
     if let Some(idx) = category_idx {
         let insert_idx = find_insertion_point(lines, idx);
         lines.insert(insert_idx, entry);
     } else {
-        add_new_category(lines, archive_dir, category, filename, title)?;
+        add_new_category(lines, archive_dir, category, entry_path, title, author)?;
     }
 
     Ok(())
 }
 
-fn find_category_index(lines: &[String], category_header: &str, category: &str) -> Option<usize> {
-    for (i, line) in lines.iter().enumerate() {
-        if line.trim().eq_ignore_ascii_case(category_header)
-            || line.trim().to_lowercase() == format!("### {}", category.to_lowercase())
-        {
-            return Some(i);
-        }
+/// Comes between a README entry's link and its author in team repos: `- [Title](path) by @jane`
+const AUTHOR_CREDIT: &str = " by @";
+
+fn entry_line(archive_dir: &str, entry_path: &str, title: &str, author: Option<&str>) -> String {
+    let entry = format!("- [{}]({}/{})", title, archive_dir, entry_path);
+    match author.map(|author| author.trim().trim_start_matches('@')).filter(|author| !author.is_empty()) {
+        Some(author) => format!("{}{}{}", entry, AUTHOR_CREDIT, author),
+        None => entry,
     }
-    None
+}
+
+/// A README entry line without the author credit team repos add after the link
+pub(super) fn without_author(line: &str) -> &str {
+    match line.rfind(AUTHOR_CREDIT) {
+        Some(i) if line[..i].ends_with(')') => &line[..i],
+        _ => line,
+    }
+}
+
+/// The spots a `category` entry could go when `find_insertion_point` would only be guessing:
+/// the category's header appears more than once, or its section has lines other than `- [..]`
+/// entries (prose, sub-headings, `*` lists, tables). `None` when the usual spot is clear or
+/// there is no section yet.
+fn insertion_choice(lines: &[String], category: &str) -> Option<InsertionChoice> {
+    let headers: Vec<usize> = lines
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| is_category_header(line, category))
+        .map(|(i, _)| i)
+        .collect();
+    let first = *headers.first()?;
+    let sections: Vec<std::ops::Range<usize>> =
+        headers.iter().map(|&header| header..section_end(lines, header)).collect();
+    let irregular = sections.iter().any(|section| {
+        lines[section.start + 1..section.end]
+            .iter()
+            .any(|line| !line.trim().is_empty() && !line.starts_with("- ["))
+    });
+    if headers.len() == 1 && !irregular {
+        return None;
+    }
+
+    // The entry can go right after the header or any non-blank line in a section
+    let spots: Vec<usize> = sections
+        .into_iter()
+        .flatten()
+        .filter(|&i| !lines[i].trim().is_empty())
+        .map(|i| i + 1)
+        .collect();
+    let heuristic = find_insertion_point(lines, first);
+    let suggested = spots.iter().rposition(|&spot| spot <= heuristic).unwrap_or(0);
+    Some(InsertionChoice {
+        lines: spots,
+        suggested,
+    })
+}
+
+/// The index just past a section: the next heading of level 3 or higher, a `---` rule, or the end
+fn section_end(lines: &[String], header: usize) -> usize {
+    let is_boundary = |line: &String| {
+        line.starts_with("---") || ["# ", "## ", "### "].iter().any(|level| line.starts_with(level))
+    };
+    lines
+        .iter()
+        .skip(header + 1)
+        .position(is_boundary)
+        .map_or(lines.len(), |offset| header + 1 + offset)
+}
+
+fn find_category_index(lines: &[String], category: &str) -> Option<usize> {
+    lines.iter().position(|line| is_category_header(line, category))
+}
+
+/// Whether `line` is the section header for `category`: `### Rust – Async` for `rust/async`, or
+/// the category written as is
+fn is_category_header(line: &str, category: &str) -> bool {
+    line.trim().strip_prefix("### ").is_some_and(|name| {
+        name.eq_ignore_ascii_case(&category_heading(category)) || name.eq_ignore_ascii_case(category)
+    })
+}
+
+/// The README heading for a category, with sub-categories after their parents:
+/// `rust` is `Rust` and `rust/async` is `Rust – Async`
+pub fn category_heading(category: &str) -> String {
+    category
+        .split('/')
+        .filter(|part| !part.is_empty())
+        .map(capitalize_first)
+        .collect::<Vec<_>>()
+        .join(SUBCATEGORY_SEPARATOR)
+}
+
+/// The Categories list link for a section heading, sub-categories indented under their parent
+pub(super) fn category_link(heading: &str) -> String {
+    let depth = heading.matches(SUBCATEGORY_SEPARATOR).count();
+    format!("{}* [{}](#{})", "  ".repeat(depth), heading, anchor_for(heading))
+}
+
+/// GitHub's heading anchor: lowercase, spaces to dashes, punctuation dropped
+pub(super) fn anchor_for(heading: &str) -> String {
+    heading
+        .trim()
+        .to_lowercase()
+        .chars()
+        .filter_map(|c| match c {
+            ' ' => Some('-'),
+            c if c.is_alphanumeric() || c == '-' || c == '_' => Some(c),
+            _ => None,
+        })
+        .collect()
 }
 
 fn find_insertion_point(lines: &[String], category_idx: usize) -> usize {
@@ -186,33 +407,57 @@ fn add_new_category(
     lines: &mut Vec<String>,
     archive_dir: &str,
     category: &str,
-    filename: &str,
+    entry_path: &str,
     title: &str,
+    author: Option<&str>,
 ) -> Result<()> {
-    let category_display = capitalize_first(category);
+    let heading = category_heading(category);
+    // A sub-category goes after its parent's sections and links, when the parent has any
+    let parent = category.rsplit_once('/').map(|(parent, _)| category_heading(parent));
+    let in_family = |name: &str| {
+        parent.as_ref().is_some_and(|parent| {
+            name.eq_ignore_ascii_case(parent)
+                || name.to_lowercase().starts_with(&format!("{}{}", parent, SUBCATEGORY_SEPARATOR).to_lowercase())
+        })
+    };
 
     // Add to Categories list (find ### Categories section)
     if let Some(end_idx) = find_categories_end(lines) {
-        let cat_link = format!("* [{}](#{})", category_display, category.to_lowercase());
-        lines.insert(end_idx, cat_link);
+        let start = lines.iter().position(|line| line.trim() == "### Categories").unwrap_or(0);
+        let linked_name = |line: &str| line.trim().strip_prefix("* [")?.split_once(']').map(|(name, _)| name.to_string());
+        let after_family = (start..end_idx)
+            .rev()
+            .find(|&i| linked_name(&lines[i]).is_some_and(|name| in_family(&name)))
+            .map(|i| i + 1);
+        lines.insert(after_family.unwrap_or(end_idx), category_link(&heading));
     }
 
-    // Add the category section at the end
-    let insert_pos = find_end_position(lines);
-
-    lines.insert(insert_pos, String::new());
-    lines.insert(insert_pos + 1, format!("### {}", category_display));
-    lines.insert(insert_pos + 2, String::new());
-    lines.insert(
-        insert_pos + 3,
-        format!("- [{}]({}/{}/{})", title, archive_dir, category, filename),
-    );
-    lines.insert(insert_pos + 4, String::new());
+    let last_family_header = lines
+        .iter()
+        .rposition(|line| line.trim().strip_prefix("### ").is_some_and(&in_family));
+    let section = vec![format!("### {}", heading), String::new(), entry_line(archive_dir, entry_path, title, author)];
+    match last_family_header.map(|header| section_end(lines, header)) {
+        Some(end) if end < lines.len() => {
+            let mut section = section;
+            section.push(String::new());
+            if !lines[end - 1].trim().is_empty() {
+                section.insert(0, String::new());
+            }
+            lines.splice(end..end, section);
+        }
+        _ => {
+            // Add the category section at the end
+            let insert_pos = find_end_position(lines);
+            lines.insert(insert_pos, String::new());
+            lines.splice(insert_pos + 1..insert_pos + 1, section);
+            lines.insert(insert_pos + 4, String::new());
+        }
+    }
 
     Ok(())
 }
 
-fn find_categories_end(lines: &[String]) -> Option<usize> {
+pub(super) fn find_categories_end(lines: &[String]) -> Option<usize> {
     let mut in_categories = false;
 
     for (i, line) in lines.iter().enumerate() {
@@ -244,7 +489,7 @@ fn ensure_trailing_newline(s: &str) -> String {
     }
 }
 
-fn capitalize_first(s: &str) -> String {
+pub fn capitalize_first(s: &str) -> String {
     let mut chars = s.chars();
     match chars.next() {
         None => String::new(),
@@ -255,8 +500,15 @@ fn capitalize_first(s: &str) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::storage::Filesystem;
     use tempfile::TempDir;
 
+    /// Save a plan into the archive it was planned for
+    fn save(plan: &TilWrite) -> Result<PathBuf> {
+        plan.save_to(&Filesystem::new(&plan.archive_path), |content| Ok(content.as_bytes().to_vec()))?;
+        Ok(plan.file_path.clone())
+    }
+
     #[test]
     fn test_title_to_filename() {
         assert_eq!(title_to_filename("Git Rebasing"), "git_rebasing.md");
@@ -293,7 +545,7 @@ mod tests {
     fn test_sanitize_filename() {
         assert_eq!(sanitize_filename("test"), "test.md");
         assert_eq!(sanitize_filename("test.md"), "test.md");
-        assert_eq!(sanitize_filename("Test File"), "test_file.md");
+        assert_eq!(sanitize_filename("Test File"), "Test_File.md");
     }
 
     #[test]
@@ -324,6 +576,32 @@ mod tests {
         assert_eq!(find_categories_end(&lines), Some(2));
     }
 
+    #[test]
+    fn test_write_til_encoded() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        fs::write(temp_dir.path().join("README.md"), "# TIL\n1 TILs & Counting\n")?;
+
+        let plan = plan_til(
+            temp_dir.path(),
+            "archive",
+            "git",
+            "secret.md.age",
+            "# Secret\n",
+            "Secret",
+            None,
+        )?;
+        let saved = plan.save_to(&Filesystem::new(&plan.archive_path), |content| {
+            Ok(content.to_uppercase().into_bytes())
+        })?;
+
+        assert_eq!(saved, Saved::Local(temp_dir.path().join("archive/git/secret.md.age")));
+        assert_eq!(fs::read_to_string(&plan.file_path)?, "# SECRET\n");
+        let readme = fs::read_to_string(temp_dir.path().join("README.md"))?;
+        assert!(readme.contains("- [Secret](archive/git/secret.md.age)"));
+
+        Ok(())
+    }
+
     #[test]
     fn test_write_til() -> Result<()> {
         let temp_dir = TempDir::new()?;
@@ -337,14 +615,16 @@ mod tests {
 "#;
         fs::write(temp_dir.path().join("README.md"), readme_content)?;
 
-        let result = write_til(
+        let plan = plan_til(
             temp_dir.path(),
             "archive",
             "git",
             "new_entry.md",
             "# New Entry\n\nContent here.",
             "New Entry",
+            None,
         )?;
+        let result = save(&plan)?;
 
         assert!(result.exists());
         assert!(result.to_string_lossy().contains("archive/git"));
@@ -355,6 +635,32 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_plan_til_does_not_touch_disk() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let readme_content = "# TIL\n1 TILs & Counting\n### Categories\n---\n";
+        fs::write(temp_dir.path().join("README.md"), readme_content)?;
+
+        let plan = plan_til(
+            temp_dir.path(),
+            "archive",
+            "Git",
+            "Rebase",
+            "# Rebase",
+            "Rebase",
+            None,
+        )?;
+
+        assert_eq!(plan.file_path, temp_dir.path().join("archive/git/Rebase.md"));
+        assert_eq!(plan.content, "# Rebase\n");
+        assert_eq!(plan.readme_before, readme_content);
+        assert!(plan.readme_after.contains("2 TILs & Counting"));
+        assert!(!plan.file_path.exists());
+        assert_eq!(fs::read_to_string(temp_dir.path().join("README.md"))?, readme_content);
+
+        Ok(())
+    }
+
     #[test]
     fn test_write_til_new_category() -> Result<()> {
         let temp_dir = TempDir::new()?;
@@ -368,14 +674,16 @@ mod tests {
 "#;
         fs::write(temp_dir.path().join("README.md"), readme_content)?;
 
-        let result = write_til(
+        let plan = plan_til(
             temp_dir.path(),
             "archive",
             "rust",
             "ownership.md",
             "# Ownership\n\nRust ownership.",
             "Ownership",
+            None,
         )?;
+        let result = save(&plan)?;
 
         assert!(result.exists());
         assert!(result.to_string_lossy().contains("archive/rust"));
@@ -385,4 +693,166 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_save_keeps_readme_changes_made_after_planning() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let readme_path = temp_dir.path().join("README.md");
+        fs::write(&readme_path, "# TIL\n1 TILs & Counting\n### Categories\n---\n")?;
+
+        let rebase = plan_til(temp_dir.path(), "archive", "git", "rebase.md", "# Rebase", "Rebase", None)?;
+        let bisect = plan_til(temp_dir.path(), "archive", "git", "bisect.md", "# Bisect", "Bisect", None)?;
+        save(&rebase)?;
+        save(&bisect)?;
+
+        let readme = fs::read_to_string(&readme_path)?;
+        assert!(readme.contains("- [Rebase](archive/git/rebase.md)"));
+        assert!(readme.contains("- [Bisect](archive/git/bisect.md)"));
+        assert!(readme.contains("3 TILs & Counting"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_readme_entry_credits_author() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let readme_path = temp_dir.path().join("README.md");
+        fs::write(&readme_path, "# TIL\n1 TILs & Counting\n### Categories\n* [Git](#git)\n---\n### Git\n")?;
+
+        let rebase = plan_til(temp_dir.path(), "archive", "git", "rebase.md", "# Rebase", "Rebase", Some("@jane"))?;
+        let bisect = plan_til(temp_dir.path(), "archive", "rust", "pin.md", "# Pin", "Pin", Some("jo"))?;
+        save(&rebase)?;
+        save(&bisect)?;
+
+        let readme = fs::read_to_string(&readme_path)?;
+        assert!(readme.contains("- [Rebase](archive/git/rebase.md) by @jane\n"));
+        assert!(readme.contains("### Rust\n\n- [Pin](archive/rust/pin.md) by @jo\n"));
+        assert_eq!(without_author("- [Rebase](archive/git/rebase.md) by @jane"), "- [Rebase](archive/git/rebase.md)");
+        assert_eq!(without_author("- [Stand by @ me](a.md)"), "- [Stand by @ me](a.md)");
+        Ok(())
+    }
+
+    #[test]
+    fn test_save_elsewhere_leaves_readme_alone() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let readme_content = "# TIL\n1 TILs & Counting\n### Categories\n---\n";
+        fs::write(temp_dir.path().join("README.md"), readme_content)?;
+
+        let plan = plan_til(temp_dir.path(), "archive", "git", "rebase.md", "# Rebase", "Rebase", None)?;
+        let elsewhere = temp_dir.path().join("elsewhere");
+        let saved = plan.save_to(&Filesystem::new(&elsewhere), |content| Ok(content.as_bytes().to_vec()))?;
+
+        assert_eq!(saved, Saved::Local(elsewhere.join("git/rebase.md")));
+        assert!(!plan.file_path.exists());
+        assert_eq!(fs::read_to_string(temp_dir.path().join("README.md"))?, readme_content);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_insertion_choice_only_when_ambiguous() {
+        let lines = |text: &str| -> Vec<String> { text.lines().map(str::to_string).collect() };
+
+        let plain = lines("### Git\n\n- [Rebase](archive/git/rebase.md)\n\n### Rust\nPlease read this first\n");
+        assert_eq!(insertion_choice(&plain, "git"), None);
+        assert_eq!(insertion_choice(&plain, "python"), None);
+
+        let duplicated = lines("### Git\n- [A](a.md)\n\n### Git\n- [B](b.md)\n---\n");
+        assert_eq!(
+            insertion_choice(&duplicated, "git"),
+            Some(InsertionChoice {
+                lines: vec![1, 2, 4, 5],
+                suggested: 1,
+            })
+        );
+
+        let prose = lines("# TIL\n### Git\nNotes on git.\n#### Rebasing\n- [A](a.md)\n## Other\n");
+        assert_eq!(
+            insertion_choice(&prose, "git"),
+            Some(InsertionChoice {
+                lines: vec![2, 3, 4, 5],
+                suggested: 0,
+            })
+        );
+    }
+
+    #[test]
+    fn test_insert_entry_at() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let readme = "# TIL\n1 TILs & Counting\n### Git\n- [A](archive/git/a.md)\n\n### Git\n- [B](archive/git/b.md)\n";
+        fs::write(temp_dir.path().join("README.md"), readme)?;
+
+        let mut plan = plan_til(temp_dir.path(), "archive", "git", "c.md", "# C", "C", None)?;
+        let choice = plan.insertion_choice.clone().ok_or_else(|| anyhow::anyhow!("expected a choice"))?;
+        assert_eq!(choice.lines, vec![3, 4, 6, 7]);
+
+        plan.insert_entry_at(7);
+        save(&plan)?;
+        assert_eq!(
+            fs::read_to_string(temp_dir.path().join("README.md"))?,
+            "# TIL\n2 TILs & Counting\n### Git\n- [A](archive/git/a.md)\n\n### Git\n- [B](archive/git/b.md)\n\
+             - [C](archive/git/c.md)\n"
+        );
+
+        // The chosen spot holds when the README changes between planning and saving
+        let mut plan = plan_til(temp_dir.path(), "archive", "git", "d.md", "# D", "D", None)?;
+        plan.insert_entry_at(4);
+        let changed = "# TIL\n2 TILs & Counting\n### Git\n- [Z](archive/git/z.md)\n- [A](archive/git/a.md)\n\n\
+                       ### Git\n- [B](archive/git/b.md)\n- [C](archive/git/c.md)\n";
+        fs::write(temp_dir.path().join("README.md"), changed)?;
+        save(&plan)?;
+        assert_eq!(
+            fs::read_to_string(temp_dir.path().join("README.md"))?,
+            "# TIL\n3 TILs & Counting\n### Git\n- [Z](archive/git/z.md)\n- [A](archive/git/a.md)\n\
+             - [D](archive/git/d.md)\n\n### Git\n- [B](archive/git/b.md)\n- [C](archive/git/c.md)\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_sub_category_sections() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let readme = "# TIL\n1 TILs & Counting\n### Categories\n* [Rust](#rust)\n* [Sql](#sql)\n---\n\n\
+                      ### Rust\n\n- [Borrow](archive/rust/borrow.md)\n\n### Sql\n\n- [Joins](archive/sql/joins.md)\n";
+        fs::write(temp_dir.path().join("README.md"), readme)?;
+
+        let plan = plan_til(temp_dir.path(), "archive", "rust/async", "pin.md", "# Pin", "Pin", None)?;
+        assert_eq!(plan.file_path, temp_dir.path().join("archive/rust/async/pin.md"));
+        assert_eq!(
+            plan.readme_after,
+            "# TIL\n2 TILs & Counting\n### Categories\n* [Rust](#rust)\n  * [Rust – Async](#rust--async)\n\
+             * [Sql](#sql)\n---\n\n### Rust\n\n- [Borrow](archive/rust/borrow.md)\n\n### Rust – Async\n\n\
+             - [Pin](archive/rust/async/pin.md)\n\n### Sql\n\n- [Joins](archive/sql/joins.md)\n"
+        );
+
+        save(&plan)?;
+        let plan = plan_til(temp_dir.path(), "archive", "rust/async", "waker.md", "# Waker", "Waker", None)?;
+        assert!(plan
+            .readme_after
+            .contains("- [Pin](archive/rust/async/pin.md)\n- [Waker](archive/rust/async/waker.md)\n"));
+        assert_eq!(category_heading("sql/indexes"), "Sql – Indexes");
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_til_dated_entry_dir() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        fs::write(temp_dir.path().join("README.md"), "# TIL\n0 TILs & Counting\n### Categories\n---\n")?;
+
+        let plan = plan_til(
+            temp_dir.path(),
+            "archive",
+            "2024/06/Rust",
+            "ownership.md",
+            "# Ownership\n",
+            "Ownership",
+            None,
+        )?;
+
+        assert_eq!(plan.file_path, temp_dir.path().join("archive/2024/06/rust/ownership.md"));
+        assert!(plan.readme_after.contains("### Rust"));
+        assert!(plan.readme_after.contains("- [Ownership](archive/2024/06/rust/ownership.md)"));
+
+        Ok(())
+    }
 }
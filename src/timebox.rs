@@ -0,0 +1,168 @@
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// How long before the end the timebox warns
+const WARNING_MINUTES: i64 = 5;
+
+/// A pomodoro-style limit on a session, kept in the session history once it ends
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Timebox {
+    pub minutes: u32,
+    /// Minutes the session actually ran, set when it ends
+    #[serde(default)]
+    pub spent_minutes: Option<u32>,
+    #[serde(skip)]
+    warned: bool,
+    #[serde(skip)]
+    expired: bool,
+}
+
+/// A nudge the timebox gives, each at most once per session
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Alert {
+    /// Five minutes or less to go
+    Warning,
+    Expired,
+}
+
+impl Timebox {
+    pub fn new(minutes: u32) -> Self {
+        Self {
+            minutes,
+            spent_minutes: None,
+            warned: false,
+            expired: false,
+        }
+    }
+
+    /// Time left for a session started at `started_at` (negative once over)
+    pub fn remaining(&self, started_at: DateTime<Local>, now: DateTime<Local>) -> Duration {
+        started_at + Duration::minutes(i64::from(self.minutes)) - now
+    }
+
+    /// The alert that's due, if it hasn't been given yet
+    pub fn check(&mut self, started_at: DateTime<Local>, now: DateTime<Local>) -> Option<Alert> {
+        let remaining = self.remaining(started_at, now);
+        if remaining <= Duration::zero() && !self.expired {
+            self.expired = true;
+            self.warned = true;
+            Some(Alert::Expired)
+        } else if remaining <= Duration::minutes(WARNING_MINUTES) && !self.warned {
+            self.warned = true;
+            Some(Alert::Warning)
+        } else {
+            None
+        }
+    }
+
+    /// Shown in the prompt, e.g. "12m left" or "3m over"
+    pub fn label(&self, started_at: DateTime<Local>, now: DateTime<Local>) -> String {
+        let remaining = self.remaining(started_at, now);
+        if remaining > Duration::zero() {
+            // Round up, so the last minute reads "1m left" rather than "0m left"
+            format!("{}m left", (remaining.num_seconds() + 59) / 60)
+        } else {
+            format!("{}m over", -remaining.num_minutes())
+        }
+    }
+
+    /// Record how long the session ran
+    pub fn finish(&mut self, started_at: DateTime<Local>, now: DateTime<Local>) {
+        self.spent_minutes = Some(u32::try_from((now - started_at).num_minutes().max(0)).unwrap_or(u32::MAX));
+    }
+}
+
+/// Parse a timebox length such as "25m", "1h", "1h30m" or a bare number of minutes
+pub fn parse_minutes(text: &str) -> Result<u32> {
+    let invalid = || anyhow!("Invalid timebox \"{}\". Use minutes or hours, e.g. 25m, 1h or 1h30m", text);
+    let text = text.trim().to_lowercase();
+    if let Ok(minutes) = text.parse::<u32>() {
+        return (minutes > 0).then_some(minutes).ok_or_else(invalid);
+    }
+
+    let mut minutes = 0u32;
+    let mut number = String::new();
+    for c in text.chars() {
+        if c.is_ascii_digit() {
+            number.push(c);
+            continue;
+        }
+        let value: u32 = number.parse().map_err(|_| invalid())?;
+        number.clear();
+        let factor = match c {
+            'h' => 60,
+            'm' => 1,
+            _ => return Err(invalid()),
+        };
+        minutes = value
+            .checked_mul(factor)
+            .and_then(|value| minutes.checked_add(value))
+            .ok_or_else(invalid)?;
+    }
+
+    if !number.is_empty() || minutes == 0 {
+        return Err(invalid());
+    }
+    Ok(minutes)
+}
+
+/// Minutes spent per week (keyed by the week's Monday), oldest first
+pub fn minutes_per_week(sessions: &[(NaiveDate, u32)]) -> Vec<(NaiveDate, u32)> {
+    let mut weeks: BTreeMap<NaiveDate, u32> = BTreeMap::new();
+    for (date, minutes) in sessions {
+        let monday = *date - Duration::days(i64::from(date.weekday().num_days_from_monday()));
+        *weeks.entry(monday).or_default() += minutes;
+    }
+    weeks.into_iter().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_parse_minutes() -> Result<()> {
+        assert_eq!(parse_minutes("25m")?, 25);
+        assert_eq!(parse_minutes("1h")?, 60);
+        assert_eq!(parse_minutes("1H30m")?, 90);
+        assert_eq!(parse_minutes("45")?, 45);
+        for invalid in ["", "0", "0m", "m", "25x", "1h30", "25 m"] {
+            assert!(parse_minutes(invalid).is_err(), "{:?} should be rejected", invalid);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_alerts_fire_once() -> Result<()> {
+        let start = Local
+            .with_ymd_and_hms(2024, 6, 3, 9, 0, 0)
+            .single()
+            .ok_or_else(|| anyhow!("bad time"))?;
+        let at = |minutes: i64| start + Duration::minutes(minutes);
+        let mut timebox = Timebox::new(25);
+
+        assert_eq!(timebox.check(start, at(10)), None);
+        assert_eq!(timebox.label(start, at(10)), "15m left");
+        assert_eq!(timebox.check(start, at(21)), Some(Alert::Warning));
+        assert_eq!(timebox.check(start, at(22)), None);
+        assert_eq!(timebox.check(start, at(26)), Some(Alert::Expired));
+        assert_eq!(timebox.check(start, at(40)), None);
+        assert_eq!(timebox.label(start, at(28)), "3m over");
+
+        timebox.finish(start, at(31));
+        assert_eq!(timebox.spent_minutes, Some(31));
+        Ok(())
+    }
+
+    #[test]
+    fn test_minutes_per_week() -> Result<()> {
+        let date = |d: u32| NaiveDate::from_ymd_opt(2024, 6, d).ok_or_else(|| anyhow!("bad date"));
+        // June 3rd and 10th 2024 are Mondays
+        let weeks = minutes_per_week(&[(date(4)?, 25), (date(9)?, 30), (date(10)?, 50)]);
+        assert_eq!(weeks, vec![(date(3)?, 55), (date(10)?, 50)]);
+        Ok(())
+    }
+}
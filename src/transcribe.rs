@@ -0,0 +1,133 @@
+use crate::config::TranscriptionConfig;
+use crate::http;
+use anyhow::{anyhow, Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+const DEFAULT_API_URL: &str = "https://api.openai.com/v1/audio/transcriptions";
+const DEFAULT_MODEL: &str = "whisper-1";
+
+/// Transcribe a recording, reusing an earlier transcript of the same file from `cache_dir`
+pub fn transcribe_cached(audio: &Path, config: &TranscriptionConfig, cache_dir: &Path) -> Result<String> {
+    let cache_path = cache_path(audio, cache_dir)?;
+    if let Ok(transcript) = fs::read_to_string(&cache_path) {
+        return Ok(transcript);
+    }
+
+    let transcript = transcribe(audio, config)?;
+    fs::create_dir_all(cache_dir).with_context(|| format!("Failed to create {:?}", cache_dir))?;
+    fs::write(&cache_path, &transcript).with_context(|| format!("Failed to write {:?}", cache_path))?;
+    Ok(transcript)
+}
+
+/// Turn a recording into text with the configured command or API
+pub fn transcribe(audio: &Path, config: &TranscriptionConfig) -> Result<String> {
+    let transcript = match (&config.command, &config.api_key) {
+        (Some(command), _) => run_command(command, audio)?,
+        (None, Some(api_key)) => call_api(audio, config, api_key)?,
+        (None, None) => {
+            return Err(anyhow!(
+                "No transcription configured. Set `command` or `api_key` under [transcription] in the config file"
+            ))
+        }
+    };
+
+    let transcript = clean_transcript(&transcript);
+    if transcript.is_empty() {
+        return Err(anyhow!("The transcription of {:?} came back empty", audio));
+    }
+    Ok(transcript)
+}
+
+fn run_command(command: &str, audio: &Path) -> Result<String> {
+    let output = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .env("HOLOCRON_AUDIO_FILE", audio)
+        .output()
+        .with_context(|| format!("Failed to run transcription command: {}", command))?;
+
+    if !output.status.success() {
+        return Err(anyhow!(
+            "Transcription command exited with {}: {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+fn call_api(audio: &Path, config: &TranscriptionConfig, api_key: &str) -> Result<String> {
+    let url = config.api_url.as_deref().unwrap_or(DEFAULT_API_URL);
+    let model = config.model.as_deref().unwrap_or(DEFAULT_MODEL);
+    let auth = format!("Bearer {}", api_key);
+
+    http::post_file(
+        url,
+        &[("Authorization", &auth)],
+        &[("model", model), ("response_format", "text")],
+        "file",
+        audio,
+    )
+}
+
+/// Where a recording's transcript is cached: its name plus size, so re-exported files don't collide
+fn cache_path(audio: &Path, cache_dir: &Path) -> Result<PathBuf> {
+    let size = fs::metadata(audio)
+        .with_context(|| format!("Can't read audio file {:?}", audio))?
+        .len();
+    let stem = audio.file_stem().unwrap_or_default().to_string_lossy();
+    Ok(cache_dir.join(format!("{}-{}.txt", stem, size)))
+}
+
+/// Trim each line and drop blank ones and whisper.cpp's `[BLANK_AUDIO]`-style markers
+fn clean_transcript(raw: &str) -> String {
+    raw.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !is_marker(line))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn is_marker(line: &str) -> bool {
+    line.starts_with('[') && line.ends_with(']')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_clean_transcript() {
+        assert_eq!(
+            clean_transcript("  Hello there.\n\n[BLANK_AUDIO]\n Today: lifetimes. \n"),
+            "Hello there.\nToday: lifetimes."
+        );
+    }
+
+    #[test]
+    fn test_transcribe_with_command_is_cached() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let audio = temp_dir.path().join("talk.m4a");
+        fs::write(&audio, "fake audio")?;
+        let cache_dir = temp_dir.path().join("cache");
+        let config = TranscriptionConfig {
+            command: Some("echo \"transcript of $(basename \"$HOLOCRON_AUDIO_FILE\")\"".to_string()),
+            ..TranscriptionConfig::default()
+        };
+
+        assert_eq!(transcribe_cached(&audio, &config, &cache_dir)?, "transcript of talk.m4a");
+
+        let broken = TranscriptionConfig {
+            command: Some("exit 1".to_string()),
+            ..TranscriptionConfig::default()
+        };
+        assert_eq!(transcribe_cached(&audio, &broken, &cache_dir)?, "transcript of talk.m4a");
+        assert!(transcribe(&audio, &broken).is_err());
+        assert!(transcribe(&audio, &TranscriptionConfig::default()).is_err());
+
+        Ok(())
+    }
+}
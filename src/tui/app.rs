@@ -0,0 +1,254 @@
+use super::palette::{matching_actions, Action};
+use crate::archive::{fuzzy_score, Entry};
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+/// What the keyboard is currently driving
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Mode {
+    Browse,
+    Filter,
+    Palette,
+}
+
+/// State of the archive browser
+pub struct App {
+    pub entries: Vec<Entry>,
+    pub mode: Mode,
+    pub filter: String,
+    /// Indices into `entries` that match the filter, best first
+    pub visible: Vec<usize>,
+    pub selected: usize,
+    pub palette_query: String,
+    pub palette_selected: usize,
+}
+
+impl App {
+    pub fn new(entries: Vec<Entry>) -> Self {
+        let mut app = Self {
+            entries,
+            mode: Mode::Browse,
+            filter: String::new(),
+            visible: Vec::new(),
+            selected: 0,
+            palette_query: String::new(),
+            palette_selected: 0,
+        };
+        app.refilter();
+        app
+    }
+
+    pub fn selected_entry(&self) -> Option<&Entry> {
+        self.visible.get(self.selected).map(|&i| &self.entries[i])
+    }
+
+    pub fn palette_matches(&self) -> Vec<Action> {
+        matching_actions(&self.palette_query)
+    }
+
+    /// Handle a keypress, returning an action when the browser should hand control back
+    pub fn handle_key(&mut self, key: KeyEvent) -> Option<Action> {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+
+        if ctrl && key.code == KeyCode::Char('c') {
+            return Some(Action::Quit);
+        }
+        if ctrl && key.code == KeyCode::Char('p') {
+            self.open_palette();
+            return None;
+        }
+
+        match self.mode {
+            Mode::Browse => self.handle_browse_key(key),
+            Mode::Filter => {
+                self.handle_filter_key(key);
+                None
+            }
+            Mode::Palette => self.handle_palette_key(key),
+        }
+    }
+
+    fn handle_browse_key(&mut self, key: KeyEvent) -> Option<Action> {
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return Some(Action::Quit),
+            KeyCode::Char('/') => self.mode = Mode::Filter,
+            KeyCode::Down | KeyCode::Char('j') => self.move_selection(1),
+            KeyCode::Up | KeyCode::Char('k') => self.move_selection(-1),
+            KeyCode::Enter => {
+                return self.selected_entry().map(|e| Action::OpenEntry(e.path.clone()));
+            }
+            _ => {}
+        }
+        None
+    }
+
+    fn handle_filter_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Esc => {
+                self.filter.clear();
+                self.refilter();
+                self.mode = Mode::Browse;
+            }
+            KeyCode::Enter => self.mode = Mode::Browse,
+            KeyCode::Down => self.move_selection(1),
+            KeyCode::Up => self.move_selection(-1),
+            KeyCode::Backspace => {
+                self.filter.pop();
+                self.refilter();
+            }
+            KeyCode::Char(c) => {
+                self.filter.push(c);
+                self.refilter();
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_palette_key(&mut self, key: KeyEvent) -> Option<Action> {
+        match key.code {
+            KeyCode::Esc => self.mode = Mode::Browse,
+            KeyCode::Down => {
+                let count = self.palette_matches().len();
+                self.palette_selected = (self.palette_selected + 1).min(count.saturating_sub(1));
+            }
+            KeyCode::Up => self.palette_selected = self.palette_selected.saturating_sub(1),
+            KeyCode::Backspace => {
+                self.palette_query.pop();
+                self.palette_selected = 0;
+            }
+            KeyCode::Char(c) => {
+                self.palette_query.push(c);
+                self.palette_selected = 0;
+            }
+            KeyCode::Enter => {
+                let action = self.palette_matches().get(self.palette_selected).cloned();
+                self.mode = Mode::Browse;
+                // Search is handled inside the browser rather than handed back
+                if action == Some(Action::Search) {
+                    self.mode = Mode::Filter;
+                    return None;
+                }
+                return action;
+            }
+            _ => {}
+        }
+        None
+    }
+
+    fn open_palette(&mut self) {
+        self.mode = Mode::Palette;
+        self.palette_query.clear();
+        self.palette_selected = 0;
+    }
+
+    fn move_selection(&mut self, delta: isize) {
+        if self.visible.is_empty() {
+            return;
+        }
+        let last = self.visible.len() - 1;
+        self.selected = self.selected.saturating_add_signed(delta).min(last);
+    }
+
+    fn refilter(&mut self) {
+        let mut scored: Vec<(i64, usize)> = self
+            .entries
+            .iter()
+            .enumerate()
+            .filter_map(|(i, e)| fuzzy_score(&self.filter, &e.title).map(|score| (score, i)))
+            .collect();
+        if !self.filter.is_empty() {
+            scored.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.cmp(&b.1)));
+        }
+
+        self.visible = scored.into_iter().map(|(_, i)| i).collect();
+        self.selected = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::archive::EntryKind;
+    use std::path::PathBuf;
+
+    fn entry(title: &str) -> Entry {
+        Entry {
+            kind: EntryKind::Til,
+            title: title.to_string(),
+            path: PathBuf::from(format!("/til/{}.md", title)),
+            category: Some("git".to_string()),
+            reading_minutes: 1,
+            level: None,
+        }
+    }
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    fn ctrl(c: char) -> KeyEvent {
+        KeyEvent::new(KeyCode::Char(c), KeyModifiers::CONTROL)
+    }
+
+    fn app() -> App {
+        App::new(vec![entry("Rebase"), entry("Squash"), entry("Stash")])
+    }
+
+    #[test]
+    fn test_navigation_and_open() {
+        let mut app = app();
+        app.handle_key(key(KeyCode::Down));
+        app.handle_key(key(KeyCode::Down));
+        app.handle_key(key(KeyCode::Down));
+
+        assert_eq!(
+            app.handle_key(key(KeyCode::Enter)),
+            Some(Action::OpenEntry(PathBuf::from("/til/Stash.md")))
+        );
+    }
+
+    #[test]
+    fn test_filter() {
+        let mut app = app();
+        app.handle_key(key(KeyCode::Char('/')));
+        for c in "sta".chars() {
+            app.handle_key(key(KeyCode::Char(c)));
+        }
+
+        assert_eq!(app.visible.len(), 1);
+        assert_eq!(app.selected_entry().map(|e| e.title.as_str()), Some("Stash"));
+
+        app.handle_key(key(KeyCode::Esc));
+        assert_eq!(app.visible.len(), 3);
+        assert_eq!(app.mode, Mode::Browse);
+    }
+
+    #[test]
+    fn test_palette_runs_action() {
+        let mut app = app();
+        app.handle_key(ctrl('p'));
+        assert_eq!(app.mode, Mode::Palette);
+
+        for c in "relink".chars() {
+            app.handle_key(key(KeyCode::Char(c)));
+        }
+        assert_eq!(app.handle_key(key(KeyCode::Enter)), Some(Action::Relink));
+    }
+
+    #[test]
+    fn test_palette_search_enters_filter_mode() {
+        let mut app = app();
+        app.handle_key(ctrl('p'));
+        for c in "search".chars() {
+            app.handle_key(key(KeyCode::Char(c)));
+        }
+
+        assert_eq!(app.handle_key(key(KeyCode::Enter)), None);
+        assert_eq!(app.mode, Mode::Filter);
+    }
+
+    #[test]
+    fn test_quit_keys() {
+        assert_eq!(app().handle_key(key(KeyCode::Char('q'))), Some(Action::Quit));
+        assert_eq!(app().handle_key(ctrl('c')), Some(Action::Quit));
+    }
+}
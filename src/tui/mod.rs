@@ -0,0 +1,35 @@
+mod app;
+mod palette;
+mod session_view;
+mod ui;
+
+pub use palette::Action;
+pub use session_view::{run_session, SessionExit};
+
+use crate::archive::Entry;
+use anyhow::Result;
+use app::App;
+use ratatui::crossterm::event::{self, Event, KeyEventKind};
+
+/// Browse the archive until the user picks an action (or quits)
+pub fn browse(entries: Vec<Entry>) -> Result<Action> {
+    let mut terminal = ratatui::init();
+    let result = run(&mut terminal, App::new(entries));
+    ratatui::restore();
+    result
+}
+
+fn run(terminal: &mut ratatui::DefaultTerminal, mut app: App) -> Result<Action> {
+    loop {
+        terminal.draw(|frame| ui::draw(frame, &app))?;
+
+        if let Event::Key(key) = event::read()? {
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            if let Some(action) = app.handle_key(key) {
+                return Ok(action);
+            }
+        }
+    }
+}
@@ -0,0 +1,81 @@
+use crate::archive::fuzzy_score;
+use std::path::PathBuf;
+
+/// An action the browser hands back to the CLI to run outside the TUI
+#[derive(Debug, Clone, PartialEq)]
+pub enum Action {
+    StartSession,
+    AnalyzeLink,
+    Search,
+    OpenEntry(PathBuf),
+    DraftFromSessions,
+    ReviewDrafts,
+    RegenerateReadme,
+    Relink,
+    ExportBook,
+    Sync,
+    Quit,
+}
+
+/// Actions listed in the command palette, in their default order
+pub const PALETTE_ACTIONS: &[Action] = &[
+    Action::StartSession,
+    Action::AnalyzeLink,
+    Action::Search,
+    Action::DraftFromSessions,
+    Action::ReviewDrafts,
+    Action::RegenerateReadme,
+    Action::Relink,
+    Action::ExportBook,
+    Action::Sync,
+    Action::Quit,
+];
+
+impl Action {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Action::StartSession => "Start learning session",
+            Action::AnalyzeLink => "Analyze a link",
+            Action::Search => "Search entries",
+            Action::OpenEntry(_) => "Open entry",
+            Action::DraftFromSessions => "Draft TILs from recent sessions",
+            Action::ReviewDrafts => "Review drafted TILs",
+            Action::RegenerateReadme => "Regenerate README index",
+            Action::Relink => "Relink archive",
+            Action::ExportBook => "Export book",
+            Action::Sync => "Sync state through git",
+            Action::Quit => "Quit",
+        }
+    }
+}
+
+/// Palette actions matching `query`, best match first
+pub fn matching_actions(query: &str) -> Vec<Action> {
+    let mut scored: Vec<(i64, usize, &Action)> = PALETTE_ACTIONS
+        .iter()
+        .enumerate()
+        .filter_map(|(i, action)| fuzzy_score(query, action.label()).map(|score| (score, i, action)))
+        .collect();
+
+    // Ties keep the default order
+    scored.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.cmp(&b.1)));
+    scored.into_iter().map(|(_, _, action)| action.clone()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matching_actions_empty_query_lists_all() {
+        assert_eq!(matching_actions(""), PALETTE_ACTIONS.to_vec());
+    }
+
+    #[test]
+    fn test_matching_actions_fuzzy() {
+        assert_eq!(matching_actions("readme").first(), Some(&Action::RegenerateReadme));
+        assert_eq!(matching_actions("rvw drft").first(), Some(&Action::ReviewDrafts));
+        assert_eq!(matching_actions("sync").first(), Some(&Action::Sync));
+        assert!(matching_actions("zzz").is_empty());
+    }
+}
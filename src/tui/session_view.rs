@@ -0,0 +1,512 @@
+use crate::archive::Entry;
+use crate::claude::{self, CancellationToken, ClaudeResponse};
+use crate::compact;
+use crate::modes::build_search_prompt;
+use crate::search::SearchIndex;
+use crate::session::Session;
+use anyhow::Result;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, List, ListItem, Paragraph, Wrap};
+use ratatui::Frame;
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::Duration;
+
+/// How many related entries to show alongside the conversation
+const RELATED_LIMIT: usize = 8;
+
+/// Why the session view handed control back
+#[derive(Debug, Clone, PartialEq)]
+pub enum SessionExit {
+    /// The user ended the session
+    Exit,
+    /// A command that needs the regular terminal, e.g. `/til`
+    Command(String),
+}
+
+/// What submitting the input line asks for
+#[derive(Debug, Clone, PartialEq)]
+enum Submit {
+    Send { shown: String, prompt: String },
+    /// Keep a note of the user's own with the session, without sending it
+    Jot(String),
+    /// Cancel the request in flight
+    Stop,
+    Leave(SessionExit),
+    Nothing,
+}
+
+enum StreamEvent {
+    Chunk(String),
+    Done(Result<ClaudeResponse>),
+}
+
+/// State of the split-pane session view
+struct SessionView {
+    input: String,
+    /// The user's message and the partial response while a request is in flight
+    pending: Option<(String, String)>,
+    status: Option<String>,
+    /// Lines scrolled up from the bottom of the conversation
+    scroll_back: u16,
+    related: Vec<Entry>,
+}
+
+impl SessionView {
+    fn new() -> Self {
+        Self {
+            input: String::new(),
+            pending: None,
+            status: None,
+            scroll_back: 0,
+            related: Vec::new(),
+        }
+    }
+
+    fn refresh_related(&mut self, index: &SearchIndex, text: &str) {
+        self.related = index.related(text, RELATED_LIMIT).into_iter().cloned().collect();
+    }
+
+    fn handle_key(&mut self, key: KeyEvent, busy: bool) -> Submit {
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+            return Submit::Leave(SessionExit::Exit);
+        }
+
+        match key.code {
+            KeyCode::Enter if busy && self.input.trim().eq_ignore_ascii_case("/stop") => {
+                self.input.clear();
+                return Submit::Stop;
+            }
+            KeyCode::Enter if busy => {}
+            KeyCode::Esc if busy => return Submit::Stop,
+            KeyCode::Enter => return self.submit(),
+            KeyCode::Backspace => {
+                self.input.pop();
+            }
+            KeyCode::Char(c) => self.input.push(c),
+            KeyCode::PageUp => self.scroll_back = self.scroll_back.saturating_add(10),
+            KeyCode::PageDown => self.scroll_back = self.scroll_back.saturating_sub(10),
+            _ => {}
+        }
+        Submit::Nothing
+    }
+
+    fn submit(&mut self) -> Submit {
+        let input = std::mem::take(&mut self.input);
+        let input = input.trim();
+        self.status = None;
+        self.scroll_back = 0;
+
+        if input.is_empty() {
+            return Submit::Nothing;
+        }
+        if input.eq_ignore_ascii_case("/exit") || input.eq_ignore_ascii_case("/quit") {
+            return Submit::Leave(SessionExit::Exit);
+        }
+        if ["/til", "/outline", "/note"].iter().any(|command| input.eq_ignore_ascii_case(command)) {
+            return Submit::Leave(SessionExit::Command(input.to_lowercase()));
+        }
+        if let Some(query) = input.strip_prefix("/search ") {
+            return Submit::Send {
+                shown: input.to_string(),
+                prompt: build_search_prompt(query.trim()),
+            };
+        }
+        if let Some(text) = input.strip_prefix("/jot ") {
+            return Submit::Jot(text.trim().to_string());
+        }
+        if input.starts_with('/') {
+            self.status = Some(format!(
+                "{} isn't available here. Use /search, /jot, /til, /outline, /note or /exit.",
+                input
+            ));
+            return Submit::Nothing;
+        }
+
+        Submit::Send {
+            shown: input.to_string(),
+            prompt: input.to_string(),
+        }
+    }
+}
+
+/// Run the conversation in a split-pane view, with related archive entries alongside.
+/// `initial_prompt` is sent straight away; `on_exchange` runs after every completed exchange.
+/// Once the backend conversation reaches `context_limit` tokens it's summarized into a fresh one.
+pub fn run_session<F>(
+    session: &mut Session,
+    index: &SearchIndex,
+    initial_prompt: Option<String>,
+    context_limit: usize,
+    on_exchange: F,
+) -> Result<SessionExit>
+where
+    F: FnMut(&Session),
+{
+    let mut terminal = ratatui::init();
+    let result = session_loop(&mut terminal, session, index, initial_prompt, context_limit, on_exchange);
+    ratatui::restore();
+    result
+}
+
+fn session_loop<F>(
+    terminal: &mut ratatui::DefaultTerminal,
+    session: &mut Session,
+    index: &SearchIndex,
+    initial_prompt: Option<String>,
+    context_limit: usize,
+    mut on_exchange: F,
+) -> Result<SessionExit>
+where
+    F: FnMut(&Session),
+{
+    let mut view = SessionView::new();
+    view.refresh_related(index, session.topic());
+
+    let mut inflight: Option<Inflight> = None;
+    if let Some(prompt) = initial_prompt {
+        view.pending = Some((session.mode.to_string(), String::new()));
+        inflight = Some(Inflight::spawn(session, prompt));
+    }
+
+    loop {
+        terminal.draw(|frame| draw(frame, &view, session))?;
+
+        if let Some(ref request) = inflight {
+            if let Some(result) = drain(&request.events, &mut view) {
+                let message = request.message.clone();
+                inflight = None;
+                match result {
+                    Err(e) if claude::is_stale_session(&e) => {
+                        inflight = restart_conversation(terminal, session, &mut view, message)?;
+                    }
+                    result => finish_exchange(session, &mut view, index, message, result, &mut on_exchange),
+                }
+            }
+        }
+
+        if !event::poll(Duration::from_millis(50))? {
+            continue;
+        }
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+
+        match view.handle_key(key, inflight.is_some()) {
+            Submit::Send { shown, prompt } => {
+                if compact::due(session, context_limit) {
+                    view.status = Some("Summarizing the conversation so far to free up context…".to_string());
+                    terminal.draw(|frame| draw(frame, &view, session))?;
+                    view.status = compact::compact(session)
+                        .err()
+                        .map(|e| format!("Couldn't summarize the conversation: {}", e));
+                }
+                view.refresh_related(index, &format!("{} {}", session.topic(), shown));
+                view.pending = Some((shown, String::new()));
+                inflight = Some(Inflight::spawn(session, prompt));
+            }
+            Submit::Jot(text) => {
+                session.jot(&text);
+                on_exchange(session);
+                view.status = Some("Jotted. It'll go in the TIL and note as you wrote it.".to_string());
+            }
+            Submit::Stop => {
+                if let Some(ref request) = inflight {
+                    request.cancel.cancel();
+                }
+            }
+            Submit::Leave(exit) => return Ok(exit),
+            Submit::Nothing => {}
+        }
+    }
+}
+
+/// Carry the session on in a fresh backend conversation when its old one can't be resumed any
+/// more, and send `message` again there
+fn restart_conversation(
+    terminal: &mut ratatui::DefaultTerminal,
+    session: &mut Session,
+    view: &mut SessionView,
+    message: String,
+) -> Result<Option<Inflight>> {
+    view.status =
+        Some("The backend conversation has expired; continuing in a new one seeded with this session…".to_string());
+    terminal.draw(|frame| draw(frame, view, session))?;
+
+    match compact::restart(session) {
+        Ok(()) => Ok(Some(Inflight::spawn(session, message))),
+        Err(e) => {
+            view.pending = None;
+            view.status = Some(format!("Error: couldn't start a new conversation: {}", e));
+            Ok(None)
+        }
+    }
+}
+
+/// Pull streamed text into the view, returning the result once the request is done
+fn drain(rx: &Receiver<StreamEvent>, view: &mut SessionView) -> Option<Result<ClaudeResponse>> {
+    while let Ok(event) = rx.try_recv() {
+        match event {
+            StreamEvent::Chunk(text) => {
+                if let Some((_, ref mut partial)) = view.pending {
+                    partial.push_str(&text);
+                }
+            }
+            StreamEvent::Done(result) => return Some(result),
+        }
+    }
+    None
+}
+
+fn finish_exchange<F>(
+    session: &mut Session,
+    view: &mut SessionView,
+    index: &SearchIndex,
+    message: String,
+    result: Result<ClaudeResponse>,
+    on_exchange: &mut F,
+) where
+    F: FnMut(&Session),
+{
+    view.pending = None;
+
+    let response = match result {
+        Ok(response) => response,
+        Err(e) => match claude::partial_response(e) {
+            Ok(partial) => {
+                view.status = Some("Stopped.".to_string());
+                partial
+            }
+            Err(e) => {
+                view.status = Some(format!("Error: {}", e));
+                return;
+            }
+        },
+    };
+
+    // A stopped response keeps whatever had streamed in so far
+    if response.text.trim().is_empty() {
+        return;
+    }
+    view.refresh_related(index, &format!("{} {}", session.topic(), response.text));
+    session.record_response(message, response);
+    on_exchange(session);
+}
+
+/// A request streaming on a background thread
+struct Inflight {
+    message: String,
+    events: Receiver<StreamEvent>,
+    cancel: CancellationToken,
+}
+
+impl Inflight {
+    fn spawn(session: &Session, message: String) -> Self {
+        let session_id = session.claude_session_id.clone();
+        let claude_dir = session.claude_dir.clone();
+        let settings = session.tool_settings.clone();
+        let (tx, events) = mpsc::channel();
+        let cancel = CancellationToken::new();
+
+        let request_cancel = cancel.clone();
+        let request_message = message.clone();
+        thread::spawn(move || {
+            let chunks = tx.clone();
+            let on_text = |text: &str| {
+                // The view may have gone away; there's nobody left to tell
+                chunks.send(StreamEvent::Chunk(text.to_string())).ok();
+            };
+            let resume = claude::Resume { session_id: session_id.as_deref(), dir: claude_dir.as_deref() };
+            let result = claude::send(
+                resume,
+                &request_message,
+                settings.as_deref(),
+                on_text,
+                request_cancel,
+            );
+            tx.send(StreamEvent::Done(result)).ok();
+        });
+
+        Self {
+            message,
+            events,
+            cancel,
+        }
+    }
+}
+
+fn draw(frame: &mut Frame, view: &SessionView, session: &Session) {
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(1), Constraint::Length(3), Constraint::Length(1)])
+        .split(frame.area());
+    let panes = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(65), Constraint::Percentage(35)])
+        .split(rows[0]);
+
+    draw_conversation(frame, view, session, panes[0]);
+    draw_related(frame, view, panes[1]);
+
+    let prompt = if view.pending.is_some() { " thinking… " } else { " you " };
+    let input = Paragraph::new(view.input.as_str()).block(Block::default().borders(Borders::ALL).title(prompt));
+    frame.render_widget(input, rows[1]);
+
+    let status = view
+        .status
+        .clone()
+        .unwrap_or_else(|| match view.pending {
+            Some(_) => "esc or /stop to cancel".to_string(),
+            None => "enter send  /search <query>  /til  /note  /exit  pgup/pgdn scroll".to_string(),
+        });
+    frame.render_widget(Paragraph::new(status).style(Style::default().fg(Color::DarkGray)), rows[2]);
+}
+
+fn draw_conversation(frame: &mut Frame, view: &SessionView, session: &Session, area: Rect) {
+    let you = Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD);
+    let mut lines: Vec<Line> = Vec::new();
+
+    let mut push_exchange = |user: &str, response: &str| {
+        // Prompts built by holocron are long; the first line is enough to recognize them
+        let user = user.lines().next().unwrap_or_default().to_string();
+        lines.push(Line::from(Span::styled(format!("› {}", user), you)));
+        lines.extend(response.lines().map(|l| Line::from(l.to_string())));
+        lines.push(Line::default());
+    };
+
+    for exchange in &session.exchanges {
+        push_exchange(&exchange.user_message, &exchange.assistant_response);
+    }
+    if let Some((ref user, ref partial)) = view.pending {
+        push_exchange(user, partial);
+    }
+
+    // Keep the latest text in view unless the user has scrolled back
+    let width = area.width.saturating_sub(2).max(1) as usize;
+    let height = area.height.saturating_sub(2);
+    let wrapped: usize = lines.iter().map(|l| l.width().max(1).div_ceil(width)).sum();
+    let bottom = (wrapped as u16).saturating_sub(height);
+    let scroll = bottom.saturating_sub(view.scroll_back);
+
+    let title = format!(" {} ", session.mode);
+    let conversation = Paragraph::new(lines)
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .wrap(Wrap { trim: false })
+        .scroll((scroll, 0));
+    frame.render_widget(conversation, area);
+}
+
+fn draw_related(frame: &mut Frame, view: &SessionView, area: Rect) {
+    let items: Vec<ListItem> = view
+        .related
+        .iter()
+        .map(|entry| {
+            let location = match entry.category {
+                Some(ref cat) => format!("{} {}", entry.kind, cat),
+                None => entry.kind.to_string(),
+            };
+            ListItem::new(vec![
+                Line::from(entry.title.clone()),
+                Line::from(Span::styled(format!("  {}", location), Style::default().fg(Color::DarkGray))),
+            ])
+        })
+        .collect();
+
+    let list = List::new(items).block(Block::default().borders(Borders::ALL).title(" From your archive "));
+    frame.render_widget(list, area);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session::LearningMode;
+    use ratatui::backend::TestBackend;
+    use ratatui::Terminal;
+
+    fn type_line(view: &mut SessionView, text: &str) -> Submit {
+        for c in text.chars() {
+            view.handle_key(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE), false);
+        }
+        view.handle_key(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE), false)
+    }
+
+    #[test]
+    fn test_submit_message() {
+        let mut view = SessionView::new();
+        assert_eq!(
+            type_line(&mut view, "what about lifetimes?"),
+            Submit::Send {
+                shown: "what about lifetimes?".to_string(),
+                prompt: "what about lifetimes?".to_string(),
+            }
+        );
+        assert!(view.input.is_empty());
+    }
+
+    #[test]
+    fn test_submit_commands() {
+        let mut view = SessionView::new();
+        assert_eq!(type_line(&mut view, "/til"), Submit::Leave(SessionExit::Command("/til".to_string())));
+        assert_eq!(type_line(&mut view, "/Outline"), Submit::Leave(SessionExit::Command("/outline".to_string())));
+        assert_eq!(type_line(&mut view, "/exit"), Submit::Leave(SessionExit::Exit));
+        assert!(matches!(type_line(&mut view, "/search tokio"), Submit::Send { prompt, .. } if prompt.contains("WebSearch")));
+
+        assert_eq!(type_line(&mut view, "/jot  check the docs "), Submit::Jot("check the docs".to_string()));
+        assert_eq!(type_line(&mut view, "/learn rust"), Submit::Nothing);
+        assert!(view.status.is_some());
+    }
+
+    #[test]
+    fn test_enter_ignored_while_busy() {
+        let mut view = SessionView::new();
+        view.input = "hello".to_string();
+        assert_eq!(
+            view.handle_key(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE), true),
+            Submit::Nothing
+        );
+        assert_eq!(view.input, "hello");
+    }
+
+    #[test]
+    fn test_stop_while_busy() {
+        let mut view = SessionView::new();
+        view.input = "/stop".to_string();
+        assert_eq!(
+            view.handle_key(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE), true),
+            Submit::Stop
+        );
+        assert!(view.input.is_empty());
+        assert_eq!(view.handle_key(KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE), true), Submit::Stop);
+    }
+
+    #[test]
+    fn test_draw_streams_pending_response() -> Result<()> {
+        let mut session = Session::new(
+            LearningMode::DeepDive {
+                topic: "Rust".to_string(),
+            },
+            None,
+        );
+        session.add_exchange("first question".to_string(), "first answer".to_string());
+
+        let mut view = SessionView::new();
+        view.pending = Some(("second question".to_string(), "partial ans".to_string()));
+
+        let mut terminal = Terminal::new(TestBackend::new(100, 20))?;
+        terminal.draw(|frame| draw(frame, &view, &session))?;
+
+        let screen: String = terminal.backend().buffer().content().iter().map(|c| c.symbol()).collect();
+        assert!(screen.contains("first answer"));
+        assert!(screen.contains("› second question"));
+        assert!(screen.contains("partial ans"));
+        assert!(screen.contains("From your archive"));
+
+        Ok(())
+    }
+}
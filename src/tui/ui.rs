@@ -0,0 +1,143 @@
+use super::app::{App, Mode};
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph, Wrap};
+use ratatui::Frame;
+use std::fs;
+
+pub fn draw(frame: &mut Frame, app: &App) {
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(1), Constraint::Length(1)])
+        .split(frame.area());
+    let panes = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(40), Constraint::Percentage(60)])
+        .split(rows[0]);
+
+    draw_entries(frame, app, panes[0]);
+    draw_preview(frame, app, panes[1]);
+    draw_status(frame, app, rows[1]);
+
+    if app.mode == Mode::Palette {
+        draw_palette(frame, app);
+    }
+}
+
+fn draw_entries(frame: &mut Frame, app: &App, area: Rect) {
+    let items: Vec<ListItem> = app
+        .visible
+        .iter()
+        .map(|&i| {
+            let entry = &app.entries[i];
+            let mut meta = match entry.category {
+                Some(ref cat) => format!(" {} {} · {} min", entry.kind, cat, entry.reading_minutes),
+                None => format!(" {} · {} min", entry.kind, entry.reading_minutes),
+            };
+            if let Some(ref level) = entry.level {
+                meta.push_str(&format!(" · {}", level));
+            }
+            ListItem::new(Line::from(vec![
+                Span::raw(entry.title.clone()),
+                Span::styled(meta, Style::default().fg(Color::DarkGray)),
+            ]))
+        })
+        .collect();
+
+    let title = format!(" Archive ({}) ", app.visible.len());
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .highlight_style(Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD))
+        .highlight_symbol("› ");
+
+    let mut state = ListState::default().with_selected((!app.visible.is_empty()).then_some(app.selected));
+    frame.render_stateful_widget(list, area, &mut state);
+}
+
+fn draw_preview(frame: &mut Frame, app: &App, area: Rect) {
+    let content = app
+        .selected_entry()
+        .map(|e| fs::read_to_string(&e.path).unwrap_or_else(|err| format!("Could not read file: {}", err)))
+        .unwrap_or_default();
+
+    let preview = Paragraph::new(content)
+        .block(Block::default().borders(Borders::ALL).title(" Preview "))
+        .wrap(Wrap { trim: false });
+    frame.render_widget(preview, area);
+}
+
+fn draw_status(frame: &mut Frame, app: &App, area: Rect) {
+    let text = match app.mode {
+        Mode::Filter => format!("/{}", app.filter),
+        _ => "↑↓ move  enter open  / search  ctrl-p commands  q quit".to_string(),
+    };
+    frame.render_widget(Paragraph::new(text).style(Style::default().fg(Color::DarkGray)), area);
+}
+
+fn draw_palette(frame: &mut Frame, app: &App) {
+    let area = centered(frame.area(), 50, 12);
+    frame.render_widget(Clear, area);
+
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Min(1)])
+        .split(area);
+
+    let input = Paragraph::new(format!("> {}", app.palette_query))
+        .block(Block::default().borders(Borders::ALL).title(" Command Palette "));
+    frame.render_widget(input, rows[0]);
+
+    let matches = app.palette_matches();
+    let items: Vec<ListItem> = matches.iter().map(|a| ListItem::new(a.label())).collect();
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::LEFT | Borders::RIGHT | Borders::BOTTOM))
+        .highlight_style(Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD))
+        .highlight_symbol("› ");
+    let mut state = ListState::default().with_selected((!matches.is_empty()).then_some(app.palette_selected));
+    frame.render_stateful_widget(list, rows[1], &mut state);
+}
+
+/// A `width` x `height` rect centered in `area`, clamped to fit
+fn centered(area: Rect, width: u16, height: u16) -> Rect {
+    let width = width.min(area.width);
+    let height = height.min(area.height);
+    Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::archive::{Entry, EntryKind};
+    use ratatui::backend::TestBackend;
+    use ratatui::Terminal;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_draw_with_palette() -> anyhow::Result<()> {
+        let mut app = App::new(vec![Entry {
+            kind: EntryKind::Til,
+            title: "Rebase".to_string(),
+            path: PathBuf::from("/nonexistent/rebase.md"),
+            category: Some("git".to_string()),
+            reading_minutes: 2,
+            level: Some("beginner".to_string()),
+        }]);
+        app.mode = Mode::Palette;
+
+        let mut terminal = Terminal::new(TestBackend::new(120, 20))?;
+        terminal.draw(|frame| draw(frame, &app))?;
+
+        let screen: String = terminal.backend().buffer().content().iter().map(|c| c.symbol()).collect();
+        assert!(screen.contains("Rebase"));
+        assert!(screen.contains("git · 2 min · beginner"));
+        assert!(screen.contains("Command Palette"));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,84 @@
+use crate::config::Config;
+use crate::init;
+use anyhow::Result;
+use tempfile::TempDir;
+
+/// The tutorial's deep dive, small enough to answer in a screenful
+pub const TOPIC: &str = "git stash";
+
+/// Category the tutorial's TIL is filed under, so it doesn't ask for one
+pub const CATEGORY: &str = "git";
+
+pub const INTRO: &str = "holocron turns what you learn into short notes called TILs (\"Today I Learned\").
+You study a topic in a conversation, then save what stuck as a markdown file in your TIL repo.
+
+This tutorial runs a tiny session end to end. Everything is saved to a throwaway repo
+that's deleted when the tutorial finishes, so your real archive isn't touched.
+
+First, a deep dive. This is what `holocron learn <topic>` (or `/learn <topic>`) does.";
+
+pub const FOLLOW_UPS: &str = "In a real session you keep the conversation going: type a question and press Enter.
+Commands start with a slash, for example:
+
+  /history    list the exchanges so far (/undo or /exclude <n> drops one from the TIL)
+  /sources    list the URLs cited so far
+  /run        run the last code example and discuss its output
+  /til        turn the session into a TIL
+  /exit       end the session
+
+Try a follow-up question now, or press Enter to skip.";
+
+pub const SAVING: &str = "Now the session becomes a TIL, the same as typing /til.
+It streams in, then you choose what to do with it: s saves, e opens it in $EDITOR,
+r regenerates, v revises it from your feedback, p previews the files it will change.
+Enter saves.";
+
+pub const NEXT: &str = "That's the whole loop: learn, ask, save. To start for real:
+
+  holocron init ~/til          create your TIL repo (or --from-existing to adopt one)
+  holocron learn <topic>       deep dive on a topic
+  holocron link <url>          learn from an article
+  holocron                     the interactive mode, with every slash command
+
+Run `holocron --help` for everything else.";
+
+/// A throwaway TIL repo and a config pointing at it, removed when dropped
+pub struct Sandbox {
+    _dir: TempDir,
+    pub config: Config,
+}
+
+impl Sandbox {
+    pub fn create() -> Result<Self> {
+        let dir = tempfile::Builder::new().prefix("holocron-tutorial-").tempdir()?;
+        let config = Config::new(dir.path().to_path_buf());
+        init::init_til_repo(&config.til_path, &config.archive_dir)?;
+        Ok(Self { _dir: dir, config })
+    }
+}
+
+/// A deep dive prompt kept short, since the tutorial is about the workflow rather than the topic
+pub fn build_tutorial_prompt() -> String {
+    format!(
+        "Explain {} for someone who uses git daily but has never stashed. Keep it under 200 words: what it does, \
+         the three commands worth knowing, and one gotcha.",
+        TOPIC
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sandbox_is_removed_on_drop() -> Result<()> {
+        let sandbox = Sandbox::create()?;
+        let root = sandbox.config.til_path.clone();
+        assert!(sandbox.config.archive_path().is_dir());
+        assert!(root.join("README.md").is_file());
+
+        drop(sandbox);
+        assert!(!root.exists());
+        Ok(())
+    }
+}
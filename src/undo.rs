@@ -0,0 +1,222 @@
+use crate::files;
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Where undo entries live, inside the TIL repo
+const UNDO_DIR: &str = ".holocron/undo";
+const OPERATION_FILE: &str = "operation.toml";
+
+/// The TIL index, locked while files are put back so a save can't interleave with the restore
+const README_FILE: &str = "README.md";
+
+/// How many operations can be undone; older ones are dropped
+const MAX_ENTRIES: usize = 50;
+
+/// One write operation, as recorded before it ran
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Operation {
+    /// What it did, e.g. "Save TIL rust/borrowing.md"
+    pub description: String,
+    pub recorded_at: DateTime<Local>,
+    pub files: Vec<FileState>,
+}
+
+/// A file as it was before the operation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileState {
+    /// Relative to the TIL repo where possible
+    pub path: PathBuf,
+    /// Whether it existed; if so its contents are kept alongside the operation
+    pub existed: bool,
+}
+
+/// The files an operation is about to change, captured so `holocron undo` can put them back
+pub struct Snapshot {
+    til_path: PathBuf,
+    description: String,
+    files: Vec<(PathBuf, Option<Vec<u8>>)>,
+}
+
+impl Snapshot {
+    pub fn new(til_path: &Path, description: impl Into<String>) -> Self {
+        Self {
+            til_path: til_path.to_path_buf(),
+            description: description.into(),
+            files: Vec::new(),
+        }
+    }
+
+    /// Remember `path` as it is now: its contents, or that it doesn't exist yet
+    pub fn capture(&mut self, path: &Path) -> Result<()> {
+        if self.files.iter().any(|(captured, _)| captured == path) {
+            return Ok(());
+        }
+        let contents = if path.exists() {
+            Some(fs::read(path).with_context(|| format!("Failed to read {:?} for undo", path))?)
+        } else {
+            None
+        };
+        self.files.push((path.to_path_buf(), contents));
+        Ok(())
+    }
+
+    /// Store the snapshot as the newest undo entry
+    pub fn save(self) -> Result<()> {
+        if self.files.is_empty() {
+            return Ok(());
+        }
+        let undo_dir = self.til_path.join(UNDO_DIR);
+        let entry_dir = undo_dir.join(next_entry_name(&undo_dir)?);
+        fs::create_dir_all(&entry_dir).with_context(|| format!("Failed to create {:?}", entry_dir))?;
+        // Undo entries are local history, not something to commit with the TILs
+        fs::write(undo_dir.join(".gitignore"), "*\n").context("Failed to write the undo .gitignore")?;
+
+        let mut files = Vec::new();
+        for (index, (path, contents)) in self.files.iter().enumerate() {
+            if let Some(contents) = contents {
+                fs::write(entry_dir.join(index.to_string()), contents)
+                    .with_context(|| format!("Failed to back up {:?}", path))?;
+            }
+            files.push(FileState {
+                path: path.strip_prefix(&self.til_path).unwrap_or(path).to_path_buf(),
+                existed: contents.is_some(),
+            });
+        }
+
+        let operation = Operation {
+            description: self.description,
+            recorded_at: Local::now(),
+            files,
+        };
+        let content = toml::to_string_pretty(&operation).context("Failed to serialize undo entry")?;
+        fs::write(entry_dir.join(OPERATION_FILE), content).context("Failed to write undo entry")?;
+
+        prune(&undo_dir)
+    }
+}
+
+/// The most recent operation that can be undone, without undoing it
+pub fn last(til_path: &Path) -> Result<Option<Operation>> {
+    match entries(&til_path.join(UNDO_DIR))?.last() {
+        Some(entry_dir) => Ok(Some(read_operation(entry_dir)?)),
+        None => Ok(None),
+    }
+}
+
+/// Put back the files the most recent operation changed, and forget it. Files it created are removed.
+pub fn undo_last(til_path: &Path) -> Result<Option<Operation>> {
+    let _lock = files::lock(&til_path.join(README_FILE)).context("Failed to lock the README")?;
+    let Some(entry_dir) = entries(&til_path.join(UNDO_DIR))?.pop() else {
+        return Ok(None);
+    };
+    let operation = read_operation(&entry_dir)?;
+
+    for (index, file) in operation.files.iter().enumerate() {
+        let path = til_path.join(&file.path);
+        if file.existed {
+            let contents = fs::read(entry_dir.join(index.to_string()))
+                .with_context(|| format!("The backup of {:?} is missing", file.path))?;
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir).with_context(|| format!("Failed to create {:?}", dir))?;
+            }
+            files::write_atomic(&path, contents).with_context(|| format!("Failed to restore {:?}", path))?;
+        } else if path.exists() {
+            fs::remove_file(&path).with_context(|| format!("Failed to remove {:?}", path))?;
+        }
+    }
+
+    fs::remove_dir_all(&entry_dir).with_context(|| format!("Failed to remove undo entry {:?}", entry_dir))?;
+    Ok(Some(operation))
+}
+
+fn read_operation(entry_dir: &Path) -> Result<Operation> {
+    let path = entry_dir.join(OPERATION_FILE);
+    let content = fs::read_to_string(&path).with_context(|| format!("Failed to read undo entry {:?}", path))?;
+    toml::from_str(&content).map_err(|e| anyhow!("Failed to parse undo entry {:?}: {}", path, e))
+}
+
+/// Undo entry directories, oldest first. Names are zero-padded sequence numbers, so they sort.
+fn entries(undo_dir: &Path) -> Result<Vec<PathBuf>> {
+    if !undo_dir.is_dir() {
+        return Ok(Vec::new());
+    }
+    let mut entries: Vec<PathBuf> = fs::read_dir(undo_dir)
+        .with_context(|| format!("Failed to read {:?}", undo_dir))?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.join(OPERATION_FILE).is_file())
+        .collect();
+    entries.sort();
+    Ok(entries)
+}
+
+fn next_entry_name(undo_dir: &Path) -> Result<String> {
+    let next = entries(undo_dir)?
+        .last()
+        .and_then(|last| last.file_name()?.to_str()?.parse::<u64>().ok())
+        .map_or(1, |last| last + 1);
+    Ok(format!("{:08}", next))
+}
+
+fn prune(undo_dir: &Path) -> Result<()> {
+    let entries = entries(undo_dir)?;
+    for old in entries.iter().take(entries.len().saturating_sub(MAX_ENTRIES)) {
+        fs::remove_dir_all(old).with_context(|| format!("Failed to remove undo entry {:?}", old))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_undo_restores_changed_files_and_removes_new_ones() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let til_path = temp_dir.path();
+        let readme = til_path.join("README.md");
+        let til = til_path.join("archive/rust/borrowing.md");
+        fs::write(&readme, "# TIL\n0 TILs\n")?;
+
+        let mut snapshot = Snapshot::new(til_path, "Save TIL rust/borrowing.md");
+        snapshot.capture(&til)?;
+        snapshot.capture(&readme)?;
+        snapshot.save()?;
+        fs::create_dir_all(til_path.join("archive/rust"))?;
+        fs::write(&til, "# Borrowing\n")?;
+        fs::write(&readme, "# TIL\n1 TILs\n")?;
+
+        assert_eq!(last(til_path)?.map(|op| op.description), Some("Save TIL rust/borrowing.md".to_string()));
+        let undone = undo_last(til_path)?.context("nothing to undo")?;
+
+        assert_eq!(undone.files[0].path, PathBuf::from("archive/rust/borrowing.md"));
+        assert!(!til.exists());
+        assert_eq!(fs::read_to_string(&readme)?, "# TIL\n0 TILs\n");
+        assert!(undo_last(til_path)?.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_undo_goes_back_one_operation_at_a_time() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let til_path = temp_dir.path();
+        let til = til_path.join("rebase.md");
+
+        for version in ["first", "second", "third"] {
+            let mut snapshot = Snapshot::new(til_path, format!("Write {}", version));
+            snapshot.capture(&til)?;
+            snapshot.save()?;
+            fs::write(&til, version)?;
+        }
+
+        assert_eq!(undo_last(til_path)?.map(|op| op.description), Some("Write third".to_string()));
+        assert_eq!(fs::read_to_string(&til)?, "second");
+        undo_last(til_path)?;
+        assert_eq!(fs::read_to_string(&til)?, "first");
+        Ok(())
+    }
+}
@@ -0,0 +1,71 @@
+use crate::markdown;
+use std::collections::BTreeMap;
+
+/// Replace `{name}` with each variable's value from the config's `[variables]` table, e.g.
+/// `{author}`. Braces that don't name a variable (code, placeholders) are left alone.
+pub fn fill(text: &str, variables: &BTreeMap<String, String>) -> String {
+    variables
+        .iter()
+        .fold(text.to_string(), |text, (name, value)| text.replace(&format!("{{{}}}", name), value))
+}
+
+/// Set every variable as a frontmatter field, creating the frontmatter if there is none
+pub fn add_to_frontmatter(content: &str, variables: &BTreeMap<String, String>) -> String {
+    variables
+        .iter()
+        .filter(|(name, _)| is_field_name(name))
+        .fold(content.to_string(), |content, (name, value)| {
+            markdown::set_frontmatter_field(&content, name, &yaml_scalar(value))
+        })
+}
+
+/// Names that make a valid, unquoted YAML key
+fn is_field_name(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(|c| c.is_alphanumeric() || c == '_' || c == '-')
+}
+
+/// Quote a value that YAML would otherwise misread: empty, containing `: ` or ` #`, or starting
+/// with an indicator like `[`, `*` or a quote
+pub fn yaml_scalar(value: &str) -> String {
+    let value = value.trim();
+    let needs_quotes = value.is_empty()
+        || value.contains(": ")
+        || value.contains(" #")
+        || value.ends_with(':')
+        || value.starts_with(['[', ']', '{', '}', '&', '*', '!', '|', '>', '\'', '"', '%', '@', '`', '#', '-']);
+    if needs_quotes {
+        format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn variables() -> BTreeMap<String, String> {
+        BTreeMap::from([
+            ("author".to_string(), "Jo Spauley".to_string()),
+            ("project".to_string(), "billing: v2".to_string()),
+            ("bad key".to_string(), "ignored".to_string()),
+        ])
+    }
+
+    #[test]
+    fn test_fill_leaves_unknown_braces() {
+        let filled = fill("Written by {author} for {project}. fn main() {} {unknown}", &variables());
+        assert_eq!(filled, "Written by Jo Spauley for billing: v2. fn main() {} {unknown}");
+    }
+
+    #[test]
+    fn test_add_to_frontmatter() {
+        let content = add_to_frontmatter("---\ntags: [git]\nauthor: someone\n---\n# Rebase\n", &variables());
+        assert_eq!(
+            content,
+            "---\ntags: [git]\nauthor: Jo Spauley\nproject: \"billing: v2\"\n---\n# Rebase\n"
+        );
+        assert!(add_to_frontmatter("# Rebase\n", &variables()).starts_with("---\nauthor: Jo Spauley\n"));
+        assert_eq!(add_to_frontmatter("# Rebase\n", &BTreeMap::new()), "# Rebase\n");
+    }
+}
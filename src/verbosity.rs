@@ -0,0 +1,49 @@
+use crate::config::Config;
+use chrono::Local;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// How much holocron says, set once from `--quiet` / `--verbose`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Verbosity {
+    /// No banners, spinners or decorative rules
+    Quiet,
+    Normal,
+    /// Normal output, plus a debug log of every backend call
+    Verbose,
+}
+
+static LEVEL: AtomicU8 = AtomicU8::new(Verbosity::Normal as u8);
+
+pub fn set(verbosity: Verbosity) {
+    LEVEL.store(verbosity as u8, Ordering::Relaxed);
+}
+
+pub fn get() -> Verbosity {
+    match LEVEL.load(Ordering::Relaxed) {
+        level if level == Verbosity::Quiet as u8 => Verbosity::Quiet,
+        level if level == Verbosity::Verbose as u8 => Verbosity::Verbose,
+        _ => Verbosity::Normal,
+    }
+}
+
+pub fn quiet() -> bool {
+    get() == Verbosity::Quiet
+}
+
+/// Append a timestamped line to the debug log, if verbose. Logging never fails the command.
+pub fn log(message: &str) {
+    if get() != Verbosity::Verbose {
+        return;
+    }
+    let Ok(path) = Config::log_path() else {
+        return;
+    };
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).ok();
+    }
+    if let Ok(mut file) = OpenOptions::new().create(true).append(true).open(&path) {
+        writeln!(file, "{} {}", Local::now().format("%Y-%m-%d %H:%M:%S%.3f"), message).ok();
+    }
+}
@@ -0,0 +1,184 @@
+use anyhow::{anyhow, Context, Result};
+use std::fs;
+use std::process::Command;
+
+/// Captions are grouped into paragraphs of roughly this many seconds, each with a timestamp
+const CHUNK_SECONDS: u32 = 60;
+
+/// One caption cue
+#[derive(Debug, Clone, PartialEq)]
+pub struct Caption {
+    pub start: u32,
+    pub text: String,
+}
+
+/// A video's title and captions
+#[derive(Debug)]
+pub struct Video {
+    pub title: String,
+    pub captions: Vec<Caption>,
+}
+
+/// Fetch a video's title and captions (uploaded or auto-generated English) with yt-dlp
+pub fn fetch(url: &str) -> Result<Video> {
+    let dir = tempfile::tempdir().context("Failed to create a temporary directory")?;
+
+    let output = Command::new("yt-dlp")
+        .args(["--skip-download", "--no-simulate", "--no-playlist", "--print", "title"])
+        .args(["--write-subs", "--write-auto-subs", "--sub-langs", "en.*,en", "--sub-format", "vtt"])
+        .arg("--output")
+        .arg(dir.path().join("video.%(ext)s"))
+        .arg(url)
+        .output()
+        .map_err(|e| anyhow!("Failed to run yt-dlp (is it installed?): {}", e))?;
+
+    if !output.status.success() {
+        return Err(anyhow!(
+            "yt-dlp couldn't fetch {}: {}",
+            url,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    let mut subtitles: Vec<_> = fs::read_dir(dir.path())?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "vtt"))
+        .collect();
+    subtitles.sort();
+    let vtt = subtitles
+        .first()
+        .ok_or_else(|| anyhow!("{} has no English captions", url))?;
+
+    Ok(Video {
+        title: String::from_utf8_lossy(&output.stdout).trim().to_string(),
+        captions: parse_vtt(&fs::read_to_string(vtt)?),
+    })
+}
+
+/// Parse WebVTT captions, dropping inline timing tags and the repeated lines of rolling auto-captions
+pub fn parse_vtt(vtt: &str) -> Vec<Caption> {
+    let mut captions: Vec<Caption> = Vec::new();
+    let mut start = None;
+
+    for line in vtt.lines() {
+        let line = line.trim();
+        if let Some((from, _)) = line.split_once(" --> ") {
+            start = parse_timestamp(from);
+            continue;
+        }
+        let Some(start) = start else {
+            continue;
+        };
+
+        let text = strip_tags(line);
+        if text.is_empty() || captions.iter().rev().take(3).any(|c| c.text == text) {
+            continue;
+        }
+        captions.push(Caption { start, text });
+    }
+
+    captions
+}
+
+/// Group captions into timestamped paragraphs, e.g. "[12:30] So the borrow checker..."
+pub fn chunk(captions: &[Caption]) -> Vec<String> {
+    let mut chunks: Vec<(u32, Vec<&str>)> = Vec::new();
+
+    for caption in captions {
+        match chunks.last_mut() {
+            Some((start, lines)) if caption.start < *start + CHUNK_SECONDS => lines.push(&caption.text),
+            _ => chunks.push((caption.start, vec![&caption.text])),
+        }
+    }
+
+    chunks
+        .into_iter()
+        .map(|(start, lines)| format!("[{}] {}", format_timestamp(start), lines.join(" ")))
+        .collect()
+}
+
+/// Format seconds as `m:ss`, or `h:mm:ss` past the hour
+pub fn format_timestamp(seconds: u32) -> String {
+    let (hours, minutes, seconds) = (seconds / 3600, seconds / 60 % 60, seconds % 60);
+    if hours > 0 {
+        format!("{}:{:02}:{:02}", hours, minutes, seconds)
+    } else {
+        format!("{}:{:02}", minutes, seconds)
+    }
+}
+
+/// Parse `hh:mm:ss.mmm` or `mm:ss.mmm` into whole seconds
+fn parse_timestamp(timestamp: &str) -> Option<u32> {
+    let whole = timestamp.trim().split('.').next()?;
+    whole
+        .split(':')
+        .try_fold(0, |total, part| Some(total * 60 + part.parse::<u32>().ok()?))
+}
+
+fn strip_tags(line: &str) -> String {
+    let mut text = String::new();
+    let mut in_tag = false;
+    for c in line.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' => in_tag = false,
+            _ if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+    text.replace("&amp;", "&").replace("&gt;", ">").replace("&lt;", "<").trim().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const AUTO_CAPTIONS: &str = "WEBVTT
+Kind: captions
+Language: en
+
+00:00:01.000 --> 00:00:03.000 align:start position:0%
+so<00:00:01.500><c> today</c><00:00:02.000><c> we're</c>
+
+00:00:03.000 --> 00:00:03.010 align:start position:0%
+so today we're
+
+00:00:03.010 --> 00:01:05.000 align:start position:0%
+so today we're
+talking about lifetimes
+
+00:01:05.000 --> 00:01:08.000
+and borrowing &amp; moves
+";
+
+    #[test]
+    fn test_parse_vtt_dedupes_rolling_captions() {
+        assert_eq!(
+            parse_vtt(AUTO_CAPTIONS),
+            vec![
+                Caption { start: 1, text: "so today we're".to_string() },
+                Caption { start: 3, text: "talking about lifetimes".to_string() },
+                Caption { start: 65, text: "and borrowing & moves".to_string() },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_chunk() {
+        assert_eq!(
+            chunk(&parse_vtt(AUTO_CAPTIONS)),
+            vec![
+                "[0:01] so today we're talking about lifetimes".to_string(),
+                "[1:05] and borrowing & moves".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_format_timestamp() {
+        assert_eq!(format_timestamp(750), "12:30");
+        assert_eq!(format_timestamp(3723), "1:02:03");
+        assert_eq!(parse_timestamp("01:02:03.500"), Some(3723));
+        assert_eq!(parse_timestamp("12:30.000"), Some(750));
+    }
+}
@@ -0,0 +1,123 @@
+use anyhow::{anyhow, Context, Result};
+use std::collections::HashSet;
+use std::fmt;
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+
+/// Clipboard readers, tried in order; the first one that works is used for the whole watch
+const CLIPBOARD_COMMANDS: &[&[&str]] = &[
+    &["pbpaste"],
+    &["wl-paste", "--no-newline"],
+    &["xclip", "-selection", "clipboard", "-o"],
+    &["xsel", "--clipboard", "--output"],
+];
+
+/// Where `holocron watch` looks for links
+pub enum Source {
+    /// The system clipboard, with whatever it held at the last poll
+    Clipboard { command: &'static [&'static str], last: String },
+    /// A directory that links are saved into as files (`.txt`, `.url`, `.webloc`, ...)
+    DropDir { dir: PathBuf, seen: HashSet<PathBuf> },
+}
+
+impl Source {
+    /// Watch the clipboard, ignoring whatever is already on it
+    pub fn clipboard() -> Result<Self> {
+        CLIPBOARD_COMMANDS
+            .iter()
+            .find_map(|command| {
+                read_clipboard(command)
+                    .ok()
+                    .map(|last| Source::Clipboard { command, last })
+            })
+            .ok_or_else(|| {
+                anyhow!("No clipboard tool found. Install pbpaste, wl-paste, xclip or xsel, or watch a directory with --dir")
+            })
+    }
+
+    /// Watch a drop directory, creating it if needed. Files already in it are picked up on the first poll.
+    pub fn drop_dir(dir: PathBuf) -> Result<Self> {
+        fs::create_dir_all(&dir).with_context(|| format!("Failed to create {:?}", dir))?;
+        Ok(Source::DropDir {
+            dir,
+            seen: HashSet::new(),
+        })
+    }
+
+    /// Text that has appeared since the last poll
+    pub fn poll(&mut self) -> Result<Vec<String>> {
+        match self {
+            Source::Clipboard { command, last } => {
+                let text = read_clipboard(command)?;
+                if text == *last {
+                    return Ok(Vec::new());
+                }
+                *last = text.clone();
+                Ok(vec![text])
+            }
+            Source::DropDir { dir, seen } => {
+                let mut paths: Vec<PathBuf> = fs::read_dir(&*dir)
+                    .with_context(|| format!("Failed to read {:?}", dir))?
+                    .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                    .filter(|path| path.is_file() && !is_hidden(path))
+                    .filter(|path| !seen.contains(path))
+                    .collect();
+                paths.sort();
+
+                // Unreadable files (binary, half-written) are skipped for good rather than retried
+                let texts = paths.iter().filter_map(|path| fs::read_to_string(path).ok()).collect();
+                seen.extend(paths);
+                Ok(texts)
+            }
+        }
+    }
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Source::Clipboard { command, .. } => write!(f, "the clipboard ({})", command[0]),
+            Source::DropDir { dir, .. } => write!(f, "{}", dir.display()),
+        }
+    }
+}
+
+fn read_clipboard(command: &[&str]) -> Result<String> {
+    let output = Command::new(command[0])
+        .args(&command[1..])
+        .output()
+        .with_context(|| format!("Failed to run {}", command[0]))?;
+
+    if !output.status.success() {
+        return Err(anyhow!("{} exited with {}", command[0], output.status));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+fn is_hidden(path: &std::path::Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| name.starts_with('.'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_drop_dir_reads_each_file_once() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let mut source = Source::drop_dir(temp_dir.path().join("inbox"))?;
+        fs::write(temp_dir.path().join("inbox/a.url"), "[InternetShortcut]\nURL=https://a.com/1\n")?;
+        fs::write(temp_dir.path().join("inbox/.DS_Store"), "")?;
+
+        assert_eq!(source.poll()?, vec!["[InternetShortcut]\nURL=https://a.com/1\n".to_string()]);
+        assert!(source.poll()?.is_empty());
+
+        fs::write(temp_dir.path().join("inbox/b.txt"), "https://b.com/2")?;
+        assert_eq!(source.poll()?, vec!["https://b.com/2".to_string()]);
+        Ok(())
+    }
+}
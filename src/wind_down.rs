@@ -0,0 +1,98 @@
+use crate::session::Session;
+
+/// Sign-off messages are made only of these words, e.g. "thanks, that makes sense" or "got it"
+const CLOSING_WORDS: &[&str] = &[
+    "thanks", "thank", "thx", "ty", "cheers", "appreciate", "it", "you", "so", "much", "very", "a", "lot", "for",
+    "the", "that", "that's", "thats", "this", "help", "helps", "helped", "helpful", "explanation", "got", "gotcha",
+    "makes", "sense", "perfect", "great", "awesome", "cool", "nice", "good", "excellent", "brilliant", "ok", "okay",
+    "alright", "right", "i", "see", "ah", "oh", "understood", "clear", "now", "all", "done", "is",
+];
+
+/// Longest message still read as a sign-off
+const CLOSING_MAX_WORDS: usize = 8;
+
+/// Follow-ups this short ("and for structs?") suggest the questions are drying up...
+const SHORT_MAX_WORDS: usize = 4;
+
+/// ...when this many come in a row
+const SHORT_STREAK: usize = 3;
+
+/// Whether `message` reads like a sign-off: a thank-you or acknowledgement with no new question
+pub fn is_closing(message: &str) -> bool {
+    if message.contains('?') {
+        return false;
+    }
+    let normalized: String = message
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_alphanumeric() || c == '\'' { c } else { ' ' })
+        .collect();
+    let words: Vec<&str> = normalized.split_whitespace().collect();
+    !words.is_empty() && words.len() <= CLOSING_MAX_WORDS && words.iter().all(|word| CLOSING_WORDS.contains(word))
+}
+
+/// Whether a session with no TIL yet looks like it's concluding: its latest message is a
+/// sign-off, or the last few follow-ups have all been short. The opening prompt doesn't count.
+pub fn winding_down(session: &Session) -> bool {
+    if session.saved_til.is_some() {
+        return false;
+    }
+    let messages: Vec<&str> = session
+        .exchanges
+        .iter()
+        .skip(1)
+        .filter(|exchange| !exchange.excluded)
+        .map(|exchange| exchange.user_message.as_str())
+        .collect();
+
+    let Some(latest) = messages.last() else {
+        return false;
+    };
+    is_closing(latest)
+        || (messages.len() >= SHORT_STREAK
+            && messages[messages.len() - SHORT_STREAK..]
+                .iter()
+                .all(|message| message.split_whitespace().count() <= SHORT_MAX_WORDS))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session::LearningMode;
+
+    fn session(messages: &[&str]) -> Session {
+        let mut session = Session::new(
+            LearningMode::DeepDive {
+                topic: "rust lifetimes".to_string(),
+            },
+            None,
+        );
+        session.add_exchange("Teach me about rust lifetimes in depth".to_string(), "...".to_string());
+        for message in messages {
+            session.add_exchange(message.to_string(), "...".to_string());
+        }
+        session
+    }
+
+    #[test]
+    fn test_is_closing() {
+        assert!(is_closing("Thanks!"));
+        assert!(is_closing("ok got it, that makes sense"));
+        assert!(is_closing("Perfect, thank you so much."));
+        assert!(!is_closing("thanks, but why does the borrow end there?"));
+        assert!(!is_closing("great, now show me how drop interacts with this"));
+        assert!(!is_closing(""));
+    }
+
+    #[test]
+    fn test_winding_down() {
+        assert!(!winding_down(&session(&[])));
+        assert!(winding_down(&session(&["What does 'static mean for a trait object here?", "thanks!"])));
+        assert!(winding_down(&session(&["and structs?", "example?", "ok and enums"])));
+        assert!(!winding_down(&session(&["and structs?", "How do elided lifetimes work in impl blocks?", "ok and enums"])));
+
+        let mut saved = session(&["thanks"]);
+        saved.record_til("til.md".into());
+        assert!(!winding_down(&saved));
+    }
+}
@@ -0,0 +1,372 @@
+//! End-to-end tests that drive the holocron binary against a temp TIL repo,
+//! with the `claude` CLI replaced by canned responses from `tests/fixtures/`.
+
+use anyhow::{anyhow, Result};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
+use tempfile::TempDir;
+
+/// A throwaway holocron install: config dir, TIL repo and mock backend, all in one temp dir
+struct Harness {
+    root: TempDir,
+}
+
+impl Harness {
+    /// Set up a TIL repo and config, with the mock backend answering from `fixtures/<name>`
+    fn new(fixtures: &str) -> Result<Self> {
+        let harness = Self { root: TempDir::new()? };
+        fs::create_dir_all(harness.mock_dir())?;
+        for entry in fs::read_dir(Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures").join(fixtures))? {
+            let path = entry?.path();
+            if let Some(name) = path.file_name() {
+                fs::copy(&path, harness.mock_dir().join(name))?;
+            }
+        }
+
+        let til_path = harness.til_path();
+        harness.run(&["init", &til_path.to_string_lossy()], "")?;
+        harness.run(&["config", "--til-path", &til_path.to_string_lossy()], "")?;
+        Ok(harness)
+    }
+
+    fn til_path(&self) -> PathBuf {
+        self.root.path().join("til")
+    }
+
+    fn mock_dir(&self) -> PathBuf {
+        self.root.path().join("mock")
+    }
+
+    fn command(&self, args: &[&str]) -> Command {
+        let mut command = Command::new(env!("CARGO_BIN_EXE_holocron"));
+        command
+            .args(args)
+            .current_dir(self.root.path())
+            .env("HOLOCRON_CONFIG_DIR", self.root.path().join("config"))
+            .env("HOLOCRON_MOCK_BACKEND", self.mock_dir())
+            .env("NO_COLOR", "1");
+        command
+    }
+
+    /// Run holocron with `stdin` as its input, failing unless it exits cleanly
+    fn run(&self, args: &[&str], stdin: &str) -> Result<String> {
+        let output = self.output(self.command(args), stdin)?;
+        if !output.status.success() {
+            return Err(anyhow!(
+                "holocron {:?} exited with {}: {}",
+                args,
+                output.status,
+                String::from_utf8_lossy(&output.stderr)
+            ));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    }
+
+    fn output(&self, mut command: Command, stdin: &str) -> Result<Output> {
+        let mut child = command
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+        if let Some(mut input) = child.stdin.take() {
+            input.write_all(stdin.as_bytes())?;
+        }
+        Ok(child.wait_with_output()?)
+    }
+
+    /// The arguments of each request the mock backend answered, in order
+    fn requests(&self) -> Result<Vec<Vec<String>>> {
+        fs::read_to_string(self.mock_dir().join("requests.jsonl"))?
+            .lines()
+            .map(|line| Ok(serde_json::from_str(line)?))
+            .collect()
+    }
+}
+
+/// Parse JSONL output into events, checking every line is valid JSON
+fn events(stdout: &str) -> Result<Vec<serde_json::Value>> {
+    stdout.lines().map(|line| Ok(serde_json::from_str(line)?)).collect()
+}
+
+fn event_names(events: &[serde_json::Value]) -> Vec<&str> {
+    events.iter().filter_map(|event| event["event"].as_str()).collect()
+}
+
+#[test]
+fn test_learn_then_save_til() -> Result<()> {
+    let harness = Harness::new("learn_til")?;
+
+    let stdout = harness.run(
+        &["learn", "rust ownership", "--category", "rust", "--output", "jsonl"],
+        "/til\n/exit\n",
+    )?;
+    let events = events(&stdout)?;
+    assert_eq!(
+        event_names(&events),
+        vec![
+            "session_started",
+            "text_delta",
+            "text_delta",
+            "response_done",
+            "til_generated",
+            "saved",
+            "session_ended"
+        ]
+    );
+
+    let saved = harness.til_path().join("archive/rust/borrowing_instead_of_moving.md");
+    assert_eq!(events[5]["path"].as_str(), saved.to_str());
+    assert!(fs::read_to_string(&saved)?.starts_with("# Borrowing Instead of Moving"));
+    assert!(fs::read_to_string(harness.til_path().join("README.md"))?.contains("Borrowing Instead of Moving"));
+
+    // The TIL request continues the conversation the session started
+    let requests = harness.requests()?;
+    assert_eq!(requests.len(), 2);
+    assert!(requests[0].iter().any(|arg| arg.contains("rust ownership")));
+    assert!(requests[1].windows(2).any(|pair| pair == ["--resume", "mock-session-1"]));
+
+    // A deep dive may search the web; writing the TIL may not use any tools
+    assert!(requests[0].iter().any(|arg| arg.contains(r#""allow":["WebSearch"]"#)));
+    assert!(requests[1].iter().any(|arg| arg.contains(r#""allow":[]"#)));
+
+    assert_eq!(events[6]["exchanges"], 1);
+    Ok(())
+}
+
+#[test]
+fn test_missing_response_is_reported() -> Result<()> {
+    let harness = Harness::new("learn_til")?;
+    fs::remove_file(harness.mock_dir().join("1.jsonl"))?;
+
+    let stdout = harness.run(&["learn", "rust", "--output", "jsonl"], "/exit\n")?;
+    let events = events(&stdout)?;
+    assert_eq!(event_names(&events), vec!["session_started", "error", "session_ended"]);
+    assert!(events[1]["message"]
+        .as_str()
+        .is_some_and(|message| message.contains("no response for request 1")));
+    Ok(())
+}
+
+#[test]
+fn test_expired_conversation_is_restarted() -> Result<()> {
+    let harness = Harness::new("expired_session")?;
+
+    let stdout = harness.run(&["learn", "rust ownership", "--output", "jsonl"], "and borrowing?\n/exit\n")?;
+    let events = events(&stdout)?;
+    let names = event_names(&events);
+    assert!(names.contains(&"conversation_restarted"));
+    assert!(!names.contains(&"error"));
+    assert_eq!(names.iter().filter(|name| **name == "response_done").count(), 2);
+
+    // The new conversation is seeded with the session so far, then gets the message again
+    let requests = harness.requests()?;
+    assert_eq!(requests.len(), 4);
+    assert!(requests[1].windows(2).any(|pair| pair == ["--resume", "mock-session-1"]));
+    assert!(!requests[2].iter().any(|arg| arg == "--resume"));
+    assert!(requests[2].iter().any(|arg| arg.contains("Borrowing with & lets you read")));
+    assert!(requests[3].windows(2).any(|pair| pair == ["--resume", "mock-session-2"]));
+    assert_eq!(requests[3].last().map(String::as_str), Some("and borrowing?"));
+    Ok(())
+}
+
+#[test]
+fn test_extract_from_claude_code_transcript() -> Result<()> {
+    let harness = Harness::new("extract")?;
+    let transcript = harness.root.path().join("transcript.jsonl");
+    fs::write(
+        &transcript,
+        concat!(
+            r#"{"type":"user","message":{"content":"How do I stash new files?"}}"#,
+            "\n",
+            r#"{"type":"assistant","message":{"content":[{"type":"text","text":"Use git stash -u."}]}}"#,
+            "\n",
+        ),
+    )?;
+
+    let stdout = harness.run(&["--dry-run", "extract", &transcript.to_string_lossy()], "")?;
+    assert!(stdout.contains("Found 2 learning(s)"));
+    assert!(stdout.contains("Learning 1/2 git/stash_untracked_files.md"));
+    assert!(stdout.contains("Learning 2/2 cargo/why_cargo_rebuilds_everything.md"));
+
+    let requests = harness.requests()?;
+    assert_eq!(requests.len(), 1);
+    assert!(requests[0].iter().any(|arg| arg.contains("User: How do I stash new files?")));
+    Ok(())
+}
+
+#[test]
+fn test_ask_prints_only_the_answer() -> Result<()> {
+    let harness = Harness::new("learn_til")?;
+    let readme = fs::read_to_string(harness.til_path().join("README.md"))?;
+
+    let stdout = harness.run(&["ask", "what", "is", "borrowing?"], "")?;
+    assert_eq!(stdout, "Rust moves values by default. Borrowing with & lets you read without taking ownership.\n");
+
+    let requests = harness.requests()?;
+    assert!(requests[0].iter().any(|arg| arg.starts_with("what is borrowing?\n\nAnswer directly")));
+    assert!(requests[0].iter().any(|arg| arg.contains(r#""allow":[]"#)));
+    assert_eq!(fs::read_to_string(harness.til_path().join("README.md"))?, readme);
+    assert!(!harness.root.path().join("config/sessions").exists());
+    Ok(())
+}
+
+#[test]
+fn test_offline_without_backend() -> Result<()> {
+    let harness = Harness::new("learn_til")?;
+
+    let mut command = harness.command(&["learn", "rust"]);
+    command.env_remove("HOLOCRON_MOCK_BACKEND").env("PATH", "");
+    let output = harness.output(command, "")?;
+    assert_eq!(output.status.code(), Some(4));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("claude CLI isn't installed"));
+    Ok(())
+}
+
+#[test]
+fn test_missing_config_and_repo_exit_codes() -> Result<()> {
+    let harness = Harness::new("learn_til")?;
+
+    let mut command = harness.command(&["list"]);
+    command.env("HOLOCRON_CONFIG_DIR", harness.root.path().join("elsewhere"));
+    let output = harness.output(command, "")?;
+    assert_eq!(output.status.code(), Some(3));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Try: holocron config --til-path"));
+
+    fs::remove_dir_all(harness.til_path())?;
+    let output = harness.output(harness.command(&["list"]), "")?;
+    assert_eq!(output.status.code(), Some(5));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Try: holocron init"));
+    Ok(())
+}
+
+#[test]
+fn test_interactive_commands_fail_when_piped() -> Result<()> {
+    let harness = Harness::new("learn_til")?;
+
+    let output = harness.output(harness.command(&["browse"]), "")?;
+    assert_eq!(output.status.code(), Some(7));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("output isn't going to a terminal"));
+    Ok(())
+}
+
+#[test]
+fn test_verbose_logs_backend_calls() -> Result<()> {
+    let harness = Harness::new("learn_til")?;
+
+    harness.run(&["--verbose", "learn", "rust ownership", "--output", "jsonl"], "/exit\n")?;
+
+    let log = fs::read_to_string(harness.root.path().join("config/holocron.log"))?;
+    assert!(log.contains(r#"claude "--print" "--output-format" "stream-json""#));
+    assert!(log.contains("mock backend replaying"));
+    assert!(log.contains(r#""session_id":"mock-session-1""#));
+    Ok(())
+}
+
+#[test]
+fn test_undo_reverts_saved_til() -> Result<()> {
+    let harness = Harness::new("learn_til")?;
+    let readme = harness.til_path().join("README.md");
+    let readme_before = fs::read_to_string(&readme)?;
+
+    harness.run(
+        &["learn", "rust ownership", "--category", "rust", "--output", "jsonl"],
+        "/til\n/exit\n",
+    )?;
+    let saved = harness.til_path().join("archive/rust/borrowing_instead_of_moving.md");
+    assert!(saved.exists());
+
+    let stdout = harness.run(&["undo"], "")?;
+    assert!(stdout.contains("Undid \"Save TIL rust/borrowing_instead_of_moving.md\""));
+    assert!(!saved.exists());
+    assert_eq!(fs::read_to_string(&readme)?, readme_before);
+
+    assert!(harness.run(&["undo"], "")?.contains("Nothing to undo"));
+    Ok(())
+}
+
+#[test]
+fn test_dry_run_leaves_config_and_repo_alone() -> Result<()> {
+    let harness = Harness::new("learn_til")?;
+    let config = harness.root.path().join("config/config.toml");
+    let config_before = fs::read_to_string(&config)?;
+
+    let stdout = harness.run(&["--dry-run", "config", "--archive-dir", "notes"], "")?;
+    assert!(stdout.contains("+archive_dir = \"notes\""));
+    assert_eq!(fs::read_to_string(&config)?, config_before);
+
+    let elsewhere = harness.root.path().join("elsewhere");
+    let output = harness.output(harness.command(&["--dry-run", "init", &elsewhere.to_string_lossy()]), "")?;
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--dry-run isn't supported"));
+    assert!(!elsewhere.exists());
+    Ok(())
+}
+
+/// A fake `claude` on PATH that replays the fixtures and notes whether its stdin was a pipe
+#[cfg(unix)]
+fn install_stub_claude(harness: &Harness) -> Result<PathBuf> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let bin = harness.root.path().join("bin");
+    fs::create_dir_all(&bin)?;
+    let script = format!(
+        "#!/bin/sh\n\
+         dir='{}'\n\
+         if [ -p /dev/stdin ]; then echo pipe >> \"$dir/stdin\"; fi\n\
+         n=$(($(cat \"$dir/count\" 2>/dev/null || echo 0) + 1))\n\
+         echo $n > \"$dir/count\"\n\
+         cat \"$dir/$n.jsonl\"\n",
+        harness.mock_dir().display()
+    );
+    let stub = bin.join("claude");
+    fs::write(&stub, script)?;
+    fs::set_permissions(&stub, fs::Permissions::from_mode(0o755))?;
+    Ok(bin)
+}
+
+#[test]
+#[cfg(unix)]
+fn test_real_backend_does_not_read_piped_input() -> Result<()> {
+    let harness = Harness::new("learn_til")?;
+    let bin = install_stub_claude(&harness)?;
+
+    let mut command = harness.command(&["learn", "rust ownership", "--category", "rust", "--output", "jsonl"]);
+    let inherited = std::env::var_os("PATH").unwrap_or_default();
+    let path = std::env::join_paths(std::iter::once(bin).chain(std::env::split_paths(&inherited)))?;
+    command.env_remove("HOLOCRON_MOCK_BACKEND").env("PATH", path);
+    let output = harness.output(command, "/til\n/exit\n")?;
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    // Both queued messages reached holocron rather than the backend
+    let events = events(&String::from_utf8_lossy(&output.stdout))?;
+    let names = event_names(&events);
+    assert!(names.contains(&"saved"));
+    assert_eq!(names.last(), Some(&"session_ended"));
+    assert!(!harness.mock_dir().join("stdin").exists());
+    Ok(())
+}
+
+#[test]
+fn test_save_flags_only_reach_commands_that_save() -> Result<()> {
+    let harness = Harness::new("learn_til")?;
+
+    let output = harness.output(harness.command(&["grep", "rust", "--append-to", "rust/x.md"]), "")?;
+    assert!(String::from_utf8_lossy(&output.stderr).contains("unexpected argument '--append-to'"));
+    let output = harness.output(harness.command(&["--encrypt", "list"]), "")?;
+    assert!(String::from_utf8_lossy(&output.stderr).contains("go after the command"));
+    assert!(!output.status.success());
+    Ok(())
+}
+
+#[test]
+fn test_ask_checks_the_repo_before_answering() -> Result<()> {
+    let harness = Harness::new("learn_til")?;
+
+    fs::remove_dir_all(harness.til_path())?;
+    let output = harness.output(harness.command(&["ask", "what is a borrow?", "--save-til"]), "")?;
+    assert_eq!(output.status.code(), Some(5));
+    assert!(!harness.mock_dir().join("requests.jsonl").exists());
+    Ok(())
+}